@@ -0,0 +1,58 @@
+//! Bearer-token auth for HTTP servers over engrams. The MCP server's HTTP
+//! transport uses it too, so both check tokens the same way.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+
+/// Refuse to serve on `addr` without a token unless it is a loopback
+/// address or `insecure` is set, so engrams aren't opened up to the network
+/// by accident.
+pub fn check_unauthenticated(
+    addr: SocketAddr,
+    token: Option<&str>,
+    insecure: bool,
+) -> io::Result<()> {
+    if token.is_none() && !insecure && !addr.ip().is_loopback() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("refusing to serve on {addr} without a token"),
+        ));
+    }
+    Ok(())
+}
+
+/// `router`, refusing requests without `Authorization: Bearer <token>`.
+pub fn require_bearer(router: Router, token: &str) -> Router {
+    let expected = Arc::new(format!("Bearer {token}"));
+    router.layer(middleware::from_fn_with_state(expected, require_token))
+}
+
+async fn require_token(
+    State(expected): State<Arc<String>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authorized = request
+        .headers()
+        .get(AUTHORIZATION)
+        .is_some_and(|value| constant_time_eq(value.as_bytes(), expected.as_bytes()));
+    if authorized {
+        next.run(request).await
+    } else {
+        (StatusCode::UNAUTHORIZED, [(WWW_AUTHENTICATE, "Bearer")]).into_response()
+    }
+}
+
+/// Compare without stopping at the first difference, so response times
+/// don't reveal how much of a guessed token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
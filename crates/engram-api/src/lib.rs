@@ -0,0 +1,105 @@
+//! Read-only HTTP API over a repository's engrams, for dashboards and other
+//! tools that want reasoning history without shelling out to the CLI.
+//!
+//! Every endpoint is a `GET` returning JSON; nothing is ever written to the
+//! engrams. Each request opens its own repository handle, so commits made
+//! while the server runs are visible to the next request.
+
+pub mod auth;
+mod error;
+mod routes;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::http::header::AUTHORIZATION;
+use axum::http::{HeaderValue, Method};
+use axum::routing::get;
+use axum::Router;
+use tokio::net::TcpListener;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+pub use crate::error::ApiError;
+
+/// Version of the JSON responses. Changes only when a field is removed or
+/// changes meaning; new fields may appear within a version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Environment variable holding the bearer token clients must send.
+/// Takes precedence over `engram.serve.token`.
+pub const TOKEN_ENV: &str = "ENGRAM_SERVE_TOKEN";
+
+/// How the API is served.
+#[derive(Debug, Clone, Default)]
+pub struct ApiOptions {
+    /// When set, requests without `Authorization: Bearer <token>` are refused.
+    pub token: Option<String>,
+    /// Origins browsers may call the API from; `*` allows any. Empty sends
+    /// no CORS headers, so only same-origin pages can read responses.
+    pub cors_origins: Vec<String>,
+    /// Serve on a non-loopback address without a token, which [`serve`]
+    /// otherwise refuses.
+    pub insecure: bool,
+}
+
+/// The API's routes for the repository at `repo_path`.
+pub fn router(repo_path: PathBuf, options: &ApiOptions) -> Result<Router, ApiError> {
+    let mut router = Router::new()
+        .route("/engrams", get(routes::list))
+        .route("/engrams/{id}", get(routes::show))
+        .route("/search", get(routes::search))
+        .route("/trace", get(routes::trace))
+        .route("/graph", get(routes::graph))
+        .route("/stats", get(routes::stats))
+        .route("/review", get(routes::review))
+        .with_state(Arc::new(repo_path));
+    if let Some(token) = &options.token {
+        router = auth::require_bearer(router, token);
+    }
+    // Outermost, so preflight requests are answered before the token check
+    if !options.cors_origins.is_empty() {
+        router = router.layer(cors(&options.cors_origins)?);
+    }
+    Ok(router)
+}
+
+/// Serve the API on an already bound listener, until the listener fails.
+/// Without a token, a listener on a non-loopback address is refused unless
+/// `options.insecure` is set.
+pub async fn serve(
+    listener: TcpListener,
+    repo_path: PathBuf,
+    options: &ApiOptions,
+) -> std::io::Result<()> {
+    auth::check_unauthenticated(
+        listener.local_addr()?,
+        options.token.as_deref(),
+        options.insecure,
+    )?;
+    let router = router(repo_path, options)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
+    tracing::info!(
+        "Serving the engram API on http://{}",
+        listener.local_addr()?
+    );
+    axum::serve(listener, router).await
+}
+
+fn cors(origins: &[String]) -> Result<CorsLayer, ApiError> {
+    let allow = if origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|o| {
+                HeaderValue::from_str(o)
+                    .map_err(|_| ApiError::bad_request(format!("Invalid CORS origin '{o}'")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    Ok(CorsLayer::new()
+        .allow_origin(allow)
+        .allow_methods([Method::GET])
+        .allow_headers([AUTHORIZATION]))
+}
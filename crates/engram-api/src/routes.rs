@@ -0,0 +1,287 @@
+//! Endpoint handlers. Each runs its repository work on the blocking pool,
+//! as `git2::Repository` is neither `Send` nor cheap to share.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use engram_core::error::CoreError;
+use engram_core::storage::{GitStorage, ListOptions};
+use engram_query::index::{parse_date_bound, FilterField};
+use engram_query::{
+    agent_stats_for, build_graph, review_branch, trace_file, ReviewOptions, SearchEngine,
+    SearchOptions,
+};
+
+use crate::{ApiError, SCHEMA_VERSION};
+
+type Repo = State<Arc<PathBuf>>;
+type ApiResult = Result<Json<Value>, ApiError>;
+
+/// Engrams `GET /engrams` returns when no `limit` is given.
+const DEFAULT_LIST_LIMIT: usize = 50;
+
+/// Results `GET /search` returns when no `limit` is given.
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Open the repository and run `f` on the blocking pool.
+async fn with_storage<F>(repo: Arc<PathBuf>, f: F) -> ApiResult
+where
+    F: FnOnce(&GitStorage) -> Result<Value, ApiError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let storage = GitStorage::open(&repo)
+            .map_err(|e| ApiError::internal(format!("Failed to open repository: {e}")))?;
+        f(&storage)
+    })
+    .await
+    .map_err(ApiError::internal)?
+    .map(Json)
+}
+
+/// A `since`/`until` query parameter, as a date or an RFC 3339 time.
+fn date_bound(value: Option<&str>, end_of_day: bool) -> Result<Option<DateTime<Utc>>, ApiError> {
+    value
+        .map(|v| parse_date_bound(v, end_of_day))
+        .transpose()
+        .map_err(|e| ApiError::bad_request(e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ListQuery {
+    agent: Option<String>,
+    tag: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    min_cost: Option<f64>,
+    max_cost: Option<f64>,
+    min_tokens: Option<u64>,
+    max_tokens: Option<u64>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+/// `GET /engrams`: manifests, most recent first, filtered like `engram log`.
+pub(crate) async fn list(State(repo): Repo, Query(query): Query<ListQuery>) -> ApiResult {
+    let options = ListOptions {
+        limit: None,
+        offset: 0,
+        agent_filter: query.agent,
+        min_cost: query.min_cost,
+        max_cost: query.max_cost,
+        min_tokens: query.min_tokens,
+        max_tokens: query.max_tokens,
+        tag: query.tag,
+        since: date_bound(query.since.as_deref(), false)?,
+        until: date_bound(query.until.as_deref(), true)?,
+        hide_trivial: None,
+    };
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    with_storage(repo, move |storage| {
+        let all = storage.list(&options)?;
+        let engrams: Vec<_> = all.iter().skip(offset).take(limit).collect();
+        Ok(json!({
+            "schema_version": SCHEMA_VERSION,
+            "total": all.len(),
+            "offset": offset,
+            "engrams": engrams,
+        }))
+    })
+    .await
+}
+
+/// `GET /engrams/{id}`: the whole engram, transcript included. `id` may be
+/// a unique prefix or `HEAD`.
+pub(crate) async fn show(State(repo): Repo, Path(id): Path<String>) -> ApiResult {
+    with_storage(repo, move |storage| {
+        let id = storage.resolve(&id).map_err(|e| match e {
+            CoreError::NotFound { .. } => ApiError::not_found(e.to_string()),
+            _ => ApiError::bad_request(e.to_string()),
+        })?;
+        let data = storage.read(&id)?;
+        let mut value = serde_json::to_value(&data).map_err(ApiError::internal)?;
+        value["schema_version"] = json!(SCHEMA_VERSION);
+        Ok(value)
+    })
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SearchQuery {
+    q: String,
+    agent: Option<String>,
+    tag: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+/// `GET /search?q=`: full-text search, best match first. Builds the search
+/// index on first use, like `engram search`.
+pub(crate) async fn search(State(repo): Repo, Query(query): Query<SearchQuery>) -> ApiResult {
+    let filters = [
+        (FilterField::Agent, query.agent),
+        (FilterField::Tag, query.tag),
+    ]
+    .into_iter()
+    .filter_map(|(field, value)| Some((field, value?)))
+    .collect();
+    let options = SearchOptions {
+        limit: query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        offset: query.offset.unwrap_or(0),
+        since: date_bound(query.since.as_deref(), false)?,
+        until: date_bound(query.until.as_deref(), true)?,
+        filters,
+        ..Default::default()
+    };
+    with_storage(repo, move |storage| {
+        let engine = SearchEngine::open(storage)?;
+        let page = engine.search(storage, &query.q, &options)?;
+        let results: Vec<_> = page
+            .results
+            .into_iter()
+            .map(|r| {
+                json!({
+                    "manifest": r.manifest,
+                    "score": r.score,
+                    "snippet": r.snippet,
+                })
+            })
+            .collect();
+        Ok(json!({
+            "schema_version": SCHEMA_VERSION,
+            "query": query.q,
+            "total": page.total,
+            "offset": page.offset,
+            "results": results,
+        }))
+    })
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TraceQuery {
+    file: String,
+}
+
+/// `GET /trace?file=`: every engram that touched a file, oldest first,
+/// following renames.
+pub(crate) async fn trace(State(repo): Repo, Query(query): Query<TraceQuery>) -> ApiResult {
+    with_storage(repo, move |storage| {
+        let engine = SearchEngine::open(storage)?;
+        let entries: Vec<_> = trace_file(storage, &engine, &query.file, true)?
+            .into_iter()
+            .map(|e| {
+                json!({
+                    "manifest": e.manifest,
+                    "change_type": e.change_type,
+                    "path": e.path,
+                })
+            })
+            .collect();
+        Ok(json!({
+            "schema_version": SCHEMA_VERSION,
+            "file": query.file,
+            "entries": entries,
+        }))
+    })
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GraphQuery {
+    focus: Option<String>,
+    depth: Option<usize>,
+}
+
+/// `GET /graph`: the context graph of engrams, files, agents and commits,
+/// or with `focus` the part within `depth` (default 2) hops of one node.
+pub(crate) async fn graph(State(repo): Repo, Query(query): Query<GraphQuery>) -> ApiResult {
+    with_storage(repo, move |storage| {
+        let mut graph = build_graph(storage)?;
+        if let Some(reference) = &query.focus {
+            let focus = graph.resolve_node(reference).ok_or_else(|| {
+                ApiError::not_found(format!("No graph node matches '{reference}'"))
+            })?;
+            graph = graph.subgraph(&focus, query.depth.unwrap_or(2));
+        }
+        let mut value = graph.to_json();
+        value["schema_version"] = json!(SCHEMA_VERSION);
+        Ok(value)
+    })
+    .await
+}
+
+/// `GET /stats`: totals across all engrams, and per agent and model.
+pub(crate) async fn stats(State(repo): Repo) -> ApiResult {
+    with_storage(repo, move |storage| {
+        let manifests = storage.list(&ListOptions::default())?;
+        let by_agent_model = agent_stats_for(storage, &manifests)?;
+        Ok(json!({
+            "schema_version": SCHEMA_VERSION,
+            "total_engrams": manifests.len(),
+            "total_tokens": manifests.iter().map(|m| m.token_usage.total_tokens).sum::<u64>(),
+            "total_cost_usd": manifests
+                .iter()
+                .filter_map(|m| m.token_usage.cost_usd)
+                .sum::<f64>(),
+            "earliest": manifests.last().map(|m| m.created_at),
+            "latest": manifests.first().map(|m| m.created_at),
+            "by_agent_model": by_agent_model,
+        }))
+    })
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReviewQuery {
+    base: String,
+    head: Option<String>,
+    #[serde(default)]
+    inferred: bool,
+}
+
+/// `GET /review?base=&head=`: the engrams behind the commits in
+/// `base..head` (head defaults to `HEAD`), as `engram review` reports them.
+pub(crate) async fn review(State(repo): Repo, Query(query): Query<ReviewQuery>) -> ApiResult {
+    with_storage(repo, move |storage| {
+        let head = query.head.as_deref().unwrap_or("HEAD");
+        let options = ReviewOptions {
+            inferred: query.inferred,
+            ..Default::default()
+        };
+        let review = review_branch(storage, &query.base, head, &options)
+            .map_err(|e| ApiError::bad_request(format!("Review failed: {e}")))?;
+        let engrams: Vec<_> = review
+            .engrams
+            .iter()
+            .map(|e| {
+                json!({
+                    "manifest": e.manifest,
+                    "commit": e.commit_sha,
+                    "linkage": e.linkage,
+                    "dead_ends": e.dead_ends,
+                    "decisions": e.decisions,
+                })
+            })
+            .collect();
+        Ok(json!({
+            "schema_version": SCHEMA_VERSION,
+            "range": review.range,
+            "total_commits": review.total_commits,
+            "total_tokens": review.total_tokens,
+            "total_cost": review.total_cost,
+            "files_changed": review.files_changed,
+            "engrams": engrams,
+            "by_file": review.by_file(),
+        }))
+    })
+    .await
+}
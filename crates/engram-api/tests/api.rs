@@ -0,0 +1,289 @@
+use std::net::SocketAddr;
+
+use engram_api::ApiOptions;
+use engram_core::storage::GitStorage;
+use engram_sdk::EngramSession;
+use reqwest::StatusCode;
+use serde_json::Value;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+
+/// Start the API for `repo` on an ephemeral port.
+async fn start(repo: &TempDir, options: ApiOptions) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let path = repo.path().to_path_buf();
+    tokio::spawn(async move { engram_api::serve(listener, path, &options).await });
+    addr
+}
+
+async fn get(addr: SocketAddr, path: &str) -> (StatusCode, Value) {
+    let response = reqwest::get(format!("http://{addr}{path}")).await.unwrap();
+    let status = response.status();
+    (status, response.json().await.unwrap_or(Value::Null))
+}
+
+fn init_repo() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    git2::Repository::init(tmp.path()).unwrap();
+    GitStorage::open(tmp.path()).unwrap().init().unwrap();
+    tmp
+}
+
+fn record(repo: &TempDir, agent: &str, request: &str, path: &str) -> String {
+    let storage = GitStorage::open(repo.path()).unwrap();
+    let mut session = EngramSession::begin(agent, Some("sonnet"));
+    session
+        .set_request(request)
+        .log_message("user", request)
+        .log_file_change(path, "created")
+        .log_rejection("Polling", "Too slow")
+        .add_tokens(100, 20, Some(0.5));
+    session
+        .commit_to(&storage, None, None)
+        .unwrap()
+        .as_str()
+        .to_string()
+}
+
+/// Commit on HEAD with `message`.
+fn commit(repo: &TempDir, message: &str) -> String {
+    let repo = git2::Repository::open(repo.path()).unwrap();
+    let tree = repo.treebuilder(None).unwrap().write().unwrap();
+    let tree = repo.find_tree(tree).unwrap();
+    let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_list_and_show() {
+    let repo = init_repo();
+    let first = record(
+        &repo,
+        "claude-code",
+        "Add a websocket transport",
+        "src/ws.rs",
+    );
+    let second = record(&repo, "cursor", "Add caching", "src/cache.rs");
+    let addr = start(&repo, ApiOptions::default()).await;
+
+    let (status, list) = get(addr, "/engrams").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["schema_version"], 1);
+    assert_eq!(list["total"], 2);
+    assert_eq!(list["engrams"][0]["id"], second.as_str());
+
+    let (_, list) = get(addr, "/engrams?agent=claude-code").await;
+    assert_eq!(list["total"], 1);
+    assert_eq!(list["engrams"][0]["id"], first.as_str());
+    let (_, list) = get(addr, "/engrams?limit=1&offset=1").await;
+    assert_eq!(list["total"], 2);
+    assert_eq!(list["offset"], 1);
+    assert_eq!(list["engrams"].as_array().unwrap().len(), 1);
+    assert_eq!(list["engrams"][0]["id"], first.as_str());
+
+    let (status, body) = get(addr, "/engrams?since=yesterday-ish").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].is_string());
+
+    let (status, engram) = get(addr, &format!("/engrams/{}", &first[..8])).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(engram["manifest"]["id"], first.as_str());
+    assert_eq!(
+        engram["intent"]["original_request"],
+        "Add a websocket transport"
+    );
+    assert_eq!(engram["transcript"][0]["role"], "user");
+    assert_eq!(engram["operations"]["file_changes"][0]["path"], "src/ws.rs");
+
+    let (status, body) = get(addr, "/engrams/ffffffff").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["error"].as_str().unwrap().contains("ffffffff"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_search_trace_graph_and_stats() {
+    let repo = init_repo();
+    let id = record(
+        &repo,
+        "claude-code",
+        "Add a websocket transport",
+        "src/ws.rs",
+    );
+    record(&repo, "cursor", "Add caching", "src/cache.rs");
+    let addr = start(&repo, ApiOptions::default()).await;
+
+    let (status, found) = get(addr, "/search?q=websocket").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(found["total"], 1);
+    assert_eq!(found["results"][0]["manifest"]["id"], id.as_str());
+    let (status, _) = get(addr, "/search").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, trace) = get(addr, "/trace?file=src/ws.rs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(trace["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(trace["entries"][0]["change_type"], "created");
+
+    let (status, graph) = get(addr, &format!("/graph?focus={}&depth=1", &id[..8])).await;
+    assert_eq!(status, StatusCode::OK);
+    let nodes = graph["nodes"].as_array().unwrap();
+    assert!(nodes.iter().any(|n| n["label"] == "src/ws.rs"), "{graph}");
+    assert!(
+        !nodes.iter().any(|n| n["label"] == "src/cache.rs"),
+        "{graph}"
+    );
+    let (status, _) = get(addr, "/graph?focus=nothing-like-this").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, stats) = get(addr, "/stats").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stats["total_engrams"], 2);
+    assert_eq!(stats["total_tokens"], 240);
+    assert_eq!(stats["by_agent_model"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_review() {
+    let repo = init_repo();
+    let base = commit(&repo, "init");
+    let id = record(&repo, "claude-code", "Add caching", "src/cache.rs");
+    commit(&repo, &format!("Add caching\n\nEngram-Id: {id}"));
+    let addr = start(&repo, ApiOptions::default()).await;
+
+    let (status, review) = get(addr, &format!("/review?base={base}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(review["total_commits"], 1);
+    assert_eq!(review["engrams"][0]["manifest"]["id"], id.as_str());
+    assert_eq!(review["engrams"][0]["dead_ends"][0]["approach"], "Polling");
+    assert_eq!(review["by_file"][0]["path"], "src/cache.rs");
+
+    let (status, body) = get(addr, "/review?base=no-such-branch").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("no-such-branch"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_only() {
+    let repo = init_repo();
+    let id = record(&repo, "claude-code", "Add caching", "src/cache.rs");
+    let addr = start(&repo, ApiOptions::default()).await;
+
+    let client = reqwest::Client::new();
+    for request in [
+        client.post(format!("http://{addr}/engrams")),
+        client.delete(format!("http://{addr}/engrams/{id}")),
+        client.put(format!("http://{addr}/engrams/{id}")),
+    ] {
+        let status = request.send().await.unwrap().status();
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    }
+    let (_, list) = get(addr, "/engrams").await;
+    assert_eq!(list["total"], 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_token_and_cors() {
+    let repo = init_repo();
+    let addr = start(
+        &repo,
+        ApiOptions {
+            token: Some("s3cret".into()),
+            cors_origins: vec!["https://dash.example".into()],
+            ..Default::default()
+        },
+    )
+    .await;
+    let client = reqwest::Client::new();
+    let url = format!("http://{addr}/stats");
+
+    let (status, _) = get(addr, "/stats").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let response = client.get(&url).bearer_auth("wrong").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = client
+        .get(&url)
+        .bearer_auth("s3cret")
+        .header("Origin", "https://dash.example")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://dash.example"
+    );
+
+    // Preflight requests carry no token and are answered by the CORS layer
+    let response = client
+        .request(reqwest::Method::OPTIONS, &url)
+        .header("Origin", "https://dash.example")
+        .header("Access-Control-Request-Method", "GET")
+        .header("Access-Control-Request-Headers", "authorization")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://dash.example"
+    );
+
+    let response = client
+        .get(&url)
+        .bearer_auth("s3cret")
+        .header("Origin", "https://elsewhere.example")
+        .send()
+        .await
+        .unwrap();
+    assert!(!response
+        .headers()
+        .contains_key("access-control-allow-origin"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_network_bind_needs_a_token() {
+    let repo = init_repo();
+    let serve = |token: Option<&str>, insecure| {
+        let path = repo.path().to_path_buf();
+        let options = ApiOptions {
+            token: token.map(str::to_string),
+            insecure,
+            ..Default::default()
+        };
+        async move {
+            let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server =
+                tokio::spawn(async move { engram_api::serve(listener, path, &options).await });
+            (SocketAddr::from(([127, 0, 0, 1], port)), server)
+        }
+    };
+
+    let (_, server) = serve(None, false).await;
+    let err = server.await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+
+    let (addr, server) = serve(None, true).await;
+    assert_eq!(get(addr, "/stats").await.0, StatusCode::OK);
+    server.abort();
+    let (addr, server) = serve(Some("s3cret"), false).await;
+    assert_eq!(get(addr, "/stats").await.0, StatusCode::UNAUTHORIZED);
+    server.abort();
+}
+
+#[test]
+fn test_invalid_cors_origin() {
+    let options = ApiOptions {
+        token: None,
+        cors_origins: vec!["https://bad\norigin".into()],
+        ..Default::default()
+    };
+    let err = engram_api::router("/nonexistent".into(), &options).unwrap_err();
+    assert!(err.message.contains("Invalid CORS origin"));
+}
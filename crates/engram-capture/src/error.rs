@@ -20,6 +20,9 @@ pub enum CaptureError {
     #[error("Session error: {0}")]
     Session(String),
 
+    #[error("Invalid insight pattern {0}")]
+    InvalidPattern(String),
+
     #[error("Process exited with code {0}")]
     ProcessFailed(i32),
 }
@@ -6,6 +6,8 @@ use sha2::{Digest, Sha256};
 use engram_core::model::*;
 
 use crate::error::CaptureError;
+use crate::import::batch::source_path_key;
+use crate::import::importer::{ImportCandidate, SessionImporter};
 
 /// Import Aider chat history from .aider.chat.history.md
 pub struct AiderImporter;
@@ -26,16 +28,35 @@ impl AiderImporter {
     pub fn import_history(path: &Path) -> Result<Vec<EngramData>, CaptureError> {
         let content = std::fs::read_to_string(path).map_err(CaptureError::Io)?;
         let file_hash = format!("{:x}", Sha256::digest(content.as_bytes()));
+        let source_key = source_path_key(path);
         let mut engrams = parse_aider_history(&content)?;
         // Each session in the file gets a unique source hash based on the file + session index
         for (i, engram) in engrams.iter_mut().enumerate() {
             let session_hash = format!("{:x}", Sha256::digest(format!("{file_hash}:{i}")));
             engram.manifest.source_hash = Some(session_hash);
+            engram.manifest.source_path = Some(format!("{source_key}#{i}"));
         }
         Ok(engrams)
     }
 }
 
+impl SessionImporter for AiderImporter {
+    fn name(&self) -> &str {
+        "aider"
+    }
+
+    fn discover(&self, repo_root: &Path) -> Result<Vec<ImportCandidate>, CaptureError> {
+        Ok(Self::discover(repo_root)?
+            .into_iter()
+            .map(|path| ImportCandidate::new(self.name(), path))
+            .collect())
+    }
+
+    fn import(&self, candidate: &ImportCandidate) -> Result<Vec<EngramData>, CaptureError> {
+        Self::import_history(&candidate.path)
+    }
+}
+
 fn parse_aider_history(content: &str) -> Result<Vec<EngramData>, CaptureError> {
     let mut engrams = Vec::new();
 
@@ -164,6 +185,7 @@ fn parse_aider_session(session_text: &str) -> Result<Option<EngramData>, Capture
         tags: Vec::new(),
         capture_mode: CaptureMode::Import,
         source_hash: None,
+        source_path: None,
     };
 
     let intent = Intent {
@@ -186,6 +208,7 @@ fn parse_aider_session(session_text: &str) -> Result<Option<EngramData>, Capture
         },
         operations: Operations::default(),
         lineage: Lineage::default(),
+        attachments: Vec::new(),
     }))
 }
 
@@ -234,7 +257,7 @@ fn parse_token_line(line: &str, sent: &mut u64, received: &mut u64, cost: &mut f
     }
 }
 
-fn extract_token_count(s: &str) -> Option<u64> {
+pub(crate) fn extract_token_count(s: &str) -> Option<u64> {
     // Find a number (possibly with k/m suffix)
     for word in s.split_whitespace() {
         let word = word.trim_matches(|c: char| {
@@ -258,7 +281,7 @@ fn extract_token_count(s: &str) -> Option<u64> {
     None
 }
 
-fn extract_cost(s: &str) -> Option<f64> {
+pub(crate) fn extract_cost(s: &str) -> Option<f64> {
     for word in s.split_whitespace() {
         if let Some(num_str) = word.strip_prefix('$') {
             if let Ok(c) = num_str.parse::<f64>() {
@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::error::CaptureError;
+
+/// Expand an import argument into the list of files to import.
+///
+/// - A file is returned as-is.
+/// - A directory is walked recursively, keeping files with the given extension.
+/// - Anything else is treated as a glob pattern (e.g. `exports/*.jsonl`).
+///
+/// Results are sorted for deterministic import order.
+pub fn collect_import_files(input: &Path, extension: &str) -> Result<Vec<PathBuf>, CaptureError> {
+    if input.is_file() {
+        return Ok(vec![input.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+
+    if input.is_dir() {
+        for entry in WalkDir::new(input).follow_links(true) {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(e) => {
+                    tracing::debug!("Skipping walk error: {e}");
+                    continue;
+                }
+            };
+            if entry.file_type().is_file() && has_extension(entry.path(), extension) {
+                files.push(entry.into_path());
+            }
+        }
+    } else {
+        let pattern = input.to_string_lossy();
+        let paths = glob::glob(&pattern)
+            .map_err(|e| CaptureError::Import(format!("Invalid glob pattern '{pattern}': {e}")))?;
+        for path in paths.flatten() {
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+        if files.is_empty() && !is_glob(&pattern) {
+            return Err(CaptureError::Import(format!(
+                "Path does not exist: {}",
+                input.display()
+            )));
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Stable identity for an import source file (canonical path when resolvable).
+pub fn source_path_key(path: &Path) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|e| e == extension)
+}
+
+fn is_glob(s: &str) -> bool {
+    s.contains(['*', '?', '['])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_collect_directory_recursive() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("nested/deeper")).unwrap();
+        std::fs::write(root.join("a.jsonl"), "{}").unwrap();
+        std::fs::write(root.join("nested/b.jsonl"), "{}").unwrap();
+        std::fs::write(root.join("nested/deeper/c.jsonl"), "{}").unwrap();
+        std::fs::write(root.join("nested/notes.txt"), "skip me").unwrap();
+
+        let files = collect_import_files(root, "jsonl").unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|f| has_extension(f, "jsonl")));
+    }
+
+    #[test]
+    fn test_collect_glob_and_single_file() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::write(root.join("one.jsonl"), "{}").unwrap();
+        std::fs::write(root.join("two.jsonl"), "{}").unwrap();
+        std::fs::write(root.join("three.md"), "#").unwrap();
+
+        let pattern = root.join("*.jsonl");
+        let files = collect_import_files(&pattern, "jsonl").unwrap();
+        assert_eq!(files.len(), 2);
+
+        // A single file is returned regardless of extension
+        let single = collect_import_files(&root.join("three.md"), "jsonl").unwrap();
+        assert_eq!(single, vec![root.join("three.md")]);
+
+        // A missing non-glob path is an error
+        assert!(collect_import_files(&root.join("missing.jsonl"), "jsonl").is_err());
+    }
+}
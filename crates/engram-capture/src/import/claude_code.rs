@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
@@ -7,6 +8,11 @@ use sha2::{Digest, Sha256};
 use engram_core::model::*;
 
 use crate::error::CaptureError;
+use crate::import::batch::source_path_key;
+use crate::import::importer::{ImportCandidate, SessionImporter};
+
+/// Characters of a tool result kept as its call's `output_summary`.
+const OUTPUT_SUMMARY_CHARS: usize = 200;
 
 /// Import a Claude Code session from a JSONL file.
 pub struct ClaudeCodeImporter;
@@ -46,10 +52,28 @@ impl ClaudeCodeImporter {
         let source_hash = format!("{:x}", Sha256::digest(content.as_bytes()));
         let mut data = parse_claude_code_session(&content)?;
         data.manifest.source_hash = Some(source_hash);
+        data.manifest.source_path = Some(source_path_key(path));
         Ok(data)
     }
 }
 
+impl SessionImporter for ClaudeCodeImporter {
+    fn name(&self) -> &str {
+        "claude-code"
+    }
+
+    fn discover(&self, repo_root: &Path) -> Result<Vec<ImportCandidate>, CaptureError> {
+        Ok(Self::discover_sessions(repo_root)?
+            .into_iter()
+            .map(|path| ImportCandidate::new(self.name(), path))
+            .collect())
+    }
+
+    fn import(&self, candidate: &ImportCandidate) -> Result<Vec<EngramData>, CaptureError> {
+        Self::import_session(&candidate.path).map(|data| vec![data])
+    }
+}
+
 /// Internal Claude Code JSONL entry.
 #[derive(Debug, Deserialize)]
 struct ClaudeEntry {
@@ -99,10 +123,14 @@ fn parse_claude_code_session(content: &str) -> Result<EngramData, CaptureError>
     let mut token_usage = TokenUsage::default();
     let mut transcript_entries = Vec::new();
     let mut tool_calls = Vec::new();
+    // tool_use id -> index in `tool_calls` and when the call was made, to
+    // complete calls from their results
+    let mut tool_call_ids: HashMap<String, (usize, Option<DateTime<Utc>>)> = HashMap::new();
     let mut file_changes = Vec::new();
     let mut original_request = String::new();
 
     // Parse all lines
+    let mut skipped_lines = 0;
     for line in content.lines() {
         if line.trim().is_empty() {
             continue;
@@ -111,10 +139,18 @@ fn parse_claude_code_session(content: &str) -> Result<EngramData, CaptureError>
             Ok(entry) => entries.push(entry),
             Err(e) => {
                 tracing::debug!("Skipping unparseable JSONL line: {e}");
+                skipped_lines += 1;
             }
         }
     }
 
+    // A file where no line parses is not a Claude Code session at all
+    if entries.is_empty() && skipped_lines > 0 {
+        return Err(CaptureError::Import(format!(
+            "No valid Claude Code entries ({skipped_lines} unparseable line(s))"
+        )));
+    }
+
     for entry in &entries {
         // Skip non-message entries
         if !matches!(entry.entry_type.as_str(), "user" | "assistant") {
@@ -235,6 +271,7 @@ fn parse_claude_code_session(content: &str) -> Result<EngramData, CaptureError>
                                 }
                             }
 
+                            tool_call_ids.insert(tool_id.clone(), (tool_calls.len(), ts));
                             tool_calls.push(ToolCall {
                                 timestamp: ts.unwrap_or_else(Utc::now),
                                 tool_name: tool_name.clone(),
@@ -263,13 +300,21 @@ fn parse_claude_code_session(content: &str) -> Result<EngramData, CaptureError>
                                 .to_string();
                             let output = block
                                 .get("content")
-                                .and_then(|c| c.as_str())
-                                .unwrap_or("")
-                                .to_string();
+                                .map(tool_result_text)
+                                .unwrap_or_default();
                             let is_error = block
                                 .get("is_error")
                                 .and_then(|e| e.as_bool())
                                 .unwrap_or(false);
+                            if let Some(&(i, called_at)) = tool_call_ids.get(&tool_id) {
+                                let call = &mut tool_calls[i];
+                                call.is_error |= is_error;
+                                call.output_summary = output_summary(&output);
+                                if let (Some(called_at), Some(ts)) = (called_at, ts) {
+                                    call.duration_ms =
+                                        u64::try_from((ts - called_at).num_milliseconds()).ok();
+                                }
+                            }
 
                             transcript_entries.push(TranscriptEntry {
                                 timestamp: ts.unwrap_or_else(Utc::now),
@@ -348,6 +393,7 @@ fn parse_claude_code_session(content: &str) -> Result<EngramData, CaptureError>
         tags: Vec::new(),
         capture_mode: CaptureMode::Import,
         source_hash: None,
+        source_path: None,
     };
 
     let intent = Intent {
@@ -376,9 +422,39 @@ fn parse_claude_code_session(content: &str) -> Result<EngramData, CaptureError>
         },
         operations,
         lineage: Lineage::default(),
+        attachments: Vec::new(),
     })
 }
 
+/// The text of a tool result's `content`: a string, or an array of blocks
+/// whose text blocks are joined.
+fn tool_result_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// The start of a tool's output, cut to [`OUTPUT_SUMMARY_CHARS`], or `None`
+/// when it printed nothing.
+fn output_summary(output: &str) -> Option<String> {
+    let output = output.trim();
+    if output.is_empty() {
+        return None;
+    }
+    let mut summary: String = output.chars().take(OUTPUT_SUMMARY_CHARS).collect();
+    if summary.len() < output.len() {
+        summary.push_str("...");
+    }
+    Some(summary)
+}
+
 /// Convert a filesystem path to Claude Code's project key format.
 /// /Users/sjonas/myproject -> -Users-sjonas-myproject
 fn path_to_claude_key(path: &Path) -> String {
@@ -436,6 +512,55 @@ mod tests {
 
         // Should have 3 transcript entries: user text, tool_use, tool_result
         assert_eq!(data.transcript.entries.len(), 3);
+        assert!(!data.operations.tool_calls[0].is_error);
+    }
+
+    #[test]
+    fn test_parse_session_marks_failed_tool_calls() {
+        let jsonl = r#"{"type":"user","uuid":"u1","timestamp":"2026-01-15T10:00:00Z","message":{"role":"user","content":"Run tests"}}
+{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2026-01-15T10:00:02Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"cargo test"}},{"type":"tool_use","id":"toolu_2","name":"Read","input":{"file_path":"src/lib.rs"}}]}}
+{"type":"user","uuid":"u2","timestamp":"2026-01-15T10:00:10Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"error[E0425]: cannot find value","is_error":true},{"type":"tool_result","tool_use_id":"toolu_2","content":"pub fn f() {}"}]}}"#;
+
+        let data = parse_claude_code_session(jsonl).unwrap();
+        let errors: Vec<_> = data
+            .operations
+            .tool_calls
+            .iter()
+            .map(|tc| (tc.tool_name.as_str(), tc.is_error))
+            .collect();
+        assert_eq!(errors, [("Bash", true), ("Read", false)]);
+        assert!(data.operations.has_errors());
+
+        let bash = &data.operations.tool_calls[0];
+        assert_eq!(
+            bash.output_summary.as_deref(),
+            Some("error[E0425]: cannot find value")
+        );
+        assert_eq!(bash.duration_ms, Some(8000));
+    }
+
+    #[test]
+    fn test_parse_session_tool_result_blocks() {
+        let output = "x".repeat(500);
+        let jsonl = format!(
+            r#"{{"type":"assistant","uuid":"a1","timestamp":"2026-01-15T10:00:02.250Z","message":{{"role":"assistant","content":[{{"type":"tool_use","id":"toolu_1","name":"Bash","input":{{"command":"make"}}}},{{"type":"tool_use","id":"toolu_2","name":"Bash","input":{{"command":"true"}}}}]}}}}
+{{"type":"user","uuid":"u2","timestamp":"2026-01-15T10:00:03Z","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"toolu_1","content":[{{"type":"text","text":"{output}"}}],"is_error":true}},{{"type":"tool_result","tool_use_id":"toolu_2","content":""}}]}}}}"#
+        );
+
+        let data = parse_claude_code_session(&jsonl).unwrap();
+        let make = &data.operations.tool_calls[0];
+        assert!(make.is_error);
+        assert_eq!(make.duration_ms, Some(750));
+        let summary = make.output_summary.as_deref().unwrap();
+        assert_eq!(summary, format!("{}...", &output[..OUTPUT_SUMMARY_CHARS]));
+        assert!(matches!(
+            &data.transcript.entries[2].content,
+            TranscriptContent::ToolResult { output: o, .. } if *o == output
+        ));
+
+        let silent = &data.operations.tool_calls[1];
+        assert!(!silent.is_error);
+        assert_eq!(silent.output_summary, None);
     }
 
     #[test]
@@ -444,4 +569,10 @@ mod tests {
         assert_eq!(data.manifest.agent.name, "claude-code");
         assert!(data.transcript.entries.is_empty());
     }
+
+    #[test]
+    fn test_parse_corrupt_session_errors() {
+        let result = parse_claude_code_session("not json\n{also not json\n");
+        assert!(matches!(result, Err(CaptureError::Import(_))));
+    }
 }
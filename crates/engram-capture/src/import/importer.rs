@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+
+use engram_core::model::EngramData;
+
+use crate::error::CaptureError;
+use crate::import::aider::AiderImporter;
+use crate::import::claude_code::ClaudeCodeImporter;
+
+/// A source file that an importer can turn into engrams.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportCandidate {
+    /// Name of the importer that discovered (and can parse) this source.
+    pub importer: String,
+    pub path: PathBuf,
+}
+
+impl ImportCandidate {
+    pub fn new(importer: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            importer: importer.into(),
+            path: path.into(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        format!("{}: {}", self.importer, self.path.display())
+    }
+}
+
+/// A pluggable session log format.
+///
+/// Implement this to import sessions from agents Engram does not know about,
+/// then add it to an [`ImporterRegistry`].
+pub trait SessionImporter: Send + Sync {
+    /// Stable identifier for the format (e.g. `claude-code`).
+    fn name(&self) -> &str;
+
+    /// Find importable sources belonging to the given repository.
+    fn discover(&self, repo_root: &Path) -> Result<Vec<ImportCandidate>, CaptureError>;
+
+    /// Parse a candidate into one engram per session it contains.
+    fn import(&self, candidate: &ImportCandidate) -> Result<Vec<EngramData>, CaptureError>;
+}
+
+/// An ordered set of importers consulted during auto-detection.
+#[derive(Default)]
+pub struct ImporterRegistry {
+    importers: Vec<Box<dyn SessionImporter>>,
+}
+
+impl ImporterRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry containing the built-in Claude Code and Aider importers.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(ClaudeCodeImporter));
+        registry.register(Box::new(AiderImporter));
+        registry
+    }
+
+    /// Add an importer. A later importer with the same name replaces the earlier one.
+    pub fn register(&mut self, importer: Box<dyn SessionImporter>) {
+        self.importers.retain(|i| i.name() != importer.name());
+        self.importers.push(importer);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn SessionImporter> {
+        self.importers
+            .iter()
+            .find(|i| i.name() == name)
+            .map(|i| i.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.importers.iter().map(|i| i.name()).collect()
+    }
+
+    /// Run discovery across all importers. An importer that fails to
+    /// discover is skipped so one broken source does not hide the rest.
+    pub fn discover(&self, repo_root: &Path) -> Vec<ImportCandidate> {
+        let mut candidates = Vec::new();
+        for importer in &self.importers {
+            match importer.discover(repo_root) {
+                Ok(found) => candidates.extend(found),
+                Err(e) => tracing::debug!("{} discovery failed: {e}", importer.name()),
+            }
+        }
+        candidates
+    }
+
+    /// Parse a candidate with the importer named in it.
+    pub fn import(&self, candidate: &ImportCandidate) -> Result<Vec<EngramData>, CaptureError> {
+        let importer = self.get(&candidate.importer).ok_or_else(|| {
+            CaptureError::Import(format!("Unknown importer: {}", candidate.importer))
+        })?;
+        importer.import(candidate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::detect::detect_sources_with;
+    use crate::pty::CapturedSession;
+    use crate::session::SessionBuilder;
+    use engram_core::model::{AgentInfo, Role, TranscriptContent, TranscriptEntry};
+    use tempfile::TempDir;
+
+    /// Reads `*.toylog` files: one user prompt per line.
+    struct ToyImporter;
+
+    impl SessionImporter for ToyImporter {
+        fn name(&self) -> &str {
+            "toy"
+        }
+
+        fn discover(&self, repo_root: &Path) -> Result<Vec<ImportCandidate>, CaptureError> {
+            let mut found = Vec::new();
+            for entry in std::fs::read_dir(repo_root)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|e| e == "toylog") {
+                    found.push(ImportCandidate::new(self.name(), path));
+                }
+            }
+            Ok(found)
+        }
+
+        fn import(&self, candidate: &ImportCandidate) -> Result<Vec<EngramData>, CaptureError> {
+            let content = std::fs::read_to_string(&candidate.path)?;
+            let now = chrono::Utc::now();
+            let captured = CapturedSession {
+                raw_output: content.clone().into_bytes().into(),
+                chunks: Vec::new(),
+                start_time: now,
+                end_time: now,
+                exit_code: Some(0),
+                signal: None,
+                file_changes: Vec::new(),
+                command: "toy".into(),
+                args: Vec::new(),
+                interrupted: false,
+                limit_exceeded: None,
+                output_truncated: false,
+                git_state: Default::default(),
+                terminal_size: (80, 24),
+            };
+            let agent = AgentInfo {
+                name: "toy".into(),
+                model: None,
+                version: None,
+            };
+            let mut data = SessionBuilder::new(agent, captured).build();
+            data.transcript.entries = content
+                .lines()
+                .map(|line| TranscriptEntry {
+                    timestamp: now,
+                    role: Role::User,
+                    content: TranscriptContent::Text { text: line.into() },
+                    token_count: None,
+                })
+                .collect();
+            Ok(vec![data])
+        }
+    }
+
+    #[test]
+    fn test_custom_importer_through_auto_detect() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("run.toylog"), "first\nsecond\n").unwrap();
+
+        let mut registry = ImporterRegistry::new();
+        registry.register(Box::new(ToyImporter));
+
+        let candidates = detect_sources_with(&registry, tmp.path());
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].importer, "toy");
+
+        let engrams = registry.import(&candidates[0]).unwrap();
+        assert_eq!(engrams.len(), 1);
+        assert_eq!(engrams[0].transcript.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_registry_builtins_and_replacement() {
+        let mut registry = ImporterRegistry::with_builtins();
+        assert_eq!(registry.names(), vec!["claude-code", "aider"]);
+
+        registry.register(Box::new(ToyImporter));
+        registry.register(Box::new(ToyImporter));
+        assert_eq!(registry.names(), vec!["claude-code", "aider", "toy"]);
+
+        let unknown = ImportCandidate::new("nope", "/tmp/x");
+        assert!(registry.import(&unknown).is_err());
+    }
+}
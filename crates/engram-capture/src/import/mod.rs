@@ -1,3 +1,7 @@
 pub mod aider;
+pub mod batch;
 pub mod claude_code;
 pub mod detect;
+pub mod importer;
+
+pub use importer::{ImportCandidate, ImporterRegistry, SessionImporter};
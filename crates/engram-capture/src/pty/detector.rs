@@ -1,23 +1,88 @@
 use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
 
+use ignore::overrides::OverrideBuilder;
 use ignore::WalkBuilder;
 use sha2::{Digest, Sha256};
 
+use engram_core::config::EngramConfig;
 use engram_core::model::{FileChange, FileChangeType};
 
+/// Default size above which files are fingerprinted by metadata instead of hashed.
+pub const DEFAULT_MAX_SNAPSHOT_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Read size when streaming file contents into the hasher.
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Per-repo ignore file with gitignore syntax, for paths that are tracked
+/// but not worth snapshotting.
+pub const ENGRAM_IGNORE_FILE: &str = ".engramignore";
+
+/// What to leave out of working-tree snapshots, on top of gitignore rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotOptions {
+    /// Gitignore-style patterns, relative to the snapshot root.
+    pub exclude: Vec<String>,
+    /// Files larger than this many bytes are fingerprinted by size and
+    /// modification time rather than content; `None` hashes everything.
+    pub max_file_size: Option<u64>,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            exclude: Vec::new(),
+            max_file_size: Some(DEFAULT_MAX_SNAPSHOT_FILE_SIZE),
+        }
+    }
+}
+
+impl SnapshotOptions {
+    /// Options from `engram.capture.exclude` and `engram.capture.maxFileSize`.
+    pub fn from_config(config: &EngramConfig) -> Self {
+        Self {
+            exclude: config.capture_exclude.clone(),
+            max_file_size: config
+                .capture_max_file_size
+                .or(Some(DEFAULT_MAX_SNAPSHOT_FILE_SIZE)),
+        }
+    }
+}
+
 /// Snapshot the working tree: map of relative path -> SHA256 hash.
-/// Respects .gitignore, .git/info/exclude, and global gitignore.
+/// Respects .gitignore, .git/info/exclude, global gitignore and
+/// `.engramignore`, plus the patterns in `options`.
+///
+/// Contents are streamed through the hasher. Files over
+/// `options.max_file_size` get a hash of their size and mtime instead, so
+/// changes to them are still noticed without reading them.
 pub fn snapshot_working_tree(
     repo_root: &Path,
+    options: &SnapshotOptions,
 ) -> Result<HashMap<PathBuf, Vec<u8>>, std::io::Error> {
     let mut snapshot = HashMap::new();
 
+    // Overrides whitelist by default; a leading "!" turns a glob into an ignore
+    let mut overrides = OverrideBuilder::new(repo_root);
+    for pattern in &options.exclude {
+        overrides
+            .add(&format!("!{pattern}"))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
     let walker = WalkBuilder::new(repo_root)
         .hidden(false) // include dotfiles (.gitignore, .eslintrc, etc.)
         .git_ignore(true) // respect .gitignore
         .git_global(true) // respect global gitignore
         .git_exclude(true) // respect .git/info/exclude
+        .add_custom_ignore_filename(ENGRAM_IGNORE_FILE)
+        .overrides(overrides)
         .filter_entry(|e| {
             // Skip .git directory (not filtered by gitignore since it's special)
             e.file_name().to_str() != Some(".git")
@@ -41,9 +106,15 @@ pub fn snapshot_working_tree(
             .unwrap_or(entry.path())
             .to_path_buf();
 
-        match std::fs::read(entry.path()) {
-            Ok(contents) => {
-                let hash = Sha256::digest(&contents).to_vec();
+        let hash = entry.metadata().map_err(io::Error::other).and_then(|meta| {
+            if options.max_file_size.is_some_and(|max| meta.len() > max) {
+                Ok(metadata_fingerprint(&meta))
+            } else {
+                hash_reader(File::open(entry.path())?)
+            }
+        });
+        match hash {
+            Ok(hash) => {
                 snapshot.insert(rel_path, hash);
             }
             Err(e) => {
@@ -55,53 +126,117 @@ pub fn snapshot_working_tree(
     Ok(snapshot)
 }
 
+/// SHA256 of everything `reader` yields, read in fixed-size buffers.
+fn hash_reader(mut reader: impl Read) -> io::Result<Vec<u8>> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; HASH_BUFFER_SIZE];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => hasher.update(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Stand-in hash for files too large to read: size and mtime, domain-separated
+/// so it cannot collide with a content hash.
+fn metadata_fingerprint(meta: &Metadata) -> Vec<u8> {
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let mut hasher = Sha256::new();
+    hasher.update(b"engram-metadata\0");
+    hasher.update(meta.len().to_le_bytes());
+    hasher.update(mtime.to_le_bytes());
+    hasher.finalize().to_vec()
+}
+
 /// Compare before/after snapshots to detect file changes.
+///
+/// A deleted file whose exact content reappears at a created path is reported
+/// as [`FileChangeType::Renamed`]. When several created files share that
+/// content, one with the same file name is preferred, then one in the same
+/// directory. Renames that also changed the content are not detected and
+/// still appear as a deletion plus a creation.
 pub fn detect_changes(
     before: &HashMap<PathBuf, Vec<u8>>,
     after: &HashMap<PathBuf, Vec<u8>>,
 ) -> Vec<FileChange> {
     let mut changes = Vec::new();
+    let mut created: Vec<&PathBuf> = Vec::new();
 
-    // Check for created and modified files
+    // Check for modified files, collecting new ones for rename pairing
     for (path, after_hash) in after {
         match before.get(path) {
-            None => {
-                changes.push(FileChange {
-                    path: path.to_string_lossy().to_string(),
-                    change_type: FileChangeType::Created,
-                    lines_added: None,
-                    lines_removed: None,
-                });
-            }
+            None => created.push(path),
             Some(before_hash) if before_hash != after_hash => {
-                changes.push(FileChange {
-                    path: path.to_string_lossy().to_string(),
-                    change_type: FileChangeType::Modified,
-                    lines_added: None,
-                    lines_removed: None,
-                });
+                changes.push(change(path, FileChangeType::Modified));
             }
             _ => {} // Unchanged
         }
     }
 
-    // Check for deleted files
-    for path in before.keys() {
-        if !after.contains_key(path) {
-            changes.push(FileChange {
-                path: path.to_string_lossy().to_string(),
-                change_type: FileChangeType::Deleted,
-                lines_added: None,
-                lines_removed: None,
-            });
+    let mut deleted: Vec<&PathBuf> = before
+        .keys()
+        .filter(|path| !after.contains_key(*path))
+        .collect();
+    deleted.sort();
+    created.sort();
+
+    // Pair deletions with identical-content creations
+    for from in deleted {
+        let hash = &before[from];
+        let best = created
+            .iter()
+            .enumerate()
+            .filter(|(_, to)| &after[**to] == hash)
+            // Ties go to the first path in sorted order
+            .min_by_key(|(_, to)| {
+                (
+                    to.file_name() != from.file_name(),
+                    to.parent() != from.parent(),
+                )
+            })
+            .map(|(i, _)| i);
+
+        match best {
+            Some(i) => {
+                let to = created.remove(i);
+                changes.push(change(
+                    to,
+                    FileChangeType::Renamed {
+                        from: from.to_string_lossy().to_string(),
+                    },
+                ));
+            }
+            None => changes.push(change(from, FileChangeType::Deleted)),
         }
     }
 
+    for path in created {
+        changes.push(change(path, FileChangeType::Created));
+    }
+
     // Sort for deterministic output
     changes.sort_by(|a, b| a.path.cmp(&b.path));
     changes
 }
 
+fn change(path: &Path, change_type: FileChangeType) -> FileChange {
+    FileChange {
+        path: path.to_string_lossy().to_string(),
+        change_type,
+        lines_added: None,
+        lines_removed: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,7 +252,7 @@ mod tests {
         std::fs::write(root.join("to_delete.txt"), "goodbye").unwrap();
         std::fs::write(root.join("unchanged.txt"), "same").unwrap();
 
-        let before = snapshot_working_tree(root).unwrap();
+        let before = snapshot_working_tree(root, &SnapshotOptions::default()).unwrap();
         assert_eq!(before.len(), 3);
 
         // Make changes
@@ -125,7 +260,7 @@ mod tests {
         std::fs::remove_file(root.join("to_delete.txt")).unwrap();
         std::fs::write(root.join("new_file.txt"), "new").unwrap();
 
-        let after = snapshot_working_tree(root).unwrap();
+        let after = snapshot_working_tree(root, &SnapshotOptions::default()).unwrap();
         let changes = detect_changes(&before, &after);
 
         assert_eq!(changes.len(), 3);
@@ -161,7 +296,7 @@ mod tests {
         std::fs::write(root.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
         std::fs::write(root.join("real_file.txt"), "content").unwrap();
 
-        let snapshot = snapshot_working_tree(root).unwrap();
+        let snapshot = snapshot_working_tree(root, &SnapshotOptions::default()).unwrap();
         assert_eq!(snapshot.len(), 1);
         assert!(snapshot.contains_key(Path::new("real_file.txt")));
     }
@@ -179,11 +314,190 @@ mod tests {
         std::fs::create_dir_all(root.join("build")).unwrap();
         std::fs::write(root.join("build/output.bin"), "binary").unwrap();
 
-        let snapshot = snapshot_working_tree(root).unwrap();
+        let snapshot = snapshot_working_tree(root, &SnapshotOptions::default()).unwrap();
         // Only .gitignore and source.rs should be included (debug.log and build/ are ignored)
         assert!(snapshot.contains_key(Path::new("source.rs")));
         assert!(snapshot.contains_key(Path::new(".gitignore")));
         assert!(!snapshot.contains_key(Path::new("debug.log")));
         assert!(!snapshot.contains_key(Path::new("build/output.bin")));
     }
+
+    #[test]
+    fn test_exclude_patterns_and_size_limit() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let options = SnapshotOptions {
+            exclude: vec!["fixtures/**".into()],
+            ..Default::default()
+        };
+
+        std::fs::write(root.join("src.rs"), "fn main() {}").unwrap();
+        std::fs::create_dir_all(root.join("fixtures/nested")).unwrap();
+        std::fs::write(root.join("fixtures/nested/data.json"), "{}").unwrap();
+        std::fs::write(root.join(ENGRAM_IGNORE_FILE), "*.pb.go\n").unwrap();
+        std::fs::write(root.join("api.pb.go"), "package api").unwrap();
+
+        let before = snapshot_working_tree(root, &options).unwrap();
+        assert!(before.contains_key(Path::new("src.rs")));
+        assert!(!before.contains_key(Path::new("fixtures/nested/data.json")));
+        assert!(!before.contains_key(Path::new("api.pb.go")));
+
+        // Changes to excluded files are not reported; oversized files are
+        // fingerprinted rather than hashed
+        std::fs::write(root.join("fixtures/nested/data.json"), "{\"a\": 1}").unwrap();
+        std::fs::write(root.join("fixtures/new.json"), "{}").unwrap();
+        let big = vec![0u8; 10 * 1024 * 1024];
+        std::fs::write(root.join("model.bin"), &big).unwrap();
+        std::fs::write(root.join("src.rs"), "fn main() { run() }").unwrap();
+
+        let after = snapshot_working_tree(root, &options).unwrap();
+        assert!(!after.contains_key(Path::new("fixtures/new.json")));
+        let content_hash = Sha256::digest(&big).to_vec();
+        assert_ne!(after[Path::new("model.bin")], content_hash);
+
+        let changes = detect_changes(&before, &after);
+        let paths: Vec<_> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["model.bin", "src.rs"]);
+
+        // Without a limit the large file is hashed
+        let unlimited = SnapshotOptions {
+            max_file_size: None,
+            ..options
+        };
+        let all = snapshot_working_tree(root, &unlimited).unwrap();
+        assert_eq!(all[Path::new("model.bin")], content_hash);
+    }
+
+    #[test]
+    fn test_oversized_file_changes_detected_by_metadata() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let options = SnapshotOptions {
+            max_file_size: Some(16),
+            ..Default::default()
+        };
+
+        std::fs::write(root.join("asset.bin"), [1u8; 64]).unwrap();
+        let before = snapshot_working_tree(root, &options).unwrap();
+        assert_eq!(
+            before,
+            snapshot_working_tree(root, &options).unwrap(),
+            "fingerprint is stable while the file is untouched"
+        );
+
+        std::fs::write(root.join("asset.bin"), [1u8; 65]).unwrap();
+        let after = snapshot_working_tree(root, &options).unwrap();
+        let changes = detect_changes(&before, &after);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].change_type, FileChangeType::Modified);
+    }
+
+    /// Records the largest read request so tests can bound buffer size.
+    struct CountingReader<R> {
+        inner: R,
+        peak_read: usize,
+        total: u64,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.peak_read = self.peak_read.max(buf.len());
+            let n = self.inner.read(buf)?;
+            self.total += n as u64;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_hashing_streams_large_file() {
+        const SIZE: u64 = 100 * 1024 * 1024;
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("sparse.img");
+        File::create(&path).unwrap().set_len(SIZE).unwrap();
+
+        let mut reader = CountingReader {
+            inner: File::open(&path).unwrap(),
+            peak_read: 0,
+            total: 0,
+        };
+        let hash = hash_reader(&mut reader).unwrap();
+
+        assert_eq!(reader.total, SIZE);
+        assert!(reader.peak_read <= HASH_BUFFER_SIZE);
+        assert_eq!(hash.len(), 32);
+    }
+
+    fn snapshot(files: &[(&str, &str)]) -> HashMap<PathBuf, Vec<u8>> {
+        files
+            .iter()
+            .map(|(path, content)| {
+                (
+                    PathBuf::from(path),
+                    Sha256::digest(content.as_bytes()).to_vec(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_pure_rename() {
+        let before = snapshot(&[("src/a.rs", "fn a() {}"), ("src/lib.rs", "mod a;")]);
+        let after = snapshot(&[("src/b.rs", "fn a() {}"), ("src/lib.rs", "mod b;")]);
+
+        let changes = detect_changes(&before, &after);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, "src/b.rs");
+        assert_eq!(
+            changes[0].change_type,
+            FileChangeType::Renamed {
+                from: "src/a.rs".into()
+            }
+        );
+        assert_eq!(changes[1].change_type, FileChangeType::Modified);
+    }
+
+    #[test]
+    fn test_rename_prefers_same_name_then_same_directory() {
+        let before = snapshot(&[("src/util.rs", "shared"), ("src/old.rs", "shared")]);
+        let after = snapshot(&[
+            ("lib/util.rs", "shared"),
+            ("src/new.rs", "shared"),
+            ("tests/copy.rs", "shared"),
+        ]);
+
+        let changes = detect_changes(&before, &after);
+        let renamed_from = |path: &str| {
+            changes
+                .iter()
+                .find(|c| c.path == path)
+                .map(|c| c.change_type.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            renamed_from("lib/util.rs"),
+            FileChangeType::Renamed {
+                from: "src/util.rs".into()
+            }
+        );
+        assert_eq!(
+            renamed_from("src/new.rs"),
+            FileChangeType::Renamed {
+                from: "src/old.rs".into()
+            }
+        );
+        assert_eq!(renamed_from("tests/copy.rs"), FileChangeType::Created);
+    }
+
+    #[test]
+    fn test_no_rename_when_content_differs() {
+        let before = snapshot(&[("src/a.rs", "fn a() {}")]);
+        let after = snapshot(&[("src/b.rs", "fn b() {}")]);
+
+        let changes = detect_changes(&before, &after);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, "src/a.rs");
+        assert_eq!(changes[0].change_type, FileChangeType::Deleted);
+        assert_eq!(changes[1].path, "src/b.rs");
+        assert_eq!(changes[1].change_type, FileChangeType::Created);
+    }
 }
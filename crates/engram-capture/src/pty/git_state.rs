@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use git2::{Repository, StatusOptions};
+
+/// Repository state observed when a session starts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitState {
+    /// Short name of the checked-out branch. `None` on a detached HEAD (the
+    /// commit itself is already recorded via the session's git commits), on an
+    /// unborn branch, or outside a repository.
+    pub branch: Option<String>,
+    /// Tracked or untracked (non-ignored) changes were present.
+    pub dirty: bool,
+}
+
+impl GitState {
+    /// Inspect the repository containing `working_dir`. Never fails: anything
+    /// that cannot be determined is left at its default.
+    pub fn capture(working_dir: &Path) -> Self {
+        let Ok(repo) = Repository::discover(working_dir) else {
+            return Self::default();
+        };
+
+        let branch = repo
+            .head()
+            .ok()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(str::to_string));
+
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true).include_ignored(false);
+        let dirty = repo
+            .statuses(Some(&mut opts))
+            .map(|statuses| !statuses.is_empty())
+            .unwrap_or(false);
+
+        Self { branch, dirty }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn repo_with_commit() -> (TempDir, Repository) {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        std::fs::write(tmp.path().join("README.md"), "hello").unwrap();
+        {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("README.md")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let sig = git2::Signature::now("test", "test@example.com").unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+                .unwrap();
+        }
+        (tmp, repo)
+    }
+
+    #[test]
+    fn test_named_branch_and_dirty_tree() {
+        let (tmp, repo) = repo_with_commit();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature/auth", &head, false).unwrap();
+        repo.set_head("refs/heads/feature/auth").unwrap();
+
+        let clean = GitState::capture(tmp.path());
+        assert_eq!(clean.branch.as_deref(), Some("feature/auth"));
+        assert!(!clean.dirty);
+
+        std::fs::write(tmp.path().join("notes.txt"), "wip").unwrap();
+        assert!(GitState::capture(tmp.path()).dirty);
+    }
+
+    #[test]
+    fn test_detached_head_has_no_branch() {
+        let (tmp, repo) = repo_with_commit();
+        let head = repo.head().unwrap().target().unwrap();
+        repo.set_head_detached(head).unwrap();
+
+        let state = GitState::capture(tmp.path());
+        assert_eq!(state.branch, None);
+        assert!(!state.dirty);
+    }
+
+    #[test]
+    fn test_outside_repository() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(GitState::capture(tmp.path()), GitState::default());
+    }
+}
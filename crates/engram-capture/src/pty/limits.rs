@@ -0,0 +1,109 @@
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use portable_pty::ChildKiller;
+
+use super::signals::terminate;
+
+/// How often the monitor checks the elapsed time and output size.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A limit from [`PtyWrapperConfig`](super::PtyWrapperConfig) that ended the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionLimit {
+    /// The session ran longer than `timeout`.
+    Timeout,
+    /// The child printed more than `max_output_bytes`.
+    MaxOutput,
+}
+
+/// Counts bytes read through it so the monitor can see output volume.
+pub(crate) struct MeteredReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+
+impl<R: Read> MeteredReader<R> {
+    pub(crate) fn new(inner: R, count: Arc<AtomicU64>) -> Self {
+        Self { inner, count }
+    }
+}
+
+impl<R: Read> Read for MeteredReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// Stops the child (SIGTERM, then SIGKILL after a grace period) once the
+/// session exceeds its time or output limit.
+pub(crate) struct LimitMonitor {
+    exited: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Option<SessionLimit>>>,
+}
+
+impl LimitMonitor {
+    pub(crate) fn spawn(
+        timeout: Option<Duration>,
+        max_output_bytes: Option<u64>,
+        output_len: Arc<AtomicU64>,
+        child_pid: Option<u32>,
+        mut killer: Box<dyn ChildKiller + Send + Sync>,
+    ) -> Self {
+        let exited = Arc::new(AtomicBool::new(false));
+        if timeout.is_none() && max_output_bytes.is_none() {
+            return Self {
+                exited,
+                thread: None,
+            };
+        }
+
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let exited_flag = Arc::clone(&exited);
+        let thread = std::thread::spawn(move || {
+            while !exited_flag.load(Ordering::SeqCst) {
+                let exceeded = if deadline.is_some_and(|d| Instant::now() >= d) {
+                    Some(SessionLimit::Timeout)
+                } else if max_output_bytes
+                    .is_some_and(|max| output_len.load(Ordering::Relaxed) > max)
+                {
+                    Some(SessionLimit::MaxOutput)
+                } else {
+                    None
+                };
+                if let Some(limit) = exceeded {
+                    tracing::debug!("Session limit exceeded ({limit:?}); stopping child");
+                    terminate(child_pid, killer.as_mut(), &exited_flag);
+                    return Some(limit);
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            None
+        });
+
+        Self {
+            exited,
+            thread: Some(thread),
+        }
+    }
+
+    /// Call once the child has exited. Returns the limit that stopped it, if any.
+    pub(crate) fn finish(mut self) -> Option<SessionLimit> {
+        self.exited.store(true, Ordering::SeqCst);
+        self.thread.take().and_then(|t| t.join().ok()).flatten()
+    }
+}
+
+impl Drop for LimitMonitor {
+    fn drop(&mut self) {
+        self.exited.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -1,5 +1,16 @@
 mod detector;
+mod git_state;
+mod limits;
+mod output;
+mod resize;
+mod signals;
 mod wrapper;
 
-pub use detector::{detect_changes, snapshot_working_tree};
+pub use detector::{
+    detect_changes, snapshot_working_tree, SnapshotOptions, DEFAULT_MAX_SNAPSHOT_FILE_SIZE,
+    ENGRAM_IGNORE_FILE,
+};
+pub use git_state::GitState;
+pub use limits::SessionLimit;
+pub use output::{CapturedOutput, OutputChunk, DEFAULT_MAX_BUFFER_BYTES};
 pub use wrapper::{CapturedSession, PtySession, PtyWrapperConfig};
@@ -0,0 +1,350 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tempfile::TempPath;
+
+/// Default in-memory limit for captured PTY output before spilling to disk.
+pub const DEFAULT_MAX_BUFFER_BYTES: usize = 16 * 1024 * 1024;
+
+/// Raw output captured from a PTY session.
+#[derive(Debug, Clone)]
+pub enum CapturedOutput {
+    InMemory(Vec<u8>),
+    /// Output exceeded the in-memory cap and was written to a temp file,
+    /// which is deleted when the last clone is dropped.
+    Spooled {
+        path: Arc<TempPath>,
+        total_len: u64,
+    },
+}
+
+impl CapturedOutput {
+    pub fn len(&self) -> u64 {
+        match self {
+            Self::InMemory(buf) => buf.len() as u64,
+            Self::Spooled { total_len, .. } => *total_len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_spooled(&self) -> bool {
+        matches!(self, Self::Spooled { .. })
+    }
+
+    /// Path of the spool file, if output was spilled to disk.
+    pub fn spool_path(&self) -> Option<&Path> {
+        match self {
+            Self::InMemory(_) => None,
+            Self::Spooled { path, .. } => Some(path),
+        }
+    }
+
+    /// Read the entire output. Prefer [`window`](Self::window) for large sessions.
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::InMemory(buf) => Ok(buf.clone()),
+            Self::Spooled { path, .. } => std::fs::read(&**path),
+        }
+    }
+
+    /// Stream the output from the start without loading it into memory.
+    pub fn reader(&self) -> io::Result<Box<dyn Read + '_>> {
+        match self {
+            Self::InMemory(buf) => Ok(Box::new(buf.as_slice())),
+            Self::Spooled { path, .. } => Ok(Box::new(BufReader::new(File::open(&**path)?))),
+        }
+    }
+
+    /// At most `max` bytes: the whole output if it fits, otherwise the first
+    /// and last `max / 2` bytes.
+    pub fn window(&self, max: usize) -> io::Result<Vec<u8>> {
+        let len = self.len();
+        if len <= max as u64 {
+            return self.to_vec();
+        }
+        let half = max / 2;
+        match self {
+            Self::InMemory(buf) => {
+                let mut out = buf[..half].to_vec();
+                out.extend_from_slice(&buf[buf.len() - half..]);
+                Ok(out)
+            }
+            Self::Spooled { path, .. } => {
+                let mut file = File::open(&**path)?;
+                let mut out = vec![0u8; half * 2];
+                file.read_exact(&mut out[..half])?;
+                file.seek(SeekFrom::Start(len - half as u64))?;
+                file.read_exact(&mut out[half..])?;
+                Ok(out)
+            }
+        }
+    }
+}
+
+impl Default for CapturedOutput {
+    fn default() -> Self {
+        Self::InMemory(Vec::new())
+    }
+}
+
+impl From<Vec<u8>> for CapturedOutput {
+    fn from(buf: Vec<u8>) -> Self {
+        Self::InMemory(buf)
+    }
+}
+
+/// Reads closer together than this are merged into one [`OutputChunk`].
+const CHUNK_MERGE_GAP_MS: i64 = 100;
+
+/// A burst of PTY output: when it arrived and where it sits in the captured
+/// output. Chunks reference byte ranges rather than holding bytes so the
+/// in-memory cap still applies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputChunk {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Byte offset of the first byte in the captured output.
+    pub offset: u64,
+    pub len: u64,
+}
+
+impl OutputChunk {
+    pub fn end(&self) -> u64 {
+        self.offset + self.len
+    }
+}
+
+/// Accumulates output in memory up to a cap, then streams everything to a
+/// temp file.
+pub(crate) struct OutputSink {
+    max_in_memory: usize,
+    /// Where the temp file goes; the system temp directory by default.
+    spool_dir: Option<PathBuf>,
+    buffer: Vec<u8>,
+    spool: Option<(BufWriter<File>, TempPath)>,
+    total_len: u64,
+}
+
+impl OutputSink {
+    pub(crate) fn new(max_in_memory: usize) -> Self {
+        Self {
+            max_in_memory,
+            spool_dir: None,
+            buffer: Vec::new(),
+            spool: None,
+            total_len: 0,
+        }
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if let Some((writer, _)) = &mut self.spool {
+            writer.write_all(bytes)?;
+        } else if self.buffer.len() + bytes.len() <= self.max_in_memory {
+            self.buffer.extend_from_slice(bytes);
+        } else {
+            let mut builder = tempfile::Builder::new();
+            builder.prefix("engram-capture-");
+            let (file, path) = match &self.spool_dir {
+                Some(dir) => builder.tempfile_in(dir)?,
+                None => builder.tempfile()?,
+            }
+            .into_parts();
+            let mut writer = BufWriter::new(file);
+            writer.write_all(&self.buffer)?;
+            writer.write_all(bytes)?;
+            self.buffer = Vec::new();
+            self.spool = Some((writer, path));
+        }
+        self.total_len += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// The output written. After a failed write the spool file may hold
+    /// less than was written, so its length is taken from the file.
+    pub(crate) fn finish(self) -> io::Result<CapturedOutput> {
+        match self.spool {
+            None => Ok(CapturedOutput::InMemory(self.buffer)),
+            Some((writer, path)) => {
+                let file = writer.into_inner().map_err(|e| e.into_error())?;
+                Ok(CapturedOutput::Spooled {
+                    total_len: file.metadata()?.len(),
+                    path: Arc::new(path),
+                })
+            }
+        }
+    }
+}
+
+/// Append a read of `len` bytes at `offset` to the chunk timeline.
+fn record_chunk(chunks: &mut Vec<OutputChunk>, at: DateTime<Utc>, offset: u64, len: u64) {
+    if let Some(last) = chunks.last_mut() {
+        if (at - last.ended_at).num_milliseconds() < CHUNK_MERGE_GAP_MS {
+            last.ended_at = at;
+            last.len += len;
+            return;
+        }
+    }
+    chunks.push(OutputChunk {
+        started_at: at,
+        ended_at: at,
+        offset,
+        len,
+    });
+}
+
+/// What [`capture_stream`] read.
+pub(crate) struct StreamCapture {
+    pub output: CapturedOutput,
+    pub chunks: Vec<OutputChunk>,
+    /// Storing the output failed part way, so `output` lacks the rest.
+    pub truncated: bool,
+}
+
+/// Copy `reader` to `passthrough` until EOF, capturing everything read along
+/// with when it arrived.
+///
+/// Read errors end the stream (a closed PTY reports EIO rather than EOF).
+/// If storing the output fails, the rest is still read and passed through,
+/// just not captured, so the child never blocks on a full PTY.
+pub(crate) fn capture_stream(
+    reader: impl Read,
+    passthrough: impl Write,
+    max_in_memory: usize,
+) -> StreamCapture {
+    capture_into(reader, passthrough, OutputSink::new(max_in_memory))
+}
+
+fn capture_into(
+    mut reader: impl Read,
+    mut passthrough: impl Write,
+    mut sink: OutputSink,
+) -> StreamCapture {
+    let mut chunks = Vec::new();
+    let mut truncated = false;
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let _ = passthrough.write_all(&buf[..n]);
+                let _ = passthrough.flush();
+                if truncated {
+                    continue;
+                }
+                let offset = sink.total_len;
+                match sink.write(&buf[..n]) {
+                    Ok(()) => record_chunk(&mut chunks, Utc::now(), offset, n as u64),
+                    Err(e) => {
+                        tracing::warn!("Failed to spool PTY output, capturing no more of it: {e}");
+                        truncated = true;
+                    }
+                }
+            }
+        }
+    }
+
+    let output = sink.finish().unwrap_or_else(|e| {
+        tracing::warn!("Failed to spool PTY output, dropping it: {e}");
+        truncated = true;
+        CapturedOutput::default()
+    });
+    if truncated {
+        // Chunks may point past what made it into the spool file
+        let len = output.len();
+        chunks.retain(|c| c.offset < len);
+        if let Some(last) = chunks.last_mut() {
+            last.len = last.len.min(len - last.offset);
+        }
+    }
+    StreamCapture {
+        output,
+        chunks,
+        truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_output_stays_in_memory() {
+        let StreamCapture { output, chunks, .. } = capture_stream(&b"hello"[..], io::sink(), 1024);
+        assert!(!output.is_spooled());
+        assert_eq!(output.to_vec().unwrap(), b"hello");
+        assert_eq!(chunks.len(), 1);
+        assert_eq!((chunks[0].offset, chunks[0].len), (0, 5));
+    }
+
+    #[test]
+    fn test_large_output_spills_to_disk() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let StreamCapture {
+            output,
+            chunks,
+            truncated,
+        } = capture_stream(io::Cursor::new(data.clone()), io::sink(), 1000);
+        assert!(!truncated);
+        assert_eq!(chunks.iter().map(|c| c.len).sum::<u64>(), data.len() as u64);
+
+        assert!(output.is_spooled());
+        assert_eq!(output.len(), data.len() as u64);
+        assert_eq!(output.to_vec().unwrap(), data);
+
+        let window = output.window(100).unwrap();
+        assert_eq!(&window[..50], &data[..50]);
+        assert_eq!(&window[50..], &data[data.len() - 50..]);
+
+        // The spool file goes away with the last clone
+        let path = output.spool_path().unwrap().to_path_buf();
+        let clone = output.clone();
+        drop(output);
+        assert!(path.exists());
+        drop(clone);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_spool_failure_keeps_reading() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let mut sink = OutputSink::new(5000);
+        let dir = tempfile::tempdir().unwrap();
+        sink.spool_dir = Some(dir.path().join("missing"));
+
+        let mut passed_through = Vec::new();
+        let capture = capture_into(io::Cursor::new(data.clone()), &mut passed_through, sink);
+
+        // Everything reached the terminal, but only what fit in memory was kept
+        assert_eq!(passed_through, data);
+        assert!(capture.truncated);
+        assert!(!capture.output.is_spooled());
+        let kept = capture.output.to_vec().unwrap();
+        assert!(!kept.is_empty() && kept.len() <= 5000);
+        assert_eq!(kept, data[..kept.len()]);
+        assert_eq!(
+            capture.chunks.last().map(OutputChunk::end),
+            Some(kept.len() as u64)
+        );
+    }
+
+    #[test]
+    fn test_record_chunk_merges_close_reads() {
+        let t0 = Utc::now();
+        let ms = chrono::Duration::milliseconds;
+        let mut chunks = Vec::new();
+        record_chunk(&mut chunks, t0, 0, 10);
+        record_chunk(&mut chunks, t0 + ms(50), 10, 5);
+        record_chunk(&mut chunks, t0 + ms(400), 15, 7);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!((chunks[0].offset, chunks[0].len), (0, 15));
+        assert_eq!(chunks[0].ended_at, t0 + ms(50));
+        assert_eq!((chunks[1].offset, chunks[1].end()), (15, 22));
+    }
+}
@@ -0,0 +1,198 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use portable_pty::{MasterPty, PtySize};
+
+/// Size used when neither the terminal nor the environment reports one.
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// How often to re-check the terminal size where SIGWINCH is unavailable.
+#[cfg(not(unix))]
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Current terminal size as `(cols, rows)`.
+///
+/// Queries the controlling terminal first, then `COLUMNS`/`LINES`, then
+/// falls back to 80x24.
+pub fn terminal_size() -> (u16, u16) {
+    resolve_size(query_tty_size(), |key| std::env::var(key).ok())
+}
+
+pub(crate) fn pty_size((cols, rows): (u16, u16)) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// Fallback chain for the terminal size: tty ioctl, then environment, then default.
+fn resolve_size(tty: Option<(u16, u16)>, env: impl Fn(&str) -> Option<String>) -> (u16, u16) {
+    if let Some(size) = tty.filter(|&(c, r)| c > 0 && r > 0) {
+        return size;
+    }
+    let cols = env("COLUMNS").and_then(|v| v.parse::<u16>().ok());
+    let rows = env("LINES").and_then(|v| v.parse::<u16>().ok());
+    match (cols, rows) {
+        (Some(c), Some(r)) if c > 0 && r > 0 => (c, r),
+        _ => DEFAULT_SIZE,
+    }
+}
+
+#[cfg(unix)]
+fn query_tty_size() -> Option<(u16, u16)> {
+    use rustix::termios::tcgetwinsize;
+
+    // stdout is usually the terminal; stdin/stderr cover `engram record > log`.
+    [
+        tcgetwinsize(std::io::stdout()),
+        tcgetwinsize(std::io::stdin()),
+        tcgetwinsize(std::io::stderr()),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|ws| (ws.ws_col, ws.ws_row))
+    .find(|&(c, r)| c > 0 && r > 0)
+}
+
+#[cfg(windows)]
+fn query_tty_size() -> Option<(u16, u16)> {
+    // GetConsoleScreenBufferInfo on the console attached to stdout, then stderr
+    terminal_size::terminal_size_of(std::io::stdout())
+        .or_else(|| terminal_size::terminal_size_of(std::io::stderr()))
+        .map(|(w, h)| (w.0, h.0))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn query_tty_size() -> Option<(u16, u16)> {
+    None
+}
+
+/// Keeps the PTY size in sync with the user's terminal until dropped.
+///
+/// On Unix this waits for SIGWINCH; elsewhere it polls.
+pub(crate) struct ResizeWatcher {
+    stop: Arc<AtomicBool>,
+    #[cfg(unix)]
+    signals: Option<signal_hook::iterator::Handle>,
+    handle: Option<JoinHandle<()>>,
+    /// Held when no watcher thread owns the master, so the PTY stays open.
+    _master: Option<Box<dyn MasterPty + Send>>,
+}
+
+impl ResizeWatcher {
+    pub(crate) fn spawn(master: Box<dyn MasterPty + Send>, initial: (u16, u16)) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+
+        #[cfg(unix)]
+        {
+            use signal_hook::consts::SIGWINCH;
+            use signal_hook::iterator::Signals;
+
+            match Signals::new([SIGWINCH]) {
+                Ok(mut signals) => {
+                    let handle = signals.handle();
+                    let thread = std::thread::spawn(move || {
+                        let mut current = initial;
+                        for _ in signals.forever() {
+                            current = apply_size(master.as_ref(), current);
+                        }
+                    });
+                    Self {
+                        stop,
+                        signals: Some(handle),
+                        handle: Some(thread),
+                        _master: None,
+                    }
+                }
+                Err(e) => {
+                    tracing::debug!("SIGWINCH handler unavailable, resize disabled: {e}");
+                    Self {
+                        stop,
+                        signals: None,
+                        handle: None,
+                        _master: Some(master),
+                    }
+                }
+            }
+        }
+
+        #[cfg(not(unix))]
+        {
+            let stop_poll = Arc::clone(&stop);
+            let thread = std::thread::spawn(move || {
+                let mut current = initial;
+                while !stop_poll.load(Ordering::Relaxed) {
+                    std::thread::sleep(POLL_INTERVAL);
+                    current = apply_size(master.as_ref(), current);
+                }
+            });
+            Self {
+                stop,
+                handle: Some(thread),
+                _master: None,
+            }
+        }
+    }
+}
+
+impl Drop for ResizeWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        #[cfg(unix)]
+        if let Some(signals) = self.signals.take() {
+            signals.close();
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Resize the PTY if the terminal size changed; returns the size now in effect.
+fn apply_size(master: &dyn MasterPty, current: (u16, u16)) -> (u16, u16) {
+    let size = terminal_size();
+    if size == current {
+        return current;
+    }
+    match master.resize(pty_size(size)) {
+        Ok(()) => size,
+        Err(e) => {
+            tracing::debug!("Failed to resize PTY: {e}");
+            current
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn test_resolve_size_fallback_chain() {
+        let vars = [("COLUMNS", "132"), ("LINES", "50")];
+
+        // The tty wins over the environment
+        assert_eq!(resolve_size(Some((200, 60)), env(&vars)), (200, 60));
+        // A zero-sized tty (e.g. a pipe reporting 0x0) falls through
+        assert_eq!(resolve_size(Some((0, 0)), env(&vars)), (132, 50));
+        assert_eq!(resolve_size(None, env(&vars)), (132, 50));
+        // Incomplete or invalid environment falls back to the default
+        assert_eq!(resolve_size(None, env(&[("COLUMNS", "132")])), DEFAULT_SIZE);
+        assert_eq!(
+            resolve_size(None, env(&[("COLUMNS", "wide"), ("LINES", "50")])),
+            DEFAULT_SIZE
+        );
+        assert_eq!(resolve_size(None, env(&[])), DEFAULT_SIZE);
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use portable_pty::ChildKiller;
+
+/// How long the child gets to exit after a forwarded signal before SIGKILL.
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Forwards SIGINT/SIGTERM received by the wrapper to the recorded child's
+/// process group, escalating to SIGKILL if the child ignores them.
+///
+/// Installing the handlers also stops the signals from terminating the
+/// wrapper, so the normal post-run path still stores the engram.
+pub(crate) struct SignalForwarder {
+    interrupted: Arc<AtomicBool>,
+    exited: Arc<AtomicBool>,
+    #[cfg(unix)]
+    signals: Option<signal_hook::iterator::Handle>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SignalForwarder {
+    #[cfg(unix)]
+    pub(crate) fn install(child_pid: Option<u32>) -> Self {
+        use signal_hook::consts::{SIGINT, SIGTERM};
+        use signal_hook::iterator::Signals;
+
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let exited = Arc::new(AtomicBool::new(false));
+
+        let mut signals = match Signals::new([SIGINT, SIGTERM]) {
+            Ok(signals) => signals,
+            Err(e) => {
+                tracing::debug!("Signal forwarding unavailable: {e}");
+                return Self {
+                    interrupted,
+                    exited,
+                    signals: None,
+                    thread: None,
+                };
+            }
+        };
+        let handle = signals.handle();
+
+        let interrupted_flag = Arc::clone(&interrupted);
+        let exited_flag = Arc::clone(&exited);
+        let thread = std::thread::spawn(move || {
+            let mut escalation: Option<JoinHandle<()>> = None;
+            for sig in signals.forever() {
+                interrupted_flag.store(true, Ordering::SeqCst);
+                let Some(pid) = child_pid else { continue };
+                forward(pid, sig);
+                if escalation.is_none() {
+                    let exited = Arc::clone(&exited_flag);
+                    escalation = Some(std::thread::spawn(move || {
+                        if !wait_for_exit(&exited, KILL_GRACE_PERIOD) {
+                            forward(pid, signal_hook::consts::SIGKILL);
+                        }
+                    }));
+                }
+            }
+            if let Some(escalation) = escalation {
+                let _ = escalation.join();
+            }
+        });
+
+        Self {
+            interrupted,
+            exited,
+            signals: Some(handle),
+            thread: Some(thread),
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn install(_child_pid: Option<u32>) -> Self {
+        Self {
+            interrupted: Arc::new(AtomicBool::new(false)),
+            exited: Arc::new(AtomicBool::new(false)),
+            thread: None,
+        }
+    }
+
+    /// Call once the child has exited. Returns whether a signal was received.
+    pub(crate) fn finish(mut self) -> bool {
+        self.stop();
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    fn stop(&mut self) {
+        self.exited.store(true, Ordering::SeqCst);
+        #[cfg(unix)]
+        if let Some(signals) = self.signals.take() {
+            signals.close();
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for SignalForwarder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Stop the child on the wrapper's own initiative: SIGTERM to its process
+/// group, then SIGKILL if it is still running after the grace period.
+/// Without a pid (or off Unix) the child is killed outright.
+pub(crate) fn terminate(child_pid: Option<u32>, killer: &mut dyn ChildKiller, exited: &AtomicBool) {
+    #[cfg(unix)]
+    if let Some(pid) = child_pid {
+        forward(pid, signal_hook::consts::SIGTERM);
+        if !wait_for_exit(exited, KILL_GRACE_PERIOD) {
+            forward(pid, signal_hook::consts::SIGKILL);
+        }
+        return;
+    }
+    #[cfg(not(unix))]
+    let _ = (child_pid, exited);
+
+    if let Err(e) = killer.kill() {
+        tracing::debug!("Failed to kill child: {e}");
+    }
+}
+
+/// Wait up to `grace` for `exited` to be set; returns whether it was.
+fn wait_for_exit(exited: &AtomicBool, grace: Duration) -> bool {
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline {
+        if exited.load(Ordering::SeqCst) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    exited.load(Ordering::SeqCst)
+}
+
+/// The number of the signal portable-pty describes as `description`: the C
+/// library's `strsignal` text (`Terminated`, sometimes with a `: 15`
+/// suffix), or `Signal 15` when it has none. `None` for descriptions not
+/// known here.
+#[cfg(unix)]
+pub(crate) fn signal_number(description: &str) -> Option<i32> {
+    use signal_hook::consts::*;
+
+    if let Some((_, number)) = description.rsplit_once(": ") {
+        return number.parse().ok();
+    }
+    if let Some(number) = description.strip_prefix("Signal ") {
+        return number.parse().ok();
+    }
+    let sig = match description {
+        "Hangup" => SIGHUP,
+        "Interrupt" => SIGINT,
+        "Quit" => SIGQUIT,
+        "Illegal instruction" => SIGILL,
+        "Trace/breakpoint trap" => SIGTRAP,
+        "Aborted" | "Abort trap" => SIGABRT,
+        "Bus error" => SIGBUS,
+        "Floating point exception" | "Floating-point exception" => SIGFPE,
+        "Killed" => SIGKILL,
+        "User defined signal 1" | "User signal 1" => SIGUSR1,
+        "Segmentation fault" => SIGSEGV,
+        "User defined signal 2" | "User signal 2" => SIGUSR2,
+        "Broken pipe" => SIGPIPE,
+        "Alarm clock" => SIGALRM,
+        "Terminated" => SIGTERM,
+        _ => return None,
+    };
+    Some(sig)
+}
+
+/// Send `sig` to the child's process group (the PTY child leads its own
+/// session), falling back to the child alone.
+#[cfg(unix)]
+fn forward(pid: u32, sig: i32) {
+    use rustix::process::{kill_process, kill_process_group, Pid, Signal};
+
+    let (Some(pid), Some(sig)) = (
+        i32::try_from(pid).ok().and_then(Pid::from_raw),
+        Signal::from_named_raw(sig),
+    ) else {
+        return;
+    };
+    if kill_process_group(pid, sig).is_err() {
+        if let Err(e) = kill_process(pid, sig) {
+            tracing::debug!("Failed to forward signal to child: {e}");
+        }
+    }
+}
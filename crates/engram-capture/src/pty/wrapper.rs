@@ -1,17 +1,23 @@
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use portable_pty::{native_pty_system, CommandBuilder};
 
 use engram_core::model::FileChange;
 
 use crate::error::CaptureError;
 
-use super::detector::{detect_changes, snapshot_working_tree};
+use super::detector::{detect_changes, snapshot_working_tree, SnapshotOptions};
+use super::git_state::GitState;
+use super::limits::{LimitMonitor, MeteredReader, SessionLimit};
+use super::output::{capture_stream, CapturedOutput, OutputChunk};
+use super::resize::{pty_size, terminal_size, ResizeWatcher};
+use super::signals::SignalForwarder;
 
 /// Configuration for a PTY-wrapped agent session.
 #[derive(Debug, Clone)]
@@ -20,18 +26,40 @@ pub struct PtyWrapperConfig {
     pub args: Vec<String>,
     pub working_dir: PathBuf,
     pub agent_name: Option<String>,
+    /// Bytes of output kept in memory before spilling to a temp file.
+    pub max_buffer_bytes: usize,
+    /// Exclude patterns and size limit for the before/after snapshots.
+    pub snapshot: SnapshotOptions,
+    /// Stop the child once the session has run this long.
+    pub timeout: Option<Duration>,
+    /// Stop the child once it has printed more than this many bytes.
+    pub max_output_bytes: Option<u64>,
 }
 
 /// Result of a captured PTY session.
 #[derive(Debug, Clone)]
 pub struct CapturedSession {
-    pub raw_output: Vec<u8>,
+    pub raw_output: CapturedOutput,
+    /// When each burst of output arrived, as ranges of `raw_output`.
+    pub chunks: Vec<OutputChunk>,
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
     pub exit_code: Option<u32>,
+    /// The signal that killed the child, if one did (Unix only).
+    pub signal: Option<i32>,
     pub file_changes: Vec<FileChange>,
     pub command: String,
     pub args: Vec<String>,
+    /// The session ended because the wrapper received SIGINT/SIGTERM.
+    pub interrupted: bool,
+    /// The configured limit that stopped the child, if one did.
+    pub limit_exceeded: Option<SessionLimit>,
+    /// Storing the output failed part way, so `raw_output` lacks the rest.
+    pub output_truncated: bool,
+    /// Branch and working-tree state when the session started.
+    pub git_state: GitState,
+    /// Terminal size as `(cols, rows)` when the session started.
+    pub terminal_size: (u16, u16),
 }
 
 /// A PTY session that captures agent output and detects file changes.
@@ -39,18 +67,22 @@ pub struct PtySession {
     config: PtyWrapperConfig,
     file_snapshot_before: HashMap<PathBuf, Vec<u8>>,
     start_time: DateTime<Utc>,
+    git_state: GitState,
 }
 
 impl PtySession {
-    /// Start a new PTY session: snapshot the working tree.
+    /// Start a new PTY session: record the branch and snapshot the working tree.
     pub fn start(config: PtyWrapperConfig) -> Result<Self, CaptureError> {
-        let snapshot = snapshot_working_tree(&config.working_dir)
+        let git_state = GitState::capture(&config.working_dir);
+
+        let snapshot = snapshot_working_tree(&config.working_dir, &config.snapshot)
             .map_err(|e| CaptureError::Pty(format!("Failed to snapshot working tree: {e}")))?;
 
         Ok(Self {
             config,
             file_snapshot_before: snapshot,
             start_time: Utc::now(),
+            git_state,
         })
     }
 
@@ -60,15 +92,10 @@ impl PtySession {
         let pty_system = native_pty_system();
 
         // Get terminal size from current terminal, fall back to defaults
-        let (cols, rows) = terminal_size().unwrap_or((80, 24));
+        let initial_size = terminal_size();
 
         let pair = pty_system
-            .openpty(PtySize {
-                rows,
-                cols,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
+            .openpty(pty_size(initial_size))
             .map_err(|e| CaptureError::Pty(format!("Failed to open PTY: {e}")))?;
 
         // Build the command
@@ -85,39 +112,38 @@ impl PtySession {
         // Drop the slave to avoid hanging
         drop(pair.slave);
 
-        // Set up capture buffer
-        let capture_buffer = Arc::new(Mutex::new(Vec::new()));
+        // Ctrl-C / SIGTERM go to the child instead of killing the wrapper
+        let forwarder = SignalForwarder::install(child.process_id());
+
+        // Enforce the timeout and output limit from a separate thread, since
+        // this one blocks in wait()
+        let output_len = Arc::new(AtomicU64::new(0));
+        let monitor = LimitMonitor::spawn(
+            self.config.timeout,
+            self.config.max_output_bytes,
+            Arc::clone(&output_len),
+            child.process_id(),
+            child.clone_killer(),
+        );
 
         // Get reader/writer from master
-        let mut reader = pair
+        let reader = pair
             .master
             .try_clone_reader()
             .map_err(|e| CaptureError::Pty(format!("Failed to clone PTY reader: {e}")))?;
+        let reader = MeteredReader::new(reader, output_len);
         let mut writer = pair
             .master
             .take_writer()
             .map_err(|e| CaptureError::Pty(format!("Failed to take PTY writer: {e}")))?;
 
-        // Reader thread: PTY output -> stdout + capture buffer
-        let buf_clone = Arc::clone(&capture_buffer);
-        let reader_handle = std::thread::spawn(move || {
-            let mut buf = [0u8; 4096];
-            loop {
-                match reader.read(&mut buf) {
-                    Ok(0) => break,
-                    Ok(n) => {
-                        // Pass through to stdout
-                        let _ = std::io::stdout().write_all(&buf[..n]);
-                        let _ = std::io::stdout().flush();
-                        // Capture
-                        if let Ok(mut capture) = buf_clone.lock() {
-                            capture.extend_from_slice(&buf[..n]);
-                        }
-                    }
-                    Err(_) => break,
-                }
-            }
-        });
+        // Follow terminal resizes for the lifetime of the child
+        let resize_watcher = ResizeWatcher::spawn(pair.master, initial_size);
+
+        // Reader thread: PTY output -> stdout + capture buffer (spilling to disk past the cap)
+        let max_buffer_bytes = self.config.max_buffer_bytes;
+        let reader_handle =
+            std::thread::spawn(move || capture_stream(reader, std::io::stdout(), max_buffer_bytes));
 
         // Shutdown flag so we can signal the writer thread to stop
         let shutdown = Arc::new(AtomicBool::new(false));
@@ -146,54 +172,97 @@ impl PtySession {
         let status = child
             .wait()
             .map_err(|e| CaptureError::Pty(format!("Failed to wait for child: {e}")))?;
+        let interrupted = forwarder.finish();
+        let limit_exceeded = monitor.finish();
+
+        // Release the master before draining the reader: ConPTY only signals
+        // EOF on the output pipe once the pseudoconsole is closed.
+        drop(resize_watcher);
 
-        // Wait for reader to finish
-        let _ = reader_handle.join();
+        // Wait for reader to finish and collect captured output
+        let capture = reader_handle
+            .join()
+            .map_err(|_| CaptureError::Pty("PTY reader thread panicked".into()))?;
 
-        // Signal writer to stop and give it a brief moment to exit.
-        // The writer thread may be blocked on stdin.read(), which we cannot
-        // interrupt portably. We accept that it may linger until the process exits.
+        // Signal the writer to stop. It may be blocked on stdin.read() (a console
+        // read on Windows blocks until a line is entered), which we cannot
+        // interrupt portably, so it is detached rather than joined and
+        // lingers until the process exits.
         shutdown.store(true, Ordering::Relaxed);
-        let _ = writer_handle.join();
+        drop(writer_handle);
 
         let end_time = Utc::now();
         let exit_code = Some(status.exit_code());
+        #[cfg(unix)]
+        let signal = status.signal().and_then(super::signals::signal_number);
+        #[cfg(not(unix))]
+        let signal = None;
 
         // Detect file changes
-        let snapshot_after = snapshot_working_tree(&self.config.working_dir)
+        let snapshot_after = snapshot_working_tree(&self.config.working_dir, &self.config.snapshot)
             .map_err(|e| CaptureError::Pty(format!("Failed to snapshot working tree: {e}")))?;
         let file_changes = detect_changes(&self.file_snapshot_before, &snapshot_after);
 
-        // Collect captured output
-        let raw_output = capture_buffer
-            .lock()
-            .map(|buf| buf.clone())
-            .unwrap_or_default();
-
         Ok(CapturedSession {
-            raw_output,
+            raw_output: capture.output,
+            chunks: capture.chunks,
             start_time: self.start_time,
             end_time,
             exit_code,
+            signal,
             file_changes,
             command: self.config.command,
             args: self.config.args,
+            interrupted,
+            limit_exceeded,
+            output_truncated: capture.truncated,
+            git_state: self.git_state,
+            terminal_size: initial_size,
         })
     }
 }
 
-/// Try to get the current terminal size from environment variables.
-fn terminal_size() -> Option<(u16, u16)> {
-    // Try COLUMNS and LINES env vars (set by many terminals)
-    let cols = std::env::var("COLUMNS")
-        .ok()
-        .and_then(|v| v.parse::<u16>().ok());
-    let rows = std::env::var("LINES")
-        .ok()
-        .and_then(|v| v.parse::<u16>().ok());
-
-    match (cols, rows) {
-        (Some(c), Some(r)) if c > 0 && r > 0 => Some((c, r)),
-        _ => None,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pty::DEFAULT_MAX_BUFFER_BYTES;
+    use tempfile::TempDir;
+
+    fn capture(command: &str, args: &[&str]) -> CapturedSession {
+        let tmp = TempDir::new().unwrap();
+        let config = PtyWrapperConfig {
+            command: command.into(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            working_dir: tmp.path().to_path_buf(),
+            agent_name: None,
+            max_buffer_bytes: DEFAULT_MAX_BUFFER_BYTES,
+            snapshot: SnapshotOptions::default(),
+            timeout: None,
+            max_output_bytes: None,
+        };
+        PtySession::start(config).unwrap().run().unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_captures_output_and_exit_code() {
+        let captured = capture("sh", &["-c", "echo hello; exit 3"]);
+        assert!(String::from_utf8_lossy(&captured.raw_output.to_vec().unwrap()).contains("hello"));
+        assert_eq!(captured.exit_code, Some(3));
+        assert_eq!(captured.signal, None);
+
+        let killed = capture("sh", &["-c", "kill -TERM $$"]);
+        assert_eq!(killed.signal, Some(15));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_run_captures_output_and_exit_code() {
+        let captured = capture("cmd", &["/c", "echo hello"]);
+        assert!(String::from_utf8_lossy(&captured.raw_output.to_vec().unwrap()).contains("hello"));
+        assert_eq!(captured.exit_code, Some(0));
+
+        let failed = capture("cmd", &["/c", "exit 3"]);
+        assert_eq!(failed.exit_code, Some(3));
     }
 }
@@ -0,0 +1,179 @@
+//! Reduce raw terminal output to the plain text a user would have read.
+//!
+//! This is a small terminal state machine rather than a regex: TUIs move the
+//! cursor, rewrite lines with carriage returns, and switch to the alternate
+//! screen, all of which affect which characters end up as text.
+
+/// Strip ANSI escape sequences and apply line-editing control characters.
+///
+/// - CSI, OSC, DCS and other escape sequences are removed.
+/// - `\r` returns to the start of the line so later text overwrites it
+///   (progress bars keep only their final state); `\r\n` is a plain newline.
+/// - Backspace moves the cursor left; erase-in-line (`CSI K`) truncates.
+/// - Cursor positioning and alternate-screen switches start a new line so
+///   text drawn at different positions does not run together.
+/// - Trailing whitespace is trimmed and runs of blank lines collapsed.
+pub fn strip_ansi(raw: &[u8]) -> String {
+    let text = String::from_utf8_lossy(raw);
+    let mut screen = Screen::default();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut final_byte = None;
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            final_byte = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+                    if let Some(f) = final_byte {
+                        screen.csi(&params, f);
+                    }
+                }
+                // OSC, DCS, SOS, PM, APC: skip to BEL or ST (ESC \)
+                Some(']' | 'P' | 'X' | '^' | '_') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Charset designation and similar: ESC, intermediates, final
+                Some(c) if ('\x20'..='\x2f').contains(&c) => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                    screen.newline();
+                } else {
+                    screen.col = 0;
+                }
+            }
+            '\n' => screen.newline(),
+            '\x08' => screen.col = screen.col.saturating_sub(1),
+            '\t' => screen.put('\t'),
+            c if c.is_control() => {}
+            c => screen.put(c),
+        }
+    }
+
+    screen.finish()
+}
+
+#[derive(Default)]
+struct Screen {
+    lines: Vec<String>,
+    line: Vec<char>,
+    col: usize,
+}
+
+impl Screen {
+    fn put(&mut self, c: char) {
+        if self.col < self.line.len() {
+            self.line[self.col] = c;
+        } else {
+            self.line.resize(self.col, ' ');
+            self.line.push(c);
+        }
+        self.col += 1;
+    }
+
+    fn newline(&mut self) {
+        let line: String = self.line.drain(..).collect();
+        let line = line.trim_end().to_string();
+        let blank = line.is_empty();
+        if !(blank && self.lines.last().is_some_and(|l| l.is_empty())) {
+            self.lines.push(line);
+        }
+        self.col = 0;
+    }
+
+    /// Start a new line unless the current one is empty.
+    fn break_line(&mut self) {
+        if !self.line.is_empty() {
+            self.newline();
+        }
+        self.col = 0;
+    }
+
+    fn csi(&mut self, params: &str, final_byte: char) {
+        match final_byte {
+            // Erase in line: 0 (default) = to end, 1 = to start, 2 = whole line
+            'K' => match params {
+                "" | "0" => self.line.truncate(self.col),
+                "1" => {
+                    let end = self.col.min(self.line.len());
+                    self.line[..end].iter_mut().for_each(|c| *c = ' ');
+                }
+                _ => self.line.clear(),
+            },
+            // Cursor horizontal absolute
+            'G' => self.col = params.parse::<usize>().unwrap_or(1).saturating_sub(1),
+            // Cursor forward / back
+            'C' => self.col += params.parse::<usize>().unwrap_or(1),
+            'D' => self.col = self.col.saturating_sub(params.parse().unwrap_or(1)),
+            // Cursor position, vertical moves, screen erase, mode switches
+            // (including the alternate screen, `?1049h`/`?1049l`)
+            'H' | 'f' | 'A' | 'B' | 'E' | 'F' | 'd' | 'J' | 'h' | 'l' => self.break_line(),
+            // SGR colors and everything else carry no text
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.break_line();
+        while self.lines.first().is_some_and(|l| l.is_empty()) {
+            self.lines.remove(0);
+        }
+        while self.lines.last().is_some_and(|l| l.is_empty()) {
+            self.lines.pop();
+        }
+        self.lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_colors() {
+        let raw = b"\x1b[1;32m\xe2\x9c\x93 Tests passed\x1b[0m\r\n\x1b[31merror\x1b[m: boom\n";
+        assert_eq!(strip_ansi(raw), "\u{2713} Tests passed\nerror: boom");
+    }
+
+    #[test]
+    fn test_carriage_return_progress_bar() {
+        let raw = b"Downloading  10%\rDownloading  55%\rDownloading 100%\ndone\n";
+        assert_eq!(strip_ansi(raw), "Downloading 100%\ndone");
+
+        // Spinner that erases the line before redrawing a shorter status
+        let raw = b"\xe2\xa0\x8b Thinking...\r\x1b[2K\r\x1b[KOK\n";
+        assert_eq!(strip_ansi(raw), "OK");
+    }
+
+    #[test]
+    fn test_alternate_screen_and_cursor_addressing() {
+        let raw = b"before\n\x1b[?1049h\x1b[H\x1b[2J\x1b[1;1Htitle\x1b[3;5Hbody text\
+\x1b]0;window title\x07\x1b[?1049lafter\n";
+        assert_eq!(strip_ansi(raw), "before\ntitle\nbody text\nafter");
+    }
+
+    #[test]
+    fn test_blank_lines_collapsed_and_charset_sequences_removed() {
+        let raw = b"\n\n\x1b(Bone\n\n\n\ntwo   \n\n";
+        assert_eq!(strip_ansi(raw), "one\n\ntwo");
+    }
+}
@@ -1,9 +1,43 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use engram_core::model::*;
 use engram_core::storage::GitStorage;
 
-use super::extractor::extract_insights;
+use super::ansi::strip_ansi;
+use super::cast::asciicast;
+use super::extractor::{HeuristicExtractor, InsightExtractor, InsightPatterns};
+use super::usage::UsageParsers;
 use crate::error::CaptureError;
-use crate::pty::CapturedSession;
+use crate::pty::{CapturedOutput, CapturedSession, OutputChunk, SessionLimit};
+
+/// Tag applied to engrams whose recording was cut short by a signal.
+pub const INTERRUPTED_TAG: &str = "interrupted";
+
+/// Tag applied to engrams whose session was stopped by its timeout.
+pub const TIMED_OUT_TAG: &str = "timed_out";
+
+/// Tag applied to engrams whose session was stopped for printing too much output.
+pub const OUTPUT_LIMIT_TAG: &str = "output_limit";
+
+/// Tag applied to engrams whose output could only be partly stored.
+pub const OUTPUT_TRUNCATED_TAG: &str = "output_truncated";
+
+/// Bytes of PTY output (head and tail) kept in the transcript and scanned for insights.
+const OUTPUT_WINDOW_BYTES: usize = 2 * 1024 * 1024;
+
+/// Default pause in output that starts a new transcript entry.
+pub const DEFAULT_IDLE_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// Maximum characters of cleaned output per transcript entry.
+const TRANSCRIPT_CHUNK_CHARS: usize = 4000;
+
+/// Attachment name for the unprocessed PTY bytes (see [`SessionBuilder::with_raw_output`]).
+pub const RAW_OUTPUT_ATTACHMENT: &str = "pty-output.raw";
+
+/// Attachment name for the asciicast replay (see [`SessionBuilder::with_cast`]).
+pub const CAST_ATTACHMENT: &str = "session.cast";
 
 /// Builds an EngramData from a CapturedSession.
 pub struct SessionBuilder {
@@ -11,6 +45,11 @@ pub struct SessionBuilder {
     captured: CapturedSession,
     git_commits: Vec<String>,
     parent_engram: Option<EngramId>,
+    keep_raw_output: bool,
+    keep_cast: bool,
+    usage_parsers: UsageParsers,
+    extractor: Box<dyn InsightExtractor>,
+    idle_threshold: Duration,
 }
 
 impl SessionBuilder {
@@ -20,6 +59,11 @@ impl SessionBuilder {
             captured,
             git_commits: Vec::new(),
             parent_engram: None,
+            keep_raw_output: false,
+            keep_cast: false,
+            usage_parsers: UsageParsers::with_builtins(),
+            extractor: Box::new(HeuristicExtractor::default()),
+            idle_threshold: DEFAULT_IDLE_THRESHOLD,
         }
     }
 
@@ -35,10 +79,63 @@ impl SessionBuilder {
         self
     }
 
+    /// Store the unprocessed PTY bytes as an attachment alongside the cleaned transcript.
+    pub fn with_raw_output(mut self, keep: bool) -> Self {
+        self.keep_raw_output = keep;
+        self
+    }
+
+    /// Store an asciicast v2 recording of the output as an attachment.
+    pub fn with_cast(mut self, keep: bool) -> Self {
+        self.keep_cast = keep;
+        self
+    }
+
+    /// Replace the parsers used to read token usage and model from the output.
+    pub fn with_usage_parsers(mut self, parsers: UsageParsers) -> Self {
+        self.usage_parsers = parsers;
+        self
+    }
+
+    /// Add patterns for dead ends and decisions beyond the built-in phrases.
+    pub fn with_insight_patterns(self, patterns: InsightPatterns) -> Self {
+        self.with_extractor(Box::new(HeuristicExtractor::new(patterns)))
+    }
+
+    /// Replace how dead ends, decisions and the interpreted goal are extracted
+    /// (see [`extractor_from_config`](super::extractor::extractor_from_config)).
+    pub fn with_extractor(mut self, extractor: Box<dyn InsightExtractor>) -> Self {
+        self.extractor = extractor;
+        self
+    }
+
+    /// Set how long output must pause before a new transcript entry begins.
+    pub fn with_idle_threshold(mut self, threshold: Duration) -> Self {
+        self.idle_threshold = threshold;
+        self
+    }
+
     /// Build the EngramData.
-    pub fn build(self) -> EngramData {
+    pub fn build(mut self) -> EngramData {
         let id = EngramId::new();
 
+        // Work from a bounded window of the output so huge sessions are not
+        // read back whole, with terminal control sequences removed
+        let window = self
+            .captured
+            .raw_output
+            .window(OUTPUT_WINDOW_BYTES)
+            .unwrap_or_default();
+        let output_text = strip_ansi(&window);
+
+        // Token usage and model as reported by the agent itself, if recognized
+        let parsed_usage = self
+            .usage_parsers
+            .parse(&self.agent_info.name, &output_text);
+        if self.agent_info.model.is_none() {
+            self.agent_info.model = parsed_usage.model;
+        }
+
         // Extract intent from the command + args
         let original_request = if self.captured.args.is_empty() {
             self.captured.command.clone()
@@ -63,6 +160,20 @@ impl SessionBuilder {
             ))
         };
 
+        // Record why a session ended early
+        let mut tags = Vec::new();
+        if self.captured.interrupted {
+            tags.push(INTERRUPTED_TAG.to_string());
+        }
+        match self.captured.limit_exceeded {
+            Some(SessionLimit::Timeout) => tags.push(TIMED_OUT_TAG.to_string()),
+            Some(SessionLimit::MaxOutput) => tags.push(OUTPUT_LIMIT_TAG.to_string()),
+            None => {}
+        }
+        if self.captured.output_truncated {
+            tags.push(OUTPUT_TRUNCATED_TAG.to_string());
+        }
+
         let manifest = Manifest {
             id,
             version: 1,
@@ -70,39 +181,89 @@ impl SessionBuilder {
             finished_at: Some(self.captured.end_time),
             agent: self.agent_info,
             git_commits: self.git_commits.clone(),
-            token_usage: TokenUsage::default(), // PTY capture doesn't know token usage
+            token_usage: parsed_usage.token_usage.unwrap_or_default(),
             summary,
-            tags: Vec::new(),
+            tags,
             capture_mode: CaptureMode::Wrapper,
             source_hash: None,
+            source_path: None,
         };
 
-        // Best-effort extraction of dead ends and decisions from raw output
-        let insights = extract_insights(&self.captured.raw_output);
+        // Best-effort extraction of dead ends, decisions and the goal
+        let insights = self.extractor.extract(&output_text);
 
         let intent = Intent {
             original_request,
-            interpreted_goal: None,
+            interpreted_goal: insights.interpreted_goal,
             summary: manifest.summary.clone(),
             dead_ends: insights.dead_ends,
             decisions: insights.decisions,
         };
 
-        // Build transcript from raw output
-        let transcript = Transcript {
-            entries: vec![TranscriptEntry {
-                timestamp: self.captured.start_time,
-                role: Role::System,
-                content: TranscriptContent::Text {
-                    text: format!(
-                        "PTY session: {} {}",
-                        self.captured.command,
-                        self.captured.args.join(" ")
-                    ),
-                },
-                token_count: None,
-            }],
+        // Build transcript: a header entry, then the cleaned agent output
+        let mut entries = vec![TranscriptEntry {
+            timestamp: self.captured.start_time,
+            role: Role::System,
+            content: TranscriptContent::Text {
+                text: format!(
+                    "PTY session: {} {}",
+                    self.captured.command,
+                    self.captured.args.join(" ")
+                ),
+            },
+            token_count: None,
+        }];
+
+        // One group of entries per burst of activity when the timing of the
+        // whole output is known; otherwise a single group at session start
+        let fits_window = self.captured.raw_output.len() <= OUTPUT_WINDOW_BYTES as u64;
+        let segments = if fits_window {
+            segment_chunks(&self.captured.chunks, self.idle_threshold)
+        } else {
+            Vec::new()
         };
+        let timeline: Vec<(DateTime<Utc>, String)> = if segments.is_empty() {
+            vec![(self.captured.start_time, output_text.clone())]
+        } else {
+            segments
+                .into_iter()
+                .filter_map(|(at, range)| {
+                    let bytes = window.get(range.start as usize..range.end as usize)?;
+                    Some((at, strip_ansi(bytes)))
+                })
+                .collect()
+        };
+        for (timestamp, text) in timeline {
+            entries.extend(
+                chunk_lines(&text, TRANSCRIPT_CHUNK_CHARS)
+                    .into_iter()
+                    .map(|text| TranscriptEntry {
+                        timestamp,
+                        role: Role::Assistant,
+                        content: TranscriptContent::Text { text },
+                        token_count: None,
+                    }),
+            );
+        }
+        let transcript = Transcript { entries };
+
+        let mut attachments = Vec::new();
+        if self.keep_raw_output {
+            attachments.push(match &self.captured.raw_output {
+                CapturedOutput::InMemory(raw) => {
+                    Attachment::new(RAW_OUTPUT_ATTACHMENT, raw.clone())
+                }
+                CapturedOutput::Spooled { path, .. } => {
+                    Attachment::from_file(RAW_OUTPUT_ATTACHMENT, path.clone())
+                }
+            });
+        }
+        if self.keep_cast {
+            match asciicast(&self.captured) {
+                Ok(cast) => attachments.push(Attachment::new(CAST_ATTACHMENT, cast)),
+                Err(e) => tracing::warn!("Failed to write asciicast: {e}"),
+            }
+        }
 
         let operations = Operations {
             tool_calls: Vec::new(),
@@ -120,7 +281,8 @@ impl SessionBuilder {
         let lineage = Lineage {
             parent_engram: self.parent_engram,
             git_commits: self.git_commits,
-            branch: None, // Could detect from git HEAD
+            branch: self.captured.git_state.branch.clone(),
+            workdir_dirty_at_start: self.captured.git_state.dirty,
             ..Default::default()
         };
 
@@ -130,6 +292,7 @@ impl SessionBuilder {
             transcript,
             operations,
             lineage,
+            attachments,
         }
     }
 
@@ -141,17 +304,69 @@ impl SessionBuilder {
     }
 }
 
+/// Merge output chunks separated by less than `idle` into segments, each
+/// stamped with its first chunk's start time.
+fn segment_chunks(chunks: &[OutputChunk], idle: Duration) -> Vec<(DateTime<Utc>, Range<u64>)> {
+    let idle = chrono::Duration::from_std(idle).unwrap_or(chrono::Duration::MAX);
+    let mut segments: Vec<(DateTime<Utc>, Range<u64>)> = Vec::new();
+    let mut last_end: Option<DateTime<Utc>> = None;
+    for chunk in chunks {
+        match (segments.last_mut(), last_end) {
+            (Some((_, range)), Some(prev)) if chunk.started_at - prev < idle => {
+                range.end = chunk.end();
+            }
+            _ => segments.push((chunk.started_at, chunk.offset..chunk.end())),
+        }
+        last_end = Some(chunk.ended_at);
+    }
+    segments
+}
+
+/// Split text into chunks of at most `max_chars`, breaking at line boundaries
+/// where possible.
+fn chunk_lines(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        if !current.is_empty() && current.chars().count() + line.chars().count() > max_chars {
+            chunks.push(std::mem::take(&mut current).trim_end().to_string());
+        }
+        // A single over-long line is hard-wrapped
+        let mut rest = line;
+        while rest.chars().count() > max_chars {
+            let split = rest
+                .char_indices()
+                .nth(max_chars)
+                .map(|(i, _)| i)
+                .unwrap_or(rest.len());
+            chunks.push(rest[..split].to_string());
+            rest = &rest[split..];
+        }
+        current.push_str(rest);
+        current.push('\n');
+    }
+    let last = current.trim_end();
+    if !last.is_empty() {
+        chunks.push(last.to_string());
+    }
+    chunks.retain(|c| !c.trim().is_empty());
+    chunks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pty::GitState;
     use chrono::Utc;
 
     fn mock_captured_session() -> CapturedSession {
         CapturedSession {
-            raw_output: b"hello world\n".to_vec(),
+            raw_output: b"hello world\n".to_vec().into(),
+            chunks: Vec::new(),
             start_time: Utc::now(),
             end_time: Utc::now(),
             exit_code: Some(0),
+            signal: None,
             file_changes: vec![FileChange {
                 path: "src/main.rs".into(),
                 change_type: FileChangeType::Modified,
@@ -160,6 +375,11 @@ mod tests {
             }],
             command: "claude".into(),
             args: vec!["add auth".into()],
+            interrupted: false,
+            limit_exceeded: None,
+            output_truncated: false,
+            git_state: Default::default(),
+            terminal_size: (80, 24),
         }
     }
 
@@ -182,5 +402,252 @@ mod tests {
         assert_eq!(data.operations.file_changes.len(), 1);
         assert_eq!(data.operations.shell_commands.len(), 1);
         assert_eq!(data.lineage.git_commits, vec!["abc123".to_string()]);
+        assert_eq!(data.lineage.branch, None);
+    }
+
+    #[test]
+    fn test_git_state_flows_into_lineage() {
+        let agent = AgentInfo {
+            name: "claude-code".into(),
+            model: None,
+            version: None,
+        };
+        let mut captured = mock_captured_session();
+        captured.git_state = GitState {
+            branch: Some("feature/auth".into()),
+            dirty: true,
+        };
+
+        let data = SessionBuilder::new(agent, captured).build();
+        assert_eq!(data.lineage.branch.as_deref(), Some("feature/auth"));
+        assert!(data.lineage.workdir_dirty_at_start);
+    }
+
+    #[test]
+    fn test_early_exit_tags() {
+        let agent = AgentInfo {
+            name: "claude-code".into(),
+            model: None,
+            version: None,
+        };
+        let mut captured = mock_captured_session();
+        assert!(SessionBuilder::new(agent.clone(), captured.clone())
+            .build()
+            .manifest
+            .tags
+            .is_empty());
+
+        captured.interrupted = true;
+        let data = SessionBuilder::new(agent.clone(), captured.clone()).build();
+        assert_eq!(data.manifest.tags, vec![INTERRUPTED_TAG.to_string()]);
+
+        captured.interrupted = false;
+        captured.limit_exceeded = Some(SessionLimit::Timeout);
+        let data = SessionBuilder::new(agent.clone(), captured.clone()).build();
+        assert_eq!(data.manifest.tags, vec![TIMED_OUT_TAG.to_string()]);
+
+        captured.limit_exceeded = Some(SessionLimit::MaxOutput);
+        let data = SessionBuilder::new(agent.clone(), captured.clone()).build();
+        assert_eq!(data.manifest.tags, vec![OUTPUT_LIMIT_TAG.to_string()]);
+
+        captured.limit_exceeded = None;
+        captured.output_truncated = true;
+        let data = SessionBuilder::new(agent, captured).build();
+        assert_eq!(data.manifest.tags, vec![OUTPUT_TRUNCATED_TAG.to_string()]);
+    }
+
+    #[test]
+    fn test_transcript_contains_cleaned_output() {
+        let agent = AgentInfo {
+            name: "claude-code".into(),
+            model: None,
+            version: None,
+        };
+        let mut captured = mock_captured_session();
+        captured.raw_output =
+            b"\x1b[1mPlan\x1b[0m\r\nworking  5%\rworking 100%\r\n\x1b[32mDone\x1b[0m\r\n"
+                .to_vec()
+                .into();
+
+        let data = SessionBuilder::new(agent.clone(), captured.clone()).build();
+        assert_eq!(data.transcript.entries.len(), 2);
+        assert_eq!(data.transcript.entries[0].role, Role::System);
+        assert_eq!(data.transcript.entries[1].role, Role::Assistant);
+        assert_eq!(
+            data.transcript.entries[1].content,
+            TranscriptContent::Text {
+                text: "Plan\nworking 100%\nDone".into()
+            }
+        );
+        assert!(data.attachments.is_empty());
+
+        let data = SessionBuilder::new(agent.clone(), captured.clone())
+            .with_raw_output(true)
+            .build();
+        assert_eq!(data.attachments.len(), 1);
+        assert_eq!(data.attachments[0].name, RAW_OUTPUT_ATTACHMENT);
+        assert_eq!(
+            data.attachments[0].bytes().unwrap(),
+            captured.raw_output.to_vec().unwrap()
+        );
+
+        let data = SessionBuilder::new(agent, captured.clone())
+            .with_raw_output(true)
+            .with_cast(true)
+            .build();
+        let names: Vec<_> = data.attachments.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec![RAW_OUTPUT_ATTACHMENT, CAST_ATTACHMENT]);
+    }
+
+    #[test]
+    fn test_spooled_output_is_stored_from_disk() {
+        let spool = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(spool.path(), b"spooled output\n").unwrap();
+        let mut captured = mock_captured_session();
+        captured.raw_output = CapturedOutput::Spooled {
+            path: std::sync::Arc::new(spool.into_temp_path()),
+            total_len: 15,
+        };
+        let agent = AgentInfo {
+            name: "claude-code".into(),
+            model: None,
+            version: None,
+        };
+        let builder = SessionBuilder::new(agent, captured).with_raw_output(true);
+        let data = builder.build();
+        assert!(matches!(data.attachments[0].data, AttachmentData::File(_)));
+
+        let dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(dir.path()).unwrap();
+        let storage = GitStorage::open(dir.path()).unwrap();
+        let id = storage.create(&data).unwrap();
+        assert_eq!(
+            storage
+                .read_attachment(id.as_str(), RAW_OUTPUT_ATTACHMENT)
+                .unwrap(),
+            Some(b"spooled output\n".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_custom_extractor_sets_goal_and_insights() {
+        use crate::session::extractor::ExtractedInsights;
+
+        struct Fixed;
+        impl InsightExtractor for Fixed {
+            fn extract(&self, output: &str) -> ExtractedInsights {
+                ExtractedInsights {
+                    decisions: vec![Decision {
+                        description: "keep it".into(),
+                        rationale: output.lines().next().unwrap_or_default().into(),
+                    }],
+                    interpreted_goal: Some("greet the world".into()),
+                    ..Default::default()
+                }
+            }
+        }
+
+        let agent = AgentInfo {
+            name: "claude-code".into(),
+            model: None,
+            version: None,
+        };
+        let data = SessionBuilder::new(agent, mock_captured_session())
+            .with_extractor(Box::new(Fixed))
+            .build();
+        assert_eq!(
+            data.intent.interpreted_goal.as_deref(),
+            Some("greet the world")
+        );
+        assert_eq!(data.intent.decisions[0].rationale, "hello world");
+    }
+
+    #[test]
+    fn test_chunk_lines() {
+        let text = "aaaa\nbbbb\ncccc";
+        assert_eq!(chunk_lines(text, 9), vec!["aaaa\nbbbb", "cccc"]);
+        assert_eq!(chunk_lines("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert!(chunk_lines("", 10).is_empty());
+    }
+
+    #[test]
+    fn test_usage_parsed_from_output() {
+        let agent = AgentInfo {
+            name: "aider".into(),
+            model: None,
+            version: None,
+        };
+        let mut captured = mock_captured_session();
+        captured.raw_output = b"Main model: \x1b[1mgpt-4o\x1b[0m with diff edit format\r\n\
+Tokens: 1.5k sent, 100 received. Cost: $0.01 message, $0.01 session.\r\n"
+            .to_vec()
+            .into();
+
+        let data = SessionBuilder::new(agent, captured).build();
+        assert_eq!(data.manifest.agent.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(data.manifest.token_usage.total_tokens, 1600);
+        assert_eq!(data.manifest.token_usage.cost_usd, Some(0.01));
+    }
+
+    fn chunk(start_ms: i64, end_ms: i64, offset: u64, len: u64) -> OutputChunk {
+        let t0 = DateTime::<Utc>::UNIX_EPOCH;
+        OutputChunk {
+            started_at: t0 + chrono::Duration::milliseconds(start_ms),
+            ended_at: t0 + chrono::Duration::milliseconds(end_ms),
+            offset,
+            len,
+        }
+    }
+
+    #[test]
+    fn test_segment_chunks_by_idle_gap() {
+        let chunks = [
+            chunk(0, 500, 0, 10),
+            chunk(1_500, 1_600, 10, 5), // 1.0s after the previous chunk ended
+            chunk(4_000, 4_100, 15, 8), // 2.4s gap
+            chunk(9_000, 9_000, 23, 2), // 4.9s gap
+        ];
+        let segments = segment_chunks(&chunks, Duration::from_secs(2));
+        let ranges: Vec<_> = segments.iter().map(|(_, r)| r.clone()).collect();
+        assert_eq!(ranges, vec![0..15, 15..23, 23..25]);
+        assert_eq!(segments[1].0, chunks[2].started_at);
+
+        // A longer threshold merges everything but the last burst
+        let segments = segment_chunks(&chunks, Duration::from_secs(3));
+        assert_eq!(segments.len(), 2);
+        assert!(segment_chunks(&[], Duration::from_secs(2)).is_empty());
+    }
+
+    #[test]
+    fn test_transcript_follows_output_timeline() {
+        let agent = AgentInfo {
+            name: "claude-code".into(),
+            model: None,
+            version: None,
+        };
+        let mut captured = mock_captured_session();
+        captured.raw_output = b"\x1b[32mthinking\x1b[0m\r\nplan ready\r\nwrote file\r\n"
+            .to_vec()
+            .into();
+        captured.chunks = vec![chunk(0, 10, 0, 30), chunk(5_000, 5_000, 30, 12)];
+
+        let data = SessionBuilder::new(agent, captured.clone()).build();
+        let texts: Vec<_> = data.transcript.entries[1..]
+            .iter()
+            .map(|e| match &e.content {
+                TranscriptContent::Text { text } => (e.timestamp, text.clone()),
+                other => panic!("unexpected content {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (
+                    captured.chunks[0].started_at,
+                    "thinking\nplan ready".to_string()
+                ),
+                (captured.chunks[1].started_at, "wrote file".to_string()),
+            ]
+        );
     }
 }
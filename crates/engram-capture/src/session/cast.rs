@@ -0,0 +1,214 @@
+//! Export a recorded PTY session as an [asciicast v2] file for replay with
+//! `asciinema play`.
+//!
+//! [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use std::io::{self, Read, Write};
+
+use serde_json::json;
+
+use crate::pty::CapturedSession;
+
+/// Write `session` as asciicast v2: a JSON header line followed by one
+/// `[seconds, "o", text]` event line per output chunk.
+///
+/// Output is streamed from the capture buffer (or its spill file), so large
+/// sessions are not loaded whole. Sessions without chunk timings, and any
+/// output past the last chunk, are emitted as a single event.
+pub fn write_asciicast(session: &CapturedSession, mut out: impl Write) -> io::Result<()> {
+    let (width, height) = session.terminal_size;
+    let command = if session.args.is_empty() {
+        session.command.clone()
+    } else {
+        format!("{} {}", session.command, session.args.join(" "))
+    };
+    let header = json!({
+        "version": 2,
+        "width": width,
+        "height": height,
+        "timestamp": session.start_time.timestamp(),
+        "duration": seconds_since_start(session, session.end_time),
+        "command": command,
+    });
+    writeln!(out, "{header}")?;
+
+    let mut reader = session.raw_output.reader()?;
+    let mut decoder = Utf8Carry::default();
+    let mut last_time = 0.0_f64;
+    let mut consumed = 0u64;
+
+    for chunk in &session.chunks {
+        let mut bytes = Vec::with_capacity(chunk.len as usize);
+        (&mut reader).take(chunk.len).read_to_end(&mut bytes)?;
+        consumed += bytes.len() as u64;
+
+        // Chunks are in arrival order, but clamp in case the clock stepped back
+        last_time = last_time.max(seconds_since_start(session, chunk.started_at));
+        write_event(&mut out, last_time, &decoder.decode(&bytes))?;
+    }
+
+    if consumed < session.raw_output.len() {
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest)?;
+        write_event(&mut out, last_time, &decoder.decode(&rest))?;
+    }
+    let tail = decoder.finish();
+    if !tail.is_empty() {
+        write_event(&mut out, last_time, &tail)?;
+    }
+    Ok(())
+}
+
+/// [`write_asciicast`] into a buffer.
+pub fn asciicast(session: &CapturedSession) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    write_asciicast(session, &mut buf)?;
+    Ok(buf)
+}
+
+fn seconds_since_start(session: &CapturedSession, at: chrono::DateTime<chrono::Utc>) -> f64 {
+    let micros = (at - session.start_time)
+        .num_microseconds()
+        .unwrap_or(0)
+        .max(0);
+    micros as f64 / 1_000_000.0
+}
+
+fn write_event(out: &mut impl Write, time: f64, text: &str) -> io::Result<()> {
+    if text.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "{}", json!([time, "o", text]))
+}
+
+/// Decodes UTF-8 across chunk boundaries, holding back a trailing partial
+/// character until the next chunk completes it.
+#[derive(Default)]
+struct Utf8Carry {
+    pending: Vec<u8>,
+}
+
+impl Utf8Carry {
+    fn decode(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let keep_from = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            // An incomplete sequence at the very end: wait for more bytes
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        let rest = self.pending.split_off(keep_from);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = rest;
+        text
+    }
+
+    fn finish(self) -> String {
+        String::from_utf8_lossy(&self.pending).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pty::{CapturedOutput, OutputChunk};
+    use chrono::{Duration, Utc};
+
+    fn session(output: CapturedOutput, reads: &[(i64, u64)]) -> CapturedSession {
+        let start = Utc::now();
+        let mut offset = 0;
+        let chunks = reads
+            .iter()
+            .map(|&(ms, len)| {
+                let chunk = OutputChunk {
+                    started_at: start + Duration::milliseconds(ms),
+                    ended_at: start + Duration::milliseconds(ms),
+                    offset,
+                    len,
+                };
+                offset += len;
+                chunk
+            })
+            .collect();
+        CapturedSession {
+            raw_output: output,
+            chunks,
+            start_time: start,
+            end_time: start + Duration::seconds(3),
+            exit_code: Some(0),
+            signal: None,
+            file_changes: Vec::new(),
+            command: "claude".into(),
+            args: vec!["fix the build".into()],
+            interrupted: false,
+            limit_exceeded: None,
+            output_truncated: false,
+            git_state: Default::default(),
+            terminal_size: (120, 40),
+        }
+    }
+
+    fn parse(cast: &[u8]) -> (serde_json::Value, Vec<(f64, String)>) {
+        let text = String::from_utf8(cast.to_vec()).unwrap();
+        let mut lines = text.lines();
+        let header = serde_json::from_str(lines.next().unwrap()).unwrap();
+        let events = lines
+            .map(|line| {
+                let event: (f64, String, String) = serde_json::from_str(line).unwrap();
+                assert_eq!(event.1, "o");
+                (event.0, event.2)
+            })
+            .collect();
+        (header, events)
+    }
+
+    #[test]
+    fn test_header_and_monotonic_events() {
+        // "✓" (3 bytes) is split across the second and third reads, so the
+        // second read alone produces no event; the last read is stamped
+        // before its predecessor and must not go backwards
+        let output = b"\x1b[1mhello\x1b[0m\r\n\xe2\x9c\x93 done\r\n".to_vec();
+        let s = session(
+            output.clone().into(),
+            &[(10, 15), (900, 2), (1200, 4), (700, 4)],
+        );
+        let (header, events) = parse(&asciicast(&s).unwrap());
+
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["width"], 120);
+        assert_eq!(header["height"], 40);
+        assert_eq!(header["command"], "claude fix the build");
+        assert_eq!(header["duration"], 3.0);
+
+        let times: Vec<f64> = events.iter().map(|(t, _)| *t).collect();
+        assert_eq!(times, vec![0.01, 1.2, 1.2]);
+        assert!(times.windows(2).all(|w| w[0] <= w[1]));
+
+        let replayed: String = events.into_iter().map(|(_, text)| text).collect();
+        assert_eq!(replayed.as_bytes(), output);
+    }
+
+    #[test]
+    fn test_untimed_and_spooled_output() {
+        let data: Vec<u8> = b"line\r\n".repeat(500);
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+        let spooled = CapturedOutput::Spooled {
+            path: std::sync::Arc::new(file.into_temp_path()),
+            total_len: data.len() as u64,
+        };
+
+        // Timings cover only part of the output; the rest follows the last chunk
+        let s = session(spooled, &[(0, 1000), (400, 1000)]);
+        let (_, events) = parse(&asciicast(&s).unwrap());
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].0, 0.4);
+        let replayed: String = events.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(replayed.as_bytes(), data);
+
+        // No chunk timings: everything is one event at the start
+        let (_, events) = parse(&asciicast(&session(data.clone().into(), &[])).unwrap());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, 0.0);
+    }
+}
@@ -1,10 +1,142 @@
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use engram_core::config::EngramConfig;
 use engram_core::model::{DeadEnd, Decision};
 
+use crate::error::CaptureError;
+
+/// Repo-relative file with additional insight patterns:
+///
+/// ```toml
+/// dead_end = ['^abandoning approach: (?P<approach>.+?)(?: - (?P<reason>.+))?$']
+/// decision = ['^Entscheidung: (?P<description>.+?)(?:, weil (?P<rationale>.+))?$']
+/// ```
+pub const INSIGHTS_FILE: &str = ".engram/insights.toml";
+
+/// User-supplied regexes, tried on each line before the built-in phrase
+/// heuristics.
+///
+/// Dead-end patterns must have an `approach` group and may have `reason`;
+/// decision patterns must have `description` and may have `rationale`. When
+/// the optional group is missing or does not participate, the whole line is
+/// used instead.
+#[derive(Debug, Clone, Default)]
+pub struct InsightPatterns {
+    dead_ends: Vec<Regex>,
+    decisions: Vec<Regex>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct InsightsFile {
+    dead_end: Vec<String>,
+    decision: Vec<String>,
+}
+
+impl InsightPatterns {
+    /// Compile patterns, rejecting invalid regexes and missing groups up front.
+    pub fn new(dead_ends: &[String], decisions: &[String]) -> Result<Self, CaptureError> {
+        Ok(Self {
+            dead_ends: compile(dead_ends, "approach")?,
+            decisions: compile(decisions, "description")?,
+        })
+    }
+
+    /// Patterns from `engram.insights.*` config plus [`INSIGHTS_FILE`] under
+    /// `repo_root`, if it exists.
+    pub fn load(repo_root: &Path, config: &EngramConfig) -> Result<Self, CaptureError> {
+        let mut dead_ends = config.insight_dead_end_patterns.clone();
+        let mut decisions = config.insight_decision_patterns.clone();
+
+        let path = repo_root.join(INSIGHTS_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                let file: InsightsFile = toml::from_str(&text).map_err(|e| {
+                    CaptureError::InvalidPattern(format!("file {}: {e}", path.display()))
+                })?;
+                dead_ends.extend(file.dead_end);
+                decisions.extend(file.decision);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        Self::new(&dead_ends, &decisions)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dead_ends.is_empty() && self.decisions.is_empty()
+    }
+
+    fn match_dead_end(&self, line: &str) -> Option<DeadEnd> {
+        let (approach, reason) = first_match(&self.dead_ends, line, "approach", "reason")?;
+        Some(DeadEnd { approach, reason })
+    }
+
+    fn match_decision(&self, line: &str) -> Option<Decision> {
+        let (description, rationale) =
+            first_match(&self.decisions, line, "description", "rationale")?;
+        Some(Decision {
+            description,
+            rationale,
+        })
+    }
+}
+
+fn compile(patterns: &[String], required_group: &str) -> Result<Vec<Regex>, CaptureError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let re = Regex::new(pattern)
+                .map_err(|e| CaptureError::InvalidPattern(format!("'{pattern}': {e}")))?;
+            if !re.capture_names().flatten().any(|n| n == required_group) {
+                return Err(CaptureError::InvalidPattern(format!(
+                    "'{pattern}': missing a (?P<{required_group}>...) group"
+                )));
+            }
+            Ok(re)
+        })
+        .collect()
+}
+
+/// The `primary` and `secondary` groups of the first pattern matching `line`.
+fn first_match(
+    patterns: &[Regex],
+    line: &str,
+    primary: &str,
+    secondary: &str,
+) -> Option<(String, String)> {
+    patterns.iter().find_map(|re| {
+        let caps = re.captures(line)?;
+        let primary = caps.name(primary)?.as_str().trim();
+        if primary.is_empty() {
+            return None;
+        }
+        let secondary = caps
+            .name(secondary)
+            .map(|m| m.as_str().trim())
+            .filter(|s| !s.is_empty())
+            .unwrap_or(line);
+        Some((primary.to_string(), secondary.to_string()))
+    })
+}
+
 /// Best-effort extraction of reasoning insights from raw PTY output.
 ///
 /// This is heuristic: it looks for common phrases that indicate rejected
 /// approaches or architectural decisions. Returns empty vecs if no patterns found.
 pub fn extract_insights(raw_output: &[u8]) -> ExtractedInsights {
+    extract_insights_with(raw_output, &InsightPatterns::default())
+}
+
+/// [`extract_insights`] with custom patterns tried before the built-in ones.
+pub fn extract_insights_with(raw_output: &[u8], patterns: &InsightPatterns) -> ExtractedInsights {
     let text = String::from_utf8_lossy(raw_output);
     let mut dead_ends = Vec::new();
     let mut decisions = Vec::new();
@@ -17,12 +149,18 @@ pub fn extract_insights(raw_output: &[u8]) -> ExtractedInsights {
         let lower = trimmed.to_lowercase();
 
         // Dead end patterns
-        if let Some(de) = try_extract_dead_end(&lower, trimmed) {
+        if let Some(de) = patterns
+            .match_dead_end(trimmed)
+            .or_else(|| try_extract_dead_end(&lower, trimmed))
+        {
             dead_ends.push(de);
         }
 
         // Decision patterns
-        if let Some(d) = try_extract_decision(&lower, trimmed) {
+        if let Some(d) = patterns
+            .match_decision(trimmed)
+            .or_else(|| try_extract_decision(&lower, trimmed))
+        {
             decisions.push(d);
         }
     }
@@ -34,12 +172,188 @@ pub fn extract_insights(raw_output: &[u8]) -> ExtractedInsights {
     ExtractedInsights {
         dead_ends,
         decisions,
+        interpreted_goal: None,
     }
 }
 
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
 pub struct ExtractedInsights {
     pub dead_ends: Vec<DeadEnd>,
     pub decisions: Vec<Decision>,
+    /// What the agent understood the task to be, if the extractor can tell.
+    pub interpreted_goal: Option<String>,
+}
+
+/// Turns the cleaned output of a recorded session into reasoning insights.
+pub trait InsightExtractor: Send + Sync {
+    fn extract(&self, output: &str) -> ExtractedInsights;
+}
+
+/// The built-in phrase heuristics plus any configured [`InsightPatterns`].
+#[derive(Debug, Clone, Default)]
+pub struct HeuristicExtractor {
+    patterns: InsightPatterns,
+}
+
+impl HeuristicExtractor {
+    pub fn new(patterns: InsightPatterns) -> Self {
+        Self { patterns }
+    }
+}
+
+impl InsightExtractor for HeuristicExtractor {
+    fn extract(&self, output: &str) -> ExtractedInsights {
+        extract_insights_with(output.as_bytes(), &self.patterns)
+    }
+}
+
+/// Default time an external extractor may run.
+pub const DEFAULT_EXTRACTOR_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default limit on what an external extractor may print.
+pub const DEFAULT_EXTRACTOR_MAX_OUTPUT: usize = 1024 * 1024;
+
+/// Runs a shell command with the cleaned output on stdin and reads
+/// `{"dead_ends": [...], "decisions": [...], "interpreted_goal": "..."}`
+/// (every field optional) from its stdout.
+///
+/// If the command fails, times out, prints more than the size cap, or
+/// prints invalid JSON, the failure is logged and the fallback extractor
+/// (if any) is used instead.
+pub struct ExternalCommandExtractor {
+    command: String,
+    timeout: Duration,
+    max_output_bytes: usize,
+    fallback: Option<Box<dyn InsightExtractor>>,
+}
+
+impl ExternalCommandExtractor {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            timeout: DEFAULT_EXTRACTOR_TIMEOUT,
+            max_output_bytes: DEFAULT_EXTRACTOR_MAX_OUTPUT,
+            fallback: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_max_output(mut self, bytes: usize) -> Self {
+        self.max_output_bytes = bytes;
+        self
+    }
+
+    /// Extractor to use when the command does not produce usable insights.
+    pub fn with_fallback(mut self, fallback: Box<dyn InsightExtractor>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    fn run(&self, output: &str) -> Result<ExtractedInsights, CaptureError> {
+        let mut child = shell_command(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+
+        // Feed stdin and drain stdout on their own threads so neither pipe
+        // can fill up and stall the command
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = output.to_string();
+        let writer = std::thread::spawn(move || {
+            // The command may exit without reading everything
+            let _ = stdin.write_all(input.as_bytes());
+        });
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let limit = self.max_output_bytes as u64 + 1;
+        let reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            stdout.take(limit).read_to_end(&mut buf).map(|_| buf)
+        });
+
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(CaptureError::Session(format!(
+                    "insight command timed out after {:.1}s",
+                    self.timeout.as_secs_f64()
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        let _ = writer.join();
+        let stdout = reader
+            .join()
+            .map_err(|_| CaptureError::Session("insight command reader panicked".into()))??;
+
+        if !status.success() {
+            return Err(CaptureError::Session(format!(
+                "insight command exited with {status}"
+            )));
+        }
+        if stdout.len() > self.max_output_bytes {
+            return Err(CaptureError::Session(format!(
+                "insight command printed more than {} bytes",
+                self.max_output_bytes
+            )));
+        }
+        Ok(serde_json::from_slice(&stdout)?)
+    }
+}
+
+impl InsightExtractor for ExternalCommandExtractor {
+    fn extract(&self, output: &str) -> ExtractedInsights {
+        match self.run(output) {
+            Ok(insights) => insights,
+            Err(e) => {
+                tracing::warn!("Insight command '{}' failed: {e}", self.command);
+                self.fallback
+                    .as_ref()
+                    .map(|f| f.extract(output))
+                    .unwrap_or_default()
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(not(unix))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// The extractor selected by config: `engram.insights.command` if set (with
+/// the heuristics as fallback), otherwise the heuristics with any configured
+/// patterns.
+pub fn extractor_from_config(
+    repo_root: &Path,
+    config: &EngramConfig,
+) -> Result<Box<dyn InsightExtractor>, CaptureError> {
+    let heuristics = HeuristicExtractor::new(InsightPatterns::load(repo_root, config)?);
+    Ok(match &config.insight_command {
+        Some(command) => {
+            Box::new(ExternalCommandExtractor::new(command).with_fallback(Box::new(heuristics)))
+        }
+        None => Box::new(heuristics),
+    })
 }
 
 fn try_extract_dead_end(lower: &str, original: &str) -> Option<DeadEnd> {
@@ -168,4 +482,129 @@ mod tests {
         assert!(insights.dead_ends.is_empty());
         assert!(insights.decisions.is_empty());
     }
+
+    #[test]
+    fn test_custom_patterns_extract_what_defaults_miss() {
+        let output = "abandoning approach: gRPC streaming - proxy strips trailers\n\
+Entscheidung: SQLite verwenden, weil keine Serverinstallation\n\
+tried redis but the cluster was unreachable\n"
+            .as_bytes();
+
+        let defaults = extract_insights(output);
+        assert_eq!(defaults.dead_ends.len(), 1);
+        assert!(defaults.decisions.is_empty());
+
+        let patterns = InsightPatterns::new(
+            &[r"^abandoning approach: (?P<approach>.+?)(?: - (?P<reason>.+))?$".into()],
+            &[r"^Entscheidung: (?P<description>.+?)(?:, weil (?P<rationale>.+))?$".into()],
+        )
+        .unwrap();
+        let insights = extract_insights_with(output, &patterns);
+
+        assert_eq!(insights.dead_ends.len(), 2);
+        assert_eq!(insights.dead_ends[0].approach, "gRPC streaming");
+        assert_eq!(insights.dead_ends[0].reason, "proxy strips trailers");
+        // Built-in heuristics still apply to other lines
+        assert_eq!(insights.dead_ends[1].approach, "redis");
+        assert_eq!(insights.decisions.len(), 1);
+        assert_eq!(insights.decisions[0].description, "SQLite verwenden");
+        assert_eq!(insights.decisions[0].rationale, "keine Serverinstallation");
+    }
+
+    #[test]
+    fn test_invalid_patterns_rejected_at_load() {
+        let err = InsightPatterns::new(&["(?P<approach>unclosed".into()], &[]).unwrap_err();
+        assert!(matches!(err, CaptureError::InvalidPattern(_)));
+
+        let err = InsightPatterns::new(&[], &["decided: (.+)".into()]).unwrap_err();
+        assert!(err.to_string().contains("description"));
+    }
+
+    #[test]
+    fn test_load_merges_config_and_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join(".engram")).unwrap();
+        std::fs::write(
+            tmp.path().join(INSIGHTS_FILE),
+            "decision = ['^Decision: (?P<description>.+)']\n",
+        )
+        .unwrap();
+        let mut config = EngramConfig::default_init();
+        config.insight_dead_end_patterns = vec!["^Dropped (?P<approach>.+)".into()];
+
+        let patterns = InsightPatterns::load(tmp.path(), &config).unwrap();
+        let insights = extract_insights_with(
+            b"Dropped the cache layer entirely\nDecision: keep the sync API\n",
+            &patterns,
+        );
+        assert_eq!(insights.dead_ends[0].approach, "the cache layer entirely");
+        assert_eq!(insights.decisions[0].description, "keep the sync API");
+        assert_eq!(
+            insights.decisions[0].rationale,
+            "Decision: keep the sync API"
+        );
+
+        std::fs::write(tmp.path().join(INSIGHTS_FILE), "decision = 'not a list'\n").unwrap();
+        assert!(InsightPatterns::load(tmp.path(), &config).is_err());
+    }
+
+    #[cfg(unix)]
+    mod external {
+        use super::*;
+
+        /// Reports the first stdin line back as a dead end.
+        const ECHO_SCRIPT: &str = r#"read first; printf '{"dead_ends":[{"approach":"%s","reason":"from script"}],"interpreted_goal":"summarize"}' "$first""#;
+
+        fn fallback() -> Box<dyn InsightExtractor> {
+            Box::new(HeuristicExtractor::default())
+        }
+
+        #[test]
+        fn test_external_command_output_is_used() {
+            let extractor = ExternalCommandExtractor::new(ECHO_SCRIPT).with_fallback(fallback());
+            let insights = extractor.extract("vendoring the SDK\ntried X but Y\n");
+            assert_eq!(insights.dead_ends.len(), 1);
+            assert_eq!(insights.dead_ends[0].approach, "vendoring the SDK");
+            assert_eq!(insights.dead_ends[0].reason, "from script");
+            assert!(insights.decisions.is_empty());
+            assert_eq!(insights.interpreted_goal.as_deref(), Some("summarize"));
+        }
+
+        #[test]
+        fn test_external_command_failures_fall_back() {
+            let output = "tried passport.js but middleware conflict\n";
+
+            let broken = [
+                ExternalCommandExtractor::new("cat >/dev/null; echo not json"),
+                ExternalCommandExtractor::new("exit 2"),
+                ExternalCommandExtractor::new("sleep 5").with_timeout(Duration::from_millis(200)),
+                ExternalCommandExtractor::new(ECHO_SCRIPT).with_max_output(16),
+            ];
+            for extractor in broken {
+                let started = Instant::now();
+                let insights = extractor.with_fallback(fallback()).extract(output);
+                assert_eq!(insights.dead_ends.len(), 1);
+                assert_eq!(insights.dead_ends[0].approach, "passport.js");
+                assert!(started.elapsed() < Duration::from_secs(4));
+            }
+
+            // Without a fallback a failure yields nothing
+            let insights = ExternalCommandExtractor::new("exit 1").extract(output);
+            assert!(insights.dead_ends.is_empty());
+        }
+
+        #[test]
+        fn test_extractor_from_config() {
+            let tmp = tempfile::TempDir::new().unwrap();
+            let mut config = EngramConfig::default_init();
+            let output = "vendoring the SDK\n";
+
+            let heuristic = extractor_from_config(tmp.path(), &config).unwrap();
+            assert!(heuristic.extract(output).dead_ends.is_empty());
+
+            config.insight_command = Some(ECHO_SCRIPT.into());
+            let external = extractor_from_config(tmp.path(), &config).unwrap();
+            assert_eq!(external.extract(output).dead_ends.len(), 1);
+        }
+    }
 }
@@ -1,4 +1,10 @@
+pub mod ansi;
 mod builder;
+pub mod cast;
 pub mod extractor;
+pub mod usage;
 
-pub use builder::SessionBuilder;
+pub use builder::{
+    SessionBuilder, CAST_ATTACHMENT, DEFAULT_IDLE_THRESHOLD, INTERRUPTED_TAG, OUTPUT_LIMIT_TAG,
+    OUTPUT_TRUNCATED_TAG, RAW_OUTPUT_ATTACHMENT, TIMED_OUT_TAG,
+};
@@ -0,0 +1,328 @@
+use engram_core::model::TokenUsage;
+
+use crate::import::aider::{extract_cost, extract_token_count};
+
+/// Token usage and model details recovered from an agent's terminal output.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedUsage {
+    pub token_usage: Option<TokenUsage>,
+    pub model: Option<String>,
+}
+
+/// Extracts usage from the cleaned (ANSI-stripped) output of a wrapped agent.
+///
+/// Parsers are heuristic and keyed by agent name; implement this to teach
+/// `engram record` about another agent's status output.
+pub trait UsageParser: Send + Sync {
+    /// Agent name this parser handles, as detected by `engram record`.
+    fn agent(&self) -> &str;
+
+    fn parse(&self, output: &str) -> ParsedUsage;
+}
+
+/// Usage parsers by agent name.
+#[derive(Default)]
+pub struct UsageParsers {
+    parsers: Vec<Box<dyn UsageParser>>,
+}
+
+impl UsageParsers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in Claude Code and Aider parsers.
+    pub fn with_builtins() -> Self {
+        let mut parsers = Self::new();
+        parsers.register(Box::new(ClaudeCodeUsageParser));
+        parsers.register(Box::new(AiderUsageParser));
+        parsers
+    }
+
+    /// Add a parser, replacing any existing parser for the same agent.
+    pub fn register(&mut self, parser: Box<dyn UsageParser>) {
+        self.parsers.retain(|p| p.agent() != parser.agent());
+        self.parsers.push(parser);
+    }
+
+    pub fn for_agent(&self, agent: &str) -> Option<&dyn UsageParser> {
+        self.parsers
+            .iter()
+            .find(|p| p.agent() == agent)
+            .map(|p| p.as_ref())
+    }
+
+    /// Parse output with the parser for `agent`, if there is one.
+    pub fn parse(&self, agent: &str, output: &str) -> ParsedUsage {
+        self.for_agent(agent)
+            .map(|p| p.parse(output))
+            .unwrap_or_default()
+    }
+}
+
+/// Reads the session summary Claude Code prints on exit (and for `/cost`):
+///
+/// ```text
+/// Total cost:            $0.1234
+/// Usage by model:
+///     claude-sonnet-4-5:  12.3k input, 1.2k output, 45.6k cache read, 3.4k cache write ($0.1234)
+/// ```
+///
+/// The last summary in the output wins, since each one is cumulative.
+pub struct ClaudeCodeUsageParser;
+
+impl UsageParser for ClaudeCodeUsageParser {
+    fn agent(&self) -> &str {
+        "claude-code"
+    }
+
+    fn parse(&self, output: &str) -> ParsedUsage {
+        let mut usage: Option<TokenUsage> = None;
+        let mut model = None;
+        let mut in_model_table = false;
+
+        for line in output.lines() {
+            // Drop tree glyphs such as "⎿" that prefix command output
+            let trimmed = line
+                .trim()
+                .trim_start_matches(|c: char| !c.is_alphanumeric())
+                .trim();
+            let lower = trimmed.to_lowercase();
+
+            if lower.starts_with("total cost:") {
+                // A new cumulative summary starts; discard the previous one
+                usage = Some(TokenUsage {
+                    cost_usd: extract_cost(trimmed),
+                    ..Default::default()
+                });
+                in_model_table = false;
+                continue;
+            }
+            if lower.starts_with("usage by model") {
+                in_model_table = true;
+                continue;
+            }
+
+            if in_model_table {
+                if let Some((name, counts)) = trimmed.split_once(':') {
+                    if name.starts_with("claude") && !counts.trim().is_empty() {
+                        let usage = usage.get_or_insert_with(TokenUsage::default);
+                        add_claude_counts(usage, counts);
+                        model = Some(name.trim().to_string());
+                        continue;
+                    }
+                }
+                if !trimmed.is_empty() {
+                    in_model_table = false;
+                }
+            }
+
+            if let Some(name) = claude_model_mention(trimmed) {
+                model = Some(name);
+            }
+        }
+
+        if let Some(usage) = &mut usage {
+            usage.total_tokens = usage.input_tokens
+                + usage.output_tokens
+                + usage.cache_read_tokens
+                + usage.cache_write_tokens;
+        }
+
+        ParsedUsage {
+            token_usage: usage.filter(|u| u.total_tokens > 0 || u.cost_usd.is_some()),
+            model,
+        }
+    }
+}
+
+/// Add "12.3k input, 1.2k output, 45.6k cache read, 3.4k cache write" to `usage`.
+fn add_claude_counts(usage: &mut TokenUsage, counts: &str) {
+    for part in counts.split(',') {
+        let part = part.trim();
+        let Some(n) = extract_token_count(part) else {
+            continue;
+        };
+        if part.contains("cache read") {
+            usage.cache_read_tokens += n;
+        } else if part.contains("cache write") {
+            usage.cache_write_tokens += n;
+        } else if part.contains("input") {
+            usage.input_tokens += n;
+        } else if part.contains("output") {
+            usage.output_tokens += n;
+        }
+    }
+}
+
+/// Model named in the welcome banner or a `/model` switch, e.g.
+/// "Model: claude-opus-4-1" or "Set model to claude-sonnet-4-5".
+fn claude_model_mention(line: &str) -> Option<String> {
+    let lower = line.to_lowercase();
+    if !(lower.contains("model:") || lower.contains("model to")) {
+        return None;
+    }
+    line.split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ',' | '`'))
+        .find(|w| w.starts_with("claude-"))
+        .map(|w| w.trim_end_matches(['.', ':']).to_string())
+}
+
+/// Reads Aider's startup banner and per-message token reports:
+///
+/// ```text
+/// Main model: gpt-4o with diff edit format
+/// Tokens: 3.2k sent, 245 received. Cost: $0.01 message, $0.05 session.
+/// ```
+pub struct AiderUsageParser;
+
+impl UsageParser for AiderUsageParser {
+    fn agent(&self) -> &str {
+        "aider"
+    }
+
+    fn parse(&self, output: &str) -> ParsedUsage {
+        let mut usage = TokenUsage::default();
+        let mut message_cost = 0.0;
+        let mut session_cost = None;
+        let mut seen = false;
+        let mut model = None;
+
+        for line in output.lines() {
+            let trimmed = line.trim().trim_start_matches('>').trim();
+
+            if let Some(rest) = trimmed
+                .strip_prefix("Main model:")
+                .or_else(|| trimmed.strip_prefix("Model:"))
+            {
+                model = rest.split_whitespace().next().map(str::to_string);
+                continue;
+            }
+
+            let Some(rest) = trimmed.strip_prefix("Tokens:") else {
+                continue;
+            };
+            seen = true;
+            for part in rest.split(',') {
+                let part = part.trim();
+                if part.contains("sent") {
+                    usage.input_tokens += extract_token_count(part).unwrap_or(0);
+                } else if part.contains("received") {
+                    usage.output_tokens += extract_token_count(part).unwrap_or(0);
+                }
+                if part.contains("session") {
+                    // Cumulative; the latest report is authoritative
+                    session_cost = extract_cost(part);
+                } else if let Some(cost) = extract_cost(part) {
+                    message_cost += cost;
+                }
+            }
+        }
+
+        usage.total_tokens = usage.input_tokens + usage.output_tokens;
+        if seen {
+            usage.cost_usd = session_cost.or(Some(message_cost));
+        }
+
+        ParsedUsage {
+            token_usage: seen.then_some(usage),
+            model,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLAUDE_OUTPUT: &str = r#"
+ ✻ Welcome to Claude Code!
+
+   /help for help, /status for your current setup
+
+   cwd: /home/dev/project
+   Model: claude-sonnet-4-5-20250929
+
+> add input validation to the signup form
+
+⏺ I'll add validation to the signup handler.
+
+> /model
+  ⎿  Set model to claude-opus-4-1-20250805
+
+> /cost
+  ⎿  Total cost:            $0.0512
+     Total duration (API):  41.2s
+     Total duration (wall): 2m 3.4s
+     Total code changes:    14 lines added, 2 lines removed
+     Usage by model:
+         claude-sonnet-4-5:  3.1k input, 612 output, 10.2k cache read, 1.9k cache write ($0.0412)
+
+Total cost:            $0.1734
+Total duration (API):  1m 52.7s
+Total duration (wall): 6m 10.1s
+Total code changes:    51 lines added, 9 lines removed
+Usage by model:
+    claude-sonnet-4-5:  3.1k input, 612 output, 10.2k cache read, 1.9k cache write ($0.0412)
+      claude-opus-4-1:  2.4k input, 1.1k output, 22.5k cache read, 4.0k cache write ($0.1322)
+"#;
+
+    const AIDER_OUTPUT: &str = r#"
+Aider v0.86.1
+Main model: gpt-4o with diff edit format
+Weak model: gpt-4o-mini
+Git repo: .git with 42 files
+Repo-map: using 1024 tokens, auto refresh
+
+> Add a fibonacci function to math_utils.py
+
+I'll add the function.
+
+Tokens: 3.2k sent, 245 received. Cost: $0.01 message, $0.01 session.
+Applied edit to math_utils.py
+
+> Fix the off-by-one error
+
+Tokens: 1.5k sent, 100 received. Cost: $0.0050 message, $0.02 session.
+"#;
+
+    #[test]
+    fn test_claude_code_final_summary() {
+        let parsed = ClaudeCodeUsageParser.parse(CLAUDE_OUTPUT);
+        let usage = parsed.token_usage.unwrap();
+        assert_eq!(usage.input_tokens, 5500);
+        assert_eq!(usage.output_tokens, 1712);
+        assert_eq!(usage.cache_read_tokens, 32_700);
+        assert_eq!(usage.cache_write_tokens, 5900);
+        assert_eq!(usage.total_tokens, 5500 + 1712 + 32_700 + 5900);
+        assert_eq!(usage.cost_usd, Some(0.1734));
+        assert_eq!(parsed.model.as_deref(), Some("claude-opus-4-1"));
+    }
+
+    #[test]
+    fn test_claude_code_banner_only() {
+        let parsed = ClaudeCodeUsageParser.parse("   Model: claude-sonnet-4-5\n> hi\n");
+        assert!(parsed.token_usage.is_none());
+        assert_eq!(parsed.model.as_deref(), Some("claude-sonnet-4-5"));
+    }
+
+    #[test]
+    fn test_aider_token_reports() {
+        let parsed = AiderUsageParser.parse(AIDER_OUTPUT);
+        let usage = parsed.token_usage.unwrap();
+        assert_eq!(usage.input_tokens, 4700);
+        assert_eq!(usage.output_tokens, 345);
+        assert_eq!(usage.total_tokens, 5045);
+        assert_eq!(usage.cost_usd, Some(0.02));
+        assert_eq!(parsed.model.as_deref(), Some("gpt-4o"));
+    }
+
+    #[test]
+    fn test_registry_dispatches_by_agent() {
+        let parsers = UsageParsers::with_builtins();
+        assert!(parsers.parse("aider", AIDER_OUTPUT).token_usage.is_some());
+        assert_eq!(
+            parsers.parse("unknown-agent", AIDER_OUTPUT),
+            ParsedUsage::default()
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! Runs in its own test binary: raising SIGINT is process-wide.
+#![cfg(unix)]
+
+use std::time::{Duration, Instant};
+
+use engram_capture::pty::{
+    PtySession, PtyWrapperConfig, SnapshotOptions, DEFAULT_MAX_BUFFER_BYTES,
+};
+use engram_capture::session::{SessionBuilder, INTERRUPTED_TAG};
+use engram_core::model::AgentInfo;
+use signal_hook::consts::SIGINT;
+use tempfile::TempDir;
+
+#[test]
+fn test_sigint_is_forwarded_and_session_finalized() {
+    let tmp = TempDir::new().unwrap();
+    let config = PtyWrapperConfig {
+        command: "sleep".into(),
+        args: vec!["30".into()],
+        working_dir: tmp.path().to_path_buf(),
+        agent_name: None,
+        max_buffer_bytes: DEFAULT_MAX_BUFFER_BYTES,
+        snapshot: SnapshotOptions::default(),
+        timeout: None,
+        max_output_bytes: None,
+    };
+
+    let started = Instant::now();
+    let recorder = std::thread::spawn(move || PtySession::start(config).unwrap().run());
+
+    // Give the wrapper time to spawn the child and install its handlers
+    std::thread::sleep(Duration::from_secs(1));
+    signal_hook::low_level::raise(SIGINT).unwrap();
+
+    let captured = recorder.join().unwrap().unwrap();
+    assert!(captured.interrupted);
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    let agent = AgentInfo {
+        name: "sleep".into(),
+        model: None,
+        version: None,
+    };
+    let data = SessionBuilder::new(agent, captured).build();
+    assert!(data.manifest.tags.iter().any(|t| t == INTERRUPTED_TAG));
+}
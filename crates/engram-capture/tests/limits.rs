@@ -0,0 +1,85 @@
+#![cfg(unix)]
+
+use std::time::{Duration, Instant};
+
+use engram_capture::pty::{
+    CapturedSession, PtySession, PtyWrapperConfig, SessionLimit, SnapshotOptions,
+    DEFAULT_MAX_BUFFER_BYTES,
+};
+use engram_capture::session::{SessionBuilder, OUTPUT_LIMIT_TAG, TIMED_OUT_TAG};
+use engram_core::model::AgentInfo;
+use tempfile::TempDir;
+
+fn record(
+    command: &str,
+    args: &[&str],
+    timeout: Option<Duration>,
+    max_output_bytes: Option<u64>,
+) -> CapturedSession {
+    let tmp = TempDir::new().unwrap();
+    let config = PtyWrapperConfig {
+        command: command.into(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        working_dir: tmp.path().to_path_buf(),
+        agent_name: None,
+        max_buffer_bytes: DEFAULT_MAX_BUFFER_BYTES,
+        snapshot: SnapshotOptions::default(),
+        timeout,
+        max_output_bytes,
+    };
+    PtySession::start(config).unwrap().run().unwrap()
+}
+
+fn tags(captured: CapturedSession) -> Vec<String> {
+    let agent = AgentInfo {
+        name: "test".into(),
+        model: None,
+        version: None,
+    };
+    SessionBuilder::new(agent, captured).build().manifest.tags
+}
+
+#[test]
+fn test_timeout_stops_hung_child() {
+    let started = Instant::now();
+    let captured = record(
+        "sh",
+        &["-c", "echo started; sleep 60"],
+        Some(Duration::from_secs(1)),
+        None,
+    );
+
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(captured.limit_exceeded, Some(SessionLimit::Timeout));
+    assert!(!captured.interrupted);
+    // Output produced before the timeout is kept
+    let output = String::from_utf8_lossy(&captured.raw_output.to_vec().unwrap()).to_string();
+    assert!(output.contains("started"));
+    assert_eq!(tags(captured), vec![TIMED_OUT_TAG.to_string()]);
+}
+
+#[test]
+fn test_max_output_stops_noisy_child() {
+    let captured = record(
+        "sh",
+        &["-c", "while :; do echo spam; done"],
+        Some(Duration::from_secs(30)),
+        Some(64 * 1024),
+    );
+
+    assert_eq!(captured.limit_exceeded, Some(SessionLimit::MaxOutput));
+    assert!(captured.raw_output.len() > 64 * 1024);
+    assert_eq!(tags(captured), vec![OUTPUT_LIMIT_TAG.to_string()]);
+}
+
+#[test]
+fn test_limits_not_hit() {
+    let captured = record(
+        "sh",
+        &["-c", "echo quick"],
+        Some(Duration::from_secs(30)),
+        Some(1024 * 1024),
+    );
+    assert_eq!(captured.limit_exceeded, None);
+    assert_eq!(captured.exit_code, Some(0));
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use engram_core::storage::{GitStorage, InitPolicy};
+
+use crate::output::{theme, OutputFormat};
+
+#[derive(Args)]
+pub struct AliasArgs {
+    #[command(subcommand)]
+    pub command: AliasCommand,
+}
+
+#[derive(Subcommand)]
+pub enum AliasCommand {
+    /// Name an engram, so the name works wherever an ID does; setting an
+    /// existing name repoints it
+    Set {
+        /// a-z, 0-9, '.', '_' and '-', with at least one character that
+        /// isn't a hex digit
+        name: String,
+        /// Engram ID (full, prefix, alias or HEAD)
+        id: String,
+    },
+    /// Remove an alias; the engram is kept
+    #[command(visible_alias = "remove")]
+    Rm { name: String },
+    /// List aliases and the engrams they name
+    #[command(visible_alias = "ls")]
+    List,
+}
+
+pub fn run(args: &AliasArgs, format: OutputFormat) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::Error)?;
+
+    match &args.command {
+        AliasCommand::Set { name, id } => {
+            let (id, previous) = storage
+                .set_alias(name, id)
+                .with_context(|| format!("Failed to set alias '{name}'"))?;
+            match previous {
+                Some(previous) if previous != id => println!(
+                    "{name} -> {} (was {})",
+                    &id.as_str()[..8],
+                    &previous.as_str()[..8]
+                ),
+                _ => println!("{name} -> {}", &id.as_str()[..8]),
+            }
+        }
+        AliasCommand::Rm { name } => {
+            let id = storage
+                .remove_alias(name)
+                .with_context(|| format!("Failed to remove alias '{name}'"))?;
+            println!("Removed alias {name} (was {})", &id.as_str()[..8]);
+        }
+        AliasCommand::List => list(&storage, format)?,
+    }
+    Ok(())
+}
+
+fn list(storage: &GitStorage, format: OutputFormat) -> Result<()> {
+    let aliases = storage.aliases().context("Failed to read aliases")?;
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let aliases: Vec<_> = aliases
+                .iter()
+                .map(|(name, id)| serde_json::json!({ "name": name, "id": id.as_str() }))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&aliases)?);
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            if aliases.is_empty() {
+                eprintln!("No aliases. Add one with `engram alias set <name> <id>`.");
+            }
+            let width = aliases.iter().map(|(name, _)| name.len()).max();
+            for (name, id) in &aliases {
+                let summary = match storage.read_manifest(id.as_str()) {
+                    Ok(manifest) => manifest.summary.unwrap_or_default(),
+                    Err(_) => "(engram not found)".to_string(),
+                };
+                println!(
+                    "{name:<width$}  {}  {summary}",
+                    theme::ID.paint(&id.as_str()[..8]),
+                    width = width.unwrap_or_default()
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use engram_core::model::{DeadEnd, Decision, Intent};
+use engram_core::storage::{GitStorage, InitPolicy};
+
+use super::tag::reindex;
+
+#[derive(Args)]
+pub struct AmendArgs {
+    /// Engram ID (full, prefix, alias or HEAD)
+    pub id: String,
+
+    /// Replace the one-line summary shown by `engram log`
+    #[arg(long)]
+    pub summary: Option<String>,
+
+    /// Replace the interpreted goal
+    #[arg(long)]
+    pub goal: Option<String>,
+
+    /// Add a dead end, written "approach::reason" (repeatable)
+    #[arg(long, value_name = "APPROACH::REASON", value_parser = parse_pair)]
+    pub add_dead_end: Vec<(String, String)>,
+
+    /// Add a decision, written "description::rationale" (repeatable)
+    #[arg(long, value_name = "DESCRIPTION::RATIONALE", value_parser = parse_pair)]
+    pub add_decision: Vec<(String, String)>,
+
+    /// Edit the intent as Markdown in $VISUAL or $EDITOR, after any other
+    /// changes given
+    #[arg(long)]
+    pub edit: bool,
+}
+
+fn parse_pair(value: &str) -> Result<(String, String), String> {
+    match value.split_once("::") {
+        Some((first, second)) if !first.trim().is_empty() && !second.trim().is_empty() => {
+            Ok((first.trim().to_string(), second.trim().to_string()))
+        }
+        _ => Err(format!(
+            "expected two parts separated by '::', got '{value}'"
+        )),
+    }
+}
+
+pub fn run(args: &AmendArgs) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::Error)?;
+
+    if args.summary.is_none()
+        && args.goal.is_none()
+        && args.add_dead_end.is_empty()
+        && args.add_decision.is_empty()
+        && !args.edit
+    {
+        anyhow::bail!(
+            "Nothing to amend; give --summary, --goal, --add-dead-end, --add-decision or --edit"
+        );
+    }
+
+    let id = storage
+        .resolve(&args.id)
+        .with_context(|| format!("Failed to resolve engram '{}'", args.id))?;
+
+    let mut intent = storage
+        .read_intent(&id)
+        .with_context(|| format!("Failed to read engram '{id}'"))?;
+    if let Some(goal) = &args.goal {
+        intent.interpreted_goal = Some(goal.clone());
+    }
+    for (approach, reason) in &args.add_dead_end {
+        intent.dead_ends.push(DeadEnd {
+            approach: approach.clone(),
+            reason: reason.clone(),
+        });
+    }
+    for (description, rationale) in &args.add_decision {
+        intent.decisions.push(Decision {
+            description: description.clone(),
+            rationale: rationale.clone(),
+        });
+    }
+    if args.edit {
+        intent = edit_intent(&storage, &id, &intent)?;
+    }
+
+    storage
+        .update(&id, |m, i| {
+            if let Some(summary) = &args.summary {
+                m.summary = Some(summary.clone());
+            }
+            *i = intent;
+        })
+        .with_context(|| format!("Failed to amend engram '{id}'"))?;
+    reindex(&storage, &id);
+    eprintln!("Amended {}", &id[..8.min(id.len())]);
+    Ok(())
+}
+
+/// Open `intent` as Markdown in the user's editor and parse the result. The
+/// file is kept if it can't be parsed, so the edit isn't lost.
+fn edit_intent(storage: &GitStorage, id: &str, intent: &Intent) -> Result<Intent> {
+    let path = storage
+        .repo()
+        .path()
+        .join(format!("engram-amend-{}.md", &id[..8.min(id.len())]));
+    std::fs::write(&path, intent.to_markdown())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    run_editor(&path)?;
+
+    let md = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let edited = Intent::from_markdown_strict(&md).with_context(|| {
+        format!(
+            "Could not parse the edited intent; nothing was changed. Your edit is saved in {}",
+            path.display()
+        )
+    })?;
+    let _ = std::fs::remove_file(&path);
+    Ok(edited)
+}
+
+/// Run $VISUAL or $EDITOR (falling back to vi) on `path` through the shell,
+/// so that editors given with arguments, like "code --wait", work.
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg(&editor)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to run editor '{editor}'"))?;
+    if !status.success() {
+        anyhow::bail!(
+            "Editor '{editor}' exited with {status}; nothing was changed. Your edit is in {}",
+            path.display()
+        );
+    }
+    Ok(())
+}
@@ -1,94 +1,85 @@
 use anyhow::{Context, Result};
 use clap::Args;
-use engram_core::storage::GitStorage;
-use engram_query::search::SearchEngine;
+use engram_core::storage::{GitStorage, InitPolicy};
+use engram_query::{trace_file, TraceEntry};
 
-use crate::output::OutputFormat;
+use crate::output::ndjson::NdjsonWriter;
+use crate::output::{theme, OutputFormat};
 
 #[derive(Args)]
 pub struct BlameArgs {
-    /// File path to find reasoning history for
+    /// File path to find reasoning history for, relative to the current
+    /// directory or absolute
     pub file: String,
 
     /// Maximum number of results
     #[arg(short = 'n', long, default_value = "20")]
     pub limit: usize,
+
+    /// Don't include history recorded under the file's earlier names
+    #[arg(long)]
+    pub no_follow: bool,
+
+    /// If the search index can't be opened, rebuild it first rather than
+    /// scanning engrams
+    #[arg(long)]
+    pub rebuild_index: bool,
+
+    #[command(flatten)]
+    pub filters: super::trace::TraceFilters,
 }
 
 pub fn run(args: &BlameArgs, format: OutputFormat) -> Result<()> {
     let storage = GitStorage::discover().context("Not inside a Git repository")?;
 
-    if !storage.is_initialized() {
-        anyhow::bail!("Engram is not initialized. Run `engram init` first.");
-    }
+    storage.ensure_initialized(InitPolicy::WarnAndContinue)?;
 
-    let search = SearchEngine::open(&storage).context("Failed to open search index")?;
-    let results = search
-        .search_by_file(&storage, &args.file, args.limit)
-        .context("Search failed")?;
+    let file = super::trace::repo_path(&storage, &args.file)?;
+    let filters = args.filters.list_options()?;
+    let search = super::search::open_engine(&storage, args.rebuild_index)?;
+    let mut results =
+        trace_file(&storage, &search, &file, !args.no_follow).context("Search failed")?;
+    // Filter before truncating, so --limit counts matching engrams
+    results.retain(|r| filters.matches(&r.manifest));
+    // Most recent first
+    results.reverse();
+    results.truncate(args.limit);
 
     if results.is_empty() {
-        println!("No engrams found that touched '{}'.", args.file);
+        println!("No engrams found that touched '{file}'.");
         return Ok(());
     }
 
     match format {
         OutputFormat::Json => {
-            let entries: Vec<_> = results
-                .iter()
-                .map(|r| {
-                    // Get actual change type
-                    let change_info = storage.read(r.manifest.id.as_str()).ok().and_then(|data| {
-                        data.operations
-                            .file_changes
-                            .iter()
-                            .find(|fc| fc.path == args.file)
-                            .map(|fc| {
-                                serde_json::json!({
-                                    "change_type": format!("{:?}", fc.change_type),
-                                    "lines_added": fc.lines_added,
-                                    "lines_removed": fc.lines_removed,
-                                })
-                            })
-                    });
-
-                    serde_json::json!({
-                        "engram_id": r.manifest.id.as_str(),
-                        "created_at": r.manifest.created_at,
-                        "agent": r.manifest.agent.name,
-                        "summary": r.manifest.summary,
-                        "intent": r.manifest.summary,
-                        "change": change_info,
-                    })
-                })
-                .collect();
+            let entries: Vec<_> = results.iter().map(|r| blame_entry(&storage, r)).collect();
             println!("{}", serde_json::to_string_pretty(&entries).unwrap());
         }
-        OutputFormat::Text | OutputFormat::Markdown => {
-            println!("Reasoning blame for: {}", args.file);
-            println!("{}", "=".repeat(40 + args.file.len()));
+        OutputFormat::Ndjson => {
+            let mut out = NdjsonWriter::stdout();
+            for r in &results {
+                out.write("blame_entry", &blame_entry(&storage, r))?;
+            }
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            let title = format!("Reasoning blame for: {file}");
+            println!("{}", theme::HEADING.paint(title));
+            println!("{}", "=".repeat(40 + file.len()));
             println!();
 
             for r in &results {
                 let m = &r.manifest;
-                let short_id = &m.id.as_str()[..8];
-                let date = m.created_at.format("%Y-%m-%d %H:%M");
+                let short_id = theme::ID.paint(&m.id.as_str()[..8]);
+                let date = theme::DATE.paint(m.created_at.format("%Y-%m-%d %H:%M"));
                 let summary = m.summary.as_deref().unwrap_or("(no summary)");
 
-                // Get change type from full data
-                let change_type = storage
-                    .read(m.id.as_str())
-                    .ok()
-                    .and_then(|data| {
-                        data.operations
-                            .file_changes
-                            .iter()
-                            .find(|fc| fc.path == args.file)
-                            .map(|fc| format!("{:?}", fc.change_type).to_lowercase())
-                    })
-                    .unwrap_or_else(|| "touched".to_string());
-
-                println!("{short_id} {date} [{change_type}] {}", m.agent.name);
+                let change_type = theme::change_named(&r.change_type).paint(&r.change_type);
+                let agent = theme::AGENT.paint(&m.agent.name);
+                if r.path == file {
+                    println!("{short_id} {date} [{change_type}] {agent}");
+                } else {
+                    println!("{short_id} {date} [{change_type}] {agent} (as {})", r.path);
+                }
                 println!("  {summary}");
 
                 // Show intent if we can read it
@@ -117,3 +108,31 @@ pub fn run(args: &BlameArgs, format: OutputFormat) -> Result<()> {
 
     Ok(())
 }
+
+/// One engram's change to the file, as `--format json` reports it.
+fn blame_entry(storage: &GitStorage, r: &TraceEntry) -> serde_json::Value {
+    // Get actual change type
+    let change_info = storage.read(r.manifest.id.as_str()).ok().and_then(|data| {
+        data.operations
+            .file_changes
+            .iter()
+            .find(|fc| fc.path == r.path)
+            .map(|fc| {
+                serde_json::json!({
+                    "change_type": format!("{:?}", fc.change_type),
+                    "lines_added": fc.lines_added,
+                    "lines_removed": fc.lines_removed,
+                })
+            })
+    });
+
+    serde_json::json!({
+        "engram_id": r.manifest.id.as_str(),
+        "path": r.path,
+        "created_at": r.manifest.created_at,
+        "agent": r.manifest.agent.name,
+        "summary": r.manifest.summary,
+        "intent": r.manifest.summary,
+        "change": change_info,
+    })
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+
+use engram_core::storage::GitStorage;
+use engram_protocol::bundle;
+use engram_query::SearchEngine;
+
+use super::fetch::unindex;
+use super::push::SelectArgs;
+
+#[derive(Args)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: BundleCommand,
+}
+
+#[derive(Subcommand)]
+pub enum BundleCommand {
+    /// Write engrams to a Git bundle file
+    Create(CreateArgs),
+    /// Add the engrams in a bundle file to this repository
+    Import(ImportArgs),
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    /// Bundle file to write
+    pub out: PathBuf,
+
+    /// Engram IDs or prefixes to bundle instead of all
+    #[arg(value_name = "ID")]
+    pub ids: Vec<String>,
+
+    #[command(flatten)]
+    pub select: SelectArgs,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Bundle file to read
+    pub file: PathBuf,
+}
+
+pub fn run(args: &BundleArgs) -> Result<()> {
+    let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
+    match &args.command {
+        BundleCommand::Create(args) => create(&storage, args),
+        BundleCommand::Import(args) => import(&storage, args),
+    }
+}
+
+fn create(storage: &GitStorage, args: &CreateArgs) -> Result<()> {
+    let filter = args.select.filter(args.ids.clone())?;
+    let summary = bundle::write(storage.repo(), &args.out, &filter)?;
+    eprintln!(
+        "Bundled {} engram(s), {} object(s), into {}",
+        summary.refs.len(),
+        summary.objects,
+        args.out.display()
+    );
+    Ok(())
+}
+
+fn import(storage: &GitStorage, args: &ImportArgs) -> Result<()> {
+    let import = bundle::read(storage.repo(), &args.file)?;
+    eprintln!(
+        "Imported {} engram(s) from {} ({} new, {} updated, {} unchanged)",
+        import.created.len() + import.updated.len(),
+        args.file.display(),
+        import.created.len(),
+        import.updated.len(),
+        import.unchanged
+    );
+    for id in &import.kept {
+        eprintln!(
+            "Kept {}: it has changed here since the bundle was made",
+            id.as_str().get(..8).unwrap_or(id.as_str())
+        );
+    }
+
+    // Updated engrams are dropped so that they are indexed afresh
+    if !import.created.is_empty() || !import.updated.is_empty() {
+        unindex(storage, &import.updated)?;
+        let count = SearchEngine::open(storage)?.index_missing(storage)?;
+        eprintln!("Indexed {count} engram(s).");
+    }
+    Ok(())
+}
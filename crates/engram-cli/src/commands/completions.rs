@@ -0,0 +1,237 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::{Args, Command};
+use clap_complete::Shell;
+use engram_core::storage::{GitStorage, ListOptions};
+
+#[derive(Args)]
+#[command(after_help = "\
+Installation:
+  bash        engram completions bash > ~/.local/share/bash-completion/completions/engram
+  zsh         engram completions zsh > \"${fpath[1]}/_engram\"
+  fish        engram completions fish > ~/.config/fish/completions/engram.fish
+  powershell  engram completions powershell >> $PROFILE
+
+The zsh and fish scripts also complete engram IDs and remote names by
+running `engram` in the current repository.")]
+pub struct CompletionsArgs {
+    /// Shell to generate the script for
+    pub shell: Shell,
+}
+
+#[derive(Args)]
+pub struct CompleteArgs {
+    /// What has been typed so far
+    #[arg(default_value = "", allow_hyphen_values = true)]
+    pub prefix: String,
+}
+
+/// What a positional argument takes, for the dynamic completions.
+#[derive(Clone, Copy)]
+enum Dynamic {
+    Ids,
+    Remotes,
+    RemotesOrIds,
+}
+
+impl Dynamic {
+    fn of(arg: &clap::Arg) -> Option<Self> {
+        match arg.get_id().as_str() {
+            "id" | "ids" => Some(Dynamic::Ids),
+            "remote" => Some(Dynamic::Remotes),
+            "targets" => Some(Dynamic::RemotesOrIds),
+            _ => None,
+        }
+    }
+
+    /// Name of the zsh function in [`ZSH_FUNCTIONS`] completing it.
+    fn zsh_function(self) -> &'static str {
+        match self {
+            Dynamic::Ids => "_engram_ids",
+            Dynamic::Remotes => "_engram_remotes",
+            Dynamic::RemotesOrIds => "_engram_targets",
+        }
+    }
+
+    fn fish_candidates(self) -> &'static str {
+        match self {
+            Dynamic::Ids => "(engram __complete-ids (commandline -ct))",
+            Dynamic::Remotes => "(engram __complete-remotes (commandline -ct))",
+            Dynamic::RemotesOrIds => {
+                "(engram __complete-remotes (commandline -ct); engram __complete-ids (commandline -ct))"
+            }
+        }
+    }
+}
+
+const ZSH_FUNCTIONS: &str = r#"
+(( $+functions[_engram_ids] )) ||
+_engram_ids() {
+    local -a ids
+    ids=(${(f)"$(engram __complete-ids "$PREFIX" 2>/dev/null)"})
+    _describe -t engram-ids 'engram ID' ids
+}
+(( $+functions[_engram_remotes] )) ||
+_engram_remotes() {
+    local -a remotes
+    remotes=(${(f)"$(engram __complete-remotes "$PREFIX" 2>/dev/null)"})
+    _describe -t remotes 'remote' remotes
+}
+(( $+functions[_engram_targets] )) ||
+_engram_targets() {
+    _alternative 'remotes:remote:_engram_remotes' 'engram-ids:engram ID:_engram_ids'
+}
+
+"#;
+
+pub fn run(args: &CompletionsArgs, mut cmd: Command) -> Result<()> {
+    let mut script = Vec::new();
+    clap_complete::generate(args.shell, &mut cmd, "engram", &mut script);
+    let script = String::from_utf8(script)?;
+    let script = match args.shell {
+        Shell::Zsh => with_zsh_dynamic(&script, &cmd),
+        Shell::Fish => with_fish_dynamic(script, &cmd),
+        _ => script,
+    };
+    std::io::stdout().write_all(script.as_bytes())?;
+    Ok(())
+}
+
+/// Every subcommand path with a positional argument completed dynamically.
+fn dynamic_args(cmd: &Command) -> Vec<(Vec<String>, &clap::Arg, Dynamic)> {
+    fn walk<'a>(
+        cmd: &'a Command,
+        path: &mut Vec<String>,
+        out: &mut Vec<(Vec<String>, &'a clap::Arg, Dynamic)>,
+    ) {
+        for arg in cmd.get_positionals() {
+            if let Some(dynamic) = Dynamic::of(arg) {
+                out.push((path.clone(), arg, dynamic));
+            }
+        }
+        for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+            path.push(sub.get_name().to_string());
+            walk(sub, path, out);
+            path.pop();
+        }
+    }
+    let mut out = Vec::new();
+    walk(cmd, &mut Vec::new(), &mut out);
+    out
+}
+
+/// Point the zsh specs of ID and remote arguments at the helper functions,
+/// which are defined ahead of the script's entry point.
+fn with_zsh_dynamic(script: &str, cmd: &Command) -> String {
+    let mut names: Vec<(String, Dynamic)> = dynamic_args(cmd)
+        .into_iter()
+        .map(|(_, arg, dynamic)| (arg.get_id().to_string(), dynamic))
+        .collect();
+    names.dedup_by(|a, b| a.0 == b.0);
+
+    let mut out = String::with_capacity(script.len() + ZSH_FUNCTIONS.len());
+    for line in script.split_inclusive('\n') {
+        if line.starts_with("if [ \"$funcstack[1]\" = \"_engram\" ]") {
+            out.push_str(ZSH_FUNCTIONS);
+        }
+        let spec = line.trim_start_matches(['\'', '*', ':']);
+        let dynamic = names
+            .iter()
+            .find(|(name, _)| spec.starts_with(&format!("{name} -- ")));
+        match (dynamic, line.rfind(":_default'")) {
+            (Some((_, dynamic)), Some(at)) if line.starts_with('\'') => {
+                out.push_str(&line[..at + 1]);
+                out.push_str(dynamic.zsh_function());
+                out.push_str(&line[at + ":_default".len()..]);
+            }
+            _ => out.push_str(line),
+        }
+    }
+    out
+}
+
+/// Add fish completions for ID and remote arguments, with the same
+/// conditions the generated script uses for the subcommand's options.
+fn with_fish_dynamic(mut script: String, cmd: &Command) -> String {
+    script.push('\n');
+    for (path, _, dynamic) in dynamic_args(cmd) {
+        let condition = match path.as_slice() {
+            [] => continue,
+            [sub] => format!("__fish_engram_using_subcommand {sub}"),
+            [sub, nested, ..] => format!(
+                "__fish_engram_using_subcommand {sub}; and __fish_seen_subcommand_from {nested}"
+            ),
+        };
+        script.push_str(&format!(
+            "complete -c engram -n \"{condition}\" -f -a \"{}\"\n",
+            dynamic.fish_candidates()
+        ));
+    }
+    script
+}
+
+/// Print engrams whose IDs start with `prefix`, as `engram show` resolves
+/// them: a short but unambiguous ID, a tab, and the summary. Prints nothing
+/// outside an initialized repository, as completions must not fail loudly.
+pub fn complete_ids(args: &CompleteArgs) -> Result<()> {
+    let Ok(storage) = GitStorage::discover() else {
+        return Ok(());
+    };
+    let Ok(manifests) = storage.list(&ListOptions::default()) else {
+        return Ok(());
+    };
+    let mut ids: Vec<&str> = manifests.iter().map(|m| m.id.as_str()).collect();
+    ids.sort_unstable();
+
+    let mut out = std::io::stdout().lock();
+    if !args.prefix.is_empty() && "head".starts_with(&args.prefix.to_ascii_lowercase()) {
+        writeln!(out, "HEAD\tMost recent engram")?;
+    }
+    for (name, id) in storage.aliases().unwrap_or_default() {
+        if name.starts_with(&args.prefix) {
+            writeln!(out, "{name}\tAlias for {}", short_id(&ids, id.as_str()))?;
+        }
+    }
+    for m in manifests
+        .iter()
+        .filter(|m| m.id.as_str().starts_with(&args.prefix))
+    {
+        let summary = m.summary.as_deref().unwrap_or("(no summary)");
+        let summary = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+        writeln!(out, "{}\t{summary}", short_id(&ids, m.id.as_str()))?;
+    }
+    Ok(())
+}
+
+/// The shortest prefix of `id`, at least 8 characters, that no other ID in
+/// the sorted `ids` shares.
+fn short_id<'a>(ids: &[&str], id: &'a str) -> &'a str {
+    let shared = |other: &str| {
+        id.bytes()
+            .zip(other.bytes())
+            .take_while(|(a, b)| a == b)
+            .count()
+    };
+    let at = ids.binary_search(&id).unwrap_or_else(|i| i);
+    let before = at.checked_sub(1).map_or(0, |i| shared(ids[i]));
+    let after = ids.get(at + 1).map_or(0, |other| shared(other));
+    &id[..(before.max(after) + 1).clamp(8, id.len())]
+}
+
+/// Print the names of the repository's remotes starting with `prefix`.
+pub fn complete_remotes(args: &CompleteArgs) -> Result<()> {
+    let Ok(storage) = GitStorage::discover() else {
+        return Ok(());
+    };
+    let Ok(remotes) = storage.repo().remotes() else {
+        return Ok(());
+    };
+    let mut out = std::io::stdout().lock();
+    for name in remotes.iter().flatten() {
+        if name.starts_with(&args.prefix) {
+            writeln!(out, "{name}")?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use engram_core::config::{Setting, SETTINGS};
+use git2::{Config, ConfigLevel, Repository};
+
+use crate::output::OutputFormat;
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    /// Use the user-level Git config (defaults for every repository)
+    /// instead of this repository's
+    #[arg(long, global = true)]
+    pub global: bool,
+
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// List engram settings and their values
+    List(ListArgs),
+    /// Print the value of a setting, one line per value
+    Get(KeyArgs),
+    /// Set a setting, checking the value first
+    Set(SetArgs),
+    /// Remove a setting
+    Unset(KeyArgs),
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Also list settings that are not set, with their descriptions
+    #[arg(long)]
+    pub all: bool,
+}
+
+#[derive(Args)]
+pub struct KeyArgs {
+    /// Setting name, with or without the `engram.` prefix
+    pub key: String,
+}
+
+#[derive(Args)]
+pub struct SetArgs {
+    /// Setting name, with or without the `engram.` prefix
+    pub key: String,
+
+    pub value: String,
+
+    /// Add a value to a multi-valued setting instead of replacing its values
+    #[arg(long)]
+    pub add: bool,
+}
+
+pub fn run(args: &ConfigArgs, format: OutputFormat) -> Result<()> {
+    match &args.command {
+        ConfigCommand::List(list_args) => list(&read_config(args.global)?, list_args, format),
+        ConfigCommand::Get(key_args) => {
+            let setting = Setting::find(&key_args.key)?;
+            let values = setting.get(&read_config(args.global)?)?;
+            if values.is_empty() {
+                anyhow::bail!("{} is not set", setting.key);
+            }
+            for value in values {
+                println!("{value}");
+            }
+            Ok(())
+        }
+        ConfigCommand::Set(set_args) => {
+            let setting = Setting::find(&set_args.key)?;
+            let mut config = write_config(args.global)?;
+            setting.set(&mut config, &set_args.value, set_args.add)?;
+            Ok(())
+        }
+        ConfigCommand::Unset(key_args) => {
+            let setting = Setting::find(&key_args.key)?;
+            let mut config = write_config(args.global)?;
+            if !setting.unset(&mut config)? {
+                eprintln!("{} was not set", setting.key);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn list(config: &Config, args: &ListArgs, format: OutputFormat) -> Result<()> {
+    let mut rows = Vec::new();
+    for setting in SETTINGS {
+        let values = setting.get(config)?;
+        if args.all || !values.is_empty() {
+            rows.push((setting, values));
+        }
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let json: Vec<_> = rows
+                .iter()
+                .map(|(setting, values)| {
+                    serde_json::json!({
+                        "key": setting.key,
+                        "kind": setting.kind.name(),
+                        "multi": setting.multi,
+                        "description": setting.description,
+                        "values": values,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            for (setting, values) in rows {
+                if values.is_empty() {
+                    println!("{} (unset)  # {}", setting.key, setting.description);
+                }
+                for value in values {
+                    if args.all {
+                        println!("{} = {value}  # {}", setting.key, setting.description);
+                    } else {
+                        println!("{} = {value}", setting.key);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The config values are read from: the user's Git config with `--global`,
+/// otherwise the repository's (which includes the user's), or the user's
+/// when outside a repository.
+fn read_config(global: bool) -> Result<Config> {
+    if global {
+        return Ok(Config::open(&global_path()?)?);
+    }
+    match Repository::discover(".") {
+        Ok(repo) => Ok(repo.config()?.snapshot()?),
+        Err(_) => Ok(Config::open_default()?),
+    }
+}
+
+/// The single config file `set` and `unset` write to.
+fn write_config(global: bool) -> Result<Config> {
+    if global {
+        return Ok(Config::open(&global_path()?)?);
+    }
+    let repo = Repository::discover(".")
+        .context("Not inside a Git repository (use --global to change your defaults)")?;
+    Ok(repo.config()?.open_level(ConfigLevel::Local)?)
+}
+
+/// The user's `~/.gitconfig`, whether or not it exists yet.
+fn global_path() -> Result<PathBuf> {
+    if let Ok(path) = Config::find_global() {
+        return Ok(path);
+    }
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".gitconfig"))
+        .context("Cannot locate the user-level Git config: HOME is not set")
+}
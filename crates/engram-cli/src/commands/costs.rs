@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::Args;
+
+use engram_core::storage::{GitStorage, InitPolicy};
+use engram_query::index::parse_date_bound;
+use engram_query::{usage_over_time, Bucket, UsageBucket, UsageTotals};
+
+use crate::output::OutputFormat;
+
+#[derive(Args)]
+pub struct CostsArgs {
+    /// Bucket width: day, week (starting Monday) or month, all in UTC
+    #[arg(long = "by", default_value = "week")]
+    pub bucket: Bucket,
+
+    /// Only engrams created since this date (YYYY-MM-DD or RFC 3339) or
+    /// this long ago (e.g. "90d", "12w", "6m")
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only engrams created on or before this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    pub until: Option<String>,
+
+    /// Print CSV, the same as `--format csv`: a row per bucket, with an
+    /// empty agent column, each followed by a row per agent
+    #[arg(long)]
+    pub csv: bool,
+}
+
+pub fn run(args: &CostsArgs, format: OutputFormat) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::WarnAndContinue)?;
+
+    let since = args.since.as_deref().map(parse_since).transpose()?;
+    let until = args
+        .until
+        .as_deref()
+        .map(|s| parse_date_bound(s, true))
+        .transpose()?;
+    let buckets =
+        usage_over_time(&storage, args.bucket, since, until).context("Failed to total usage")?;
+
+    let format = if args.csv { OutputFormat::Csv } else { format };
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&buckets)?);
+        }
+        OutputFormat::Csv => write_csv(&buckets)?,
+        OutputFormat::Text | OutputFormat::Markdown => {
+            if buckets.is_empty() {
+                println!("No engrams found.");
+                return Ok(());
+            }
+            println!(
+                "{:<12} {:>7} {:>12} {:>12} {:>12} {:>10} {:>12}",
+                "Start", "Engrams", "Input", "Output", "Cache", "Cost", "Cost unknown"
+            );
+            for b in &buckets {
+                print_row(&b.start.format("%Y-%m-%d").to_string(), &b.totals);
+                for a in &b.agents {
+                    print_row(&format!("  {}", a.agent), &a.totals);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A `--since` value: a date, or a duration before now.
+pub(crate) fn parse_since(value: &str) -> Result<DateTime<Utc>> {
+    // A number and a unit is a duration, even one too long to go back
+    let number = value.trim().get(..value.trim().len().saturating_sub(1));
+    if number.is_some_and(|n| n.parse::<i64>().is_ok()) {
+        return super::gc::ago(value);
+    }
+    Ok(parse_date_bound(value, false)?)
+}
+
+fn print_row(label: &str, t: &UsageTotals) {
+    println!(
+        "{label:<12} {:>7} {:>12} {:>12} {:>12} {:>10} {:>12}",
+        t.engrams,
+        t.input_tokens,
+        t.output_tokens,
+        t.cache_read_tokens + t.cache_write_tokens,
+        format!("${:.2}", t.cost_usd),
+        t.cost_unknown
+    );
+}
+
+fn write_csv(buckets: &[UsageBucket]) -> Result<()> {
+    let mut out = csv::Writer::from_writer(std::io::stdout());
+    out.write_record([
+        "start",
+        "agent",
+        "engrams",
+        "input_tokens",
+        "output_tokens",
+        "cache_read_tokens",
+        "cache_write_tokens",
+        "total_tokens",
+        "cost_usd",
+        "cost_unknown",
+    ])?;
+    for b in buckets {
+        let start = b.start.format("%Y-%m-%d").to_string();
+        let rows = std::iter::once(("", &b.totals))
+            .chain(b.agents.iter().map(|a| (a.agent.as_str(), &a.totals)));
+        for (agent, t) in rows {
+            out.write_record([
+                start.clone(),
+                agent.to_string(),
+                t.engrams.to_string(),
+                t.input_tokens.to_string(),
+                t.output_tokens.to_string(),
+                t.cache_read_tokens.to_string(),
+                t.cache_write_tokens.to_string(),
+                t.total_tokens.to_string(),
+                format!("{:.4}", t.cost_usd),
+                t.cost_unknown.to_string(),
+            ])?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use engram_core::storage::{GitStorage, InitPolicy};
+use engram_query::{find_duplicates, DuplicateEngram, DuplicateGroup, DuplicateReason};
+
+use super::fetch::unindex;
+use crate::output::OutputFormat;
+
+#[derive(Args)]
+pub struct DedupeArgs {
+    /// Delete the redundant engrams, keeping the one with the most
+    /// transcript entries in each group. Without it, only list the groups
+    #[arg(long)]
+    pub apply: bool,
+}
+
+pub fn run(args: &DedupeArgs, format: OutputFormat) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::Error)?;
+
+    let groups = find_duplicates(&storage).context("Failed to look for duplicates")?;
+    if let OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv = format {
+        print_groups(&groups);
+    }
+
+    let mut deleted = Vec::new();
+    let mut failed = 0;
+    if args.apply {
+        for engram in groups.iter().flat_map(DuplicateGroup::redundant) {
+            let id = &engram.manifest.id;
+            match storage.delete(id.as_str()) {
+                Ok(()) => deleted.push(id.clone()),
+                Err(e) => {
+                    eprintln!("Failed to delete {}: {e}", &id.as_str()[..8]);
+                    failed += 1;
+                }
+            }
+        }
+        unindex(&storage, &deleted).context("Failed to update the search index")?;
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let report = serde_json::json!({
+                "applied": args.apply,
+                "groups": groups,
+                "deleted": deleted.len(),
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            if groups.is_empty() {
+                return Ok(());
+            }
+            if args.apply {
+                println!("\nDeleted {} engram(s).", deleted.len());
+            } else {
+                eprintln!("\nUse --apply to delete the redundant engrams.");
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{failed} engram(s) could not be deleted");
+    }
+    Ok(())
+}
+
+fn print_groups(groups: &[DuplicateGroup]) {
+    if groups.is_empty() {
+        println!("No duplicate engrams found.");
+        return;
+    }
+
+    let redundant: usize = groups.iter().map(|g| g.redundant().len()).sum();
+    println!(
+        "{} group(s) of duplicates, {redundant} redundant engram(s):",
+        groups.len()
+    );
+    for group in groups {
+        let reason = match group.reason {
+            DuplicateReason::SourceHash => "same source session",
+            DuplicateReason::SameRequest => "same agent and request",
+        };
+        println!("\n  {reason}:");
+        print_engram("keep", group.keep());
+        for engram in group.redundant() {
+            print_engram("drop", engram);
+        }
+    }
+}
+
+fn print_engram(action: &str, engram: &DuplicateEngram) {
+    let m = &engram.manifest;
+    println!(
+        "    {action} {} {} [{}] {:>4} entries  {}",
+        &m.id.as_str()[..8],
+        m.created_at.format("%Y-%m-%d %H:%M"),
+        m.agent.name,
+        engram.transcript_entries,
+        m.summary.as_deref().unwrap_or("(no summary)")
+    );
+}
@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use engram_core::model::Manifest;
+use engram_core::storage::{GitStorage, InitPolicy, ListOptions};
+
+use super::fetch::unindex;
+use super::push::confirm;
+
+#[derive(Args)]
+pub struct DeleteArgs {
+    /// Engram IDs (full, prefix, alias or HEAD)
+    #[arg(
+        value_name = "ID",
+        required_unless_present_any = ["agent", "before"],
+        conflicts_with_all = ["agent", "before"]
+    )]
+    pub ids: Vec<String>,
+
+    /// Delete every engram by this agent (requires --yes)
+    #[arg(long)]
+    pub agent: Option<String>,
+
+    /// Delete every engram created before this date (YYYY-MM-DD or RFC 3339)
+    /// or this long ago (e.g. "90d") (requires --yes)
+    #[arg(long)]
+    pub before: Option<String>,
+
+    /// Skip the confirmation prompt
+    #[arg(long, short)]
+    pub yes: bool,
+
+    /// Keep skipping the deleted sessions on import. By default deleting an
+    /// imported engram lets the same session be imported again.
+    #[arg(long)]
+    pub keep_dedup: bool,
+}
+
+pub fn run(args: &DeleteArgs) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::Error)?;
+
+    let batch = args.agent.is_some() || args.before.is_some();
+    if batch && !args.yes {
+        anyhow::bail!("--agent and --before delete engrams in bulk; pass --yes to confirm");
+    }
+
+    let to_delete = if batch {
+        select(&storage, args)?
+    } else {
+        resolve(&storage, &args.ids)?
+    };
+    if to_delete.is_empty() {
+        println!("No engrams match the deletion criteria.");
+        return Ok(());
+    }
+
+    println!("{} engram(s) to delete:", to_delete.len());
+    for m in &to_delete {
+        println!(
+            "  {} {} [{}] {}",
+            &m.id.as_str()[..8],
+            m.created_at.format("%Y-%m-%d %H:%M"),
+            m.agent.name,
+            m.summary.as_deref().unwrap_or("(no summary)")
+        );
+    }
+    if !(args.yes || confirm("Delete them?")?) {
+        return Ok(());
+    }
+
+    let mut deleted = Vec::new();
+    for m in &to_delete {
+        if let Err(e) = storage.delete(m.id.as_str()) {
+            eprintln!("Failed to delete {}: {e}", &m.id.as_str()[..8]);
+            continue;
+        }
+        if let (true, Some(hash)) = (args.keep_dedup, &m.source_hash) {
+            storage
+                .block_source_hash(hash)
+                .context("Failed to record the deleted source")?;
+        }
+        deleted.push(m.id.clone());
+    }
+    unindex(&storage, &deleted).context("Failed to update the search index")?;
+
+    println!("Deleted {} engram(s).", deleted.len());
+    if deleted.len() < to_delete.len() {
+        anyhow::bail!(
+            "{} engram(s) could not be deleted",
+            to_delete.len() - deleted.len()
+        );
+    }
+    Ok(())
+}
+
+/// The manifests of the engrams named by `ids`, each once.
+fn resolve(storage: &GitStorage, ids: &[String]) -> Result<Vec<Manifest>> {
+    let mut manifests: Vec<Manifest> = Vec::new();
+    for id in ids {
+        let resolved = storage
+            .resolve(id)
+            .with_context(|| format!("Failed to resolve engram '{id}'"))?;
+        if manifests.iter().any(|m| m.id.as_str() == resolved) {
+            continue;
+        }
+        let manifest = storage
+            .read_manifest(&resolved)
+            .with_context(|| format!("Failed to read engram '{resolved}'"))?;
+        manifests.push(manifest);
+    }
+    Ok(manifests)
+}
+
+/// The engrams matching every batch selector given.
+fn select(storage: &GitStorage, args: &DeleteArgs) -> Result<Vec<Manifest>> {
+    let before = args
+        .before
+        .as_deref()
+        .map(super::costs::parse_since)
+        .transpose()?;
+    let manifests = storage
+        .list(&ListOptions::default())
+        .context("Failed to list engrams")?;
+    Ok(manifests
+        .into_iter()
+        .filter(|m| {
+            before.map_or(true, |before| m.created_at < before)
+                && args
+                    .agent
+                    .as_ref()
+                    .map_or(true, |agent| m.agent.name == *agent)
+        })
+        .collect())
+}
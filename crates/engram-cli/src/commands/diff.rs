@@ -1,35 +1,61 @@
 use anyhow::{Context, Result};
 use clap::Args;
 
+use engram_core::model::EngramId;
 use engram_core::storage::GitStorage;
-use engram_query::diff_engrams;
+use engram_query::{compare_engrams, diff_engrams, EngramComparison, EngramDiff};
 
-use crate::output::OutputFormat;
+use crate::output::{theme, OutputFormat};
 
 #[derive(Args)]
 pub struct DiffArgs {
-    /// First engram ID (or prefix)
-    pub id_a: String,
+    /// Engram IDs (full, prefix, alias, HEAD or HEAD~N) to compare; more
+    /// than two are shown side by side as a matrix
+    #[arg(num_args = 2.., required = true, value_name = "ID")]
+    pub ids: Vec<String>,
 
-    /// Second engram ID (or prefix)
-    pub id_b: String,
+    /// List the differing dead ends, decisions, goals and tool counts
+    /// instead of only summarizing them
+    #[arg(long)]
+    pub full: bool,
+
+    /// Show two engrams as a matrix too
+    #[arg(long)]
+    pub matrix: bool,
 }
 
 pub fn run(args: &DiffArgs, format: OutputFormat) -> Result<()> {
     let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
 
+    if args.ids.len() > 2 || args.matrix {
+        let ids = args
+            .ids
+            .iter()
+            .map(|id| {
+                storage
+                    .resolve(id)
+                    .map(EngramId)
+                    .with_context(|| format!("Failed to find engram '{id}'"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let comparison = compare_engrams(&storage, &ids)?;
+        return print_comparison(&comparison, format);
+    }
+
     // Resolve IDs through storage to get full EngramIds
-    let data_a = storage
-        .read(&args.id_a)
+    let id_a = storage
+        .resolve(&args.ids[0])
+        .map(EngramId)
         .context("Failed to find first engram")?;
-    let data_b = storage
-        .read(&args.id_b)
+    let id_b = storage
+        .resolve(&args.ids[1])
+        .map(EngramId)
         .context("Failed to find second engram")?;
 
-    let diff = diff_engrams(&storage, &data_a.manifest.id, &data_b.manifest.id)?;
+    let diff = diff_engrams(&storage, &id_a, &id_b)?;
 
     match format {
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Ndjson => {
             let json = serde_json::json!({
                 "id_a": diff.id_a.as_str(),
                 "id_b": diff.id_b.as_str(),
@@ -38,12 +64,23 @@ pub fn run(args: &DiffArgs, format: OutputFormat) -> Result<()> {
                 "only_b_files": diff.only_b_files,
                 "token_delta": diff.token_delta,
                 "cost_delta": diff.cost_delta,
+                "dead_ends_only_a": diff.dead_ends_only_a,
+                "dead_ends_only_b": diff.dead_ends_only_b,
+                "decisions_only_a": diff.decisions_only_a,
+                "decisions_only_b": diff.decisions_only_b,
+                "intent_changed": diff.intent_changed.as_ref().map(|(a, b)| {
+                    serde_json::json!({ "a": a, "b": b })
+                }),
+                "tool_call_counts": diff.tool_call_counts.iter().map(|(tool, (a, b))| {
+                    (tool.clone(), serde_json::json!({ "a": a, "b": b }))
+                }).collect::<serde_json::Map<_, _>>(),
+                "transcript_entry_delta": diff.transcript_entry_delta,
             });
             println!("{}", serde_json::to_string_pretty(&json)?);
         }
-        OutputFormat::Text | OutputFormat::Markdown => {
-            let short_a = &diff.id_a.as_str()[..8];
-            let short_b = &diff.id_b.as_str()[..8];
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            let short_a = theme::ID.paint(&diff.id_a.as_str()[..8]);
+            let short_b = theme::ID.paint(&diff.id_b.as_str()[..8]);
             println!("Comparing {short_a} vs {short_b}\n");
 
             if !diff.common_files.is_empty() {
@@ -55,23 +92,222 @@ pub fn run(args: &DiffArgs, format: OutputFormat) -> Result<()> {
             if !diff.only_a_files.is_empty() {
                 println!("Only in {short_a} ({}):", diff.only_a_files.len());
                 for f in &diff.only_a_files {
-                    println!("  {f}");
+                    println!("  {}", theme::REMOVED.paint(f));
                 }
             }
             if !diff.only_b_files.is_empty() {
                 println!("Only in {short_b} ({}):", diff.only_b_files.len());
                 for f in &diff.only_b_files {
-                    println!("  {f}");
+                    println!("  {}", theme::ADDED.paint(f));
                 }
             }
 
             println!();
-            println!("Token delta: {:+}", diff.token_delta);
+            println!(
+                "Token delta: {}",
+                delta(diff.token_delta as f64, format!("{:+}", diff.token_delta))
+            );
             if let Some(cost) = diff.cost_delta {
-                println!("Cost delta:  {:+.4}", cost);
+                println!("Cost delta:  {}", delta(cost, format!("{cost:+.4}")));
+            }
+            println!(
+                "Transcript delta: {:+} entries",
+                diff.transcript_entry_delta
+            );
+
+            print_reasoning(&diff, &short_a, &short_b, args.full);
+        }
+    }
+
+    Ok(())
+}
+
+/// A difference as `text`, red when `value` grew and green when it shrank.
+fn delta(value: f64, text: String) -> String {
+    if value > 0.0 {
+        theme::REMOVED.paint(text)
+    } else if value < 0.0 {
+        theme::ADDED.paint(text)
+    } else {
+        text
+    }
+}
+
+fn print_reasoning(diff: &EngramDiff, short_a: &str, short_b: &str, full: bool) {
+    let changed_tools: Vec<_> = diff
+        .tool_call_counts
+        .iter()
+        .filter(|(_, (a, b))| full || a != b)
+        .collect();
+    let differs = !diff.dead_ends_only_a.is_empty()
+        || !diff.dead_ends_only_b.is_empty()
+        || !diff.decisions_only_a.is_empty()
+        || !diff.decisions_only_b.is_empty()
+        || diff.intent_changed.is_some()
+        || !changed_tools.is_empty();
+    if !differs {
+        return;
+    }
+    println!();
+
+    if !full {
+        if diff.intent_changed.is_some() {
+            println!("Interpreted goal changed");
+        }
+        for (label, a, b) in [
+            (
+                "Dead ends",
+                diff.dead_ends_only_a.len(),
+                diff.dead_ends_only_b.len(),
+            ),
+            (
+                "Decisions",
+                diff.decisions_only_a.len(),
+                diff.decisions_only_b.len(),
+            ),
+        ] {
+            if a + b > 0 {
+                println!("{label}: {a} only in {short_a}, {b} only in {short_b}");
+            }
+        }
+        if !changed_tools.is_empty() {
+            let tools: Vec<_> = changed_tools
+                .iter()
+                .map(|(tool, (a, b))| format!("{tool} {a} -> {b}"))
+                .collect();
+            println!("Tool calls: {}", tools.join(", "));
+        }
+        println!("(use --full for details)");
+        return;
+    }
+
+    if let Some((a, b)) = &diff.intent_changed {
+        println!("Interpreted goal:");
+        println!("  {short_a}: {a}");
+        println!("  {short_b}: {b}");
+    }
+    for (short, dead_ends) in [
+        (short_a, &diff.dead_ends_only_a),
+        (short_b, &diff.dead_ends_only_b),
+    ] {
+        if !dead_ends.is_empty() {
+            println!("Dead ends only in {short} ({}):", dead_ends.len());
+            for de in dead_ends {
+                println!("  - {}: {}", de.approach, de.reason);
             }
         }
     }
+    for (short, decisions) in [
+        (short_a, &diff.decisions_only_a),
+        (short_b, &diff.decisions_only_b),
+    ] {
+        if !decisions.is_empty() {
+            println!("Decisions only in {short} ({}):", decisions.len());
+            for d in decisions {
+                println!("  - {}: {}", d.description, d.rationale);
+            }
+        }
+    }
+    if !changed_tools.is_empty() {
+        println!("Tool calls ({short_a} -> {short_b}):");
+        for (tool, (a, b)) in changed_tools {
+            println!("  {tool}: {a} -> {b}");
+        }
+    }
+}
+
+fn print_comparison(cmp: &EngramComparison, format: OutputFormat) -> Result<()> {
+    if matches!(format, OutputFormat::Json | OutputFormat::Ndjson) {
+        println!("{}", serde_json::to_string_pretty(cmp)?);
+        return Ok(());
+    }
+
+    const LABEL: usize = 28;
+    const COLUMN: usize = 13;
+    let short_ids: Vec<_> = cmp
+        .engrams
+        .iter()
+        .map(|e| &e.id.as_str()[..8.min(e.id.as_str().len())])
+        .collect();
+    let row = |label: &str, cells: Vec<String>| {
+        let cells: Vec<_> = cells.iter().map(|c| format!("{c:>COLUMN$}")).collect();
+        println!("{label:<LABEL$}{}", cells.join(""));
+    };
+    let truncate = |text: &str, max: usize| {
+        if text.chars().count() > max {
+            let cut: String = text.chars().take(max - 3).collect();
+            format!("{cut}...")
+        } else {
+            text.to_string()
+        }
+    };
+
+    println!("Comparing {} engrams\n", cmp.engrams.len());
+    row("", short_ids.iter().map(|s| s.to_string()).collect());
+    row(
+        "Agent",
+        cmp.engrams
+            .iter()
+            .map(|e| truncate(&e.agent, COLUMN - 2))
+            .collect(),
+    );
+    row(
+        "Model",
+        cmp.engrams
+            .iter()
+            .map(|e| truncate(e.model.as_deref().unwrap_or("-"), COLUMN - 2))
+            .collect(),
+    );
+    row(
+        "Tokens",
+        cmp.engrams
+            .iter()
+            .map(|e| e.total_tokens.to_string())
+            .collect(),
+    );
+    row(
+        "Cost",
+        cmp.engrams
+            .iter()
+            .map(|e| e.cost_usd.map_or("-".into(), |c| format!("${c:.2}")))
+            .collect(),
+    );
+    row(
+        "Duration",
+        cmp.engrams
+            .iter()
+            .map(|e| e.duration_secs.map_or("-".into(), |s| format!("{s}s")))
+            .collect(),
+    );
+    row(
+        "Files touched",
+        cmp.engrams
+            .iter()
+            .map(|e| e.files_touched.to_string())
+            .collect(),
+    );
+
+    for (title, rows) in [("Files", &cmp.files), ("Dead ends", &cmp.dead_ends)] {
+        if rows.is_empty() {
+            continue;
+        }
+        println!("\n{title} ({}):", rows.len());
+        for p in rows {
+            let marks = p
+                .present
+                .iter()
+                .map(|present| if *present { "x" } else { "." }.to_string())
+                .collect();
+            row(&format!("  {}", truncate(&p.name, LABEL - 3)), marks);
+        }
+    }
+
+    if let Some(i) = cmp.most_files {
+        println!(
+            "\nMost files: {} ({} files)",
+            short_ids[i], cmp.engrams[i].files_touched
+        );
+    }
 
     Ok(())
 }
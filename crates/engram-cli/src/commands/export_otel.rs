@@ -0,0 +1,109 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::export::trace::{SpanData, SpanExporter as _};
+use opentelemetry_sdk::trace::{Span, SpanProcessor, TracerProvider};
+use opentelemetry_sdk::Resource;
+
+use engram_core::storage::GitStorage;
+
+#[derive(Args)]
+#[command(after_help = "\
+Each engram becomes a trace: an `engram.session` span with the agent, tokens,
+cost and engram ID, a child span per tool call, and events for dead ends and
+decisions. Spans keep the times the engram recorded. They are sent as
+OTLP/HTTP protobuf; OTEL_SERVICE_NAME names the service (default: engram)
+and OTEL_EXPORTER_OTLP_HEADERS adds headers, e.g. for authentication.")]
+pub struct ExportOtelArgs {
+    /// Engram IDs (full, prefix or alias)
+    #[arg(required = true)]
+    pub ids: Vec<String>,
+
+    /// OTLP/HTTP collector to send spans to, e.g. http://localhost:4318
+    /// (`/v1/traces` is added unless the URL already ends with it)
+    #[arg(long, value_name = "URL", env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    pub endpoint: String,
+}
+
+pub fn run(args: &ExportOtelArgs) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+    let engrams = args
+        .ids
+        .iter()
+        .map(|id| {
+            let resolved = storage
+                .resolve(id)
+                .with_context(|| format!("Failed to resolve engram '{id}'"))?;
+            storage
+                .read(&resolved)
+                .with_context(|| format!("Failed to read engram '{resolved}'"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let spans = Collected::default();
+    let provider = TracerProvider::builder()
+        .with_span_processor(spans.clone())
+        .build();
+    let tracer = provider.tracer("engram");
+    for data in &engrams {
+        engram_sdk::otel::export_engram(data, &tracer);
+    }
+    let batch = std::mem::take(&mut *spans.0.lock().unwrap());
+    let count = batch.len();
+
+    let url = traces_url(&args.endpoint);
+    let mut exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&url)
+        .build()
+        .with_context(|| format!("Failed to set up an OTLP exporter for {url}"))?;
+    let service = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "engram".to_string());
+    exporter.set_resource(&Resource::new([KeyValue::new("service.name", service)]));
+    // The SDK's own processors only log export failures; export in one
+    // batch instead so they reach the user
+    let result = futures_executor::block_on(exporter.export(batch));
+    exporter.shutdown();
+    result.map_err(|e| anyhow!("Failed to export spans to {url}: {e}"))?;
+
+    if !crate::output::quiet() {
+        eprintln!(
+            "Exported {} engram(s) as {count} span(s) to {url}.",
+            engrams.len()
+        );
+    }
+    Ok(())
+}
+
+/// The OTLP/HTTP traces URL for a collector's base URL.
+fn traces_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{endpoint}/v1/traces")
+    }
+}
+
+/// Keeps finished spans, to be exported together.
+#[derive(Clone, Debug, Default)]
+struct Collected(Arc<Mutex<Vec<SpanData>>>);
+
+impl SpanProcessor for Collected {
+    fn on_start(&self, _span: &mut Span, _cx: &opentelemetry::Context) {}
+
+    fn on_end(&self, span: SpanData) {
+        self.0.lock().unwrap().push(span);
+    }
+
+    fn force_flush(&self) -> opentelemetry::trace::TraceResult<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> opentelemetry::trace::TraceResult<()> {
+        Ok(())
+    }
+}
@@ -1,37 +1,184 @@
 use anyhow::{Context, Result};
 use clap::Args;
 
+use engram_core::model::EngramId;
 use engram_core::storage::GitStorage;
-use engram_protocol::{fetch_engrams, SyncOptions};
+use engram_protocol::{all_remotes, fetch_from_remotes, FetchResult, RemoteOutcome, SyncOptions};
+use engram_query::SearchEngine;
+
+use crate::output::progress::SyncBar;
 
 #[derive(Args)]
 pub struct FetchArgs {
     /// Remote name (default: origin)
-    #[arg(default_value = "origin")]
-    pub remote: String,
+    #[arg(conflicts_with = "all_remotes")]
+    pub remote: Option<String>,
+
+    #[command(flatten)]
+    pub remotes: RemotesArgs,
+
+    /// Delete local engrams the remote has dropped since they were last
+    /// synced with it
+    #[arg(long)]
+    pub prune: bool,
 
     /// Dry run — show what would be fetched
     #[arg(long)]
     pub dry_run: bool,
 }
 
+/// Syncing with several remotes in one command.
+#[derive(Args)]
+pub struct RemotesArgs {
+    /// Sync with every remote of the repository, one after another
+    #[arg(long)]
+    pub all_remotes: bool,
+
+    /// Fail if any remote fails; by default only if all of them do
+    #[arg(long)]
+    pub require_all: bool,
+}
+
 pub fn run(args: &FetchArgs) -> Result<()> {
     let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
+    let remotes = fetch_remotes(&storage, args.remote.as_deref(), &args.remotes)?;
+    let bar = SyncBar::new("Fetching", crate::output::quiet());
     let opts = SyncOptions {
         dry_run: args.dry_run,
+        progress: bar.callback(),
+        prune: args.prune,
         ..Default::default()
     };
 
-    let result = fetch_engrams(storage.repo(), &args.remote, &opts)?;
+    let outcomes = fetch_from_remotes(storage.repo(), &remotes, &opts);
+    bar.finish();
+    let fetched = finish(outcomes, args.remotes.require_all, |result| {
+        if args.dry_run {
+            eprintln!("Would fetch engram refs from {}", result.remote);
+        } else {
+            print_fetched(result);
+        }
+    })?;
+    if fetched.len() > 1 && !args.dry_run {
+        print_fetched_total(&fetched);
+    }
+
+    let pruned: Vec<_> = fetched.iter().flat_map(|r| &r.pruned).collect();
+    unindex(&storage, pruned)?;
+
+    Ok(())
+}
 
-    if args.dry_run {
-        eprintln!("Would fetch engram refs from {}", result.remote);
+/// The remotes `engram fetch` and `engram pull` read from.
+pub(crate) fn fetch_remotes(
+    storage: &GitStorage,
+    remote: Option<&str>,
+    args: &RemotesArgs,
+) -> Result<Vec<String>> {
+    if args.all_remotes {
+        let remotes = all_remotes(storage.repo())?;
+        if remotes.is_empty() {
+            anyhow::bail!("This repository has no remotes");
+        }
+        Ok(remotes)
     } else {
+        Ok(vec![remote.unwrap_or("origin").to_string()])
+    }
+}
+
+/// Report each remote's outcome, printing successes with `print`, and
+/// return the successful results. Fails if every remote failed, or with
+/// `require_all` if any did. A lone remote's error is returned as is.
+pub(crate) fn finish<T>(
+    outcomes: Vec<RemoteOutcome<T>>,
+    require_all: bool,
+    print: impl Fn(&T),
+) -> Result<Vec<T>> {
+    let total = outcomes.len();
+    if total == 1 {
+        let outcome = outcomes.into_iter().next().expect("one outcome");
+        let result = outcome.result?;
+        print(&result);
+        return Ok(vec![result]);
+    }
+
+    let mut succeeded = Vec::new();
+    let mut failed = Vec::new();
+    for outcome in outcomes {
+        match outcome.result {
+            Ok(result) => {
+                print(&result);
+                succeeded.push(result);
+            }
+            Err(e) => {
+                eprintln!("{}: failed: {e}", outcome.remote);
+                failed.push(outcome.remote);
+            }
+        }
+    }
+    if succeeded.is_empty() || (require_all && !failed.is_empty()) {
+        anyhow::bail!(
+            "{} of {total} remotes failed: {}",
+            failed.len(),
+            failed.join(", ")
+        );
+    }
+    Ok(succeeded)
+}
+
+/// Summarize a fetch, shared with `engram pull`.
+pub(crate) fn print_fetched(result: &FetchResult) {
+    let merged = match result.refs_merged {
+        0 => String::new(),
+        n => format!(", {n} merged"),
+    };
+    eprintln!(
+        "Fetched {} engram ref(s) from {} ({} new, {} updated{merged}, {} unchanged)",
+        result.refs_fetched,
+        result.remote,
+        result.refs_created,
+        result.refs_updated,
+        result.refs_unchanged
+    );
+    for id in &result.diverged {
         eprintln!(
-            "Fetched {} new engram ref(s) from {}",
-            result.refs_fetched, result.remote
+            "Kept {}: it was changed differently here and on {}",
+            id.as_str().get(..8).unwrap_or(id.as_str()),
+            result.remote
         );
     }
+    if !result.pruned.is_empty() {
+        eprintln!(
+            "Pruned {} engram(s) deleted on {}",
+            result.pruned.len(),
+            result.remote
+        );
+    }
+}
 
+/// Drop deleted engrams from the search index, shared with `engram push`.
+pub(crate) fn unindex<'a>(
+    storage: &GitStorage,
+    ids: impl IntoIterator<Item = &'a EngramId>,
+) -> Result<()> {
+    let mut ids = ids.into_iter().peekable();
+    if ids.peek().is_none() {
+        return Ok(());
+    }
+    let engine = SearchEngine::open(storage)?;
+    for id in ids {
+        engine.remove_engram(id.as_str())?;
+    }
     Ok(())
 }
+
+/// Total over fetches from several remotes.
+pub(crate) fn print_fetched_total(results: &[FetchResult]) {
+    let (fetched, created) = results
+        .iter()
+        .fold((0, 0), |(f, c), r| (f + r.refs_fetched, c + r.refs_created));
+    eprintln!(
+        "Fetched {fetched} engram ref(s) ({created} new) from {} remotes",
+        results.len()
+    );
+}
@@ -1,7 +1,15 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+
 use anyhow::{Context, Result};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use clap::Args;
-use engram_core::storage::{GitStorage, ListOptions};
+use engram_core::config::TrivialThresholds;
+use engram_core::model::Manifest;
+use engram_core::storage::{GitStorage, InitPolicy, ListOptions, SpaceUsage};
+
+use crate::output::progress::human_bytes;
+use crate::output::OutputFormat;
 
 #[derive(Args)]
 pub struct GcArgs {
@@ -9,25 +17,44 @@ pub struct GcArgs {
     #[arg(long)]
     pub older_than: Option<String>,
 
-    /// Preview what would be deleted without actually deleting
+    /// Delete trivial engrams: no file changes, and fewer tokens and seconds
+    /// than engram.trivial.maxTokens and engram.trivial.maxSeconds. With
+    /// --older-than, only trivial engrams older than that
+    #[arg(long)]
+    pub trivial: bool,
+
+    /// Preview what would be deleted, and how much space it takes, without
+    /// actually deleting
     #[arg(long)]
     pub dry_run: bool,
 
+    /// After deleting, run `git gc --prune=now` so the space is freed right
+    /// away. This prunes every unreachable object in the repository, not
+    /// only the deleted engrams'
+    #[arg(long, conflicts_with = "dry_run")]
+    pub prune_objects: bool,
+
     /// Skip confirmation prompt
     #[arg(long, short)]
     pub yes: bool,
 }
 
-pub fn run(args: &GcArgs) -> Result<()> {
+pub fn run(args: &GcArgs, format: OutputFormat) -> Result<()> {
     let storage = GitStorage::discover().context("Not inside a Git repository")?;
 
-    if !storage.is_initialized() {
-        anyhow::bail!("Engram is not initialized. Run `engram init` first.");
-    }
+    storage.ensure_initialized(InitPolicy::Error)?;
 
-    let cutoff = if let Some(duration_str) = &args.older_than {
-        let dur = parse_duration(duration_str)?;
-        Some(Utc::now() - dur)
+    let cutoff = match &args.older_than {
+        Some(duration_str) => Some(ago(duration_str)?),
+        None => None,
+    };
+
+    let thresholds = if args.trivial {
+        let config = storage
+            .repo()
+            .config()
+            .context("Failed to read git config")?;
+        Some(TrivialThresholds::load(&config))
     } else {
         None
     };
@@ -39,61 +66,162 @@ pub fn run(args: &GcArgs) -> Result<()> {
     let to_delete: Vec<_> = manifests
         .iter()
         .filter(|m| {
-            if let Some(cutoff) = cutoff {
-                m.created_at < cutoff
-            } else {
-                false
+            if cutoff.is_none() && thresholds.is_none() {
+                return false;
+            }
+            if cutoff.is_some_and(|cutoff| m.created_at >= cutoff) {
+                return false;
+            }
+            match &thresholds {
+                Some(t) => {
+                    t.might_be_trivial(m) && storage.is_trivial(m.id.as_str(), t).unwrap_or(false)
+                }
+                None => true,
             }
         })
         .collect();
 
+    let ids: Vec<_> = to_delete.iter().map(|m| m.id.clone()).collect();
+    let usage = storage
+        .space_usage(&ids)
+        .context("Failed to measure engram sizes")?;
+    let confirmed = !args.dry_run && args.yes;
+
+    if let OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv = format {
+        print_candidates(&to_delete, &usage, args.dry_run);
+    }
+
+    let mut deleted = 0;
+    if confirmed {
+        for m in &to_delete {
+            match storage.delete(m.id.as_str()) {
+                Ok(()) => deleted += 1,
+                Err(e) => eprintln!("Failed to delete {}: {e}", &m.id.as_str()[..8]),
+            }
+        }
+    }
+    let pruned = confirmed && deleted > 0 && args.prune_objects;
+    if pruned {
+        prune_objects(&storage)?;
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let report = serde_json::json!({
+                "dry_run": args.dry_run,
+                "candidates": to_delete
+                    .iter()
+                    .zip(&usage.engrams)
+                    .map(|(m, (_, bytes))| serde_json::json!({
+                        "id": m.id.as_str(),
+                        "created_at": m.created_at,
+                        "agent": m.agent.name,
+                        "summary": m.summary,
+                        "bytes": bytes,
+                    }))
+                    .collect::<Vec<_>>(),
+                "by_agent": by_agent(&to_delete),
+                "total_bytes": usage.total_bytes,
+                "reclaimable_bytes": usage.reclaimable_bytes,
+                "shared_blobs": usage.shared_blobs,
+                "deleted": deleted,
+                "pruned": pruned,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            if to_delete.is_empty() {
+                return Ok(());
+            }
+            if args.dry_run {
+                println!("\nDry run — no engrams were deleted.");
+            } else if confirmed {
+                println!("\nDeleted {deleted} engram(s).");
+                if pruned {
+                    println!("Pruned unreachable objects with `git gc --prune=now`.");
+                } else if deleted > 0 && !crate::output::quiet() {
+                    println!(
+                        "Their objects stay on disk until Git prunes them: run \
+                         `git gc --prune=now`, or pass --prune-objects."
+                    );
+                }
+            }
+        }
+    }
+
+    if !args.dry_run && !args.yes && !to_delete.is_empty() {
+        eprintln!("\nUse --yes to confirm deletion.");
+    }
+    Ok(())
+}
+
+fn print_candidates(to_delete: &[&Manifest], usage: &SpaceUsage, dry_run: bool) {
     if to_delete.is_empty() {
         println!("No engrams match the deletion criteria.");
-        return Ok(());
+        return;
     }
 
     println!(
         "{} engram(s) to {}:",
         to_delete.len(),
-        if args.dry_run {
+        if dry_run {
             "delete (dry run)"
         } else {
             "delete"
         }
     );
-    for m in &to_delete {
+    for (m, (_, bytes)) in to_delete.iter().zip(&usage.engrams) {
         println!(
-            "  {} {} [{}] {}",
+            "  {} {} [{}] {:>9} {}",
             &m.id.as_str()[..8],
             m.created_at.format("%Y-%m-%d %H:%M"),
             m.agent.name,
+            human_bytes(*bytes),
             m.summary.as_deref().unwrap_or("(no summary)")
         );
     }
 
-    if args.dry_run {
-        println!("\nDry run — no engrams were deleted.");
-        return Ok(());
-    }
+    let agents: Vec<_> = by_agent(to_delete)
+        .into_iter()
+        .map(|(agent, count)| format!("{agent} {count}"))
+        .collect();
+    println!("\nBy agent: {}", agents.join(", "));
+    let shared = match usage.shared_blobs {
+        0 => String::new(),
+        n => format!(" ({n} blob(s) shared with other engrams, counted once)"),
+    };
+    println!(
+        "Size: {}{shared}, {} reclaimable",
+        human_bytes(usage.total_bytes),
+        human_bytes(usage.reclaimable_bytes)
+    );
+}
 
-    if !args.yes {
-        eprintln!("\nUse --yes to confirm deletion.");
-        return Ok(());
+/// How many of the engrams each agent made.
+fn by_agent(manifests: &[&Manifest]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for m in manifests {
+        *counts.entry(m.agent.name.clone()).or_default() += 1;
     }
+    counts
+}
 
-    let mut deleted = 0;
-    for m in &to_delete {
-        match storage.delete(m.id.as_str()) {
-            Ok(()) => deleted += 1,
-            Err(e) => eprintln!("Failed to delete {}: {e}", &m.id.as_str()[..8]),
-        }
+/// Run `git gc --prune=now` on the repository, so the objects of deleted
+/// engrams leave the disk.
+fn prune_objects(storage: &GitStorage) -> Result<()> {
+    let status = Command::new("git")
+        .arg("--git-dir")
+        .arg(storage.repo().path())
+        .args(["gc", "--prune=now", "--quiet"])
+        .status()
+        .context("Failed to run `git gc`")?;
+    if !status.success() {
+        anyhow::bail!("`git gc --prune=now` failed ({status})");
     }
-
-    println!("\nDeleted {deleted} engram(s).");
     Ok(())
 }
 
-fn parse_duration(s: &str) -> Result<Duration> {
+pub(crate) fn parse_duration(s: &str) -> Result<Duration> {
     let s = s.trim();
     if s.is_empty() {
         anyhow::bail!("Empty duration string");
@@ -104,13 +232,23 @@ fn parse_duration(s: &str) -> Result<Duration> {
         .parse()
         .with_context(|| format!("Invalid duration number: {num_str}"))?;
 
-    match unit {
-        "d" => Ok(Duration::days(num)),
-        "w" => Ok(Duration::weeks(num)),
-        "m" => Ok(Duration::days(num * 30)),
-        "y" => Ok(Duration::days(num * 365)),
+    let days_per_unit = match unit {
+        "d" => 1,
+        "w" => 7,
+        "m" => 30,
+        "y" => 365,
         _ => anyhow::bail!(
             "Unknown duration unit '{unit}'. Use d (days), w (weeks), m (months), y (years)."
         ),
-    }
+    };
+    num.checked_mul(days_per_unit)
+        .and_then(Duration::try_days)
+        .with_context(|| format!("Duration '{s}' is too long"))
+}
+
+/// The time a duration such as "30d" before now.
+pub(crate) fn ago(s: &str) -> Result<DateTime<Utc>> {
+    Utc::now()
+        .checked_sub_signed(parse_duration(s)?)
+        .with_context(|| format!("Duration '{s}' is too long"))
 }
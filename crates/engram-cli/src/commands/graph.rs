@@ -1,8 +1,14 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
 use anyhow::{Context, Result};
 use clap::Args;
 
 use engram_core::storage::GitStorage;
-use engram_query::build_graph;
+use engram_query::graph::NodeType;
+use engram_query::index::parse_date_bound;
+use engram_query::{build_graph_with, GraphFilter, GraphOptions};
 
 use crate::output::OutputFormat;
 
@@ -11,43 +17,158 @@ pub struct GraphArgs {
     /// Center node (e.g. "file:src/auth.rs" or engram ID prefix)
     pub node: Option<String>,
 
+    /// Center node, as an engram ID (or prefix), file path or node ID;
+    /// same as the positional NODE
+    #[arg(long, conflicts_with = "node")]
+    pub focus: Option<String>,
+
     /// Traversal depth from center node
     #[arg(long, default_value = "2")]
     pub depth: usize,
 
-    /// Output DOT format for Graphviz
+    /// Only these node types: engram, file, agent, commit (repeatable)
+    #[arg(long = "only", value_name = "TYPE")]
+    pub only: Vec<NodeType>,
+
+    /// Leave out these node types and their edges (repeatable)
+    #[arg(long = "exclude", value_name = "TYPE")]
+    pub exclude: Vec<NodeType>,
+
+    /// Only engrams created on or after this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only engrams created on or before this date (YYYY-MM-DD or RFC 3339)
     #[arg(long)]
+    pub until: Option<String>,
+
+    /// Only engrams by this agent (repeatable)
+    #[arg(long = "agent", value_name = "NAME")]
+    pub agents: Vec<String>,
+
+    /// Show engrams that lineage refers to but that are not in this
+    /// repository as placeholder nodes, instead of dropping those links
+    #[arg(long)]
+    pub show_missing: bool,
+
+    /// Output DOT format for Graphviz
+    #[arg(long, conflicts_with = "mermaid")]
     pub dot: bool,
+
+    /// Output a Mermaid flowchart
+    #[arg(long)]
+    pub mermaid: bool,
+
+    /// Render to this file (.svg, .png or .pdf) with Graphviz `dot`, or
+    /// with mermaid-cli `mmdc` when --mermaid is given
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Open the rendered file with the system viewer
+    #[arg(long, requires = "output")]
+    pub open: bool,
+}
+
+/// A program that turns graph source into an image.
+struct Renderer {
+    program: &'static str,
+    /// Extension for the source file written when the program is missing
+    source_extension: &'static str,
+    install_hint: &'static str,
+}
+
+const GRAPHVIZ: Renderer = Renderer {
+    program: "dot",
+    source_extension: "dot",
+    install_hint: "Install Graphviz (https://graphviz.org/download/), then run `dot -T{format} {source} -o {output}`",
+};
+
+const MERMAID_CLI: Renderer = Renderer {
+    program: "mmdc",
+    source_extension: "mmd",
+    install_hint: "Install mermaid-cli (`npm install -g @mermaid-js/mermaid-cli`), then run `mmdc -i {source} -o {output}`",
+};
+
+impl Renderer {
+    fn args(&self, format: &str, output: &Path) -> Vec<std::ffi::OsString> {
+        let mut args: Vec<std::ffi::OsString> = match self.program {
+            "dot" => vec![format!("-T{format}").into()],
+            _ => vec!["-i".into(), "-".into()],
+        };
+        args.push("-o".into());
+        args.push(output.into());
+        args
+    }
 }
 
 pub fn run(args: &GraphArgs, format: OutputFormat) -> Result<()> {
     let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
 
-    let full_graph = build_graph(&storage)?;
+    let full_graph = build_graph_with(
+        &storage,
+        &GraphOptions {
+            show_missing: args.show_missing,
+        },
+    )?;
 
-    let graph = if let Some(center) = &args.node {
-        // Convert user-friendly node references to internal IDs
-        let node_id = if center.starts_with("file:") || center.starts_with("agent:") {
-            center.clone()
-        } else {
-            // Assume engram ID prefix
-            format!("engram:{center}")
-        };
-        full_graph.subgraph(&node_id, args.depth)
-    } else {
-        full_graph
+    let focus = match args.focus.as_ref().or(args.node.as_ref()) {
+        Some(reference) => Some(
+            full_graph
+                .resolve_node(reference)
+                .with_context(|| format!("No graph node matches '{reference}'"))?,
+        ),
+        None => None,
+    };
+    let filter = GraphFilter {
+        node_types: (!args.only.is_empty()).then(|| args.only.clone()),
+        exclude_node_types: args.exclude.clone(),
+        since: args
+            .since
+            .as_deref()
+            .map(|s| parse_date_bound(s, false))
+            .transpose()?,
+        until: args
+            .until
+            .as_deref()
+            .map(|s| parse_date_bound(s, true))
+            .transpose()?,
+        agents: args.agents.clone(),
+        focus,
+        depth: args.depth,
     };
+    let graph = full_graph.filter(&filter);
+    if !crate::output::quiet() {
+        eprintln!(
+            "Graph: {} nodes, {} edges; after filtering: {} nodes, {} edges",
+            full_graph.nodes.len(),
+            full_graph.edges.len(),
+            graph.nodes.len(),
+            graph.edges.len()
+        );
+    }
 
+    if let Some(output) = &args.output {
+        let (renderer, source) = if args.mermaid {
+            (&MERMAID_CLI, graph.to_mermaid())
+        } else {
+            (&GRAPHVIZ, graph.to_dot())
+        };
+        return render(renderer, &source, output, args.open);
+    }
     if args.dot {
         print!("{}", graph.to_dot());
         return Ok(());
     }
+    if args.mermaid {
+        print!("{}", graph.to_mermaid());
+        return Ok(());
+    }
 
     match format {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&graph)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&graph.to_json())?);
         }
-        OutputFormat::Text | OutputFormat::Markdown => {
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
             println!(
                 "Context graph: {} nodes, {} edges",
                 graph.nodes.len(),
@@ -64,9 +185,95 @@ pub fn run(args: &GraphArgs, format: OutputFormat) -> Result<()> {
                 }
             }
             println!();
-            println!("Use --dot to output Graphviz format");
+            println!(
+                "Use --dot for Graphviz, --mermaid for a Mermaid flowchart, or --output graph.svg to render"
+            );
         }
     }
 
     Ok(())
 }
+
+/// Render `source` to `output`, whose extension picks the image format. When
+/// the renderer isn't installed, write the source next to `output` instead.
+fn render(renderer: &Renderer, source: &str, output: &Path, open: bool) -> Result<()> {
+    let format = output
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .filter(|ext| matches!(ext.as_str(), "svg" | "png" | "pdf"))
+        .with_context(|| {
+            format!(
+                "Can't tell the image format of {}; use a .svg, .png or .pdf file",
+                output.display()
+            )
+        })?;
+
+    let spawned = Command::new(renderer.program)
+        .args(renderer.args(&format, output))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let source_path = output.with_extension(renderer.source_extension);
+            std::fs::write(&source_path, source)
+                .with_context(|| format!("Failed to write {}", source_path.display()))?;
+            println!("{}", source_path.display());
+            eprintln!(
+                "`{}` was not found, so the graph source was written instead.",
+                renderer.program
+            );
+            eprintln!(
+                "{}",
+                renderer
+                    .install_hint
+                    .replace("{format}", &format)
+                    .replace("{source}", &source_path.display().to_string())
+                    .replace("{output}", &output.display().to_string())
+            );
+            return Ok(());
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to run `{}`", renderer.program));
+        }
+    };
+    // A renderer that fails early closes the pipe; its exit status says more
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(source.as_bytes()),
+        None => Ok(()),
+    };
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("`{}` exited with {status}", renderer.program);
+    }
+    written.with_context(|| format!("Failed to send the graph to `{}`", renderer.program))?;
+    println!("{}", output.display());
+
+    if open {
+        open_file(output)?;
+    }
+    Ok(())
+}
+
+/// Open `path` with the platform's default application.
+fn open_file(path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    if !status.success() {
+        anyhow::bail!("Opening {} failed: {status}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use engram_core::storage::{GitStorage, ListOptions};
+use engram_query::index::parse_date_bound;
+use engram_query::{grep_engrams, GrepField, GrepMatch, GrepOptions};
+
+use super::record::parse_timeout;
+use crate::output::OutputFormat;
+
+#[derive(Args)]
+pub struct GrepArgs {
+    /// Text to find in each line, punctuation included (a regular
+    /// expression with --regex)
+    pub pattern: String,
+
+    /// Treat the pattern as a regular expression
+    #[arg(short = 'E', long)]
+    pub regex: bool,
+
+    /// Lines of context to show before and after each match
+    #[arg(short = 'C', long, value_name = "N", default_value = "0")]
+    pub context: usize,
+
+    /// Fields to scan: intent, transcript, dead_end, file (default: all).
+    /// The transcript includes thinking and tool output.
+    #[arg(long = "field", value_delimiter = ',')]
+    pub fields: Vec<GrepField>,
+
+    /// Only scan engrams by this agent
+    #[arg(long)]
+    pub agent: Option<String>,
+
+    /// Only scan engrams with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Only scan engrams created on or after this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only scan engrams created on or before this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    pub until: Option<String>,
+
+    /// Only scan engrams matching this search query
+    #[arg(long)]
+    pub query: Option<String>,
+
+    /// Maximum number of matching lines
+    #[arg(short = 'n', long, default_value = "100")]
+    pub limit: usize,
+
+    /// Stop scanning after this long (e.g. 30s, 2m)
+    #[arg(long, value_parser = parse_timeout, default_value = "10s")]
+    pub timeout: Duration,
+}
+
+pub fn run(args: &GrepArgs, format: OutputFormat) -> Result<()> {
+    let options = GrepOptions {
+        fields: if args.fields.is_empty() {
+            GrepField::ALL.to_vec()
+        } else {
+            args.fields.clone()
+        },
+        literal: !args.regex,
+        query: args.query.clone(),
+        filter: ListOptions {
+            agent_filter: args.agent.clone(),
+            tag: args.tag.clone(),
+            since: args
+                .since
+                .as_deref()
+                .map(|s| parse_date_bound(s, false))
+                .transpose()?,
+            until: args
+                .until
+                .as_deref()
+                .map(|s| parse_date_bound(s, true))
+                .transpose()?,
+            ..Default::default()
+        },
+        context: args.context,
+        limit: args.limit,
+        timeout: Some(args.timeout),
+    };
+    if !scan(&args.pattern, &options, format)? {
+        // Like grep: nothing matched
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Run a scan and print its matches; shared with `engram search --regex`.
+/// Returns whether anything matched.
+pub fn scan(pattern: &str, options: &GrepOptions, format: OutputFormat) -> Result<bool> {
+    let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
+    let results = grep_engrams(&storage, pattern, options)?;
+
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&results)?)
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            for (i, m) in results.matches.iter().enumerate() {
+                if i > 0 && options.context > 0 {
+                    println!("--");
+                }
+                print_match(m);
+            }
+            if results.matches.is_empty() {
+                eprintln!("No matches for: {pattern}");
+            }
+        }
+    }
+    if results.truncated {
+        eprintln!(
+            "Stopped after {} matches; raise --limit to see more.",
+            results.matches.len()
+        );
+    }
+    if results.timed_out {
+        eprintln!(
+            "Timed out after scanning {} engram(s); narrow with --query or raise --timeout.",
+            results.scanned
+        );
+    }
+    Ok(!results.matches.is_empty())
+}
+
+/// `shortid:entry: line` for a transcript match (`shortid:field: line`
+/// otherwise), with context lines marked `-` instead of `:`, as grep does.
+fn print_match(m: &GrepMatch) {
+    let short_id = &m.engram_id.as_str()[..8.min(m.engram_id.as_str().len())];
+    let location = match m.entry {
+        Some(entry) => entry.to_string(),
+        None => m.field.to_string(),
+    };
+    for line in &m.before {
+        println!("{short_id}-{location}- {}", line.trim_end());
+    }
+    println!("{short_id}:{location}: {}", m.line.trim_end());
+    for line in &m.after {
+        println!("{short_id}-{location}- {}", line.trim_end());
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use engram_core::hooks::{self, HookStatus};
+use engram_core::storage::{GitStorage, InitPolicy};
+
+use crate::output::OutputFormat;
+
+#[derive(Args)]
+pub struct HooksArgs {
+    #[command(subcommand)]
+    pub command: HooksCommand,
+}
+
+#[derive(Subcommand)]
+pub enum HooksCommand {
+    /// Install the Git hooks that add Engram-Id trailers to commits; an
+    /// existing hook is kept as <hook>.pre-engram and still runs first
+    Install,
+    /// Remove engram's Git hooks, restoring any hooks they replaced
+    Uninstall,
+    /// Show which hooks are installed, whether they are engram's, and
+    /// whether an earlier hook is backed up
+    Status,
+}
+
+pub fn run(args: &HooksArgs, format: OutputFormat) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+    let git_dir = storage.repo().path().to_path_buf();
+
+    match args.command {
+        HooksCommand::Install => {
+            storage.ensure_initialized(InitPolicy::Error)?;
+            hooks::install_hooks(&git_dir).context("Failed to install git hooks")?;
+            println!("Installed engram git hooks.");
+        }
+        HooksCommand::Uninstall => {
+            hooks::uninstall_hooks(&git_dir).context("Failed to uninstall git hooks")?;
+            println!("Removed engram git hooks.");
+        }
+        HooksCommand::Status => {
+            let status = hooks::hook_status(&git_dir).context("Failed to read git hooks")?;
+            print_status(&status, format)?;
+        }
+    }
+    Ok(())
+}
+
+fn print_status(status: &[HookStatus], format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(status)?);
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            for hook in status {
+                let state = match (hook.installed, hook.managed) {
+                    (false, _) => "not installed",
+                    (true, true) => "installed (engram)",
+                    (true, false) => "installed (not engram's)",
+                };
+                let backup = if hook.backup {
+                    format!(", backup in {}.pre-engram", hook.name)
+                } else {
+                    String::new()
+                };
+                println!("{:<20} {state}{backup}", hook.name);
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,28 +1,31 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use clap::{Args, ValueEnum};
 
-use engram_capture::import::aider::AiderImporter;
-use engram_capture::import::claude_code::ClaudeCodeImporter;
-use engram_capture::import::detect::detect_sources;
-use engram_core::storage::GitStorage;
+use engram_capture::import::batch::collect_import_files;
+use engram_capture::import::detect::detect_sources_with;
+use engram_capture::import::{ImportCandidate, ImporterRegistry};
+use engram_core::model::{EngramData, EngramId};
+use engram_core::storage::{GitStorage, InitPolicy};
 use engram_query::search::SearchEngine;
 
+use crate::output::{self, OutputFormat};
+
 #[derive(Args)]
 pub struct ImportArgs {
-    /// Path to session file or directory
+    /// Path to a session file, a directory (searched recursively), or a glob pattern
     pub path: Option<PathBuf>,
 
-    /// Format hint
+    /// Source format of the session files
     #[arg(long, value_enum)]
-    pub format: Option<ImportFormat>,
+    pub from: Option<ImportFormat>,
 
     /// Auto-detect and import all discoverable sessions
     #[arg(long)]
     pub auto_detect: bool,
 
-    /// Only show what would be imported (dry run)
+    /// Parse every source and show what would be imported, without writing anything
     #[arg(long)]
     pub dry_run: bool,
 }
@@ -33,33 +36,134 @@ pub enum ImportFormat {
     Aider,
 }
 
-/// Check if this engram was already imported (by source hash).
-fn check_duplicate(
-    storage: &GitStorage,
-    data: &engram_core::model::EngramData,
-) -> Option<engram_core::model::EngramId> {
-    data.manifest
-        .source_hash
+impl ImportFormat {
+    /// File extension used when importing a directory of sessions.
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::ClaudeCode => "jsonl",
+            Self::Aider => "md",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::ClaudeCode => "claude-code",
+            Self::Aider => "aider",
+        }
+    }
+}
+
+/// What importing a parsed session would do to the repository.
+enum ImportAction {
+    Import,
+    /// Replace a stale engram previously imported from the same source.
+    Update(EngramId),
+    /// Identical content was already imported.
+    Skip(EngramId),
+    /// Identical content was imported and then deleted with
+    /// `engram delete --keep-dedup`.
+    Deleted,
+}
+
+impl ImportAction {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Import => "import",
+            Self::Update(_) => "update",
+            Self::Skip(_) | Self::Deleted => "skip",
+        }
+    }
+
+    fn existing(&self) -> Option<&EngramId> {
+        match self {
+            Self::Import | Self::Deleted => None,
+            Self::Update(id) | Self::Skip(id) => Some(id),
+        }
+    }
+}
+
+/// Running totals for a batch of imports.
+#[derive(Debug, Default)]
+struct ImportSummary {
+    imported: usize,
+    updated: usize,
+    skipped: usize,
+    errored: usize,
+}
+
+impl ImportSummary {
+    fn record(&mut self, action: &ImportAction) {
+        match action {
+            ImportAction::Import => self.imported += 1,
+            ImportAction::Update(_) => self.updated += 1,
+            ImportAction::Skip(_) | ImportAction::Deleted => self.skipped += 1,
+        }
+    }
+
+    /// Print the final summary line. Fails only if every source errored.
+    fn finish(&self) -> Result<()> {
+        if !output::quiet() {
+            println!();
+            println!(
+                "Imported {}, updated {}, skipped {}, errored {}.",
+                self.imported, self.updated, self.skipped, self.errored
+            );
+        }
+        self.check()
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.errored > 0 && self.imported + self.updated + self.skipped == 0 {
+            anyhow::bail!("All {} import(s) failed", self.errored);
+        }
+        Ok(())
+    }
+}
+
+/// Decide whether a parsed engram is new, a duplicate, or replaces a stale import.
+fn plan_action(storage: &GitStorage, data: &EngramData) -> ImportAction {
+    if let Some(hash) = data.manifest.source_hash.as_deref() {
+        if let Some(existing) = storage.find_by_source_hash(hash) {
+            return ImportAction::Skip(existing);
+        }
+        if storage.is_source_hash_blocked(hash) {
+            return ImportAction::Deleted;
+        }
+    }
+    match data
+        .manifest
+        .source_path
         .as_deref()
-        .and_then(|h| storage.find_by_source_hash(h))
+        .and_then(|p| storage.find_by_source_path(p))
+    {
+        Some(previous) => ImportAction::Update(previous),
+        None => ImportAction::Import,
+    }
 }
 
-/// Best-effort incremental search index update after storing an engram.
-fn try_index(storage: &GitStorage, data: &engram_core::model::EngramData) {
-    if let Ok(search) = SearchEngine::open(storage) {
-        let _ = search.index_engram(data);
+/// Incremental search index update after storing an engram. Failing, such
+/// as when another engram process holds the index for too long, leaves the
+/// engram stored but unindexed; that is reported rather than fatal.
+pub(crate) fn try_index(storage: &GitStorage, data: &engram_core::model::EngramData) {
+    let indexed = SearchEngine::open(storage).and_then(|search| search.index_engram(data));
+    if let Err(e) = indexed {
+        eprintln!(
+            "  Stored {} but did not index it: {e}. Run `engram reindex --incremental` \
+             to catch up.",
+            &data.manifest.id.as_str()[..8]
+        );
     }
 }
 
-pub fn run(args: &ImportArgs) -> Result<()> {
+pub fn run(args: &ImportArgs, format: OutputFormat) -> Result<()> {
     let storage = GitStorage::discover().context("Not inside a Git repository")?;
 
-    if !storage.is_initialized() {
-        anyhow::bail!("Engram is not initialized. Run `engram init` first.");
-    }
+    storage.ensure_initialized(InitPolicy::Error)?;
+
+    let registry = ImporterRegistry::with_builtins();
 
     if args.auto_detect {
-        return run_auto_detect(&storage, args.dry_run);
+        return run_auto_detect(&storage, &registry, args.dry_run, format);
     }
 
     let path = args
@@ -67,75 +171,46 @@ pub fn run(args: &ImportArgs) -> Result<()> {
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("Specify a path or use --auto-detect"))?;
 
-    let format = args.format.as_ref().ok_or_else(|| {
-        anyhow::anyhow!("Specify --format (claude-code or aider) or use --auto-detect")
+    let from = args.from.as_ref().ok_or_else(|| {
+        anyhow::anyhow!("Specify --from (claude-code or aider) or use --auto-detect")
     })?;
 
-    match format {
-        ImportFormat::ClaudeCode => {
-            println!("Importing Claude Code session: {}", path.display());
-            if args.dry_run {
-                println!("  (dry run - no changes made)");
-                return Ok(());
-            }
-            let data = ClaudeCodeImporter::import_session(path)
-                .context("Failed to parse Claude Code session")?;
-            if let Some(existing) = check_duplicate(&storage, &data) {
-                println!(
-                    "  Skipped (already imported as {})",
-                    &existing.as_str()[..8]
-                );
-                return Ok(());
-            }
-            let tokens = data.manifest.token_usage.total_tokens;
-            let entries = data.transcript.entries.len();
-            let id = storage.create(&data).context("Failed to store engram")?;
-            try_index(&storage, &data);
-            println!(
-                "  Imported engram {} ({} transcript entries, {} tokens)",
-                &id.as_str()[..8],
-                entries,
-                tokens
-            );
-        }
-        ImportFormat::Aider => {
-            println!("Importing Aider history: {}", path.display());
-            if args.dry_run {
-                println!("  (dry run - no changes made)");
-                return Ok(());
-            }
-            let engrams =
-                AiderImporter::import_history(path).context("Failed to parse Aider history")?;
-            for data in engrams {
-                if let Some(existing) = check_duplicate(&storage, &data) {
-                    println!(
-                        "  Skipped (already imported as {})",
-                        &existing.as_str()[..8]
-                    );
-                    continue;
-                }
-                let entries = data.transcript.entries.len();
-                let id = storage.create(&data).context("Failed to store engram")?;
-                try_index(&storage, &data);
-                println!(
-                    "  Imported engram {} ({} transcript entries)",
-                    &id.as_str()[..8],
-                    entries
-                );
-            }
-        }
+    let files = collect_import_files(path, from.extension())
+        .with_context(|| format!("Failed to read import path {}", path.display()))?;
+
+    if files.is_empty() {
+        anyhow::bail!("No .{} files found at {}", from.extension(), path.display());
     }
 
-    Ok(())
+    let sources: Vec<_> = files
+        .into_iter()
+        .map(|f| ImportCandidate::new(from.name(), f))
+        .collect();
+    if args.dry_run {
+        return preview_sources(&storage, &registry, &sources, format);
+    }
+    import_sources(&storage, &registry, &sources)
 }
 
-fn run_auto_detect(storage: &GitStorage, dry_run: bool) -> Result<()> {
+fn run_auto_detect(
+    storage: &GitStorage,
+    registry: &ImporterRegistry,
+    dry_run: bool,
+    format: OutputFormat,
+) -> Result<()> {
     let workdir = storage
         .workdir()
         .ok_or_else(|| anyhow::anyhow!("Cannot determine working directory"))?;
 
-    let sources = detect_sources(workdir).context("Failed to detect import sources")?;
+    let sources = detect_sources_with(registry, workdir);
 
+    if dry_run {
+        return preview_sources(storage, registry, &sources, format);
+    }
+
+    if output::quiet() {
+        return import_sources(storage, registry, &sources);
+    }
     if sources.is_empty() {
         println!("No importable sessions found.");
         println!();
@@ -149,90 +224,215 @@ fn run_auto_detect(storage: &GitStorage, dry_run: bool) -> Result<()> {
     for source in &sources {
         println!("  - {}", source.description());
     }
+    println!();
 
-    if dry_run {
-        println!();
-        println!("(dry run - no changes made)");
-        return Ok(());
+    import_sources(storage, registry, &sources)
+}
+
+/// Import every source, continuing past per-file errors.
+fn import_sources(
+    storage: &GitStorage,
+    registry: &ImporterRegistry,
+    sources: &[ImportCandidate],
+) -> Result<()> {
+    let mut summary = ImportSummary::default();
+
+    for source in sources {
+        let path = &source.path;
+        let engrams = match registry.import(source) {
+            Ok(engrams) => engrams,
+            Err(e) => {
+                eprintln!("  Error importing {}: {e}", path.display());
+                summary.errored += 1;
+                continue;
+            }
+        };
+        for data in &engrams {
+            store_engram(storage, data, path, &mut summary);
+        }
     }
 
-    println!();
-    let mut total_imported = 0;
+    summary.finish()
+}
 
-    for source in &sources {
-        match source {
-            engram_capture::import::detect::ImportSource::ClaudeCode { session_path } => {
-                match ClaudeCodeImporter::import_session(session_path) {
-                    Ok(data) => {
-                        if let Some(existing) = check_duplicate(storage, &data) {
-                            println!(
-                                "  Skipped {} (already imported as {})",
-                                session_path.display(),
-                                &existing.as_str()[..8]
-                            );
-                            continue;
-                        }
-                        let entries = data.transcript.entries.len();
-                        let tokens = data.manifest.token_usage.total_tokens;
-                        match storage.create(&data) {
-                            Ok(id) => {
-                                try_index(storage, &data);
-                                println!(
-                                    "  Imported {} ({} entries, {} tokens)",
-                                    &id.as_str()[..8],
-                                    entries,
-                                    tokens,
-                                );
-                                total_imported += 1;
-                            }
-                            Err(e) => {
-                                eprintln!("  Error storing {}: {e}", session_path.display());
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("  Error importing {}: {e}", session_path.display());
-                    }
-                }
+/// Store a parsed engram, skipping exact duplicates and replacing stale
+/// imports of the same source.
+fn store_engram(storage: &GitStorage, data: &EngramData, path: &Path, summary: &mut ImportSummary) {
+    let action = plan_action(storage, data);
+
+    let quiet = output::quiet();
+    if let ImportAction::Skip(existing) = &action {
+        if !quiet {
+            println!(
+                "  Skipped {} (already imported as {})",
+                path.display(),
+                &existing.as_str()[..8]
+            );
+        }
+        summary.record(&action);
+        return;
+    }
+    if let ImportAction::Deleted = action {
+        if !quiet {
+            println!("  Skipped {} (deleted with --keep-dedup)", path.display());
+        }
+        summary.record(&action);
+        return;
+    }
+
+    let entries = data.transcript.entries.len();
+    let tokens = data.manifest.token_usage.total_tokens;
+
+    let id = match storage.create(data) {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("  Error storing {}: {e}", path.display());
+            summary.errored += 1;
+            return;
+        }
+    };
+    try_index(storage, data);
+
+    if let ImportAction::Update(old) = &action {
+        if let Err(e) = storage.delete(old.as_str()) {
+            eprintln!(
+                "  Failed to remove stale engram {}: {e}",
+                &old.as_str()[..8]
+            );
+        } else if let Ok(search) = SearchEngine::open(storage) {
+            let _ = search.remove_engram(old.as_str());
+        }
+    }
+    if quiet {
+        println!("{}", id.as_str());
+    } else if let ImportAction::Update(old) = &action {
+        println!(
+            "  Updated {} -> {} ({} entries, {} tokens)",
+            &old.as_str()[..8],
+            &id.as_str()[..8],
+            entries,
+            tokens
+        );
+    } else {
+        println!(
+            "  Imported {} ({} entries, {} tokens)",
+            &id.as_str()[..8],
+            entries,
+            tokens
+        );
+    }
+    summary.record(&action);
+}
+
+/// Parse every source and report what an import would do, without writing.
+fn preview_sources(
+    storage: &GitStorage,
+    registry: &ImporterRegistry,
+    sources: &[ImportCandidate],
+    format: OutputFormat,
+) -> Result<()> {
+    let mut summary = ImportSummary::default();
+    let mut previews = Vec::with_capacity(sources.len());
+
+    for source in sources {
+        let parsed = registry.import(source).map(|engrams| {
+            engrams
+                .into_iter()
+                .map(|data| {
+                    let action = plan_action(storage, &data);
+                    summary.record(&action);
+                    (data, action)
+                })
+                .collect::<Vec<_>>()
+        });
+        if parsed.is_err() {
+            summary.errored += 1;
+        }
+        previews.push((source, parsed));
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let json = serde_json::json!({
+                "dry_run": true,
+                "sources": previews.iter().map(|(source, parsed)| {
+                    let (sessions, error) = match parsed {
+                        Ok(sessions) => (sessions.iter().map(|(data, action)| {
+                            let m = &data.manifest;
+                            serde_json::json!({
+                                "action": action.label(),
+                                "existing_id": action.existing().map(|id| id.as_str()),
+                                "summary": m.summary,
+                                "transcript_entries": data.transcript.entries.len(),
+                                "total_tokens": m.token_usage.total_tokens,
+                                "cost_usd": m.token_usage.cost_usd,
+                                "started_at": m.created_at,
+                                "finished_at": m.finished_at,
+                            })
+                        }).collect::<Vec<_>>(), None),
+                        Err(e) => (Vec::new(), Some(e.to_string())),
+                    };
+                    serde_json::json!({
+                        "path": source.path.display().to_string(),
+                        "format": source.importer,
+                        "session_count": sessions.len(),
+                        "sessions": sessions,
+                        "error": error,
+                    })
+                }).collect::<Vec<_>>(),
+                "totals": {
+                    "import": summary.imported,
+                    "update": summary.updated,
+                    "skip": summary.skipped,
+                    "error": summary.errored,
+                },
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            if previews.is_empty() {
+                println!("No importable sessions found.");
             }
-            engram_capture::import::detect::ImportSource::Aider { history_path } => {
-                match AiderImporter::import_history(history_path) {
-                    Ok(engrams) => {
-                        for data in engrams {
-                            if let Some(existing) = check_duplicate(storage, &data) {
-                                println!(
-                                    "  Skipped aider session (already imported as {})",
-                                    &existing.as_str()[..8]
-                                );
-                                continue;
-                            }
-                            let entries = data.transcript.entries.len();
-                            match storage.create(&data) {
-                                Ok(id) => {
-                                    try_index(storage, &data);
-                                    println!(
-                                        "  Imported {} ({} entries)",
-                                        &id.as_str()[..8],
-                                        entries,
-                                    );
-                                    total_imported += 1;
-                                }
-                                Err(e) => {
-                                    eprintln!("  Error storing aider session: {e}");
-                                }
-                            }
+            for (source, parsed) in &previews {
+                println!("{} ({})", source.path.display(), source.importer);
+                match parsed {
+                    Ok(sessions) => {
+                        println!("  {} session(s)", sessions.len());
+                        for (data, action) in sessions {
+                            print_session_preview(data, action);
                         }
                     }
-                    Err(e) => {
-                        eprintln!("  Error importing {}: {e}", history_path.display());
-                    }
+                    Err(e) => println!("  Error: {e}"),
                 }
             }
+            println!();
+            println!(
+                "Would import {}, update {}, skip {}; {} error(s).",
+                summary.imported, summary.updated, summary.skipped, summary.errored
+            );
+            println!("(dry run - no changes made)");
         }
     }
 
-    println!();
-    println!("Imported {total_imported} engram(s).");
+    summary.check()
+}
 
-    Ok(())
+fn print_session_preview(data: &EngramData, action: &ImportAction) {
+    let m = &data.manifest;
+    let verb = match action {
+        ImportAction::Import => "would import".to_string(),
+        ImportAction::Update(id) => format!("would update {}", &id.as_str()[..8]),
+        ImportAction::Skip(id) => format!("would skip (duplicate of {})", &id.as_str()[..8]),
+        ImportAction::Deleted => "would skip (deleted with --keep-dedup)".to_string(),
+    };
+    let start = m.created_at.format("%Y-%m-%d %H:%M");
+    let end = m
+        .finished_at
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "?".to_string());
+    println!(
+        "  - {verb}: {} entries, {} tokens, {start} to {end}",
+        data.transcript.entries.len(),
+        m.token_usage.total_tokens
+    );
 }
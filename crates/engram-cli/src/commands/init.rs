@@ -12,6 +12,11 @@ pub struct InitArgs {
     /// Remote name to configure refspecs on (default: all remotes)
     #[arg(long)]
     pub remote: Option<String>,
+
+    /// Don't install the Git hooks that add Engram-Id trailers to commits
+    /// (see `engram hooks`)
+    #[arg(long)]
+    pub no_hooks: bool,
 }
 
 pub fn run(args: &InitArgs) -> Result<()> {
@@ -29,8 +34,10 @@ pub fn run(args: &InitArgs) -> Result<()> {
         .context("Failed to initialize engram")?;
 
     // Install git hooks for commit trailer injection
-    let git_dir = storage.repo().path().to_path_buf();
-    hooks::install_hooks(&git_dir).context("Failed to install git hooks")?;
+    if !args.no_hooks {
+        let git_dir = storage.repo().path().to_path_buf();
+        hooks::install_hooks(&git_dir).context("Failed to install git hooks")?;
+    }
 
     println!("Engram initialized. Reasoning capture is ready.");
     println!();
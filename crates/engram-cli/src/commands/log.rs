@@ -1,11 +1,17 @@
 use std::collections::BTreeMap;
 
 use anyhow::{Context, Result};
-use clap::Args;
-use engram_core::storage::{GitStorage, ListOptions};
+use chrono::SecondsFormat;
+use clap::{Args, ValueEnum};
+use engram_core::config::TrivialThresholds;
+use engram_core::display::DisplayTimezone;
+use engram_core::model::{Manifest, Operations};
+use engram_core::storage::{EngramPart, GitStorage, InitPolicy, ListOptions};
+use engram_query::{find_failures, FailureSummary};
 
-use crate::output::format::format_manifest_list;
-use crate::output::OutputFormat;
+use crate::output::format::{format_manifest_list, Dates};
+use crate::output::ndjson::NdjsonWriter;
+use crate::output::{porcelain, OutputFormat};
 
 #[derive(Args)]
 pub struct LogArgs {
@@ -21,24 +27,190 @@ pub struct LogArgs {
     #[arg(long)]
     pub agent: Option<String>,
 
+    /// Only engrams with this tag (see `engram tag`)
+    #[arg(long)]
+    pub tag: Option<String>,
+
     /// Group output by agent name
     #[arg(long)]
     pub by_agent: bool,
+
+    /// Only sessions with failed tool calls or non-zero shell exits
+    #[arg(long, conflicts_with = "by_agent")]
+    pub failed: bool,
+
+    /// Only engrams that cost at least this much (USD); excludes engrams
+    /// with no recorded cost
+    #[arg(long, value_name = "USD")]
+    pub min_cost: Option<f64>,
+
+    /// Only engrams that cost at most this much (USD); excludes engrams
+    /// with no recorded cost
+    #[arg(long, value_name = "USD")]
+    pub max_cost: Option<f64>,
+
+    /// Only engrams that used at least this many tokens
+    #[arg(long, value_name = "N")]
+    pub min_tokens: Option<u64>,
+
+    /// Only engrams that used at most this many tokens
+    #[arg(long, value_name = "N")]
+    pub max_tokens: Option<u64>,
+
+    /// Include trivial sessions: no file changes, and fewer tokens and
+    /// seconds than engram.trivial.maxTokens and engram.trivial.maxSeconds
+    #[arg(long, short = 'a')]
+    pub all: bool,
+
+    /// Show dates as timestamps (in engram.displayTimezone) instead of
+    /// how long ago they were
+    #[arg(long)]
+    pub absolute_time: bool,
+
+    /// Print stable tab-separated lines for scripts (see README), whatever
+    /// --format says
+    #[arg(long, conflicts_with_all = ["by_agent", "columns"])]
+    pub porcelain: bool,
+
+    /// Columns to print with --format csv, comma-separated (default: all)
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Vec<LogColumn>,
+}
+
+/// A column of `engram log --format csv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogColumn {
+    Id,
+    #[value(name = "created_at")]
+    CreatedAt,
+    Agent,
+    Model,
+    #[value(name = "tokens_in")]
+    TokensIn,
+    #[value(name = "tokens_out")]
+    TokensOut,
+    Cost,
+    #[value(name = "files_changed")]
+    FilesChanged,
+    Summary,
+}
+
+impl LogColumn {
+    const ALL: [LogColumn; 9] = [
+        LogColumn::Id,
+        LogColumn::CreatedAt,
+        LogColumn::Agent,
+        LogColumn::Model,
+        LogColumn::TokensIn,
+        LogColumn::TokensOut,
+        LogColumn::Cost,
+        LogColumn::FilesChanged,
+        LogColumn::Summary,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            LogColumn::Id => "id",
+            LogColumn::CreatedAt => "created_at",
+            LogColumn::Agent => "agent",
+            LogColumn::Model => "model",
+            LogColumn::TokensIn => "tokens_in",
+            LogColumn::TokensOut => "tokens_out",
+            LogColumn::Cost => "cost",
+            LogColumn::FilesChanged => "files_changed",
+            LogColumn::Summary => "summary",
+        }
+    }
+
+    fn value(self, storage: &GitStorage, m: &Manifest) -> String {
+        match self {
+            LogColumn::Id => m.id.as_str().to_string(),
+            LogColumn::CreatedAt => m.created_at.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            LogColumn::Agent => m.agent.name.clone(),
+            LogColumn::Model => m.agent.model.clone().unwrap_or_default(),
+            LogColumn::TokensIn => m.token_usage.input_tokens.to_string(),
+            LogColumn::TokensOut => m.token_usage.output_tokens.to_string(),
+            LogColumn::Cost => m
+                .token_usage
+                .cost_usd
+                .map(|c| c.to_string())
+                .unwrap_or_default(),
+            // Only read when asked for, as it means reading the operations
+            LogColumn::FilesChanged => storage
+                .read_part(m.id.as_str(), EngramPart::Operations)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<Operations>(&bytes).ok())
+                .map_or(0, |ops| ops.file_changes.len())
+                .to_string(),
+            LogColumn::Summary => m.summary.clone().unwrap_or_default(),
+        }
+    }
 }
 
 pub fn run(args: &LogArgs, format: OutputFormat) -> Result<()> {
     let storage = GitStorage::discover().context("Not inside a Git repository")?;
 
-    if !storage.is_initialized() {
-        anyhow::bail!("Engram is not initialized. Run `engram init` first.");
+    storage.ensure_initialized(InitPolicy::WarnAndContinue)?;
+    if !args.columns.is_empty() && format != OutputFormat::Csv {
+        anyhow::bail!("--columns only applies to --format csv");
     }
 
+    let config = storage
+        .repo()
+        .config()
+        .context("Failed to read git config")?;
+    let hide_trivial = (!args.all).then(|| TrivialThresholds::load(&config));
+    let dates = if args.absolute_time {
+        Dates::Absolute(DisplayTimezone::load(&config))
+    } else {
+        Dates::Relative
+    };
     let opts = ListOptions {
         limit: Some(args.limit),
         agent_filter: args.agent.clone(),
+        tag: args.tag.clone(),
+        min_cost: args.min_cost,
+        max_cost: args.max_cost,
+        min_tokens: args.min_tokens,
+        max_tokens: args.max_tokens,
+        hide_trivial,
+        ..Default::default()
     };
+    if args.failed {
+        let failures = find_failures(&storage, &opts).context("Failed to list engrams")?;
+        if args.porcelain {
+            for failure in &failures {
+                print!("{}", porcelain::log_line(&failure.manifest));
+            }
+            return Ok(());
+        }
+        if format == OutputFormat::Csv {
+            let manifests: Vec<_> = failures.into_iter().map(|f| f.manifest).collect();
+            return write_csv(&storage, &manifests, &args.columns);
+        }
+        return print_failures(&failures, args.cost, dates, format);
+    }
     let manifests = storage.list(&opts).context("Failed to list engrams")?;
 
+    if args.porcelain {
+        for m in &manifests {
+            print!("{}", porcelain::log_line(m));
+        }
+        return Ok(());
+    }
+
+    if format == OutputFormat::Csv {
+        return write_csv(&storage, &manifests, &args.columns);
+    }
+
+    if format == OutputFormat::Ndjson {
+        // Each record names its agent, so --by-agent needs no grouping here
+        let mut out = NdjsonWriter::stdout();
+        for m in &manifests {
+            out.write("engram", m)?;
+        }
+        return Ok(());
+    }
     if args.by_agent {
         let mut grouped: BTreeMap<String, Vec<_>> = BTreeMap::new();
         for m in &manifests {
@@ -49,14 +221,73 @@ pub fn run(args: &LogArgs, format: OutputFormat) -> Result<()> {
         }
         for (agent, entries) in &grouped {
             println!("## {agent} ({} engrams)", entries.len());
-            let output = format_manifest_list(entries, args.cost, format);
+            let output = format_manifest_list(entries, args.cost, dates, format);
             print!("{output}");
             println!();
         }
     } else {
-        let output = format_manifest_list(&manifests, args.cost, format);
+        let output = format_manifest_list(&manifests, args.cost, dates, format);
         print!("{output}");
     }
 
     Ok(())
 }
+
+/// One row per engram with `columns`, or every column when none are given.
+fn write_csv(storage: &GitStorage, manifests: &[Manifest], columns: &[LogColumn]) -> Result<()> {
+    let columns = if columns.is_empty() {
+        &LogColumn::ALL[..]
+    } else {
+        columns
+    };
+    let mut out = csv::Writer::from_writer(std::io::stdout());
+    out.write_record(columns.iter().map(|c| c.name()))?;
+    for m in manifests {
+        out.write_record(columns.iter().map(|c| c.value(storage, m)))?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn print_failures(
+    failures: &[FailureSummary],
+    show_cost: bool,
+    dates: Dates,
+    format: OutputFormat,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(failures)?);
+            return Ok(());
+        }
+        OutputFormat::Ndjson => {
+            let mut out = NdjsonWriter::stdout();
+            for failure in failures {
+                out.write("failure", failure)?;
+            }
+            return Ok(());
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {}
+    }
+    if failures.is_empty() {
+        println!("No failed sessions found.");
+        return Ok(());
+    }
+    for failure in failures {
+        // One line per failure; a Markdown table per failure would repeat its header
+        let line = format_manifest_list(
+            std::slice::from_ref(&failure.manifest),
+            show_cost,
+            dates,
+            OutputFormat::Text,
+        );
+        print!("{line}");
+        if failure.error_tool_calls > 0 {
+            println!("    {} failed tool call(s)", failure.error_tool_calls);
+        }
+        for cmd in &failure.failed_commands {
+            println!("    exit {}: {}", cmd.exit_code, cmd.command);
+        }
+    }
+    Ok(())
+}
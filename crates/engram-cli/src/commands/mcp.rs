@@ -1,25 +1,90 @@
+use std::net::SocketAddr;
+
 use anyhow::{Context, Result};
+use clap::Args;
+
+use engram_core::config::EngramConfig;
+use engram_core::storage::{GitStorage, InitPolicy};
+
+#[derive(Args)]
+pub struct McpArgs {
+    /// Serve streamable HTTP at this address (e.g. 127.0.0.1:7345) instead
+    /// of stdio. Clients must send the bearer token from ENGRAM_MCP_TOKEN or
+    /// engram.mcp.token, when one is set
+    #[arg(long, value_name = "ADDR")]
+    pub http: Option<SocketAddr>,
 
-use engram_core::storage::GitStorage;
+    /// Serve HTTP on a non-loopback address without a token, letting anyone
+    /// who can reach it read and write engrams
+    #[arg(long, requires = "http")]
+    pub insecure: bool,
+
+    /// Cut text results longer than this many characters, with a hint on
+    /// how to fetch the rest (default: engram.mcp.maxResponseChars, or 20480)
+    #[arg(long, value_name = "CHARS")]
+    pub max_response_chars: Option<usize>,
+}
 
-pub fn run() -> Result<()> {
+pub fn run(args: &McpArgs) -> Result<()> {
     let storage = GitStorage::discover().context("Not inside a Git repository")?;
 
-    if !storage.is_initialized() {
-        anyhow::bail!("Engram is not initialized. Run `engram init` first.");
-    }
+    storage.ensure_initialized(InitPolicy::WarnAndContinue)?;
 
+    // A bare repository has no working directory; its path is the repo
     let repo_path = storage
+        .workdir()
+        .unwrap_or_else(|| storage.repo().path())
+        .to_path_buf();
+
+    let repo_config = storage
         .repo()
-        .path()
-        .parent()
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| storage.repo().path().to_path_buf());
+        .config()
+        .context("Failed to read git config")?;
+    let config = EngramConfig::load(&repo_config)?;
+    let max_response_chars = args
+        .max_response_chars
+        .or(config.mcp_max_response_chars)
+        .unwrap_or(engram_mcp::DEFAULT_MAX_RESPONSE_CHARS);
 
     let rt = tokio::runtime::Runtime::new().context("Failed to create async runtime")?;
+    let Some(addr) = args.http else {
+        return rt.block_on(async {
+            engram_mcp::run_stdio(repo_path, engram_mcp::DEFAULT_CACHE_TTL, max_response_chars)
+                .await
+                .map_err(|e| anyhow::anyhow!("MCP server error: {e}"))
+        });
+    };
+
+    let token = match std::env::var(engram_mcp::TOKEN_ENV) {
+        Ok(token) if !token.is_empty() => Some(token),
+        _ => config.mcp_token.filter(|t| !t.is_empty()),
+    };
+    if token.is_none() && !addr.ip().is_loopback() {
+        if !args.insecure {
+            anyhow::bail!(
+                "Refusing to serve on {addr} without a token: anyone who can reach it could \
+                 read and write engrams. Set {} or engram.mcp.token, or pass --insecure",
+                engram_mcp::TOKEN_ENV
+            );
+        }
+        eprintln!("Warning: serving on {addr} without a token (--insecure)");
+    }
+
     rt.block_on(async {
-        engram_mcp::run_stdio(repo_path)
+        let listener = tokio::net::TcpListener::bind(addr)
             .await
-            .map_err(|e| anyhow::anyhow!("MCP server error: {e}"))
+            .with_context(|| format!("Failed to listen on {addr}"))?;
+        let local = listener.local_addr()?;
+        eprintln!("Serving MCP on http://{local}{}", engram_mcp::MCP_PATH);
+        engram_mcp::serve_http(
+            listener,
+            repo_path,
+            engram_mcp::DEFAULT_CACHE_TTL,
+            max_response_chars,
+            token,
+            args.insecure,
+        )
+        .await
+        .context("MCP server error")
     })
 }
@@ -1,23 +1,44 @@
+pub mod alias;
+pub mod amend;
 pub mod blame;
+pub mod bundle;
+pub mod completions;
+pub mod config;
+pub mod costs;
+pub mod dedupe;
+pub mod delete;
 pub mod diff;
+#[cfg(feature = "otel")]
+pub mod export_otel;
 pub mod fetch;
 pub mod gc;
 pub mod graph;
+pub mod grep;
 pub mod hook_handler;
+pub mod hooks;
 pub mod import;
 pub mod init;
 pub mod log;
 pub mod mcp;
+pub mod note;
 pub mod pr_summary;
 pub mod pull;
 pub mod push;
 pub mod record;
 pub mod reindex;
+pub mod related;
 pub mod review;
 pub mod search;
+#[cfg(feature = "serve")]
+pub mod serve;
 pub mod show;
 pub mod stats;
+pub mod status;
+pub mod tag;
 pub mod trace;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod uninit;
 pub mod version;
 
 use clap::Subcommand;
@@ -26,6 +47,8 @@ use clap::Subcommand;
 pub enum Commands {
     /// Initialize engram in the current Git repository
     Init(init::InitArgs),
+    /// Remove engram's config, refspecs, hooks and index from the repository
+    Uninit(uninit::UninitArgs),
     /// Record an agent session (wraps any command in a PTY)
     Record(record::RecordArgs),
     /// Import sessions from known agent formats
@@ -33,9 +56,28 @@ pub enum Commands {
     /// List engrams (most recent first)
     Log(log::LogArgs),
     /// Show details of a specific engram
+    ///
+    /// With `--format json` this prints the whole engram (manifest, intent,
+    /// operations, transcript and lineage) under a top-level `schema_version`.
+    /// Earlier versions printed only the manifest; `--fields manifest` still
+    /// does.
     Show(show::ShowArgs),
+    /// Delete engrams by ID, or in bulk by agent or age
+    Delete(delete::DeleteArgs),
+    /// Add notes to engrams after the fact, or list them
+    Note(note::NoteArgs),
+    /// Fix an engram's summary, goal, dead ends or decisions
+    Amend(amend::AmendArgs),
+    /// List, add or remove an engram's tags
+    Tag(tag::TagArgs),
+    /// Name engrams, so `engram show payments-refactor` works
+    Alias(alias::AliasArgs),
     /// Search engrams by content
     Search(search::SearchArgs),
+    /// Scan engrams for lines matching a regular expression
+    Grep(grep::GrepArgs),
+    /// List engrams related by lineage, files, tags, commits or intent
+    Related(related::RelatedArgs),
     /// Trace reasoning history for a file
     Trace(trace::TraceArgs),
     /// Compare two engrams
@@ -50,21 +92,61 @@ pub enum Commands {
     Pull(pull::PullArgs),
     /// Fetch engram refs from a remote (no reindex)
     Fetch(fetch::FetchArgs),
+    /// Compare local engram refs with a remote's, without transferring
+    Status(status::StatusArgs),
+    /// Move engrams between repositories as Git bundle files
+    Bundle(bundle::BundleArgs),
     /// Show aggregate statistics across all engrams
-    Stats,
-    /// Start MCP server (stdio transport) for AI agent integration
-    Mcp,
+    Stats(stats::StatsArgs),
+    /// Show token and cost totals per day, week or month
+    Costs(costs::CostsArgs),
+    /// Start MCP server (stdio, or streamable HTTP) for AI agent integration
+    Mcp(mcp::McpArgs),
     /// Generate a PR description from the engram chain
     PrSummary(pr_summary::PrSummaryArgs),
     /// Garbage collect old engrams
     Gc(gc::GcArgs),
+    /// Find engrams recording the same session, and delete the extra copies
+    Dedupe(dedupe::DedupeArgs),
     /// Show reasoning blame for a file
     Blame(blame::BlameArgs),
     /// Rebuild the search index
-    Reindex,
+    Reindex(reindex::ReindexArgs),
+    /// Read and change engram settings (`engram.*` Git config)
+    Config(config::ConfigArgs),
+    /// Install, remove or inspect the Git hooks that link commits to engrams
+    Hooks(hooks::HooksArgs),
+    /// Serve a read-only JSON API over HTTP (built with the `serve` feature)
+    #[cfg(feature = "serve")]
+    Serve(serve::ServeArgs),
+    /// Send engrams to an OpenTelemetry collector as traces (built with the
+    /// `otel` feature)
+    #[cfg(feature = "otel")]
+    ExportOtel(export_otel::ExportOtelArgs),
+    /// Browse engrams interactively (built with the `tui` feature)
+    #[cfg(feature = "tui")]
+    Tui(tui::TuiArgs),
+    /// Print a shell completion script
+    Completions(completions::CompletionsArgs),
+    /// List engram IDs for shell completion
+    #[command(name = "__complete-ids", hide = true)]
+    CompleteIds(completions::CompleteArgs),
+    /// List remote names for shell completion
+    #[command(name = "__complete-remotes", hide = true)]
+    CompleteRemotes(completions::CompleteArgs),
     /// Print version information
     Version,
     /// Internal: handle git hook callbacks
     #[command(hide = true)]
     HookHandler(hook_handler::HookHandlerArgs),
 }
+
+impl Commands {
+    /// Whether the command prints a table `--format csv` can render.
+    pub fn prints_csv(&self) -> bool {
+        matches!(
+            self,
+            Commands::Log(_) | Commands::Stats(_) | Commands::Costs(_)
+        )
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+
+use engram_core::storage::{GitStorage, InitPolicy};
+
+use crate::output::OutputFormat;
+
+#[derive(Args)]
+pub struct NoteArgs {
+    #[command(subcommand)]
+    pub command: NoteCommand,
+}
+
+#[derive(Subcommand)]
+pub enum NoteCommand {
+    /// Add a note to an engram, e.g. that its approach was later reverted
+    Add(AddArgs),
+    /// List an engram's notes, oldest first
+    List(ListArgs),
+}
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// Engram ID (full, prefix or alias)
+    pub id: String,
+
+    /// Text of the note
+    pub text: String,
+
+    /// Who the note is from (default: Git's user.name)
+    #[arg(long)]
+    pub author: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Engram ID (full, prefix or alias)
+    pub id: String,
+}
+
+pub fn run(args: &NoteArgs, format: OutputFormat) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::Error)?;
+
+    match &args.command {
+        NoteCommand::Add(args) => add(&storage, args),
+        NoteCommand::List(args) => list(&storage, args, format),
+    }
+}
+
+fn add(storage: &GitStorage, args: &AddArgs) -> Result<()> {
+    let text = args.text.trim();
+    if text.is_empty() {
+        anyhow::bail!("Note text is empty");
+    }
+    let id = storage
+        .resolve(&args.id)
+        .with_context(|| format!("Failed to resolve engram '{}'", args.id))?;
+    let author = match &args.author {
+        Some(author) => author.clone(),
+        None => storage
+            .repo()
+            .config()
+            .and_then(|c| c.get_string("user.name"))
+            .unwrap_or_else(|_| "unknown".into()),
+    };
+    storage
+        .add_note(&id, &author, text)
+        .with_context(|| format!("Failed to add note to engram '{id}'"))?;
+    eprintln!("Added note to {}", &id[..8.min(id.len())]);
+    Ok(())
+}
+
+fn list(storage: &GitStorage, args: &ListArgs, format: OutputFormat) -> Result<()> {
+    let id = storage
+        .resolve(&args.id)
+        .with_context(|| format!("Failed to resolve engram '{}'", args.id))?;
+    let notes = storage
+        .read_notes(&id)
+        .with_context(|| format!("Failed to read notes of engram '{id}'"))?;
+
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&notes)?)
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            if notes.is_empty() {
+                eprintln!("No notes on {}.", &id[..8.min(id.len())]);
+            }
+            for note in &notes {
+                println!(
+                    "{} {}: {}",
+                    note.created_at.format("%Y-%m-%d %H:%M"),
+                    note.author,
+                    note.text
+                );
+            }
+        }
+    }
+    Ok(())
+}
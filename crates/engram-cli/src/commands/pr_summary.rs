@@ -1,18 +1,30 @@
-use std::collections::BTreeSet;
+use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use clap::Args;
 
-use engram_core::model::FileChangeType;
 use engram_core::storage::GitStorage;
-use engram_query::review_branch;
+use engram_query::{review_branch, BranchReview, Linkage};
 
+use super::review::{by_file_text, LinkageArgs};
 use crate::output::OutputFormat;
 
 #[derive(Args)]
 pub struct PrSummaryArgs {
     /// Commit range (e.g. "main..feature" or "HEAD~5..HEAD")
     pub range: String,
+
+    /// Group changes by file, listing the engrams that changed each
+    #[arg(long)]
+    pub by_file: bool,
+
+    #[command(flatten)]
+    pub linkage: LinkageArgs,
+
+    /// Write the summary to this file instead of stdout, e.g. with
+    /// `--format markdown` for a PR description
+    #[arg(long, value_name = "FILE")]
+    pub out: Option<PathBuf>,
 }
 
 pub fn run(args: &PrSummaryArgs, format: OutputFormat) -> Result<()> {
@@ -24,16 +36,16 @@ pub fn run(args: &PrSummaryArgs, format: OutputFormat) -> Result<()> {
     }
     let (base, head) = (parts[0], parts[1]);
 
-    let review = review_branch(&storage, base, head)?;
+    let review = review_branch(&storage, base, head, &args.linkage.options())?;
 
     if review.engrams.is_empty() {
         println!("No engrams found in range {}", review.range);
         return Ok(());
     }
 
-    match format {
-        OutputFormat::Json => {
-            let json = serde_json::json!({
+    let output = match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let mut json = serde_json::json!({
                 "range": review.range,
                 "total_commits": review.total_commits,
                 "total_tokens": review.total_tokens,
@@ -45,152 +57,93 @@ pub fn run(args: &PrSummaryArgs, format: OutputFormat) -> Result<()> {
                         "model": e.manifest.agent.model,
                         "summary": e.manifest.summary,
                         "commit": e.commit_sha,
+                        "linkage": e.linkage,
                         "tokens": e.manifest.token_usage.total_tokens,
                         "cost": e.manifest.token_usage.cost_usd,
                     })
                 }).collect::<Vec<_>>(),
                 "files_changed": review.files_changed,
             });
-            println!("{}", serde_json::to_string_pretty(&json)?);
-        }
-        OutputFormat::Text => {
-            print_text(&storage, &review);
+            if args.by_file {
+                json["by_file"] = serde_json::to_value(review.by_file())?;
+            }
+            format!("{}\n", serde_json::to_string_pretty(&json)?)
         }
-        OutputFormat::Markdown => {
-            print_markdown(&storage, &review);
+        OutputFormat::Text | OutputFormat::Csv => text_summary(&review, args.by_file),
+        OutputFormat::Markdown => review.to_markdown(args.by_file),
+    };
+
+    match &args.out {
+        Some(path) => {
+            std::fs::write(path, &output)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("Wrote {}", path.display());
         }
+        None => print!("{output}"),
     }
-
     Ok(())
 }
 
-fn print_text(storage: &GitStorage, review: &engram_query::review::BranchReview) {
-    println!("PR Summary: {}\n", review.range);
+fn text_summary(review: &BranchReview, by_file: bool) -> String {
+    let mut out = format!("PR Summary: {}\n\n", review.range);
 
     // Summary from intents
-    println!("Summary:");
+    out.push_str("Summary:\n");
     for entry in &review.engrams {
         let summary = entry.manifest.summary.as_deref().unwrap_or("(no summary)");
-        println!("  - {summary}");
+        if entry.linkage == Linkage::Inferred {
+            out.push_str(&format!("  - {summary} (inferred)\n"));
+        } else {
+            out.push_str(&format!("  - {summary}\n"));
+        }
     }
-    println!();
+    out.push('\n');
 
     // Files
-    if !review.files_changed.is_empty() {
-        println!("Files changed ({}):", review.files_changed.len());
+    if by_file {
+        out.push_str(&format!(
+            "Files changed ({}):\n",
+            review.files_changed.len()
+        ));
+        out.push_str(&by_file_text(&review.by_file()));
+        out.push('\n');
+    } else if !review.files_changed.is_empty() {
+        out.push_str(&format!(
+            "Files changed ({}):\n",
+            review.files_changed.len()
+        ));
         let mut sorted: Vec<_> = review.files_changed.iter().collect();
         sorted.sort();
         for f in sorted {
-            println!("  {f}");
+            out.push_str(&format!("  {f}\n"));
         }
-        println!();
+        out.push('\n');
     }
 
     // Dead ends
-    let mut dead_ends = Vec::new();
-    for entry in &review.engrams {
-        if let Ok(data) = storage.read(entry.manifest.id.as_str()) {
-            for de in &data.intent.dead_ends {
-                dead_ends.push(format!("{} — {}", de.approach, de.reason));
-            }
-        }
-    }
+    let dead_ends = dead_ends(review);
     if !dead_ends.is_empty() {
-        println!("Dead ends:");
+        out.push_str("Dead ends:\n");
         for de in &dead_ends {
-            println!("  - {de}");
+            out.push_str(&format!("  - {de}\n"));
         }
-        println!();
+        out.push('\n');
     }
 
     // Economics
-    println!("Tokens: {}", review.total_tokens);
+    out.push_str(&format!("Tokens: {}\n", review.total_tokens));
     if let Some(cost) = review.total_cost {
-        println!("Cost:   ${cost:.2}");
+        out.push_str(&format!("Cost:   ${cost:.2}\n"));
     }
-    println!("Commits: {}", review.total_commits);
+    out.push_str(&format!("Commits: {}\n", review.total_commits));
+    out
 }
 
-fn print_markdown(storage: &GitStorage, review: &engram_query::review::BranchReview) {
-    // Summary
-    println!("## Summary\n");
-    for entry in &review.engrams {
-        if let Some(summary) = &entry.manifest.summary {
-            println!("- {summary}");
-        }
-    }
-    println!();
-
-    // Changes — collect file change types from full data
-    if !review.files_changed.is_empty() {
-        println!("## Changes\n");
-        let mut file_types: Vec<(String, String)> = Vec::new();
-        let mut seen: BTreeSet<String> = BTreeSet::new();
-
-        for entry in &review.engrams {
-            if let Ok(data) = storage.read(entry.manifest.id.as_str()) {
-                for fc in &data.operations.file_changes {
-                    if seen.insert(fc.path.clone()) {
-                        let change_label = match &fc.change_type {
-                            FileChangeType::Created => "Created",
-                            FileChangeType::Modified => "Modified",
-                            FileChangeType::Deleted => "Deleted",
-                            FileChangeType::Renamed { from } => {
-                                file_types
-                                    .push((fc.path.clone(), format!("Renamed from `{from}`")));
-                                continue;
-                            }
-                        };
-                        file_types.push((fc.path.clone(), change_label.to_string()));
-                    }
-                }
-            }
-        }
-
-        file_types.sort_by(|a, b| a.0.cmp(&b.0));
-        for (path, change) in &file_types {
-            println!("- `{path}` — {change}");
-        }
-        println!();
-    }
-
-    // Reasoning chain
-    println!("## Reasoning\n");
-    for entry in &review.engrams {
-        let m = &entry.manifest;
-        let short_id = &entry.commit_sha[..8.min(entry.commit_sha.len())];
-        let agent = &m.agent.name;
-        let model = m.agent.model.as_deref().unwrap_or("unknown");
-        let summary = m.summary.as_deref().unwrap_or("(no summary)");
-        println!("- **{short_id}** ({agent}/{model}): {summary}");
-    }
-    println!();
-
-    // Dead ends
-    let mut dead_ends = Vec::new();
-    for entry in &review.engrams {
-        if let Ok(data) = storage.read(entry.manifest.id.as_str()) {
-            for de in &data.intent.dead_ends {
-                dead_ends.push(format!("{} — {}", de.approach, de.reason));
-            }
-        }
-    }
-    if !dead_ends.is_empty() {
-        println!("## Dead Ends\n");
-        for de in &dead_ends {
-            println!("- {de}");
-        }
-        println!();
-    }
-
-    // Economics
-    println!("## Economics\n");
-    println!("- **Tokens:** {} total", review.total_tokens);
-    if let Some(cost) = review.total_cost {
-        println!("- **Cost:** ${cost:.2}");
-    }
-    println!("- **Commits:** {}", review.total_commits);
-    println!();
-
-    println!("\u{1f916} Generated with [Engram](https://github.com/AtticAIInc/Engram-SDK)");
+fn dead_ends(review: &BranchReview) -> Vec<String> {
+    review
+        .engrams
+        .iter()
+        .flat_map(|e| &e.dead_ends)
+        .map(|de| format!("{} — {}", de.approach, de.reason))
+        .collect()
 }
@@ -2,32 +2,52 @@ use anyhow::{Context, Result};
 use clap::Args;
 
 use engram_core::storage::GitStorage;
-use engram_protocol::{fetch_engrams, SyncOptions};
+use engram_protocol::{fetch_from_remotes, SyncOptions};
 use engram_query::SearchEngine;
 
+use super::fetch::{fetch_remotes, finish, print_fetched, print_fetched_total, RemotesArgs};
+use crate::output::progress::SyncBar;
+
 #[derive(Args)]
 pub struct PullArgs {
     /// Remote name (default: origin)
-    #[arg(default_value = "origin")]
-    pub remote: String,
+    #[arg(conflicts_with = "all_remotes")]
+    pub remote: Option<String>,
+
+    #[command(flatten)]
+    pub remotes: RemotesArgs,
+
+    /// Delete local engrams the remote has dropped since they were last
+    /// synced with it
+    #[arg(long)]
+    pub prune: bool,
 }
 
 pub fn run(args: &PullArgs) -> Result<()> {
     let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
-    let opts = SyncOptions::default();
+    let remotes = fetch_remotes(&storage, args.remote.as_deref(), &args.remotes)?;
+    let bar = SyncBar::new("Fetching", crate::output::quiet());
+    let opts = SyncOptions {
+        progress: bar.callback(),
+        prune: args.prune,
+        ..Default::default()
+    };
 
-    let result = fetch_engrams(storage.repo(), &args.remote, &opts)?;
-
-    eprintln!(
-        "Fetched {} new engram ref(s) from {}",
-        result.refs_fetched, result.remote
-    );
+    let outcomes = fetch_from_remotes(storage.repo(), &remotes, &opts);
+    bar.finish();
+    let fetched = finish(outcomes, args.remotes.require_all, print_fetched)?;
+    if fetched.len() > 1 {
+        print_fetched_total(&fetched);
+    }
 
-    // Reindex if new refs were fetched
-    if result.refs_fetched > 0 {
+    // Index just the fetched engrams, dropping any pruned ones
+    if fetched
+        .iter()
+        .any(|r| r.refs_fetched > 0 || !r.pruned.is_empty())
+    {
         let engine = SearchEngine::open(&storage)?;
-        let count = engine.rebuild(&storage)?;
-        eprintln!("Reindexed {count} engram(s).");
+        let count = engine.index_missing(&storage)?;
+        eprintln!("Indexed {count} new engram(s).");
     }
 
     Ok(())
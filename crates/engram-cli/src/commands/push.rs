@@ -1,14 +1,41 @@
+use std::io::IsTerminal;
+
 use anyhow::{Context, Result};
 use clap::Args;
 
+use engram_core::model::{EngramId, Manifest};
 use engram_core::storage::GitStorage;
-use engram_protocol::{push_engrams, SyncOptions};
+use engram_protocol::{
+    all_remotes, configured_push_remotes, push_deletions, push_to_remotes, PushFilter, PushResult,
+    SyncOptions,
+};
+
+use super::fetch::{finish, unindex, RemotesArgs};
+
+use crate::output::progress::SyncBar;
 
 #[derive(Args)]
 pub struct PushArgs {
-    /// Remote name (default: origin)
-    #[arg(default_value = "origin")]
-    pub remote: String,
+    /// Remote name, then engram IDs or prefixes to push instead of all. The
+    /// first argument is the remote only if a remote has that name; without
+    /// one, the remotes in `engram.pushRemotes` are used, or else origin.
+    #[arg(value_name = "REMOTE|ID")]
+    pub targets: Vec<String>,
+
+    #[command(flatten)]
+    pub remotes: RemotesArgs,
+
+    #[command(flatten)]
+    pub select: SelectArgs,
+
+    /// Also delete from the remote the engrams deleted here since they
+    /// were last synced with it
+    #[arg(long)]
+    pub prune_remote: bool,
+
+    /// Skip the confirmation prompt for --prune-remote
+    #[arg(long, short, requires = "prune_remote")]
+    pub yes: bool,
 
     /// Dry run — show what would be pushed
     #[arg(long)]
@@ -17,24 +44,162 @@ pub struct PushArgs {
 
 pub fn run(args: &PushArgs) -> Result<()> {
     let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
+
+    let repo = storage.repo();
+    let mut targets = args.targets.as_slice();
+    let mut remotes = Vec::new();
+    if let Some((first, rest)) = targets.split_first() {
+        if !args.remotes.all_remotes && repo.find_remote(first).is_ok() {
+            remotes.push(first.clone());
+            targets = rest;
+        }
+    }
+    if args.remotes.all_remotes {
+        remotes = all_remotes(repo)?;
+        if remotes.is_empty() {
+            anyhow::bail!("This repository has no remotes");
+        }
+    } else if remotes.is_empty() {
+        remotes = configured_push_remotes(repo)?;
+        if remotes.is_empty() {
+            remotes.push("origin".into());
+        }
+    }
+
+    let filter = args.select.filter(targets.to_vec())?;
+
+    let bar = SyncBar::new("Pushing", crate::output::quiet());
     let opts = SyncOptions {
         dry_run: args.dry_run,
+        progress: bar.callback(),
         ..Default::default()
     };
 
-    let result = push_engrams(storage.repo(), &args.remote, &opts)?;
+    let outcomes = push_to_remotes(repo, &remotes, &filter, &opts);
+    bar.finish();
+    finish(outcomes, args.remotes.require_all, |result| {
+        print_pushed(result, args.dry_run)
+    })?;
+
+    if args.prune_remote {
+        for remote in &remotes {
+            prune_remote(&storage, remote, args)?;
+        }
+    }
+
+    Ok(())
+}
 
-    if args.dry_run {
+/// Selecting engrams by manifest, shared with `engram bundle create`.
+#[derive(Args)]
+pub struct SelectArgs {
+    /// Only engrams with this tag (repeatable; all must match)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Only engrams by this agent
+    #[arg(long)]
+    pub agent: Option<String>,
+
+    /// Only engrams created since this date (YYYY-MM-DD or RFC 3339) or
+    /// this long ago (e.g. "7d")
+    #[arg(long)]
+    pub since: Option<String>,
+}
+
+impl SelectArgs {
+    /// The filter picking `ids` (or all engrams, if empty) that match.
+    pub(crate) fn filter(&self, ids: Vec<String>) -> Result<PushFilter> {
+        let since = self
+            .since
+            .as_deref()
+            .map(super::costs::parse_since)
+            .transpose()?;
+        let (tags, agent) = (self.tags.clone(), self.agent.clone());
+        let filtered = since.is_some() || !tags.is_empty() || agent.is_some();
+        Ok(PushFilter {
+            ids,
+            predicate: filtered.then(|| {
+                Box::new(move |m: &Manifest| {
+                    since.map_or(true, |since| m.created_at >= since)
+                        && agent.as_ref().map_or(true, |agent| m.agent.name == *agent)
+                        && tags.iter().all(|tag| m.tags.contains(tag))
+                }) as Box<_>
+            }),
+        })
+    }
+}
+
+/// Push the deletions of engrams removed here, after listing them and
+/// asking for confirmation.
+fn prune_remote(storage: &GitStorage, remote: &str, args: &PushArgs) -> Result<()> {
+    let repo = storage.repo();
+    let dry = SyncOptions {
+        dry_run: true,
+        ..Default::default()
+    };
+    let planned = push_deletions(repo, remote, &dry)?;
+    if planned.refs.is_empty() {
+        eprintln!("No deleted engrams to prune from {remote}");
+        return Ok(());
+    }
+
+    eprintln!(
+        "{} engram(s) deleted here are still on {remote}:",
+        planned.refs.len()
+    );
+    for id in ref_ids(&planned) {
+        eprintln!("  {}", id.as_str().get(..8).unwrap_or(id.as_str()));
+    }
+    if args.dry_run || !(args.yes || confirm(&format!("Delete them from {remote}?"))?) {
+        return Ok(());
+    }
+
+    let deleted = push_deletions(repo, remote, &SyncOptions::default())?;
+    eprintln!("Deleted {} engram ref(s) from {remote}", deleted.refs.len());
+    unindex(storage, &ref_ids(&deleted))
+}
+
+/// The engram IDs of the refs in a push, `refs/engrams/<ab>/<id>`.
+fn ref_ids(result: &PushResult) -> Vec<EngramId> {
+    result
+        .refs
+        .iter()
+        .filter_map(|name| name.rsplit('/').next())
+        .map(|id| EngramId(id.to_string()))
+        .collect()
+}
+
+/// Ask a yes/no question on the terminal; without one, the answer is no.
+/// Shared with `engram delete`.
+pub(crate) fn confirm(question: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        eprintln!("Use --yes to confirm deletion.");
+        return Ok(false);
+    }
+    eprint!("{question} [y/N] ");
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn print_pushed(result: &PushResult, dry_run: bool) {
+    if dry_run {
         eprintln!(
             "Would push {} engram ref(s) to {}",
             result.refs_pushed, result.remote
         );
+        for name in &result.refs {
+            println!("{name}");
+        }
     } else {
         eprintln!(
-            "Pushed {} engram ref(s) to {}",
-            result.refs_pushed, result.remote
+            "Pushed {} engram ref(s) to {} ({} new, {} updated, {} unchanged)",
+            result.refs_pushed,
+            result.remote,
+            result.refs_created,
+            result.refs_updated,
+            result.refs_unchanged
         );
     }
-
-    Ok(())
 }
@@ -1,12 +1,20 @@
+use std::io::Write;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use clap::Args;
 
-use engram_capture::pty::{PtySession, PtyWrapperConfig};
+use engram_capture::pty::{
+    PtySession, PtyWrapperConfig, SessionLimit, SnapshotOptions, DEFAULT_MAX_BUFFER_BYTES,
+};
+use engram_capture::session::extractor::extractor_from_config;
 use engram_capture::session::SessionBuilder;
+use engram_core::config::EngramConfig;
 use engram_core::hooks::ActiveSession;
 use engram_core::model::{AgentInfo, EngramId};
-use engram_core::storage::GitStorage;
-use engram_query::search::SearchEngine;
+use engram_core::storage::{GitStorage, InitPolicy};
+
+use crate::output;
 
 #[derive(Args)]
 pub struct RecordArgs {
@@ -18,6 +26,31 @@ pub struct RecordArgs {
     #[arg(long)]
     pub model: Option<String>,
 
+    /// Also store the unprocessed terminal output as an attachment
+    #[arg(long)]
+    pub keep_raw: bool,
+
+    /// Also store an asciicast recording for replay with `asciinema play`
+    #[arg(long)]
+    pub cast: bool,
+
+    /// Stop the agent after this long, e.g. `90s`, `30m`, `2h` (exit code 124)
+    #[arg(long, value_parser = parse_timeout)]
+    pub timeout: Option<Duration>,
+
+    /// Stop the agent once it prints more than this, e.g. `500k`, `50M` (exit code 125)
+    #[arg(long, value_parser = parse_size)]
+    pub max_output: Option<u64>,
+
+    /// Exit 0 even when the command fails, instead of with its exit code
+    /// (a timeout or --max-output still exits 124 or 125)
+    #[arg(long)]
+    pub exit_zero: bool,
+
+    /// Seconds of silence that start a new transcript entry
+    #[arg(long, value_name = "SECONDS", value_parser = parse_idle_gap, default_value = "2")]
+    pub idle_gap: Duration,
+
     /// Command and arguments to run (after --)
     #[arg(trailing_var_arg = true, required = true)]
     pub command: Vec<String>,
@@ -26,9 +59,7 @@ pub struct RecordArgs {
 pub fn run(args: &RecordArgs) -> Result<()> {
     let storage = GitStorage::discover().context("Not inside a Git repository")?;
 
-    if !storage.is_initialized() {
-        anyhow::bail!("Engram is not initialized. Run `engram init` first.");
-    }
+    storage.ensure_initialized(InitPolicy::Error)?;
 
     if args.command.is_empty() {
         anyhow::bail!("No command specified. Usage: engram record -- <command> [args...]");
@@ -40,14 +71,25 @@ pub fn run(args: &RecordArgs) -> Result<()> {
 
     let working_dir = std::env::current_dir().context("Failed to get current directory")?;
 
-    eprintln!(
-        "Recording session: {} {} (agent: {})",
-        cmd,
-        cmd_args.join(" "),
-        agent_name
-    );
+    if !output::quiet() {
+        eprintln!(
+            "Recording session: {} {} (agent: {})",
+            cmd,
+            cmd_args.join(" "),
+            agent_name
+        );
+    }
 
     let git_dir = storage.repo().path().to_path_buf();
+    let repo_config = storage
+        .repo()
+        .config()
+        .context("Failed to read git config")?;
+    let engram_config = EngramConfig::load(&repo_config)?;
+    // Fail before the agent runs rather than after
+    let repo_root = storage.workdir().unwrap_or(&working_dir);
+    let extractor = extractor_from_config(repo_root, &engram_config)
+        .context("Failed to load insight patterns")?;
 
     // Create active session so hooks can inject trailers during recording
     let agent_info_for_session = AgentInfo {
@@ -65,20 +107,32 @@ pub fn run(args: &RecordArgs) -> Result<()> {
         args: cmd_args.to_vec(),
         working_dir,
         agent_name: Some(agent_name.clone()),
+        max_buffer_bytes: DEFAULT_MAX_BUFFER_BYTES,
+        snapshot: SnapshotOptions::from_config(&engram_config),
+        timeout: args.timeout,
+        max_output_bytes: args.max_output,
     };
 
-    let session = PtySession::start(config).context("Failed to start PTY session")?;
-    let captured = session.run().context("PTY session failed")?;
+    let captured = match PtySession::start(config).and_then(|session| session.run()) {
+        Ok(captured) => captured,
+        Err(e) => {
+            ActiveSession::cleanup(&git_dir);
+            return Err(e).context("PTY session failed");
+        }
+    };
 
-    // Load accumulated commits from active session before cleanup
-    let commits = ActiveSession::load(&git_dir)
-        .map(|s| s.commits.clone())
+    // Load accumulated commits and dead ends from active session before cleanup
+    let (commits, dead_ends) = ActiveSession::load(&git_dir)
+        .map(|s| (s.commits, s.dead_ends))
         .unwrap_or_default();
 
     // Clean up active session
     ActiveSession::cleanup(&git_dir);
 
-    let exit_code = captured.exit_code;
+    let exit_code = child_exit_code(captured.exit_code, captured.signal);
+    let signal = captured.signal;
+    let interrupted = captured.interrupted;
+    let limit_exceeded = captured.limit_exceeded;
     let file_count = captured.file_changes.len();
     let duration = captured.end_time - captured.start_time;
 
@@ -88,35 +142,149 @@ pub fn run(args: &RecordArgs) -> Result<()> {
         version: None,
     };
 
-    let data = SessionBuilder::new(agent_info, captured)
+    let mut data = SessionBuilder::new(agent_info, captured)
         .with_commits(commits)
+        .with_raw_output(args.keep_raw)
+        .with_cast(args.cast)
+        .with_extractor(extractor)
+        .with_idle_threshold(args.idle_gap)
         .build();
+    data.intent.dead_ends.extend(dead_ends);
     let id = storage.create(&data).context("Failed to store engram")?;
 
-    // Best-effort incremental index update
-    if let Ok(search) = SearchEngine::open(&storage) {
-        let _ = search.index_engram(&data);
-    }
+    super::import::try_index(&storage, &data);
 
-    eprintln!();
-    eprintln!("Engram {} captured:", &id.as_str()[..8]);
-    eprintln!(
-        "  Exit code: {}",
-        exit_code
-            .map(|c| c.to_string())
-            .unwrap_or_else(|| "unknown".into())
-    );
-    eprintln!(
-        "  Duration:  {:.1}s",
-        duration.num_milliseconds() as f64 / 1000.0
-    );
-    eprintln!("  Files changed: {file_count}");
-    eprintln!();
-    eprintln!("View with: engram show {}", &id.as_str()[..8]);
+    // With --quiet, the ID is the only output besides the child's
+    if output::quiet() {
+        println!("{}", id.as_str());
+    } else {
+        eprintln!();
+        if interrupted {
+            eprintln!("Session was interrupted; the child was stopped by a signal.");
+        }
+        match limit_exceeded {
+            Some(SessionLimit::Timeout) => {
+                eprintln!("Session timed out; the child was stopped and the partial capture kept.")
+            }
+            Some(SessionLimit::MaxOutput) => eprintln!(
+                "Session exceeded --max-output; the child was stopped and the partial capture kept."
+            ),
+            None => {}
+        }
+        eprintln!("Engram {} captured:", &id.as_str()[..8]);
+        match signal {
+            Some(signal) => eprintln!("  Exit code: {exit_code} (signal {signal})"),
+            None => eprintln!("  Exit code: {exit_code}"),
+        }
+        eprintln!(
+            "  Duration:  {:.1}s",
+            duration.num_milliseconds() as f64 / 1000.0
+        );
+        eprintln!("  Files changed: {file_count}");
+        let tokens = &data.manifest.token_usage;
+        if tokens.total_tokens > 0 {
+            eprintln!("  Tokens:    {}", tokens.total_tokens);
+        }
+        if let Some(model) = &data.manifest.agent.model {
+            eprintln!("  Model:     {model}");
+        }
+        eprintln!();
+        eprintln!("View with: engram show {}", &id.as_str()[..8]);
+        if engram_config
+            .trivial
+            .is_trivial(&data.manifest, data.operations.file_changes.len())
+        {
+            eprintln!(
+                "This session changed no files and is hidden from `engram log` as trivial; \
+                 list it with `engram log --all` or remove such sessions with `engram gc --trivial`."
+            );
+        }
+    }
 
+    // Distinct exit codes so CI can tell a stopped agent from a failed one
+    let code = match limit_exceeded {
+        Some(SessionLimit::Timeout) => EXIT_TIMED_OUT,
+        Some(SessionLimit::MaxOutput) => EXIT_OUTPUT_LIMIT,
+        None if args.exit_zero => 0,
+        None => exit_code,
+    };
+    if code != 0 {
+        let _ = std::io::stdout().flush();
+        std::process::exit(code);
+    }
     Ok(())
 }
 
+/// The exit code a shell would report for the child: its own, or 128 plus
+/// the signal that killed it.
+fn child_exit_code(exit_code: Option<u32>, signal: Option<i32>) -> i32 {
+    match (signal, exit_code) {
+        (Some(signal), _) => 128 + signal,
+        // Windows exit codes are full 32-bit values such as 0xC0000005
+        (None, Some(code)) => code as i32,
+        (None, None) => 1,
+    }
+}
+
+/// Exit code when `--timeout` stopped the agent (matches coreutils `timeout`).
+pub const EXIT_TIMED_OUT: i32 = 124;
+
+/// Exit code when `--max-output` stopped the agent.
+pub const EXIT_OUTPUT_LIMIT: i32 = 125;
+
+/// Parse `90`, `90s`, `30m` or `2h`; a bare number is seconds.
+pub(crate) fn parse_timeout(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let num: f64 = num.parse().map_err(|_| format!("Invalid duration '{s}'"))?;
+    let secs = match unit {
+        "" | "s" => num,
+        "m" => num * 60.0,
+        "h" => num * 3600.0,
+        _ => return Err(format!("Unknown duration unit '{unit}'. Use s, m or h.")),
+    };
+    if !(secs > 0.0 && secs.is_finite()) {
+        return Err("Timeout must be positive".into());
+    }
+    Duration::try_from_secs_f64(secs).map_err(|_| format!("Timeout '{s}' is too long"))
+}
+
+/// Parse `--idle-gap`: a number of seconds, at most a day.
+fn parse_idle_gap(s: &str) -> Result<Duration, String> {
+    let secs: f64 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid number of seconds '{s}'"))?;
+    if !(0.0..=86_400.0).contains(&secs) {
+        return Err("Idle gap must be between 0 and 86400 seconds".into());
+    }
+    Ok(Duration::from_secs_f64(secs))
+}
+
+/// Parse a byte count with an optional `k`/`m`/`g` suffix (binary multiples,
+/// optionally followed by `b`, case-insensitive).
+fn parse_size(s: &str) -> Result<u64, String> {
+    let lower = s.trim().to_ascii_lowercase();
+    let trimmed = lower.strip_suffix('b').unwrap_or(&lower);
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (num, unit) = trimmed.split_at(split);
+    let num: u64 = num.parse().map_err(|_| format!("Invalid size '{s}'"))?;
+    let multiplier: u64 = match unit {
+        "" => 1,
+        "k" => 1024,
+        "m" => 1024 * 1024,
+        "g" => 1024 * 1024 * 1024,
+        _ => return Err(format!("Unknown size unit '{unit}'. Use k, m or g.")),
+    };
+    num.checked_mul(multiplier)
+        .ok_or_else(|| format!("Size '{s}' is too large"))
+}
+
 fn detect_agent_name(cmd: &str) -> String {
     let basename = std::path::Path::new(cmd)
         .file_name()
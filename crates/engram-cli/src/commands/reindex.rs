@@ -1,12 +1,35 @@
 use anyhow::{Context, Result};
+use clap::Args;
 
 use engram_core::storage::GitStorage;
+use engram_query::index::schema::SCHEMA_VERSION;
 use engram_query::SearchEngine;
 
-pub fn run() -> Result<()> {
+#[derive(Args)]
+pub struct ReindexArgs {
+    /// Only index engrams missing from the index and drop deleted ones,
+    /// instead of rebuilding from scratch
+    #[arg(long)]
+    pub incremental: bool,
+}
+
+pub fn run(args: &ReindexArgs) -> Result<()> {
     let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
     let engine = SearchEngine::open(&storage)?;
+    eprintln!("Search index: {}", engine.index_path().display());
+
+    if args.incremental && engine.index_version() == Some(SCHEMA_VERSION) {
+        let count = engine.index_missing(&storage)?;
+        eprintln!("Indexed {count} new engram(s).");
+        return Ok(());
+    }
 
+    match engine.index_version() {
+        Some(version) if version != SCHEMA_VERSION => eprintln!(
+            "Search index was built with schema version {version}; upgrading to version {SCHEMA_VERSION}."
+        ),
+        _ => {}
+    }
     eprintln!("Rebuilding search index...");
     let count = engine.rebuild(&storage)?;
     eprintln!("Indexed {count} engram(s).");
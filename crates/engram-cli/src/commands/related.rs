@@ -0,0 +1,67 @@
+use anyhow::{Context, Result};
+use clap::Args;
+
+use engram_core::storage::{GitStorage, InitPolicy};
+use engram_query::{related_engrams, RelatedEngram, Relation};
+
+use crate::output::OutputFormat;
+
+#[derive(Args)]
+pub struct RelatedArgs {
+    /// Engram ID (full, prefix or alias) or HEAD
+    pub id: String,
+
+    /// Maximum number of related engrams, besides lineage
+    #[arg(short = 'n', long, default_value = "10")]
+    pub limit: usize,
+}
+
+pub fn run(args: &RelatedArgs, format: OutputFormat) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::WarnAndContinue)?;
+
+    show_related(&storage, &args.id, args.limit, format)
+}
+
+/// Print the engrams related to `id`; shared with `engram show --related`.
+pub fn show_related(
+    storage: &GitStorage,
+    id: &str,
+    limit: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let related = related_engrams(storage, id, limit)
+        .with_context(|| format!("Failed to find engrams related to '{id}'"))?;
+
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&related)?)
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            if related.is_empty() {
+                eprintln!("No related engrams found.");
+            }
+            for r in &related {
+                print_related(r);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_related(r: &RelatedEngram) {
+    let m = &r.manifest;
+    let short_id = &m.id.as_str()[..8.min(m.id.as_str().len())];
+    let date = m.created_at.format("%Y-%m-%d");
+    let summary = m.summary.as_deref().unwrap_or("(no summary)");
+    let label = match r.relation {
+        Relation::Parent => "parent".to_string(),
+        Relation::Child => "child".to_string(),
+        Relation::Similar => format!("{:.2}", r.score),
+    };
+    println!("{short_id}  {date}  [{label}]  {summary}");
+    for reason in &r.reasons {
+        println!("          {reason}");
+    }
+}
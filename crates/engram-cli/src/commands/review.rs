@@ -2,7 +2,8 @@ use anyhow::{Context, Result};
 use clap::Args;
 
 use engram_core::storage::GitStorage;
-use engram_query::review_branch;
+use engram_query::review::{change_label, files_markdown};
+use engram_query::{review_branch, FileReview, Linkage, ReviewOptions};
 
 use crate::output::OutputFormat;
 
@@ -10,6 +11,36 @@ use crate::output::OutputFormat;
 pub struct ReviewArgs {
     /// Commit range (e.g. "main..feature" or "HEAD~5..HEAD")
     pub range: String,
+
+    /// Group by changed file, listing the engrams that changed each
+    #[arg(long)]
+    pub by_file: bool,
+
+    #[command(flatten)]
+    pub linkage: LinkageArgs,
+}
+
+/// Which engrams count as part of a branch.
+#[derive(Args)]
+pub struct LinkageArgs {
+    /// Only engrams named by `Engram-Id` commit trailers, not those whose
+    /// manifest records a commit in the range
+    #[arg(long, conflicts_with = "inferred")]
+    pub trailers_only: bool,
+
+    /// Also engrams with no recorded link that ran while the branch was
+    /// being worked on and changed files it changes (marked "inferred")
+    #[arg(long)]
+    pub inferred: bool,
+}
+
+impl LinkageArgs {
+    pub fn options(&self) -> ReviewOptions {
+        ReviewOptions {
+            manifest_commits: !self.trailers_only,
+            inferred: self.inferred,
+        }
+    }
 }
 
 pub fn run(args: &ReviewArgs, format: OutputFormat) -> Result<()> {
@@ -22,11 +53,11 @@ pub fn run(args: &ReviewArgs, format: OutputFormat) -> Result<()> {
     }
     let (base, head) = (parts[0], parts[1]);
 
-    let review = review_branch(&storage, base, head)?;
+    let review = review_branch(&storage, base, head, &args.linkage.options())?;
 
     match format {
-        OutputFormat::Json => {
-            let json = serde_json::json!({
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let mut json = serde_json::json!({
                 "range": review.range,
                 "total_commits": review.total_commits,
                 "engrams": review.engrams.iter().map(|e| {
@@ -37,15 +68,23 @@ pub fn run(args: &ReviewArgs, format: OutputFormat) -> Result<()> {
                         "tokens": e.manifest.token_usage.total_tokens,
                         "cost": e.manifest.token_usage.cost_usd,
                         "commit": e.commit_sha,
+                        "linkage": e.linkage,
                     })
                 }).collect::<Vec<_>>(),
                 "total_tokens": review.total_tokens,
                 "total_cost": review.total_cost,
                 "files_changed": review.files_changed,
             });
+            if args.by_file {
+                json["by_file"] = serde_json::to_value(review.by_file())?;
+            }
             println!("{}", serde_json::to_string_pretty(&json)?);
         }
-        OutputFormat::Text | OutputFormat::Markdown => {
+        OutputFormat::Markdown if args.by_file => {
+            println!("# Branch review: {}\n", review.range);
+            print!("{}", files_markdown(&review.by_file()));
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
             println!("Branch review: {}\n", review.range);
             println!(
                 "  Commits: {}   Engrams: {}",
@@ -60,14 +99,24 @@ pub fn run(args: &ReviewArgs, format: OutputFormat) -> Result<()> {
                 println!("  Files changed: {}", review.files_changed.len());
             }
 
-            if !review.engrams.is_empty() {
+            if args.by_file {
+                println!();
+                print!("{}", by_file_text(&review.by_file()));
+            } else if !review.engrams.is_empty() {
                 println!("\nEngrams:");
                 for entry in &review.engrams {
                     let m = &entry.manifest;
                     let short_id = &m.id.as_str()[..8];
                     let summary = m.summary.as_deref().unwrap_or("(no summary)");
-                    let commit_short = &entry.commit_sha[..8];
-                    println!("  {short_id}  [{commit_short}]  {summary}");
+                    let commit_short = entry
+                        .commit_sha
+                        .as_deref()
+                        .map_or("--------", |sha| &sha[..8.min(sha.len())]);
+                    let linkage = match entry.linkage {
+                        Linkage::Trailer => String::new(),
+                        other => format!("  ({})", other.as_str()),
+                    };
+                    println!("  {short_id}  [{commit_short}]  {summary}{linkage}");
                 }
             }
         }
@@ -75,3 +124,29 @@ pub fn run(args: &ReviewArgs, format: OutputFormat) -> Result<()> {
 
     Ok(())
 }
+
+/// Each file, with the engrams that changed it, oldest first.
+pub(crate) fn by_file_text(files: &[FileReview]) -> String {
+    let mut out = String::new();
+    for file in files {
+        if file.previous_paths.is_empty() {
+            out.push_str(&format!("{}\n", file.path));
+        } else {
+            out.push_str(&format!(
+                "{} (was {})\n",
+                file.path,
+                file.previous_paths.join(", ")
+            ));
+        }
+        for c in &file.contributions {
+            let short_id = &c.engram_id.as_str()[..8];
+            let summary = c.summary.as_deref().unwrap_or("(no summary)");
+            out.push_str(&format!(
+                "  {short_id}  {:<10}  {:<12}  {summary}\n",
+                change_label(&c.change_type),
+                c.agent
+            ));
+        }
+    }
+    out
+}
@@ -1,48 +1,356 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::{Context, Result};
 use clap::Args;
+use serde::Serialize;
 
-use engram_core::storage::GitStorage;
-use engram_query::SearchEngine;
+use engram_core::model::Manifest;
+use engram_core::storage::{GitStorage, ListOptions};
+use engram_query::index::{parse_date_bound, FilterField, DEFAULT_SNIPPET_MAX_CHARS};
+use engram_query::{
+    GrepOptions, MultiRepoSearch, SearchEngine, SearchOptions, SearchPage, SearchSort,
+};
 
-use crate::output::OutputFormat;
+use crate::output::format::format_search_results_markdown;
+use crate::output::ndjson::NdjsonWriter;
+use crate::output::{theme, OutputFormat};
 
 #[derive(Args)]
 pub struct SearchArgs {
-    /// Search query (free-text, searches intent, transcript, file paths, dead ends)
-    pub query: String,
+    /// Search query (free-text, searches intent, transcript, file paths, dead ends,
+    /// decisions, tags and tool names; scope with agent:, model:, file:, tag:,
+    /// dead_end:, has_errors:true). With --regex, narrows which engrams are scanned.
+    /// May be left out when --agent, --file or --tag is given.
+    #[arg(required_unless_present_any = ["regex", "agent", "file", "tag"])]
+    pub query: Option<String>,
+
+    /// Scan for lines matching this regular expression instead of searching
+    /// the index (see `engram grep`)
+    #[arg(long, value_name = "PATTERN")]
+    pub regex: Option<String>,
+
+    /// Search every repository listed in ~/.config/engram/repos.toml
+    #[arg(long, conflicts_with = "regex")]
+    pub all_repos: bool,
+
+    /// Search this repository instead of the current one (repeatable)
+    #[arg(long = "repo", value_name = "PATH", conflicts_with = "regex")]
+    pub repos: Vec<PathBuf>,
 
     /// Maximum number of results
     #[arg(short = 'n', long, default_value = "10")]
     pub limit: usize,
+
+    /// Skip this many results (for paging with --limit)
+    #[arg(long, default_value = "0")]
+    pub offset: usize,
+
+    /// Result order: relevance, newest, oldest, cost, tokens
+    #[arg(long, default_value = "relevance")]
+    pub sort: SearchSort,
+
+    /// Only engrams by this agent (like `agent:` in the query)
+    #[arg(long)]
+    pub agent: Option<String>,
+
+    /// Only engrams that changed this file (like `file:` in the query),
+    /// relative to the current directory or absolute; across repositories,
+    /// relative to each repository's root
+    #[arg(long, conflicts_with = "regex")]
+    pub file: Option<String>,
+
+    /// Only engrams with this tag (like `tag:` in the query)
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Only engrams created on or after this date (YYYY-MM-DD or RFC 3339),
+    /// or within this long (e.g. "7d", "2w")
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only engrams created on or before this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    pub until: Option<String>,
+
+    /// Also match words with typos (one or two edits), ranked below exact matches
+    #[arg(long)]
+    pub fuzzy: bool,
+
+    /// Also print how many matches each agent, model and tag has
+    #[arg(long)]
+    pub facets: bool,
+
+    /// Only engrams that cost at least this much (USD); excludes engrams
+    /// with no recorded cost
+    #[arg(long, value_name = "USD")]
+    pub min_cost: Option<f64>,
+
+    /// Only engrams that cost at most this much (USD); excludes engrams
+    /// with no recorded cost
+    #[arg(long, value_name = "USD")]
+    pub max_cost: Option<f64>,
+
+    /// Only engrams that used at least this many tokens
+    #[arg(long, value_name = "N")]
+    pub min_tokens: Option<u64>,
+
+    /// Only engrams that used at most this many tokens
+    #[arg(long, value_name = "N")]
+    pub max_tokens: Option<u64>,
+
+    /// Maximum length of the matched-text snippet shown under each result
+    #[arg(long, default_value_t = DEFAULT_SNIPPET_MAX_CHARS)]
+    pub snippet_chars: usize,
+
+    /// If the search index can't be opened, rebuild it first rather than
+    /// scanning engrams
+    #[arg(long, conflicts_with_all = ["regex", "all_repos", "repos"])]
+    pub rebuild_index: bool,
+}
+
+/// The repository's search engine; with `rebuild`, its index is rebuilt
+/// first if it can't be opened. Otherwise searches fall back to scanning
+/// engrams, with a warning.
+pub(crate) fn open_engine(storage: &GitStorage, rebuild: bool) -> Result<SearchEngine> {
+    let engine = SearchEngine::open(storage).context("Failed to open search index")?;
+    if rebuild
+        && engine
+            .repair(storage)
+            .context("Failed to rebuild the search index")?
+        && !crate::output::quiet()
+    {
+        eprintln!("Rebuilt the search index.");
+    }
+    Ok(engine)
 }
 
 pub fn run(args: &SearchArgs, format: OutputFormat) -> Result<()> {
-    let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
-    let engine = SearchEngine::open(&storage)?;
+    let query = args.query.as_deref().unwrap_or_default();
+    let since = args
+        .since
+        .as_deref()
+        .map(super::costs::parse_since)
+        .transpose()?;
+    let until = args
+        .until
+        .as_deref()
+        .map(|s| parse_date_bound(s, true))
+        .transpose()?;
+    if let Some(pattern) = &args.regex {
+        let options = GrepOptions {
+            query: args.query.clone(),
+            filter: ListOptions {
+                agent_filter: args.agent.clone(),
+                tag: args.tag.clone(),
+                since,
+                until,
+                ..Default::default()
+            },
+            limit: args.limit,
+            ..Default::default()
+        };
+        return super::grep::scan(pattern, &options, format).map(drop);
+    }
 
-    let results = engine.search(&storage, &args.query, args.limit)?;
+    let multi_repo = args.all_repos || !args.repos.is_empty();
+    let storage = if multi_repo {
+        None
+    } else {
+        Some(GitStorage::discover().context("Not in a Git repository with engram")?)
+    };
+    let file = match (&args.file, &storage) {
+        (Some(file), Some(storage)) => Some(super::trace::repo_path(storage, file)?),
+        (file, _) => file.clone(),
+    };
 
-    if results.is_empty() {
-        eprintln!("No results found for: {}", args.query);
+    // Applied by the index, so --limit and --offset count matching engrams
+    let filters = [
+        (FilterField::Agent, &args.agent),
+        (FilterField::File, &file),
+        (FilterField::Tag, &args.tag),
+    ]
+    .into_iter()
+    .filter_map(|(field, value)| value.clone().map(|value| (field, value)))
+    .collect();
+    let options = SearchOptions {
+        limit: args.limit,
+        offset: args.offset,
+        sort: args.sort,
+        since,
+        until,
+        snippet_max_chars: args.snippet_chars,
+        fuzzy: args.fuzzy,
+        facets: args.facets,
+        min_cost: args.min_cost,
+        max_cost: args.max_cost,
+        min_tokens: args.min_tokens,
+        max_tokens: args.max_tokens,
+        filters,
+    };
+    let page = match &storage {
+        Some(storage) => {
+            open_engine(storage, args.rebuild_index)?.search(storage, query, &options)?
+        }
+        None => {
+            let found = multi_repo_search(args)?.search(query, &options);
+            for skipped in &found.skipped {
+                eprintln!("Skipped {}: {}", skipped.path.display(), skipped.reason);
+            }
+            found.page
+        }
+    };
+    print_page(args, query, &page, format)
+}
+
+/// The repositories named by `--repo`, plus the configured ones with `--all-repos`.
+fn multi_repo_search(args: &SearchArgs) -> Result<MultiRepoSearch> {
+    let mut repos = args.repos.clone();
+    if args.all_repos {
+        let path = MultiRepoSearch::default_config_path().context("HOME is not set")?;
+        if !path.exists() {
+            anyhow::bail!(
+                "No repositories configured. List them in {} as `repos = [\"~/src/api\", ...]`, \
+                 or pass --repo.",
+                path.display()
+            );
+        }
+        repos.extend(MultiRepoSearch::load_config(&path)?);
+    }
+    Ok(MultiRepoSearch::new(repos))
+}
+
+/// One `--format ndjson` search result: the manifest's fields plus the match.
+#[derive(Serialize)]
+struct ResultRecord<'a> {
+    #[serde(flatten)]
+    manifest: &'a Manifest,
+    score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snippet: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repo: Option<&'a Path>,
+}
+
+fn print_page(
+    args: &SearchArgs,
+    query: &str,
+    page: &SearchPage,
+    format: OutputFormat,
+) -> Result<()> {
+    if page.is_empty() {
+        if page.total > 0 {
+            eprintln!("No results at offset {} ({})", args.offset, page.showing());
+        } else {
+            eprintln!("No results found for: {query}");
+        }
         return Ok(());
     }
+    let results = &page.results;
 
     match format {
         OutputFormat::Json => {
             let manifests: Vec<_> = results.iter().map(|r| &r.manifest).collect();
-            println!("{}", serde_json::to_string_pretty(&manifests)?);
+            let multi_repo = results.iter().any(|r| r.repo.is_some());
+            let json = if multi_repo {
+                let results: Vec<_> = results
+                    .iter()
+                    .map(|r| serde_json::json!({ "repo": r.repo, "manifest": r.manifest }))
+                    .collect();
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "results": results,
+                    "total": page.total,
+                    "facets": page.facets,
+                }))?
+            } else {
+                match &page.facets {
+                    Some(facets) => serde_json::to_string_pretty(&serde_json::json!({
+                        "results": manifests,
+                        "total": page.total,
+                        "facets": facets,
+                    }))?,
+                    None => serde_json::to_string_pretty(&manifests)?,
+                }
+            };
+            println!("{json}");
+        }
+        OutputFormat::Ndjson => {
+            let mut out = NdjsonWriter::stdout();
+            for result in results {
+                out.write(
+                    "search_result",
+                    &ResultRecord {
+                        manifest: &result.manifest,
+                        score: result.score,
+                        snippet: result.snippet.as_deref(),
+                        repo: result.repo.as_deref(),
+                    },
+                )?;
+            }
+            if let Some(facets) = &page.facets {
+                out.write("facets", facets)?;
+            }
         }
-        OutputFormat::Text | OutputFormat::Markdown => {
-            eprintln!("Found {} result(s) for: {}\n", results.len(), args.query);
-            for result in &results {
+        OutputFormat::Markdown => {
+            if !crate::output::quiet() {
+                eprintln!(
+                    "Found {} result(s) for: {} ({})\n",
+                    page.total,
+                    query,
+                    page.showing()
+                );
+            }
+            print!("{}", format_search_results_markdown(results));
+            if let Some(facets) = &page.facets {
+                println!("\n{facets}");
+            }
+        }
+        OutputFormat::Text | OutputFormat::Csv => {
+            if !crate::output::quiet() {
+                eprintln!(
+                    "Found {} result(s) for: {} ({})\n",
+                    page.total,
+                    query,
+                    page.showing()
+                );
+            }
+            // Styled matches when coloring, markdown-style markers otherwise
+            let markers = theme::MATCH.markers();
+            let (open, close) = markers
+                .as_ref()
+                .map_or(("**", "**"), |(open, close)| (open.as_str(), *close));
+            for result in results {
                 let m = &result.manifest;
-                let short_id = &m.id.as_str()[..8];
+                let short_id = theme::ID.paint(&m.id.as_str()[..8]);
                 let summary = m.summary.as_deref().unwrap_or("(no summary)");
-                let score = result.score;
-                println!("{short_id}  {summary}  (score: {score:.2})");
+                if args.sort == SearchSort::Relevance {
+                    let score = theme::DATE.paint(format!("(score: {:.2})", result.score));
+                    println!("{short_id}  {summary}  {score}");
+                } else {
+                    let date = theme::DATE.paint(m.created_at.format("%Y-%m-%d"));
+                    println!("{short_id}  {date}  {summary}");
+                }
+                if let Some(repo) = &result.repo {
+                    println!("          in {}", repo.display());
+                }
+                if !result.highlights.is_empty() {
+                    if let Some(snippet) = result.marked_snippet(open, close) {
+                        println!("          {}", one_line(&snippet));
+                    }
+                    if result.truncated_transcript {
+                        println!("          (matched in truncated transcript)");
+                    }
+                }
+            }
+            if let Some(facets) = &page.facets {
+                println!("\n{facets}");
             }
         }
     }
 
     Ok(())
 }
+
+/// Collapse the newlines and indentation of a transcript fragment.
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
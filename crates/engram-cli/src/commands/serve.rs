@@ -0,0 +1,94 @@
+use std::net::SocketAddr;
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use engram_api::ApiOptions;
+use engram_core::config::EngramConfig;
+use engram_core::storage::{GitStorage, InitPolicy};
+
+#[derive(Args)]
+#[command(after_help = "\
+Endpoints (all GET, JSON):
+  /engrams              agent, tag, since, until, min_cost, max_cost,
+                        min_tokens, max_tokens, limit (50), offset
+  /engrams/{id}         the whole engram; id may be a prefix or HEAD
+  /search?q=            agent, tag, since, until, limit (10), offset
+  /trace?file=
+  /graph                focus, depth (2)
+  /stats
+  /review?base=         head (HEAD), inferred")]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    pub bind: SocketAddr,
+
+    /// Let browsers call the API from this origin, or `*` for any
+    /// (repeatable; default: engram.serve.corsOrigin)
+    #[arg(long, value_name = "ORIGIN")]
+    pub cors_origin: Vec<String>,
+
+    /// Serve on a non-loopback address without a token, letting anyone who
+    /// can reach it read the repository's engrams
+    #[arg(long)]
+    pub insecure: bool,
+}
+
+pub fn run(args: &ServeArgs) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::WarnAndContinue)?;
+
+    // A bare repository has no working directory; its path is the repo
+    let repo_path = storage
+        .workdir()
+        .unwrap_or_else(|| storage.repo().path())
+        .to_path_buf();
+
+    let repo_config = storage
+        .repo()
+        .config()
+        .context("Failed to read git config")?;
+    let config = EngramConfig::load(&repo_config)?;
+    let token = match std::env::var(engram_api::TOKEN_ENV) {
+        Ok(token) if !token.is_empty() => Some(token),
+        _ => config.serve_token.filter(|t| !t.is_empty()),
+    };
+    if token.is_none() && !args.bind.ip().is_loopback() {
+        if !args.insecure {
+            anyhow::bail!(
+                "Refusing to serve on {} without a token: anyone who can reach it could \
+                 read the repository's engrams. Set {} or engram.serve.token, or pass --insecure",
+                args.bind,
+                engram_api::TOKEN_ENV
+            );
+        }
+        eprintln!(
+            "Warning: serving on {} without a token (--insecure)",
+            args.bind
+        );
+    }
+    let options = ApiOptions {
+        token,
+        cors_origins: if args.cors_origin.is_empty() {
+            config.serve_cors_origins
+        } else {
+            args.cors_origin.clone()
+        },
+        insecure: args.insecure,
+    };
+
+    let rt = tokio::runtime::Runtime::new().context("Failed to create async runtime")?;
+    rt.block_on(async {
+        let listener = tokio::net::TcpListener::bind(args.bind)
+            .await
+            .with_context(|| format!("Failed to listen on {}", args.bind))?;
+        eprintln!(
+            "Serving the engram API on http://{}",
+            listener.local_addr()?
+        );
+        engram_api::serve(listener, repo_path, &options)
+            .await
+            .context("API server error")
+    })
+}
@@ -1,61 +1,276 @@
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
-use clap::Args;
-use engram_core::storage::GitStorage;
+use clap::{Args, ValueEnum};
+use engram_capture::session::CAST_ATTACHMENT;
+use engram_core::display::DisplayTimezone;
+use engram_core::model::{Role, Transcript, TranscriptContent, TranscriptEntry};
+use engram_core::storage::{EngramPart, GitStorage, InitPolicy};
 
-use crate::output::format::{format_engram_full, format_intent};
-use crate::output::OutputFormat;
+use crate::output::format::{format_engram_full, format_engram_json, format_intent};
+use crate::output::{porcelain, OutputFormat};
 
 #[derive(Args)]
 pub struct ShowArgs {
-    /// Engram ID (full or prefix)
+    /// Engram ID (full, prefix or alias)
     pub id: String,
 
     /// Show only the intent
     #[arg(long)]
     pub intent: bool,
 
-    /// Show only the transcript (as JSONL)
+    /// Show only the transcript: each entry's number, time, role and content
     #[arg(long)]
     pub transcript: bool,
 
+    /// Skip this many transcript entries (after --role filtering)
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "transcript")]
+    pub from: usize,
+
+    /// Show at most this many transcript entries
+    #[arg(long, value_name = "N", requires = "transcript")]
+    pub count: Option<usize>,
+
+    /// Only transcript entries with these roles: user, assistant, system, tool
+    #[arg(
+        long,
+        value_name = "ROLES",
+        value_delimiter = ',',
+        value_parser = parse_role,
+        requires = "transcript"
+    )]
+    pub role: Vec<Role>,
+
+    /// Print the selected transcript entries as stored, one JSON object per line
+    #[arg(long, requires = "transcript")]
+    pub raw_jsonl: bool,
+
+    /// With --format json, print only these parts of the engram
+    /// (`--fields manifest` prints the bare manifest, as before)
+    #[arg(long, value_name = "FIELDS", value_enum, value_delimiter = ',')]
+    pub fields: Vec<ShowField>,
+
     /// Show only operations
     #[arg(long)]
     pub operations: bool,
+
+    /// Print stable tab-separated `key<TAB>value` lines for scripts (see
+    /// README), whatever --format says
+    #[arg(long, conflicts_with_all = ["intent", "transcript", "operations", "related", "cast", "fields"])]
+    pub porcelain: bool,
+
+    /// Show only related engrams (see `engram related`)
+    #[arg(long)]
+    pub related: bool,
+
+    /// Extract the asciicast recording (stored with `engram record --cast`)
+    #[arg(long)]
+    pub cast: bool,
+
+    /// Write the extracted recording to this file instead of stdout
+    #[arg(long, requires = "cast")]
+    pub out: Option<PathBuf>,
+}
+
+/// A top-level part of the `engram show` JSON document.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum ShowField {
+    Manifest,
+    Intent,
+    Operations,
+    Transcript,
+    Lineage,
+}
+
+impl ShowField {
+    fn key(self) -> &'static str {
+        match self {
+            ShowField::Manifest => "manifest",
+            ShowField::Intent => "intent",
+            ShowField::Operations => "operations",
+            ShowField::Transcript => "transcript",
+            ShowField::Lineage => "lineage",
+        }
+    }
 }
 
 pub fn run(args: &ShowArgs, format: OutputFormat) -> Result<()> {
     let storage = GitStorage::discover().context("Not inside a Git repository")?;
 
-    if !storage.is_initialized() {
-        anyhow::bail!("Engram is not initialized. Run `engram init` first.");
-    }
+    storage.ensure_initialized(InitPolicy::WarnAndContinue)?;
 
     let resolved_id = storage
         .resolve(&args.id)
         .with_context(|| format!("Failed to resolve engram '{}'", args.id))?;
 
+    if args.related {
+        return super::related::show_related(&storage, &resolved_id, 10, format);
+    }
+    if args.transcript {
+        return show_transcript(&storage, &resolved_id, args, format);
+    }
+
+    if args.cast {
+        let cast = storage
+            .read_attachment(&resolved_id, CAST_ATTACHMENT)
+            .with_context(|| format!("Failed to read engram '{}'", resolved_id))?
+            .with_context(|| {
+                format!("Engram {resolved_id} has no recording; capture one with `engram record --cast`")
+            })?;
+        match &args.out {
+            Some(path) => {
+                std::fs::write(path, &cast)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                eprintln!("Wrote {}", path.display());
+            }
+            None => std::io::stdout().write_all(&cast)?,
+        }
+        return Ok(());
+    }
+
     let data = storage
         .read(&resolved_id)
         .with_context(|| format!("Failed to read engram '{}'", resolved_id))?;
 
+    if args.porcelain {
+        print!("{}", porcelain::show_lines(&data));
+        return Ok(());
+    }
+
     let output = if args.intent {
         format_intent(&data, format)
-    } else if args.transcript {
-        match format {
-            OutputFormat::Json => {
-                serde_json::to_string_pretty(&data.transcript.entries).unwrap_or_default()
-            }
-            OutputFormat::Text | OutputFormat::Markdown => {
-                let jsonl = data.transcript.to_jsonl().unwrap_or_default();
-                String::from_utf8_lossy(&jsonl).to_string()
-            }
-        }
     } else if args.operations {
         serde_json::to_string_pretty(&data.operations).unwrap_or_default()
+    } else if let (OutputFormat::Json | OutputFormat::Ndjson, [ShowField::Manifest]) =
+        (format, args.fields.as_slice())
+    {
+        serde_json::to_string_pretty(&data.manifest).unwrap_or_default()
+    } else if let (OutputFormat::Json | OutputFormat::Ndjson, false) =
+        (format, args.fields.is_empty())
+    {
+        let fields: Vec<_> = args.fields.iter().map(|f| f.key()).collect();
+        format_engram_json(&data, &fields)
     } else {
-        format_engram_full(&data, format)
+        let config = storage
+            .repo()
+            .config()
+            .context("Failed to read git config")?;
+        format_engram_full(&data, DisplayTimezone::load(&config), format)
     };
 
     println!("{output}");
     Ok(())
 }
+
+/// Longest tool call input shown, in characters of pretty-printed JSON.
+const MAX_TOOL_INPUT_CHARS: usize = 2000;
+
+fn parse_role(role: &str) -> Result<Role, String> {
+    serde_json::from_value(serde_json::Value::String(role.to_lowercase()))
+        .map_err(|_| format!("unknown role '{role}'; use user, assistant, system or tool"))
+}
+
+/// Print a page of the transcript, reading only its JSONL and parsing it one
+/// entry at a time.
+fn show_transcript(
+    storage: &GitStorage,
+    id: &str,
+    args: &ShowArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let bytes = storage
+        .read_part(id, EngramPart::Transcript)
+        .with_context(|| format!("Failed to read transcript of engram '{id}'"))?;
+    let text = String::from_utf8_lossy(&bytes);
+
+    // entries_from_jsonl skips the same blank lines, so lines and entries pair up
+    let lines = text.lines().filter(|line| !line.trim().is_empty());
+    let count = args.count.unwrap_or(usize::MAX);
+    let mut total = 0;
+    let mut selected = Vec::new();
+    for (index, (line, entry)) in lines.zip(Transcript::entries_from_jsonl(&text)).enumerate() {
+        let entry = entry.with_context(|| format!("Invalid transcript entry {index}"))?;
+        if !args.role.is_empty() && !args.role.contains(&entry.role) {
+            continue;
+        }
+        total += 1;
+        if total > args.from && selected.len() < count {
+            selected.push((index, line, entry));
+        }
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    if args.raw_jsonl {
+        for (_, line, _) in &selected {
+            writeln!(stdout, "{line}")?;
+        }
+    } else if let OutputFormat::Json | OutputFormat::Ndjson = format {
+        let entries: Vec<_> = selected.iter().map(|(_, _, entry)| entry).collect();
+        writeln!(stdout, "{}", serde_json::to_string_pretty(&entries)?)?;
+    } else {
+        let dim = stdout.is_terminal();
+        for (index, _, entry) in &selected {
+            write!(stdout, "{}", render_entry(*index, entry, dim))?;
+        }
+    }
+
+    let end = args.from + selected.len();
+    if end < total {
+        eprintln!(
+            "Showing entries {}..{end} of {total}; continue with --from {end}",
+            args.from
+        );
+    }
+    Ok(())
+}
+
+fn render_entry(index: usize, entry: &TranscriptEntry, dim: bool) -> String {
+    let role = match entry.role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::System => "system",
+        Role::Tool => "tool",
+    };
+    let head = format!(
+        "#{index} {} {role}:",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S")
+    );
+    let body = match &entry.content {
+        TranscriptContent::Text { text } => format!(" {}", indent(text)),
+        TranscriptContent::Thinking { text } if dim => {
+            format!(" \x1b[2m(thinking) {}\x1b[0m", indent(text))
+        }
+        TranscriptContent::Thinking { text } => format!(" (thinking) {}", indent(text)),
+        TranscriptContent::ToolUse {
+            tool_name, input, ..
+        } => {
+            let input = serde_json::to_string_pretty(input).unwrap_or_default();
+            let input = match input.char_indices().nth(MAX_TOOL_INPUT_CHARS) {
+                Some((cut, _)) => format!(
+                    "{}… [{} more chars]",
+                    &input[..cut],
+                    input[cut..].chars().count()
+                ),
+                None => input,
+            };
+            format!(" (tool call) {tool_name}\n    {}", indent(&input))
+        }
+        TranscriptContent::ToolResult {
+            output, is_error, ..
+        } => {
+            let label = if *is_error {
+                "tool error"
+            } else {
+                "tool result"
+            };
+            format!(" ({label}) {}", indent(output))
+        }
+    };
+    format!("{head}{body}\n")
+}
+
+/// Indent continuation lines under the entry's header.
+fn indent(text: &str) -> String {
+    text.trim_end().replace('\n', "\n    ")
+}
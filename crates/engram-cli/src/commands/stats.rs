@@ -1,22 +1,95 @@
 use std::collections::BTreeMap;
 
 use anyhow::{Context, Result};
-use engram_core::storage::{GitStorage, ListOptions};
+use chrono::SecondsFormat;
+use clap::Args;
+use engram_core::storage::{GitStorage, InitPolicy, ListOptions};
+use engram_query::index::parse_date_bound;
+use engram_query::{
+    agent_stats_for, file_churn, usage_buckets, usage_by, AgentStats, Bucket, GroupUsage,
+    StatsDimension, UsageBucket,
+};
 
+use super::costs::parse_since;
 use crate::output::OutputFormat;
 
-pub fn run(format: OutputFormat) -> Result<()> {
+/// Widest bar of the engrams-per-week histogram.
+const HISTOGRAM_WIDTH: usize = 40;
+
+#[derive(Args)]
+pub struct StatsArgs {
+    /// Report per-file churn: the files engrams touch most
+    #[arg(long)]
+    pub files: bool,
+
+    /// Number of files to show with --files
+    #[arg(long, default_value = "20", requires = "files")]
+    pub top: usize,
+
+    /// Also break usage down by agent and model, with files touched and
+    /// last activity
+    #[arg(long, conflicts_with = "files")]
+    pub by_agent: bool,
+
+    /// Group totals by agent, model, capture-mode or tag (an engram counts
+    /// once per tag)
+    #[arg(long, value_name = "DIMENSION", conflicts_with_all = ["files", "by_agent"])]
+    pub by: Option<StatsDimension>,
+
+    /// Only engrams created since this date (YYYY-MM-DD or RFC 3339) or
+    /// this long ago (e.g. "90d", "12w", "6m")
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only engrams created on or before this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    pub until: Option<String>,
+
+    /// Only engrams by this agent
+    #[arg(long)]
+    pub agent: Option<String>,
+}
+
+impl StatsArgs {
+    /// Whether any option narrows or groups the engrams; bare `engram stats`
+    /// keeps its original report.
+    fn sliced(&self) -> bool {
+        self.by.is_some() || self.since.is_some() || self.until.is_some() || self.agent.is_some()
+    }
+}
+
+pub fn run(args: &StatsArgs, format: OutputFormat) -> Result<()> {
     let storage = GitStorage::discover().context("Not inside a Git repository")?;
 
-    if !storage.is_initialized() {
-        anyhow::bail!("Engram is not initialized. Run `engram init` first.");
+    storage.ensure_initialized(InitPolicy::WarnAndContinue)?;
+
+    if format == OutputFormat::Csv && !args.by_agent && args.by.is_none() {
+        anyhow::bail!(
+            "--format csv needs --by-agent or --by: it prints a row per agent and model, \
+             or per group"
+        );
+    }
+    let since = args.since.as_deref().map(parse_since).transpose()?;
+    let until = args
+        .until
+        .as_deref()
+        .map(|s| parse_date_bound(s, true))
+        .transpose()?;
+    let opts = ListOptions {
+        agent_filter: args.agent.clone(),
+        since,
+        until,
+        ..Default::default()
+    };
+    if args.files {
+        return print_churn(&storage, &opts, args.top, format);
     }
 
-    let manifests = storage
-        .list(&ListOptions::default())
-        .context("Failed to list engrams")?;
+    let manifests = storage.list(&opts).context("Failed to list engrams")?;
 
-    if manifests.is_empty() {
+    // Sliced reports still print their (empty) JSON or CSV, for scripts
+    let text = matches!(format, OutputFormat::Text | OutputFormat::Markdown);
+    if manifests.is_empty() && (text || !args.sliced()) {
         println!("No engrams found.");
         return Ok(());
     }
@@ -42,9 +115,19 @@ pub fn run(format: OutputFormat) -> Result<()> {
         *by_mode.entry(format!("{:?}", m.capture_mode)).or_default() += 1;
     }
 
+    let agent_stats = if args.by_agent {
+        Some(agent_stats_for(&storage, &manifests).context("Failed to compute agent statistics")?)
+    } else {
+        None
+    };
+    let groups = args.by.map(|by| usage_by(&manifests, by));
+    let weeks = args
+        .sliced()
+        .then(|| usage_buckets(&manifests, Bucket::Week, since, until));
+
     match format {
-        OutputFormat::Json => {
-            let stats = serde_json::json!({
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let mut stats = serde_json::json!({
                 "total_engrams": total,
                 "total_tokens": total_tokens,
                 "total_cost_usd": total_cost,
@@ -60,8 +143,22 @@ pub fn run(format: OutputFormat) -> Result<()> {
                 }).collect::<Vec<_>>(),
                 "by_capture_mode": by_mode,
             });
+            if let Some(agent_stats) = &agent_stats {
+                stats["by_agent_model"] = serde_json::to_value(agent_stats)?;
+            }
+            if let (Some(by), Some(groups)) = (args.by, &groups) {
+                stats["by"] = serde_json::to_value(by)?;
+                stats["groups"] = serde_json::to_value(groups)?;
+            }
+            if let Some(weeks) = &weeks {
+                stats["weeks"] = serde_json::to_value(weeks)?;
+            }
             println!("{}", serde_json::to_string_pretty(&stats).unwrap());
         }
+        OutputFormat::Csv => match (args.by, &groups) {
+            (Some(by), Some(groups)) => write_group_csv(by, groups)?,
+            _ => write_agent_csv(agent_stats.as_deref().unwrap_or_default())?,
+        },
         OutputFormat::Text | OutputFormat::Markdown => {
             println!("Engram Statistics");
             println!("=================");
@@ -77,16 +174,206 @@ pub fn run(format: OutputFormat) -> Result<()> {
             }
             println!();
 
-            println!("By Agent:");
-            for (name, (count, tokens, cost)) in &by_agent {
-                println!("  {name}: {count} engrams, {tokens} tokens, ${cost:.2}");
+            if let (Some(by), Some(groups)) = (args.by, &groups) {
+                println!("By {}:", dimension_title(by));
+                for g in groups {
+                    let t = &g.totals;
+                    println!(
+                        "  {}: {} engrams, {} tokens, ${:.2}",
+                        g.key, t.engrams, t.total_tokens, t.cost_usd
+                    );
+                }
+            } else {
+                println!("By Agent:");
+                for (name, (count, tokens, cost)) in &by_agent {
+                    println!("  {name}: {count} engrams, {tokens} tokens, ${cost:.2}");
+                }
+                println!();
+
+                println!("By Capture Mode:");
+                for (mode, count) in &by_mode {
+                    println!("  {mode}: {count}");
+                }
             }
-            println!();
 
-            println!("By Capture Mode:");
-            for (mode, count) in &by_mode {
-                println!("  {mode}: {count}");
+            if let Some(weeks) = &weeks {
+                println!();
+                print_histogram(weeks);
             }
+
+            if let Some(agent_stats) = &agent_stats {
+                println!();
+                print_agent_stats(agent_stats);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_agent_stats(stats: &[AgentStats]) {
+    println!("By Agent and Model:");
+    for s in stats {
+        let model = s.model.as_deref().unwrap_or("unknown model");
+        let modes: Vec<_> = s
+            .capture_modes
+            .iter()
+            .map(|(mode, count)| format!("{count} {mode}"))
+            .collect();
+        println!(
+            "  {} / {model}: {} engrams ({})",
+            s.agent,
+            s.engrams,
+            modes.join(", ")
+        );
+        println!(
+            "    tokens:      {} ({} in, {} out), {:.0} per session",
+            s.total_tokens, s.input_tokens, s.output_tokens, s.avg_tokens
+        );
+        println!("    cost:        ${:.2}", s.cost_usd);
+        println!("    files:       {}", s.files_touched);
+        println!(
+            "    last active: {}",
+            s.last_active.format("%Y-%m-%d %H:%M")
+        );
+    }
+}
+
+fn dimension_title(by: StatsDimension) -> &'static str {
+    match by {
+        StatsDimension::Agent => "Agent",
+        StatsDimension::Model => "Model",
+        StatsDimension::CaptureMode => "Capture Mode",
+        StatsDimension::Tag => "Tag",
+    }
+}
+
+/// A bar of `#` per week, scaled so the busiest week fills the width.
+fn print_histogram(weeks: &[UsageBucket]) {
+    println!("Engrams per Week:");
+    let most = weeks.iter().map(|w| w.totals.engrams).max().unwrap_or(0);
+    for week in weeks {
+        let count = week.totals.engrams;
+        let width = (count * HISTOGRAM_WIDTH).div_ceil(most.max(1));
+        println!(
+            "  {}  {:<HISTOGRAM_WIDTH$} {count}",
+            week.start.format("%Y-%m-%d"),
+            "#".repeat(width)
+        );
+    }
+}
+
+fn write_group_csv(by: StatsDimension, groups: &[GroupUsage]) -> Result<()> {
+    let mut out = csv::Writer::from_writer(std::io::stdout());
+    let key = serde_json::to_value(by)?;
+    out.write_record([
+        key.as_str().unwrap_or("key"),
+        "engrams",
+        "input_tokens",
+        "output_tokens",
+        "cache_read_tokens",
+        "cache_write_tokens",
+        "total_tokens",
+        "cost_usd",
+        "cost_unknown",
+    ])?;
+    for g in groups {
+        let t = &g.totals;
+        out.write_record([
+            g.key.clone(),
+            t.engrams.to_string(),
+            t.input_tokens.to_string(),
+            t.output_tokens.to_string(),
+            t.cache_read_tokens.to_string(),
+            t.cache_write_tokens.to_string(),
+            t.total_tokens.to_string(),
+            format!("{:.4}", t.cost_usd),
+            t.cost_unknown.to_string(),
+        ])?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn write_agent_csv(stats: &[AgentStats]) -> Result<()> {
+    let mut out = csv::Writer::from_writer(std::io::stdout());
+    out.write_record([
+        "agent",
+        "model",
+        "engrams",
+        "input_tokens",
+        "output_tokens",
+        "total_tokens",
+        "cost_usd",
+        "avg_tokens",
+        "files_touched",
+        "last_active",
+    ])?;
+    for s in stats {
+        out.write_record([
+            s.agent.clone(),
+            s.model.clone().unwrap_or_default(),
+            s.engrams.to_string(),
+            s.input_tokens.to_string(),
+            s.output_tokens.to_string(),
+            s.total_tokens.to_string(),
+            format!("{:.4}", s.cost_usd),
+            format!("{:.1}", s.avg_tokens),
+            s.files_touched.to_string(),
+            s.last_active.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        ])?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn print_churn(
+    storage: &GitStorage,
+    opts: &ListOptions,
+    top: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut churn = file_churn(storage, opts).context("Failed to compute file churn")?;
+    let total = churn.len();
+    churn.truncate(top);
+
+    if matches!(format, OutputFormat::Json | OutputFormat::Ndjson) {
+        let report = serde_json::json!({
+            "total_files": total,
+            "files": churn,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if churn.is_empty() {
+        println!("No file changes recorded.");
+        return Ok(());
+    }
+
+    println!("File Churn (top {} of {total} files)", churn.len());
+    println!("==========");
+    for file in &churn {
+        let c = &file.changes;
+        println!(
+            "  {}: {} engrams ({} created, {} modified, {} deleted, {} renamed)",
+            file.path, file.touches, c.created, c.modified, c.deleted, c.renamed
+        );
+        if file.lines_added.is_some() || file.lines_removed.is_some() {
+            println!(
+                "    lines:    +{} -{}",
+                file.lines_added.unwrap_or(0),
+                file.lines_removed.unwrap_or(0)
+            );
+        }
+        println!("    agents:   {}", file.agents.join(", "));
+        println!(
+            "    touched:  {} to {}",
+            file.first_touched.format("%Y-%m-%d"),
+            file.last_touched.format("%Y-%m-%d")
+        );
+        if !file.previous_paths.is_empty() {
+            println!("    formerly: {}", file.previous_paths.join(", "));
         }
     }
 
@@ -0,0 +1,71 @@
+use anyhow::{Context, Result};
+use clap::Args;
+
+use engram_core::model::EngramId;
+use engram_core::storage::GitStorage;
+use engram_protocol::{sync_status, SyncOptions, SyncStatus};
+
+use crate::output::OutputFormat;
+
+#[derive(Args)]
+pub struct StatusArgs {
+    /// Remote name (default: origin)
+    #[arg(default_value = "origin")]
+    pub remote: String,
+
+    /// Number of IDs to list per category
+    #[arg(long, default_value = "5")]
+    pub limit: usize,
+}
+
+pub fn run(args: &StatusArgs, format: OutputFormat) -> Result<()> {
+    let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
+    let status = sync_status(storage.repo(), &args.remote, &SyncOptions::default())?;
+
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            fn ids(ids: &[EngramId]) -> Vec<&str> {
+                ids.iter().map(EngramId::as_str).collect()
+            }
+            let json = serde_json::json!({
+                "remote": status.remote,
+                "local_only": ids(&status.local_only),
+                "remote_only": ids(&status.remote_only),
+                "diverged": ids(&status.diverged),
+                "in_sync": status.in_sync,
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            print_text(&status, args.limit)
+        }
+    }
+    Ok(())
+}
+
+fn print_text(status: &SyncStatus, limit: usize) {
+    if status.is_in_sync() {
+        println!(
+            "Up to date with {}: {} engram(s) on both sides",
+            status.remote, status.in_sync
+        );
+        return;
+    }
+
+    println!("Engrams compared with {}:", status.remote);
+    for (label, ids) in [
+        ("Only local (push to send)", &status.local_only),
+        ("Only on remote (fetch to get)", &status.remote_only),
+        ("Diverged", &status.diverged),
+    ] {
+        println!("  {label}: {}", ids.len());
+        for id in ids.iter().take(limit) {
+            let id = id.as_str();
+            println!("    {}", id.get(..8).unwrap_or(id));
+        }
+        if ids.len() > limit {
+            println!("    ... and {} more", ids.len() - limit);
+        }
+    }
+    println!("  In sync: {}", status.in_sync);
+}
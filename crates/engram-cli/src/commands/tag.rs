@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use engram_core::model::Manifest;
+use engram_core::storage::{GitStorage, InitPolicy, ListOptions};
+use engram_query::SearchEngine;
+
+use crate::output::OutputFormat;
+
+#[derive(Args)]
+pub struct TagArgs {
+    /// Engram IDs (full, prefix, alias or HEAD)
+    #[arg(
+        value_name = "ID",
+        required_unless_present = "filter",
+        conflicts_with = "filter"
+    )]
+    pub ids: Vec<String>,
+
+    /// Add this tag (repeatable)
+    #[arg(long, value_name = "TAG")]
+    pub add: Vec<String>,
+
+    /// Remove this tag (repeatable)
+    #[arg(long, value_name = "TAG")]
+    pub remove: Vec<String>,
+
+    /// Select engrams instead of naming them: agent=<name>
+    #[arg(long, value_name = "KEY=VALUE")]
+    pub filter: Option<String>,
+}
+
+pub fn run(args: &TagArgs, format: OutputFormat) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::Error)?;
+
+    for tag in args.add.iter().chain(&args.remove) {
+        if tag.trim().is_empty() || tag.trim() != tag {
+            anyhow::bail!("Invalid tag {tag:?}: tags must be non-empty without surrounding spaces");
+        }
+    }
+
+    let ids = match &args.filter {
+        Some(filter) => select(&storage, filter)?,
+        None => args
+            .ids
+            .iter()
+            .map(|id| {
+                storage
+                    .resolve(id)
+                    .with_context(|| format!("Failed to resolve engram '{id}'"))
+            })
+            .collect::<Result<_>>()?,
+    };
+
+    let manifests = if args.add.is_empty() && args.remove.is_empty() {
+        ids.iter()
+            .map(|id| {
+                storage
+                    .read_manifest(id)
+                    .with_context(|| format!("Failed to read engram '{id}'"))
+            })
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        retag(&storage, &ids, args)?
+    };
+    print_tags(&manifests, format);
+    Ok(())
+}
+
+/// IDs of the engrams matching a `--filter` expression.
+fn select(storage: &GitStorage, filter: &str) -> Result<Vec<String>> {
+    let agent = match filter.split_once('=') {
+        Some(("agent", agent)) if !agent.is_empty() => agent,
+        _ => anyhow::bail!("Invalid filter '{filter}': expected agent=<name>"),
+    };
+    let manifests = storage
+        .list(&ListOptions::default())
+        .context("Failed to list engrams")?;
+    Ok(manifests
+        .into_iter()
+        .filter(|m| m.agent.name == agent)
+        .map(|m| m.id.as_str().to_string())
+        .collect())
+}
+
+/// Apply `--remove` then `--add` to each engram and update its search
+/// document. Returns the updated manifests.
+fn retag(storage: &GitStorage, ids: &[String], args: &TagArgs) -> Result<Vec<Manifest>> {
+    let mut manifests = Vec::new();
+    for id in ids {
+        let manifest = storage
+            .update_manifest(id, |m| {
+                m.tags.retain(|tag| !args.remove.contains(tag));
+                for tag in &args.add {
+                    if !m.tags.contains(tag) {
+                        m.tags.push(tag.clone());
+                    }
+                }
+            })
+            .with_context(|| format!("Failed to update engram '{id}'"))?;
+        reindex(storage, id);
+        manifests.push(manifest);
+    }
+    Ok(manifests)
+}
+
+/// Best-effort update of a changed engram's search document, shared with
+/// `engram amend`. The next search rebuilds a missing or outdated index.
+pub(crate) fn reindex(storage: &GitStorage, id: &str) {
+    if let (Ok(search), Ok(data)) = (SearchEngine::open(storage), storage.read(id)) {
+        let _ = search.reindex_engram(&data);
+    }
+}
+
+fn print_tags(manifests: &[Manifest], format: OutputFormat) {
+    if let OutputFormat::Json | OutputFormat::Ndjson = format {
+        let tags: Vec<_> = manifests
+            .iter()
+            .map(|m| serde_json::json!({ "id": m.id.as_str(), "tags": m.tags }))
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&tags).unwrap_or_default()
+        );
+        return;
+    }
+    if manifests.is_empty() {
+        println!("No engrams found.");
+    }
+    for m in manifests {
+        let tags = if m.tags.is_empty() {
+            "(no tags)".to_string()
+        } else {
+            m.tags.join(", ")
+        };
+        println!("{}  {tags}", &m.id.as_str()[..8]);
+    }
+}
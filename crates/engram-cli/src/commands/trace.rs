@@ -1,25 +1,118 @@
 use anyhow::{Context, Result};
 use clap::Args;
+use serde::Serialize;
 
-use engram_core::storage::GitStorage;
-use engram_query::{trace_file, SearchEngine};
+use engram_core::model::Manifest;
+use engram_core::storage::{GitStorage, ListOptions};
+use engram_query::index::parse_date_bound;
+use engram_query::trace_file;
 
-use crate::output::OutputFormat;
+use crate::output::format::format_trace_markdown;
+use crate::output::ndjson::NdjsonWriter;
+use crate::output::{porcelain, OutputFormat};
 
 #[derive(Args)]
 pub struct TraceArgs {
-    /// File path to trace reasoning history for
+    /// File path to trace reasoning history for, relative to the current
+    /// directory or absolute
     pub file: String,
+
+    /// Don't include history recorded under the file's earlier names
+    #[arg(long)]
+    pub no_follow: bool,
+
+    /// Print stable tab-separated lines for scripts (see README), whatever
+    /// --format says
+    #[arg(long)]
+    pub porcelain: bool,
+
+    /// If the search index can't be opened, rebuild it first rather than
+    /// scanning engrams
+    #[arg(long)]
+    pub rebuild_index: bool,
+
+    #[command(flatten)]
+    pub filters: TraceFilters,
+}
+
+/// Which engrams in a file's history to show; shared with `engram blame`.
+#[derive(Args)]
+pub struct TraceFilters {
+    /// Only engrams by agents whose name contains this
+    #[arg(long)]
+    pub agent: Option<String>,
+
+    /// Only engrams with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Only engrams created on or after this date, or within this long
+    /// (e.g. "7d", "2w")
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only engrams created on or before this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    pub until: Option<String>,
+}
+
+impl TraceFilters {
+    pub fn list_options(&self) -> Result<ListOptions> {
+        Ok(ListOptions {
+            agent_filter: self.agent.clone(),
+            tag: self.tag.clone(),
+            since: self
+                .since
+                .as_deref()
+                .map(super::costs::parse_since)
+                .transpose()?,
+            until: self
+                .until
+                .as_deref()
+                .map(|s| parse_date_bound(s, true))
+                .transpose()?,
+            ..Default::default()
+        })
+    }
+}
+
+/// A file path argument as engrams store it: relative to the repository
+/// root with `/` separators, resolved against the current directory. Bare
+/// repositories have no working tree, so their paths are taken as given.
+pub(crate) fn repo_path(storage: &GitStorage, path: &str) -> Result<String> {
+    let Some(workdir) = storage.workdir() else {
+        return Ok(path.to_string());
+    };
+    let cwd = std::env::current_dir().context("Failed to read the current directory")?;
+    Ok(engram_core::paths::repo_relative(workdir, &cwd, path)?)
+}
+
+/// One `--format ndjson` trace entry: the manifest's fields plus the change.
+#[derive(Serialize)]
+struct EntryRecord<'a> {
+    #[serde(flatten)]
+    manifest: &'a Manifest,
+    change_type: &'a str,
+    path: &'a str,
 }
 
 pub fn run(args: &TraceArgs, format: OutputFormat) -> Result<()> {
     let storage = GitStorage::discover().context("Not in a Git repository with engram")?;
-    let engine = SearchEngine::open(&storage)?;
+    let engine = super::search::open_engine(&storage, args.rebuild_index)?;
 
-    let entries = trace_file(&storage, &engine, &args.file)?;
+    let file = repo_path(&storage, &args.file)?;
+    let filters = args.filters.list_options()?;
+    let mut entries = trace_file(&storage, &engine, &file, !args.no_follow)?;
+    entries.retain(|entry| filters.matches(&entry.manifest));
 
+    if args.porcelain {
+        for entry in &entries {
+            print!("{}", porcelain::trace_line(entry));
+        }
+        return Ok(());
+    }
     if entries.is_empty() {
-        eprintln!("No engrams found that touched: {}", args.file);
+        eprintln!("No engrams found that touched: {file}");
         return Ok(());
     }
 
@@ -28,19 +121,51 @@ pub fn run(args: &TraceArgs, format: OutputFormat) -> Result<()> {
             let manifests: Vec<_> = entries.iter().map(|e| &e.manifest).collect();
             println!("{}", serde_json::to_string_pretty(&manifests)?);
         }
-        OutputFormat::Text | OutputFormat::Markdown => {
-            eprintln!(
-                "Reasoning trace for: {} ({} engram(s))\n",
-                args.file,
-                entries.len()
-            );
+        OutputFormat::Ndjson => {
+            let mut out = NdjsonWriter::stdout();
+            for entry in &entries {
+                out.write(
+                    "trace_entry",
+                    &EntryRecord {
+                        manifest: &entry.manifest,
+                        change_type: &entry.change_type,
+                        path: &entry.path,
+                    },
+                )?;
+            }
+        }
+        OutputFormat::Markdown => {
+            if !crate::output::quiet() {
+                eprintln!(
+                    "Reasoning trace for: {} ({} engram(s))\n",
+                    file,
+                    entries.len()
+                );
+            }
+            print!("{}", format_trace_markdown(&file, &entries));
+        }
+        OutputFormat::Text | OutputFormat::Csv => {
+            if !crate::output::quiet() {
+                eprintln!(
+                    "Reasoning trace for: {} ({} engram(s))\n",
+                    file,
+                    entries.len()
+                );
+            }
             for entry in &entries {
                 let m = &entry.manifest;
                 let short_id = &m.id.as_str()[..8];
                 let ts = m.created_at.format("%Y-%m-%d %H:%M");
                 let summary = m.summary.as_deref().unwrap_or("(no summary)");
                 let agent = &m.agent.name;
-                println!("{short_id}  {ts}  [{agent}]  {summary}");
+                if entry.path == file {
+                    println!("{short_id}  {ts}  [{agent}]  {summary}");
+                } else {
+                    println!(
+                        "{short_id}  {ts}  [{agent}]  (as {})  {summary}",
+                        entry.path
+                    );
+                }
             }
         }
     }
@@ -0,0 +1,72 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use engram_core::storage::{GitStorage, InitPolicy};
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+
+use crate::tui::{ui, App, Effect};
+
+#[derive(Args)]
+pub struct TuiArgs {
+    /// Start with only this agent's engrams listed (`a` cycles agents)
+    #[arg(long)]
+    pub agent: Option<String>,
+}
+
+pub fn run(args: &TuiArgs) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    storage.ensure_initialized(InitPolicy::WarnAndContinue)?;
+
+    let mut app = App::open(storage, args.agent.clone()).context("Failed to list engrams")?;
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut ratatui::DefaultTerminal, app: &mut App) -> Result<()> {
+    loop {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match app.handle_key(key) {
+            Some(Effect::Quit) => return Ok(()),
+            Some(Effect::Copy(text)) => copy_to_clipboard(&text)?,
+            None => {}
+        }
+    }
+}
+
+/// Ask the terminal to put `text` on the clipboard (OSC 52), which also
+/// works over SSH.
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (u32::from(*b) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,108 @@
+use std::io::IsTerminal;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use engram_core::hooks;
+use engram_core::storage::{GitStorage, ListOptions};
+use engram_query::SearchEngine;
+
+#[derive(Args)]
+pub struct UninitArgs {
+    /// Also delete every engram in this repository
+    #[arg(long)]
+    pub purge_data: bool,
+
+    /// Don't ask before deleting engrams with --purge-data
+    #[arg(long, requires = "purge_data")]
+    pub yes: bool,
+}
+
+pub fn run(args: &UninitArgs) -> Result<()> {
+    let storage = GitStorage::discover().context("Not inside a Git repository")?;
+
+    if !storage.is_initialized() {
+        anyhow::bail!("Engram is not initialized in this repository.");
+    }
+
+    if args.purge_data && !args.yes {
+        let count = storage
+            .list(&ListOptions::default())
+            .context("Failed to list engrams")?
+            .len();
+        if !confirm_purge(count)? {
+            eprintln!("Nothing removed.");
+            return Ok(());
+        }
+    }
+
+    // Resolved before the config that may name it is removed
+    let index_path = SearchEngine::open(&storage)
+        .context("Failed to find the search index")?
+        .index_path()
+        .clone();
+
+    let git_dir = storage.repo().path().to_path_buf();
+    let managed_hooks: Vec<_> = hooks::hook_status(&git_dir)
+        .context("Failed to read git hooks")?
+        .into_iter()
+        .filter(|hook| hook.managed)
+        .collect();
+    hooks::uninstall_hooks(&git_dir).context("Failed to uninstall git hooks")?;
+
+    let report = storage
+        .uninit(args.purge_data)
+        .context("Failed to remove engram from the repository")?;
+
+    // An index outside the Git directory (engram.indexPath, ENGRAM_INDEX_DIR)
+    // may point anywhere, so only a directory holding a Tantivy index goes
+    let index_removed = index_path.join("meta.json").is_file();
+    if index_removed {
+        std::fs::remove_dir_all(&index_path)
+            .with_context(|| format!("Failed to remove {}", index_path.display()))?;
+    }
+
+    println!("Engram removed from this repository.");
+    for key in &report.config_keys {
+        println!("  config   {key}");
+    }
+    for (key, refspec) in &report.refspecs {
+        println!("  refspec  {key} {refspec}");
+    }
+    for hook in &managed_hooks {
+        let restored = if hook.backup {
+            " (restored the previous hook)"
+        } else {
+            ""
+        };
+        println!("  hook     {}{restored}", hook.name);
+    }
+    for path in &report.paths {
+        println!("  file     {}", path.display());
+    }
+    if index_removed {
+        println!("  index    {}", index_path.display());
+    } else if index_path.exists() {
+        println!(
+            "Left {} in place; it does not look like a search index.",
+            index_path.display()
+        );
+    }
+    if args.purge_data {
+        println!("  deleted  {} engram ref(s)", report.refs_deleted);
+    } else {
+        println!("Engrams were kept; `engram init` picks them up again, and `--purge-data` deletes them.");
+    }
+    Ok(())
+}
+
+/// Ask for `purge` to be typed before deleting every engram.
+fn confirm_purge(count: usize) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        eprintln!("Use --yes to confirm deleting every engram.");
+        return Ok(false);
+    }
+    eprint!("This deletes all {count} engram(s) in this repository. Type 'purge' to confirm: ");
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim() == "purge")
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use tracing_subscriber::{fmt, EnvFilter};
 
 mod commands;
 mod output;
+#[cfg(feature = "tui")]
+mod tui;
 
 #[derive(Parser)]
 #[command(
@@ -20,6 +22,16 @@ struct Cli {
     #[arg(long, global = true, default_value = "text")]
     format: output::OutputFormat,
 
+    /// When to color text output; `auto` colors on a terminal unless
+    /// NO_COLOR is set
+    #[arg(long, global = true, value_enum, default_value = "auto")]
+    color: output::theme::ColorChoice,
+
+    /// Print only results: no progress bars, hints or summaries; `record`
+    /// and `import` print just the new engram IDs on stdout
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
     #[command(subcommand)]
     command: commands::Commands,
 }
@@ -40,27 +52,56 @@ fn init_tracing(verbose: u8) {
 fn main() -> Result<()> {
     let cli = Cli::parse();
     init_tracing(cli.verbose);
+    output::theme::init(cli.color);
+    output::set_quiet(cli.quiet);
+
+    if cli.format == output::OutputFormat::Csv && !cli.command.prints_csv() {
+        anyhow::bail!("--format csv is only supported by log, stats --by-agent or --by, and costs");
+    }
 
     match &cli.command {
         commands::Commands::Init(args) => commands::init::run(args),
+        commands::Commands::Uninit(args) => commands::uninit::run(args),
         commands::Commands::Record(args) => commands::record::run(args),
-        commands::Commands::Import(args) => commands::import::run(args),
+        commands::Commands::Import(args) => commands::import::run(args, cli.format),
         commands::Commands::Log(args) => commands::log::run(args, cli.format),
         commands::Commands::Show(args) => commands::show::run(args, cli.format),
+        commands::Commands::Delete(args) => commands::delete::run(args),
+        commands::Commands::Note(args) => commands::note::run(args, cli.format),
+        commands::Commands::Amend(args) => commands::amend::run(args),
+        commands::Commands::Tag(args) => commands::tag::run(args, cli.format),
+        commands::Commands::Alias(args) => commands::alias::run(args, cli.format),
         commands::Commands::Search(args) => commands::search::run(args, cli.format),
+        commands::Commands::Grep(args) => commands::grep::run(args, cli.format),
+        commands::Commands::Related(args) => commands::related::run(args, cli.format),
         commands::Commands::Trace(args) => commands::trace::run(args, cli.format),
         commands::Commands::Diff(args) => commands::diff::run(args, cli.format),
         commands::Commands::Graph(args) => commands::graph::run(args, cli.format),
         commands::Commands::Review(args) => commands::review::run(args, cli.format),
-        commands::Commands::Mcp => commands::mcp::run(),
+        commands::Commands::Mcp(args) => commands::mcp::run(args),
         commands::Commands::PrSummary(args) => commands::pr_summary::run(args, cli.format),
         commands::Commands::Push(args) => commands::push::run(args),
         commands::Commands::Pull(args) => commands::pull::run(args),
         commands::Commands::Fetch(args) => commands::fetch::run(args),
-        commands::Commands::Stats => commands::stats::run(cli.format),
-        commands::Commands::Gc(args) => commands::gc::run(args),
+        commands::Commands::Status(args) => commands::status::run(args, cli.format),
+        commands::Commands::Bundle(args) => commands::bundle::run(args),
+        commands::Commands::Stats(args) => commands::stats::run(args, cli.format),
+        commands::Commands::Costs(args) => commands::costs::run(args, cli.format),
+        commands::Commands::Gc(args) => commands::gc::run(args, cli.format),
+        commands::Commands::Dedupe(args) => commands::dedupe::run(args, cli.format),
         commands::Commands::Blame(args) => commands::blame::run(args, cli.format),
-        commands::Commands::Reindex => commands::reindex::run(),
+        commands::Commands::Reindex(args) => commands::reindex::run(args),
+        commands::Commands::Config(args) => commands::config::run(args, cli.format),
+        commands::Commands::Hooks(args) => commands::hooks::run(args, cli.format),
+        #[cfg(feature = "serve")]
+        commands::Commands::Serve(args) => commands::serve::run(args),
+        #[cfg(feature = "otel")]
+        commands::Commands::ExportOtel(args) => commands::export_otel::run(args),
+        #[cfg(feature = "tui")]
+        commands::Commands::Tui(args) => commands::tui::run(args),
+        commands::Commands::Completions(args) => commands::completions::run(args, Cli::command()),
+        commands::Commands::CompleteIds(args) => commands::completions::complete_ids(args),
+        commands::Commands::CompleteRemotes(args) => commands::completions::complete_remotes(args),
         commands::Commands::Version => commands::version::run(),
         commands::Commands::HookHandler(args) => commands::hook_handler::run(args),
     }
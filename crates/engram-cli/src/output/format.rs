@@ -1,35 +1,61 @@
-use engram_core::model::{EngramData, Manifest};
+use chrono::Utc;
+use engram_core::display::{format_duration, relative_time, DisplayTimezone};
+use engram_core::model::{EngramData, FileChangeType, Manifest};
+use engram_query::{SearchResult, TraceEntry};
 
-use super::OutputFormat;
+use super::{ndjson, theme, OutputFormat};
 
-pub fn format_manifest_list(manifests: &[Manifest], show_cost: bool, fmt: OutputFormat) -> String {
+/// How text listings show when each engram was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dates {
+    /// `3h ago`, `2d ago`
+    Relative,
+    /// `2026-01-15 10:00`, in the given timezone
+    Absolute(DisplayTimezone),
+}
+
+pub fn format_manifest_list(
+    manifests: &[Manifest],
+    show_cost: bool,
+    dates: Dates,
+    fmt: OutputFormat,
+) -> String {
     match fmt {
         OutputFormat::Json => serde_json::to_string_pretty(manifests).unwrap_or_default(),
-        OutputFormat::Text | OutputFormat::Markdown => {
-            format_manifest_list_text(manifests, show_cost)
+        OutputFormat::Ndjson => manifests
+            .iter()
+            .filter_map(|m| ndjson::line("engram", m).ok())
+            .collect(),
+        OutputFormat::Text | OutputFormat::Csv => {
+            format_manifest_list_text(manifests, show_cost, dates)
         }
+        OutputFormat::Markdown => format_manifest_list_markdown(manifests),
     }
 }
 
-fn format_manifest_list_text(manifests: &[Manifest], show_cost: bool) -> String {
+fn format_manifest_list_text(manifests: &[Manifest], show_cost: bool, dates: Dates) -> String {
     if manifests.is_empty() {
         return "No engrams found.".to_string();
     }
 
+    let now = Utc::now();
     let mut out = String::new();
     for m in manifests {
-        let short_id = &m.id.as_str()[..8.min(m.id.as_str().len())];
+        let short_id = theme::ID.paint(&m.id.as_str()[..8.min(m.id.as_str().len())]);
         let summary = m.summary.as_deref().unwrap_or("(no summary)");
-        let agent = &m.agent.name;
-        let model = m.agent.model.as_deref().unwrap_or("");
-        let time = m.created_at.format("%Y-%m-%d %H:%M");
+        let agent = theme::AGENT.paint(&m.agent.name);
+        let model = theme::AGENT.paint(m.agent.model.as_deref().unwrap_or(""));
+        let time = theme::DATE.paint(match dates {
+            Dates::Relative => relative_time(m.created_at, now),
+            Dates::Absolute(timezone) => timezone.format(m.created_at, "%Y-%m-%d %H:%M"),
+        });
 
         if show_cost {
             let tokens = m.token_usage.total_tokens;
             let cost = m
                 .token_usage
                 .cost_usd
-                .map(|c| format!("${c:.2}"))
+                .map(|c| theme::COST.paint(format!("${c:.2}")))
                 .unwrap_or_else(|| "-".to_string());
             out.push_str(&format!(
                 "\u{25c6} {short_id} {summary} [{agent}/{model}] {cost} {tokens}tok  {time}\n"
@@ -43,31 +69,96 @@ fn format_manifest_list_text(manifests: &[Manifest], show_cost: bool) -> String
     out
 }
 
-pub fn format_engram_full(data: &EngramData, fmt: OutputFormat) -> String {
+/// A table of manifests, always with tokens and cost.
+fn format_manifest_list_markdown(manifests: &[Manifest]) -> String {
+    if manifests.is_empty() {
+        return "No engrams found.".to_string();
+    }
+
+    let mut out = String::from(
+        "| ID | Agent/Model | Date | Tokens | Cost | Summary |\n\
+         |----|-------------|------|-------:|-----:|---------|\n",
+    );
+    for m in manifests {
+        let cost = m
+            .token_usage
+            .cost_usd
+            .map(|c| format!("${c:.2}"))
+            .unwrap_or_else(|| "-".to_string());
+        out.push_str(&format!(
+            "| `{}` | {} | {} | {} | {cost} | {} |\n",
+            short_id(m),
+            table_cell(&agent_model(m)),
+            m.created_at.format("%Y-%m-%d %H:%M"),
+            m.token_usage.total_tokens,
+            table_cell(m.summary.as_deref().unwrap_or("(no summary)")),
+        ));
+    }
+    out
+}
+
+/// Version of the `engram show --format json` document. Changes only when a
+/// field is removed or changes meaning.
+pub const SHOW_SCHEMA_VERSION: u32 = 1;
+
+/// The `engram show` JSON document: `schema_version` and the named top-level
+/// fields of [`EngramData`], or all of them when `fields` is empty.
+pub fn format_engram_json(data: &EngramData, fields: &[&str]) -> String {
+    let mut doc = match serde_json::to_value(data) {
+        Ok(serde_json::Value::Object(doc)) => doc,
+        _ => return String::new(),
+    };
+    if !fields.is_empty() {
+        doc.retain(|key, _| fields.contains(&key.as_str()));
+    }
+    doc.insert("schema_version".into(), SHOW_SCHEMA_VERSION.into());
+    serde_json::to_string_pretty(&doc).unwrap_or_default()
+}
+
+pub fn format_engram_full(
+    data: &EngramData,
+    timezone: DisplayTimezone,
+    fmt: OutputFormat,
+) -> String {
     match fmt {
-        OutputFormat::Json => serde_json::to_string_pretty(&data.manifest).unwrap_or_default(),
-        OutputFormat::Text | OutputFormat::Markdown => format_engram_full_text(data),
+        OutputFormat::Json | OutputFormat::Ndjson => format_engram_json(data, &[]),
+        OutputFormat::Text | OutputFormat::Csv => format_engram_full_text(data, timezone),
+        OutputFormat::Markdown => format_engram_full_markdown(data),
     }
 }
 
-fn format_engram_full_text(data: &EngramData) -> String {
+fn format_engram_full_text(data: &EngramData, timezone: DisplayTimezone) -> String {
     let m = &data.manifest;
     let mut out = String::new();
 
-    out.push_str(&format!("Engram: {}\n", m.id));
+    out.push_str(&format!("Engram: {}\n", theme::ID.paint(&m.id)));
     out.push_str(&format!(
         "Agent:  {}{}\n",
-        m.agent.name,
+        theme::AGENT.paint(&m.agent.name),
         m.agent
             .model
             .as_ref()
-            .map(|m| format!(" ({m})"))
+            .map(|m| format!(" ({})", theme::AGENT.paint(m)))
             .unwrap_or_default()
     ));
     out.push_str(&format!(
         "Date:   {}\n",
-        m.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+        theme::DATE.paint(timezone.format(m.created_at, "%Y-%m-%d %H:%M:%S %Z"))
     ));
+    if let Some(finished_at) = m.finished_at {
+        out.push_str(&format!(
+            "Duration: {}\n",
+            format_duration(finished_at - m.created_at)
+        ));
+    }
+    if let Some(branch) = &data.lineage.branch {
+        let dirty = if data.lineage.workdir_dirty_at_start {
+            " (uncommitted changes at start)"
+        } else {
+            ""
+        };
+        out.push_str(&format!("Branch: {branch}{dirty}\n"));
+    }
     if let Some(summary) = &m.summary {
         out.push_str(&format!("Summary: {summary}\n"));
     }
@@ -80,7 +171,10 @@ fn format_engram_full_text(data: &EngramData) -> String {
             tu.total_tokens, tu.input_tokens, tu.output_tokens
         ));
         if let Some(cost) = tu.cost_usd {
-            out.push_str(&format!("  Cost: ${cost:.4}"));
+            out.push_str(&format!(
+                "  Cost: {}",
+                theme::COST.paint(format!("${cost:.4}"))
+            ));
         }
         out.push('\n');
     }
@@ -94,49 +188,227 @@ fn format_engram_full_text(data: &EngramData) -> String {
     }
 
     // Intent
-    out.push_str("\n--- Intent ---\n");
+    out.push_str(&format!("\n{}\n", theme::HEADING.paint("--- Intent ---")));
     out.push_str(&data.intent.to_markdown());
 
     // Operations summary
     if !data.operations.file_changes.is_empty() {
-        out.push_str("\n--- File Changes ---\n");
+        out.push_str(&format!(
+            "\n{}\n",
+            theme::HEADING.paint("--- File Changes ---")
+        ));
         for fc in &data.operations.file_changes {
+            let style = theme::change(&fc.change_type);
             let symbol = match &fc.change_type {
                 engram_core::model::FileChangeType::Created => "+",
                 engram_core::model::FileChangeType::Modified => "~",
                 engram_core::model::FileChangeType::Deleted => "-",
                 engram_core::model::FileChangeType::Renamed { from } => {
-                    out.push_str(&format!("  {from} -> {}\n", fc.path));
+                    let arrow = style.paint("->");
+                    out.push_str(&format!("  {from} {arrow} {}\n", fc.path));
                     continue;
                 }
             };
-            out.push_str(&format!("  {symbol} {}\n", fc.path));
+            out.push_str(&format!("  {} {}\n", style.paint(symbol), fc.path));
         }
     }
 
     if !data.operations.tool_calls.is_empty() {
-        out.push_str(&format!(
-            "\n--- Tool Calls ({}) ---\n",
-            data.operations.tool_calls.len()
-        ));
+        let heading = format!("--- Tool Calls ({}) ---", data.operations.tool_calls.len());
+        out.push_str(&format!("\n{}\n", theme::HEADING.paint(heading)));
         for tc in &data.operations.tool_calls {
-            let err_marker = if tc.is_error { " [ERROR]" } else { "" };
-            out.push_str(&format!("  {}{err_marker}\n", tc.tool_name));
+            let duration = match tc.duration_ms {
+                Some(ms) if ms < 1000 => format!(" {}", theme::DATE.paint(format!("{ms}ms"))),
+                Some(ms) => {
+                    let took = format_duration(chrono::Duration::milliseconds(ms as i64));
+                    format!(" {}", theme::DATE.paint(took))
+                }
+                None => String::new(),
+            };
+            let err_marker = if tc.is_error {
+                format!(" {}", theme::ERROR.paint("[ERROR]"))
+            } else {
+                String::new()
+            };
+            out.push_str(&format!("  {}{duration}{err_marker}\n", tc.tool_name));
+            // What went wrong, from the start of the output
+            let reason = tc
+                .output_summary
+                .as_deref()
+                .and_then(|s| s.lines().find(|l| !l.trim().is_empty()))
+                .filter(|_| tc.is_error);
+            if let Some(line) = reason {
+                out.push_str(&format!("    {}\n", line.trim()));
+            }
         }
     }
 
     // Transcript summary
+    let heading = format!(
+        "--- Transcript ({} entries) ---",
+        data.transcript.entries.len()
+    );
+    out.push_str(&format!("\n{}\n", theme::HEADING.paint(heading)));
+
+    out
+}
+
+fn format_engram_full_markdown(data: &EngramData) -> String {
+    let m = &data.manifest;
+    let intent = &data.intent;
+    let mut out = format!(
+        "# {}\n\n",
+        m.summary.as_deref().unwrap_or(&intent.original_request)
+    );
+
+    out.push_str(&format!("- **Engram:** `{}`\n", m.id));
+    out.push_str(&format!("- **Agent:** {}\n", agent_model(m)));
     out.push_str(&format!(
-        "\n--- Transcript ({} entries) ---\n",
+        "- **Date:** {}\n",
+        m.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+    ));
+    if let Some(branch) = &data.lineage.branch {
+        out.push_str(&format!("- **Branch:** `{branch}`\n"));
+    }
+    if !m.git_commits.is_empty() {
+        let commits: Vec<_> = m.git_commits.iter().map(|c| format!("`{c}`")).collect();
+        out.push_str(&format!("- **Commits:** {}\n", commits.join(", ")));
+    }
+    if !m.tags.is_empty() {
+        out.push_str(&format!("- **Tags:** {}\n", m.tags.join(", ")));
+    }
+
+    out.push_str(&format!("\n## Intent\n\n{}\n", intent.original_request));
+    if let Some(goal) = &intent.interpreted_goal {
+        out.push_str(&format!("\n**Goal:** {goal}\n"));
+    }
+    if let Some(summary) = &intent.summary {
+        out.push_str(&format!("\n**Summary:** {summary}\n"));
+    }
+
+    if !data.operations.file_changes.is_empty() {
+        out.push_str("\n## File Changes\n\n");
+        for fc in &data.operations.file_changes {
+            let line = match &fc.change_type {
+                FileChangeType::Created => format!("`{}` (created)", fc.path),
+                FileChangeType::Modified => format!("`{}` (modified)", fc.path),
+                FileChangeType::Deleted => format!("`{}` (deleted)", fc.path),
+                FileChangeType::Renamed { from } => {
+                    format!("`{from}` → `{}` (renamed)", fc.path)
+                }
+            };
+            out.push_str(&format!("- {line}\n"));
+        }
+    }
+
+    if !intent.dead_ends.is_empty() {
+        out.push_str("\n## Dead Ends\n\n");
+        for de in &intent.dead_ends {
+            out.push_str(&format!("- **{}**: {}\n", de.approach, de.reason));
+        }
+    }
+
+    if !intent.decisions.is_empty() {
+        out.push_str("\n## Decisions\n\n");
+        for d in &intent.decisions {
+            out.push_str(&format!("- **{}**: {}\n", d.description, d.rationale));
+        }
+    }
+
+    let tu = &m.token_usage;
+    out.push_str("\n## Economics\n\n");
+    out.push_str(&format!(
+        "- **Tokens:** {} total ({} in, {} out)\n",
+        tu.total_tokens, tu.input_tokens, tu.output_tokens
+    ));
+    if let Some(cost) = tu.cost_usd {
+        out.push_str(&format!("- **Cost:** ${cost:.4}\n"));
+    }
+    out.push_str(&format!(
+        "- **Tool calls:** {}\n",
+        data.operations.tool_calls.len()
+    ));
+    out.push_str(&format!(
+        "- **Transcript:** {} entries\n",
         data.transcript.entries.len()
     ));
 
     out
 }
 
+/// Search results as a bulleted list, with matched terms in bold.
+pub fn format_search_results_markdown(results: &[SearchResult]) -> String {
+    let mut out = String::new();
+    for result in results {
+        let m = &result.manifest;
+        out.push_str(&format!(
+            "- `{}` **{}** — {}, {}\n",
+            short_id(m),
+            m.summary.as_deref().unwrap_or("(no summary)"),
+            agent_model(m),
+            m.created_at.format("%Y-%m-%d")
+        ));
+        if let Some(repo) = &result.repo {
+            out.push_str(&format!("  - Repository: `{}`\n", repo.display()));
+        }
+        if !result.highlights.is_empty() {
+            if let Some(snippet) = result.marked_snippet("**", "**") {
+                let snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+                out.push_str(&format!("  > {snippet}\n"));
+                if result.truncated_transcript {
+                    out.push_str("  > _(matched in truncated transcript)_\n");
+                }
+            }
+        }
+    }
+    out
+}
+
+/// A file's history as a bulleted list, oldest first.
+pub fn format_trace_markdown(file: &str, entries: &[TraceEntry]) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        let m = &entry.manifest;
+        let renamed = if entry.path == file {
+            String::new()
+        } else {
+            format!(" (as `{}`)", entry.path)
+        };
+        out.push_str(&format!(
+            "- `{}` {} — {}, {}{renamed}: {}\n",
+            short_id(m),
+            m.created_at.format("%Y-%m-%d %H:%M"),
+            m.agent.name,
+            entry.change_type,
+            m.summary.as_deref().unwrap_or("(no summary)")
+        ));
+    }
+    out
+}
+
+fn short_id(m: &Manifest) -> &str {
+    &m.id.as_str()[..8.min(m.id.as_str().len())]
+}
+
+fn agent_model(m: &Manifest) -> String {
+    match &m.agent.model {
+        Some(model) => format!("{}/{model}", m.agent.name),
+        None => m.agent.name.clone(),
+    }
+}
+
+/// Text that can't break out of a Markdown table cell.
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
 pub fn format_intent(data: &EngramData, fmt: OutputFormat) -> String {
     match fmt {
-        OutputFormat::Json => serde_json::to_string_pretty(&data.intent).unwrap_or_default(),
-        OutputFormat::Text | OutputFormat::Markdown => data.intent.to_markdown(),
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            serde_json::to_string_pretty(&data.intent).unwrap_or_default()
+        }
+        OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv => {
+            data.intent.to_markdown()
+        }
     }
 }
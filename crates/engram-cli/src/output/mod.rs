@@ -1,8 +1,33 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 pub mod format;
+pub mod ndjson;
+pub mod porcelain;
+pub mod progress;
+pub mod theme;
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Record `--quiet`, once, in `main`.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether `--quiet` was given: commands leave out progress bars, hints and
+/// summaries, and `record` and `import` print only the new engram IDs.
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
 
-#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum OutputFormat {
     Text,
     Json,
     Markdown,
+    /// One compact JSON object per line, tagged with a `kind`; streamed by
+    /// `log`, `search`, `trace` and `blame`, the same as `json` elsewhere
+    Ndjson,
+    /// Comma-separated values with a header row; for `log`,
+    /// `stats --by-agent` and `costs`
+    Csv,
 }
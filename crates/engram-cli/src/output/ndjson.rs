@@ -0,0 +1,57 @@
+//! Newline-delimited JSON: one compact object per line, each with a `kind`
+//! naming its record type so streams that mix types stay parseable.
+
+use std::io::{self, StdoutLock, Write};
+
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Record<'a, T> {
+    kind: &'a str,
+    #[serde(flatten)]
+    record: &'a T,
+}
+
+/// `record` as one NDJSON line, newline included. `record` must serialize
+/// to an object.
+pub fn line<T: Serialize>(kind: &str, record: &T) -> serde_json::Result<String> {
+    let mut line = serde_json::to_string(&Record { kind, record })?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Writes records to stdout as they are produced, flushing each line so a
+/// reader such as `jq` sees it at once.
+pub struct NdjsonWriter {
+    out: StdoutLock<'static>,
+    /// Set once the reader has gone away (`engram log | head`); later
+    /// records are dropped instead of failing the command.
+    closed: bool,
+}
+
+impl NdjsonWriter {
+    pub fn stdout() -> Self {
+        Self {
+            out: io::stdout().lock(),
+            closed: false,
+        }
+    }
+
+    pub fn write<T: Serialize>(&mut self, kind: &str, record: &T) -> io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        let line = line(kind, record)?;
+        match self
+            .out
+            .write_all(line.as_bytes())
+            .and_then(|()| self.out.flush())
+        {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                self.closed = true;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
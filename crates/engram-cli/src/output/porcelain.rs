@@ -0,0 +1,125 @@
+//! `--porcelain` output for `log`, `show` and `trace`: tab-separated lines
+//! whose layout is part of engram's interface and does not change between
+//! versions, for scripts to parse.
+//!
+//! - `log`: one engram per line, newest first:
+//!   `id  created_at  agent  model  total_tokens  cost_usd  summary`
+//! - `trace`: one engram per line, oldest first:
+//!   `id  created_at  change  path  agent  summary`, where `change` is
+//!   `created`, `modified`, `deleted` or `renamed` and `path` is the file's
+//!   name in that engram
+//! - `show`: one `key  value...` line per fact; `tag`, `commit`, `file`
+//!   (`change  path`, then the old path for `renamed`), `dead_end`
+//!   (`approach  reason`) and `decision` (`description  rationale`) repeat,
+//!   and keys without a value are left out
+//!
+//! IDs are full, times are RFC 3339 in UTC, unknown values are empty, and
+//! tabs and line breaks inside values become spaces. New fields are only
+//! ever appended to a line, and new `show` keys only added, so parsers
+//! should ignore what they don't know.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use engram_core::model::{EngramData, FileChangeType, Manifest};
+use engram_query::TraceEntry;
+
+/// `value` with tabs and line breaks replaced by spaces.
+fn field(value: &str) -> String {
+    value.replace(['\t', '\r', '\n'], " ")
+}
+
+fn time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn line(fields: &[&str]) -> String {
+    let fields: Vec<_> = fields.iter().map(|f| field(f)).collect();
+    format!("{}\n", fields.join("\t"))
+}
+
+/// One `engram log --porcelain` line.
+pub fn log_line(m: &Manifest) -> String {
+    line(&[
+        m.id.as_str(),
+        &time(m.created_at),
+        &m.agent.name,
+        m.agent.model.as_deref().unwrap_or_default(),
+        &m.token_usage.total_tokens.to_string(),
+        &m.token_usage
+            .cost_usd
+            .map(|c| c.to_string())
+            .unwrap_or_default(),
+        m.summary.as_deref().unwrap_or_default(),
+    ])
+}
+
+/// One `engram trace --porcelain` line.
+pub fn trace_line(entry: &TraceEntry) -> String {
+    let m = &entry.manifest;
+    // "renamed from <path>" keeps only its kind; the old name has its own entry
+    let change = entry
+        .change_type
+        .split_whitespace()
+        .next()
+        .unwrap_or("modified");
+    line(&[
+        m.id.as_str(),
+        &time(m.created_at),
+        change,
+        &entry.path,
+        &m.agent.name,
+        m.summary.as_deref().unwrap_or_default(),
+    ])
+}
+
+/// The `engram show --porcelain` lines for an engram.
+pub fn show_lines(data: &EngramData) -> String {
+    let m = &data.manifest;
+    let intent = &data.intent;
+    let tokens = &m.token_usage;
+    let mut out = String::new();
+    let mut push = |fields: &[&str]| out.push_str(&line(fields));
+
+    push(&["id", m.id.as_str()]);
+    push(&["created_at", &time(m.created_at)]);
+    if let Some(finished) = m.finished_at {
+        push(&["finished_at", &time(finished)]);
+    }
+    push(&["agent", &m.agent.name]);
+    if let Some(model) = &m.agent.model {
+        push(&["model", model]);
+    }
+    push(&["input_tokens", &tokens.input_tokens.to_string()]);
+    push(&["output_tokens", &tokens.output_tokens.to_string()]);
+    push(&["total_tokens", &tokens.total_tokens.to_string()]);
+    if let Some(cost) = tokens.cost_usd {
+        push(&["cost_usd", &cost.to_string()]);
+    }
+    if let Some(summary) = &m.summary {
+        push(&["summary", summary]);
+    }
+    push(&["request", &intent.original_request]);
+    if let Some(goal) = &intent.interpreted_goal {
+        push(&["goal", goal]);
+    }
+    for tag in &m.tags {
+        push(&["tag", tag]);
+    }
+    for commit in &m.git_commits {
+        push(&["commit", commit]);
+    }
+    for change in &data.operations.file_changes {
+        match &change.change_type {
+            FileChangeType::Created => push(&["file", "created", &change.path]),
+            FileChangeType::Modified => push(&["file", "modified", &change.path]),
+            FileChangeType::Deleted => push(&["file", "deleted", &change.path]),
+            FileChangeType::Renamed { from } => push(&["file", "renamed", &change.path, from]),
+        }
+    }
+    for dead_end in &intent.dead_ends {
+        push(&["dead_end", &dead_end.approach, &dead_end.reason]);
+    }
+    for decision in &intent.decisions {
+        push(&["decision", &decision.description, &decision.rationale]);
+    }
+    out
+}
@@ -0,0 +1,72 @@
+use std::io::{IsTerminal, Write};
+
+use engram_protocol::{ProgressCallback, SyncProgress};
+
+const BAR_WIDTH: usize = 24;
+
+/// A one-line progress bar for push and fetch, drawn on stderr only when it
+/// is a terminal.
+pub struct SyncBar {
+    label: &'static str,
+    enabled: bool,
+}
+
+impl SyncBar {
+    pub fn new(label: &'static str, quiet: bool) -> Self {
+        Self {
+            label,
+            enabled: !quiet && std::io::stderr().is_terminal(),
+        }
+    }
+
+    /// The callback for [`engram_protocol::SyncOptions::progress`].
+    pub fn callback(&self) -> Option<Box<ProgressCallback>> {
+        let label = self.label;
+        self.enabled.then(|| {
+            Box::new(move |p: SyncProgress| {
+                let mut stderr = std::io::stderr().lock();
+                let _ = write!(stderr, "\r\x1b[2K{}", render(label, &p));
+                let _ = stderr.flush();
+            }) as Box<ProgressCallback>
+        })
+    }
+
+    /// Clear the bar so that a summary can take its line.
+    pub fn finish(&self) {
+        if self.enabled {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+fn render(label: &str, p: &SyncProgress) -> String {
+    let filled = (p.objects * BAR_WIDTH)
+        .checked_div(p.total_objects)
+        .unwrap_or(0)
+        .min(BAR_WIDTH);
+    format!(
+        "{label} [{}{}] {}/{} objects, {}, {} ref(s)",
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled),
+        p.objects,
+        p.total_objects,
+        human_bytes(p.bytes as u64),
+        p.refs_updated
+    )
+}
+
+/// `bytes` in B, KiB, MiB or GiB, with one decimal above bytes.
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
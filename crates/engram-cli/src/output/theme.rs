@@ -0,0 +1,90 @@
+//! Colors for text output. Whether to color is decided once, in `main`, from
+//! `--color`, `NO_COLOR` and whether stdout is a terminal; when it is off
+//! every style renders its text unchanged, so piped output stays plain.
+
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use engram_core::model::FileChangeType;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// When to color text output (`--color`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and `NO_COLOR` is unset or empty
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Decide once whether styles emit color.
+pub fn init(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            !no_color && std::io::stdout().is_terminal()
+        }
+    };
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether styles emit color.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// An SGR escape sequence applied to a piece of text.
+#[derive(Debug, Clone, Copy)]
+pub struct Style(&'static str);
+
+impl Style {
+    pub fn paint(self, text: impl Display) -> String {
+        match self.markers() {
+            Some((open, close)) => format!("{open}{text}{close}"),
+            None => text.to_string(),
+        }
+    }
+
+    /// The sequences that start and end the style, when coloring.
+    pub fn markers(self) -> Option<(String, &'static str)> {
+        enabled().then(|| (format!("\x1b[{}m", self.0), "\x1b[0m"))
+    }
+}
+
+pub const ID: Style = Style("33");
+pub const AGENT: Style = Style("36");
+pub const DATE: Style = Style("2");
+pub const COST: Style = Style("32");
+pub const HEADING: Style = Style("1");
+pub const MATCH: Style = Style("1;4");
+pub const ADDED: Style = Style("32");
+pub const MODIFIED: Style = Style("33");
+pub const REMOVED: Style = Style("31");
+pub const RENAMED: Style = Style("35");
+pub const ERROR: Style = Style("1;31");
+
+/// The style for a kind of file change.
+pub fn change(change_type: &FileChangeType) -> Style {
+    match change_type {
+        FileChangeType::Created => ADDED,
+        FileChangeType::Modified => MODIFIED,
+        FileChangeType::Deleted => REMOVED,
+        FileChangeType::Renamed { .. } => RENAMED,
+    }
+}
+
+/// The style for a change described as trace entries describe it
+/// (`created`, `modified`, `deleted`, `renamed from ...`).
+pub fn change_named(change_type: &str) -> Style {
+    match change_type.split_whitespace().next() {
+        Some("created") => ADDED,
+        Some("deleted") => REMOVED,
+        Some("renamed") => RENAMED,
+        _ => MODIFIED,
+    }
+}
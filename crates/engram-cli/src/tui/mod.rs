@@ -0,0 +1,403 @@
+//! State and key handling for `engram tui`. Everything here only reads from
+//! storage and the search index; drawing is in [`ui`].
+
+pub(crate) mod ui;
+
+use engram_core::model::{Intent, Manifest, Operations, Transcript, TranscriptEntry};
+use engram_core::storage::{EngramPart, GitStorage, ListOptions};
+use engram_query::{diff_engrams, EngramDiff, SearchEngine, SearchOptions};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Transcript entries shown per page of the Transcript tab.
+pub(crate) const TRANSCRIPT_PAGE: usize = 50;
+
+/// Most search results listed for a query.
+const MAX_SEARCH_RESULTS: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Tab {
+    Intent,
+    Files,
+    DeadEnds,
+    Transcript,
+}
+
+impl Tab {
+    pub(crate) const ALL: [Tab; 4] = [Tab::Intent, Tab::Files, Tab::DeadEnds, Tab::Transcript];
+
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            Tab::Intent => "Intent",
+            Tab::Files => "Files",
+            Tab::DeadEnds => "Dead Ends",
+            Tab::Transcript => "Transcript",
+        }
+    }
+
+    fn index(self) -> usize {
+        Tab::ALL.iter().position(|t| *t == self).unwrap_or(0)
+    }
+}
+
+/// The parts of the selected engram the detail pane shows. The transcript
+/// is read one page at a time.
+pub(crate) struct Detail {
+    pub(crate) manifest: Manifest,
+    pub(crate) intent: Intent,
+    pub(crate) operations: Operations,
+    pub(crate) transcript: Vec<TranscriptEntry>,
+    pub(crate) transcript_total: usize,
+    pub(crate) transcript_page: usize,
+}
+
+/// Something the event loop does for the app outside the terminal.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Effect {
+    Quit,
+    /// Put this text on the clipboard.
+    Copy(String),
+}
+
+pub(crate) struct App {
+    storage: GitStorage,
+    search: Option<SearchEngine>,
+    /// Every engram, newest first.
+    manifests: Vec<Manifest>,
+    /// Positions in `manifests` of the listed engrams.
+    pub(crate) visible: Vec<usize>,
+    /// Position in `visible` of the selected engram.
+    pub(crate) selected: usize,
+    /// Engrams picked for a diff, at most two.
+    pub(crate) marked: Vec<String>,
+    pub(crate) agent: Option<String>,
+    pub(crate) query: String,
+    /// Whether keys go to the search box.
+    pub(crate) searching: bool,
+    /// The search index was missing, so the query filters summaries.
+    pub(crate) fallback: bool,
+    pub(crate) tab: Tab,
+    pub(crate) scroll: u16,
+    pub(crate) detail: Option<Detail>,
+    /// Shown instead of the detail pane until closed with Esc.
+    pub(crate) diff: Option<EngramDiff>,
+    pub(crate) status: String,
+}
+
+impl App {
+    pub(crate) fn open(storage: GitStorage, agent: Option<String>) -> anyhow::Result<Self> {
+        let manifests = storage.list(&ListOptions::default())?;
+        let search = SearchEngine::open(&storage).ok();
+        let mut app = Self {
+            storage,
+            search,
+            manifests,
+            visible: Vec::new(),
+            selected: 0,
+            marked: Vec::new(),
+            agent,
+            query: String::new(),
+            searching: false,
+            fallback: false,
+            tab: Tab::Intent,
+            scroll: 0,
+            detail: None,
+            diff: None,
+            status: String::new(),
+        };
+        app.refresh();
+        Ok(app)
+    }
+
+    pub(crate) fn manifest(&self, row: usize) -> &Manifest {
+        &self.manifests[self.visible[row]]
+    }
+
+    fn selected_id(&self) -> Option<String> {
+        (!self.visible.is_empty()).then(|| self.manifest(self.selected).id.as_str().to_string())
+    }
+
+    /// Agents in the order they first appear, newest engram first.
+    fn agents(&self) -> Vec<&str> {
+        let mut agents: Vec<&str> = Vec::new();
+        for m in &self.manifests {
+            if !agents.contains(&m.agent.name.as_str()) {
+                agents.push(&m.agent.name);
+            }
+        }
+        agents
+    }
+
+    /// Recompute the listed engrams from the query and agent filter, keeping
+    /// the selection on the same engram when it is still listed.
+    fn refresh(&mut self) {
+        let keep = self.selected_id();
+        let found = if self.query.trim().is_empty() {
+            None
+        } else {
+            self.search_index()
+        };
+        self.fallback = found.is_none() && !self.query.trim().is_empty();
+        let query = self.query.to_lowercase();
+        let agent = self.agent.as_deref();
+        let listed = |m: &Manifest| {
+            agent.map_or(true, |a| m.agent.name == a)
+                && (query.trim().is_empty()
+                    || found.is_some()
+                    || m.id.as_str().starts_with(&query)
+                    || m.summary
+                        .as_deref()
+                        .is_some_and(|s| s.to_lowercase().contains(&query)))
+        };
+        self.visible = match &found {
+            Some(ids) => ids
+                .iter()
+                .filter_map(|id| self.manifests.iter().position(|m| m.id.as_str() == id))
+                .filter(|&i| listed(&self.manifests[i]))
+                .collect(),
+            None => (0..self.manifests.len())
+                .filter(|&i| listed(&self.manifests[i]))
+                .collect(),
+        };
+        self.selected = keep
+            .and_then(|id| {
+                self.visible
+                    .iter()
+                    .position(|&i| self.manifests[i].id.as_str() == id)
+            })
+            .unwrap_or(0);
+        self.load_detail();
+    }
+
+    /// IDs matching the query in the search index, best first, or `None`
+    /// if there is no usable index.
+    fn search_index(&mut self) -> Option<Vec<String>> {
+        let options = SearchOptions {
+            limit: MAX_SEARCH_RESULTS,
+            ..Default::default()
+        };
+        match self
+            .search
+            .as_ref()?
+            .search_if_indexed(&self.query, &options)
+        {
+            Ok(Some(page)) => Some(
+                page.results
+                    .into_iter()
+                    .map(|r| r.manifest.id.as_str().to_string())
+                    .collect(),
+            ),
+            Ok(None) => None,
+            Err(e) => {
+                self.status = format!("Search failed: {e}");
+                Some(Vec::new())
+            }
+        }
+    }
+
+    /// Read the selected engram for the detail pane, without its transcript
+    /// beyond the first page.
+    fn load_detail(&mut self) {
+        self.scroll = 0;
+        let Some(id) = self.selected_id() else {
+            self.detail = None;
+            return;
+        };
+        if self
+            .detail
+            .as_ref()
+            .is_some_and(|d| d.manifest.id.as_str() == id)
+        {
+            return;
+        }
+        let manifest = self.manifest(self.selected).clone();
+        let intent = self.storage.read_intent(&id);
+        let operations = self
+            .storage
+            .read_part(&id, EngramPart::Operations)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        match intent {
+            Ok(intent) => {
+                self.detail = Some(Detail {
+                    manifest,
+                    intent,
+                    operations,
+                    transcript: Vec::new(),
+                    transcript_total: 0,
+                    transcript_page: 0,
+                });
+                self.load_transcript_page(0);
+            }
+            Err(e) => {
+                self.detail = None;
+                self.status = format!("Failed to read {}: {e}", &id[..8.min(id.len())]);
+            }
+        }
+    }
+
+    /// Parse just one page of the selected engram's transcript.
+    fn load_transcript_page(&mut self, page: usize) {
+        let Some(detail) = self.detail.as_mut() else {
+            return;
+        };
+        let bytes = match self
+            .storage
+            .read_part(detail.manifest.id.as_str(), EngramPart::Transcript)
+        {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                self.status = format!("Failed to read transcript: {e}");
+                return;
+            }
+        };
+        let text = String::from_utf8_lossy(&bytes);
+        let total = text.lines().filter(|line| !line.trim().is_empty()).count();
+        let last = total.saturating_sub(1) / TRANSCRIPT_PAGE;
+        let page = page.min(last);
+        detail.transcript = Transcript::entries_from_jsonl(&text)
+            .skip(page * TRANSCRIPT_PAGE)
+            .take(TRANSCRIPT_PAGE)
+            .filter_map(Result::ok)
+            .collect();
+        detail.transcript_total = total;
+        detail.transcript_page = page;
+    }
+
+    fn select(&mut self, row: usize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        self.selected = row.min(self.visible.len() - 1);
+        self.load_detail();
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> Option<Effect> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Effect::Quit);
+        }
+        if self.searching {
+            self.search_key(key);
+            return None;
+        }
+        self.status.clear();
+        match key.code {
+            KeyCode::Char('q') => return Some(Effect::Quit),
+            KeyCode::Esc if self.diff.is_some() => self.diff = None,
+            KeyCode::Esc if !self.query.is_empty() => {
+                self.query.clear();
+                self.refresh();
+            }
+            KeyCode::Esc => return Some(Effect::Quit),
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::PageDown => self.select(self.selected + 10),
+            KeyCode::PageUp => self.select(self.selected.saturating_sub(10)),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(usize::MAX),
+            KeyCode::Tab => self.set_tab((self.tab.index() + 1) % Tab::ALL.len()),
+            KeyCode::BackTab => {
+                self.set_tab((self.tab.index() + Tab::ALL.len() - 1) % Tab::ALL.len())
+            }
+            KeyCode::Char(c @ '1'..='4') => self.set_tab(c as usize - '1' as usize),
+            KeyCode::Char('J') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Char('K') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Char(']') => self.turn_page(1),
+            KeyCode::Char('[') => self.turn_page(-1),
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Char('a') => self.next_agent(),
+            KeyCode::Char(' ') => self.toggle_mark(),
+            KeyCode::Char('d') => self.open_diff(),
+            KeyCode::Char('y') => {
+                let id = self.selected_id()?;
+                self.status = format!("Copied {id}");
+                return Some(Effect::Copy(id));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.searching = false,
+            KeyCode::Esc => {
+                self.searching = false;
+                self.query.clear();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+            }
+            KeyCode::Char(c) => self.query.push(c),
+            _ => return,
+        }
+        self.refresh();
+    }
+
+    fn set_tab(&mut self, index: usize) {
+        self.tab = Tab::ALL[index];
+        self.scroll = 0;
+    }
+
+    fn turn_page(&mut self, by: isize) {
+        if self.tab != Tab::Transcript {
+            return;
+        }
+        if let Some(detail) = &self.detail {
+            let page = detail.transcript_page.saturating_add_signed(by);
+            self.scroll = 0;
+            self.load_transcript_page(page);
+        }
+    }
+
+    /// Filter by the next agent in the list, then by none.
+    fn next_agent(&mut self) {
+        let agents = self.agents();
+        let next = match &self.agent {
+            None => agents.first(),
+            Some(current) => agents
+                .iter()
+                .position(|a| a == current)
+                .and_then(|i| agents.get(i + 1)),
+        }
+        .map(|a| a.to_string());
+        self.agent = next;
+        self.status = match &self.agent {
+            Some(agent) => format!("Agent: {agent}"),
+            None => "All agents".into(),
+        };
+        self.refresh();
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(id) = self.selected_id() else {
+            return;
+        };
+        if let Some(i) = self.marked.iter().position(|m| *m == id) {
+            self.marked.remove(i);
+        } else {
+            if self.marked.len() == 2 {
+                self.marked.remove(0);
+            }
+            self.marked.push(id);
+        }
+    }
+
+    fn open_diff(&mut self) {
+        let [a, b] = self.marked.as_slice() else {
+            self.status = "Mark two engrams with space to diff them".into();
+            return;
+        };
+        let find = |id: &str| self.manifests.iter().find(|m| m.id.as_str() == id);
+        let (Some(a), Some(b)) = (find(a), find(b)) else {
+            return;
+        };
+        match diff_engrams(&self.storage, &a.id, &b.id) {
+            Ok(diff) => {
+                self.diff = Some(diff);
+                self.scroll = 0;
+            }
+            Err(e) => self.status = format!("Diff failed: {e}"),
+        }
+    }
+}
@@ -0,0 +1,478 @@
+//! Drawing for `engram tui`: the engram list on the left, the selected
+//! engram (or a diff of two) on the right, and a status line.
+
+use engram_core::model::{FileChangeType, Role, TranscriptContent};
+use engram_query::EngramDiff;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Tabs, Wrap};
+use ratatui::Frame;
+
+use super::{App, Detail, Tab, TRANSCRIPT_PAGE};
+
+const HELP: &str = "j/k move  / search  a agent  space mark  d diff  y copy ID  \
+                    tab/1-4 tabs  [/] page  J/K scroll  q quit";
+
+pub(crate) fn draw(frame: &mut Frame, app: &App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+    let [list, detail] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(main);
+
+    draw_list(frame, app, list);
+    match &app.diff {
+        Some(diff) => draw_diff(frame, diff, app.scroll, detail),
+        None => draw_detail(frame, app, detail),
+    }
+
+    let status_text = if app.status.is_empty() {
+        HELP.to_string()
+    } else {
+        app.status.clone()
+    };
+    frame.render_widget(Paragraph::new(status_text).dim(), status);
+}
+
+fn draw_list(frame: &mut Frame, app: &App, area: Rect) {
+    let [search, list] = Layout::vertical([Constraint::Length(3), Constraint::Min(1)]).areas(area);
+
+    let mut title = String::from("Search");
+    if app.fallback {
+        title.push_str(" (no index: matching summaries)");
+    }
+    let cursor = if app.searching { "_" } else { "" };
+    let search_box = Paragraph::new(format!("{}{cursor}", app.query)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(if app.searching {
+                Style::default().bold()
+            } else {
+                Style::default()
+            }),
+    );
+    frame.render_widget(search_box, search);
+
+    let items: Vec<ListItem> = (0..app.visible.len())
+        .map(|row| {
+            let m = app.manifest(row);
+            let mark = if app.marked.iter().any(|id| id == m.id.as_str()) {
+                "*"
+            } else {
+                " "
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{mark}{} ", &m.id.as_str()[..8])),
+                Span::raw(format!("{} ", m.created_at.format("%Y-%m-%d"))),
+                Span::raw(m.summary.as_deref().unwrap_or("(no summary)").to_string()),
+            ]))
+        })
+        .collect();
+    let mut title = format!("Engrams ({})", app.visible.len());
+    if let Some(agent) = &app.agent {
+        title.push_str(&format!(" [{agent}]"));
+    }
+    let list_widget = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default();
+    if !app.visible.is_empty() {
+        state.select(Some(app.selected));
+    }
+    frame.render_stateful_widget(list_widget, list, &mut state);
+}
+
+fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(detail) = &app.detail else {
+        let empty =
+            Paragraph::new("No engrams found.").block(Block::default().borders(Borders::ALL));
+        frame.render_widget(empty, area);
+        return;
+    };
+    let [header, tabs, body] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Length(1),
+        Constraint::Min(1),
+    ])
+    .areas(area);
+
+    let m = &detail.manifest;
+    let model = m.agent.model.as_deref().unwrap_or("unknown");
+    let cost = m
+        .token_usage
+        .cost_usd
+        .map(|c| format!(", ${c:.2}"))
+        .unwrap_or_default();
+    let header_text = Text::from(vec![
+        Line::from(m.summary.as_deref().unwrap_or("(no summary)").to_string()).bold(),
+        Line::from(format!("{}  {}/{model}", m.id, m.agent.name)),
+        Line::from(format!(
+            "{}  {} tokens{cost}",
+            m.created_at.format("%Y-%m-%d %H:%M"),
+            m.token_usage.total_tokens
+        )),
+    ]);
+    frame.render_widget(
+        Paragraph::new(header_text).block(Block::default().borders(Borders::TOP)),
+        header,
+    );
+
+    let titles: Vec<String> = Tab::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| format!("{} {}", i + 1, tab.title()))
+        .collect();
+    frame.render_widget(
+        Tabs::new(titles)
+            .select(app.tab.index())
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+        tabs,
+    );
+
+    let (title, text) = match app.tab {
+        Tab::Intent => (String::new(), intent_text(detail)),
+        Tab::Files => (String::new(), files_text(detail)),
+        Tab::DeadEnds => (String::new(), dead_ends_text(detail)),
+        Tab::Transcript => transcript_text(detail),
+    };
+    frame.render_widget(
+        Paragraph::new(text)
+            .block(Block::default().borders(Borders::TOP).title(title))
+            .wrap(Wrap { trim: false })
+            .scroll((app.scroll, 0)),
+        body,
+    );
+}
+
+fn intent_text(detail: &Detail) -> Text<'static> {
+    let intent = &detail.intent;
+    let mut lines = vec![Line::from("Request").bold()];
+    lines.extend(
+        intent
+            .original_request
+            .lines()
+            .map(|l| Line::from(l.to_string())),
+    );
+    for (heading, text) in [
+        ("Interpreted goal", &intent.interpreted_goal),
+        ("Summary", &intent.summary),
+    ] {
+        if let Some(text) = text {
+            lines.push(Line::default());
+            lines.push(Line::from(heading).bold());
+            lines.extend(text.lines().map(|l| Line::from(l.to_string())));
+        }
+    }
+    if !intent.decisions.is_empty() {
+        lines.push(Line::default());
+        lines.push(Line::from("Decisions").bold());
+        for d in &intent.decisions {
+            lines.push(Line::from(format!("- {}: {}", d.description, d.rationale)));
+        }
+    }
+    Text::from(lines)
+}
+
+fn files_text(detail: &Detail) -> Text<'static> {
+    let changes = &detail.operations.file_changes;
+    if changes.is_empty() {
+        return Text::from("No file changes.");
+    }
+    let lines = changes.iter().map(|fc| {
+        let kind = match fc.change_type {
+            FileChangeType::Created => "A",
+            FileChangeType::Modified => "M",
+            FileChangeType::Deleted => "D",
+            FileChangeType::Renamed { .. } => "R",
+        };
+        Line::from(format!("{kind} {}", fc.path))
+    });
+    Text::from(lines.collect::<Vec<_>>())
+}
+
+fn dead_ends_text(detail: &Detail) -> Text<'static> {
+    let dead_ends = &detail.intent.dead_ends;
+    if dead_ends.is_empty() {
+        return Text::from("No dead ends recorded.");
+    }
+    let mut lines = Vec::new();
+    for de in dead_ends {
+        lines.push(Line::from(de.approach.clone()).bold());
+        lines.push(Line::from(format!("  {}", de.reason)));
+    }
+    Text::from(lines)
+}
+
+fn transcript_text(detail: &Detail) -> (String, Text<'static>) {
+    if detail.transcript_total == 0 {
+        return (String::new(), Text::from("Empty transcript."));
+    }
+    let first = detail.transcript_page * TRANSCRIPT_PAGE;
+    let title = format!(
+        "entries {}..{} of {}",
+        first,
+        first + detail.transcript.len(),
+        detail.transcript_total
+    );
+    let mut lines = Vec::new();
+    for (i, entry) in detail.transcript.iter().enumerate() {
+        let role = match entry.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+            Role::Tool => "tool",
+        };
+        let (label, body) = match &entry.content {
+            TranscriptContent::Text { text } => ("", text.clone()),
+            TranscriptContent::Thinking { text } => ("(thinking) ", text.clone()),
+            TranscriptContent::ToolUse { tool_name, .. } => ("(tool call) ", tool_name.clone()),
+            TranscriptContent::ToolResult { output, .. } => ("(tool result) ", output.clone()),
+        };
+        let mut body_lines = body.lines();
+        lines.push(Line::from(vec![
+            Span::raw(format!("#{} ", first + i)).dim(),
+            Span::raw(format!("{role}: ")).bold(),
+            Span::raw(format!("{label}{}", body_lines.next().unwrap_or(""))),
+        ]));
+        lines.extend(body_lines.map(|l| Line::from(format!("    {l}"))));
+    }
+    (title, Text::from(lines))
+}
+
+fn draw_diff(frame: &mut Frame, diff: &EngramDiff, scroll: u16, area: Rect) {
+    let (a, b) = (&diff.id_a.as_str()[..8], &diff.id_b.as_str()[..8]);
+    let mut lines = Vec::new();
+    let mut section = |title: String, items: Vec<String>| {
+        if items.is_empty() {
+            return;
+        }
+        lines.push(Line::from(title).bold());
+        lines.extend(
+            items
+                .into_iter()
+                .map(|item| Line::from(format!("  {item}"))),
+        );
+        lines.push(Line::default());
+    };
+    section("Common files".into(), diff.common_files.clone());
+    section(format!("Only in {a}"), diff.only_a_files.clone());
+    section(format!("Only in {b}"), diff.only_b_files.clone());
+    if let Some((goal_a, goal_b)) = &diff.intent_changed {
+        section(
+            "Goal".into(),
+            vec![format!("{a}: {goal_a}"), format!("{b}: {goal_b}")],
+        );
+    }
+    section(
+        format!("Dead ends only in {a}"),
+        diff.dead_ends_only_a
+            .iter()
+            .map(|d| d.approach.clone())
+            .collect(),
+    );
+    section(
+        format!("Dead ends only in {b}"),
+        diff.dead_ends_only_b
+            .iter()
+            .map(|d| d.approach.clone())
+            .collect(),
+    );
+    section(
+        format!("Decisions only in {a}"),
+        diff.decisions_only_a
+            .iter()
+            .map(|d| d.description.clone())
+            .collect(),
+    );
+    section(
+        format!("Decisions only in {b}"),
+        diff.decisions_only_b
+            .iter()
+            .map(|d| d.description.clone())
+            .collect(),
+    );
+    lines.push(Line::from(format!("Token delta: {:+}", diff.token_delta)));
+    lines.push(Line::from(format!(
+        "Transcript delta: {:+} entries",
+        diff.transcript_entry_delta
+    )));
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Diff {a} vs {b} (Esc to close)"));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0)),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use engram_core::model::*;
+    use engram_core::storage::GitStorage;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+    use ratatui::Terminal;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::tui::Effect;
+
+    fn engram(id: char, agent: &str, created_at: &str, summary: &str) -> EngramData {
+        let builder = EngramData::builder()
+            .id(id.to_string().repeat(32))
+            .agent(agent)
+            .model("sonnet")
+            .created_at(created_at.parse().unwrap())
+            .total_tokens(1200)
+            .summary(summary)
+            .request(summary)
+            .dead_end("In-process LRU", "lost on deploy")
+            .file_change("src/cache.rs", FileChangeType::Created);
+        (0..60)
+            .fold(builder, |b, i| {
+                let role = if i % 2 == 0 {
+                    Role::User
+                } else {
+                    Role::Assistant
+                };
+                b.message(role, &format!("message {i}"))
+            })
+            .build()
+    }
+
+    /// An app over three engrams, without a search index.
+    fn app() -> (TempDir, App) {
+        let tmp = TempDir::new().unwrap();
+        git2::Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        for data in [
+            engram('a', "claude-code", "2026-01-15T10:00:00Z", "Add caching"),
+            engram('b', "aider", "2026-01-16T10:00:00Z", "Fix login"),
+            engram(
+                'c',
+                "claude-code",
+                "2026-01-17T10:00:00Z",
+                "Tune cache size",
+            ),
+        ] {
+            storage.create(&data).unwrap();
+        }
+        let app = App::open(storage, None).unwrap();
+        (tmp, app)
+    }
+
+    fn render(app: &App) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(100, 16)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn press(app: &mut App, keys: &str) -> Option<Effect> {
+        let mut effect = None;
+        for c in keys.chars() {
+            effect = app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        effect
+    }
+
+    #[test]
+    fn test_list_and_intent() {
+        let (_tmp, app) = app();
+        let screen = render(&app);
+        assert_eq!(
+            screen[..8],
+            [
+                "┌Search────────────────────────────────┐────────────────────────────────────────────────────────────",
+                "│                                      │Tune cache size",
+                "└──────────────────────────────────────┘cccccccccccccccccccccccccccccccc  claude-code/sonnet",
+                "┌Engrams (3)───────────────────────────┐2026-01-17 10:00  1200 tokens",
+                "│ cccccccc 2026-01-17 Tune cache size  │ 1 Intent │ 2 Files │ 3 Dead Ends │ 4 Transcript",
+                "│ bbbbbbbb 2026-01-16 Fix login        │────────────────────────────────────────────────────────────",
+                "│ aaaaaaaa 2026-01-15 Add caching      │Request",
+                "│                                      │Tune cache size",
+            ]
+        );
+        assert!(screen[15].starts_with("j/k move"));
+    }
+
+    #[test]
+    fn test_tabs_and_transcript_pages() {
+        let (_tmp, mut app) = app();
+        press(&mut app, "j2");
+        let screen = render(&app);
+        assert!(screen[1].ends_with("Fix login"));
+        assert!(screen[6].ends_with("A src/cache.rs"));
+
+        press(&mut app, "3");
+        assert!(render(&app)[6].ends_with("In-process LRU"));
+
+        press(&mut app, "4");
+        let screen = render(&app);
+        assert!(screen[5].contains("entries 0..50 of 60"), "{screen:#?}");
+        assert!(screen[6].ends_with("#0 user: message 0"));
+        press(&mut app, "]");
+        let screen = render(&app);
+        assert!(screen[5].contains("entries 50..60 of 60"));
+        assert!(screen[6].ends_with("#50 user: message 50"));
+        // Past the last page stays on it
+        press(&mut app, "]");
+        assert_eq!(app.detail.as_ref().unwrap().transcript_page, 1);
+    }
+
+    #[test]
+    fn test_search_falls_back_without_index() {
+        let (_tmp, mut app) = app();
+        press(&mut app, "/cache");
+        assert!(app.fallback);
+        let screen = render(&app);
+        assert!(screen[0].contains("Search (no index: matching summaries)"));
+        assert!(screen[1].starts_with("│cache_"));
+        assert!(screen[3].contains("Engrams (1)"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert!(!app.searching);
+        assert_eq!(app.visible.len(), 3);
+    }
+
+    #[test]
+    fn test_agent_filter_diff_and_copy() {
+        let (_tmp, mut app) = app();
+        press(&mut app, "a");
+        assert_eq!(app.agent.as_deref(), Some("claude-code"));
+        assert!(render(&app)[3].contains("Engrams (2) [claude-code]"));
+        press(&mut app, "aa");
+        assert_eq!(app.agent, None);
+
+        assert_eq!(press(&mut app, "d"), None);
+        assert!(app.status.contains("Mark two engrams"));
+        press(&mut app, "g j ");
+        assert_eq!(app.marked.len(), 2);
+        press(&mut app, "d");
+        let screen = render(&app);
+        assert!(
+            screen[0].contains("Diff cccccccc vs bbbbbbbb"),
+            "{screen:#?}"
+        );
+        assert!(screen.iter().any(|line| line.contains("Common files")));
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert!(app.diff.is_none());
+
+        assert_eq!(press(&mut app, "y"), Some(Effect::Copy("b".repeat(32))));
+        assert_eq!(press(&mut app, "q"), Some(Effect::Quit));
+    }
+}
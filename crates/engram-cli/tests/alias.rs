@@ -0,0 +1,135 @@
+mod common;
+
+use std::path::Path;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+use serde_json::Value;
+
+fn ids(dir: &Path) -> Vec<String> {
+    let output = engram(dir)
+        .args(["--format", "json", "log", "--all"])
+        .output()
+        .unwrap();
+    let log: Vec<Value> = serde_json::from_slice(&output.stdout).unwrap();
+    log.iter()
+        .map(|m| m["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+/// Import a session and return the new engram's ID.
+fn import(dir: &Path, prompt: &str) -> String {
+    let before = ids(dir);
+    let file = format!("{}.jsonl", prompt.replace(' ', "-"));
+    std::fs::write(dir.join(&file), claude_session(prompt)).unwrap();
+    engram(dir)
+        .args(["import", "--from", "claude-code", &file])
+        .assert()
+        .success();
+    ids(dir)
+        .into_iter()
+        .find(|id| !before.contains(id))
+        .unwrap()
+}
+
+#[test]
+fn test_alias_set_show_overwrite_remove() {
+    let repo = init_repo();
+    let dir = repo.path();
+    let payments = import(dir, "Refactor payments");
+    let auth = import(dir, "Fix auth");
+
+    engram(dir)
+        .args(["alias", "set", "payments-refactor", &payments[..8]])
+        .assert()
+        .success()
+        .stdout(format!("payments-refactor -> {}\n", &payments[..8]));
+    engram(dir)
+        .args(["show", "payments-refactor"])
+        .assert()
+        .success()
+        .stdout(contains("Refactor payments"));
+    engram(dir)
+        .args(["alias", "list"])
+        .assert()
+        .success()
+        .stdout(contains(format!("payments-refactor  {}", &payments[..8])));
+
+    // Setting it again repoints it
+    engram(dir)
+        .args(["alias", "set", "payments-refactor", &auth])
+        .assert()
+        .success()
+        .stdout(format!(
+            "payments-refactor -> {} (was {})\n",
+            &auth[..8],
+            &payments[..8]
+        ));
+    engram(dir)
+        .args(["show", "payments-refactor"])
+        .assert()
+        .success()
+        .stdout(contains("Fix auth"));
+
+    let output = engram(dir)
+        .args(["--format", "json", "alias", "list"])
+        .output()
+        .unwrap();
+    let listed: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        listed,
+        serde_json::json!([{ "name": "payments-refactor", "id": auth }])
+    );
+
+    engram(dir)
+        .args(["alias", "rm", "payments-refactor"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "Removed alias payments-refactor (was {})\n",
+            &auth[..8]
+        ));
+    engram(dir)
+        .args(["show", "payments-refactor"])
+        .assert()
+        .failure();
+    engram(dir)
+        .args(["alias", "rm", "payments-refactor"])
+        .assert()
+        .failure()
+        .stderr(contains("No alias named"));
+}
+
+#[test]
+fn test_alias_names_and_deleted_engrams() {
+    let repo = init_repo();
+    let dir = repo.path();
+    let id = import(dir, "Refactor payments");
+
+    for name in ["Payments", "cafe", "head", "a/b"] {
+        engram(dir)
+            .args(["alias", "set", name, &id])
+            .assert()
+            .failure()
+            .stderr(contains("Invalid alias name"));
+    }
+    engram(dir)
+        .args(["alias", "set", "missing", "0000"])
+        .assert()
+        .failure();
+
+    engram(dir)
+        .args(["alias", "set", "pay", &id])
+        .assert()
+        .success();
+    engram(dir)
+        .args(["delete", "pay", "--yes"])
+        .assert()
+        .success();
+    engram(dir)
+        .args(["alias", "list"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(contains("No aliases"));
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use std::path::Path;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+fn repo_with_engram() -> TempDir {
+    let dir = init_repo();
+    std::fs::write(
+        dir.path().join("session.jsonl"),
+        claude_session("Add caching"),
+    )
+    .unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+    dir
+}
+
+fn show(dir: &Path) -> serde_json::Value {
+    let output = engram(dir)
+        .args(["--format", "json", "show", "HEAD"])
+        .output()
+        .unwrap();
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_amend_with_flags() {
+    let dir = repo_with_engram();
+
+    engram(dir.path())
+        .args(["amend", "HEAD", "--summary", "Add a response cache"])
+        .args(["--goal", "Cut p99 latency"])
+        .args(["--add-dead-end", "In-process LRU::lost on every deploy"])
+        .args(["--add-decision", "Redis :: shared by all workers"])
+        .assert()
+        .success()
+        .stderr(contains("Amended"));
+
+    let doc = show(dir.path());
+    assert_eq!(doc["manifest"]["summary"], "Add a response cache");
+    assert_eq!(doc["intent"]["original_request"], "Add caching");
+    assert_eq!(doc["intent"]["interpreted_goal"], "Cut p99 latency");
+    assert_eq!(doc["intent"]["dead_ends"][0]["approach"], "In-process LRU");
+    assert_eq!(
+        doc["intent"]["dead_ends"][0]["reason"],
+        "lost on every deploy"
+    );
+    assert_eq!(
+        doc["intent"]["decisions"][0]["rationale"],
+        "shared by all workers"
+    );
+
+    let output = engram(dir.path())
+        .args(["--format", "json", "search", "dead_end:LRU"])
+        .output()
+        .unwrap();
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(results[0]["summary"], "Add a response cache");
+
+    // The amendment sits on top of the original commit
+    let repo = git2::Repository::open(dir.path()).unwrap();
+    let id = doc["manifest"]["id"].as_str().unwrap();
+    let commit = repo
+        .find_reference(&format!("refs/engrams/{}/{id}", &id[..2]))
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    assert_eq!(commit.parent_count(), 1);
+
+    engram(dir.path())
+        .args(["amend", "HEAD", "--add-dead-end", "no separator"])
+        .assert()
+        .failure()
+        .stderr(contains("separated by '::'"));
+    engram(dir.path())
+        .args(["amend", "HEAD"])
+        .assert()
+        .failure()
+        .stderr(contains("Nothing to amend"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_amend_in_editor() {
+    let dir = repo_with_engram();
+    let edited = dir.path().join("edited.md");
+    std::fs::write(
+        &edited,
+        "# Intent\n\nAdd caching to the API\n\n## Summary\n\nCached GET responses\n\n\
+         ## Dead Ends\n\n- **Memcached**: no one runs it here\n",
+    )
+    .unwrap();
+
+    engram(dir.path())
+        .args(["amend", "HEAD", "--edit"])
+        .env("VISUAL", format!("cp {}", edited.display()))
+        .assert()
+        .success();
+    let doc = show(dir.path());
+    assert_eq!(doc["intent"]["original_request"], "Add caching to the API");
+    assert_eq!(doc["intent"]["summary"], "Cached GET responses");
+    assert_eq!(doc["intent"]["dead_ends"][0]["approach"], "Memcached");
+
+    // Editing the Markdown written from the stored intent changes nothing
+    engram(dir.path())
+        .args(["amend", "HEAD", "--edit"])
+        .env("VISUAL", "true")
+        .assert()
+        .success();
+    assert_eq!(show(dir.path())["intent"], doc["intent"]);
+
+    std::fs::write(
+        &edited,
+        "# Intent\n\nAdd caching\n\n## Decisions\n\nUse Redis\n",
+    )
+    .unwrap();
+    engram(dir.path())
+        .args(["amend", "HEAD", "--edit"])
+        .env("VISUAL", format!("cp {}", edited.display()))
+        .assert()
+        .failure()
+        .stderr(contains("Your edit is saved in"))
+        .stderr(contains("line 7"));
+    assert_eq!(show(dir.path())["intent"], doc["intent"]);
+    let backups: Vec<_> = std::fs::read_dir(dir.path().join(".git"))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with("engram-amend-"))
+        .collect();
+    assert_eq!(backups.len(), 1);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn test_bundle_round_trip() {
+    let a = init_repo();
+    for (file, prompt) in [("a.jsonl", "Add a rate limiter"), ("b.jsonl", "Fix login")] {
+        std::fs::write(a.path().join(file), claude_session(prompt)).unwrap();
+        engram(a.path())
+            .args(["import", "--from", "claude-code", file])
+            .assert()
+            .success();
+    }
+    let out = TempDir::new().unwrap();
+    let path = out.path().join("engrams.bundle");
+    let path = path.to_str().unwrap();
+
+    engram(a.path())
+        .args(["bundle", "create", path])
+        .assert()
+        .success()
+        .stderr(contains("Bundled 2 engram(s)"));
+    // Nothing that recent
+    engram(a.path())
+        .args(["bundle", "create", path, "--since", "2099-01-01"])
+        .assert()
+        .failure()
+        .stderr(contains("no engrams to bundle"));
+
+    let b = init_repo();
+    engram(b.path())
+        .args(["bundle", "import", path])
+        .assert()
+        .success()
+        .stderr(contains("(2 new, 0 updated, 0 unchanged)"))
+        .stderr(contains("Indexed 2 engram(s)."));
+    engram(b.path())
+        .args(["search", "limiter"])
+        .assert()
+        .success()
+        .stderr(contains("Found 1 result(s)"));
+    engram(b.path())
+        .args(["bundle", "import", path])
+        .assert()
+        .success()
+        .stderr(contains("(0 new, 0 updated, 2 unchanged)"));
+}
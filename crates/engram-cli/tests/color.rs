@@ -0,0 +1,82 @@
+mod common;
+
+use std::path::Path;
+
+use common::history::{assert_golden, repo_with_history, stdout_with_placeholders};
+
+/// The text commands whose output is colored on a terminal, and the golden
+/// file holding their uncolored output.
+fn text_outputs(dir: &Path, ids: &[String], extra: &[&str]) -> Vec<(&'static str, String)> {
+    let commands: [(&str, Vec<&str>); 5] = [
+        ("log.txt", vec!["log", "--cost", "--absolute-time"]),
+        ("show.txt", vec!["show", &ids[0]]),
+        ("search.txt", vec!["search", "cache", "--sort", "oldest"]),
+        ("blame.txt", vec!["blame", "src/cache.rs"]),
+        ("diff.txt", vec!["diff", &ids[0], &ids[1], "--full"]),
+    ];
+    commands
+        .into_iter()
+        .map(|(name, args)| {
+            let args: Vec<_> = extra.iter().chain(&args).copied().collect();
+            (name, stdout_with_placeholders(dir, ids, &args))
+        })
+        .collect()
+}
+
+#[test]
+fn test_uncolored_text_golden_files() {
+    let (dir, ids) = repo_with_history();
+    for (name, output) in text_outputs(dir.path(), &ids, &[]) {
+        assert_golden(name, &output);
+    }
+}
+
+/// `text` without SGR escape sequences.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("\x1b[") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find('m').unwrap();
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[test]
+fn test_color_never_matches_golden_files() {
+    let (dir, ids) = repo_with_history();
+    for (name, output) in text_outputs(dir.path(), &ids, &["--color", "never"]) {
+        assert_golden(name, &output);
+    }
+
+    // NO_COLOR only affects `auto`; asking for color explicitly still gets it
+    let output = common::engram(dir.path())
+        .env("NO_COLOR", "1")
+        .args(["--color", "always", "log"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(output.stdout).unwrap().contains('\x1b'));
+}
+
+#[test]
+fn test_color_always_only_adds_escapes() {
+    let (dir, ids) = repo_with_history();
+    for (name, output) in text_outputs(dir.path(), &ids, &["--color", "always"]) {
+        assert!(output.contains("\x1b[33m<"), "{name}: {output:?}");
+        if name == "search.txt" {
+            // Matches are styled instead of wrapped in markdown markers
+            assert!(!output.contains("**"), "{output:?}");
+            continue;
+        }
+        assert_golden(name, &strip_ansi(&output));
+    }
+
+    // JSON is never colored
+    let output = common::engram(dir.path())
+        .args(["--color", "always", "--format", "json", "log"])
+        .output()
+        .unwrap();
+    assert!(!String::from_utf8(output.stdout).unwrap().contains('\x1b'));
+}
@@ -0,0 +1,115 @@
+//! A small history with fixed dates, shared by the golden-file tests.
+
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use super::{engram, init_repo};
+
+/// A Claude Code session at `time` on 2026-01-15 that uses `tool` on
+/// src/cache.rs.
+fn session(prompt: &str, time: &str, tool: &str) -> String {
+    format!(
+        r#"{{"type":"user","uuid":"u1","timestamp":"2026-01-15T{time}:00Z","message":{{"role":"user","content":"{prompt}"}}}}
+{{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2026-01-15T{time}:05Z","message":{{"role":"assistant","content":[{{"type":"text","text":"Updating the cache."}},{{"type":"tool_use","id":"t1","name":"{tool}","input":{{"file_path":"src/cache.rs","content":"// cache"}}}}],"model":"claude-sonnet-4-5","usage":{{"input_tokens":1000,"output_tokens":200}}}}}}
+"#
+    )
+}
+
+/// Add a dead end and a decision to engram `id` by amending its intent.md.
+fn add_insights(dir: &Path, id: &str) {
+    let repo = git2::Repository::open(dir).unwrap();
+    let refname = format!("refs/engrams/{}/{id}", &id[..2]);
+    let commit = repo
+        .find_reference(&refname)
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    let tree = commit.tree().unwrap();
+    let entry = tree.get_name("intent.md").unwrap();
+    let blob = repo.find_blob(entry.id()).unwrap();
+    let intent = format!(
+        "{}\n## Dead Ends\n\n- **In-process LRU**: lost on every deploy\n\
+         \n## Decisions\n\n- **Redis with a 5 minute TTL**: shared by all workers\n",
+        String::from_utf8_lossy(blob.content())
+    );
+    let blob = repo.blob(intent.as_bytes()).unwrap();
+    let mut builder = repo.treebuilder(Some(&tree)).unwrap();
+    builder.insert("intent.md", blob, 0o100644).unwrap();
+    let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+    let sig = commit.author();
+    repo.commit(
+        Some(&refname),
+        &sig,
+        &sig,
+        "engram: add insights",
+        &tree,
+        &[&commit],
+    )
+    .unwrap();
+}
+
+/// Two engrams that changed src/cache.rs, the older with a dead end and a
+/// decision, and their IDs oldest first. The source of the golden files.
+pub fn repo_with_history() -> (TempDir, Vec<String>) {
+    let dir = init_repo();
+    for (i, (prompt, time, tool)) in [
+        ("Add a response cache", "10:00", "Write"),
+        ("Tune cache eviction", "14:30", "Edit"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let file = format!("session{i}.jsonl");
+        std::fs::write(dir.path().join(&file), session(prompt, time, tool)).unwrap();
+        engram(dir.path())
+            .args(["import", "--from", "claude-code", &file])
+            .assert()
+            .success();
+    }
+    let output = engram(dir.path())
+        .args(["--format", "json", "log"])
+        .output()
+        .unwrap();
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut ids: Vec<String> = log
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["id"].as_str().unwrap().to_string())
+        .collect();
+    ids.reverse();
+    add_insights(dir.path(), &ids[0]);
+    (dir, ids)
+}
+
+/// Run `engram <args>`, with engram IDs replaced by placeholders.
+pub fn stdout_with_placeholders(dir: &Path, ids: &[String], args: &[&str]) -> String {
+    let output = engram(dir).args(args).output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let mut out = String::from_utf8(output.stdout).unwrap();
+    for (id, name) in ids.iter().zip(["A", "B"]) {
+        out = out
+            .replace(id.as_str(), &format!("<ID-{name}>"))
+            .replace(&id[..8], &format!("<{name}>"));
+    }
+    out
+}
+
+/// Compare `actual` with tests/golden/`name`, or rewrite the file when
+/// UPDATE_GOLDEN is set.
+pub fn assert_golden(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name]
+        .iter()
+        .collect();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
+    assert_eq!(
+        actual, expected,
+        "{name} changed; rerun with UPDATE_GOLDEN=1 to accept"
+    );
+}
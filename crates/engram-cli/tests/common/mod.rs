@@ -0,0 +1,38 @@
+#![allow(dead_code)]
+
+pub mod history;
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+/// Create a temp Git repository with engram initialized.
+pub fn init_repo() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    let repo = git2::Repository::init(tmp.path()).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Test User").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+
+    engram(tmp.path()).arg("init").assert().success();
+    tmp
+}
+
+/// Build an `engram` command running inside the given directory.
+pub fn engram(dir: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("engram").unwrap();
+    cmd.current_dir(dir)
+        .env("HOME", dir)
+        .env_remove("ENGRAM_LOG");
+    cmd
+}
+
+/// A minimal valid Claude Code session with the given first prompt.
+pub fn claude_session(prompt: &str) -> String {
+    format!(
+        r#"{{"type":"user","uuid":"u1","timestamp":"2026-01-15T10:00:00Z","message":{{"role":"user","content":"{prompt}"}}}}
+{{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2026-01-15T10:00:05Z","message":{{"role":"assistant","content":[{{"type":"text","text":"Done."}}],"model":"claude-sonnet-4-5","usage":{{"input_tokens":100,"output_tokens":20}}}}}}
+"#
+    )
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+#[test]
+fn test_completion_scripts_generate() {
+    let dir = init_repo();
+    for shell in ["bash", "zsh", "fish", "powershell", "elvish"] {
+        let output = engram(dir.path())
+            .args(["completions", shell])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{shell}: {output:?}");
+        let script = String::from_utf8(output.stdout).unwrap();
+        assert!(script.contains("amend"), "{shell}");
+    }
+
+    let zsh = engram(dir.path())
+        .args(["completions", "zsh"])
+        .output()
+        .unwrap();
+    let zsh = String::from_utf8(zsh.stdout).unwrap();
+    assert!(zsh.contains("':id -- Engram ID (full, prefix or alias):_engram_ids'"));
+    assert!(zsh.contains("::remote -- Remote name (default\\: origin):_engram_remotes'"));
+    // The helpers are defined before the script first runs _engram
+    let defined = zsh.find("_engram_ids() {").unwrap();
+    assert!(defined < zsh.find("if [ \"$funcstack[1]\" = \"_engram\" ]").unwrap());
+
+    engram(dir.path())
+        .args(["completions", "fish"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "-n \"__fish_engram_using_subcommand show\" -f -a \"(engram __complete-ids (commandline -ct))\"",
+        ))
+        .stdout(contains(
+            "-n \"__fish_engram_using_subcommand note; and __fish_seen_subcommand_from add\"",
+        ));
+}
+
+#[test]
+fn test_complete_ids_matches_prefix_resolution() {
+    let dir = init_repo();
+    for prompt in ["Add caching", "Fix login", "Tune pool"] {
+        let file = format!("{}.jsonl", prompt.replace(' ', "-"));
+        std::fs::write(dir.path().join(&file), claude_session(prompt)).unwrap();
+        engram(dir.path())
+            .args(["import", "--from", "claude-code", &file])
+            .assert()
+            .success();
+    }
+    let complete = |prefix: &str| -> Vec<(String, String)> {
+        let output = engram(dir.path())
+            .args(["__complete-ids", prefix])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let (id, summary) = line.split_once('\t').unwrap();
+                (id.to_string(), summary.to_string())
+            })
+            .collect()
+    };
+
+    let all = complete("");
+    assert_eq!(all.len(), 3);
+    let mut summaries: Vec<_> = all.iter().map(|(_, s)| s.as_str()).collect();
+    summaries.sort();
+    assert_eq!(summaries, ["Add caching", "Fix login", "Tune pool"]);
+
+    // Each completion resolves to the engram it describes
+    for (id, summary) in &all {
+        assert!(id.len() >= 8);
+        engram(dir.path())
+            .args(["show", id])
+            .assert()
+            .success()
+            .stdout(contains(summary.as_str()));
+    }
+
+    // A prefix completes to exactly the IDs it would resolve among
+    for (id, _) in &all {
+        for len in 1..=3 {
+            let prefix = &id[..len];
+            let matches = complete(prefix);
+            let expected = all.iter().filter(|(i, _)| i.starts_with(prefix)).count();
+            assert_eq!(matches.len(), expected, "{prefix}");
+            assert!(matches.iter().all(|(i, _)| i.starts_with(prefix)));
+            let resolved = engram(dir.path())
+                .args(["show", prefix])
+                .output()
+                .unwrap()
+                .status
+                .success();
+            assert_eq!(resolved, expected == 1, "{prefix}");
+        }
+    }
+    assert!(complete("zz").is_empty());
+    assert_eq!(complete("he")[0].0, "HEAD");
+
+    let outside = tempfile::TempDir::new().unwrap();
+    engram(outside.path())
+        .args(["__complete-ids", ""])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn test_complete_remotes() {
+    let dir = init_repo();
+    let repo = git2::Repository::open(dir.path()).unwrap();
+    repo.remote("origin", "https://example.com/a.git").unwrap();
+    repo.remote("upstream", "https://example.com/b.git")
+        .unwrap();
+
+    engram(dir.path())
+        .args(["__complete-remotes", "up"])
+        .assert()
+        .success()
+        .stdout("upstream\n");
+    engram(dir.path())
+        .args(["__complete-remotes"])
+        .assert()
+        .success()
+        .stdout("origin\nupstream\n");
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{engram, init_repo};
+use predicates::prelude::*;
+
+fn get(dir: &std::path::Path, key: &str) -> String {
+    let output = engram(dir)
+        .args(["config", "get", key])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_set_get_roundtrip() {
+    let tmp = init_repo();
+
+    engram(tmp.path())
+        .args(["config", "set", "autoCapture", "yes"])
+        .assert()
+        .success();
+    assert_eq!(get(tmp.path(), "engram.autoCapture"), "true\n");
+
+    engram(tmp.path())
+        .args(["config", "set", "capture.exclude", "fixtures/**"])
+        .assert()
+        .success();
+    engram(tmp.path())
+        .args(["config", "set", "--add", "capture.exclude", "*.pb.go"])
+        .assert()
+        .success();
+    assert_eq!(get(tmp.path(), "capture.exclude"), "fixtures/**\n*.pb.go\n");
+
+    engram(tmp.path())
+        .args(["config", "set", "capture.maxFileSize", "2m"])
+        .assert()
+        .success();
+    engram(tmp.path())
+        .args(["config", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("engram.enabled = true"))
+        .stdout(predicate::str::contains("engram.capture.exclude = *.pb.go"))
+        .stdout(predicate::str::contains("engram.capture.maxFileSize = 2m"))
+        .stdout(predicate::str::contains("defaultAgent").not());
+
+    let output = engram(tmp.path())
+        .args(["--format", "json", "config", "list", "--all"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let agent = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["key"] == "engram.defaultAgent")
+        .unwrap();
+    assert_eq!(agent["kind"], "text");
+    assert_eq!(agent["values"], serde_json::json!([]));
+
+    engram(tmp.path())
+        .args(["config", "unset", "autoCapture"])
+        .assert()
+        .success();
+    engram(tmp.path())
+        .args(["config", "get", "autoCapture"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("engram.autoCapture is not set"));
+}
+
+#[test]
+fn test_set_rejects_invalid_values() {
+    let tmp = init_repo();
+
+    engram(tmp.path())
+        .args(["config", "set", "enabled", "maybe"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected true or false"));
+    engram(tmp.path())
+        .args(["config", "set", "capture.maxFileSize", "2mb"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("a size such as 512k"));
+    engram(tmp.path())
+        .args(["config", "set", "mcp.maxResponseChars", "lots"])
+        .assert()
+        .failure();
+    engram(tmp.path())
+        .args(["config", "set", "--add", "defaultAgent", "aider"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("takes a single value"));
+    engram(tmp.path())
+        .args(["config", "set", "autoPush", "true"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown key 'autoPush'"))
+        .stderr(predicate::str::contains("engram.pushOnPush"));
+
+    assert_eq!(get(tmp.path(), "enabled"), "true\n");
+}
+
+#[test]
+fn test_global_sets_defaults_for_every_repo() {
+    let tmp = init_repo();
+
+    engram(tmp.path())
+        .args(["config", "set", "--global", "defaultAgent", "aider"])
+        .assert()
+        .success();
+    let global = std::fs::read_to_string(tmp.path().join(".gitconfig")).unwrap();
+    assert!(global.contains("defaultAgent = aider"));
+    let local = std::fs::read_to_string(tmp.path().join(".git/config")).unwrap();
+    assert!(!local.contains("defaultAgent"));
+
+    // The repository inherits the default until it sets its own
+    assert_eq!(get(tmp.path(), "defaultAgent"), "aider\n");
+    engram(tmp.path())
+        .args(["config", "set", "defaultAgent", "claude-code"])
+        .assert()
+        .success();
+    assert_eq!(get(tmp.path(), "defaultAgent"), "claude-code\n");
+    engram(tmp.path())
+        .args(["config", "get", "--global", "defaultAgent"])
+        .assert()
+        .success()
+        .stdout("aider\n");
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+#[test]
+fn test_costs_by_week_json_and_csv() {
+    let repo = init_repo();
+    std::fs::write(
+        repo.path().join("session.jsonl"),
+        claude_session("Add a rate limiter"),
+    )
+    .unwrap();
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+
+    // The session is dated Thursday 2026-01-15, in the week starting Monday the 12th
+    let output = engram(repo.path())
+        .args(["--format", "json", "costs", "--by", "week"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["start"], "2026-01-12T00:00:00Z");
+    assert_eq!(json[0]["engrams"], 1);
+    assert_eq!(json[0]["input_tokens"], 100);
+    assert_eq!(json[0]["agents"][0]["agent"], "claude-code");
+
+    engram(repo.path())
+        .args(["costs", "--by", "month", "--csv"])
+        .assert()
+        .success()
+        .stdout(contains("start,agent,engrams,"))
+        .stdout(contains("2026-01-01,,1,100,20,"))
+        .stdout(contains("2026-01-01,claude-code,1,100,20,"));
+}
+
+#[test]
+fn test_durations_too_long_are_errors() {
+    let repo = init_repo();
+
+    for since in ["999999999d", "9999999999999999d", "9223372036854775807y"] {
+        engram(repo.path())
+            .args(["costs", "--since", since])
+            .assert()
+            .failure()
+            .code(1)
+            .stderr(contains(format!("Duration '{since}' is too long")));
+    }
+    engram(repo.path())
+        .args(["gc", "--older-than", "999999999d", "--dry-run"])
+        .assert()
+        .code(1)
+        .stderr(contains("is too long"));
+    engram(repo.path())
+        .args(["costs", "--since", "90x"])
+        .assert()
+        .code(1)
+        .stderr(contains("Unknown duration unit 'x'"));
+}
+
+#[test]
+fn test_costs_with_bounds_and_no_engrams() {
+    let repo = init_repo();
+
+    engram(repo.path())
+        .args([
+            "costs",
+            "--by",
+            "day",
+            "--since",
+            "1900-01-01",
+            "--until",
+            "2100-01-01",
+        ])
+        .assert()
+        .success()
+        .stdout("No engrams found.\n");
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use std::path::Path;
+
+use common::{engram, init_repo};
+use predicates::str::contains;
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// A Claude Code session at `time` on 2026-01-15 that writes `file`.
+fn session(prompt: &str, time: &str, file: &str) -> String {
+    format!(
+        r#"{{"type":"user","uuid":"u1","timestamp":"2026-01-15T{time}:00Z","message":{{"role":"user","content":"{prompt}"}}}}
+{{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2026-01-15T{time}:05Z","message":{{"role":"assistant","content":[{{"type":"tool_use","id":"t1","name":"Write","input":{{"file_path":"{file}","content":"// new"}}}}],"model":"claude-sonnet-4-5","usage":{{"input_tokens":1000,"output_tokens":200}}}}}}
+"#
+    )
+}
+
+fn repo_with_history() -> TempDir {
+    let dir = init_repo();
+    for (i, (prompt, time, file)) in [
+        ("Add a cache, with eviction", "10:00", "src/cache.rs"),
+        (r#"Rename \"limit\"\nto max"#, "11:00", "src/limit.rs"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let name = format!("session{i}.jsonl");
+        std::fs::write(dir.path().join(&name), session(prompt, time, file)).unwrap();
+        engram(dir.path())
+            .args(["import", "--from", "claude-code", &name])
+            .assert()
+            .success();
+    }
+    dir
+}
+
+fn json(dir: &Path, args: &[&str]) -> Value {
+    let output = engram(dir)
+        .args(["--format", "json"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// The header and rows `engram --format csv <args>` prints.
+fn csv(dir: &Path, args: &[&str]) -> (Vec<String>, Vec<Vec<String>>) {
+    let output = engram(dir)
+        .args(["--format", "csv"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let mut reader = csv::Reader::from_reader(output.stdout.as_slice());
+    let header = reader.headers().unwrap().iter().map(String::from).collect();
+    let rows = reader
+        .records()
+        .map(|r| r.unwrap().iter().map(String::from).collect())
+        .collect();
+    (header, rows)
+}
+
+#[test]
+fn test_log_csv_matches_json() {
+    let repo = repo_with_history();
+    let log = json(repo.path(), &["log"]);
+    let (header, rows) = csv(repo.path(), &["log"]);
+
+    assert_eq!(
+        header,
+        [
+            "id",
+            "created_at",
+            "agent",
+            "model",
+            "tokens_in",
+            "tokens_out",
+            "cost",
+            "files_changed",
+            "summary"
+        ]
+    );
+    assert_eq!(rows.len(), 2);
+    for (row, m) in rows.iter().zip(log.as_array().unwrap()) {
+        assert_eq!(row[0], m["id"].as_str().unwrap());
+        assert_eq!(row[1], m["created_at"].as_str().unwrap());
+        assert_eq!(row[2], m["agent"]["name"].as_str().unwrap());
+        assert_eq!(row[3], m["agent"]["model"].as_str().unwrap());
+        assert_eq!(row[4], m["token_usage"]["input_tokens"].to_string());
+        assert_eq!(row[5], m["token_usage"]["output_tokens"].to_string());
+        assert_eq!(row[7], "1");
+        // Commas, quotes and newlines survive the round trip
+        assert_eq!(row[8], m["summary"].as_str().unwrap());
+    }
+    assert!(rows[0][8].contains('\n'), "{rows:?}");
+    assert!(rows[1][8].contains(','), "{rows:?}");
+
+    let (header, rows) = csv(repo.path(), &["log", "--columns", "summary,id"]);
+    assert_eq!(header, ["summary", "id"]);
+    assert_eq!(rows[1][1], log[1]["id"].as_str().unwrap());
+
+    engram(repo.path())
+        .args(["log", "--columns", "id"])
+        .assert()
+        .failure()
+        .stderr(contains("--columns only applies to --format csv"));
+}
+
+#[test]
+fn test_stats_and_costs_csv_match_json() {
+    let repo = repo_with_history();
+
+    let stats = json(repo.path(), &["stats", "--by-agent"]);
+    let (header, rows) = csv(repo.path(), &["stats", "--by-agent"]);
+    assert_eq!(header[..3], ["agent", "model", "engrams"]);
+    let by_model = &stats["by_agent_model"][0];
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0][0], by_model["agent"].as_str().unwrap());
+    assert_eq!(rows[0][1], by_model["model"].as_str().unwrap());
+    assert_eq!(rows[0][2], by_model["engrams"].to_string());
+    assert_eq!(rows[0][5], by_model["total_tokens"].to_string());
+    assert_eq!(rows[0][8], by_model["files_touched"].to_string());
+    assert_eq!(rows[0][9], by_model["last_active"].as_str().unwrap());
+
+    engram(repo.path())
+        .args(["--format", "csv", "stats"])
+        .assert()
+        .failure()
+        .stderr(contains("needs --by-agent"));
+
+    let costs = json(repo.path(), &["costs", "--by", "week"]);
+    let (header, rows) = csv(repo.path(), &["costs", "--by", "week"]);
+    assert_eq!(header[..3], ["start", "agent", "engrams"]);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0][1], "");
+    assert_eq!(rows[0][2], costs[0]["engrams"].to_string());
+    assert_eq!(rows[0][3], costs[0]["input_tokens"].to_string());
+    assert_eq!(rows[1][1], costs[0]["agents"][0]["agent"].as_str().unwrap());
+}
+
+#[test]
+fn test_csv_refused_by_other_commands() {
+    let repo = repo_with_history();
+    engram(repo.path())
+        .args(["--format", "csv", "search", "cache"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "--format csv is only supported by log, stats --by-agent or --by, and costs",
+        ));
+}
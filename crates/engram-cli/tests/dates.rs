@@ -0,0 +1,66 @@
+mod common;
+
+use common::engram;
+use common::history::repo_with_history;
+use predicates::str::contains;
+
+fn stdout(output: std::process::Output) -> String {
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_log_shows_relative_dates_unless_asked() {
+    let (dir, _) = repo_with_history();
+
+    let log = stdout(engram(dir.path()).arg("log").output().unwrap());
+    assert_eq!(log.lines().count(), 2, "{log}");
+    for line in log.lines() {
+        assert!(line.ends_with(" ago"), "{log}");
+    }
+    assert!(!log.contains("2026-01-15"), "{log}");
+
+    engram(dir.path())
+        .args(["log", "--absolute-time"])
+        .assert()
+        .success()
+        .stdout(contains("  2026-01-15 14:30\n"))
+        .stdout(contains("  2026-01-15 10:00\n"));
+
+    // Machine-readable formats keep full timestamps
+    engram(dir.path())
+        .args(["--format", "json", "log"])
+        .assert()
+        .success()
+        .stdout(contains("\"created_at\": \"2026-01-15T10:00:00Z\""));
+}
+
+#[test]
+fn test_display_timezone() {
+    let (dir, ids) = repo_with_history();
+    engram(dir.path())
+        .args(["config", "set", "displayTimezone", "local"])
+        .assert()
+        .success();
+
+    engram(dir.path())
+        .env("TZ", "Asia/Kolkata")
+        .args(["show", &ids[0]])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Date:   2026-01-15 15:30:00 +05:30\nDuration: 5s\n",
+        ));
+    engram(dir.path())
+        .env("TZ", "Asia/Kolkata")
+        .args(["log", "--absolute-time"])
+        .assert()
+        .success()
+        .stdout(contains("  2026-01-15 20:00\n"));
+
+    engram(dir.path())
+        .args(["config", "set", "displayTimezone", "cet"])
+        .assert()
+        .failure()
+        .stderr(contains("expected one of utc, local"));
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::path::Path;
+
+use chrono::Duration;
+use common::{claude_session, engram, init_repo};
+use engram_core::model::EngramId;
+use engram_core::storage::GitStorage;
+use predicates::str::contains;
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// An imported session plus, named by their summaries:
+///
+/// | summary   | agent       | created              | entries |
+/// |-----------|-------------|----------------------|---------|
+/// | imported  | claude-code | session start        | 2       |
+/// | recorded  | claude-code | 3 minutes later      | 3       |
+/// | next day  | claude-code | a day later          | 2       |
+/// | cursor    | cursor      | 1 minute later       | 2       |
+///
+/// All four have the same request; only "imported" and "recorded" are the
+/// same session.
+fn repo_with_duplicates() -> TempDir {
+    let dir = init_repo();
+    std::fs::write(
+        dir.path().join("s.jsonl"),
+        claude_session("Fix the login flow"),
+    )
+    .unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "s.jsonl"])
+        .assert()
+        .success();
+
+    let storage = GitStorage::open(dir.path()).unwrap();
+    let imported = storage.list(&Default::default()).unwrap().remove(0);
+    engram(dir.path())
+        .args(["amend", imported.id.as_str(), "--summary", "imported"])
+        .assert()
+        .success();
+    let original = storage.read(imported.id.as_str()).unwrap();
+
+    for (summary, agent, minutes, extra_entry) in [
+        ("recorded", "claude-code", 3, true),
+        ("next day", "claude-code", 24 * 60, false),
+        ("cursor", "cursor", 1, false),
+    ] {
+        let mut data = original.clone();
+        data.manifest.id = EngramId::new();
+        data.manifest.agent.name = agent.to_string();
+        data.manifest.summary = Some(summary.to_string());
+        data.manifest.created_at += Duration::minutes(minutes);
+        data.manifest.source_hash = None;
+        if extra_entry {
+            let entry = data.transcript.entries[0].clone();
+            data.transcript.entries.push(entry);
+        }
+        storage.create(&data).unwrap();
+    }
+    engram(dir.path()).arg("reindex").assert().success();
+    dir
+}
+
+fn summaries(dir: &Path) -> Vec<String> {
+    let storage = GitStorage::open(dir).unwrap();
+    let mut summaries: Vec<_> = storage
+        .list(&Default::default())
+        .unwrap()
+        .into_iter()
+        .map(|m| m.summary.unwrap())
+        .collect();
+    summaries.sort();
+    summaries
+}
+
+#[test]
+fn test_dedupe_lists_without_deleting() {
+    let repo = repo_with_duplicates();
+    let dir = repo.path();
+
+    engram(dir)
+        .arg("dedupe")
+        .assert()
+        .success()
+        .stdout(contains("1 group(s) of duplicates, 1 redundant engram(s)"))
+        .stdout(contains("same agent and request"))
+        .stderr(contains("Use --apply"));
+    assert_eq!(
+        summaries(dir),
+        ["cursor", "imported", "next day", "recorded"]
+    );
+
+    let output = engram(dir)
+        .args(["--format", "json", "dedupe"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["applied"], false);
+    assert_eq!(report["deleted"], 0);
+    let group = &report["groups"][0];
+    assert_eq!(group["reason"], "same_request");
+    assert_eq!(group["engrams"][0]["manifest"]["summary"], "recorded");
+    assert_eq!(group["engrams"][0]["transcript_entries"], 3);
+    assert_eq!(group["engrams"][1]["manifest"]["summary"], "imported");
+}
+
+#[test]
+fn test_dedupe_apply_keeps_the_richest() {
+    let repo = repo_with_duplicates();
+    let dir = repo.path();
+
+    engram(dir)
+        .args(["dedupe", "--apply"])
+        .assert()
+        .success()
+        .stdout(contains("Deleted 1 engram(s)."));
+    assert_eq!(summaries(dir), ["cursor", "next day", "recorded"]);
+
+    // The index forgot the deleted engram
+    let output = engram(dir)
+        .args(["--format", "json", "search", "login"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let results: Vec<Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(results.len(), 3);
+
+    engram(dir)
+        .arg("dedupe")
+        .assert()
+        .success()
+        .stdout(contains("No duplicate engrams found."));
+}
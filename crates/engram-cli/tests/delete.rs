@@ -0,0 +1,141 @@
+mod common;
+
+use std::path::Path;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+/// Import a session with `prompt`, moved to `date`.
+fn import(dir: &Path, prompt: &str, date: &str) {
+    let file = format!("{}.jsonl", prompt.replace(' ', "-"));
+    let session = claude_session(prompt).replace("2026-01-15", date);
+    std::fs::write(dir.join(&file), session).unwrap();
+    engram(dir)
+        .args(["import", "--from", "claude-code", &file])
+        .assert()
+        .success();
+}
+
+/// Summaries of the engrams matching `query` in the search index.
+fn search(dir: &Path, query: &str) -> Vec<String> {
+    let output = engram(dir)
+        .args(["--format", "json", "search", query])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    if output.stdout.is_empty() {
+        return Vec::new(); // "No results found" goes to stderr
+    }
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    results
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["summary"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn summaries(dir: &Path) -> Vec<String> {
+    let output = engram(dir)
+        .args(["--format", "json", "log", "--all"])
+        .output()
+        .unwrap();
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    log.as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["summary"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_delete_single_engram() {
+    let dir = init_repo();
+    import(dir.path(), "Add caching", "2026-01-15");
+    import(dir.path(), "Fix caching bug", "2026-01-16");
+    assert_eq!(search(dir.path(), "caching").len(), 2);
+
+    // Without a terminal to confirm on, nothing is deleted
+    engram(dir.path())
+        .args(["delete", "HEAD"])
+        .assert()
+        .success()
+        .stdout(contains("Fix caching bug"))
+        .stderr(contains("Use --yes"));
+    assert_eq!(summaries(dir.path()).len(), 2);
+
+    engram(dir.path())
+        .args(["delete", "HEAD", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("Deleted 1 engram(s)."));
+    assert_eq!(summaries(dir.path()), ["Add caching"]);
+    assert_eq!(search(dir.path(), "caching"), ["Add caching"]);
+
+    // The deleted session can be imported again
+    import(dir.path(), "Fix caching bug", "2026-01-16");
+    assert_eq!(summaries(dir.path()).len(), 2);
+
+    engram(dir.path())
+        .args(["delete", "0000000", "--yes"])
+        .assert()
+        .failure()
+        .stderr(contains("Failed to resolve engram '0000000'"));
+}
+
+#[test]
+fn test_delete_keep_dedup_blocks_reimport() {
+    let dir = init_repo();
+    import(dir.path(), "Add caching", "2026-01-15");
+
+    engram(dir.path())
+        .args(["delete", "HEAD", "--yes", "--keep-dedup"])
+        .assert()
+        .success();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "Add-caching.jsonl"])
+        .assert()
+        .success()
+        .stdout(contains("deleted with --keep-dedup"));
+    assert!(summaries(dir.path()).is_empty());
+}
+
+#[test]
+fn test_delete_in_bulk() {
+    let dir = init_repo();
+    import(dir.path(), "Add caching", "2026-01-15");
+    import(dir.path(), "Fix caching bug", "2026-02-10");
+    import(dir.path(), "Tune caching", "2026-03-05");
+    assert_eq!(search(dir.path(), "caching").len(), 3);
+
+    engram(dir.path())
+        .args(["delete", "--before", "2026-02-11"])
+        .assert()
+        .failure()
+        .stderr(contains("pass --yes"));
+    engram(dir.path())
+        .args(["delete", "HEAD", "--agent", "claude-code", "--yes"])
+        .assert()
+        .failure();
+
+    engram(dir.path())
+        .args(["delete", "--before", "2026-02-11", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("2 engram(s) to delete"))
+        .stdout(contains("Deleted 2 engram(s)."));
+    assert_eq!(summaries(dir.path()), ["Tune caching"]);
+    assert_eq!(search(dir.path(), "caching"), ["Tune caching"]);
+
+    engram(dir.path())
+        .args(["delete", "--agent", "aider", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("No engrams match"));
+    engram(dir.path())
+        .args(["delete", "--agent", "claude-code", "--yes"])
+        .assert()
+        .success();
+    assert!(summaries(dir.path()).is_empty());
+    assert!(search(dir.path(), "caching").is_empty());
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+#[test]
+fn test_diff_three_ids_shows_matrix() {
+    let repo = init_repo();
+    for (i, prompt) in ["Cache with opus", "Cache with sonnet", "Cache with haiku"]
+        .iter()
+        .enumerate()
+    {
+        let file = format!("session{i}.jsonl");
+        std::fs::write(repo.path().join(&file), claude_session(prompt)).unwrap();
+        engram(repo.path())
+            .args(["import", "--from", "claude-code", &file])
+            .assert()
+            .success();
+    }
+    let output = engram(repo.path())
+        .args(["--format", "json", "log", "--all"])
+        .output()
+        .unwrap();
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let ids: Vec<String> = log
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(ids.len(), 3);
+
+    engram(repo.path())
+        .arg("diff")
+        .args(&ids)
+        .assert()
+        .success()
+        .stdout(contains("Comparing 3 engrams"))
+        .stdout(contains(&ids[2][..8]));
+
+    let output = engram(repo.path())
+        .args(["--format", "json", "diff", "--matrix", &ids[0], &ids[1]])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["engrams"].as_array().unwrap().len(), 2);
+    assert_eq!(json["engrams"][1]["id"], ids[1]);
+}
+
+#[test]
+fn test_diff_and_show_accept_head_ancestry() {
+    let repo = init_repo();
+    for (i, prompt) in ["Cache with opus", "Cache with sonnet", "Cache with haiku"]
+        .iter()
+        .enumerate()
+    {
+        // An hour apart, so HEAD~N has a definite order
+        let session = claude_session(prompt).replace("T10:", &format!("T1{i}:"));
+        let file = format!("session{i}.jsonl");
+        std::fs::write(repo.path().join(&file), session).unwrap();
+        engram(repo.path())
+            .args(["import", "--from", "claude-code", &file])
+            .assert()
+            .success();
+    }
+    let id_of = |reference: &str| {
+        let output = engram(repo.path())
+            .args([
+                "--format", "json", "show", reference, "--fields", "manifest",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["id"].as_str().unwrap().to_string()
+    };
+    let newest = id_of("HEAD");
+    let middle = id_of("HEAD~1");
+    let oldest = id_of("HEAD~2");
+    assert_ne!(newest, middle);
+    assert_ne!(middle, oldest);
+
+    engram(repo.path())
+        .args(["show", "HEAD~2"])
+        .assert()
+        .success()
+        .stdout(contains("Cache with opus"));
+
+    let output = engram(repo.path())
+        .args(["--format", "json", "diff", "HEAD", "HEAD~1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["id_a"], newest);
+    assert_eq!(json["id_b"], middle);
+
+    // Without lineage, <id>~N steps back by recency
+    engram(repo.path())
+        .args(["diff", &format!("{}~1", &middle[..8]), "HEAD~2"])
+        .assert()
+        .success();
+    assert_eq!(id_of(&format!("{}~", &newest[..8])), middle);
+
+    engram(repo.path())
+        .args(["diff", "HEAD", "HEAD~3"])
+        .assert()
+        .failure()
+        .stderr(contains("HEAD~3 (only 3 engram(s) exist)"));
+}
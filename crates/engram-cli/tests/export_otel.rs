@@ -0,0 +1,128 @@
+#![cfg(feature = "otel")]
+
+mod common;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread::JoinHandle;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+/// A collector that takes one request and returns its request line,
+/// headers and body.
+fn collector() -> (String, JoinHandle<(String, String, Vec<u8>)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut headers = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            headers.push_str(&line.to_ascii_lowercase());
+        }
+        let length = headers
+            .lines()
+            .find_map(|h| h.strip_prefix("content-length:"))
+            .map(|n| n.trim().parse().unwrap())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        (request_line, headers, body)
+    });
+    (url, handle)
+}
+
+#[test]
+fn test_export_otel_posts_spans() {
+    let dir = init_repo();
+    let session = claude_session("Add caching").replace(
+        r#"[{"type":"text","text":"Done."}]"#,
+        r#"[{"type":"text","text":"Done."},{"type":"tool_use","id":"t1","name":"Write","input":{"file_path":"src/cache.rs"}}]"#,
+    );
+    std::fs::write(dir.path().join("session.jsonl"), session).unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+    let id = engram(dir.path())
+        .args(["show", "HEAD", "--porcelain"])
+        .output()
+        .unwrap();
+    let id = String::from_utf8(id.stdout).unwrap();
+    let id = id
+        .lines()
+        .find_map(|l| l.strip_prefix("id\t"))
+        .unwrap()
+        .to_string();
+
+    let (url, collector) = collector();
+    engram(dir.path())
+        .args(["export-otel", "HEAD", "--endpoint", &url])
+        .env_remove("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT")
+        .env("OTEL_SERVICE_NAME", "backfill")
+        .assert()
+        .success()
+        .stderr(contains("Exported 1 engram(s) as 2 span(s)"));
+
+    let (request_line, headers, body) = collector.join().unwrap();
+    assert!(
+        request_line.starts_with("POST /v1/traces "),
+        "{request_line}"
+    );
+    assert!(headers.contains("content-type: application/x-protobuf"));
+    // Protobuf keeps strings as they are
+    let body = String::from_utf8_lossy(&body);
+    for expected in [
+        "engram.session",
+        "Write",
+        "engram.id",
+        id.as_str(),
+        "backfill",
+    ] {
+        assert!(body.contains(expected), "{expected} not exported");
+    }
+}
+
+#[test]
+fn test_export_otel_reports_failures() {
+    let dir = init_repo();
+    let url = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    engram(dir.path())
+        .args(["export-otel", "missing", "--endpoint", &url])
+        .assert()
+        .failure()
+        .stderr(contains("Failed to resolve engram 'missing'"));
+
+    std::fs::write(
+        dir.path().join("session.jsonl"),
+        claude_session("Add caching"),
+    )
+    .unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+    engram(dir.path())
+        .args(["export-otel", "HEAD", "--endpoint", &url])
+        .env_remove("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT")
+        .assert()
+        .failure()
+        .stderr(contains(format!(
+            "Failed to export spans to {url}/v1/traces"
+        )));
+}
@@ -0,0 +1,269 @@
+mod common;
+
+use std::path::Path;
+
+use chrono::{Duration, Utc};
+use common::{engram, init_repo};
+use engram_core::model::EngramId;
+use engram_core::storage::GitStorage;
+use predicates::str::contains;
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// A Claude Code session on 2026-01-15 that writes src/auth.rs.
+const SESSION: &str = r#"{"type":"user","uuid":"u1","timestamp":"2026-01-15T10:00:00Z","message":{"role":"user","content":"Fix the login flow"}}
+{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2026-01-15T10:00:05Z","message":{"role":"assistant","content":[{"type":"text","text":"Fixing login."},{"type":"tool_use","id":"t1","name":"Write","input":{"file_path":"src/auth.rs","content":"// auth"}}],"model":"claude-sonnet-4-5","usage":{"input_tokens":1000,"output_tokens":200}}}
+"#;
+
+/// Three login engrams, named by their summaries:
+///
+/// | summary | agent       | file        | tag  | created     |
+/// |---------|-------------|-------------|------|-------------|
+/// | old     | claude-code | src/auth.rs | auth | 2026-01-15  |
+/// | recent  | cursor      | src/auth.rs |      | 1 day ago   |
+/// | cache   | cursor      | src/cache.rs| auth | 2 days ago  |
+fn repo_with_filters() -> TempDir {
+    let dir = init_repo();
+    std::fs::write(dir.path().join("session.jsonl"), SESSION).unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+
+    let storage = GitStorage::open(dir.path()).unwrap();
+    let old = storage.list(&Default::default()).unwrap().remove(0);
+    let mut data = storage.read(old.id.as_str()).unwrap();
+    engram(dir.path())
+        .args(["tag", old.id.as_str(), "--add", "auth"])
+        .assert()
+        .success();
+    engram(dir.path())
+        .args(["amend", old.id.as_str(), "--summary", "old login fix"])
+        .assert()
+        .success();
+
+    for (summary, path, tags, days_ago) in [
+        ("recent login fix", "src/auth.rs", vec![], 1),
+        (
+            "cache login fix",
+            "src/cache.rs",
+            vec!["auth".to_string()],
+            2,
+        ),
+    ] {
+        data.manifest.id = EngramId::new();
+        data.manifest.agent.name = "cursor".to_string();
+        data.manifest.summary = Some(summary.to_string());
+        data.manifest.tags = tags;
+        data.manifest.created_at = Utc::now() - Duration::days(days_ago);
+        data.manifest.source_hash = None;
+        data.operations.file_changes[0].path = path.to_string();
+        storage.create(&data).unwrap();
+    }
+    engram(dir.path()).arg("reindex").assert().success();
+    dir
+}
+
+/// The summaries of the manifests `engram --format json <args>` prints.
+fn summaries(dir: &Path, args: &[&str]) -> Vec<String> {
+    let output = engram(dir)
+        .args(["--format", "json"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    // Nothing matching prints a message on stderr and no JSON
+    if output.stdout.is_empty() {
+        return Vec::new();
+    }
+    let manifests: Vec<Value> = serde_json::from_slice(&output.stdout).unwrap();
+    let mut summaries: Vec<String> = manifests
+        .iter()
+        .map(|m| m["summary"].as_str().unwrap().to_string())
+        .collect();
+    summaries.sort();
+    summaries
+}
+
+/// The summaries of the engrams `engram --format json blame <args>` lists,
+/// most recent first.
+fn blamed(dir: &Path, args: &[&str]) -> Vec<String> {
+    let output = engram(dir)
+        .args(["--format", "json", "blame"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let entries: Vec<Value> = serde_json::from_slice(&output.stdout).unwrap();
+    let storage = GitStorage::open(dir).unwrap();
+    entries
+        .iter()
+        .map(|entry| {
+            let id = entry["engram_id"].as_str().unwrap();
+            storage.read_manifest(id).unwrap().summary.unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn test_search_filter_flags() {
+    let repo = repo_with_filters();
+    let dir = repo.path();
+    let search = |args: &[&str]| summaries(dir, &[&["search", "login"], args].concat());
+
+    assert_eq!(
+        search(&[]),
+        ["cache login fix", "old login fix", "recent login fix"]
+    );
+    assert_eq!(
+        search(&["--agent", "cursor"]),
+        ["cache login fix", "recent login fix"]
+    );
+    assert_eq!(
+        search(&["--file", "src/auth.rs"]),
+        ["old login fix", "recent login fix"]
+    );
+    assert_eq!(
+        search(&["--tag", "auth"]),
+        ["cache login fix", "old login fix"]
+    );
+    assert_eq!(
+        search(&["--since", "7d"]),
+        ["cache login fix", "recent login fix"]
+    );
+    assert_eq!(search(&["--since", "7d", "--until", "2026-01-31"]), [""; 0]);
+
+    // Combined flags all apply
+    assert_eq!(
+        search(&["--agent", "cursor", "--file", "src/auth.rs"]),
+        ["recent login fix"]
+    );
+    assert_eq!(
+        search(&["--tag", "auth", "--since", "7d"]),
+        ["cache login fix"]
+    );
+    assert_eq!(
+        search(&[
+            "--agent",
+            "claude-code",
+            "--tag",
+            "auth",
+            "--file",
+            "src/auth.rs"
+        ]),
+        ["old login fix"]
+    );
+
+    // The limit counts filtered results
+    assert_eq!(
+        search(&["--agent", "cursor", "--sort", "newest", "-n", "1"]),
+        ["recent login fix"]
+    );
+}
+
+#[test]
+fn test_search_filter_flags_without_query() {
+    let repo = repo_with_filters();
+    assert_eq!(
+        summaries(
+            repo.path(),
+            &["search", "--tag", "auth", "--agent", "cursor"]
+        ),
+        ["cache login fix"]
+    );
+    engram(repo.path())
+        .arg("search")
+        .assert()
+        .failure()
+        .stderr(contains("required"));
+}
+
+#[test]
+fn test_trace_filter_flags() {
+    let repo = repo_with_filters();
+    let trace = |args: &[&str]| summaries(repo.path(), &[&["trace", "src/auth.rs"], args].concat());
+
+    assert_eq!(trace(&[]), ["old login fix", "recent login fix"]);
+    assert_eq!(trace(&["--agent", "cursor"]), ["recent login fix"]);
+    assert_eq!(trace(&["--tag", "auth"]), ["old login fix"]);
+    assert_eq!(trace(&["--since", "7d"]), ["recent login fix"]);
+    assert_eq!(trace(&["--until", "2026-01-15"]), ["old login fix"]);
+    assert_eq!(trace(&["--agent", "cursor", "--tag", "auth"]), [""; 0]);
+}
+
+#[test]
+fn test_blame_filter_flags() {
+    let repo = repo_with_filters();
+    let dir = repo.path();
+
+    assert_eq!(
+        blamed(dir, &["src/auth.rs"]),
+        ["recent login fix", "old login fix"]
+    );
+    assert_eq!(
+        blamed(dir, &["src/auth.rs", "-n", "1"]),
+        ["recent login fix"]
+    );
+    // Filtering happens before the limit, so the newest match is kept
+    assert_eq!(
+        blamed(dir, &["src/auth.rs", "-n", "1", "--agent", "claude-code"]),
+        ["old login fix"]
+    );
+    engram(dir)
+        .args(["blame", "src/auth.rs", "--since", "30d", "--tag", "auth"])
+        .assert()
+        .success()
+        .stdout(contains("No engrams found"));
+}
+
+#[test]
+fn test_trace_resolves_paths_against_cwd() {
+    let repo = repo_with_filters();
+    let nested = repo.path().join("src");
+    std::fs::create_dir_all(&nested).unwrap();
+    let absolute = repo.path().join("src/auth.rs");
+
+    assert_eq!(
+        summaries(&nested, &["trace", "auth.rs"]),
+        ["old login fix", "recent login fix"]
+    );
+    assert_eq!(
+        summaries(&nested, &["trace", "../src/auth.rs"]),
+        ["old login fix", "recent login fix"]
+    );
+    assert_eq!(
+        summaries(repo.path(), &["trace", absolute.to_str().unwrap()]),
+        ["old login fix", "recent login fix"]
+    );
+    assert_eq!(
+        summaries(&nested, &["search", "login", "--file", "cache.rs"]),
+        ["cache login fix"]
+    );
+    engram(&nested)
+        .args(["blame", "auth.rs"])
+        .assert()
+        .success()
+        .stdout(contains("Reasoning blame for: src/auth.rs"));
+
+    engram(&nested)
+        .args(["trace", "../../elsewhere.rs"])
+        .assert()
+        .failure()
+        .stderr(contains("outside the repository"));
+}
+
+#[test]
+fn test_trace_without_usable_index_scans_engrams() {
+    let repo = repo_with_filters();
+    std::fs::remove_file(repo.path().join(".git/engram-index/meta.json")).unwrap();
+
+    assert_eq!(
+        summaries(repo.path(), &["trace", "src/auth.rs"]),
+        ["old login fix", "recent login fix"]
+    );
+    engram(repo.path())
+        .args(["trace", "src/auth.rs", "--agent", "cursor"])
+        .assert()
+        .success()
+        .stderr(contains("engram reindex"));
+}
@@ -0,0 +1,216 @@
+mod common;
+
+use std::path::Path;
+
+use chrono::{Duration, Utc};
+use common::{claude_session, engram, init_repo};
+use engram_core::model::EngramId;
+use engram_core::storage::refs::list_engram_refs;
+use engram_core::storage::GitStorage;
+use predicates::str::contains;
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// Three engrams with the same transcript, named by their summaries:
+///
+/// | summary       | agent       | created      |
+/// |---------------|-------------|--------------|
+/// | old claude    | claude-code | 400 days ago |
+/// | old cursor    | cursor      | 200 days ago |
+/// | recent cursor | cursor      | 2 days ago   |
+fn seeded_repo() -> TempDir {
+    let dir = init_repo();
+    std::fs::write(
+        dir.path().join("s.jsonl"),
+        claude_session("Fix the login flow"),
+    )
+    .unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "s.jsonl"])
+        .assert()
+        .success();
+
+    let storage = GitStorage::open(dir.path()).unwrap();
+    let imported = storage.list(&Default::default()).unwrap().remove(0);
+    let mut data = storage.read(imported.id.as_str()).unwrap();
+    storage.delete(imported.id.as_str()).unwrap();
+    for (summary, agent, days_ago) in [
+        ("old claude", "claude-code", 400),
+        ("old cursor", "cursor", 200),
+        ("recent cursor", "cursor", 2),
+    ] {
+        data.manifest.id = EngramId::new();
+        data.manifest.agent.name = agent.to_string();
+        data.manifest.summary = Some(summary.to_string());
+        data.manifest.created_at = Utc::now() - Duration::days(days_ago);
+        data.manifest.source_hash = None;
+        storage.create(&data).unwrap();
+    }
+    dir
+}
+
+/// The engram refs and every object in the repository, for checking that
+/// nothing changed.
+fn snapshot(dir: &Path) -> (Vec<String>, Vec<String>) {
+    let repo = git2::Repository::open(dir).unwrap();
+    let mut refs: Vec<_> = list_engram_refs(&repo)
+        .unwrap()
+        .into_iter()
+        .map(|(id, oid)| format!("{} {oid}", id.as_str()))
+        .collect();
+    refs.sort();
+    let mut objects = Vec::new();
+    let odb = repo.odb().unwrap();
+    odb.foreach(|oid| {
+        objects.push(oid.to_string());
+        true
+    })
+    .unwrap();
+    objects.sort();
+    (refs, objects)
+}
+
+/// The manifest blob of the engram with this summary.
+fn manifest_blob(dir: &Path, summary: &str) -> git2::Oid {
+    let repo = git2::Repository::open(dir).unwrap();
+    let storage = GitStorage::open(dir).unwrap();
+    list_engram_refs(&repo)
+        .unwrap()
+        .into_iter()
+        .find(|(id, _)| {
+            storage
+                .read_manifest(id.as_str())
+                .unwrap()
+                .summary
+                .as_deref()
+                == Some(summary)
+        })
+        .map(|(_, oid)| {
+            let tree = repo.find_commit(oid).unwrap().tree().unwrap();
+            let blob = tree.get_name("manifest.json").unwrap().id();
+            blob
+        })
+        .unwrap()
+}
+
+fn exists(dir: &Path, oid: git2::Oid) -> bool {
+    let repo = git2::Repository::open(dir).unwrap();
+    let exists = repo.odb().unwrap().exists(oid);
+    exists
+}
+
+fn gc_json(dir: &Path, args: &[&str]) -> Value {
+    let output = engram(dir)
+        .args(["--format", "json", "gc"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_gc_dry_run_reports_candidates_and_sizes() {
+    let repo = seeded_repo();
+    let dir = repo.path();
+    let before = snapshot(dir);
+
+    let report = gc_json(dir, &["--older-than", "180d", "--dry-run"]);
+    let summaries: Vec<_> = report["candidates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["summary"].as_str().unwrap())
+        .collect();
+    assert_eq!(summaries, ["old cursor", "old claude"]);
+    assert_eq!(
+        report["by_agent"],
+        serde_json::json!({ "claude-code": 1, "cursor": 1 })
+    );
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["deleted"], 0);
+
+    // The shared transcript and operations are counted once, and stay
+    // behind for the recent engram
+    let sizes: u64 = report["candidates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["bytes"].as_u64().unwrap())
+        .sum();
+    let total = report["total_bytes"].as_u64().unwrap();
+    let reclaimable = report["reclaimable_bytes"].as_u64().unwrap();
+    assert!(total < sizes, "{report}");
+    assert!(0 < reclaimable && reclaimable < total, "{report}");
+    assert!(report["shared_blobs"].as_u64().unwrap() > 0);
+
+    engram(dir)
+        .args(["gc", "--older-than", "180d", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(contains("2 engram(s) to delete (dry run)"))
+        .stdout(contains("By agent: claude-code 1, cursor 1"))
+        .stdout(contains("counted once"))
+        .stdout(contains("reclaimable"))
+        .stdout(contains("Dry run — no engrams were deleted."));
+
+    // Neither run touched a ref or an object
+    assert_eq!(snapshot(dir), before);
+}
+
+#[test]
+fn test_gc_without_yes_deletes_nothing() {
+    let repo = seeded_repo();
+    let dir = repo.path();
+    let before = snapshot(dir);
+
+    let report = gc_json(dir, &["--older-than", "300d"]);
+    assert_eq!(report["candidates"].as_array().unwrap().len(), 1);
+    assert_eq!(report["deleted"], 0);
+    assert_eq!(snapshot(dir), before);
+
+    let report = gc_json(dir, &["--older-than", "5y", "--dry-run"]);
+    assert!(report["candidates"].as_array().unwrap().is_empty());
+    assert_eq!(report["total_bytes"], 0);
+}
+
+#[test]
+fn test_gc_prune_objects_frees_deleted_engrams() {
+    let repo = seeded_repo();
+    let dir = repo.path();
+    let claude = manifest_blob(dir, "old claude");
+    let cursor = manifest_blob(dir, "old cursor");
+
+    engram(dir)
+        .args(["gc", "--older-than", "300d", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("Deleted 1 engram(s)."))
+        .stdout(contains("git gc --prune=now"));
+    assert!(exists(dir, claude), "objects stay until Git prunes them");
+
+    let report = gc_json(dir, &["--older-than", "180d", "--yes", "--prune-objects"]);
+    assert_eq!(report["deleted"], 1);
+    assert_eq!(report["pruned"], true);
+    assert!(!exists(dir, claude));
+    assert!(!exists(dir, cursor));
+
+    let storage = GitStorage::open(dir).unwrap();
+    let left: Vec<_> = storage
+        .list(&Default::default())
+        .unwrap()
+        .into_iter()
+        .map(|m| m.summary.unwrap())
+        .collect();
+    assert_eq!(left, ["recent cursor"]);
+    engram(dir)
+        .args(["show", "HEAD"])
+        .assert()
+        .success()
+        .stdout(contains("recent cursor"));
+
+    engram(dir)
+        .args(["gc", "--dry-run", "--prune-objects"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+#[test]
+fn test_graph_filters_report_counts() {
+    let repo = repo_with_engram();
+
+    engram(repo.path())
+        .args(["graph", "--exclude", "agent"])
+        .assert()
+        .success()
+        .stderr(contains(
+            "Graph: 2 nodes, 1 edges; after filtering: 1 nodes, 0 edges",
+        ));
+
+    engram(repo.path())
+        .args(["graph", "--agent", "aider"])
+        .assert()
+        .success()
+        .stderr(contains("after filtering: 0 nodes, 0 edges"));
+
+    engram(repo.path())
+        .args(["graph", "--focus", "src/missing.rs"])
+        .assert()
+        .failure()
+        .stderr(contains("No graph node matches 'src/missing.rs'"));
+}
+
+/// A repository with one imported engram.
+fn repo_with_engram() -> tempfile::TempDir {
+    let repo = init_repo();
+    std::fs::write(
+        repo.path().join("session.jsonl"),
+        claude_session("Add a rate limiter"),
+    )
+    .unwrap();
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+    repo
+}
+
+/// Write an executable shell script `name` into `dir`.
+#[cfg(unix)]
+fn fake_program(dir: &std::path::Path, name: &str, script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{script}")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Stands in for `dot` and `mmdc`: records its arguments and copies stdin
+/// to the file after -o.
+#[cfg(unix)]
+const FAKE_RENDERER: &str = r#"echo "$@" > "$(dirname "$0")/args"
+while [ "$1" != "-o" ]; do shift; done
+cat > "$2"
+"#;
+
+#[cfg(unix)]
+#[test]
+fn test_graph_output_renders_with_dot() {
+    let repo = repo_with_engram();
+    let bin = tempfile::TempDir::new().unwrap();
+    fake_program(bin.path(), "dot", FAKE_RENDERER);
+    fake_program(
+        bin.path(),
+        "xdg-open",
+        r#"echo "$1" > "$(dirname "$0")/opened""#,
+    );
+    let path = format!(
+        "{}:{}",
+        bin.path().display(),
+        std::env::var("PATH").unwrap()
+    );
+
+    engram(repo.path())
+        .env("PATH", &path)
+        .args(["graph", "--output", "graph.svg"])
+        .assert()
+        .success()
+        .stdout("graph.svg\n");
+    let rendered = std::fs::read_to_string(repo.path().join("graph.svg")).unwrap();
+    assert!(rendered.starts_with("digraph"), "{rendered}");
+    assert_eq!(
+        std::fs::read_to_string(bin.path().join("args")).unwrap(),
+        "-Tsvg -o graph.svg\n"
+    );
+
+    fake_program(bin.path(), "mmdc", FAKE_RENDERER);
+    engram(repo.path())
+        .env("PATH", &path)
+        .args(["graph", "--mermaid", "-o", "graph.png", "--open"])
+        .assert()
+        .success()
+        .stdout("graph.png\n");
+    let rendered = std::fs::read_to_string(repo.path().join("graph.png")).unwrap();
+    assert!(rendered.starts_with("flowchart"), "{rendered}");
+    assert_eq!(
+        std::fs::read_to_string(bin.path().join("args")).unwrap(),
+        "-i - -o graph.png\n"
+    );
+    if cfg!(target_os = "linux") {
+        assert_eq!(
+            std::fs::read_to_string(bin.path().join("opened")).unwrap(),
+            "graph.png\n"
+        );
+    }
+
+    fake_program(bin.path(), "dot", "exit 2");
+    engram(repo.path())
+        .env("PATH", &path)
+        .args(["graph", "--output", "broken.svg"])
+        .assert()
+        .failure()
+        .stderr(contains("`dot` exited"));
+}
+
+#[test]
+fn test_graph_output_without_renderer_writes_source() {
+    let repo = repo_with_engram();
+    let empty = tempfile::TempDir::new().unwrap();
+
+    engram(repo.path())
+        .env("PATH", empty.path())
+        .args(["graph", "--output", "graph.svg"])
+        .assert()
+        .success()
+        .stdout("graph.dot\n")
+        .stderr(contains("Install Graphviz"))
+        .stderr(contains("dot -Tsvg graph.dot -o graph.svg"));
+    let source = std::fs::read_to_string(repo.path().join("graph.dot")).unwrap();
+    assert!(source.starts_with("digraph"));
+    assert!(!repo.path().join("graph.svg").exists());
+
+    engram(repo.path())
+        .env("PATH", empty.path())
+        .args(["graph", "--mermaid", "--output", "graph.pdf"])
+        .assert()
+        .success()
+        .stdout("graph.mmd\n")
+        .stderr(contains("mmdc -i graph.mmd -o graph.pdf"));
+
+    engram(repo.path())
+        .args(["graph", "--output", "graph.txt"])
+        .assert()
+        .failure()
+        .stderr(contains("use a .svg, .png or .pdf file"));
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+/// Two engrams; the first's transcript is 0 user text, 1 thinking, 2 tool
+/// call, 3 tool result holding an error, 4 assistant text.
+fn repo_with_tool_output() -> TempDir {
+    let dir = init_repo();
+    let session = r#"{"type":"user","uuid":"u1","timestamp":"2026-01-15T10:00:00Z","message":{"role":"user","content":"Start the API"}}
+{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2026-01-15T10:00:05Z","message":{"role":"assistant","content":[{"type":"thinking","thinking":"Postgres is probably down"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"psql -h 127.0.0.1"}}],"model":"claude-sonnet-4-5"}}
+{"type":"user","uuid":"u2","parentUuid":"a1","timestamp":"2026-01-15T10:00:06Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"psql: error\nECONNREFUSED 127.0.0.1:5432\nIs the server running?"}]}}
+{"type":"assistant","uuid":"a2","parentUuid":"u2","timestamp":"2026-01-15T10:00:09Z","message":{"role":"assistant","content":[{"type":"text","text":"The database is not running."}],"model":"claude-sonnet-4-5"}}
+"#;
+    std::fs::write(dir.path().join("api.jsonl"), session).unwrap();
+    std::fs::write(
+        dir.path().join("cache.jsonl"),
+        claude_session("Add caching"),
+    )
+    .unwrap();
+    for file in ["api.jsonl", "cache.jsonl"] {
+        engram(dir.path())
+            .args(["import", "--from", "claude-code", file])
+            .assert()
+            .success();
+    }
+    dir
+}
+
+fn api_short_id(dir: &TempDir) -> String {
+    let output = engram(dir.path())
+        .args(["--format", "json", "log", "--all"])
+        .output()
+        .unwrap();
+    let manifests: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    let api = manifests
+        .iter()
+        .find(|m| m["summary"].as_str().unwrap().contains("Start the API"))
+        .unwrap();
+    api["id"].as_str().unwrap()[..8].to_string()
+}
+
+#[test]
+fn test_grep_finds_text_only_in_tool_output() {
+    let dir = repo_with_tool_output();
+    let id = api_short_id(&dir);
+
+    engram(dir.path())
+        .args(["grep", "ECONNREFUSED 127.0.0.1:5432"])
+        .assert()
+        .success()
+        .stdout(format!("{id}:3: ECONNREFUSED 127.0.0.1:5432\n"));
+
+    engram(dir.path())
+        .args(["grep", "probably down"])
+        .assert()
+        .success()
+        .stdout(format!("{id}:1: Postgres is probably down\n"));
+}
+
+#[test]
+fn test_grep_context_lines() {
+    let dir = repo_with_tool_output();
+    let id = api_short_id(&dir);
+
+    engram(dir.path())
+        .args(["grep", "ECONNREFUSED", "-C", "1"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{id}-3- psql: error\n{id}:3: ECONNREFUSED 127.0.0.1:5432\n{id}-3- Is the server running?\n"
+        ));
+}
+
+#[test]
+fn test_grep_literal_unless_regex() {
+    let dir = repo_with_tool_output();
+
+    // `.` is a literal dot by default
+    engram(dir.path())
+        .args(["grep", "127.0.0.1:54.2"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicate::str::contains("No matches for: 127.0.0.1:54.2"));
+    engram(dir.path())
+        .args(["grep", "--regex", r"127\.0\.0\.1:54.2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ECONNREFUSED"));
+}
+
+#[test]
+fn test_grep_filters_bound_the_scan() {
+    let dir = repo_with_tool_output();
+
+    engram(dir.path())
+        .args(["grep", "ECONNREFUSED", "--agent", "aider"])
+        .assert()
+        .code(1);
+    engram(dir.path())
+        .args(["grep", "ECONNREFUSED", "--since", "2026-02-01"])
+        .assert()
+        .code(1);
+    engram(dir.path())
+        .args([
+            "grep",
+            "ECONNREFUSED",
+            "--agent",
+            "claude-code",
+            "--until",
+            "2026-01-31",
+        ])
+        .assert()
+        .success();
+    engram(dir.path())
+        .args(["grep", "ECONNREFUSED", "--since", "last week"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("last week"));
+}
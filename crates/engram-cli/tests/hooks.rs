@@ -0,0 +1,95 @@
+mod common;
+
+use std::path::Path;
+
+use common::engram;
+use predicates::str::contains;
+use serde_json::Value;
+use tempfile::TempDir;
+
+const OWN_HOOK: &str = "#!/bin/sh\necho linting\n";
+
+/// A repository initialized with `--no-hooks` and a post-commit hook of
+/// its own.
+fn repo_with_own_hook() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    git2::Repository::init(tmp.path()).unwrap();
+    engram(tmp.path())
+        .args(["init", "--no-hooks"])
+        .assert()
+        .success();
+    let hooks = tmp.path().join(".git/hooks");
+    std::fs::create_dir_all(&hooks).unwrap();
+    std::fs::write(hooks.join("post-commit"), OWN_HOOK).unwrap();
+    tmp
+}
+
+fn status(dir: &Path) -> Vec<Value> {
+    let output = engram(dir)
+        .args(["--format", "json", "hooks", "status"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn flags(hook: &Value) -> [bool; 3] {
+    ["installed", "managed", "backup"].map(|key| hook[key].as_bool().unwrap())
+}
+
+#[test]
+fn test_install_status_uninstall() {
+    let repo = repo_with_own_hook();
+    let hooks = repo.path().join(".git/hooks");
+    assert!(!hooks.join("prepare-commit-msg").exists());
+
+    let before = status(repo.path());
+    assert_eq!(before[0]["name"], "prepare-commit-msg");
+    assert_eq!(flags(&before[0]), [false, false, false]);
+    assert_eq!(before[1]["name"], "post-commit");
+    assert_eq!(flags(&before[1]), [true, false, false]);
+
+    engram(repo.path())
+        .args(["hooks", "install"])
+        .assert()
+        .success();
+    let installed = status(repo.path());
+    assert_eq!(flags(&installed[0]), [true, true, false]);
+    assert_eq!(flags(&installed[1]), [true, true, true]);
+    engram(repo.path())
+        .args(["hooks", "status"])
+        .assert()
+        .success()
+        .stdout(contains("prepare-commit-msg   installed (engram)\n"))
+        .stdout(contains(
+            "post-commit          installed (engram), backup in post-commit.pre-engram\n",
+        ));
+
+    // Installing again keeps the backup of the user's hook
+    engram(repo.path())
+        .args(["hooks", "install"])
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(hooks.join("post-commit.pre-engram")).unwrap(),
+        OWN_HOOK
+    );
+
+    engram(repo.path())
+        .args(["hooks", "uninstall"])
+        .assert()
+        .success();
+    assert_eq!(status(repo.path()), before);
+    assert_eq!(
+        std::fs::read_to_string(hooks.join("post-commit")).unwrap(),
+        OWN_HOOK
+    );
+    assert!(!hooks.join("post-commit.pre-engram").exists());
+}
+
+#[test]
+fn test_init_installs_hooks_by_default() {
+    let repo = common::init_repo();
+    let status = status(repo.path());
+    assert!(status.iter().all(|hook| flags(hook) == [true, true, false]));
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+#[test]
+fn test_import_directory_continues_past_corrupt_file() {
+    let repo = init_repo();
+    let sessions = repo.path().join("exports/nested");
+    std::fs::create_dir_all(&sessions).unwrap();
+    std::fs::write(sessions.join("one.jsonl"), claude_session("Add login")).unwrap();
+    std::fs::write(sessions.join("two.jsonl"), claude_session("Fix logout")).unwrap();
+    std::fs::write(sessions.join("broken.jsonl"), "{not json\n").unwrap();
+
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "exports"])
+        .assert()
+        .success()
+        .stdout(contains("Imported 2, updated 0, skipped 0, errored 1."));
+
+    // Re-importing the same directory dedupes every valid file
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "exports"])
+        .assert()
+        .success()
+        .stdout(contains("Imported 0, updated 0, skipped 2, errored 1."));
+}
+
+#[test]
+fn test_import_glob_updates_changed_source() {
+    let repo = init_repo();
+    let session = repo.path().join("session.jsonl");
+    std::fs::write(&session, claude_session("Add login")).unwrap();
+
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "*.jsonl"])
+        .assert()
+        .success()
+        .stdout(contains("Imported 1, updated 0"));
+
+    // The session file grew: the stale engram is replaced, not duplicated
+    let grown = format!("{}{}", claude_session("Add login"), claude_session("More"));
+    std::fs::write(&session, grown).unwrap();
+
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "*.jsonl"])
+        .assert()
+        .success()
+        .stdout(contains("Imported 0, updated 1"));
+
+    let output = engram(repo.path())
+        .args(["--format", "json", "log", "--all"])
+        .output()
+        .unwrap();
+    let list: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_import_fails_when_everything_errors() {
+    let repo = init_repo();
+    std::fs::write(repo.path().join("bad.jsonl"), "garbage\n").unwrap();
+
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "bad.jsonl"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_dry_run_json_preview_writes_nothing() {
+    let repo = init_repo();
+    let dir = repo.path().join("exports");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.jsonl"), claude_session("Add login")).unwrap();
+    std::fs::write(dir.join("b.jsonl"), "{broken\n").unwrap();
+
+    // Import one file for real so the preview reports it as a duplicate
+    std::fs::write(repo.path().join("seen.jsonl"), claude_session("Add login")).unwrap();
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "seen.jsonl"])
+        .assert()
+        .success();
+    let refs_before = git2::Repository::open(repo.path())
+        .unwrap()
+        .references_glob("refs/engrams/*/*")
+        .unwrap()
+        .count();
+
+    let output = engram(repo.path())
+        .args([
+            "--format",
+            "json",
+            "import",
+            "--dry-run",
+            "--from",
+            "claude-code",
+        ])
+        .arg("exports")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let preview: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(preview["dry_run"], true);
+    let sources = preview["sources"].as_array().unwrap();
+    assert_eq!(sources.len(), 2);
+
+    let good = &sources[0];
+    assert_eq!(good["format"], "claude-code");
+    assert_eq!(good["session_count"], 1);
+    assert!(good["error"].is_null());
+    let session = &good["sessions"][0];
+    assert_eq!(session["action"], "skip");
+    assert!(session["existing_id"].is_string());
+    assert_eq!(session["transcript_entries"], 2);
+    assert_eq!(session["total_tokens"], 120);
+    assert!(session["started_at"].is_string());
+
+    assert!(sources[1]["error"].is_string());
+    assert_eq!(preview["totals"]["skip"], 1);
+    assert_eq!(preview["totals"]["error"], 1);
+
+    let refs_after = git2::Repository::open(repo.path())
+        .unwrap()
+        .references_glob("refs/engrams/*/*")
+        .unwrap()
+        .count();
+    assert_eq!(refs_before, refs_after);
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use std::path::Path;
+
+use common::history::{assert_golden, repo_with_history, stdout_with_placeholders};
+
+/// Run `engram --format markdown <args>`, with engram IDs replaced by
+/// placeholders.
+fn markdown(dir: &Path, ids: &[String], args: &[&str]) -> String {
+    let args: Vec<_> = ["--format", "markdown"]
+        .iter()
+        .chain(args)
+        .copied()
+        .collect();
+    stdout_with_placeholders(dir, ids, &args)
+}
+
+#[test]
+fn test_markdown_golden_files() {
+    let (dir, ids) = repo_with_history();
+
+    assert_golden("log.md", &markdown(dir.path(), &ids, &["log"]));
+    assert_golden("show.md", &markdown(dir.path(), &ids, &["show", &ids[0]]));
+    assert_golden(
+        "search.md",
+        &markdown(dir.path(), &ids, &["search", "cache", "--sort", "oldest"]),
+    );
+    assert_golden(
+        "trace.md",
+        &markdown(dir.path(), &ids, &["trace", "src/cache.rs"]),
+    );
+}
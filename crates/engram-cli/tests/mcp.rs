@@ -0,0 +1,15 @@
+mod common;
+
+use common::{engram, init_repo};
+use predicates::str::contains;
+
+#[test]
+fn test_mcp_http_refuses_network_bind_without_token() {
+    let repo = init_repo();
+    engram(repo.path())
+        .args(["mcp", "--http", "0.0.0.0:0"])
+        .env_remove("ENGRAM_MCP_TOKEN")
+        .assert()
+        .failure()
+        .stderr(contains("Refusing to serve on 0.0.0.0:0 without a token"));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::path::Path;
+
+use common::{engram, init_repo};
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// A Claude Code session that writes src/cache.rs.
+fn session(prompt: &str, time: &str) -> String {
+    format!(
+        r#"{{"type":"user","uuid":"u1","timestamp":"2026-01-15T{time}:00Z","message":{{"role":"user","content":"{prompt}"}}}}
+{{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2026-01-15T{time}:05Z","message":{{"role":"assistant","content":[{{"type":"text","text":"Updating the cache."}},{{"type":"tool_use","id":"t1","name":"Write","input":{{"file_path":"src/cache.rs","content":"// cache"}}}}],"model":"claude-sonnet-4-5","usage":{{"input_tokens":1000,"output_tokens":200}}}}}}
+"#
+    )
+}
+
+fn repo_with_history() -> TempDir {
+    let dir = init_repo();
+    for (i, (prompt, time)) in [
+        ("Add a response cache", "10:00"),
+        ("Tune cache eviction", "11:00"),
+        ("Make the cache size configurable", "12:00"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let file = format!("session{i}.jsonl");
+        std::fs::write(dir.path().join(&file), session(prompt, time)).unwrap();
+        engram(dir.path())
+            .args(["import", "--from", "claude-code", &file])
+            .assert()
+            .success();
+    }
+    dir
+}
+
+/// The JSON values `engram --format ndjson <args>` prints.
+fn values(dir: &Path, args: &[&str]) -> (Vec<Value>, String) {
+    let output = engram(dir)
+        .args(["--format", "ndjson"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let records: Vec<Value> = serde_json::Deserializer::from_slice(&output.stdout)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    (records, String::from_utf8(output.stdout).unwrap())
+}
+
+/// The records `engram --format ndjson <args>` prints, checking there is
+/// exactly one per line.
+fn records(dir: &Path, args: &[&str]) -> Vec<Value> {
+    let (records, stdout) = values(dir, args);
+    assert_eq!(records.len(), stdout.lines().count());
+    records
+}
+
+fn kinds(records: &[Value]) -> Vec<&str> {
+    records
+        .iter()
+        .map(|r| r["kind"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_log_ndjson() {
+    let repo = repo_with_history();
+
+    let log = records(repo.path(), &["log"]);
+    assert_eq!(kinds(&log), ["engram"; 3]);
+    assert_eq!(log[0]["summary"], "Make the cache size configurable");
+    assert!(log[0]["id"].is_string());
+
+    let log = records(repo.path(), &["log", "-n", "2", "--by-agent"]);
+    assert_eq!(log.len(), 2);
+}
+
+#[test]
+fn test_search_trace_and_blame_ndjson() {
+    let repo = repo_with_history();
+
+    let found = records(repo.path(), &["search", "cache", "--facets"]);
+    assert_eq!(
+        kinds(&found),
+        ["search_result", "search_result", "search_result", "facets"]
+    );
+    assert!(found[0]["score"].is_number());
+    assert!(found[0]["id"].is_string());
+    assert_eq!(found[3]["agents"][0][0], "claude-code");
+
+    let trace = records(repo.path(), &["trace", "src/cache.rs"]);
+    assert_eq!(kinds(&trace), ["trace_entry"; 3]);
+    assert_eq!(trace[0]["path"], "src/cache.rs");
+    assert_eq!(trace[0]["summary"], "Add a response cache");
+
+    let blame = records(repo.path(), &["blame", "src/cache.rs", "-n", "2"]);
+    assert_eq!(kinds(&blame), ["blame_entry"; 2]);
+    assert_eq!(blame[0]["summary"], "Make the cache size configurable");
+}
+
+#[test]
+fn test_other_commands_treat_ndjson_as_json() {
+    let repo = repo_with_history();
+    let (stats, _) = values(repo.path(), &["stats"]);
+    assert_eq!(stats.len(), 1);
+    assert!(stats[0].get("kind").is_none());
+}
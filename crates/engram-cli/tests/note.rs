@@ -0,0 +1,103 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+#[test]
+fn test_note_add_and_list() {
+    let repo = init_repo();
+    std::fs::write(
+        repo.path().join("session.jsonl"),
+        claude_session("Add caching"),
+    )
+    .unwrap();
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+
+    engram(repo.path())
+        .args(["note", "list", "HEAD"])
+        .assert()
+        .success()
+        .stderr(contains("No notes"));
+
+    engram(repo.path())
+        .args(["note", "add", "HEAD", "Reverted in a later session"])
+        .assert()
+        .success()
+        .stderr(contains("Added note"));
+    engram(repo.path())
+        .args(["note", "add", "HEAD", "Back with a TTL", "--author", "bot"])
+        .assert()
+        .success();
+    engram(repo.path())
+        .args(["note", "add", "HEAD", " "])
+        .assert()
+        .failure()
+        .stderr(contains("empty"));
+
+    engram(repo.path())
+        .args(["note", "list", "HEAD"])
+        .assert()
+        .success()
+        .stdout(contains("Test User: Reverted in a later session"))
+        .stdout(contains("bot: Back with a TTL"));
+
+    let output = engram(repo.path())
+        .args(["--format", "json", "note", "list", "HEAD"])
+        .output()
+        .unwrap();
+    let notes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let texts: Vec<_> = notes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(texts, ["Reverted in a later session", "Back with a TTL"]);
+
+    // The engram itself still reads back
+    engram(repo.path())
+        .args(["show", "HEAD"])
+        .assert()
+        .success()
+        .stdout(contains("Add caching"));
+}
+
+#[test]
+fn test_fetch_keeps_unpushed_notes() {
+    let repo = init_repo();
+    std::fs::write(
+        repo.path().join("session.jsonl"),
+        claude_session("Add caching"),
+    )
+    .unwrap();
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+    let bare = tempfile::TempDir::new().unwrap();
+    git2::Repository::init_bare(bare.path()).unwrap();
+    let url = format!("file://{}", bare.path().display());
+    git2::Repository::open(repo.path())
+        .unwrap()
+        .remote("origin", &url)
+        .unwrap();
+    engram(repo.path()).arg("push").assert().success();
+
+    engram(repo.path())
+        .args(["note", "add", "HEAD", "local note A"])
+        .assert()
+        .success();
+    engram(repo.path())
+        .args(["fetch", "origin"])
+        .assert()
+        .success()
+        .stderr(contains("(0 new, 0 updated, 1 unchanged)"));
+    engram(repo.path())
+        .args(["note", "list", "HEAD"])
+        .assert()
+        .success()
+        .stdout(contains("local note A"));
+}
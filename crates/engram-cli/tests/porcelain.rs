@@ -0,0 +1,64 @@
+mod common;
+
+use common::history::{assert_golden, repo_with_history, stdout_with_placeholders};
+use common::{claude_session, engram, init_repo};
+
+#[test]
+fn test_porcelain_golden_files() {
+    let (dir, ids) = repo_with_history();
+    let dir = dir.path();
+
+    assert_golden(
+        "log.porcelain",
+        &stdout_with_placeholders(dir, &ids, &["log", "--porcelain"]),
+    );
+    assert_golden(
+        "show.porcelain",
+        &stdout_with_placeholders(dir, &ids, &["show", &ids[0], "--porcelain"]),
+    );
+    assert_golden(
+        "trace.porcelain",
+        &stdout_with_placeholders(dir, &ids, &["trace", "src/cache.rs", "--porcelain"]),
+    );
+
+    // --format and --color don't change porcelain output
+    for args in [
+        ["--format", "json"],
+        ["--format", "markdown"],
+        ["--color", "always"],
+    ] {
+        let log =
+            stdout_with_placeholders(dir, &ids, &[&args[..], &["log", "--porcelain"]].concat());
+        assert_golden("log.porcelain", &log);
+    }
+}
+
+#[test]
+fn test_quiet_import_prints_only_new_ids() {
+    let repo = init_repo();
+    std::fs::write(repo.path().join("a.jsonl"), claude_session("Add a cache")).unwrap();
+
+    let output = engram(repo.path())
+        .args(["-q", "import", "--from", "claude-code", "a.jsonl"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(output.stderr.is_empty(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let id = stdout.trim_end();
+    assert_eq!(id.len(), 32, "{stdout}");
+    assert_eq!(stdout, format!("{id}\n"));
+
+    // A duplicate creates nothing, so prints nothing
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "a.jsonl", "--quiet"])
+        .assert()
+        .success()
+        .stdout("");
+
+    engram(repo.path())
+        .args(["-q", "search", "cache"])
+        .assert()
+        .success()
+        .stderr("");
+}
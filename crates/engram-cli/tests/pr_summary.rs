@@ -0,0 +1,94 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+/// Commit `file` on top of `parent` with `message`, moving `refname`.
+fn commit(
+    repo: &git2::Repository,
+    refname: &str,
+    parent: Option<&git2::Commit>,
+    file: &str,
+    message: &str,
+) -> git2::Oid {
+    std::fs::write(repo.workdir().unwrap().join(file), message).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new(file)).unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = repo.signature().unwrap();
+    let parents: Vec<_> = parent.into_iter().collect();
+    repo.commit(Some(refname), &sig, &sig, message, &tree, &parents)
+        .unwrap()
+}
+
+#[test]
+fn test_pr_summary_of_branch_with_trailer() {
+    let dir = init_repo();
+    std::fs::write(
+        dir.path().join("session.jsonl"),
+        claude_session("Add caching"),
+    )
+    .unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+
+    let repo = git2::Repository::open(dir.path()).unwrap();
+    let engram_ref = repo
+        .references_glob("refs/engrams/*")
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let id = engram_ref
+        .name()
+        .unwrap()
+        .rsplit('/')
+        .next()
+        .unwrap()
+        .to_string();
+
+    let base = commit(&repo, "HEAD", None, "README.md", "Initial commit");
+    let base = repo.find_commit(base).unwrap();
+    commit(
+        &repo,
+        "refs/heads/feature",
+        Some(&base),
+        "cache.rs",
+        &format!("Add caching\n\nEngram-Id: {id}\n"),
+    );
+    let range = format!("{}..feature", base.id());
+
+    engram(dir.path())
+        .args([
+            "--format",
+            "markdown",
+            "pr-summary",
+            &range,
+            "--out",
+            "PR.md",
+        ])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(contains("Wrote PR.md"));
+    let markdown = std::fs::read_to_string(dir.path().join("PR.md")).unwrap();
+    assert!(markdown.contains("## Reasoning"), "{markdown}");
+    assert!(markdown.contains("## Economics"), "{markdown}");
+    assert!(markdown.contains("- **Commits:** 1"), "{markdown}");
+
+    engram(dir.path())
+        .args(["pr-summary", &range])
+        .assert()
+        .success()
+        .stdout(contains("PR Summary:"))
+        .stdout(contains("Tokens: 120"));
+
+    let output = engram(dir.path())
+        .args(["--format", "json", "pr-summary", &range])
+        .output()
+        .unwrap();
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["engrams"][0]["id"], id.as_str());
+}
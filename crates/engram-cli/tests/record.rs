@@ -0,0 +1,114 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{engram, init_repo};
+use predicates::str::contains;
+
+#[test]
+fn test_record_timeout_exit_code_and_tag() {
+    let repo = init_repo();
+
+    engram(repo.path())
+        .args(["record", "--timeout", "1s", "--", "sleep", "60"])
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .code(124)
+        .stderr(contains("timed out"));
+
+    engram(repo.path())
+        .args(["--format", "json", "show", "HEAD"])
+        .assert()
+        .success()
+        .stdout(contains("timed_out"));
+}
+
+#[test]
+fn test_record_exits_with_the_command_exit_code() {
+    let repo = init_repo();
+
+    engram(repo.path())
+        .args(["record", "--", "sh", "-c", "echo out; exit 3"])
+        .assert()
+        .code(3)
+        .stderr(contains("Exit code: 3"));
+
+    // Killed by SIGTERM, reported as a shell would: 128 + 15
+    engram(repo.path())
+        .args(["record", "--", "sh", "-c", "kill -TERM $$"])
+        .assert()
+        .code(143)
+        .stderr(contains("Exit code: 143 (signal 15)"));
+
+    engram(repo.path())
+        .args(["record", "--exit-zero", "--", "sh", "-c", "exit 3"])
+        .assert()
+        .success();
+
+    // Both failed sessions were still stored
+    let output = engram(repo.path())
+        .args(["--format", "json", "log", "--all"])
+        .output()
+        .unwrap();
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(log.as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn test_record_stdout_is_the_command_output() {
+    let repo = init_repo();
+    let output = engram(repo.path())
+        .args(["record", "--", "sh", "-c", "echo only this"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+        "only this"
+    );
+}
+
+#[test]
+fn test_record_quiet_prints_the_id_last() {
+    let repo = init_repo();
+    let output = engram(repo.path())
+        .args(["--quiet", "record", "--", "sh", "-c", "echo hello"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let id = stdout.lines().last().unwrap();
+    assert!(stdout.starts_with("hello"), "{stdout}");
+    assert_eq!(id.len(), 32, "{stdout}");
+
+    engram(repo.path())
+        .args(["show", id])
+        .assert()
+        .success()
+        .stdout(contains("echo hello"));
+}
+
+#[test]
+fn test_record_rejects_unusable_durations() {
+    let repo = init_repo();
+
+    for gap in ["inf", "NaN", "-1", "1e300"] {
+        engram(repo.path())
+            .args(["record", &format!("--idle-gap={gap}"), "--", "true"])
+            .assert()
+            .code(2)
+            .stderr(contains("--idle-gap"));
+    }
+    engram(repo.path())
+        .args([
+            "record",
+            "--timeout",
+            "99999999999999999999999s",
+            "--",
+            "true",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("too long"));
+}
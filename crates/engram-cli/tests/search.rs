@@ -0,0 +1,152 @@
+mod common;
+
+use std::path::Path;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+/// An engram repo holding one imported session, indexed unless `indexed` is false.
+fn repo_with_session(prompt: &str, indexed: bool) -> TempDir {
+    let repo = init_repo();
+    std::fs::write(repo.path().join("session.jsonl"), claude_session(prompt)).unwrap();
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+    if indexed {
+        engram(repo.path()).arg("reindex").assert().success();
+    }
+    repo
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn test_search_repeated_repo_flags() {
+    let api = repo_with_session("Add a rate limiter to the API", true);
+    let web = repo_with_session("Rate limiter for uploads", true);
+    let stale = repo_with_session("Rate limiter again", false);
+    let elsewhere = TempDir::new().unwrap();
+
+    engram(elsewhere.path())
+        .args(["search", "limiter"])
+        .args(["--repo", path_str(api.path())])
+        .args(["--repo", path_str(web.path())])
+        .args(["--repo", path_str(stale.path())])
+        .assert()
+        .success()
+        .stdout(contains(format!("in {}", api.path().display())))
+        .stdout(contains(format!("in {}", web.path().display())))
+        .stderr(contains("Found 2 result(s)"))
+        .stderr(contains(format!(
+            "Skipped {}: no search index",
+            stale.path().display()
+        )));
+}
+
+#[test]
+fn test_search_all_repos_reads_config() {
+    let api = repo_with_session("Add a rate limiter to the API", true);
+    let home = TempDir::new().unwrap();
+
+    engram(home.path())
+        .args(["search", "--all-repos", "limiter"])
+        .assert()
+        .failure()
+        .stderr(contains("No repositories configured"));
+
+    let config = home.path().join(".config/engram");
+    std::fs::create_dir_all(&config).unwrap();
+    std::fs::write(
+        config.join("repos.toml"),
+        format!("repos = [{:?}]\n", path_str(api.path())),
+    )
+    .unwrap();
+
+    let output = engram(home.path())
+        .args(["--format", "json", "search", "--all-repos", "limiter"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["total"], 1);
+    assert_eq!(json["results"][0]["repo"], path_str(api.path()));
+}
+
+#[test]
+fn test_index_dir_env_moves_index_out_of_repo() {
+    let repo = repo_with_session("Add a rate limiter to the API", false);
+    let cache = TempDir::new().unwrap();
+
+    engram(repo.path())
+        .env("ENGRAM_INDEX_DIR", cache.path())
+        .arg("reindex")
+        .assert()
+        .success()
+        .stderr(contains(format!(
+            "Search index: {}",
+            cache.path().display()
+        )));
+
+    let indexes: Vec<_> = std::fs::read_dir(cache.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(indexes.len(), 1);
+    assert!(indexes[0].join("meta.json").exists());
+    assert!(!repo.path().join(".git/engram-index").exists());
+
+    engram(repo.path())
+        .env("ENGRAM_INDEX_DIR", cache.path())
+        .args(["search", "limiter"])
+        .assert()
+        .success()
+        .stderr(contains("Found 1 result(s)"));
+    assert!(!repo.path().join(".git/engram-index").exists());
+}
+
+#[test]
+fn test_search_without_usable_index_scans_engrams() {
+    let repo = repo_with_session("Add a rate limiter to the API", true);
+    let dir = repo.path();
+    std::fs::write(
+        dir.join("other.jsonl"),
+        claude_session("Speed up the cache"),
+    )
+    .unwrap();
+    engram(dir)
+        .args(["import", "--from", "claude-code", "other.jsonl"])
+        .assert()
+        .success();
+    let index = dir.join(".git/engram-index");
+    std::fs::remove_file(index.join("meta.json")).unwrap();
+
+    let output = engram(dir)
+        .args(["--format", "json", "search", "limiter"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("engram reindex"), "{stderr}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let summaries: Vec<_> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["summary"].as_str().unwrap())
+        .collect();
+    assert_eq!(summaries, ["Add a rate limiter to the API"]);
+    // The broken index was left alone
+    assert!(!index.join("meta.json").exists());
+
+    engram(dir)
+        .args(["search", "cache", "--rebuild-index"])
+        .assert()
+        .success()
+        .stderr(contains("Rebuilt the search index."))
+        .stderr(contains("Found 1 result(s)"));
+    assert!(index.join("meta.json").exists());
+}
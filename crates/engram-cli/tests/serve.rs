@@ -0,0 +1,68 @@
+#![cfg(feature = "serve")]
+
+mod common;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::Stdio;
+
+use common::{engram, init_repo};
+use engram_core::model::EngramData;
+use engram_core::storage::GitStorage;
+use predicates::str::contains;
+
+#[test]
+fn test_serve_refuses_network_bind_without_token() {
+    let repo = init_repo();
+    engram(repo.path())
+        .args(["serve", "--bind", "0.0.0.0:0"])
+        .env_remove("ENGRAM_SERVE_TOKEN")
+        .assert()
+        .failure()
+        .stderr(contains("Refusing to serve on 0.0.0.0:0 without a token"));
+}
+
+#[test]
+fn test_serve_bare_repository() {
+    let bare = tempfile::TempDir::new().unwrap();
+    git2::Repository::init_bare(bare.path()).unwrap();
+    let storage = GitStorage::open(bare.path()).unwrap();
+    storage.init().unwrap();
+    let id = storage
+        .create(&EngramData::builder().summary("Add caching").build())
+        .unwrap();
+
+    let mut server = std::process::Command::new(assert_cmd::cargo::cargo_bin("engram"))
+        .args(["serve", "--bind", "127.0.0.1:0"])
+        .current_dir(bare.path())
+        .env("HOME", bare.path())
+        .env_remove("ENGRAM_SERVE_TOKEN")
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = BufReader::new(server.stderr.take().unwrap());
+    let addr = loop {
+        let mut line = String::new();
+        assert!(stderr.read_line(&mut line).unwrap() > 0, "server exited");
+        if let Some(addr) = line
+            .trim()
+            .strip_prefix("Serving the engram API on http://")
+        {
+            break addr.to_string();
+        }
+    };
+
+    let mut stream = TcpStream::connect(&addr).unwrap();
+    write!(
+        stream,
+        "GET /engrams HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains(id.as_str()), "{response}");
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use common::{engram, init_repo};
+use engram_core::model::{EngramData, Manifest};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+/// An engram whose transcript has every role and kind of content:
+/// 0 user text, 1 assistant thinking, 2 assistant text, 3 assistant tool
+/// call with a long input, 4 tool result, 5 assistant text.
+fn repo_with_transcript() -> TempDir {
+    let dir = init_repo();
+    let long = "x".repeat(3000);
+    let session = format!(
+        r#"{{"type":"user","uuid":"u1","timestamp":"2026-01-15T10:00:00Z","message":{{"role":"user","content":"Add caching"}}}}
+{{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2026-01-15T10:00:05Z","message":{{"role":"assistant","content":[{{"type":"thinking","thinking":"An LRU would be simplest."}},{{"type":"text","text":"Writing the cache."}},{{"type":"tool_use","id":"t1","name":"Write","input":{{"file_path":"src/cache.rs","content":"{long}"}}}}],"model":"claude-sonnet-4-5"}}}}
+{{"type":"user","uuid":"u2","parentUuid":"a1","timestamp":"2026-01-15T10:00:06Z","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"t1","content":"File written"}}]}}}}
+{{"type":"assistant","uuid":"a2","parentUuid":"u2","timestamp":"2026-01-15T10:00:09Z","message":{{"role":"assistant","content":[{{"type":"text","text":"Done.\nThe cache is in src/cache.rs."}}],"model":"claude-sonnet-4-5"}}}}
+"#
+    );
+    std::fs::write(dir.path().join("session.jsonl"), session).unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+    dir
+}
+
+#[test]
+fn test_show_transcript_entries() {
+    let dir = repo_with_transcript();
+
+    let output = engram(dir.path())
+        .args(["show", "HEAD", "--transcript"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        text.starts_with("#0 2026-01-15 10:00:00 user: Add caching\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("#1 2026-01-15 10:00:05 assistant: (thinking) An LRU would be simplest.\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("assistant: (tool call) Write\n    {\n      \"content\": \"xxx"),
+        "{text}"
+    );
+    assert!(text.contains("more chars]"), "{text}");
+    assert!(!text.contains(&"x".repeat(2500)), "{text}");
+    assert!(
+        text.contains("tool: (tool result) File written\n"),
+        "{text}"
+    );
+    assert!(
+        text.ends_with("assistant: Done.\n    The cache is in src/cache.rs.\n"),
+        "{text}"
+    );
+}
+
+#[test]
+fn test_show_transcript_paging_and_roles() {
+    let dir = repo_with_transcript();
+
+    let output = engram(dir.path())
+        .args(["show", "HEAD", "--transcript", "--role", "assistant"])
+        .args(["--from", "1", "--count", "2"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    let numbers: Vec<_> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix('#'))
+        .map(|line| line.split(' ').next().unwrap())
+        .collect();
+    assert_eq!(numbers, ["2", "3"], "{text}");
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("entries 1..3 of 4; continue with --from 3"));
+
+    let output = engram(dir.path())
+        .args(["--format", "json", "show", "HEAD", "--transcript"])
+        .args(["--role", "user,tool"])
+        .output()
+        .unwrap();
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let roles: Vec<_> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(roles, ["user", "tool"]);
+
+    let output = engram(dir.path())
+        .args(["show", "HEAD", "--transcript", "--raw-jsonl", "--from", "4"])
+        .output()
+        .unwrap();
+    let text = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["content"]["type"], "tool_result");
+
+    engram(dir.path())
+        .args(["show", "HEAD", "--transcript", "--role", "robot"])
+        .assert()
+        .failure()
+        .stderr(contains("unknown role 'robot'"));
+    engram(dir.path())
+        .args(["show", "HEAD", "--count", "1"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_show_json_is_full_engram() {
+    let dir = repo_with_transcript();
+
+    let output = engram(dir.path())
+        .args(["--format", "json", "show", "HEAD"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let doc: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(doc["schema_version"], 1);
+    let data: EngramData = serde_json::from_value(doc).unwrap();
+    assert_eq!(data.transcript.entries.len(), 6);
+    assert_eq!(data.intent.original_request, "Add caching");
+    assert_eq!(data.operations.file_changes[0].path, "src/cache.rs");
+
+    let output = engram(dir.path())
+        .args(["--format", "json", "show", "HEAD", "--fields", "manifest"])
+        .output()
+        .unwrap();
+    let manifest: Manifest = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(manifest.id.to_string(), data.manifest.id.to_string());
+
+    let output = engram(dir.path())
+        .args([
+            "--format",
+            "json",
+            "show",
+            "HEAD",
+            "--fields",
+            "intent,lineage",
+        ])
+        .output()
+        .unwrap();
+    let doc: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut keys: Vec<_> = doc.as_object().unwrap().keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["intent", "lineage", "schema_version"]);
+}
+
+#[test]
+fn test_show_failed_tool_calls() {
+    let dir = init_repo();
+    let session = r#"{"type":"user","uuid":"u1","timestamp":"2026-01-15T10:00:00Z","message":{"role":"user","content":"Run the tests"}}
+{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2026-01-15T10:00:02Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test"}}],"model":"claude-sonnet-4-5"}}
+{"type":"user","uuid":"u2","parentUuid":"a1","timestamp":"2026-01-15T10:00:14Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"error[E0425]: cannot find value `cache` in this scope\n --> src/lib.rs:3:5","is_error":true}]}}
+{"type":"assistant","uuid":"a2","parentUuid":"u2","timestamp":"2026-01-15T10:00:15Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"t2","name":"Read","input":{"file_path":"src/lib.rs"}}],"model":"claude-sonnet-4-5"}}
+{"type":"user","uuid":"u3","parentUuid":"a2","timestamp":"2026-01-15T10:00:15.400Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t2","content":"pub fn get() {}"}]}}
+"#;
+    std::fs::write(dir.path().join("session.jsonl"), session).unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+
+    engram(dir.path())
+        .args(["show", "HEAD"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "  Bash 12s [ERROR]\n    error[E0425]: cannot find value `cache` in this scope\n  Read 400ms\n",
+        ));
+
+    let output = engram(dir.path())
+        .args(["--format", "json", "show", "HEAD"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let data: EngramData = serde_json::from_slice(&output.stdout).unwrap();
+    let bash = &data.operations.tool_calls[0];
+    assert!(bash.is_error);
+    assert_eq!(bash.duration_ms, Some(12_000));
+    assert!(bash
+        .output_summary
+        .as_deref()
+        .unwrap()
+        .starts_with("error[E0425]"));
+    assert!(data.operations.has_errors());
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use std::path::Path;
+
+use common::{claude_session, engram, init_repo};
+use engram_core::model::{CaptureMode, EngramId};
+use engram_core::storage::GitStorage;
+use predicates::prelude::*;
+use predicates::str::contains;
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// Four engrams:
+///
+/// | agent       | model  | mode    | tags       | created    | tokens |
+/// |-------------|--------|---------|------------|------------|--------|
+/// | claude-code | sonnet | Import  | auth, perf | 2026-01-05 | 100    |
+/// | claude-code | opus   | Wrapper | auth       | 2026-01-06 | 200    |
+/// | cursor      | sonnet | Import  |            | 2026-01-14 | 300    |
+/// | aider       |        | Sdk     | perf       | 2026-02-20 | 400    |
+fn seeded_repo() -> TempDir {
+    let dir = init_repo();
+    std::fs::write(dir.path().join("s.jsonl"), claude_session("Seed")).unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "s.jsonl"])
+        .assert()
+        .success();
+
+    let storage = GitStorage::open(dir.path()).unwrap();
+    let imported = storage.list(&Default::default()).unwrap().remove(0);
+    let mut data = storage.read(imported.id.as_str()).unwrap();
+    storage.delete(imported.id.as_str()).unwrap();
+    for (agent, model, mode, tags, created, tokens) in [
+        (
+            "claude-code",
+            Some("sonnet"),
+            CaptureMode::Import,
+            &["auth", "perf"][..],
+            "2026-01-05T10:00:00Z",
+            100,
+        ),
+        (
+            "claude-code",
+            Some("opus"),
+            CaptureMode::Wrapper,
+            &["auth"],
+            "2026-01-06T10:00:00Z",
+            200,
+        ),
+        (
+            "cursor",
+            Some("sonnet"),
+            CaptureMode::Import,
+            &[],
+            "2026-01-14T10:00:00Z",
+            300,
+        ),
+        (
+            "aider",
+            None,
+            CaptureMode::Sdk,
+            &["perf"],
+            "2026-02-20T10:00:00Z",
+            400,
+        ),
+    ] {
+        data.manifest.id = EngramId::new();
+        data.manifest.agent.name = agent.to_string();
+        data.manifest.agent.model = model.map(str::to_string);
+        data.manifest.capture_mode = mode;
+        data.manifest.tags = tags.iter().map(|t| t.to_string()).collect();
+        data.manifest.created_at = created.parse().unwrap();
+        data.manifest.token_usage.total_tokens = tokens;
+        data.manifest.source_hash = None;
+        storage.create(&data).unwrap();
+    }
+    dir
+}
+
+fn stats_json(dir: &Path, args: &[&str]) -> Value {
+    let output = engram(dir)
+        .args(["--format", "json", "stats"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// `(key, engrams)` for each group of `engram stats --by <by> <args>`.
+fn groups(dir: &Path, by: &str, args: &[&str]) -> Vec<(String, u64)> {
+    let stats = stats_json(dir, &[&["--by", by], args].concat());
+    assert_eq!(stats["by"], by);
+    stats["groups"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|g| {
+            (
+                g["key"].as_str().unwrap().to_string(),
+                g["engrams"].as_u64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+fn pairs(expected: &[(&str, u64)]) -> Vec<(String, u64)> {
+    expected.iter().map(|(k, n)| (k.to_string(), *n)).collect()
+}
+
+#[test]
+fn test_stats_by_each_dimension() {
+    let repo = seeded_repo();
+    let dir = repo.path();
+
+    assert_eq!(
+        groups(dir, "agent", &[]),
+        pairs(&[("claude-code", 2), ("aider", 1), ("cursor", 1)])
+    );
+    assert_eq!(
+        groups(dir, "model", &[]),
+        pairs(&[("sonnet", 2), ("opus", 1), ("unknown", 1)])
+    );
+    assert_eq!(
+        groups(dir, "capture-mode", &[]),
+        pairs(&[("Import", 2), ("Sdk", 1), ("Wrapper", 1)])
+    );
+    assert_eq!(
+        groups(dir, "tag", &[]),
+        pairs(&[("auth", 2), ("perf", 2), ("untagged", 1)])
+    );
+
+    let stats = stats_json(dir, &["--by", "agent"]);
+    assert_eq!(stats["groups"][0]["total_tokens"], 300);
+
+    engram(dir)
+        .args(["stats", "--by", "branch"])
+        .assert()
+        .failure()
+        .stderr(contains("Use one of: agent, model, capture-mode, tag"));
+}
+
+#[test]
+fn test_stats_filters_and_histogram() {
+    let repo = seeded_repo();
+    let dir = repo.path();
+
+    let january = ["--since", "2026-01-01", "--until", "2026-01-31"];
+    let stats = stats_json(dir, &january);
+    assert_eq!(stats["total_engrams"], 3);
+    assert_eq!(stats["total_tokens"], 600);
+    let weeks: Vec<_> = stats["weeks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|w| (w["start"].as_str().unwrap(), w["engrams"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(
+        weeks,
+        [("2026-01-05T00:00:00Z", 2), ("2026-01-12T00:00:00Z", 1)]
+    );
+
+    assert_eq!(
+        groups(dir, "model", &["--agent", "claude-code"]),
+        pairs(&[("opus", 1), ("sonnet", 1)])
+    );
+    assert_eq!(
+        groups(dir, "tag", &["--since", "2026-02-01"]),
+        pairs(&[("perf", 1)])
+    );
+
+    engram(dir)
+        .args(["stats", "--by", "agent"])
+        .args(january)
+        .assert()
+        .success()
+        .stdout(contains("By Agent:\n  claude-code: 2 engrams, 300 tokens"))
+        .stdout(contains("Engrams per Week:"))
+        .stdout(contains(format!("2026-01-05  {} 2", "#".repeat(40))))
+        .stdout(contains(format!("2026-01-12  {:<40} 1", "#".repeat(20))));
+
+    // Nothing in the window: text says so, JSON stays parseable
+    engram(dir)
+        .args(["stats", "--agent", "nobody"])
+        .assert()
+        .success()
+        .stdout("No engrams found.\n");
+    assert_eq!(stats_json(dir, &["--agent", "nobody"])["total_engrams"], 0);
+}
+
+#[test]
+fn test_bare_stats_unchanged_and_csv_by() {
+    let repo = seeded_repo();
+    let dir = repo.path();
+
+    engram(dir)
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(contains("Total engrams:  4"))
+        .stdout(contains("By Capture Mode:"))
+        .stdout(contains("Engrams per Week").not());
+    let stats = stats_json(dir, &[]);
+    assert!(stats.get("weeks").is_none() && stats.get("groups").is_none());
+
+    let output = engram(dir)
+        .args(["--format", "csv", "stats", "--by", "capture-mode"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let csv = String::from_utf8(output.stdout).unwrap();
+    let mut lines = csv.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("capture-mode,engrams,input_tokens"));
+    assert!(lines.next().unwrap().starts_with("Import,2,"));
+}
@@ -0,0 +1,290 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use predicates::prelude::*;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn test_push_and_pull_report_ref_counts() {
+    let repo = init_repo();
+    std::fs::write(
+        repo.path().join("session.jsonl"),
+        claude_session("Add a rate limiter"),
+    )
+    .unwrap();
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+
+    let bare = TempDir::new().unwrap();
+    git2::Repository::init_bare(bare.path()).unwrap();
+    let url = format!("file://{}", bare.path().display());
+    git2::Repository::open(repo.path())
+        .unwrap()
+        .remote("origin", &url)
+        .unwrap();
+
+    // Not a terminal, so no progress bar
+    engram(repo.path())
+        .arg("push")
+        .assert()
+        .success()
+        .stderr(contains(
+            "Pushed 1 engram ref(s) to origin (1 new, 0 updated, 0 unchanged)",
+        ))
+        .stderr(contains("Pushing").not());
+    engram(repo.path())
+        .args(["push", "--quiet"])
+        .assert()
+        .success()
+        .stderr(contains("(0 new, 0 updated, 1 unchanged)"));
+
+    let other = init_repo();
+    git2::Repository::open(other.path())
+        .unwrap()
+        .remote("origin", &url)
+        .unwrap();
+    engram(other.path())
+        .arg("pull")
+        .assert()
+        .success()
+        .stderr(contains(
+            "Fetched 1 engram ref(s) from origin (1 new, 0 updated, 0 unchanged)",
+        ))
+        .stderr(contains("Indexed 1 new engram(s)."));
+}
+
+fn engram_refs(repo: &git2::Repository) -> Vec<String> {
+    let mut names: Vec<_> = repo
+        .references_glob("refs/engrams/*")
+        .unwrap()
+        .filter_map(|r| r.ok()?.name().map(String::from))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_push_selected_engrams_only() {
+    let repo = init_repo();
+    for (file, prompt) in [
+        ("a.jsonl", "Add a rate limiter"),
+        ("b.jsonl", "Try a spike"),
+    ] {
+        std::fs::write(repo.path().join(file), claude_session(prompt)).unwrap();
+        engram(repo.path())
+            .args(["import", "--from", "claude-code", file])
+            .assert()
+            .success();
+    }
+    let local = git2::Repository::open(repo.path()).unwrap();
+    let refs = engram_refs(&local);
+    assert_eq!(refs.len(), 2);
+    let id = refs[0].rsplit('/').next().unwrap().to_string();
+
+    let bare = TempDir::new().unwrap();
+    let remote = git2::Repository::init_bare(bare.path()).unwrap();
+    let url = format!("file://{}", bare.path().display());
+    local.remote("upstream", &url).unwrap();
+
+    engram(repo.path())
+        .args(["push", "upstream", &id[..8], "--dry-run"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", refs[0]))
+        .stderr(contains("Would push 1 engram ref(s) to upstream"));
+    assert!(engram_refs(&remote).is_empty());
+
+    engram(repo.path())
+        .args(["push", "upstream", &id[..8]])
+        .assert()
+        .success()
+        .stderr(contains("Pushed 1 engram ref(s) to upstream (1 new"));
+    assert_eq!(engram_refs(&remote), [refs[0].clone()]);
+
+    // No engram has this tag
+    engram(repo.path())
+        .args(["push", "upstream", "--tag", "pr-42"])
+        .assert()
+        .success()
+        .stderr(contains("Pushed 0 engram ref(s)"));
+    assert_eq!(engram_refs(&remote).len(), 1);
+}
+
+#[test]
+fn test_push_and_fetch_several_remotes() {
+    let repo = init_repo();
+    std::fs::write(
+        repo.path().join("session.jsonl"),
+        claude_session("Add a rate limiter"),
+    )
+    .unwrap();
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+
+    let local = git2::Repository::open(repo.path()).unwrap();
+    let github = TempDir::new().unwrap();
+    let gitea = TempDir::new().unwrap();
+    for (name, bare) in [("github", &github), ("gitea", &gitea)] {
+        git2::Repository::init_bare(bare.path()).unwrap();
+        local
+            .remote(name, &format!("file://{}", bare.path().display()))
+            .unwrap();
+    }
+    let mut config = local.config().unwrap();
+    for name in ["github", "gitea"] {
+        config
+            .set_multivar("engram.pushRemotes", "^$", name)
+            .unwrap();
+    }
+
+    // No remote named: the configured set
+    engram(repo.path())
+        .arg("push")
+        .assert()
+        .success()
+        .stderr(contains("Pushed 1 engram ref(s) to github (1 new"))
+        .stderr(contains("Pushed 1 engram ref(s) to gitea (1 new"));
+    for bare in [&github, &gitea] {
+        let remote = git2::Repository::open_bare(bare.path()).unwrap();
+        assert_eq!(engram_refs(&remote).len(), 1);
+    }
+
+    // One good mirror and one unreachable one
+    let other = init_repo();
+    let other_repo = git2::Repository::open(other.path()).unwrap();
+    other_repo
+        .remote("github", &format!("file://{}", github.path().display()))
+        .unwrap();
+    other_repo
+        .remote(
+            "broken",
+            &format!("file://{}/missing", gitea.path().display()),
+        )
+        .unwrap();
+
+    engram(other.path())
+        .args(["fetch", "--all-remotes"])
+        .assert()
+        .success()
+        .stderr(contains("broken: failed:"))
+        .stderr(contains("Fetched 1 engram ref(s) from github (1 new"));
+    engram(other.path())
+        .args(["pull", "--all-remotes", "--require-all"])
+        .assert()
+        .failure()
+        .stderr(contains("1 of 2 remotes failed: broken"));
+}
+
+#[test]
+fn test_status_compares_with_remote() {
+    let repo = init_repo();
+    for (file, prompt) in [("a.jsonl", "Add a rate limiter"), ("b.jsonl", "Fix login")] {
+        std::fs::write(repo.path().join(file), claude_session(prompt)).unwrap();
+        engram(repo.path())
+            .args(["import", "--from", "claude-code", file])
+            .assert()
+            .success();
+    }
+
+    let bare = TempDir::new().unwrap();
+    git2::Repository::init_bare(bare.path()).unwrap();
+    git2::Repository::open(repo.path())
+        .unwrap()
+        .remote("origin", &format!("file://{}", bare.path().display()))
+        .unwrap();
+
+    engram(repo.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(contains("Only local (push to send): 2"))
+        .stdout(contains("In sync: 0"));
+
+    engram(repo.path()).arg("push").assert().success();
+    engram(repo.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(contains("Up to date with origin: 2 engram(s)"));
+
+    let output = engram(repo.path())
+        .args(["--format", "json", "status"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["in_sync"], 2);
+    assert_eq!(json["local_only"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_prune_deletions_both_ways() {
+    let alice = init_repo();
+    for (file, prompt) in [("a.jsonl", "Add a rate limiter"), ("b.jsonl", "Fix login")] {
+        std::fs::write(alice.path().join(file), claude_session(prompt)).unwrap();
+        engram(alice.path())
+            .args(["import", "--from", "claude-code", file])
+            .assert()
+            .success();
+    }
+    let bare = TempDir::new().unwrap();
+    git2::Repository::init_bare(bare.path()).unwrap();
+    let url = format!("file://{}", bare.path().display());
+    let bob = init_repo();
+    for dir in [&alice, &bob] {
+        git2::Repository::open(dir.path())
+            .unwrap()
+            .remote("origin", &url)
+            .unwrap();
+    }
+    engram(alice.path()).arg("push").assert().success();
+    engram(bob.path()).arg("pull").assert().success();
+    engram(bob.path())
+        .args(["search", "limiter"])
+        .assert()
+        .success()
+        .stderr(contains("Found 1 result(s)"));
+
+    // Alice garbage collects both and pushes the deletions
+    engram(alice.path())
+        .args(["gc", "--older-than", "30d", "--yes"])
+        .assert()
+        .success();
+    engram(alice.path())
+        .args(["push", "--prune-remote"])
+        .assert()
+        .success()
+        .stderr(contains("2 engram(s) deleted here are still on origin"))
+        .stderr(contains("Use --yes to confirm deletion."));
+    let remote = git2::Repository::open_bare(bare.path()).unwrap();
+    assert_eq!(engram_refs(&remote).len(), 2);
+    engram(alice.path())
+        .args(["push", "--prune-remote", "--yes"])
+        .assert()
+        .success()
+        .stderr(contains("Deleted 2 engram ref(s) from origin"));
+    assert!(engram_refs(&remote).is_empty());
+
+    // Bob keeps them until pruning
+    engram(bob.path())
+        .arg("fetch")
+        .assert()
+        .success()
+        .stderr(contains("Pruned").not());
+    engram(bob.path())
+        .args(["pull", "--prune"])
+        .assert()
+        .success()
+        .stderr(contains("Pruned 2 engram(s) deleted on origin"));
+    let local = git2::Repository::open(bob.path()).unwrap();
+    assert!(engram_refs(&local).is_empty());
+    engram(bob.path())
+        .args(["search", "limiter"])
+        .assert()
+        .success()
+        .stderr(contains("No results found"));
+}
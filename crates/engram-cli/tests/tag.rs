@@ -0,0 +1,134 @@
+mod common;
+
+use std::path::Path;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+fn import(dir: &Path, prompt: &str) {
+    let file = format!("{}.jsonl", prompt.replace(' ', "-"));
+    std::fs::write(dir.join(&file), claude_session(prompt)).unwrap();
+    engram(dir)
+        .args(["import", "--from", "claude-code", &file])
+        .assert()
+        .success();
+}
+
+/// The tags of each engram `engram tag` lists, as JSON.
+fn tags(dir: &Path, args: &[&str]) -> Vec<Vec<String>> {
+    let output = engram(dir)
+        .args(["--format", "json", "tag"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| serde_json::from_value(e["tags"].clone()).unwrap())
+        .collect()
+}
+
+/// Summaries of the engrams matching `query` in the search index.
+fn search(dir: &Path, query: &str) -> Vec<String> {
+    let output = engram(dir)
+        .args(["--format", "json", "search", query])
+        .output()
+        .unwrap();
+    if output.stdout.is_empty() {
+        return Vec::new(); // "No results found" goes to stderr
+    }
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    results
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["summary"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_tag_add_remove_and_search() {
+    let dir = init_repo();
+    import(dir.path(), "Add caching");
+    assert!(search(dir.path(), "tag:perf").is_empty());
+
+    engram(dir.path())
+        .args(["tag", "HEAD"])
+        .assert()
+        .success()
+        .stdout(contains("(no tags)"));
+
+    assert_eq!(
+        tags(dir.path(), &["HEAD", "--add", "perf", "--add", "cache"]),
+        [["perf", "cache"]]
+    );
+    // Adding a tag it already has changes nothing
+    assert_eq!(
+        tags(dir.path(), &["HEAD", "--add", "perf"]),
+        [["perf", "cache"]]
+    );
+    assert_eq!(tags(dir.path(), &["HEAD"]), [["perf", "cache"]]);
+    assert_eq!(search(dir.path(), "tag:perf"), ["Add caching"]);
+
+    assert_eq!(tags(dir.path(), &["HEAD", "--remove", "perf"]), [["cache"]]);
+    assert!(search(dir.path(), "tag:perf").is_empty());
+    assert_eq!(search(dir.path(), "tag:cache"), ["Add caching"]);
+
+    engram(dir.path())
+        .args(["log", "--all", "--tag", "cache"])
+        .assert()
+        .success()
+        .stdout(contains("Add caching"));
+    engram(dir.path())
+        .args(["log", "--all", "--tag", "perf"])
+        .assert()
+        .success()
+        .stdout(contains("No engrams found."));
+
+    engram(dir.path())
+        .args(["tag", "HEAD", "--add", " "])
+        .assert()
+        .failure()
+        .stderr(contains("Invalid tag"));
+}
+
+#[test]
+fn test_tag_many_engrams() {
+    let dir = init_repo();
+    import(dir.path(), "Add caching");
+    import(dir.path(), "Fix login");
+
+    let output = engram(dir.path())
+        .args(["--format", "json", "log", "--all"])
+        .output()
+        .unwrap();
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let ids: Vec<&str> = log
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["id"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(
+        tags(dir.path(), &[&ids[0][..8], &ids[1][..8], "--add", "q1"]),
+        [["q1"], ["q1"]]
+    );
+    assert_eq!(
+        tags(
+            dir.path(),
+            &["--filter", "agent=claude-code", "--add", "imported"]
+        ),
+        [["q1", "imported"], ["q1", "imported"]]
+    );
+    assert_eq!(search(dir.path(), "tag:imported").len(), 2);
+
+    engram(dir.path())
+        .args(["tag", "--filter", "model=opus", "--add", "x"])
+        .assert()
+        .failure()
+        .stderr(contains("expected agent=<name>"));
+}
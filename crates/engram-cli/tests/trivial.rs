@@ -0,0 +1,92 @@
+mod common;
+
+use std::path::Path;
+
+use common::{claude_session, engram, init_repo};
+use predicates::str::contains;
+
+/// A session like [`claude_session`] that runs for five minutes.
+fn long_session(prompt: &str) -> String {
+    claude_session(prompt).replace("10:00:05Z", "10:05:00Z")
+}
+
+fn import(dir: &Path, name: &str, session: String) {
+    std::fs::write(dir.join(name), session).unwrap();
+    engram(dir)
+        .args(["import", "--from", "claude-code", name])
+        .assert()
+        .success();
+}
+
+/// Summaries `engram log` prints with `extra` arguments, most recent first.
+fn logged(dir: &Path, extra: &[&str]) -> Vec<String> {
+    let output = engram(dir)
+        .args(["--format", "json", "log"])
+        .args(extra)
+        .output()
+        .unwrap();
+    let list: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut summaries: Vec<_> = list
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["summary"].as_str().unwrap().to_string())
+        .collect();
+    summaries.sort();
+    summaries
+}
+
+#[test]
+fn test_log_hides_trivial_sessions_unless_all() {
+    let repo = init_repo();
+    import(repo.path(), "hi.jsonl", claude_session("Say hi"));
+    import(repo.path(), "cache.jsonl", long_session("Add caching"));
+
+    assert_eq!(logged(repo.path(), &[]), vec!["Add caching"]);
+    assert_eq!(
+        logged(repo.path(), &["--all"]),
+        vec!["Add caching", "Say hi"]
+    );
+
+    // Thresholds come from config; zero turns detection off
+    engram(repo.path())
+        .args(["config", "set", "trivial.maxSeconds", "0"])
+        .assert()
+        .success();
+    assert_eq!(logged(repo.path(), &[]), vec!["Add caching", "Say hi"]);
+
+    engram(repo.path())
+        .args(["config", "set", "trivial.maxSeconds", "600"])
+        .assert()
+        .success();
+    assert!(logged(repo.path(), &[]).is_empty());
+}
+
+#[test]
+fn test_gc_trivial() {
+    let repo = init_repo();
+    import(repo.path(), "hi.jsonl", claude_session("Say hi"));
+    import(repo.path(), "cache.jsonl", long_session("Add caching"));
+
+    engram(repo.path())
+        .args(["gc", "--trivial", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(contains("1 engram(s) to delete (dry run)"))
+        .stdout(contains("Say hi"));
+
+    engram(repo.path())
+        .args(["gc", "--trivial", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("Deleted 1 engram(s)."));
+    assert_eq!(logged(repo.path(), &["--all"]), vec!["Add caching"]);
+
+    // Combined with --older-than, both must hold
+    import(repo.path(), "bye.jsonl", claude_session("Say bye"));
+    engram(repo.path())
+        .args(["gc", "--trivial", "--older-than", "100y", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("No engrams match"));
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use common::{claude_session, engram, init_repo};
+use engram_core::storage::GitStorage;
+use predicates::prelude::*;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+/// An initialized repository with a remote, one imported engram and a
+/// built search index.
+fn repo_with_engram() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    repo.remote("origin", "https://example.com/repo.git")
+        .unwrap();
+    engram(dir.path()).arg("init").assert().success();
+
+    std::fs::write(
+        dir.path().join("session.jsonl"),
+        claude_session("Add a cache"),
+    )
+    .unwrap();
+    engram(dir.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+    engram(dir.path())
+        .args(["search", "cache"])
+        .assert()
+        .success();
+    dir
+}
+
+fn remote_refspecs(dir: &std::path::Path) -> Vec<String> {
+    let repo = git2::Repository::open(dir).unwrap();
+    let remote = repo.find_remote("origin").unwrap();
+    let fetch = remote.fetch_refspecs().unwrap();
+    let push = remote.push_refspecs().unwrap();
+    fetch
+        .iter()
+        .chain(push.iter())
+        .flatten()
+        .map(String::from)
+        .collect()
+}
+
+fn engram_count(dir: &std::path::Path) -> usize {
+    let repo = git2::Repository::open(dir).unwrap();
+    let count = repo.references_glob("refs/engrams/*").unwrap().count();
+    count
+}
+
+#[test]
+fn test_uninit_removes_setup_and_keeps_engrams() {
+    let repo = repo_with_engram();
+    let git_dir = repo.path().join(".git");
+    assert!(git_dir.join("engram-index").is_dir());
+    let refspecs = remote_refspecs(repo.path());
+    assert!(refspecs.contains(&"refs/engrams/*:refs/engrams/*".to_string()));
+
+    engram(repo.path())
+        .arg("uninit")
+        .assert()
+        .success()
+        .stdout(contains("  config   engram.enabled\n"))
+        .stdout(contains(
+            "  refspec  remote.origin.fetch refs/engrams/*:refs/engrams/*\n",
+        ))
+        .stdout(contains(
+            "  refspec  remote.origin.push refs/engrams/*:refs/engrams/*\n",
+        ))
+        .stdout(contains("  hook     prepare-commit-msg\n"))
+        .stdout(contains("engram-index\n"));
+
+    assert!(!GitStorage::open(repo.path()).unwrap().is_initialized());
+    assert_eq!(
+        remote_refspecs(repo.path()),
+        ["+refs/heads/*:refs/remotes/origin/*"]
+    );
+    assert!(!git_dir.join("hooks/prepare-commit-msg").exists());
+    assert!(!git_dir.join("hooks/post-commit").exists());
+    let leftovers: Vec<_> = std::fs::read_dir(&git_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with("engram"))
+        .collect();
+    assert!(leftovers.is_empty(), "{leftovers:?}");
+    assert_eq!(engram_count(repo.path()), 1);
+
+    engram(repo.path())
+        .arg("uninit")
+        .assert()
+        .failure()
+        .stderr(contains("not initialized"));
+
+    // Initializing again finds the engram
+    engram(repo.path()).arg("init").assert().success();
+    engram(repo.path())
+        .args(["log", "--all"])
+        .assert()
+        .success()
+        .stdout(contains("Add a cache"));
+}
+
+#[test]
+fn test_uninit_keeps_a_directory_that_is_not_an_index() {
+    let repo = init_repo();
+    let elsewhere = TempDir::new().unwrap();
+    std::fs::write(elsewhere.path().join("notes.txt"), "mine").unwrap();
+    git2::Repository::open(repo.path())
+        .unwrap()
+        .config()
+        .unwrap()
+        .set_str("engram.indexPath", elsewhere.path().to_str().unwrap())
+        .unwrap();
+
+    engram(repo.path())
+        .arg("uninit")
+        .assert()
+        .success()
+        .stdout(contains("does not look like a search index"))
+        .stdout(contains("  index    ").not());
+    assert!(elsewhere.path().join("notes.txt").is_file());
+}
+
+#[test]
+fn test_uninit_purge_data() {
+    let repo = init_repo();
+    std::fs::write(
+        repo.path().join("session.jsonl"),
+        claude_session("Add a cache"),
+    )
+    .unwrap();
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .success();
+
+    // Without a terminal to type the confirmation, nothing is removed
+    engram(repo.path())
+        .args(["uninit", "--purge-data"])
+        .assert()
+        .success()
+        .stderr(contains("Use --yes"));
+    assert!(GitStorage::open(repo.path()).unwrap().is_initialized());
+    assert_eq!(engram_count(repo.path()), 1);
+
+    engram(repo.path())
+        .args(["uninit", "--purge-data", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("  deleted  1 engram ref(s)\n"));
+    assert!(!GitStorage::open(repo.path()).unwrap().is_initialized());
+    assert_eq!(engram_count(repo.path()), 0);
+}
+
+#[test]
+fn test_commands_in_uninitialized_repo() {
+    let repo = repo_with_engram();
+    engram(repo.path()).arg("uninit").assert().success();
+
+    // Reading works on the engrams left behind, with a warning
+    engram(repo.path())
+        .args(["log", "--all"])
+        .assert()
+        .success()
+        .stdout(contains("Add a cache"))
+        .stderr(contains("run `engram init --remote origin`"));
+
+    // Writing fails, naming the command to run
+    engram(repo.path())
+        .args(["import", "--from", "claude-code", "session.jsonl"])
+        .assert()
+        .failure()
+        .stderr(contains("Run `engram init --remote origin` first."));
+}
@@ -0,0 +1,62 @@
+use git2::Config;
+
+/// How much of a transcript goes into the search index. Huge transcripts
+/// make indexing slow and segments large, and long tool outputs (logs,
+/// base64 dumps) mostly add noise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexPolicy {
+    /// `engram.index.transcriptMaxBytes`: longer transcript text keeps its
+    /// start and end, in equal parts; 0 indexes it all.
+    pub transcript_max_bytes: u64,
+    /// `engram.index.toolOutputMaxBytes`: longer tool outputs are left out;
+    /// 0 leaves out every tool output.
+    pub tool_output_max_bytes: u64,
+}
+
+impl Default for IndexPolicy {
+    fn default() -> Self {
+        Self {
+            transcript_max_bytes: 1024 * 1024,
+            tool_output_max_bytes: 4 * 1024,
+        }
+    }
+}
+
+impl IndexPolicy {
+    /// Read the policy from `engram.index.*`, falling back to the defaults
+    /// for unset or unreadable keys.
+    pub fn load(config: &Config) -> Self {
+        let defaults = Self::default();
+        let get = |key: &str| config.get_i64(key).ok().and_then(|n| u64::try_from(n).ok());
+        Self {
+            transcript_max_bytes: get("engram.index.transcriptMaxBytes")
+                .unwrap_or(defaults.transcript_max_bytes),
+            tool_output_max_bytes: get("engram.index.toolOutputMaxBytes")
+                .unwrap_or(defaults.tool_output_max_bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let defaults = IndexPolicy::load(&Config::new().unwrap());
+        assert_eq!(defaults, IndexPolicy::default());
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo = git2::Repository::init(tmp.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config
+            .set_str("engram.index.transcriptMaxBytes", "64k")
+            .unwrap();
+        config
+            .set_i64("engram.index.toolOutputMaxBytes", 0)
+            .unwrap();
+        let loaded = IndexPolicy::load(&repo.config().unwrap());
+        assert_eq!(loaded.transcript_max_bytes, 64 * 1024);
+        assert_eq!(loaded.tool_output_max_bytes, 0);
+    }
+}
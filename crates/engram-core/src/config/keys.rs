@@ -0,0 +1,383 @@
+use git2::Config;
+
+use crate::error::CoreError;
+
+/// What a setting holds, which decides how new values are validated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    /// `true`/`false` (also `yes`/`no`, `on`/`off`, `1`/`0`); stored as
+    /// `true` or `false`.
+    Bool,
+    /// A non-negative whole number.
+    Integer,
+    /// A byte count with an optional `k`, `m` or `g` suffix.
+    Size,
+    /// A filesystem path.
+    Path,
+    /// Any non-empty text.
+    Text,
+    /// One of a fixed set of lowercase words.
+    Choice(&'static [&'static str]),
+}
+
+impl SettingKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingKind::Bool => "boolean",
+            SettingKind::Integer => "integer",
+            SettingKind::Size => "size",
+            SettingKind::Path => "path",
+            SettingKind::Text => "text",
+            SettingKind::Choice(_) => "choice",
+        }
+    }
+}
+
+/// A known `engram.*` Git config key.
+#[derive(Debug)]
+pub struct Setting {
+    pub key: &'static str,
+    pub kind: SettingKind,
+    /// Whether the key may be given more than once.
+    pub multi: bool,
+    pub description: &'static str,
+}
+
+/// Every setting engram reads. `engram config` is generated from this table,
+/// so a new setting only needs a row here.
+pub const SETTINGS: &[Setting] = &[
+    Setting {
+        key: "engram.enabled",
+        kind: SettingKind::Bool,
+        multi: false,
+        description: "Whether engram is set up in this repository",
+    },
+    Setting {
+        key: "engram.version",
+        kind: SettingKind::Integer,
+        multi: false,
+        description: "Storage format version written by `engram init`",
+    },
+    Setting {
+        key: "engram.autoCapture",
+        kind: SettingKind::Bool,
+        multi: false,
+        description: "Capture agent sessions automatically",
+    },
+    Setting {
+        key: "engram.defaultAgent",
+        kind: SettingKind::Text,
+        multi: false,
+        description: "Agent assumed when none is detected",
+    },
+    Setting {
+        key: "engram.pushOnPush",
+        kind: SettingKind::Bool,
+        multi: false,
+        description: "Push engrams whenever `git push` runs",
+    },
+    Setting {
+        key: "engram.pushRemotes",
+        kind: SettingKind::Text,
+        multi: true,
+        description: "Remotes `engram push` targets by default",
+    },
+    Setting {
+        key: "engram.capture.exclude",
+        kind: SettingKind::Text,
+        multi: true,
+        description: "Gitignore-style patterns left out of working-tree snapshots",
+    },
+    Setting {
+        key: "engram.capture.maxFileSize",
+        kind: SettingKind::Size,
+        multi: false,
+        description: "Files above this size are fingerprinted instead of hashed",
+    },
+    Setting {
+        key: "engram.insights.deadEndPattern",
+        kind: SettingKind::Text,
+        multi: true,
+        description: "Extra regexes for spotting dead ends in recorded output",
+    },
+    Setting {
+        key: "engram.insights.decisionPattern",
+        kind: SettingKind::Text,
+        multi: true,
+        description: "Extra regexes for spotting decisions in recorded output",
+    },
+    Setting {
+        key: "engram.insights.command",
+        kind: SettingKind::Text,
+        multi: false,
+        description: "Shell command that extracts insights from recorded output",
+    },
+    Setting {
+        key: "engram.indexPath",
+        kind: SettingKind::Path,
+        multi: false,
+        description: "Where the search index lives, instead of .git/engram-index",
+    },
+    Setting {
+        key: "engram.index.transcriptMaxBytes",
+        kind: SettingKind::Size,
+        multi: false,
+        description: "Transcript text indexed per engram, from its start and end (0: all)",
+    },
+    Setting {
+        key: "engram.index.toolOutputMaxBytes",
+        kind: SettingKind::Size,
+        multi: false,
+        description: "Tool outputs longer than this are left out of the index (0: all are)",
+    },
+    Setting {
+        key: "engram.mcp.token",
+        kind: SettingKind::Text,
+        multi: false,
+        description: "Bearer token HTTP clients of `engram mcp --http` must send",
+    },
+    Setting {
+        key: "engram.mcp.maxResponseChars",
+        kind: SettingKind::Integer,
+        multi: false,
+        description: "Longest text result `engram mcp` returns before truncating",
+    },
+    Setting {
+        key: "engram.serve.token",
+        kind: SettingKind::Text,
+        multi: false,
+        description: "Bearer token clients of `engram serve` must send",
+    },
+    Setting {
+        key: "engram.serve.corsOrigin",
+        kind: SettingKind::Text,
+        multi: true,
+        description: "Origins browsers may call `engram serve` from (`*` for any)",
+    },
+    Setting {
+        key: "engram.trivial.maxTokens",
+        kind: SettingKind::Integer,
+        multi: false,
+        description: "Sessions with no file changes below this many tokens are trivial (0: never)",
+    },
+    Setting {
+        key: "engram.trivial.maxSeconds",
+        kind: SettingKind::Integer,
+        multi: false,
+        description: "Sessions with no file changes shorter than this are trivial (0: never)",
+    },
+    Setting {
+        key: "engram.displayTimezone",
+        kind: SettingKind::Choice(&["utc", "local"]),
+        multi: false,
+        description: "Timezone text output shows dates in",
+    },
+];
+
+impl Setting {
+    /// Look up a setting by key, with or without the `engram.` prefix.
+    /// Like Git, the match ignores case.
+    pub fn find(key: &str) -> Result<&'static Setting, CoreError> {
+        let full = if key.to_ascii_lowercase().starts_with("engram.") {
+            key.to_string()
+        } else {
+            format!("engram.{key}")
+        };
+        SETTINGS
+            .iter()
+            .find(|s| s.key.eq_ignore_ascii_case(&full))
+            .ok_or_else(|| {
+                let known: Vec<_> = SETTINGS.iter().map(|s| s.key).collect();
+                CoreError::Config(format!(
+                    "unknown key '{key}'; known keys: {}",
+                    known.join(", ")
+                ))
+            })
+    }
+
+    /// Check `value` against the setting's kind, returning it in the form to
+    /// store.
+    pub fn validate(&self, value: &str) -> Result<String, CoreError> {
+        let invalid = |expected: &str| {
+            CoreError::Config(format!(
+                "invalid value '{value}' for {}: expected {expected}",
+                self.key
+            ))
+        };
+        match self.kind {
+            SettingKind::Bool => match value.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Ok("true".into()),
+                "false" | "no" | "off" | "0" => Ok("false".into()),
+                _ => Err(invalid("true or false")),
+            },
+            SettingKind::Integer => value
+                .parse::<u64>()
+                .map(|n| n.to_string())
+                .map_err(|_| invalid("a non-negative whole number")),
+            SettingKind::Size => {
+                let digits = value.trim_end_matches(['k', 'K', 'm', 'M', 'g', 'G']);
+                if value.len() - digits.len() <= 1 && digits.parse::<u64>().is_ok() {
+                    Ok(value.to_string())
+                } else {
+                    Err(invalid("a size such as 512k, 2m or 1g"))
+                }
+            }
+            SettingKind::Choice(choices) => {
+                let value = value.to_ascii_lowercase();
+                if choices.contains(&value.as_str()) {
+                    Ok(value)
+                } else {
+                    Err(invalid(&format!("one of {}", choices.join(", "))))
+                }
+            }
+            SettingKind::Path | SettingKind::Text => {
+                if value.trim().is_empty() {
+                    Err(invalid("a non-empty value"))
+                } else {
+                    Ok(value.to_string())
+                }
+            }
+        }
+    }
+
+    /// The setting's values in `config`, in config order; empty if unset.
+    pub fn get(&self, config: &Config) -> Result<Vec<String>, CoreError> {
+        let mut values = Vec::new();
+        let mut entries = match config.multivar(self.key, None) {
+            Ok(entries) => entries,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(values),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next() {
+            if let Some(value) = entry?.value() {
+                values.push(value.to_string());
+            }
+        }
+        if !self.multi {
+            // Git uses the last value of a single-valued key
+            values.drain(..values.len().saturating_sub(1));
+        }
+        Ok(values)
+    }
+
+    /// Validate `value` and write it to `config`. Replaces every existing
+    /// value unless `add` is set, which is only allowed for multi-valued
+    /// settings.
+    pub fn set(&self, config: &mut Config, value: &str, add: bool) -> Result<(), CoreError> {
+        if add && !self.multi {
+            return Err(CoreError::Config(format!(
+                "{} takes a single value; set it without --add",
+                self.key
+            )));
+        }
+        let value = self.validate(value)?;
+        if !add {
+            self.unset(config)?;
+        }
+        config.set_multivar(self.key, "^$", &value)?;
+        Ok(())
+    }
+
+    /// Remove every value of the setting from `config`. Returns whether
+    /// there was anything to remove.
+    pub fn unset(&self, config: &mut Config) -> Result<bool, CoreError> {
+        match config.remove_multivar(self.key, ".*") {
+            Ok(()) => Ok(true),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EngramConfig;
+
+    fn local_config() -> (tempfile::TempDir, Config) {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo = git2::Repository::init(tmp.path()).unwrap();
+        let config = repo
+            .config()
+            .unwrap()
+            .open_level(git2::ConfigLevel::Local)
+            .unwrap();
+        (tmp, config)
+    }
+
+    #[test]
+    fn test_find_accepts_short_and_any_case() {
+        assert_eq!(
+            Setting::find("autocapture").unwrap().key,
+            "engram.autoCapture"
+        );
+        assert_eq!(
+            Setting::find("engram.Capture.Exclude").unwrap().key,
+            "engram.capture.exclude"
+        );
+        let err = Setting::find("autoPush").unwrap_err().to_string();
+        assert!(err.contains("unknown key 'autoPush'"));
+        assert!(err.contains("engram.pushOnPush"));
+    }
+
+    #[test]
+    fn test_validate_by_kind() {
+        let enabled = Setting::find("enabled").unwrap();
+        assert_eq!(enabled.validate("yes").unwrap(), "true");
+        assert_eq!(enabled.validate("OFF").unwrap(), "false");
+        assert!(enabled.validate("maybe").is_err());
+
+        let size = Setting::find("capture.maxFileSize").unwrap();
+        assert_eq!(size.validate("2m").unwrap(), "2m");
+        assert_eq!(size.validate("4096").unwrap(), "4096");
+        assert!(size.validate("2mb").is_err());
+        assert!(size.validate("m").is_err());
+
+        let timezone = Setting::find("displayTimezone").unwrap();
+        assert_eq!(timezone.validate("Local").unwrap(), "local");
+        assert!(timezone.validate("cet").is_err());
+
+        let chars = Setting::find("mcp.maxResponseChars").unwrap();
+        assert!(chars.validate("-1").is_err());
+        assert!(Setting::find("defaultAgent")
+            .unwrap()
+            .validate(" ")
+            .is_err());
+    }
+
+    #[test]
+    fn test_set_get_roundtrip() {
+        let (_tmp, mut config) = local_config();
+        let exclude = Setting::find("capture.exclude").unwrap();
+        exclude.set(&mut config, "fixtures/**", false).unwrap();
+        exclude.set(&mut config, "*.pb.go", true).unwrap();
+        assert_eq!(exclude.get(&config).unwrap(), ["fixtures/**", "*.pb.go"]);
+        exclude.set(&mut config, "vendor/", false).unwrap();
+        assert_eq!(exclude.get(&config).unwrap(), ["vendor/"]);
+
+        let auto = Setting::find("autoCapture").unwrap();
+        assert!(auto.set(&mut config, "true", true).is_err());
+        auto.set(&mut config, "on", false).unwrap();
+        assert_eq!(auto.get(&config).unwrap(), ["true"]);
+        assert!(EngramConfig::load(&config).unwrap().auto_capture);
+
+        assert!(auto.unset(&mut config).unwrap());
+        assert!(!auto.unset(&mut config).unwrap());
+        assert!(auto.get(&config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_table_covers_loaded_keys() {
+        for setting in SETTINGS {
+            assert!(setting.key.starts_with("engram."), "{}", setting.key);
+            assert!(!setting.description.is_empty());
+        }
+        let mut keys: Vec<_> = SETTINGS
+            .iter()
+            .map(|s| s.key.to_ascii_lowercase())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), SETTINGS.len());
+    }
+}
@@ -1,3 +1,9 @@
+mod index;
+mod keys;
 mod settings;
+mod trivial;
 
+pub use index::IndexPolicy;
+pub use keys::{Setting, SettingKind, SETTINGS};
 pub use settings::EngramConfig;
+pub use trivial::TrivialThresholds;
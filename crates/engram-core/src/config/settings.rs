@@ -1,13 +1,53 @@
+use std::path::PathBuf;
+
 use git2::Config;
 
 use crate::error::CoreError;
 
+use super::{IndexPolicy, TrivialThresholds};
+
 #[derive(Debug, Clone)]
 pub struct EngramConfig {
     pub enabled: bool,
     pub auto_capture: bool,
     pub default_agent: Option<String>,
     pub push_on_push: bool,
+    /// Gitignore-style patterns excluded from working-tree snapshots
+    /// (`engram.capture.exclude`, may be given multiple times).
+    pub capture_exclude: Vec<String>,
+    /// Files larger than this are fingerprinted by size and mtime instead of
+    /// hashed when snapshotting (`engram.capture.maxFileSize`, accepts
+    /// `k`/`m`/`g` suffixes).
+    pub capture_max_file_size: Option<u64>,
+    /// Extra regexes for spotting dead ends in PTY output
+    /// (`engram.insights.deadEndPattern`, multi-valued).
+    pub insight_dead_end_patterns: Vec<String>,
+    /// Extra regexes for spotting decisions in PTY output
+    /// (`engram.insights.decisionPattern`, multi-valued).
+    pub insight_decision_patterns: Vec<String>,
+    /// Shell command that extracts insights from recorded output
+    /// (`engram.insights.command`), replacing the built-in heuristics.
+    pub insight_command: Option<String>,
+    /// Where the search index lives (`engram.indexPath`), instead of
+    /// `.git/engram-index`; relative paths are resolved against the work tree.
+    pub index_path: Option<PathBuf>,
+    /// How much of each transcript is indexed (`engram.index.*`).
+    pub index: IndexPolicy,
+    /// Bearer token HTTP clients of `engram mcp --http` must send
+    /// (`engram.mcp.token`).
+    pub mcp_token: Option<String>,
+    /// Longest text result `engram mcp` returns before truncating it
+    /// (`engram.mcp.maxResponseChars`).
+    pub mcp_max_response_chars: Option<usize>,
+    /// Bearer token clients of `engram serve` must send
+    /// (`engram.serve.token`).
+    pub serve_token: Option<String>,
+    /// Origins browsers may call `engram serve` from
+    /// (`engram.serve.corsOrigin`, multi-valued).
+    pub serve_cors_origins: Vec<String>,
+    /// When an engram is hidden from default listings as trivial
+    /// (`engram.trivial.maxTokens`, `engram.trivial.maxSeconds`).
+    pub trivial: TrivialThresholds,
 }
 
 impl EngramConfig {
@@ -18,6 +58,24 @@ impl EngramConfig {
             auto_capture: config.get_bool("engram.autoCapture").unwrap_or(false),
             default_agent: config.get_string("engram.defaultAgent").ok(),
             push_on_push: config.get_bool("engram.pushOnPush").unwrap_or(false),
+            capture_exclude: multivar(config, "engram.capture.exclude"),
+            capture_max_file_size: config
+                .get_i64("engram.capture.maxFileSize")
+                .ok()
+                .and_then(|n| u64::try_from(n).ok()),
+            insight_dead_end_patterns: multivar(config, "engram.insights.deadEndPattern"),
+            insight_decision_patterns: multivar(config, "engram.insights.decisionPattern"),
+            insight_command: config.get_string("engram.insights.command").ok(),
+            index_path: config.get_path("engram.indexPath").ok(),
+            index: IndexPolicy::load(config),
+            mcp_token: config.get_string("engram.mcp.token").ok(),
+            mcp_max_response_chars: config
+                .get_i64("engram.mcp.maxResponseChars")
+                .ok()
+                .and_then(|n| usize::try_from(n).ok()),
+            serve_token: config.get_string("engram.serve.token").ok(),
+            serve_cors_origins: multivar(config, "engram.serve.corsOrigin"),
+            trivial: TrivialThresholds::load(config),
         })
     }
 
@@ -47,6 +105,71 @@ impl EngramConfig {
             auto_capture: false,
             default_agent: None,
             push_on_push: false,
+            capture_exclude: Vec::new(),
+            capture_max_file_size: None,
+            insight_dead_end_patterns: Vec::new(),
+            insight_decision_patterns: Vec::new(),
+            insight_command: None,
+            index_path: None,
+            index: IndexPolicy::default(),
+            mcp_token: None,
+            mcp_max_response_chars: None,
+            serve_token: None,
+            serve_cors_origins: Vec::new(),
+            trivial: TrivialThresholds::default(),
+        }
+    }
+}
+
+/// All values of a multi-valued key, in config order.
+fn multivar(config: &Config, name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    if let Ok(mut entries) = config.multivar(name, None) {
+        while let Some(Ok(entry)) = entries.next() {
+            if let Some(value) = entry.value() {
+                values.push(value.to_string());
+            }
         }
     }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_capture_settings() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo = git2::Repository::init(tmp.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config
+            .set_multivar("engram.capture.exclude", "^$", "fixtures/**")
+            .unwrap();
+        config
+            .set_multivar("engram.capture.exclude", "^$", "*.pb.go")
+            .unwrap();
+        config.set_str("engram.capture.maxFileSize", "2m").unwrap();
+        config
+            .set_multivar(
+                "engram.insights.deadEndPattern",
+                "^$",
+                "abandoning approach: (?P<approach>.+)",
+            )
+            .unwrap();
+
+        let loaded = EngramConfig::load(&repo.config().unwrap()).unwrap();
+        assert_eq!(loaded.capture_exclude, vec!["fixtures/**", "*.pb.go"]);
+        assert_eq!(loaded.capture_max_file_size, Some(2 * 1024 * 1024));
+        assert_eq!(
+            loaded.insight_dead_end_patterns,
+            vec!["abandoning approach: (?P<approach>.+)"]
+        );
+        assert!(loaded.insight_decision_patterns.is_empty());
+        assert_eq!(loaded.index_path, None);
+
+        let defaults = EngramConfig::load(&git2::Config::new().unwrap()).unwrap();
+        assert!(defaults.capture_exclude.is_empty());
+        assert_eq!(defaults.capture_max_file_size, None);
+    }
 }
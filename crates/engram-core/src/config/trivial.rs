@@ -0,0 +1,119 @@
+use git2::Config;
+
+use crate::model::Manifest;
+
+/// When an engram counts as trivial: a session that changed no files, used
+/// fewer than `max_tokens` tokens and lasted less than `max_seconds`. Such
+/// sessions (an agent opened and closed, a one-line question) are hidden
+/// from default listings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrivialThresholds {
+    /// `engram.trivial.maxTokens`; 0 turns trivial detection off.
+    pub max_tokens: u64,
+    /// `engram.trivial.maxSeconds`; 0 turns trivial detection off.
+    pub max_seconds: u64,
+}
+
+impl Default for TrivialThresholds {
+    fn default() -> Self {
+        Self {
+            max_tokens: 1000,
+            max_seconds: 30,
+        }
+    }
+}
+
+impl TrivialThresholds {
+    /// Read the thresholds from `engram.trivial.*`, falling back to the
+    /// defaults for unset or unreadable keys.
+    pub fn load(config: &Config) -> Self {
+        let defaults = Self::default();
+        let get = |key: &str| config.get_i64(key).ok().and_then(|n| u64::try_from(n).ok());
+        Self {
+            max_tokens: get("engram.trivial.maxTokens").unwrap_or(defaults.max_tokens),
+            max_seconds: get("engram.trivial.maxSeconds").unwrap_or(defaults.max_seconds),
+        }
+    }
+
+    /// Whether the manifest alone allows the engram to be trivial. Cheap, so
+    /// callers check it before reading the engram's file changes. Sessions
+    /// that never finished are kept, as their length is unknown.
+    pub fn might_be_trivial(&self, manifest: &Manifest) -> bool {
+        if self.max_tokens == 0 || self.max_seconds == 0 {
+            return false;
+        }
+        let Some(finished_at) = manifest.finished_at else {
+            return false;
+        };
+        let seconds = (finished_at - manifest.created_at).num_seconds();
+        manifest.token_usage.total_tokens < self.max_tokens
+            && seconds < i64::try_from(self.max_seconds).unwrap_or(i64::MAX)
+    }
+
+    /// Whether an engram with this manifest and this many file changes is
+    /// trivial.
+    pub fn is_trivial(&self, manifest: &Manifest, file_changes: usize) -> bool {
+        file_changes == 0 && self.might_be_trivial(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+    use crate::model::{AgentInfo, CaptureMode, EngramId, TokenUsage};
+
+    fn manifest(tokens: u64, seconds: Option<i64>) -> Manifest {
+        let created_at = Utc::now();
+        Manifest {
+            id: EngramId::new(),
+            version: 1,
+            created_at,
+            finished_at: seconds.map(|s| created_at + Duration::seconds(s)),
+            agent: AgentInfo {
+                name: "claude-code".into(),
+                model: None,
+                version: None,
+            },
+            git_commits: Vec::new(),
+            token_usage: TokenUsage {
+                total_tokens: tokens,
+                ..Default::default()
+            },
+            summary: None,
+            tags: Vec::new(),
+            capture_mode: CaptureMode::Wrapper,
+            source_hash: None,
+            source_path: None,
+        }
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let t = TrivialThresholds::default();
+        assert!(t.is_trivial(&manifest(200, Some(5)), 0));
+        assert!(!t.is_trivial(&manifest(200, Some(5)), 1));
+        assert!(!t.is_trivial(&manifest(1000, Some(5)), 0));
+        assert!(!t.is_trivial(&manifest(200, Some(30)), 0));
+        assert!(!t.is_trivial(&manifest(200, None), 0));
+
+        let off = TrivialThresholds { max_tokens: 0, ..t };
+        assert!(!off.is_trivial(&manifest(0, Some(0)), 0));
+    }
+
+    #[test]
+    fn test_load() {
+        let defaults = TrivialThresholds::load(&Config::new().unwrap());
+        assert_eq!(defaults, TrivialThresholds::default());
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo = git2::Repository::init(tmp.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_i64("engram.trivial.maxTokens", 5000).unwrap();
+        config.set_i64("engram.trivial.maxSeconds", 120).unwrap();
+        let loaded = TrivialThresholds::load(&repo.config().unwrap());
+        assert_eq!(loaded.max_tokens, 5000);
+        assert_eq!(loaded.max_seconds, 120);
+    }
+}
@@ -0,0 +1,129 @@
+//! Rendering times for people: how long ago something happened, how long a
+//! session lasted, and which timezone dates are shown in.
+
+use chrono::{DateTime, Duration, Local, Utc};
+use git2::Config;
+
+/// The timezone text output shows dates in (`engram.displayTimezone`).
+/// Stored times are always UTC; this only changes how they are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayTimezone {
+    #[default]
+    Utc,
+    /// The timezone of the machine running engram (`TZ` on Unix).
+    Local,
+}
+
+impl DisplayTimezone {
+    /// Read `engram.displayTimezone`, falling back to UTC when it is unset
+    /// or unrecognized.
+    pub fn load(config: &Config) -> Self {
+        match config.get_string("engram.displayTimezone") {
+            Ok(value) if value.eq_ignore_ascii_case("local") => DisplayTimezone::Local,
+            _ => DisplayTimezone::Utc,
+        }
+    }
+
+    /// `time` in this timezone, with a `strftime`-style format. `%Z` prints
+    /// `UTC` or the local offset (`+02:00`).
+    pub fn format(self, time: DateTime<Utc>, fmt: &str) -> String {
+        match self {
+            DisplayTimezone::Utc => time.format(fmt).to_string(),
+            DisplayTimezone::Local => time.with_timezone(&Local).format(fmt).to_string(),
+        }
+    }
+}
+
+/// How long before `now` `time` was, coarsely: `just now`, `5m ago`,
+/// `3h ago`, `2d ago`, `4mo ago`, `1y ago`. Times after `now` (clock skew
+/// between machines) are `just now`.
+pub fn relative_time(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now - time;
+    let days = elapsed.num_days();
+    if elapsed.num_minutes() < 1 {
+        "just now".to_string()
+    } else if elapsed.num_hours() < 1 {
+        format!("{}m ago", elapsed.num_minutes())
+    } else if days < 1 {
+        format!("{}h ago", elapsed.num_hours())
+    } else if days < 30 {
+        format!("{days}d ago")
+    } else if days < 365 {
+        format!("{}mo ago", days / 30)
+    } else {
+        format!("{}y ago", days / 365)
+    }
+}
+
+/// A session length such as `45s`, `14m 32s` or `2h 5m`. Seconds are dropped
+/// once a session runs for hours; negative lengths count as `0s`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    let (hours, mins, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else if mins > 0 {
+        format!("{mins}m {secs}s")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ago(now: DateTime<Utc>, duration: Duration) -> String {
+        relative_time(now - duration, now)
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let now: DateTime<Utc> = "2026-03-01T12:00:00Z".parse().unwrap();
+        assert_eq!(ago(now, Duration::zero()), "just now");
+        assert_eq!(ago(now, Duration::seconds(59)), "just now");
+        assert_eq!(ago(now, Duration::seconds(-30)), "just now");
+        assert_eq!(ago(now, Duration::seconds(60)), "1m ago");
+        assert_eq!(ago(now, Duration::minutes(59)), "59m ago");
+        assert_eq!(ago(now, Duration::hours(3)), "3h ago");
+        assert_eq!(
+            ago(now, Duration::hours(23) + Duration::minutes(59)),
+            "23h ago"
+        );
+        assert_eq!(ago(now, Duration::days(1)), "1d ago");
+        assert_eq!(ago(now, Duration::days(29)), "29d ago");
+        assert_eq!(ago(now, Duration::days(45)), "1mo ago");
+        assert_eq!(ago(now, Duration::days(364)), "12mo ago");
+        assert_eq!(ago(now, Duration::days(365)), "1y ago");
+        assert_eq!(ago(now, Duration::days(800)), "2y ago");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::zero()), "0s");
+        assert_eq!(format_duration(Duration::seconds(-5)), "0s");
+        assert_eq!(format_duration(Duration::seconds(45)), "45s");
+        assert_eq!(format_duration(Duration::seconds(14 * 60 + 32)), "14m 32s");
+        assert_eq!(format_duration(Duration::minutes(60)), "1h 0m");
+        assert_eq!(
+            format_duration(Duration::seconds(26 * 3600 + 301)),
+            "26h 5m"
+        );
+    }
+
+    #[test]
+    fn test_display_timezone() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo = git2::Repository::init(tmp.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        assert_eq!(DisplayTimezone::load(&config), DisplayTimezone::Utc);
+        config.set_str("engram.displayTimezone", "Local").unwrap();
+        assert_eq!(DisplayTimezone::load(&config), DisplayTimezone::Local);
+
+        let time = "2026-01-15T10:00:00Z".parse().unwrap();
+        assert_eq!(
+            DisplayTimezone::Utc.format(time, "%Y-%m-%d %H:%M:%S %Z"),
+            "2026-01-15 10:00:00 UTC"
+        );
+    }
+}
@@ -11,8 +11,8 @@ pub enum CoreError {
     #[error("Invalid manifest: {0}")]
     InvalidManifest(#[from] serde_json::Error),
 
-    #[error("Repository not initialized for engram (run `engram init`)")]
-    NotInitialized,
+    #[error("Engram is not initialized in this repository. Run `{0}` first.")]
+    NotInitialized(String),
 
     #[error("Config error: {0}")]
     Config(String),
@@ -29,6 +29,21 @@ pub enum CoreError {
     #[error("Parse error: {0}")]
     Parse(String),
 
+    #[error("An engram with ID {0} already exists")]
+    AlreadyExists(String),
+
     #[error("Invalid engram ID: {0}")]
     InvalidId(String),
+
+    #[error("Invalid attachment name: {0:?}")]
+    InvalidAttachment(String),
+
+    #[error("Invalid alias name {0:?}: {1}")]
+    InvalidAlias(String, String),
+
+    #[error("No alias named {0:?}")]
+    AliasNotFound(String),
+
+    #[error("{0} is outside the repository at {root}", root = .1.display())]
+    OutsideRepository(String, std::path::PathBuf),
 }
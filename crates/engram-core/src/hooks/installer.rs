@@ -1,6 +1,8 @@
 use std::fs;
 use std::path::Path;
 
+use serde::Serialize;
+
 use crate::error::CoreError;
 
 const HOOKS: &[&str] = &["prepare-commit-msg", "post-commit"];
@@ -60,6 +62,39 @@ pub fn uninstall_hooks(git_dir: &Path) -> Result<(), CoreError> {
     Ok(())
 }
 
+/// The state of one of engram's hooks in a repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HookStatus {
+    pub name: &'static str,
+    /// Whether a script exists for the hook.
+    pub installed: bool,
+    /// Whether that script is engram's.
+    pub managed: bool,
+    /// Whether the hook that was there before engram's is kept as
+    /// `<hook>.pre-engram`, to be restored by [`uninstall_hooks`].
+    pub backup: bool,
+}
+
+/// The state of each hook engram installs.
+pub fn hook_status(git_dir: &Path) -> Result<Vec<HookStatus>, CoreError> {
+    let hooks_dir = git_dir.join("hooks");
+    HOOKS
+        .iter()
+        .map(|&name| {
+            let hook_path = hooks_dir.join(name);
+            let installed = hook_path.exists();
+            let managed =
+                installed && fs::read_to_string(&hook_path)?.contains("engram hook-handler");
+            Ok(HookStatus {
+                name,
+                installed,
+                managed,
+                backup: hooks_dir.join(format!("{name}.pre-engram")).exists(),
+            })
+        })
+        .collect()
+}
+
 fn generate_hook_script(hook_name: &str, has_backup: bool) -> String {
     let mut script = String::from("#!/bin/sh\n");
     script.push_str("# Engram git hook — auto-generated, do not edit\n\n");
@@ -154,4 +189,24 @@ mod tests {
         assert!(content.contains("echo original"));
         assert!(!hooks_dir.join("prepare-commit-msg.pre-engram").exists());
     }
+
+    #[test]
+    fn test_hook_status() {
+        let tmp = TempDir::new().unwrap();
+        let git_dir = tmp.path();
+        let hooks_dir = git_dir.join("hooks");
+        fs::create_dir_all(&hooks_dir).unwrap();
+        fs::write(hooks_dir.join("post-commit"), "#!/bin/sh\necho original\n").unwrap();
+
+        let status = hook_status(git_dir).unwrap();
+        assert!(!status[0].installed);
+        assert!(status[1].installed && !status[1].managed && !status[1].backup);
+
+        install_hooks(git_dir).unwrap();
+        let status = hook_status(git_dir).unwrap();
+        assert_eq!(status[0].name, "prepare-commit-msg");
+        assert!(status.iter().all(|s| s.installed && s.managed));
+        assert!(!status[0].backup);
+        assert!(status[1].backup);
+    }
 }
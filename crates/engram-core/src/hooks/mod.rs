@@ -3,5 +3,5 @@ pub mod installer;
 pub mod session;
 
 pub use handlers::{handle_post_commit, handle_prepare_commit_msg};
-pub use installer::{install_hooks, uninstall_hooks};
+pub use installer::{hook_status, install_hooks, uninstall_hooks, HookStatus};
 pub use session::ActiveSession;
@@ -6,7 +6,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::error::CoreError;
-use crate::model::{AgentInfo, EngramId, TokenUsage};
+use crate::model::{AgentInfo, DeadEnd, EngramId, TokenUsage};
 
 const SESSION_FILE: &str = "engram-session";
 
@@ -18,6 +18,9 @@ pub struct ActiveSession {
     pub started_at: DateTime<Utc>,
     pub commits: Vec<String>,
     pub token_usage: TokenUsage,
+    /// Dead ends reported by the agent while recording, e.g. over MCP.
+    #[serde(default)]
+    pub dead_ends: Vec<DeadEnd>,
 }
 
 impl ActiveSession {
@@ -29,6 +32,7 @@ impl ActiveSession {
             started_at: Utc::now(),
             commits: Vec::new(),
             token_usage: TokenUsage::default(),
+            dead_ends: Vec::new(),
         }
     }
 
@@ -72,6 +76,24 @@ impl ActiveSession {
 
     /// Add a commit SHA to the session atomically with an exclusive lock.
     pub fn add_commit(&mut self, sha: &str, git_dir: &Path) -> Result<(), CoreError> {
+        let current = Self::update(git_dir, |session| session.commits.push(sha.to_string()))?;
+        self.commits = current.commits;
+        Ok(())
+    }
+
+    /// Add a dead end to the session atomically with an exclusive lock.
+    pub fn add_dead_end(&mut self, dead_end: DeadEnd, git_dir: &Path) -> Result<(), CoreError> {
+        let current = Self::update(git_dir, |session| session.dead_ends.push(dead_end))?;
+        self.dead_ends = current.dead_ends;
+        Ok(())
+    }
+
+    /// Apply `change` to the session on disk under an exclusive lock, and
+    /// return the result.
+    fn update(
+        git_dir: &Path,
+        change: impl FnOnce(&mut ActiveSession),
+    ) -> Result<ActiveSession, CoreError> {
         let path = Self::session_path(git_dir);
         let file = fs::OpenOptions::new().read(true).write(true).open(&path)?;
         fs2::FileExt::lock_exclusive(&file).map_err(CoreError::Io)?;
@@ -81,7 +103,7 @@ impl ActiveSession {
         (&file).read_to_string(&mut data)?;
         let mut current: ActiveSession = serde_json::from_str(&data)
             .map_err(|e| CoreError::Config(format!("Session parse: {e}")))?;
-        current.commits.push(sha.to_string());
+        change(&mut current);
 
         // Write back
         let json = serde_json::to_string_pretty(&current)
@@ -89,11 +111,8 @@ impl ActiveSession {
         file.set_len(0)?;
         (&file).seek(SeekFrom::Start(0))?;
         (&file).write_all(json.as_bytes())?;
-
-        // Update self
-        self.commits = current.commits;
         fs2::FileExt::unlock(&file).map_err(CoreError::Io)?;
-        Ok(())
+        Ok(current)
     }
 }
 
@@ -131,6 +150,20 @@ mod tests {
         let reloaded = ActiveSession::load(git_dir).unwrap();
         assert_eq!(reloaded.commits, vec!["abc123"]);
 
+        // Add a dead end; the commit is kept
+        loaded
+            .add_dead_end(
+                DeadEnd {
+                    approach: "Polling".into(),
+                    reason: "Too slow".into(),
+                },
+                git_dir,
+            )
+            .unwrap();
+        let reloaded = ActiveSession::load(git_dir).unwrap();
+        assert_eq!(reloaded.commits, vec!["abc123"]);
+        assert_eq!(reloaded.dead_ends[0].approach, "Polling");
+
         // Cleanup
         ActiveSession::cleanup(git_dir);
         assert!(ActiveSession::load(git_dir).is_none());
@@ -1,5 +1,7 @@
 pub mod config;
+pub mod display;
 pub mod error;
 pub mod hooks;
 pub mod model;
+pub mod paths;
 pub mod storage;
@@ -0,0 +1,71 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// An opaque file stored alongside an engram under `attachments/`.
+///
+/// Used for data that does not fit the structured model, such as the raw
+/// bytes of a PTY recording. Engrams read from storage leave attachments
+/// out; read one with `GitStorage::read_attachment`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attachment {
+    /// File name within the `attachments/` tree (no path separators).
+    pub name: String,
+    pub data: AttachmentData,
+}
+
+/// An attachment's contents, or the file holding them.
+#[derive(Clone)]
+pub enum AttachmentData {
+    Bytes(Vec<u8>),
+    /// Streamed into the object database when the engram is stored, so a
+    /// large file never has to fit in memory. Holding the handle keeps the
+    /// file (e.g. a temp file) alive until then.
+    File(Arc<dyn AsRef<Path> + Send + Sync>),
+}
+
+impl Attachment {
+    pub fn new(name: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            data: AttachmentData::Bytes(data),
+        }
+    }
+
+    /// An attachment whose contents are read from `file` when it is stored.
+    pub fn from_file(name: impl Into<String>, file: Arc<dyn AsRef<Path> + Send + Sync>) -> Self {
+        Self {
+            name: name.into(),
+            data: AttachmentData::File(file),
+        }
+    }
+
+    /// The attachment's contents, read from its file if it has one.
+    pub fn bytes(&self) -> io::Result<Cow<'_, [u8]>> {
+        match &self.data {
+            AttachmentData::Bytes(bytes) => Ok(Cow::Borrowed(bytes)),
+            AttachmentData::File(file) => std::fs::read((**file).as_ref()).map(Cow::Owned),
+        }
+    }
+}
+
+impl fmt::Debug for AttachmentData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes(bytes) => f.debug_tuple("Bytes").field(&bytes.len()).finish(),
+            Self::File(file) => f.debug_tuple("File").field(&(**file).as_ref()).finish(),
+        }
+    }
+}
+
+impl PartialEq for AttachmentData {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Bytes(a), Self::Bytes(b)) => a == b,
+            (Self::File(a), Self::File(b)) => (**a).as_ref() == (**b).as_ref(),
+            _ => false,
+        }
+    }
+}
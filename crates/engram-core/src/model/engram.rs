@@ -85,6 +85,10 @@ pub struct Manifest {
     /// SHA-256 of the source file used during import (for deduplication).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub source_hash: Option<String>,
+    /// Identity of the import source (file path, plus a session index for
+    /// multi-session files). Used to replace an engram when its source changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -162,6 +166,7 @@ mod tests {
             tags: vec!["auth".into()],
             capture_mode: CaptureMode::Wrapper,
             source_hash: None,
+            source_path: None,
         };
         let json = serde_json::to_string_pretty(&manifest).unwrap();
         let parsed: Manifest = serde_json::from_str(&json).unwrap();
@@ -191,6 +191,40 @@ impl Intent {
         })
     }
 
+    /// Parse Markdown written by hand, such as with `engram amend --edit`.
+    ///
+    /// [`Self::from_markdown`] skips what it can't read, so that stored
+    /// engrams always load. This instead rejects an empty request, and lines
+    /// under Dead Ends or Decisions that aren't `- **...**: ...` items, which
+    /// would otherwise be dropped without a word.
+    pub fn from_markdown_strict(md: &str) -> Result<Self, CoreError> {
+        let intent = Self::from_markdown(md)?;
+        if intent.original_request.is_empty() {
+            return Err(CoreError::Parse(
+                "no request under the `# Intent` heading".into(),
+            ));
+        }
+        let mut section = "";
+        for (number, line) in md.lines().enumerate() {
+            if let Some(heading) = line.strip_prefix("## ") {
+                section = heading.trim();
+            } else if line.starts_with("# ") {
+                section = "";
+            } else if matches!(section, "Dead Ends" | "Decisions")
+                && !line.trim().is_empty()
+                && !line
+                    .strip_prefix("- **")
+                    .is_some_and(|entry| entry.contains("**: "))
+            {
+                return Err(CoreError::Parse(format!(
+                    "line {}: expected `- **...**: ...` under `## {section}`",
+                    number + 1
+                )));
+            }
+        }
+        Ok(intent)
+    }
+
     fn save_section(
         section: &str,
         content: &str,
@@ -263,4 +297,18 @@ mod tests {
         assert!(parsed.interpreted_goal.is_none());
         assert!(parsed.dead_ends.is_empty());
     }
+
+    #[test]
+    fn test_strict_parse_rejects_malformed_items() {
+        let md = "# Intent\n\nAdd caching\n\n## Dead Ends\n\n- **LRU**: lost on deploy\n";
+        let intent = Intent::from_markdown_strict(md).unwrap();
+        assert_eq!(intent.dead_ends[0].reason, "lost on deploy");
+
+        let md = "# Intent\n\nAdd caching\n\n## Decisions\n\n- Redis, shared by workers\n";
+        assert_eq!(Intent::from_markdown(md).unwrap().decisions, []);
+        let err = Intent::from_markdown_strict(md).unwrap_err().to_string();
+        assert!(err.contains("line 7"), "{err}");
+
+        assert!(Intent::from_markdown_strict("## Summary\n\nDone\n").is_err());
+    }
 }
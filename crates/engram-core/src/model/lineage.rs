@@ -13,8 +13,12 @@ pub struct Lineage {
     pub related_engrams: Vec<Relationship>,
     #[serde(default)]
     pub git_commits: Vec<String>,
+    /// Branch checked out when the session started (`None` on a detached HEAD).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub branch: Option<String>,
+    /// The working tree had uncommitted changes when the session started.
+    #[serde(default)]
+    pub workdir_dirty_at_start: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -51,6 +55,7 @@ mod tests {
             }],
             git_commits: vec!["abc123".into(), "def456".into()],
             branch: Some("feature/auth".into()),
+            workdir_dirty_at_start: true,
         };
         let json = serde_json::to_string_pretty(&lineage).unwrap();
         let parsed: Lineage = serde_json::from_str(&json).unwrap();
@@ -64,5 +69,9 @@ mod tests {
         // Default should produce minimal JSON
         assert!(!json.contains("parent_engram"));
         assert!(!json.contains("child_engrams"));
+
+        // Engrams written before the field existed still parse
+        let old: Lineage = serde_json::from_str(r#"{"git_commits":[]}"#).unwrap();
+        assert!(!old.workdir_dirty_at_start);
     }
 }
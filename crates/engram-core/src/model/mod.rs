@@ -1,23 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+pub mod attachment;
 pub mod engram;
 pub mod intent;
 pub mod lineage;
+pub mod note;
 pub mod operations;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod token_economics;
 pub mod transcript;
 
+pub use attachment::{Attachment, AttachmentData};
 pub use engram::{AgentInfo, CaptureMode, EngramId, Manifest};
 pub use intent::{DeadEnd, Decision, Intent};
 pub use lineage::{Lineage, RelationType, Relationship};
+pub use note::Note;
 pub use operations::{FileChange, FileChangeType, Operations, ShellCommand, ToolCall};
+#[cfg(any(test, feature = "test-util"))]
+pub use test_util::EngramDataBuilder;
 pub use token_economics::TokenUsage;
 pub use transcript::{Role, Transcript, TranscriptContent, TranscriptEntry};
 
 /// All data for a single engram, ready to be stored or returned.
-#[derive(Debug, Clone)]
+///
+/// Serializes to one JSON object with the transcript as an array of entries.
+/// Attachments are binary and left out; they deserialize as empty.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EngramData {
     pub manifest: Manifest,
     pub intent: Intent,
     pub transcript: Transcript,
     pub operations: Operations,
     pub lineage: Lineage,
+    /// Optional extra files, stored under `attachments/` in the engram tree.
+    #[serde(skip)]
+    pub attachments: Vec<Attachment>,
 }
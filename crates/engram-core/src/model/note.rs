@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::CoreError;
+
+/// A remark added to an engram after it was recorded, such as that its
+/// approach was later reverted. Stored one per line in `notes.jsonl`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+    pub text: String,
+}
+
+impl Note {
+    pub fn new(author: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            author: author.into(),
+            created_at: Utc::now(),
+            text: text.into(),
+        }
+    }
+
+    /// Parse `notes.jsonl`, oldest first. Blank lines are skipped.
+    pub fn from_jsonl(data: &[u8]) -> Result<Vec<Self>, CoreError> {
+        let text = std::str::from_utf8(data).map_err(|e| CoreError::Parse(e.to_string()))?;
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(CoreError::InvalidManifest))
+            .collect()
+    }
+
+    /// This note as a line of `notes.jsonl`.
+    pub fn to_jsonl(&self) -> Result<Vec<u8>, CoreError> {
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+        Ok(line)
+    }
+}
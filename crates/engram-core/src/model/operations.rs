@@ -12,6 +12,17 @@ pub struct Operations {
     pub shell_commands: Vec<ShellCommand>,
 }
 
+impl Operations {
+    /// Whether any tool call failed or any shell command exited non-zero.
+    pub fn has_errors(&self) -> bool {
+        self.tool_calls.iter().any(|tc| tc.is_error)
+            || self
+                .shell_commands
+                .iter()
+                .any(|sc| sc.exit_code.is_some_and(|code| code != 0))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ToolCall {
     pub timestamp: DateTime<Utc>,
@@ -85,6 +96,37 @@ mod tests {
         let json = serde_json::to_string_pretty(&ops).unwrap();
         let parsed: Operations = serde_json::from_str(&json).unwrap();
         assert_eq!(ops, parsed);
+        assert!(!ops.has_errors());
+    }
+
+    #[test]
+    fn test_has_errors() {
+        let command = |exit_code| ShellCommand {
+            timestamp: Utc::now(),
+            command: "cargo test".into(),
+            exit_code,
+            duration_ms: None,
+        };
+        let mut ops = Operations {
+            shell_commands: vec![command(Some(0)), command(None)],
+            ..Default::default()
+        };
+        assert!(!ops.has_errors());
+        ops.shell_commands.push(command(Some(101)));
+        assert!(ops.has_errors());
+
+        let failed_tool = Operations {
+            tool_calls: vec![ToolCall {
+                timestamp: Utc::now(),
+                tool_name: "Bash".into(),
+                input: serde_json::Value::Null,
+                output_summary: None,
+                duration_ms: None,
+                is_error: true,
+            }],
+            ..Default::default()
+        };
+        assert!(failed_tool.has_errors());
     }
 
     #[test]
@@ -0,0 +1,192 @@
+//! A builder for engrams in tests (the `test-util` feature).
+
+use chrono::{DateTime, Utc};
+
+use super::*;
+
+impl EngramData {
+    /// An engram by `claude-code`, imported now with the request "work" and
+    /// nothing else, for tests to add to.
+    pub fn builder() -> EngramDataBuilder {
+        EngramDataBuilder(EngramData {
+            manifest: Manifest {
+                id: EngramId::new(),
+                version: 1,
+                created_at: Utc::now(),
+                finished_at: None,
+                agent: AgentInfo {
+                    name: "claude-code".into(),
+                    model: None,
+                    version: None,
+                },
+                git_commits: Vec::new(),
+                token_usage: TokenUsage::default(),
+                summary: None,
+                tags: Vec::new(),
+                capture_mode: CaptureMode::Import,
+                source_hash: None,
+                source_path: None,
+            },
+            intent: Intent {
+                original_request: "work".into(),
+                interpreted_goal: None,
+                summary: None,
+                dead_ends: Vec::new(),
+                decisions: Vec::new(),
+            },
+            transcript: Transcript {
+                entries: Vec::new(),
+            },
+            operations: Operations::default(),
+            lineage: Lineage::default(),
+            attachments: Vec::new(),
+        })
+    }
+}
+
+/// Builds an [`EngramData`]; see [`EngramData::builder`].
+#[derive(Debug, Clone)]
+pub struct EngramDataBuilder(EngramData);
+
+impl EngramDataBuilder {
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.0.manifest.id = EngramId(id.into());
+        self
+    }
+
+    /// Transcript entries added later take this time too.
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.0.manifest.created_at = created_at;
+        self
+    }
+
+    pub fn agent(mut self, name: &str) -> Self {
+        self.0.manifest.agent.name = name.into();
+        self
+    }
+
+    pub fn model(mut self, model: &str) -> Self {
+        self.0.manifest.agent.model = Some(model.into());
+        self
+    }
+
+    pub fn capture_mode(mut self, mode: CaptureMode) -> Self {
+        self.0.manifest.capture_mode = mode;
+        self
+    }
+
+    pub fn token_usage(mut self, usage: TokenUsage) -> Self {
+        self.0.manifest.token_usage = usage;
+        self
+    }
+
+    /// Only the total, with no breakdown.
+    pub fn total_tokens(mut self, total: u64) -> Self {
+        self.0.manifest.token_usage.total_tokens = total;
+        self
+    }
+
+    pub fn cost(mut self, cost: f64) -> Self {
+        self.0.manifest.token_usage.cost_usd = Some(cost);
+        self
+    }
+
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.0.manifest.summary = Some(summary.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.0.manifest.tags.push(tag.into());
+        self
+    }
+
+    pub fn commit(mut self, sha: &str) -> Self {
+        self.0.manifest.git_commits.push(sha.into());
+        self
+    }
+
+    pub fn request(mut self, request: &str) -> Self {
+        self.0.intent.original_request = request.into();
+        self
+    }
+
+    /// The summary in the intent, as opposed to the manifest's.
+    pub fn intent_summary(mut self, summary: &str) -> Self {
+        self.0.intent.summary = Some(summary.into());
+        self
+    }
+
+    pub fn dead_end(mut self, approach: &str, reason: &str) -> Self {
+        self.0.intent.dead_ends.push(DeadEnd {
+            approach: approach.into(),
+            reason: reason.into(),
+        });
+        self
+    }
+
+    pub fn decision(mut self, description: &str, rationale: &str) -> Self {
+        self.0.intent.decisions.push(Decision {
+            description: description.into(),
+            rationale: rationale.into(),
+        });
+        self
+    }
+
+    /// A text transcript entry at the engram's creation time.
+    pub fn message(mut self, role: Role, text: &str) -> Self {
+        self.0.transcript.entries.push(TranscriptEntry {
+            timestamp: self.0.manifest.created_at,
+            role,
+            content: TranscriptContent::Text { text: text.into() },
+            token_count: None,
+        });
+        self
+    }
+
+    /// A modification of `path`.
+    pub fn file(self, path: &str) -> Self {
+        self.file_change(path, FileChangeType::Modified)
+    }
+
+    pub fn file_change(mut self, path: &str, change_type: FileChangeType) -> Self {
+        self.0.operations.file_changes.push(FileChange {
+            path: path.into(),
+            change_type,
+            lines_added: None,
+            lines_removed: None,
+        });
+        self
+    }
+
+    pub fn tool_call(mut self, tool_name: &str, is_error: bool) -> Self {
+        self.0.operations.tool_calls.push(ToolCall {
+            timestamp: self.0.manifest.created_at,
+            tool_name: tool_name.into(),
+            input: serde_json::Value::Null,
+            output_summary: None,
+            duration_ms: None,
+            is_error,
+        });
+        self
+    }
+
+    pub fn shell_command(mut self, command: &str, exit_code: Option<i32>) -> Self {
+        self.0.operations.shell_commands.push(ShellCommand {
+            timestamp: self.0.manifest.created_at,
+            command: command.into(),
+            exit_code,
+            duration_ms: None,
+        });
+        self
+    }
+
+    pub fn lineage(mut self, lineage: Lineage) -> Self {
+        self.0.lineage = lineage;
+        self
+    }
+
+    pub fn build(self) -> EngramData {
+        self.0
+    }
+}
@@ -43,8 +43,10 @@ pub enum TranscriptContent {
     Thinking { text: String },
 }
 
-/// The full transcript, serialized as JSONL.
-#[derive(Debug, Clone, Default, PartialEq)]
+/// The full transcript, stored as JSONL. Serde treats it as the plain array
+/// of its entries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Transcript {
     pub entries: Vec<TranscriptEntry>,
 }
@@ -63,17 +65,19 @@ impl Transcript {
     /// Deserialize from JSONL bytes.
     pub fn from_jsonl(data: &[u8]) -> Result<Self, CoreError> {
         let text = std::str::from_utf8(data).map_err(|e| CoreError::Parse(e.to_string()))?;
-        let mut entries = Vec::new();
-        for line in text.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
-            let entry: TranscriptEntry =
-                serde_json::from_str(line).map_err(CoreError::InvalidManifest)?;
-            entries.push(entry);
-        }
+        let entries = Self::entries_from_jsonl(text).collect::<Result<_, _>>()?;
         Ok(Transcript { entries })
     }
+
+    /// Parse JSONL one entry at a time, so that part of a long transcript
+    /// can be read without holding all of it. Blank lines are skipped.
+    pub fn entries_from_jsonl(
+        text: &str,
+    ) -> impl Iterator<Item = Result<TranscriptEntry, CoreError>> + '_ {
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(CoreError::InvalidManifest))
+    }
 }
 
 #[cfg(test)]
@@ -136,6 +140,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_entries_from_jsonl_is_lazy() {
+        let mut jsonl = Transcript {
+            entries: sample_entries(),
+        }
+        .to_jsonl()
+        .unwrap();
+        jsonl.extend_from_slice(b"\nnot json\n");
+        let text = std::str::from_utf8(&jsonl).unwrap();
+
+        let first_two: Vec<_> = Transcript::entries_from_jsonl(text)
+            .take(2)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(first_two[1].role, Role::Assistant);
+        assert!(Transcript::entries_from_jsonl(text)
+            .nth(4)
+            .unwrap()
+            .is_err());
+    }
+
     #[test]
     fn test_empty_transcript() {
         let transcript = Transcript::default();
@@ -144,6 +169,18 @@ mod tests {
         assert!(parsed.entries.is_empty());
     }
 
+    #[test]
+    fn test_json_is_array_of_entries() {
+        let transcript = Transcript {
+            entries: sample_entries(),
+        };
+        let json = serde_json::to_value(&transcript).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), transcript.entries.len());
+        assert_eq!(json[0]["role"], "user");
+        let parsed: Transcript = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, transcript);
+    }
+
     #[test]
     fn test_content_variants_serde() {
         let text = TranscriptContent::Text {
@@ -0,0 +1,118 @@
+//! File paths as engrams store them: relative to the repository root, with
+//! `/` separators, whichever directory a command runs in.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::CoreError;
+
+/// `path` relative to the repository whose working tree is `workdir`, with
+/// `/` separators. A relative `path` is taken relative to `cwd`. Paths are
+/// resolved without touching the file, so files since deleted or renamed
+/// still resolve; symlinks (such as a temp directory behind one) are only
+/// followed when the lexical path falls outside `workdir`.
+pub fn repo_relative(workdir: &Path, cwd: &Path, path: &str) -> Result<String, CoreError> {
+    let outside = || CoreError::OutsideRepository(path.to_string(), workdir.to_path_buf());
+    // Accept Windows separators everywhere; engrams only store `/`
+    let given = PathBuf::from(path.replace('\\', "/"));
+    let absolute = normalize(&cwd.join(&given));
+    let root = normalize(workdir);
+
+    let relative = match absolute.strip_prefix(&root) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => {
+            let absolute = canonicalize_existing(&absolute);
+            let root = canonicalize_existing(&root);
+            absolute
+                .strip_prefix(&root)
+                .map_err(|_| outside())?
+                .to_path_buf()
+        }
+    };
+
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Ok(parts.join("/"))
+}
+
+/// `path` with `.` dropped and `..` applied, without reading the disk.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// `path` with its longest existing ancestor canonicalized, so symlinks in
+/// it resolve while the missing rest is kept as is.
+fn canonicalize_existing(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |acc, part| acc.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_repo_relative() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let nested = root.join("crates/foo");
+        std::fs::create_dir_all(&nested).unwrap();
+        let rel = |cwd: &Path, path: &str| repo_relative(root, cwd, path).unwrap();
+
+        assert_eq!(rel(root, "src/auth.rs"), "src/auth.rs");
+        assert_eq!(rel(root, "./src/auth.rs"), "src/auth.rs");
+        assert_eq!(rel(&nested, "src/auth.rs"), "crates/foo/src/auth.rs");
+        assert_eq!(rel(&nested, "../../src/auth.rs"), "src/auth.rs");
+        assert_eq!(rel(&nested, "src\\lib.rs"), "crates/foo/src/lib.rs");
+        let absolute = root.join("src/gone.rs");
+        assert_eq!(rel(&nested, absolute.to_str().unwrap()), "src/gone.rs");
+
+        let err = repo_relative(root, &nested, "../../../elsewhere.rs").unwrap_err();
+        assert!(matches!(err, CoreError::OutsideRepository(..)), "{err}");
+        assert!(repo_relative(root, root, "/etc/passwd").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_repo_relative_through_symlink() {
+        let tmp = TempDir::new().unwrap();
+        let real = tmp.path().join("real");
+        std::fs::create_dir_all(real.join("src")).unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        // The repository was found through the link, the path given for real
+        let path = real.join("src/auth.rs");
+        assert_eq!(
+            repo_relative(&link, &link, path.to_str().unwrap()).unwrap(),
+            "src/auth.rs"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Names for engrams, so `engram show payments-refactor` works instead of an
+//! ID prefix. Aliases are local to a clone: they live in `engram-aliases`
+//! in the Git directory, one `<name> <id>` per line, and are not pushed.
+
+use git2::Repository;
+
+use crate::error::CoreError;
+use crate::model::EngramId;
+
+/// The alias file, relative to the Git directory.
+pub const ALIASES_FILE: &str = "engram-aliases";
+
+/// Check that `name` can be an alias: lowercase letters, digits, `.`, `_`
+/// and `-`, starting with a letter or digit, with at least one character
+/// that is not a hex digit so that it never reads as an engram ID prefix,
+/// and not `head`.
+pub fn validate_alias_name(name: &str) -> Result<(), CoreError> {
+    let invalid = |reason: &str| Err(CoreError::InvalidAlias(name.to_string(), reason.into()));
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-'))
+    {
+        return invalid("use only a-z, 0-9, '.', '_' and '-'");
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return invalid("start with a letter or digit");
+    }
+    if name.chars().all(|c| c.is_ascii_hexdigit()) {
+        return invalid("it would read as an engram ID prefix");
+    }
+    if name == "head" {
+        return invalid("HEAD already names the latest engram");
+    }
+    Ok(())
+}
+
+/// Every alias and the engram it names, sorted by name.
+pub fn list_aliases(repo: &Repository) -> Result<Vec<(String, EngramId)>, CoreError> {
+    let content = match std::fs::read_to_string(repo.path().join(ALIASES_FILE)) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut aliases: Vec<_> = content
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(name, id)| (name.to_string(), EngramId(id.trim().to_string())))
+        .collect();
+    aliases.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(aliases)
+}
+
+/// The engram `name` points at, if it is an alias.
+pub fn lookup_alias(repo: &Repository, name: &str) -> Result<Option<EngramId>, CoreError> {
+    if validate_alias_name(name).is_err() {
+        return Ok(None);
+    }
+    Ok(list_aliases(repo)?
+        .into_iter()
+        .find(|(alias, _)| alias == name)
+        .map(|(_, id)| id))
+}
+
+/// Replace the alias file with `aliases`, removing it when there are none.
+pub fn write_aliases(repo: &Repository, aliases: &[(String, EngramId)]) -> Result<(), CoreError> {
+    let path = repo.path().join(ALIASES_FILE);
+    if aliases.is_empty() {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        };
+    }
+    let content: String = aliases
+        .iter()
+        .map(|(name, id)| format!("{name} {}\n", id.as_str()))
+        .collect();
+    // Written aside and renamed, so readers never see half a file
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_validate_alias_name() {
+        for name in ["payments-refactor", "v1.2_auth", "2026-q1", "x"] {
+            assert!(validate_alias_name(name).is_ok(), "{name}");
+        }
+        for name in [
+            "",
+            "Payments",
+            "has space",
+            "-flag",
+            ".hidden",
+            "cafe",
+            "0123",
+            "head",
+        ] {
+            assert!(validate_alias_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_write_and_list_aliases() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        assert!(list_aliases(&repo).unwrap().is_empty());
+
+        let aliases = vec![
+            ("zeta".to_string(), EngramId("aaaa".into())),
+            ("alpha".to_string(), EngramId("bbbb".into())),
+        ];
+        write_aliases(&repo, &aliases).unwrap();
+        let listed = list_aliases(&repo).unwrap();
+        assert_eq!(listed[0].0, "alpha");
+        assert_eq!(listed[1].0, "zeta");
+        assert_eq!(
+            lookup_alias(&repo, "zeta").unwrap(),
+            Some(EngramId("aaaa".into()))
+        );
+        assert_eq!(lookup_alias(&repo, "missing").unwrap(), None);
+
+        write_aliases(&repo, &[]).unwrap();
+        assert!(!repo.path().join(ALIASES_FILE).exists());
+    }
+}
@@ -1,22 +1,139 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use git2::Repository;
+use git2::{ConfigLevel, Repository};
 
-use crate::config::EngramConfig;
+use crate::config::{EngramConfig, Setting, TrivialThresholds};
 use crate::error::CoreError;
-use crate::model::{EngramData, EngramId, Manifest};
+use crate::model::{EngramData, EngramId, Intent, Lineage, Manifest, Note, Operations};
 
-use super::objects::create_engram_objects;
+use super::aliases;
+use super::objects::{amend_engram_objects, append_note_objects, create_engram_objects};
 use super::read;
 use super::refs;
+use super::usage::{self, SpaceUsage};
 
 const ENGRAM_HEAD_FILE: &str = "engram-head";
 
+/// Source hashes of deleted engrams whose re-import stays blocked, one per line.
+const DELETED_SOURCES_FILE: &str = "engram-deleted-sources";
+
+/// The refspecs `init` adds to remotes, with the patterns that match exactly
+/// them for removing them again. The fetch refspec isn't forced, so a plain
+/// `git fetch` can't replace engrams that have unpushed notes or edits; the
+/// pattern also matches the forced one earlier versions added.
+const FETCH_REFSPEC: (&str, &str) = (
+    "refs/engrams/*:refs/engrams/*",
+    r"^\+?refs/engrams/\*:refs/engrams/\*$",
+);
+/// The forced fetch refspec earlier versions added, replaced on `init`.
+const FORCED_FETCH_REFSPEC: &str = r"^\+refs/engrams/\*:refs/engrams/\*$";
+const PUSH_REFSPEC: (&str, &str) = (
+    "refs/engrams/*:refs/engrams/*",
+    r"^refs/engrams/\*:refs/engrams/\*$",
+);
+
+/// Split a `~N` ancestry suffix off an engram reference: `HEAD~2` is
+/// `("HEAD", 2)`, `abc~` is `("abc", 1)` and `abc~1~2` is `("abc", 3)`.
+fn split_ancestry(reference: &str) -> Result<(&str, usize), CoreError> {
+    let mut parts = reference.split('~');
+    let base = parts.next().unwrap_or_default();
+    let mut steps = 0;
+    for part in parts {
+        steps += match part {
+            "" => 1,
+            n => n.parse::<usize>().map_err(|_| {
+                CoreError::Parse(format!(
+                    "Invalid engram reference '{reference}': expected a number after '~'"
+                ))
+            })?,
+        };
+    }
+    Ok((base, steps))
+}
+
+/// What [`GitStorage::uninit`] removed.
+#[derive(Debug, Default)]
+pub struct UninitReport {
+    /// `engram.*` keys removed from the repository's config.
+    pub config_keys: Vec<String>,
+    /// Refspecs removed from remotes, as `remote.<name>.fetch` or
+    /// `remote.<name>.push` and the refspec.
+    pub refspecs: Vec<(String, String)>,
+    /// Files and directories removed from the Git directory.
+    pub paths: Vec<PathBuf>,
+    /// How many engram refs were deleted.
+    pub refs_deleted: usize,
+}
+
+/// What [`GitStorage::ensure_initialized`] does in a repository `engram
+/// init` never ran in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitPolicy {
+    /// Fail with [`CoreError::NotInitialized`], naming the command to run.
+    Error,
+    /// Log a warning naming the command to run, and carry on.
+    WarnAndContinue,
+    /// Initialize the repository as [`GitStorage::init`] does.
+    AutoInit,
+}
+
 /// Options for listing engrams.
 #[derive(Debug, Clone, Default)]
 pub struct ListOptions {
     pub limit: Option<usize>,
+    /// Skip this many of the most recent matching engrams, for paging.
+    pub offset: usize,
     pub agent_filter: Option<String>,
+    /// Only engrams that cost at least this much (USD). Engrams with no
+    /// recorded cost are excluded whenever either cost bound is set.
+    pub min_cost: Option<f64>,
+    pub max_cost: Option<f64>,
+    /// Only engrams with at least this many total tokens.
+    pub min_tokens: Option<u64>,
+    pub max_tokens: Option<u64>,
+    /// Only engrams with this tag.
+    pub tag: Option<String>,
+    /// Only engrams created at or after this time.
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only engrams created at or before this time.
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Leave out engrams these thresholds judge trivial.
+    pub hide_trivial: Option<TrivialThresholds>,
+}
+
+impl ListOptions {
+    /// Whether a manifest passes these filters. `limit`, `offset` and
+    /// `hide_trivial` are left to [`GitStorage::list`].
+    pub fn matches(&self, manifest: &Manifest) -> bool {
+        if let Some(agent) = &self.agent_filter {
+            if !manifest.agent.name.contains(agent.as_str()) {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            if !manifest.tags.contains(tag) {
+                return false;
+            }
+        }
+        if self.since.is_some_and(|since| manifest.created_at < since)
+            || self.until.is_some_and(|until| manifest.created_at > until)
+        {
+            return false;
+        }
+        let usage = &manifest.token_usage;
+        if self.min_cost.is_some() || self.max_cost.is_some() {
+            let Some(cost) = usage.cost_usd else {
+                return false;
+            };
+            if self.min_cost.is_some_and(|min| cost < min)
+                || self.max_cost.is_some_and(|max| cost > max)
+            {
+                return false;
+            }
+        }
+        !(self.min_tokens.is_some_and(|min| usage.total_tokens < min)
+            || self.max_tokens.is_some_and(|max| usage.total_tokens > max))
+    }
 }
 
 /// The main storage interface for engram operations.
@@ -46,6 +163,45 @@ impl GitStorage {
             .unwrap_or(false)
     }
 
+    /// Check that engram is initialized, acting on `policy` if it is not.
+    /// Returns whether it is initialized afterwards.
+    pub fn ensure_initialized(&self, policy: InitPolicy) -> Result<bool, CoreError> {
+        if self.is_initialized() {
+            return Ok(true);
+        }
+        match policy {
+            InitPolicy::Error => Err(CoreError::NotInitialized(self.init_command())),
+            InitPolicy::WarnAndContinue => {
+                tracing::warn!(
+                    "Engram is not initialized in this repository; run `{}` so its \
+                     engrams sync with remotes",
+                    self.init_command()
+                );
+                Ok(false)
+            }
+            InitPolicy::AutoInit => {
+                self.init()?;
+                tracing::info!("Initialized engram in {}", self.repo.path().display());
+                Ok(true)
+            }
+        }
+    }
+
+    /// The `engram init` command to run here: with `--remote` naming
+    /// `origin`, or else the first remote, when the repository has remotes.
+    pub fn init_command(&self) -> String {
+        let remotes = self.repo.remotes().ok();
+        let names: Vec<&str> = remotes.iter().flat_map(|r| r.iter().flatten()).collect();
+        let remote = names
+            .iter()
+            .find(|name| **name == "origin")
+            .or_else(|| names.first());
+        match remote {
+            Some(remote) => format!("engram init --remote {remote}"),
+            None => "engram init".to_string(),
+        }
+    }
+
     /// Initialize engram in this repo: set config, configure refspecs.
     /// If `remote` is Some, only configure that specific remote; otherwise configure all.
     pub fn init_with_remote(&self, remote: Option<&str>) -> Result<(), CoreError> {
@@ -69,6 +225,70 @@ impl GitStorage {
         self.init_with_remote(None)
     }
 
+    /// Undo [`Self::init`]: remove the `engram.*` keys from the repository's
+    /// config, the refspecs `init` added to its remotes (other refspecs are
+    /// kept), and the `engram*` files in the Git directory, including the
+    /// default search index. With `purge_refs`, every engram ref is deleted
+    /// too; otherwise the engrams stay and a later `init` finds them again.
+    /// Git hooks are left to [`crate::hooks::uninstall_hooks`].
+    pub fn uninit(&self, purge_refs: bool) -> Result<UninitReport, CoreError> {
+        let mut report = UninitReport::default();
+        let mut config = self.repo.config()?.open_level(ConfigLevel::Local)?;
+
+        let mut keys = Vec::new();
+        {
+            let mut entries = config.entries(Some(r"^engram\."))?;
+            while let Some(entry) = entries.next() {
+                if let Some(name) = entry?.name() {
+                    if !keys.contains(&name.to_string()) {
+                        keys.push(name.to_string());
+                    }
+                }
+            }
+        }
+        for key in keys {
+            config.remove_multivar(&key, ".*")?;
+            // Git lowercases names; report known keys as they are documented
+            let key = Setting::find(&key).map_or(key, |setting| setting.key.to_string());
+            report.config_keys.push(key);
+        }
+
+        for remote_name in self.repo.remotes()?.iter().flatten() {
+            for (kind, (refspec, pattern)) in [("fetch", FETCH_REFSPEC), ("push", PUSH_REFSPEC)] {
+                let key = format!("remote.{remote_name}.{kind}");
+                match config.remove_multivar(&key, pattern) {
+                    Ok(()) => report.refspecs.push((key, refspec.to_string())),
+                    Err(e) if e.code() == git2::ErrorCode::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+
+        if purge_refs {
+            for (id, _) in refs::list_engram_refs(&self.repo)? {
+                refs::delete_engram_ref(&self.repo, &id)?;
+                report.refs_deleted += 1;
+            }
+        }
+
+        let mut paths: Vec<_> = std::fs::read_dir(self.repo.path())?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("engram"))
+            .map(|entry| entry.path())
+            .collect();
+        paths.sort();
+        for path in paths {
+            if path.is_dir() {
+                std::fs::remove_dir_all(&path)?;
+            } else {
+                std::fs::remove_file(&path)?;
+            }
+            report.paths.push(path);
+        }
+
+        Ok(report)
+    }
+
     /// Create a new engram and store it as Git objects.
     pub fn create(&self, data: &EngramData) -> Result<EngramId, CoreError> {
         let commit_oid = create_engram_objects(&self.repo, data)?;
@@ -79,34 +299,100 @@ impl GitStorage {
         Ok(id)
     }
 
-    /// Resolve "HEAD" to the most recent engram ID, or pass through to prefix resolution.
+    /// Resolve "HEAD" to the most recent engram ID; anything else is tried
+    /// as a full ID, then an alias, then an ID prefix.
+    ///
+    /// A `~N` suffix steps back N engrams (`~` alone is `~1`): `HEAD~N` is
+    /// the N-th most recent engram, and `<id>~N` follows the engrams'
+    /// `parent_engram` lineage, stepping to the next older engram where an
+    /// engram has no parent.
     pub fn resolve(&self, id_or_alias: &str) -> Result<String, CoreError> {
-        if id_or_alias.eq_ignore_ascii_case("HEAD") {
-            // Fast path: try engram-head pointer file
-            if let Some(head_id) = self.read_head_pointer() {
-                // Validate the ref still exists
-                if refs::resolve_engram_ref(&self.repo, &head_id).is_ok() {
-                    return Ok(head_id);
-                }
-            }
-            // Fallback: O(n) scan
-            let manifests = self.list(&ListOptions::default())?;
-            if let Some(m) = manifests.first() {
-                // Repair the head pointer
-                self.update_head_pointer(&m.id, &m.created_at);
-                Ok(m.id.as_str().to_string())
-            } else {
-                Err(CoreError::NotFound {
-                    id: "HEAD (no engrams exist)".to_string(),
-                })
+        let (base, steps) = split_ancestry(id_or_alias)?;
+        if base.eq_ignore_ascii_case("HEAD") {
+            return match steps {
+                0 => self.resolve_head(),
+                n => self.nth_most_recent(id_or_alias, n),
+            };
+        }
+        let (id, _oid) = refs::resolve_engram_ref(&self.repo, base)?;
+        match steps {
+            0 => Ok(id.as_str().to_string()),
+            n => self.ancestor(id_or_alias, id, n),
+        }
+    }
+
+    fn resolve_head(&self) -> Result<String, CoreError> {
+        // Fast path: try engram-head pointer file
+        if let Some(head_id) = self.read_head_pointer() {
+            // Validate the ref still exists
+            if refs::resolve_engram_ref(&self.repo, &head_id).is_ok() {
+                return Ok(head_id);
             }
+        }
+        // Fallback: O(n) scan
+        let manifests = self.list(&ListOptions::default())?;
+        if let Some(m) = manifests.first() {
+            // Repair the head pointer
+            self.update_head_pointer(&m.id, &m.created_at);
+            Ok(m.id.as_str().to_string())
         } else {
-            let (id, _oid) = refs::resolve_engram_ref(&self.repo, id_or_alias)?;
-            Ok(id.as_str().to_string())
+            Err(CoreError::NotFound {
+                id: "HEAD (no engrams exist)".to_string(),
+            })
+        }
+    }
+
+    /// The engram `n` places after the most recent one (`HEAD~n`).
+    fn nth_most_recent(&self, reference: &str, n: usize) -> Result<String, CoreError> {
+        let manifests = self.list(&ListOptions::default())?;
+        match manifests.get(n) {
+            Some(m) => Ok(m.id.as_str().to_string()),
+            None => Err(CoreError::NotFound {
+                id: format!("{reference} (only {} engram(s) exist)", manifests.len()),
+            }),
         }
     }
 
+    /// The engram `n` steps back from `id` (`<id>~n`): its parent engram,
+    /// or the next older engram when it has none.
+    fn ancestor(&self, reference: &str, id: EngramId, n: usize) -> Result<String, CoreError> {
+        let manifests = self.list(&ListOptions::default())?;
+        let mut current = id;
+        for _ in 0..n {
+            let (_, oid) = refs::resolve_engram_ref(&self.repo, current.as_str())?;
+            let lineage: Lineage = serde_json::from_slice(&read::read_part(
+                &self.repo,
+                oid,
+                read::EngramPart::Lineage,
+            )?)?;
+            let parent = lineage
+                .parent_engram
+                .filter(|parent| manifests.iter().any(|m| m.id == *parent));
+            let older = || {
+                manifests
+                    .iter()
+                    .position(|m| m.id == current)
+                    .and_then(|i| manifests.get(i + 1))
+                    .map(|m| m.id.clone())
+            };
+            current = match parent.or_else(older) {
+                Some(next) => next,
+                None => {
+                    return Err(CoreError::NotFound {
+                        id: format!(
+                            "{reference} ({} has no parent or older engram)",
+                            &current.as_str()[..8.min(current.as_str().len())]
+                        ),
+                    })
+                }
+            };
+        }
+        Ok(current.as_str().to_string())
+    }
+
     /// Read an engram by its ID (or prefix).
+    ///
+    /// Attachments are left out; see [`Self::read_attachment`].
     pub fn read(&self, id_or_prefix: &str) -> Result<EngramData, CoreError> {
         let (_id, oid) = refs::resolve_engram_ref(&self.repo, id_or_prefix)?;
         read::read_engram(&self.repo, oid)
@@ -118,22 +404,42 @@ impl GitStorage {
         read::read_manifest(&self.repo, oid)
     }
 
+    /// Read one part of an engram (e.g. just its intent) without the rest.
+    pub fn read_part(
+        &self,
+        id_or_prefix: &str,
+        part: read::EngramPart,
+    ) -> Result<Vec<u8>, CoreError> {
+        let (_id, oid) = refs::resolve_engram_ref(&self.repo, id_or_prefix)?;
+        read::read_part(&self.repo, oid, part)
+    }
+
+    /// Read one of an engram's attachments, or `None` if it has no
+    /// attachment by that name.
+    pub fn read_attachment(
+        &self,
+        id_or_prefix: &str,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>, CoreError> {
+        let (_id, oid) = refs::resolve_engram_ref(&self.repo, id_or_prefix)?;
+        read::read_attachment(&self.repo, oid, name)
+    }
+
+    /// Read just an engram's intent, skipping its transcript and operations.
+    pub fn read_intent(&self, id_or_prefix: &str) -> Result<Intent, CoreError> {
+        let bytes = self.read_part(id_or_prefix, read::EngramPart::Intent)?;
+        Intent::from_markdown(&String::from_utf8_lossy(&bytes))
+    }
+
     /// List all engrams, optionally filtered.
     pub fn list(&self, opts: &ListOptions) -> Result<Vec<Manifest>, CoreError> {
         let all_refs = refs::list_engram_refs(&self.repo)?;
-        let mut manifests = Vec::with_capacity(all_refs.len());
+        let mut candidates = Vec::with_capacity(all_refs.len());
 
         for (_id, oid) in &all_refs {
             match read::read_manifest(&self.repo, *oid) {
-                Ok(manifest) => {
-                    // Apply agent filter
-                    if let Some(agent) = &opts.agent_filter {
-                        if !manifest.agent.name.contains(agent.as_str()) {
-                            continue;
-                        }
-                    }
-                    manifests.push(manifest);
-                }
+                Ok(manifest) if opts.matches(&manifest) => candidates.push((*oid, manifest)),
+                Ok(_) => {}
                 Err(e) => {
                     tracing::warn!("Skipping unreadable engram: {e}");
                 }
@@ -141,16 +447,52 @@ impl GitStorage {
         }
 
         // Sort by created_at descending (most recent first)
-        manifests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        candidates.sort_by_key(|(_, m)| std::cmp::Reverse(m.created_at));
 
-        // Apply limit
-        if let Some(limit) = opts.limit {
-            manifests.truncate(limit);
-        }
+        // Trivial engrams are only looked into until the page is full
+        let manifests = candidates
+            .into_iter()
+            .filter(|(oid, manifest)| {
+                !opts
+                    .hide_trivial
+                    .is_some_and(|t| self.is_trivial_oid(*oid, manifest, &t))
+            })
+            .map(|(_, manifest)| manifest)
+            .skip(opts.offset)
+            .take(opts.limit.unwrap_or(usize::MAX))
+            .collect();
 
         Ok(manifests)
     }
 
+    /// Whether an engram is trivial by these thresholds. Its file changes are
+    /// only read when the manifest leaves that open.
+    pub fn is_trivial(
+        &self,
+        id_or_prefix: &str,
+        thresholds: &TrivialThresholds,
+    ) -> Result<bool, CoreError> {
+        let (_id, oid) = refs::resolve_engram_ref(&self.repo, id_or_prefix)?;
+        let manifest = read::read_manifest(&self.repo, oid)?;
+        Ok(self.is_trivial_oid(oid, &manifest, thresholds))
+    }
+
+    fn is_trivial_oid(
+        &self,
+        oid: git2::Oid,
+        manifest: &Manifest,
+        thresholds: &TrivialThresholds,
+    ) -> bool {
+        if !thresholds.might_be_trivial(manifest) {
+            return false;
+        }
+        let file_changes = read::read_part(&self.repo, oid, read::EngramPart::Operations)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Operations>(&bytes).ok())
+            .map_or(0, |ops| ops.file_changes.len());
+        thresholds.is_trivial(manifest, file_changes)
+    }
+
     /// Check if an engram with the given source hash already exists.
     /// Used for import deduplication.
     pub fn find_by_source_hash(&self, hash: &str) -> Option<EngramId> {
@@ -165,10 +507,165 @@ impl GitStorage {
         None
     }
 
-    /// Delete an engram by removing its ref.
+    /// Keep blocking imports of content with this source hash after its
+    /// engram is deleted. Without this, deleting an engram lets the same
+    /// session be imported again.
+    pub fn block_source_hash(&self, hash: &str) -> Result<(), CoreError> {
+        if self.is_source_hash_blocked(hash) {
+            return Ok(());
+        }
+        use std::io::Write;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.repo.path().join(DELETED_SOURCES_FILE))?;
+        writeln!(file, "{hash}")?;
+        Ok(())
+    }
+
+    /// Whether the engram imported from content with this source hash was
+    /// deleted with its hash kept (see [`Self::block_source_hash`]).
+    pub fn is_source_hash_blocked(&self, hash: &str) -> bool {
+        std::fs::read_to_string(self.repo.path().join(DELETED_SOURCES_FILE))
+            .is_ok_and(|blocked| blocked.lines().any(|line| line == hash))
+    }
+
+    /// Find the engram previously imported from the given source path.
+    /// Used to replace stale imports when the source file has changed.
+    pub fn find_by_source_path(&self, source_path: &str) -> Option<EngramId> {
+        let all_refs = refs::list_engram_refs(&self.repo).ok()?;
+        for (id, oid) in &all_refs {
+            if let Ok(manifest) = read::read_manifest(&self.repo, *oid) {
+                if manifest.source_path.as_deref() == Some(source_path) {
+                    return Some(id.clone());
+                }
+            }
+        }
+        None
+    }
+
+    /// Add a note to an engram, after any it already has. The engram's ref
+    /// moves to a commit whose parent is the one it pointed at.
+    pub fn add_note(
+        &self,
+        id_or_prefix: &str,
+        author: &str,
+        text: &str,
+    ) -> Result<Note, CoreError> {
+        let (id, oid) = refs::resolve_engram_ref(&self.repo, id_or_prefix)?;
+        let note = Note::new(author, text);
+        let new_oid = append_note_objects(&self.repo, oid, &note)?;
+        refs::update_engram_ref(&self.repo, &id, new_oid, oid, "engram: note")?;
+        Ok(note)
+    }
+
+    /// Change an engram's manifest and intent with `update`, keeping its
+    /// ID. The engram's ref moves to a commit whose parent is the one it
+    /// pointed at, so earlier versions stay in its history. Nothing is
+    /// written if `update` leaves both as they were.
+    ///
+    /// Returns the updated manifest and intent.
+    pub fn update(
+        &self,
+        id_or_prefix: &str,
+        update: impl FnOnce(&mut Manifest, &mut Intent),
+    ) -> Result<(Manifest, Intent), CoreError> {
+        let (id, oid) = refs::resolve_engram_ref(&self.repo, id_or_prefix)?;
+        let old_manifest = read::read_manifest(&self.repo, oid)?;
+        let intent_bytes = read::read_part(&self.repo, oid, read::EngramPart::Intent)?;
+        let old_intent = Intent::from_markdown(&String::from_utf8_lossy(&intent_bytes))?;
+        let (mut manifest, mut intent) = (old_manifest.clone(), old_intent.clone());
+        update(&mut manifest, &mut intent);
+        manifest.id = id.clone();
+
+        let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
+        let intent_bytes = intent.to_markdown().into_bytes();
+        let mut files: Vec<(&str, &[u8])> = Vec::new();
+        if manifest != old_manifest {
+            files.push(("manifest.json", &manifest_bytes));
+        }
+        if intent != old_intent {
+            files.push(("intent.md", &intent_bytes));
+        }
+        if !files.is_empty() {
+            let new_oid = amend_engram_objects(&self.repo, oid, &files, "engram: amend")?;
+            refs::update_engram_ref(&self.repo, &id, new_oid, oid, "engram: amend")?;
+        }
+        Ok((manifest, intent))
+    }
+
+    /// Change just an engram's manifest; see [`Self::update`].
+    pub fn update_manifest(
+        &self,
+        id_or_prefix: &str,
+        update: impl FnOnce(&mut Manifest),
+    ) -> Result<Manifest, CoreError> {
+        self.update(id_or_prefix, |manifest, _| update(manifest))
+            .map(|(manifest, _)| manifest)
+    }
+
+    /// Read the notes added to an engram, oldest first.
+    pub fn read_notes(&self, id_or_prefix: &str) -> Result<Vec<Note>, CoreError> {
+        let (_id, oid) = refs::resolve_engram_ref(&self.repo, id_or_prefix)?;
+        read::read_notes(&self.repo, oid)
+    }
+
+    /// Delete an engram by removing its ref, and the aliases naming it.
     pub fn delete(&self, id_or_prefix: &str) -> Result<(), CoreError> {
         let (id, _oid) = refs::resolve_engram_ref(&self.repo, id_or_prefix)?;
-        refs::delete_engram_ref(&self.repo, &id)
+        refs::delete_engram_ref(&self.repo, &id)?;
+        let mut aliases = aliases::list_aliases(&self.repo)?;
+        let before = aliases.len();
+        aliases.retain(|(_, target)| *target != id);
+        if aliases.len() != before {
+            aliases::write_aliases(&self.repo, &aliases)?;
+        }
+        Ok(())
+    }
+
+    /// The space the engrams in `ids` take, and what deleting them would
+    /// free: blobs other engrams also reference are counted once and not as
+    /// reclaimable.
+    pub fn space_usage(&self, ids: &[EngramId]) -> Result<SpaceUsage, CoreError> {
+        let all = refs::list_engram_refs(&self.repo)?;
+        usage::space_usage(&self.repo, &all, ids)
+    }
+
+    /// Point alias `name` at an engram (ID, prefix, alias or HEAD),
+    /// replacing any engram it named before. Returns the engram's ID and
+    /// the one it replaced.
+    pub fn set_alias(
+        &self,
+        name: &str,
+        id_or_alias: &str,
+    ) -> Result<(EngramId, Option<EngramId>), CoreError> {
+        aliases::validate_alias_name(name)?;
+        let id = EngramId(self.resolve(id_or_alias)?);
+        let mut aliases = aliases::list_aliases(&self.repo)?;
+        let previous = aliases
+            .iter()
+            .position(|(alias, _)| alias == name)
+            .map(|i| aliases.remove(i).1);
+        aliases.push((name.to_string(), id.clone()));
+        aliases::write_aliases(&self.repo, &aliases)?;
+        Ok((id, previous))
+    }
+
+    /// Remove alias `name`, returning the engram it named.
+    pub fn remove_alias(&self, name: &str) -> Result<EngramId, CoreError> {
+        let mut aliases = aliases::list_aliases(&self.repo)?;
+        let i = aliases
+            .iter()
+            .position(|(alias, _)| alias == name)
+            .ok_or_else(|| CoreError::AliasNotFound(name.to_string()))?;
+        let (_, id) = aliases.remove(i);
+        aliases::write_aliases(&self.repo, &aliases)?;
+        Ok(id)
+    }
+
+    /// Every alias and the engram it names, sorted by name.
+    pub fn aliases(&self) -> Result<Vec<(String, EngramId)>, CoreError> {
+        aliases::list_aliases(&self.repo)
     }
 
     /// Get the underlying git2::Repository reference.
@@ -224,8 +721,14 @@ impl GitStorage {
             }
             let fetch_key = format!("remote.{remote_name}.fetch");
             let push_key = format!("remote.{remote_name}.push");
-            let fetch_refspec = "+refs/engrams/*:refs/engrams/*";
-            let push_refspec = "refs/engrams/*:refs/engrams/*";
+            let fetch_refspec = FETCH_REFSPEC.0;
+            let push_refspec = PUSH_REFSPEC.0;
+
+            match config.remove_multivar(&fetch_key, FORCED_FETCH_REFSPEC) {
+                Ok(()) => {}
+                Err(e) if e.code() == git2::ErrorCode::NotFound => {}
+                Err(e) => return Err(CoreError::Git(e)),
+            }
 
             // Check if already configured by iterating existing values
             let fetch_exists = config
@@ -311,6 +814,7 @@ mod tests {
                 tags: vec![],
                 capture_mode: CaptureMode::Sdk,
                 source_hash: None,
+                source_path: None,
             },
             intent: Intent {
                 original_request: "Test request".into(),
@@ -322,6 +826,7 @@ mod tests {
             transcript: Transcript::default(),
             operations: Operations::default(),
             lineage: Lineage::default(),
+            attachments: Vec::new(),
         }
     }
 
@@ -349,6 +854,15 @@ mod tests {
         let created_id = storage.create(&data).unwrap();
         assert_eq!(created_id, id);
 
+        // An engram is never replaced by another with its ID
+        let mut other = make_test_data();
+        other.manifest.id = id.clone();
+        other.intent.original_request = "Other request".into();
+        assert!(matches!(
+            storage.create(&other),
+            Err(CoreError::AlreadyExists(existing)) if existing == id.as_str()
+        ));
+
         // List
         let manifests = storage.list(&ListOptions::default()).unwrap();
         assert_eq!(manifests.len(), 1);
@@ -363,12 +877,308 @@ mod tests {
         let manifest = storage.read_manifest(id.as_str()).unwrap();
         assert_eq!(manifest.summary, Some("Test engram".into()));
 
+        // Read intent only
+        let intent = storage.read_intent(&id.as_str()[..8]).unwrap();
+        assert_eq!(intent.original_request, "Test request");
+
         // Delete
         storage.delete(id.as_str()).unwrap();
         let manifests = storage.list(&ListOptions::default()).unwrap();
         assert!(manifests.is_empty());
     }
 
+    #[test]
+    fn test_aliases() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let first = storage.create(&make_test_data()).unwrap();
+        let second = storage.create(&make_test_data()).unwrap();
+
+        let (id, previous) = storage.set_alias("auth-fix", &first.as_str()[..8]).unwrap();
+        assert_eq!((id, previous), (first.clone(), None));
+        assert_eq!(storage.resolve("auth-fix").unwrap(), first.as_str());
+        assert_eq!(storage.read_manifest("auth-fix").unwrap().id, first);
+
+        // Aliases can point at HEAD or other aliases, and be repointed
+        let (_, previous) = storage.set_alias("auth-fix", second.as_str()).unwrap();
+        assert_eq!(previous, Some(first.clone()));
+        storage.set_alias("also", "auth-fix").unwrap();
+        assert_eq!(storage.resolve("also").unwrap(), second.as_str());
+        assert!(matches!(
+            storage.set_alias("BAD", "HEAD"),
+            Err(CoreError::InvalidAlias(..))
+        ));
+
+        assert_eq!(storage.remove_alias("also").unwrap(), second);
+        assert!(matches!(
+            storage.remove_alias("also"),
+            Err(CoreError::AliasNotFound(_))
+        ));
+        assert!(storage.resolve("also").is_err());
+
+        // Deleting an engram drops its aliases
+        storage.delete("auth-fix").unwrap();
+        assert!(storage.aliases().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_ancestry() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        // oldest, middle and newest by created_at; newest continues oldest
+        let mut ids: Vec<EngramId> = Vec::new();
+        for hours_ago in [3, 2, 1] {
+            let mut data = make_test_data();
+            data.manifest.created_at = Utc::now() - chrono::Duration::hours(hours_ago);
+            if hours_ago == 1 {
+                data.lineage.parent_engram = Some(ids[0].clone());
+            }
+            ids.push(storage.create(&data).unwrap());
+        }
+        let [oldest, middle, newest] = [&ids[0], &ids[1], &ids[2]].map(|id| id.as_str());
+
+        assert_eq!(storage.resolve("HEAD").unwrap(), newest);
+        assert_eq!(storage.resolve("HEAD~0").unwrap(), newest);
+        assert_eq!(storage.resolve("HEAD~1").unwrap(), middle);
+        assert_eq!(storage.resolve("head~").unwrap(), middle);
+        assert_eq!(storage.resolve("HEAD~2").unwrap(), oldest);
+        assert_eq!(storage.resolve("HEAD~1~1").unwrap(), oldest);
+        let err = storage.resolve("HEAD~3").unwrap_err().to_string();
+        assert!(err.contains("HEAD~3 (only 3 engram(s) exist)"), "{err}");
+
+        // <id>~N follows the parent engram, then falls back to recency
+        assert_eq!(storage.resolve(&format!("{newest}~1")).unwrap(), oldest);
+        assert_eq!(
+            storage.resolve(&format!("{}~", &middle[..8])).unwrap(),
+            oldest
+        );
+        storage.set_alias("latest", newest).unwrap();
+        assert_eq!(storage.resolve("latest~1").unwrap(), oldest);
+        let err = storage.resolve("latest~2").unwrap_err().to_string();
+        assert!(err.contains("has no parent or older engram"), "{err}");
+
+        assert!(matches!(
+            storage.resolve("HEAD~x"),
+            Err(CoreError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_ensure_initialized() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+
+        let err = storage.ensure_initialized(InitPolicy::Error).unwrap_err();
+        assert!(matches!(&err, CoreError::NotInitialized(cmd) if cmd == "engram init"));
+        repo.remote("upstream", "https://example.com/up.git")
+            .unwrap();
+        assert_eq!(storage.init_command(), "engram init --remote upstream");
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        let err = storage.ensure_initialized(InitPolicy::Error).unwrap_err();
+        assert!(
+            err.to_string().contains("`engram init --remote origin`"),
+            "{err}"
+        );
+
+        assert!(!storage
+            .ensure_initialized(InitPolicy::WarnAndContinue)
+            .unwrap());
+        assert!(!storage.is_initialized());
+
+        assert!(storage.ensure_initialized(InitPolicy::AutoInit).unwrap());
+        assert!(storage.is_initialized());
+        let config = repo.config().unwrap();
+        let fetch = config.get_string("remote.origin.fetch").unwrap();
+        assert_eq!(fetch, FETCH_REFSPEC.0);
+
+        // Once initialized, every policy passes
+        assert!(storage.ensure_initialized(InitPolicy::Error).unwrap());
+    }
+
+    #[test]
+    fn test_uninit() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.remote_add_push("origin", "refs/heads/main:refs/heads/main")
+            .unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let data = make_test_data();
+        storage.create(&data).unwrap();
+
+        let report = storage.uninit(false).unwrap();
+        assert!(!storage.is_initialized());
+        assert!(report
+            .config_keys
+            .contains(&"engram.autoCapture".to_string()));
+        assert_eq!(
+            report.refspecs,
+            [
+                (
+                    "remote.origin.fetch".to_string(),
+                    "refs/engrams/*:refs/engrams/*".to_string()
+                ),
+                (
+                    "remote.origin.push".to_string(),
+                    "refs/engrams/*:refs/engrams/*".to_string()
+                ),
+            ]
+        );
+        assert_eq!(report.paths, [repo.path().join(ENGRAM_HEAD_FILE)]);
+        assert_eq!(report.refs_deleted, 0);
+
+        let remote = repo.find_remote("origin").unwrap();
+        let fetch: Vec<_> = remote
+            .fetch_refspecs()
+            .unwrap()
+            .iter()
+            .flatten()
+            .map(String::from)
+            .collect();
+        assert_eq!(fetch, ["+refs/heads/*:refs/remotes/origin/*"]);
+        let push: Vec<_> = remote
+            .push_refspecs()
+            .unwrap()
+            .iter()
+            .flatten()
+            .map(String::from)
+            .collect();
+        assert_eq!(push, ["refs/heads/main:refs/heads/main"]);
+
+        // The engram outlives uninit unless purged
+        storage.init().unwrap();
+        assert_eq!(storage.list(&ListOptions::default()).unwrap().len(), 1);
+        let report = storage.uninit(true).unwrap();
+        assert_eq!(report.refs_deleted, 1);
+        assert!(storage.list(&ListOptions::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_update_manifest_and_intent() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let mut data = make_test_data();
+        data.transcript.entries.push(TranscriptEntry {
+            timestamp: Utc::now(),
+            role: Role::User,
+            content: TranscriptContent::Text {
+                text: "Test request".into(),
+            },
+            token_count: None,
+        });
+        let id = storage.create(&data).unwrap();
+        let ref_name = refs::engram_ref_name(&id);
+        let before = storage.repo().refname_to_id(&ref_name).unwrap();
+
+        let manifest = storage
+            .update_manifest(&id.as_str()[..8], |m| m.tags.push("auth".into()))
+            .unwrap();
+        assert_eq!(manifest.tags, ["auth"]);
+        let after = storage.repo().refname_to_id(&ref_name).unwrap();
+        let commit = storage.repo().find_commit(after).unwrap();
+        assert_eq!(commit.parent_id(0).unwrap(), before);
+
+        let loaded = storage.read(id.as_str()).unwrap();
+        assert_eq!(loaded.manifest.tags, ["auth"]);
+        assert_eq!(loaded.transcript, data.transcript);
+
+        // An update that changes nothing writes nothing
+        storage.update_manifest(id.as_str(), |_| {}).unwrap();
+        assert_eq!(storage.repo().refname_to_id(&ref_name).unwrap(), after);
+
+        let (_, intent) = storage
+            .update(id.as_str(), |m, i| {
+                m.summary = Some("Fixed summary".into());
+                i.interpreted_goal = Some("Make it faster".into());
+            })
+            .unwrap();
+        assert_eq!(intent.interpreted_goal.as_deref(), Some("Make it faster"));
+        let loaded = storage.read(id.as_str()).unwrap();
+        assert_eq!(loaded.manifest.summary.as_deref(), Some("Fixed summary"));
+        assert_eq!(loaded.intent, intent);
+        let commit = storage
+            .repo()
+            .find_commit(storage.repo().refname_to_id(&ref_name).unwrap())
+            .unwrap();
+        assert_eq!(commit.parent_id(0).unwrap(), after);
+    }
+
+    #[test]
+    fn test_blocked_source_hashes() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let mut data = make_test_data();
+        data.manifest.source_hash = Some("abc123".into());
+        let id = storage.create(&data).unwrap();
+        assert_eq!(storage.find_by_source_hash("abc123"), Some(id.clone()));
+
+        storage.delete(id.as_str()).unwrap();
+        assert_eq!(storage.find_by_source_hash("abc123"), None);
+        assert!(!storage.is_source_hash_blocked("abc123"));
+
+        storage.block_source_hash("abc123").unwrap();
+        storage.block_source_hash("abc123").unwrap();
+        storage.block_source_hash("def456").unwrap();
+        assert!(storage.is_source_hash_blocked("abc123"));
+        assert!(storage.is_source_hash_blocked("def456"));
+        assert!(!storage.is_source_hash_blocked("abc"));
+        let file = std::fs::read_to_string(tmp.path().join(".git").join(DELETED_SOURCES_FILE));
+        assert_eq!(file.unwrap(), "abc123\ndef456\n");
+    }
+
+    #[test]
+    fn test_notes() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let id = storage.create(&make_test_data()).unwrap();
+        let (_, original) = refs::resolve_engram_ref(storage.repo(), id.as_str()).unwrap();
+        assert!(storage.read_notes(id.as_str()).unwrap().is_empty());
+
+        storage
+            .add_note(&id.as_str()[..8], "alice", "Reverted in a later session")
+            .unwrap();
+        storage
+            .add_note(id.as_str(), "bot", "Revert undone")
+            .unwrap();
+
+        let notes = storage.read_notes(id.as_str()).unwrap();
+        let texts: Vec<_> = notes
+            .iter()
+            .map(|n| (n.author.as_str(), n.text.as_str()))
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                ("alice", "Reverted in a later session"),
+                ("bot", "Revert undone")
+            ]
+        );
+
+        // The engram itself is unchanged, and its old commit is kept as the
+        // parent so the ref only moves forward
+        let loaded = storage.read(id.as_str()).unwrap();
+        assert_eq!(loaded.intent.original_request, "Test request");
+        let (_, noted) = refs::resolve_engram_ref(storage.repo(), id.as_str()).unwrap();
+        assert!(storage.repo().graph_descendant_of(noted, original).unwrap());
+        assert_eq!(storage.list(&ListOptions::default()).unwrap().len(), 1);
+    }
+
     #[test]
     fn test_list_with_filter() {
         let tmp = TempDir::new().unwrap();
@@ -417,4 +1227,183 @@ mod tests {
         let manifests = storage.list(&opts).unwrap();
         assert_eq!(manifests.len(), 3);
     }
+
+    #[test]
+    fn test_list_with_offset() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let start = chrono::Utc::now();
+        for i in 0..5 {
+            let mut data = make_test_data();
+            data.manifest.summary = Some(format!("engram {i}"));
+            data.manifest.created_at = start + chrono::Duration::minutes(i);
+            storage.create(&data).unwrap();
+        }
+
+        let page = |offset, limit| -> Vec<String> {
+            let opts = ListOptions {
+                offset,
+                limit,
+                ..Default::default()
+            };
+            let manifests = storage.list(&opts).unwrap();
+            manifests.into_iter().map(|m| m.summary.unwrap()).collect()
+        };
+        assert_eq!(page(1, Some(2)), ["engram 3", "engram 2"]);
+        assert_eq!(page(3, None), ["engram 1", "engram 0"]);
+        assert!(page(5, Some(2)).is_empty());
+    }
+
+    #[test]
+    fn test_list_with_cost_and_token_bounds() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        for (name, cost, tokens) in [
+            ("cheap", Some(4.99), 99_999),
+            ("exact", Some(5.0), 100_000),
+            ("pricey", Some(12.0), 250_000),
+            ("unpriced", None, 500_000),
+        ] {
+            let mut data = make_test_data();
+            data.manifest.agent.name = name.into();
+            data.manifest.token_usage.cost_usd = cost;
+            data.manifest.token_usage.total_tokens = tokens;
+            storage.create(&data).unwrap();
+        }
+        let names = |opts: ListOptions| -> Vec<String> {
+            let mut names: Vec<_> = storage
+                .list(&opts)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.agent.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        // Bounds are inclusive; no recorded cost never satisfies a cost bound
+        let min_cost = ListOptions {
+            min_cost: Some(5.0),
+            ..Default::default()
+        };
+        assert_eq!(names(min_cost), ["exact", "pricey"]);
+        let max_cost = ListOptions {
+            max_cost: Some(5.0),
+            ..Default::default()
+        };
+        assert_eq!(names(max_cost), ["cheap", "exact"]);
+
+        // Token bounds apply regardless of cost
+        let min_tokens = ListOptions {
+            min_tokens: Some(100_000),
+            ..Default::default()
+        };
+        assert_eq!(names(min_tokens), ["exact", "pricey", "unpriced"]);
+        let window = ListOptions {
+            min_tokens: Some(100_000),
+            max_tokens: Some(250_000),
+            ..Default::default()
+        };
+        assert_eq!(names(window), ["exact", "pricey"]);
+    }
+
+    #[test]
+    fn test_list_with_tag_and_dates() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        for (name, created_at, tags) in [
+            ("january", "2026-01-15T10:00:00Z", vec!["auth"]),
+            ("february", "2026-02-01T00:00:00Z", vec!["auth", "perf"]),
+            ("march", "2026-03-20T18:30:00Z", vec![]),
+        ] {
+            let mut data = make_test_data();
+            data.manifest.agent.name = name.into();
+            data.manifest.created_at = created_at.parse().unwrap();
+            data.manifest.tags = tags.into_iter().map(String::from).collect();
+            storage.create(&data).unwrap();
+        }
+        let names = |opts: ListOptions| -> Vec<String> {
+            storage
+                .list(&opts)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.agent.name)
+                .collect()
+        };
+
+        let auth = ListOptions {
+            tag: Some("auth".into()),
+            ..Default::default()
+        };
+        assert_eq!(names(auth), ["february", "january"]);
+
+        // Bounds are inclusive
+        let window = ListOptions {
+            since: Some("2026-02-01T00:00:00Z".parse().unwrap()),
+            until: Some("2026-03-20T18:30:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(names(window), ["march", "february"]);
+        let before_march = ListOptions {
+            until: Some("2026-03-01T00:00:00Z".parse().unwrap()),
+            tag: Some("perf".into()),
+            ..Default::default()
+        };
+        assert_eq!(names(before_march), ["february"]);
+    }
+
+    #[test]
+    fn test_list_hiding_trivial() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        for (name, seconds, changed) in [
+            ("trivial", Some(5), false),
+            ("edited", Some(5), true),
+            ("long", Some(600), false),
+            ("unfinished", None, false),
+        ] {
+            let mut data = make_test_data();
+            data.manifest.agent.name = name.into();
+            data.manifest.finished_at =
+                seconds.map(|s| data.manifest.created_at + chrono::Duration::seconds(s));
+            if changed {
+                data.operations.file_changes.push(FileChange {
+                    path: "src/lib.rs".into(),
+                    change_type: FileChangeType::Modified,
+                    lines_added: None,
+                    lines_removed: None,
+                });
+            }
+            storage.create(&data).unwrap();
+        }
+        let mut names: Vec<_> = storage
+            .list(&ListOptions {
+                hide_trivial: Some(TrivialThresholds::default()),
+                ..Default::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|m| m.agent.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["edited", "long", "unfinished"]);
+
+        let trivial = storage.list(&ListOptions::default()).unwrap();
+        let trivial = trivial.iter().find(|m| m.agent.name == "trivial").unwrap();
+        assert!(storage
+            .is_trivial(trivial.id.as_str(), &TrivialThresholds::default())
+            .unwrap());
+    }
 }
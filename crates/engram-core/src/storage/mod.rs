@@ -1,6 +1,10 @@
+pub mod aliases;
 pub mod git_backend;
 pub mod objects;
 pub mod read;
 pub mod refs;
+pub mod usage;
 
-pub use git_backend::{GitStorage, ListOptions};
+pub use git_backend::{GitStorage, InitPolicy, ListOptions, UninitReport};
+pub use read::EngramPart;
+pub use usage::SpaceUsage;
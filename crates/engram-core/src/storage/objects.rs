@@ -1,7 +1,13 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+
 use git2::{Oid, Repository, Signature};
 
 use crate::error::CoreError;
-use crate::model::EngramData;
+use crate::model::{AttachmentData, EngramData, Note};
+
+/// Name of the blob holding an engram's notes, when it has any.
+pub const NOTES_FILE: &str = "notes.jsonl";
 
 /// Build the engram tree object from EngramData.
 ///
@@ -16,6 +22,8 @@ use crate::model::EngramData;
 ///        -> blob "transcript.jsonl"
 ///        -> blob "operations.json"
 ///        -> blob "lineage.json"
+///        -> tree "attachments" (only when the engram has attachments)
+///        -> blob "notes.jsonl" (added later by [`append_note_objects`])
 pub fn create_engram_objects(repo: &Repository, data: &EngramData) -> Result<Oid, CoreError> {
     // 1. Serialize each component to bytes
     let manifest_bytes = serde_json::to_vec_pretty(&data.manifest)?;
@@ -38,6 +46,25 @@ pub fn create_engram_objects(repo: &Repository, data: &EngramData) -> Result<Oid
     builder.insert("transcript.jsonl", transcript_oid, 0o100644)?;
     builder.insert("operations.json", operations_oid, 0o100644)?;
     builder.insert("lineage.json", lineage_oid, 0o100644)?;
+    if !data.attachments.is_empty() {
+        let mut attachments = repo.treebuilder(None)?;
+        for attachment in &data.attachments {
+            if attachment.name.is_empty() || attachment.name.contains(['/', '\\']) {
+                return Err(CoreError::InvalidAttachment(attachment.name.clone()));
+            }
+            let oid = match &attachment.data {
+                AttachmentData::Bytes(bytes) => repo.blob(bytes)?,
+                AttachmentData::File(file) => {
+                    let mut reader = BufReader::new(File::open((**file).as_ref())?);
+                    let mut writer = repo.blob_writer(None)?;
+                    io::copy(&mut reader, &mut writer)?;
+                    writer.commit()?
+                }
+            };
+            attachments.insert(attachment.name.as_str(), oid, 0o100644)?;
+        }
+        builder.insert("attachments", attachments.write()?, 0o040000)?;
+    }
     let tree_oid = builder.write()?;
 
     // 4. Create commit (no parent — standalone orphan)
@@ -49,6 +76,136 @@ pub fn create_engram_objects(repo: &Repository, data: &EngramData) -> Result<Oid
     Ok(commit_oid)
 }
 
+/// Append `note` to the `notes.jsonl` of the engram at `commit_oid`.
+///
+/// Returns a new commit whose tree is the old one with the longer notes
+/// blob, and whose parent is the old commit, so that pushing it is a
+/// fast-forward.
+pub fn append_note_objects(
+    repo: &Repository,
+    commit_oid: Oid,
+    note: &Note,
+) -> Result<Oid, CoreError> {
+    let parent = repo.find_commit(commit_oid)?;
+    let tree = parent.tree()?;
+    let mut notes = match tree.get_name(NOTES_FILE) {
+        Some(entry) => repo.find_blob(entry.id())?.content().to_vec(),
+        None => Vec::new(),
+    };
+    notes.extend(note.to_jsonl()?);
+
+    let mut builder = repo.treebuilder(Some(&tree))?;
+    builder.insert(NOTES_FILE, repo.blob(&notes)?, 0o100644)?;
+    let tree = repo.find_tree(builder.write()?)?;
+    let sig = Signature::now("engram", "engram@local")?;
+    let message = format!("engram: note by {}", note.author);
+    Ok(repo.commit(None, &sig, &sig, &message, &tree, &[&parent])?)
+}
+
+/// Replace blobs in the tree of the engram at `commit_oid`.
+///
+/// Like [`append_note_objects`], returns a new commit on top of the old one,
+/// so that the change pushes as a fast-forward.
+pub fn amend_engram_objects(
+    repo: &Repository,
+    commit_oid: Oid,
+    files: &[(&str, &[u8])],
+    message: &str,
+) -> Result<Oid, CoreError> {
+    let parent = repo.find_commit(commit_oid)?;
+    let tree = parent.tree()?;
+    let mut builder = repo.treebuilder(Some(&tree))?;
+    for (name, bytes) in files {
+        builder.insert(*name, repo.blob(bytes)?, 0o100644)?;
+    }
+    let tree = repo.find_tree(builder.write()?)?;
+    let sig = Signature::now("engram", "engram@local")?;
+    Ok(repo.commit(None, &sig, &sig, message, &tree, &[&parent])?)
+}
+
+/// Combine two versions of an engram that grew apart, such as after two
+/// clones each added a note to it.
+///
+/// Notes from both sides are kept, oldest first. Any other file must have
+/// changed on one side only. Returns a commit with both versions as
+/// parents, or `None` when both sides changed the same file differently or
+/// the versions share no history.
+pub fn merge_engram_objects(
+    repo: &Repository,
+    ours: Oid,
+    theirs: Oid,
+) -> Result<Option<Oid>, CoreError> {
+    let base = match repo.merge_base(ours, theirs) {
+        Ok(base) => base,
+        Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let ours = repo.find_commit(ours)?;
+    let theirs = repo.find_commit(theirs)?;
+    let base_tree = repo.find_commit(base)?.tree()?;
+    let (our_tree, their_tree) = (ours.tree()?, theirs.tree()?);
+
+    let mut names: Vec<String> = our_tree
+        .iter()
+        .chain(their_tree.iter())
+        .filter_map(|entry| entry.name().map(String::from))
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let mut builder = repo.treebuilder(Some(&our_tree))?;
+    for name in &names {
+        let entry = |tree: &git2::Tree| {
+            tree.get_name(name)
+                .map(|entry| (entry.id(), entry.filemode_raw()))
+        };
+        let (base, mine, other) = (entry(&base_tree), entry(&our_tree), entry(&their_tree));
+        if mine == other || other == base {
+            continue;
+        }
+        if mine == base {
+            match other {
+                Some((oid, mode)) => {
+                    builder.insert(name, oid, mode)?;
+                }
+                None => builder.remove(name)?,
+            }
+        } else if name == NOTES_FILE {
+            let notes = merge_notes(repo, mine, other)?;
+            builder.insert(name, repo.blob(&notes)?, 0o100644)?;
+        } else {
+            return Ok(None);
+        }
+    }
+
+    let tree = repo.find_tree(builder.write()?)?;
+    let sig = Signature::now("engram", "engram@local")?;
+    let oid = repo.commit(None, &sig, &sig, "engram: merge", &tree, &[&ours, &theirs])?;
+    Ok(Some(oid))
+}
+
+/// The notes of both `notes.jsonl` blobs, each once, oldest first.
+fn merge_notes(
+    repo: &Repository,
+    ours: Option<(Oid, i32)>,
+    theirs: Option<(Oid, i32)>,
+) -> Result<Vec<u8>, CoreError> {
+    let mut notes = Vec::new();
+    for (oid, _) in ours.into_iter().chain(theirs) {
+        for note in Note::from_jsonl(repo.find_blob(oid)?.content())? {
+            if !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+    }
+    notes.sort_by_key(|note| note.created_at);
+    let mut bytes = Vec::new();
+    for note in &notes {
+        bytes.extend(note.to_jsonl()?);
+    }
+    Ok(bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,6 +231,7 @@ mod tests {
                 tags: vec![],
                 capture_mode: CaptureMode::Sdk,
                 source_hash: None,
+                source_path: None,
             },
             intent: Intent {
                 original_request: "Test request".into(),
@@ -85,6 +243,7 @@ mod tests {
             transcript: Transcript::default(),
             operations: Operations::default(),
             lineage: Lineage::default(),
+            attachments: Vec::new(),
         }
     }
 
@@ -109,4 +268,82 @@ mod tests {
         assert!(tree.get_name("operations.json").is_some());
         assert!(tree.get_name("lineage.json").is_some());
     }
+
+    #[test]
+    fn test_attachments_subtree() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let mut data = make_test_engram_data();
+        data.attachments.push(Attachment::new(
+            "pty-output.raw",
+            b"\x1b[32mok\x1b[0m".to_vec(),
+        ));
+
+        let spooled = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(spooled.path(), b"streamed from disk").unwrap();
+        data.attachments.push(Attachment::from_file(
+            "spooled.raw",
+            std::sync::Arc::new(spooled.into_temp_path()),
+        ));
+
+        let commit_oid = create_engram_objects(&repo, &data).unwrap();
+        let tree = repo.find_commit(commit_oid).unwrap().tree().unwrap();
+        assert_eq!(tree.len(), 6);
+        let read = |name| crate::storage::read::read_attachment(&repo, commit_oid, name).unwrap();
+        assert_eq!(read("pty-output.raw").unwrap(), b"\x1b[32mok\x1b[0m");
+        assert_eq!(read("spooled.raw").unwrap(), b"streamed from disk");
+        assert_eq!(read("missing"), None);
+        let loaded = crate::storage::read::read_engram(&repo, commit_oid).unwrap();
+        assert!(loaded.attachments.is_empty());
+
+        data.attachments = vec![Attachment::new("../escape", Vec::new())];
+        assert!(create_engram_objects(&repo, &data).is_err());
+    }
+
+    #[test]
+    fn test_merge_keeps_notes_from_both_sides() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let base = create_engram_objects(&repo, &make_test_engram_data()).unwrap();
+        let shared = append_note_objects(&repo, base, &Note::new("carol", "Shared")).unwrap();
+        let ours = append_note_objects(&repo, shared, &Note::new("alice", "Ours")).unwrap();
+        let theirs = append_note_objects(&repo, shared, &Note::new("bob", "Theirs")).unwrap();
+        let theirs = amend_engram_objects(
+            &repo,
+            theirs,
+            &[("intent.md", b"# Intent\n\nAmended\n")],
+            "engram: amend",
+        )
+        .unwrap();
+
+        let merged = merge_engram_objects(&repo, ours, theirs).unwrap().unwrap();
+        let commit = repo.find_commit(merged).unwrap();
+        assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [ours, theirs]);
+        let notes = crate::storage::read::read_notes(&repo, merged).unwrap();
+        let texts: Vec<_> = notes.iter().map(|n| n.text.as_str()).collect();
+        assert_eq!(texts, ["Shared", "Ours", "Theirs"]);
+        let intent = crate::storage::read::read_part(
+            &repo,
+            merged,
+            crate::storage::read::EngramPart::Intent,
+        )
+        .unwrap();
+        assert_eq!(intent, b"# Intent\n\nAmended\n");
+    }
+
+    #[test]
+    fn test_merge_refuses_conflicting_edits() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let base = create_engram_objects(&repo, &make_test_engram_data()).unwrap();
+        let edit = |text: &[u8]| {
+            amend_engram_objects(&repo, base, &[("manifest.json", text)], "engram: amend").unwrap()
+        };
+        let (ours, theirs) = (edit(b"{\"a\": 1}"), edit(b"{\"b\": 2}"));
+        assert_eq!(merge_engram_objects(&repo, ours, theirs).unwrap(), None);
+
+        // Unrelated histories can't be merged either
+        let other = create_engram_objects(&repo, &make_test_engram_data()).unwrap();
+        assert_eq!(merge_engram_objects(&repo, base, other).unwrap(), None);
+    }
 }
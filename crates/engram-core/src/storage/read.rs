@@ -1,9 +1,12 @@
 use git2::{Oid, Repository};
 
 use crate::error::CoreError;
-use crate::model::{EngramData, Intent, Lineage, Manifest, Operations, Transcript};
+use crate::model::{EngramData, Intent, Lineage, Manifest, Note, Operations, Transcript};
+use crate::storage::objects::NOTES_FILE;
 
 /// Read an engram's data from its commit Oid.
+///
+/// Attachments are left out, as they can be large; see [`read_attachment`].
 pub fn read_engram(repo: &Repository, commit_oid: Oid) -> Result<EngramData, CoreError> {
     let commit = repo.find_commit(commit_oid)?;
     let tree = commit.tree()?;
@@ -20,9 +23,43 @@ pub fn read_engram(repo: &Repository, commit_oid: Oid) -> Result<EngramData, Cor
         transcript,
         operations,
         lineage,
+        attachments: Vec::new(),
     })
 }
 
+/// One file of an engram's tree, for reading it without the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngramPart {
+    Manifest,
+    Intent,
+    Transcript,
+    Operations,
+    Lineage,
+}
+
+impl EngramPart {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Manifest => "manifest.json",
+            Self::Intent => "intent.md",
+            Self::Transcript => "transcript.jsonl",
+            Self::Operations => "operations.json",
+            Self::Lineage => "lineage.json",
+        }
+    }
+}
+
+/// Read the raw bytes of one part of an engram.
+pub fn read_part(
+    repo: &Repository,
+    commit_oid: Oid,
+    part: EngramPart,
+) -> Result<Vec<u8>, CoreError> {
+    let commit = repo.find_commit(commit_oid)?;
+    let tree = commit.tree()?;
+    read_blob_bytes(repo, &tree, part.file_name())
+}
+
 /// Read only the manifest (fast path for listing).
 pub fn read_manifest(repo: &Repository, commit_oid: Oid) -> Result<Manifest, CoreError> {
     let commit = repo.find_commit(commit_oid)?;
@@ -30,6 +67,34 @@ pub fn read_manifest(repo: &Repository, commit_oid: Oid) -> Result<Manifest, Cor
     read_blob_json::<Manifest>(repo, &tree, "manifest.json")
 }
 
+/// Read the notes added to an engram, oldest first (none for most engrams).
+pub fn read_notes(repo: &Repository, commit_oid: Oid) -> Result<Vec<Note>, CoreError> {
+    let tree = repo.find_commit(commit_oid)?.tree()?;
+    let Some(entry) = tree.get_name(NOTES_FILE) else {
+        return Ok(Vec::new());
+    };
+    let blob = repo.find_blob(entry.id())?;
+    Note::from_jsonl(blob.content())
+}
+
+/// Read one file of the optional `attachments/` subtree, if the engram has
+/// it (most have none).
+pub fn read_attachment(
+    repo: &Repository,
+    commit_oid: Oid,
+    name: &str,
+) -> Result<Option<Vec<u8>>, CoreError> {
+    let tree = repo.find_commit(commit_oid)?.tree()?;
+    let Some(entry) = tree.get_name("attachments") else {
+        return Ok(None);
+    };
+    let subtree = repo.find_tree(entry.id())?;
+    let Some(entry) = subtree.get_name(name) else {
+        return Ok(None);
+    };
+    Ok(Some(repo.find_blob(entry.id())?.content().to_vec()))
+}
+
 fn read_blob_bytes(repo: &Repository, tree: &git2::Tree, name: &str) -> Result<Vec<u8>, CoreError> {
     let entry = tree
         .get_name(name)
@@ -88,6 +153,7 @@ mod tests {
                 tags: vec!["auth".into()],
                 capture_mode: CaptureMode::Wrapper,
                 source_hash: None,
+                source_path: None,
             },
             intent: Intent {
                 original_request: "Add OAuth2 authentication".into(),
@@ -126,6 +192,7 @@ mod tests {
                 branch: Some("main".into()),
                 ..Default::default()
             },
+            attachments: Vec::new(),
         };
 
         // Store
@@ -182,6 +249,7 @@ mod tests {
                 tags: vec![],
                 capture_mode: CaptureMode::Sdk,
                 source_hash: None,
+                source_path: None,
             },
             intent: Intent {
                 original_request: "test".into(),
@@ -193,11 +261,15 @@ mod tests {
             transcript: Transcript::default(),
             operations: Operations::default(),
             lineage: Lineage::default(),
+            attachments: Vec::new(),
         };
 
         let commit_oid = create_engram_objects(&repo, &data).unwrap();
         let manifest = read_manifest(&repo, commit_oid).unwrap();
         assert_eq!(data.manifest.id, manifest.id);
         assert_eq!(data.manifest.summary, manifest.summary);
+
+        let intent = read_part(&repo, commit_oid, EngramPart::Intent).unwrap();
+        assert_eq!(intent, data.intent.to_markdown().into_bytes());
     }
 }
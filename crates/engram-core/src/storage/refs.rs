@@ -3,6 +3,8 @@ use git2::{Oid, Repository};
 use crate::error::CoreError;
 use crate::model::EngramId;
 
+use super::aliases;
+
 /// The ref prefix for all engram refs.
 pub const ENGRAM_REF_PREFIX: &str = "refs/engrams/";
 
@@ -11,14 +13,32 @@ pub fn engram_ref_name(id: &EngramId) -> String {
     format!("refs/engrams/{}/{}", id.fanout_prefix(), id.as_str())
 }
 
-/// Create or update the ref for an engram.
+/// Create the ref for a new engram, failing if one with this ID exists.
 pub fn create_engram_ref(
     repo: &Repository,
     id: &EngramId,
     commit_oid: Oid,
 ) -> Result<(), CoreError> {
     let ref_name = engram_ref_name(id);
-    repo.reference(&ref_name, commit_oid, true, "engram: create")?;
+    match repo.reference(&ref_name, commit_oid, false, "engram: create") {
+        Ok(_) => Ok(()),
+        Err(e) if e.code() == git2::ErrorCode::Exists => {
+            Err(CoreError::AlreadyExists(id.to_string()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Move the ref for an engram from `old` to `new`, failing if another
+/// writer moved it first.
+pub fn update_engram_ref(
+    repo: &Repository,
+    id: &EngramId,
+    new: Oid,
+    old: Oid,
+    log_message: &str,
+) -> Result<(), CoreError> {
+    repo.reference_matching(&engram_ref_name(id), new, true, old, log_message)?;
     Ok(())
 }
 
@@ -50,7 +70,8 @@ pub fn list_engram_refs(repo: &Repository) -> Result<Vec<(EngramId, Oid)>, CoreE
     Ok(results)
 }
 
-/// Resolve an engram ID (or prefix) to its full ID and commit Oid.
+/// Resolve an engram ID, alias or ID prefix to its full ID and commit Oid.
+/// Alias names always hold a non-hex character, so they never shadow an ID.
 pub fn resolve_engram_ref(
     repo: &Repository,
     id_or_prefix: &str,
@@ -64,6 +85,18 @@ pub fn resolve_engram_ref(
         }
     }
 
+    // Then aliases
+    if let Some(id) = aliases::lookup_alias(repo, id_or_prefix)? {
+        let oid = repo
+            .find_reference(&engram_ref_name(&id))
+            .ok()
+            .and_then(|reference| reference.target())
+            .ok_or_else(|| CoreError::NotFound {
+                id: format!("{} (alias {id_or_prefix})", id.as_str()),
+            })?;
+        return Ok((id, oid));
+    }
+
     // Try prefix match
     let all_refs = list_engram_refs(repo)?;
     let matches: Vec<_> = all_refs
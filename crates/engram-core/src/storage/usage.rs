@@ -0,0 +1,200 @@
+//! How much room engrams take in the object database, for `engram gc` to
+//! report what deleting some of them would reclaim.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use git2::{ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+
+use crate::error::CoreError;
+use crate::model::EngramId;
+
+/// The space taken by a set of engrams.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpaceUsage {
+    /// Each engram and the size of its blobs, in the order asked for.
+    pub engrams: Vec<(EngramId, u64)>,
+    /// The size of all their blobs, counting a blob they share only once.
+    pub total_bytes: u64,
+    /// The part of `total_bytes` no other engram references: what deleting
+    /// them frees once Git prunes unreachable objects.
+    pub reclaimable_bytes: u64,
+    /// How many of their blobs another engram, in the set or not, also
+    /// references.
+    pub shared_blobs: usize,
+}
+
+/// The blobs reachable from an engram's commit, with their sizes: every
+/// version of its files, through amends and notes.
+pub fn engram_blobs(repo: &Repository, commit_oid: Oid) -> Result<HashMap<Oid, u64>, CoreError> {
+    let odb = repo.odb()?;
+    let mut blobs = HashMap::new();
+    let mut trees = HashSet::new();
+    let mut commits = vec![commit_oid];
+    let mut seen = HashSet::new();
+    while let Some(oid) = commits.pop() {
+        if !seen.insert(oid) {
+            continue;
+        }
+        let commit = repo.find_commit(oid)?;
+        commits.extend(commit.parent_ids());
+        let tree = commit.tree()?;
+        if !trees.insert(tree.id()) {
+            continue;
+        }
+        let mut ids = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |_, entry| {
+            if entry.kind() == Some(ObjectType::Blob) {
+                ids.push(entry.id());
+            }
+            TreeWalkResult::Ok
+        })?;
+        for id in ids {
+            if let Entry::Vacant(slot) = blobs.entry(id) {
+                let (size, _) = odb.read_header(id)?;
+                slot.insert(size as u64);
+            }
+        }
+    }
+    Ok(blobs)
+}
+
+/// The space taken by the engrams in `ids`, measured against `all`: every
+/// engram in the repository and its commit.
+pub fn space_usage(
+    repo: &Repository,
+    all: &[(EngramId, Oid)],
+    ids: &[EngramId],
+) -> Result<SpaceUsage, CoreError> {
+    let wanted: HashSet<&EngramId> = ids.iter().collect();
+    let mut sizes: HashMap<&EngramId, u64> = HashMap::new();
+    let mut references: HashMap<Oid, usize> = HashMap::new();
+    let mut selected: HashMap<Oid, u64> = HashMap::new();
+    let mut kept: HashSet<Oid> = HashSet::new();
+
+    for (id, oid) in all {
+        let blobs = engram_blobs(repo, *oid)?;
+        for blob in blobs.keys() {
+            *references.entry(*blob).or_default() += 1;
+        }
+        if wanted.contains(id) {
+            sizes.insert(id, blobs.values().sum());
+            selected.extend(blobs);
+        } else {
+            kept.extend(blobs.into_keys());
+        }
+    }
+
+    Ok(SpaceUsage {
+        engrams: ids
+            .iter()
+            .map(|id| (id.clone(), sizes.get(id).copied().unwrap_or_default()))
+            .collect(),
+        total_bytes: selected.values().sum(),
+        reclaimable_bytes: selected
+            .iter()
+            .filter(|(blob, _)| !kept.contains(blob))
+            .map(|(_, size)| size)
+            .sum(),
+        shared_blobs: selected.keys().filter(|blob| references[blob] > 1).count(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::*;
+    use crate::storage::objects::{amend_engram_objects, create_engram_objects};
+    use tempfile::TempDir;
+
+    fn make_data(request: &str) -> EngramData {
+        EngramData::builder()
+            .agent("test-agent")
+            .capture_mode(CaptureMode::Sdk)
+            .request(request)
+            .build()
+    }
+
+    fn blob_size(repo: &Repository, commit_oid: Oid, name: &str) -> u64 {
+        let tree = repo.find_commit(commit_oid).unwrap().tree().unwrap();
+        let blob = repo.find_blob(tree.get_name(name).unwrap().id()).unwrap();
+        blob.size() as u64
+    }
+
+    #[test]
+    fn test_engram_blobs_include_history() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let first = create_engram_objects(&repo, &make_data("Fix auth")).unwrap();
+        let before = engram_blobs(&repo, first).unwrap();
+        assert_eq!(before.len(), 5);
+        assert_eq!(
+            before.values().sum::<u64>(),
+            [
+                "manifest.json",
+                "intent.md",
+                "transcript.jsonl",
+                "operations.json",
+                "lineage.json"
+            ]
+            .iter()
+            .map(|name| blob_size(&repo, first, name))
+            .sum::<u64>()
+        );
+
+        let amended =
+            amend_engram_objects(&repo, first, &[("intent.md", b"# Rewritten")], "amend").unwrap();
+        let after = engram_blobs(&repo, amended).unwrap();
+        // The old intent stays reachable through the first commit
+        assert_eq!(after.len(), 6);
+        assert!(before.keys().all(|blob| after.contains_key(blob)));
+    }
+
+    #[test]
+    fn test_space_usage_counts_shared_blobs_once() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        // a and b share their intent; all three share the empty transcript,
+        // operations and lineage
+        let a = make_data("Fix auth");
+        let b = make_data("Fix auth");
+        let c = make_data("Fix cache");
+        let all: Vec<_> = [&a, &b, &c]
+            .iter()
+            .map(|d| {
+                let oid = create_engram_objects(&repo, d).unwrap();
+                (d.manifest.id.clone(), oid)
+            })
+            .collect();
+        let manifest = |i: usize| blob_size(&repo, all[i].1, "manifest.json");
+        let intent = blob_size(&repo, all[0].1, "intent.md");
+        let common: u64 = ["transcript.jsonl", "operations.json", "lineage.json"]
+            .iter()
+            .map(|name| blob_size(&repo, all[0].1, name))
+            .sum();
+
+        let one = space_usage(&repo, &all, std::slice::from_ref(&a.manifest.id)).unwrap();
+        assert_eq!(one.engrams[0].1, manifest(0) + intent + common);
+        assert_eq!(one.total_bytes, one.engrams[0].1);
+        assert_eq!(one.reclaimable_bytes, manifest(0));
+        assert_eq!(one.shared_blobs, 4);
+
+        let ids = [b.manifest.id.clone(), a.manifest.id.clone()];
+        let both = space_usage(&repo, &all, &ids).unwrap();
+        assert_eq!(
+            both.engrams[0],
+            (ids[0].clone(), manifest(1) + intent + common)
+        );
+        assert_eq!(
+            both.total_bytes,
+            manifest(0) + manifest(1) + intent + common
+        );
+        assert_eq!(both.reclaimable_bytes, manifest(0) + manifest(1) + intent);
+        assert_eq!(both.shared_blobs, 4);
+
+        assert_eq!(
+            space_usage(&repo, &all, &[]).unwrap(),
+            SpaceUsage::default()
+        );
+    }
+}
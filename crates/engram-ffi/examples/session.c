@@ -0,0 +1,44 @@
+/*
+ * Records one engram through the C API. Every allocation is released, so
+ * a leak checker should report nothing:
+ *
+ *   cargo build -p engram-ffi
+ *   cc crates/engram-ffi/examples/session.c -Icrates/engram-ffi/include \
+ *      -Ltarget/debug -lengram_ffi -o session
+ *   LD_LIBRARY_PATH=target/debug valgrind --leak-check=full ./session <repo>
+ */
+#include <stdio.h>
+
+#include "engram.h"
+
+static int fail(const char *what) {
+    fprintf(stderr, "%s: %s\n", what, engram_last_error_message());
+    return 1;
+}
+
+int main(int argc, char **argv) {
+    const char *repo = argc > 1 ? argv[1] : NULL;
+
+    EngramSession *session = engram_session_new("c-example", NULL);
+    if (session == NULL)
+        return fail("engram_session_new");
+
+    engram_log_message(session, "user", "Add a health check endpoint");
+    engram_log_message(session, "assistant", "Adding GET /healthz.");
+    engram_log_tool_call(session, "write_file", "{\"path\":\"src/health.c\"}", "Created");
+    engram_log_file_change(session, "src/health.c", "created");
+    engram_log_rejection(session, "Reuse /status", "It needs the database");
+    engram_add_tokens(session, 1200, 300, -1.0);
+
+    char id[ENGRAM_ID_BUF_LEN];
+    int status = engram_commit(session, repo, id, sizeof id);
+    if (status != ENGRAM_STATUS_OK) {
+        fail("engram_commit");
+        engram_session_free(session);
+        return 1;
+    }
+    printf("%s\n", id);
+
+    engram_session_free(session);
+    return 0;
+}
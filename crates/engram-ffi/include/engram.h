@@ -0,0 +1,135 @@
+#ifndef ENGRAM_H
+#define ENGRAM_H
+
+/* Generated by cbindgen from crates/engram-ffi; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Bytes [`engram_commit`] needs to write an engram ID and its NUL.
+#define ENGRAM_ID_BUF_LEN 33
+
+// What a call did.
+typedef enum EngramStatus {
+  ENGRAM_STATUS_OK = 0,
+  // A required pointer was NULL.
+  ENGRAM_STATUS_NULL_ARGUMENT = 1,
+  // A string was not valid UTF-8.
+  ENGRAM_STATUS_INVALID_UTF8 = 2,
+  // The session was already committed; only freeing it is left.
+  ENGRAM_STATUS_ALREADY_COMMITTED = 3,
+  // The ID buffer is shorter than `ENGRAM_ID_BUF_LEN`.
+  ENGRAM_STATUS_BUFFER_TOO_SMALL = 4,
+  // The repository could not be opened or the engram not stored.
+  ENGRAM_STATUS_STORAGE = 5,
+  // A bug in engram. The session should be freed, not used again.
+  ENGRAM_STATUS_PANIC = 6,
+} EngramStatus;
+
+// A session being recorded, opaque to C. Created by
+// [`engram_session_new`] and released by [`engram_session_free`].
+typedef struct EngramSession EngramSession;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Begin a session for `agent`, with `model` (NULL if unknown). Returns NULL
+// on failure.
+//
+// # Safety
+//
+// `agent` must be a NUL-terminated string, and `model` one or NULL.
+struct EngramSession *engram_session_new(const char *agent, const char *model);
+
+// Record a message. `role` is `user`, `assistant`, `system` or `tool`;
+// the first user message becomes the engram's request.
+//
+// # Safety
+//
+// `session` must come from [`engram_session_new`] and not be freed;
+// `role` and `text` must be NUL-terminated strings.
+enum EngramStatus engram_log_message(struct EngramSession *session,
+                                     const char *role,
+                                     const char *text);
+
+// Record a tool call. `input` is kept as JSON when it parses, else as a
+// string; `output_summary` may be NULL.
+//
+// # Safety
+//
+// `session` must come from [`engram_session_new`] and not be freed;
+// `tool_name` and `input` must be NUL-terminated strings, and
+// `output_summary` one or NULL.
+enum EngramStatus engram_log_tool_call(struct EngramSession *session,
+                                       const char *tool_name,
+                                       const char *input,
+                                       const char *output_summary);
+
+// Record a changed file. `change_type` is `created`, `modified` or
+// `deleted`.
+//
+// # Safety
+//
+// `session` must come from [`engram_session_new`] and not be freed;
+// `path` and `change_type` must be NUL-terminated strings.
+enum EngramStatus engram_log_file_change(struct EngramSession *session,
+                                         const char *path,
+                                         const char *change_type);
+
+// Record an approach that was tried and abandoned (a dead end).
+//
+// # Safety
+//
+// `session` must come from [`engram_session_new`] and not be freed;
+// `approach` and `reason` must be NUL-terminated strings.
+enum EngramStatus engram_log_rejection(struct EngramSession *session,
+                                       const char *approach,
+                                       const char *reason);
+
+// Add token usage, accumulating across calls. A negative or NaN
+// `cost_usd` means the cost is unknown.
+//
+// # Safety
+//
+// `session` must come from [`engram_session_new`] and not be freed.
+enum EngramStatus engram_add_tokens(struct EngramSession *session,
+                                    uint64_t input_tokens,
+                                    uint64_t output_tokens,
+                                    double cost_usd);
+
+// Store the session as an engram in the repository at `repo_path` (NULL
+// for the current directory's), initializing engram there if needed, and
+// write its ID to `out_id_buf`. After this succeeds, the session only
+// needs freeing; on failure it can be committed again.
+//
+// # Safety
+//
+// `session` must come from [`engram_session_new`] and not be freed;
+// `repo_path` must be a NUL-terminated string or NULL; `out_id_buf` must
+// be NULL or writable for `out_id_len` bytes.
+enum EngramStatus engram_commit(struct EngramSession *session,
+                                const char *repo_path,
+                                char *out_id_buf,
+                                size_t out_id_len);
+
+// Release a session, committed or not. NULL is ignored.
+//
+// # Safety
+//
+// `session` must be NULL or come from [`engram_session_new`] and not have
+// been freed already.
+void engram_session_free(struct EngramSession *session);
+
+// Why the last call on this thread failed, or NULL if it succeeded. The
+// string belongs to engram and is valid until the thread's next call.
+const char *engram_last_error_message(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ENGRAM_H */
@@ -0,0 +1,386 @@
+//! C ABI over [`engram_sdk::EngramSession`], for agents written in other
+//! languages to record engrams without running the CLI.
+//!
+//! Strings passed in are NUL-terminated UTF-8 and only borrowed for the
+//! call. Functions return an [`EngramStatus`]; when it is not
+//! `ENGRAM_STATUS_OK`, [`engram_last_error_message`] says why. A session is
+//! not thread-safe: use each from one thread at a time.
+//!
+//! ```c
+//! EngramSession *session = engram_session_new("my-agent", "gpt-4");
+//! engram_log_message(session, "user", "Add authentication to the API");
+//! engram_log_file_change(session, "src/auth.rs", "created");
+//! char id[ENGRAM_ID_BUF_LEN];
+//! if (engram_commit(session, "/path/to/repo", id, sizeof id) != ENGRAM_STATUS_OK)
+//!     fprintf(stderr, "%s\n", engram_last_error_message());
+//! engram_session_free(session);
+//! ```
+//!
+//! The header is `include/engram.h`, generated with cbindgen (see
+//! `cbindgen.toml`).
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+
+use engram_core::storage::GitStorage;
+
+/// Bytes [`engram_commit`] needs to write an engram ID and its NUL.
+pub const ENGRAM_ID_BUF_LEN: usize = 33;
+
+/// What a call did.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngramStatus {
+    Ok = 0,
+    /// A required pointer was NULL.
+    NullArgument = 1,
+    /// A string was not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// The session was already committed; only freeing it is left.
+    AlreadyCommitted = 3,
+    /// The ID buffer is shorter than `ENGRAM_ID_BUF_LEN`.
+    BufferTooSmall = 4,
+    /// The repository could not be opened or the engram not stored.
+    Storage = 5,
+    /// A bug in engram. The session should be freed, not used again.
+    Panic = 6,
+}
+
+/// A session being recorded, opaque to C. Created by
+/// [`engram_session_new`] and released by [`engram_session_free`].
+pub struct EngramSession {
+    /// `None` once committed.
+    session: Option<engram_sdk::EngramSession>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Why a call failed.
+struct Failure {
+    status: EngramStatus,
+    message: String,
+}
+
+impl Failure {
+    fn new(status: EngramStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Run `f`, recording its failure (or a panic) as the thread's last error.
+fn run<T>(f: impl FnOnce() -> Result<T, Failure>) -> Result<T, EngramStatus> {
+    let result = catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err(Failure::new(EngramStatus::Panic, "engram panicked")));
+    let message = result.as_ref().err().map(|failure| {
+        // Interior NULs would cut the message short in C anyway
+        CString::new(failure.message.replace('\0', " ")).unwrap_or_default()
+    });
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    result.map_err(|failure| failure.status)
+}
+
+/// Like [`run`], for calls that only report a status.
+fn status(f: impl FnOnce() -> Result<(), Failure>) -> EngramStatus {
+    match run(f) {
+        Ok(()) => EngramStatus::Ok,
+        Err(status) => status,
+    }
+}
+
+/// Borrow the C string `ptr`, named `name` in errors.
+///
+/// # Safety
+///
+/// `ptr` must be NULL or point to a NUL-terminated string that outlives
+/// the call.
+unsafe fn string<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, Failure> {
+    if ptr.is_null() {
+        return Err(Failure::new(
+            EngramStatus::NullArgument,
+            format!("{name} is NULL"),
+        ));
+    }
+    // SAFETY: non-null, and NUL-terminated per the caller's contract
+    unsafe { CStr::from_ptr(ptr) }.to_str().map_err(|e| {
+        Failure::new(
+            EngramStatus::InvalidUtf8,
+            format!("{name} is not UTF-8: {e}"),
+        )
+    })
+}
+
+/// Like [`string`], but NULL is `None` rather than an error.
+///
+/// # Safety
+///
+/// As for [`string`].
+unsafe fn optional_string<'a>(ptr: *const c_char, name: &str) -> Result<Option<&'a str>, Failure> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    // SAFETY: forwarded from the caller
+    unsafe { string(ptr, name) }.map(Some)
+}
+
+/// The session behind `ptr`, if it hasn't been committed.
+///
+/// # Safety
+///
+/// `ptr` must be NULL or a session from [`engram_session_new`] that has
+/// not been freed, with no other reference to it alive.
+unsafe fn session<'a>(
+    ptr: *mut EngramSession,
+) -> Result<&'a mut engram_sdk::EngramSession, Failure> {
+    // SAFETY: a live, unaliased session per the caller's contract
+    let handle = unsafe { ptr.as_mut() }
+        .ok_or_else(|| Failure::new(EngramStatus::NullArgument, "session is NULL"))?;
+    handle.session.as_mut().ok_or_else(|| {
+        Failure::new(
+            EngramStatus::AlreadyCommitted,
+            "the session was already committed",
+        )
+    })
+}
+
+/// Begin a session for `agent`, with `model` (NULL if unknown). Returns NULL
+/// on failure.
+///
+/// # Safety
+///
+/// `agent` must be a NUL-terminated string, and `model` one or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn engram_session_new(
+    agent: *const c_char,
+    model: *const c_char,
+) -> *mut EngramSession {
+    run(|| {
+        // SAFETY: forwarded from the caller
+        let (agent, model) = unsafe { (string(agent, "agent")?, optional_string(model, "model")?) };
+        let session = engram_sdk::EngramSession::begin(agent, model);
+        Ok(Box::into_raw(Box::new(EngramSession {
+            session: Some(session),
+        })))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Record a message. `role` is `user`, `assistant`, `system` or `tool`;
+/// the first user message becomes the engram's request.
+///
+/// # Safety
+///
+/// `session` must come from [`engram_session_new`] and not be freed;
+/// `role` and `text` must be NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn engram_log_message(
+    session: *mut EngramSession,
+    role: *const c_char,
+    text: *const c_char,
+) -> EngramStatus {
+    status(|| {
+        // SAFETY: forwarded from the caller
+        let (session, role, text) = unsafe {
+            (
+                self::session(session)?,
+                string(role, "role")?,
+                string(text, "text")?,
+            )
+        };
+        session.log_message(role, text);
+        Ok(())
+    })
+}
+
+/// Record a tool call. `input` is kept as JSON when it parses, else as a
+/// string; `output_summary` may be NULL.
+///
+/// # Safety
+///
+/// `session` must come from [`engram_session_new`] and not be freed;
+/// `tool_name` and `input` must be NUL-terminated strings, and
+/// `output_summary` one or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn engram_log_tool_call(
+    session: *mut EngramSession,
+    tool_name: *const c_char,
+    input: *const c_char,
+    output_summary: *const c_char,
+) -> EngramStatus {
+    status(|| {
+        // SAFETY: forwarded from the caller
+        let (session, tool_name, input, output_summary) = unsafe {
+            (
+                self::session(session)?,
+                string(tool_name, "tool_name")?,
+                string(input, "input")?,
+                optional_string(output_summary, "output_summary")?,
+            )
+        };
+        session.log_tool_call(tool_name, input, output_summary);
+        Ok(())
+    })
+}
+
+/// Record a changed file. `change_type` is `created`, `modified` or
+/// `deleted`.
+///
+/// # Safety
+///
+/// `session` must come from [`engram_session_new`] and not be freed;
+/// `path` and `change_type` must be NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn engram_log_file_change(
+    session: *mut EngramSession,
+    path: *const c_char,
+    change_type: *const c_char,
+) -> EngramStatus {
+    status(|| {
+        // SAFETY: forwarded from the caller
+        let (session, path, change_type) = unsafe {
+            (
+                self::session(session)?,
+                string(path, "path")?,
+                string(change_type, "change_type")?,
+            )
+        };
+        session.log_file_change(path, change_type);
+        Ok(())
+    })
+}
+
+/// Record an approach that was tried and abandoned (a dead end).
+///
+/// # Safety
+///
+/// `session` must come from [`engram_session_new`] and not be freed;
+/// `approach` and `reason` must be NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn engram_log_rejection(
+    session: *mut EngramSession,
+    approach: *const c_char,
+    reason: *const c_char,
+) -> EngramStatus {
+    status(|| {
+        // SAFETY: forwarded from the caller
+        let (session, approach, reason) = unsafe {
+            (
+                self::session(session)?,
+                string(approach, "approach")?,
+                string(reason, "reason")?,
+            )
+        };
+        session.log_rejection(approach, reason);
+        Ok(())
+    })
+}
+
+/// Add token usage, accumulating across calls. A negative or NaN
+/// `cost_usd` means the cost is unknown.
+///
+/// # Safety
+///
+/// `session` must come from [`engram_session_new`] and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn engram_add_tokens(
+    session: *mut EngramSession,
+    input_tokens: u64,
+    output_tokens: u64,
+    cost_usd: f64,
+) -> EngramStatus {
+    status(|| {
+        // SAFETY: forwarded from the caller
+        let session = unsafe { self::session(session)? };
+        let cost = (cost_usd >= 0.0).then_some(cost_usd);
+        session.add_tokens(input_tokens, output_tokens, cost);
+        Ok(())
+    })
+}
+
+/// Store the session as an engram in the repository at `repo_path` (NULL
+/// for the current directory's), initializing engram there if needed, and
+/// write its ID to `out_id_buf`. After this succeeds, the session only
+/// needs freeing; on failure it can be committed again.
+///
+/// # Safety
+///
+/// `session` must come from [`engram_session_new`] and not be freed;
+/// `repo_path` must be a NUL-terminated string or NULL; `out_id_buf` must
+/// be NULL or writable for `out_id_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn engram_commit(
+    session: *mut EngramSession,
+    repo_path: *const c_char,
+    out_id_buf: *mut c_char,
+    out_id_len: usize,
+) -> EngramStatus {
+    status(|| {
+        if !out_id_buf.is_null() && out_id_len < ENGRAM_ID_BUF_LEN {
+            return Err(Failure::new(
+                EngramStatus::BufferTooSmall,
+                format!("the ID buffer holds {out_id_len} bytes; {ENGRAM_ID_BUF_LEN} are needed"),
+            ));
+        }
+        // SAFETY: forwarded from the caller
+        let (recorded, repo_path) = unsafe {
+            (
+                self::session(session)?,
+                optional_string(repo_path, "repo_path")?,
+            )
+        };
+        let storage = match repo_path {
+            Some(path) => GitStorage::open(Path::new(path)),
+            None => GitStorage::discover(),
+        }
+        .map_err(|e| Failure::new(EngramStatus::Storage, e.to_string()))?;
+        // Committed from a copy, so a failed commit can be retried
+        let id = recorded
+            .clone()
+            .commit_to(&storage, None, None)
+            .map_err(|e| Failure::new(EngramStatus::Storage, e.to_string()))?;
+
+        // SAFETY: checked non-null when `self::session` returned
+        unsafe { &mut *session }.session = None;
+        if !out_id_buf.is_null() {
+            let id = id.as_str().as_bytes();
+            // SAFETY: the buffer holds at least ENGRAM_ID_BUF_LEN bytes,
+            // and an ID is one shorter
+            unsafe {
+                std::ptr::copy_nonoverlapping(id.as_ptr(), out_id_buf.cast::<u8>(), id.len());
+                *out_id_buf.add(id.len()) = 0;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Release a session, committed or not. NULL is ignored.
+///
+/// # Safety
+///
+/// `session` must be NULL or come from [`engram_session_new`] and not have
+/// been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn engram_session_free(session: *mut EngramSession) {
+    if !session.is_null() {
+        // SAFETY: allocated by engram_session_new and not yet freed
+        drop(unsafe { Box::from_raw(session) });
+    }
+}
+
+/// Why the last call on this thread failed, or NULL if it succeeded. The
+/// string belongs to engram and is valid until the thread's next call.
+#[no_mangle]
+pub extern "C" fn engram_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |m| m.as_ptr())
+    })
+}
@@ -0,0 +1,200 @@
+//! The C API, called from Rust the way a C caller would.
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use engram_core::model::{FileChangeType, Role, TranscriptContent};
+use engram_core::storage::GitStorage;
+use engram_ffi::*;
+use tempfile::TempDir;
+
+fn c(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+fn last_error() -> Option<String> {
+    let message = engram_last_error_message();
+    (!message.is_null()).then(|| {
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    })
+}
+
+fn repo() -> (TempDir, CString) {
+    let dir = tempfile::tempdir().unwrap();
+    git2::Repository::init(dir.path()).unwrap();
+    let path = c(dir.path().to_str().unwrap());
+    (dir, path)
+}
+
+fn commit(session: *mut EngramSession, repo: &CString) -> Result<String, EngramStatus> {
+    let mut id = [0 as c_char; ENGRAM_ID_BUF_LEN];
+    match unsafe { engram_commit(session, repo.as_ptr(), id.as_mut_ptr(), id.len()) } {
+        EngramStatus::Ok => Ok(unsafe { CStr::from_ptr(id.as_ptr()) }
+            .to_str()
+            .unwrap()
+            .to_string()),
+        status => Err(status),
+    }
+}
+
+#[test]
+fn test_record_and_commit() {
+    let (dir, repo) = repo();
+    unsafe {
+        let session = engram_session_new(c("ts-agent").as_ptr(), c("gpt-4").as_ptr());
+        assert!(!session.is_null());
+        let calls = [
+            engram_log_message(session, c("user").as_ptr(), c("Add auth").as_ptr()),
+            engram_log_message(session, c("assistant").as_ptr(), c("Adding JWT.").as_ptr()),
+            engram_log_tool_call(
+                session,
+                c("write_file").as_ptr(),
+                c(r#"{"path":"src/auth.ts"}"#).as_ptr(),
+                ptr::null(),
+            ),
+            engram_log_file_change(session, c("src/auth.ts").as_ptr(), c("created").as_ptr()),
+            engram_log_rejection(session, c("Sessions").as_ptr(), c("Stateful").as_ptr()),
+            engram_add_tokens(session, 1000, 200, 0.01),
+            engram_add_tokens(session, 500, 100, -1.0),
+        ];
+        assert!(calls.iter().all(|&s| s == EngramStatus::Ok), "{calls:?}");
+        assert_eq!(last_error(), None);
+
+        let id = commit(session, &repo).unwrap();
+        assert_eq!(id.len(), ENGRAM_ID_BUF_LEN - 1);
+
+        // Committed: the session only needs freeing
+        assert_eq!(
+            engram_log_message(session, c("user").as_ptr(), c("More").as_ptr()),
+            EngramStatus::AlreadyCommitted
+        );
+        assert_eq!(commit(session, &repo), Err(EngramStatus::AlreadyCommitted));
+        assert!(last_error().unwrap().contains("already committed"));
+        engram_session_free(session);
+
+        let data = GitStorage::open(dir.path()).unwrap().read(&id).unwrap();
+        assert_eq!(data.manifest.agent.name, "ts-agent");
+        assert_eq!(data.manifest.agent.model.as_deref(), Some("gpt-4"));
+        assert_eq!(data.intent.original_request, "Add auth");
+        assert_eq!(data.transcript.entries.len(), 2);
+        assert_eq!(data.transcript.entries[1].role, Role::Assistant);
+        assert!(matches!(
+            &data.transcript.entries[1].content,
+            TranscriptContent::Text { text } if text == "Adding JWT."
+        ));
+        assert_eq!(data.operations.tool_calls[0].input["path"], "src/auth.ts");
+        assert_eq!(data.operations.tool_calls[0].output_summary, None);
+        assert_eq!(
+            data.operations.file_changes[0].change_type,
+            FileChangeType::Created
+        );
+        assert_eq!(data.intent.dead_ends[0].approach, "Sessions");
+        assert_eq!(data.manifest.token_usage.total_tokens, 1800);
+        assert_eq!(data.manifest.token_usage.cost_usd, Some(0.01));
+    }
+}
+
+#[test]
+fn test_invalid_arguments() {
+    unsafe {
+        assert!(engram_session_new(ptr::null(), ptr::null()).is_null());
+        assert_eq!(last_error().as_deref(), Some("agent is NULL"));
+
+        let invalid = b"caf\xe9\0";
+        assert!(engram_session_new(invalid.as_ptr().cast(), ptr::null()).is_null());
+        assert!(last_error().unwrap().starts_with("agent is not UTF-8"));
+
+        assert_eq!(
+            engram_log_message(ptr::null_mut(), c("user").as_ptr(), c("Hi").as_ptr()),
+            EngramStatus::NullArgument
+        );
+        assert_eq!(
+            engram_add_tokens(ptr::null_mut(), 1, 1, 0.0),
+            EngramStatus::NullArgument
+        );
+
+        let session = engram_session_new(c("agent").as_ptr(), ptr::null());
+        assert_eq!(last_error(), None);
+        assert_eq!(
+            engram_log_message(session, c("user").as_ptr(), ptr::null()),
+            EngramStatus::NullArgument
+        );
+        assert_eq!(last_error().as_deref(), Some("text is NULL"));
+        assert_eq!(
+            engram_log_file_change(session, c("src/a.rs").as_ptr(), invalid.as_ptr().cast()),
+            EngramStatus::InvalidUtf8
+        );
+        assert_eq!(
+            engram_log_tool_call(session, c("Bash").as_ptr(), ptr::null(), ptr::null()),
+            EngramStatus::NullArgument
+        );
+        engram_session_free(session);
+        engram_session_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn test_failed_commit_can_be_retried() {
+    let (dir, repo) = repo();
+    unsafe {
+        let session = engram_session_new(c("agent").as_ptr(), ptr::null());
+        engram_log_message(session, c("user").as_ptr(), c("Fix the build").as_ptr());
+
+        let mut short = [0 as c_char; 8];
+        assert_eq!(
+            engram_commit(session, repo.as_ptr(), short.as_mut_ptr(), short.len()),
+            EngramStatus::BufferTooSmall
+        );
+        assert_eq!(short, [0; 8]);
+
+        let missing = c(dir.path().join("missing").to_str().unwrap());
+        assert_eq!(commit(session, &missing), Err(EngramStatus::Storage));
+        assert!(last_error().is_some());
+
+        // Nothing was lost; without an ID buffer the ID just isn't written
+        assert_eq!(
+            engram_commit(session, repo.as_ptr(), ptr::null_mut(), 0),
+            EngramStatus::Ok
+        );
+        engram_session_free(session);
+    }
+    let storage = GitStorage::open(dir.path()).unwrap();
+    let engrams = storage.list(&Default::default()).unwrap();
+    assert_eq!(engrams.len(), 1);
+    assert_eq!(engrams[0].summary.as_deref(), Some("Fix the build"));
+}
+
+#[test]
+fn test_errors_are_per_thread() {
+    unsafe {
+        assert!(engram_session_new(ptr::null(), ptr::null()).is_null());
+    }
+    std::thread::spawn(|| assert_eq!(last_error(), None))
+        .join()
+        .unwrap();
+    assert_eq!(last_error().as_deref(), Some("agent is NULL"));
+}
+
+/// include/engram.h is generated, but by hand-run cbindgen; catch it
+/// falling behind the exported functions.
+#[test]
+fn test_header_declares_every_function() {
+    let dir = env!("CARGO_MANIFEST_DIR");
+    let source = std::fs::read_to_string(format!("{dir}/src/lib.rs")).unwrap();
+    let header = std::fs::read_to_string(format!("{dir}/include/engram.h")).unwrap();
+    let exported: Vec<_> = source
+        .split("extern \"C\" fn ")
+        .skip(1)
+        .map(|rest| &rest[..rest.find('(').unwrap()])
+        .collect();
+    assert_eq!(exported.len(), 9);
+    for name in exported {
+        assert!(
+            header.contains(&format!("{name}(")),
+            "{name} is not in engram.h"
+        );
+    }
+    assert!(header.contains(&format!("#define ENGRAM_ID_BUF_LEN {ENGRAM_ID_BUF_LEN}")));
+}
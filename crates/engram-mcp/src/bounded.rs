@@ -0,0 +1,115 @@
+//! Size limits for text results. Tools add their output entry by entry, and
+//! once an entry would push the response past the server's limit it and all
+//! later entries are left out, with a hint on how to fetch the rest.
+
+/// Default for the `max_response_chars` of [`crate::EngramMcpServer::new`].
+pub const DEFAULT_MAX_RESPONSE_CHARS: usize = 20 * 1024;
+
+/// Room kept free for the truncation hint and the short text that follows
+/// the entries.
+const HINT_CHARS: usize = 256;
+
+/// A text result that stops growing before it passes a size limit.
+pub(crate) struct BoundedText {
+    out: String,
+    chars: usize,
+    max: usize,
+    shown: usize,
+    dropped: usize,
+}
+
+impl BoundedText {
+    /// Start a result of at most `max` characters with `head`, which is
+    /// always kept.
+    pub(crate) fn new(max: usize, head: String) -> Self {
+        Self {
+            chars: head.chars().count(),
+            out: head,
+            max: max.saturating_sub(HINT_CHARS),
+            shown: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Add `entry` whole, or drop it if it doesn't fit or an earlier entry
+    /// was dropped. The first entry is always kept, so paging moves on.
+    pub(crate) fn push(&mut self, entry: &str) {
+        let chars = entry.chars().count();
+        if self.dropped == 0 && (self.shown == 0 || self.chars + chars <= self.max) {
+            self.out.push_str(entry);
+            self.chars += chars;
+            self.shown += 1;
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    /// Add text that isn't an entry, such as a section heading or a total.
+    /// It is kept even after entries were dropped, so should be short.
+    pub(crate) fn push_str(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.chars += text.chars().count();
+    }
+
+    /// Whether an entry has been dropped.
+    pub(crate) fn truncated(&self) -> bool {
+        self.dropped > 0
+    }
+
+    /// The result, ending with the hint `hint` gives for the numbers of
+    /// entries kept and dropped, if any were dropped.
+    pub(crate) fn finish(mut self, hint: impl FnOnce(usize, usize) -> String) -> String {
+        if self.dropped > 0 {
+            self.out
+                .push_str(&format!("\n{}\n", hint(self.shown, self.dropped)));
+        }
+        self.out
+    }
+}
+
+/// Hint for a truncated page of a paged tool.
+pub(crate) fn paging_hint(remaining: usize, noun: &str, next_offset: usize) -> String {
+    format!(
+        "truncated; {remaining} more {noun} — call again with offset={next_offset} or a narrower query"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_are_kept_whole() {
+        let mut text = BoundedText::new(HINT_CHARS + 25, "head\n".into());
+        for i in 0..5 {
+            text.push(&format!("entry {i}\n"));
+        }
+        text.push_str("Footer\n");
+        let out = text.finish(|shown, dropped| {
+            assert_eq!(shown + dropped, 5);
+            paging_hint(dropped, "entries", shown)
+        });
+        assert_eq!(
+            out,
+            "head\nentry 0\nentry 1\nFooter\n\n\
+             truncated; 3 more entries — call again with offset=2 or a narrower query\n"
+        );
+    }
+
+    #[test]
+    fn test_first_entry_is_kept_when_too_long() {
+        let mut text = BoundedText::new(10, String::new());
+        text.push(&"x".repeat(50));
+        text.push("y");
+        let out = text.finish(|shown, _| format!("shown {shown}"));
+        assert_eq!(out, format!("{}\nshown 1\n", "x".repeat(50)));
+    }
+
+    #[test]
+    fn test_nothing_is_added_when_everything_fits() {
+        let mut text = BoundedText::new(DEFAULT_MAX_RESPONSE_CHARS, "head\n".into());
+        text.push("entry\n");
+        text.push_str("Footer\n");
+        assert_eq!(text.finish(|_, _| unreachable!()), "head\nentry\nFooter\n");
+    }
+}
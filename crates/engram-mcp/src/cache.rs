@@ -0,0 +1,159 @@
+//! Repository and search handles kept between tool calls. Opening them can
+//! take longer than the rest of a small request on a large repository.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+#[cfg(test)]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime};
+
+use engram_core::error::CoreError;
+use engram_core::storage::refs::ENGRAM_REF_PREFIX;
+use engram_core::storage::GitStorage;
+use engram_query::{QueryError, SearchEngine};
+
+/// How long handles are kept before being reopened, unless engram refs
+/// change sooner.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+struct CachedStorage {
+    /// Alive as long as the cache that opened the storage.
+    owner: Weak<()>,
+    storage: Rc<GitStorage>,
+    stamp: RefsStamp,
+    opened_at: Instant,
+}
+
+thread_local! {
+    /// Storage by cache and repository path. `git2::Repository` is `!Send`,
+    /// so each thread keeps its own.
+    static STORAGES: RefCell<HashMap<(u64, PathBuf), CachedStorage>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Modification times of `packed-refs` and of the engram ref directories,
+/// which change whenever an engram ref is added, moved or deleted.
+#[derive(Debug, PartialEq, Eq)]
+struct RefsStamp {
+    packed_refs: Option<SystemTime>,
+    engram_refs: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl RefsStamp {
+    fn read(git_dir: &Path) -> Self {
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let refs_dir = git_dir.join(ENGRAM_REF_PREFIX);
+        let mut dirs: Vec<PathBuf> = std::fs::read_dir(&refs_dir)
+            .map(|entries| entries.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default();
+        dirs.sort();
+        dirs.insert(0, refs_dir);
+        Self {
+            packed_refs: modified(&git_dir.join("packed-refs")),
+            engram_refs: dirs
+                .into_iter()
+                .map(|dir| {
+                    let time = modified(&dir);
+                    (dir, time)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Handles shared by a server's tool calls. A zero TTL opens fresh handles
+/// for every call. Servers never share handles, even for the same
+/// repository, so each sees its own TTL.
+pub(crate) struct HandleCache {
+    id: u64,
+    alive: Arc<()>,
+    ttl: Duration,
+    engine: Mutex<Option<(Instant, Arc<SearchEngine>)>>,
+    #[cfg(test)]
+    pub(crate) storage_opens: AtomicUsize,
+    #[cfg(test)]
+    pub(crate) engine_opens: AtomicUsize,
+    #[cfg(test)]
+    pub(crate) intent_reads: AtomicUsize,
+}
+
+impl std::fmt::Debug for HandleCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HandleCache")
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HandleCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            alive: Arc::new(()),
+            ttl,
+            engine: Mutex::new(None),
+            #[cfg(test)]
+            storage_opens: AtomicUsize::new(0),
+            #[cfg(test)]
+            engine_opens: AtomicUsize::new(0),
+            #[cfg(test)]
+            intent_reads: AtomicUsize::new(0),
+        }
+    }
+
+    /// Storage for `repo_path`, reopened once the TTL has passed or the
+    /// engram refs have changed since it was opened.
+    pub(crate) fn storage(&self, repo_path: &Path) -> Result<Rc<GitStorage>, CoreError> {
+        STORAGES.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let key = (self.id, repo_path.to_path_buf());
+            if let Some(cached) = cache.get(&key) {
+                if cached.opened_at.elapsed() < self.ttl
+                    && RefsStamp::read(cached.storage.repo().path()) == cached.stamp
+                {
+                    return Ok(Rc::clone(&cached.storage));
+                }
+            }
+            let storage = Rc::new(GitStorage::open(repo_path)?);
+            #[cfg(test)]
+            self.storage_opens.fetch_add(1, Ordering::Relaxed);
+            let stamp = RefsStamp::read(storage.repo().path());
+            // Drop storage left behind by servers that have gone
+            cache.retain(|_, cached| cached.owner.strong_count() > 0);
+            cache.insert(
+                key,
+                CachedStorage {
+                    owner: Arc::downgrade(&self.alive),
+                    storage: Rc::clone(&storage),
+                    stamp,
+                    opened_at: Instant::now(),
+                },
+            );
+            Ok(storage)
+        })
+    }
+
+    /// The search engine for `storage`, reopened once the TTL has passed.
+    /// Its index stays open across calls but still sees later commits.
+    pub(crate) fn search_engine(
+        &self,
+        storage: &GitStorage,
+    ) -> Result<Arc<SearchEngine>, QueryError> {
+        let mut cached = self.engine.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((opened_at, engine)) = cached.as_ref() {
+            if opened_at.elapsed() < self.ttl {
+                return Ok(Arc::clone(engine));
+            }
+        }
+        let engine = Arc::new(SearchEngine::open(storage)?);
+        #[cfg(test)]
+        self.engine_opens.fetch_add(1, Ordering::Relaxed);
+        *cached = Some((Instant::now(), Arc::clone(&engine)));
+        Ok(engine)
+    }
+}
@@ -0,0 +1,75 @@
+//! Streamable HTTP transport, for agents that reach the server over the
+//! network instead of starting it as a child process.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
+use tokio::net::TcpListener;
+
+use crate::EngramMcpServer;
+
+/// Environment variable holding the bearer token HTTP clients must send.
+/// Takes precedence over `engram.mcp.token`.
+pub const TOKEN_ENV: &str = "ENGRAM_MCP_TOKEN";
+
+/// Path the MCP endpoint is served at.
+pub const MCP_PATH: &str = "/mcp";
+
+/// Start the MCP server on streamable HTTP at `bind_addr`. When `token` is
+/// set, requests without `Authorization: Bearer <token>` are refused.
+/// Without a token, only loopback addresses are served unless `insecure`.
+pub async fn run_http(
+    repo_path: PathBuf,
+    bind_addr: SocketAddr,
+    cache_ttl: Duration,
+    max_response_chars: usize,
+    token: Option<String>,
+    insecure: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(bind_addr).await?;
+    serve_http(
+        listener,
+        repo_path,
+        cache_ttl,
+        max_response_chars,
+        token,
+        insecure,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Serve the MCP endpoint on an already bound listener, until the listener
+/// fails. Sessions share one handle cache; each request thread opens its
+/// own repository handle.
+///
+/// The server has tools that write engrams, so without a token a listener
+/// on a non-loopback address is refused unless `insecure` is set.
+pub async fn serve_http(
+    listener: TcpListener,
+    repo_path: PathBuf,
+    cache_ttl: Duration,
+    max_response_chars: usize,
+    token: Option<String>,
+    insecure: bool,
+) -> std::io::Result<()> {
+    let addr = listener.local_addr()?;
+    engram_api::auth::check_unauthenticated(addr, token.as_deref(), insecure)?;
+    tracing::info!("Serving MCP on http://{addr}{MCP_PATH}");
+    let server = EngramMcpServer::new(repo_path, cache_ttl, max_response_chars);
+    let service = StreamableHttpService::new(
+        move || Ok(server.clone()),
+        Arc::new(LocalSessionManager::default()),
+        StreamableHttpServerConfig::default(),
+    );
+    let mut router = Router::new().nest_service(MCP_PATH, service);
+    if let Some(token) = token {
+        router = engram_api::auth::require_bearer(router, &token);
+    }
+    axum::serve(listener, router).await
+}
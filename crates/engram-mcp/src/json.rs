@@ -0,0 +1,239 @@
+//! JSON results of the tools that take `format: "json"`. Each carries
+//! [`SCHEMA_VERSION`], which changes only when a field is removed or changes
+//! meaning; new fields may appear within a version.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use engram_core::model::{
+    DeadEnd, Decision, EngramData, EngramId, FileChange, Intent, Lineage, Manifest, Note,
+};
+use engram_query::{EngramDiff, SearchPage, SkippedRepo, TraceEntry};
+
+/// Version of the JSON results below.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// How a tool should format its result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Text,
+    Json,
+}
+
+impl Format {
+    pub(crate) fn parse(format: Option<&str>) -> Result<Self, String> {
+        match format {
+            None | Some("text") => Ok(Self::Text),
+            Some("json") => Ok(Self::Json),
+            Some(other) => Err(format!(
+                "Unknown format '{other}'; use \"text\" or \"json\""
+            )),
+        }
+    }
+}
+
+pub(crate) fn to_string<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to encode JSON: {e}"))
+}
+
+/// `engram_search` results.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchJson {
+    pub schema_version: u32,
+    pub query: String,
+    /// Matches across all pages.
+    pub total: usize,
+    /// Position of the first result among all matches.
+    pub offset: usize,
+    pub results: Vec<SearchHit>,
+    /// Counts of all matches by agent, model and tag, when asked for.
+    pub facets: Option<serde_json::Value>,
+    /// Repositories left out of a multi-repository search.
+    pub skipped: Vec<SkippedJson>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub manifest: Manifest,
+    /// Relevance; 0 when sorted by something else.
+    pub score: f32,
+    /// Text around the matched terms, or the intent summary.
+    pub snippet: Option<String>,
+    /// Repository the engram came from, in a multi-repository search.
+    pub repo: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkippedJson {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl SearchJson {
+    pub(crate) fn new(query: &str, page: SearchPage, skipped: &[SkippedRepo]) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            query: query.to_string(),
+            total: page.total,
+            offset: page.offset,
+            facets: page
+                .facets
+                .as_ref()
+                .and_then(|f| serde_json::to_value(f).ok()),
+            results: page
+                .results
+                .into_iter()
+                .map(|r| SearchHit {
+                    manifest: r.manifest,
+                    score: r.score,
+                    snippet: r.snippet,
+                    repo: r.repo,
+                })
+                .collect(),
+            skipped: skipped
+                .iter()
+                .map(|s| SkippedJson {
+                    path: s.path.clone(),
+                    reason: s.reason.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `engram_log` results, most recent first.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogJson {
+    pub schema_version: u32,
+    pub offset: usize,
+    /// Whether more engrams follow this page. Version 1 had an exact
+    /// `total`, which took reading every matching engram.
+    pub more: bool,
+    pub engrams: Vec<Manifest>,
+}
+
+/// `engram_show` result: the engram without its transcript, which
+/// `engram_transcript` pages through.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShowJson {
+    pub schema_version: u32,
+    pub manifest: Manifest,
+    pub intent: Intent,
+    pub file_changes: Vec<FileChange>,
+    pub lineage: Lineage,
+    pub transcript_entries: usize,
+    /// Notes added since the engram was recorded, oldest first.
+    #[serde(default)]
+    pub notes: Vec<Note>,
+}
+
+impl ShowJson {
+    pub(crate) fn new(data: EngramData, notes: Vec<Note>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            transcript_entries: data.transcript.entries.len(),
+            manifest: data.manifest,
+            intent: data.intent,
+            file_changes: data.operations.file_changes,
+            lineage: data.lineage,
+            notes,
+        }
+    }
+}
+
+/// `engram_trace` results, oldest first.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraceJson {
+    pub schema_version: u32,
+    pub file_path: String,
+    pub entries: Vec<TraceHit>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraceHit {
+    pub manifest: Manifest,
+    /// created, modified, deleted or renamed.
+    pub change_type: String,
+    /// The file's name in this engram, which differs from `file_path` for
+    /// engrams before a rename.
+    pub path: String,
+}
+
+impl TraceJson {
+    pub(crate) fn new(file_path: &str, entries: Vec<TraceEntry>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            file_path: file_path.to_string(),
+            entries: entries
+                .into_iter()
+                .map(|e| TraceHit {
+                    manifest: e.manifest,
+                    change_type: e.change_type,
+                    path: e.path,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `engram_diff` result. Deltas are B minus A.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiffJson {
+    pub schema_version: u32,
+    pub id_a: EngramId,
+    pub id_b: EngramId,
+    pub common_files: Vec<String>,
+    pub only_a_files: Vec<String>,
+    pub only_b_files: Vec<String>,
+    pub token_delta: i64,
+    pub cost_delta: Option<f64>,
+    pub transcript_entry_delta: i64,
+    pub dead_ends_only_a: Vec<DeadEnd>,
+    pub dead_ends_only_b: Vec<DeadEnd>,
+    pub decisions_only_a: Vec<Decision>,
+    pub decisions_only_b: Vec<Decision>,
+    /// The interpreted goals of A and B, when they differ.
+    pub goal_changed: Option<GoalChange>,
+    /// Calls to each tool in A and in B.
+    pub tool_calls: BTreeMap<String, ToolCounts>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GoalChange {
+    pub a: String,
+    pub b: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCounts {
+    pub a: usize,
+    pub b: usize,
+}
+
+impl From<EngramDiff> for DiffJson {
+    fn from(diff: EngramDiff) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            id_a: diff.id_a,
+            id_b: diff.id_b,
+            common_files: diff.common_files,
+            only_a_files: diff.only_a_files,
+            only_b_files: diff.only_b_files,
+            token_delta: diff.token_delta,
+            cost_delta: diff.cost_delta,
+            transcript_entry_delta: diff.transcript_entry_delta,
+            dead_ends_only_a: diff.dead_ends_only_a,
+            dead_ends_only_b: diff.dead_ends_only_b,
+            decisions_only_a: diff.decisions_only_a,
+            decisions_only_b: diff.decisions_only_b,
+            goal_changed: diff.intent_changed.map(|(a, b)| GoalChange { a, b }),
+            tool_calls: diff
+                .tool_call_counts
+                .into_iter()
+                .map(|(tool, (a, b))| (tool, ToolCounts { a, b }))
+                .collect(),
+        }
+    }
+}
@@ -1,40 +1,139 @@
+mod bounded;
+mod cache;
+mod http;
+pub mod json;
+mod prompts;
+
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
 
-use rmcp::model::{ServerCapabilities, ServerInfo};
+use rmcp::model::{
+    GetPromptRequestParams, GetPromptResult, ListPromptsResult, PaginatedRequestParams,
+    ServerCapabilities, ServerInfo,
+};
+use rmcp::service::RequestContext;
 use rmcp::{
-    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
-    tool, tool_handler, tool_router, ServerHandler,
+    handler::server::{
+        router::{prompt::PromptRouter, tool::ToolRouter},
+        wrapper::Parameters,
+    },
+    prompt_handler, tool, tool_handler, tool_router, RoleServer, ServerHandler,
 };
 use schemars::JsonSchema;
 use serde::Deserialize;
 
-use engram_core::model::FileChangeType;
-use engram_core::storage::{GitStorage, ListOptions};
+use engram_core::config::TrivialThresholds;
+use engram_core::display::format_duration;
+use engram_core::hooks::ActiveSession;
+use engram_core::model::{
+    DeadEnd, EngramId, FileChangeType, Intent, Role, Transcript, TranscriptContent, TranscriptEntry,
+};
+use engram_core::storage::{EngramPart, GitStorage, ListOptions};
+use engram_query::index::{parse_date_bound, FilterField};
+use engram_query::review::{change_label, files_markdown};
 use engram_query::search::SearchEngine;
-use engram_query::{diff_engrams, EngramDiff};
+use engram_query::{
+    agent_stats_for, build_graph, diff_engrams, related_engrams, review_branch, trace_file,
+    EngramDiff, MultiRepoSearch, Relation, ReviewOptions, SearchOptions, SearchSort,
+};
+use engram_sdk::EngramSession;
+
+use crate::bounded::{paging_hint, BoundedText};
+use crate::cache::HandleCache;
+use crate::json::Format;
+
+pub use crate::bounded::DEFAULT_MAX_RESPONSE_CHARS;
+pub use crate::cache::DEFAULT_CACHE_TTL;
+pub use crate::http::{run_http, serve_http, MCP_PATH, TOKEN_ENV};
+
+/// Most transcript text `engram_record` accepts, summed over messages.
+pub const MAX_TRANSCRIPT_BYTES: usize = 4 * 1024 * 1024;
+
+/// Most nodes `engram_graph` renders; larger graphs must be narrowed.
+pub const MAX_GRAPH_NODES: usize = 200;
+
+/// Most entries one `engram_transcript` call returns.
+pub const MAX_TRANSCRIPT_PAGE: usize = 200;
+
+/// Most characters of one entry's content `engram_transcript` shows.
+pub const MAX_ENTRY_CHARS: usize = 1000;
+
+/// Most bytes one `engram_transcript` response holds; a page stops early
+/// rather than grow past it.
+pub const MAX_TRANSCRIPT_PAGE_BYTES: usize = 64 * 1024;
+
+/// Most engrams one `engram_dead_ends` call reads.
+pub const MAX_DEAD_END_ENGRAMS: usize = 50;
 
 /// MCP server exposing engram reasoning data to AI agents.
 ///
 /// Stores `repo_path: PathBuf` instead of `GitStorage` because
 /// `git2::Repository` is `!Send` and rmcp requires `ServerHandler: Send + Sync + 'static`.
-/// Each tool handler opens the repository fresh per request.
+/// Tool handlers reuse the repository each thread last opened, and a shared
+/// search engine, until `cache_ttl` passes or the engram refs change.
 #[derive(Debug, Clone)]
 pub struct EngramMcpServer {
     repo_path: PathBuf,
+    cache: Arc<HandleCache>,
+    max_response_chars: usize,
     tool_router: ToolRouter<Self>,
+    prompt_router: PromptRouter<Self>,
 }
 
 impl EngramMcpServer {
-    /// Create a new MCP server for the repository at the given path.
-    pub fn new(repo_path: PathBuf) -> Self {
+    /// Create a new MCP server for the repository at the given path, keeping
+    /// opened handles for up to `cache_ttl` (see [`DEFAULT_CACHE_TTL`]).
+    /// Text results of search, log, trace, show and diff are cut at an entry
+    /// boundary to stay within `max_response_chars` (see
+    /// [`DEFAULT_MAX_RESPONSE_CHARS`]), ending with a hint on how to get the
+    /// rest.
+    pub fn new(repo_path: PathBuf, cache_ttl: Duration, max_response_chars: usize) -> Self {
         Self {
             repo_path,
+            cache: Arc::new(HandleCache::new(cache_ttl)),
+            max_response_chars,
             tool_router: Self::tool_router(),
+            prompt_router: Self::prompt_router(),
         }
     }
 
-    fn open_storage(&self) -> Result<GitStorage, String> {
-        GitStorage::open(&self.repo_path).map_err(|e| format!("Failed to open repository: {e}"))
+    fn open_storage(&self) -> Result<Rc<GitStorage>, String> {
+        self.cache
+            .storage(&self.repo_path)
+            .map_err(|e| format!("Failed to open repository: {e}"))
+    }
+
+    fn search_engine(&self, storage: &GitStorage) -> Result<Arc<SearchEngine>, String> {
+        self.cache
+            .search_engine(storage)
+            .map_err(|e| format!("Failed to open search: {e}"))
+    }
+
+    /// Just the intent of engram `id`: its dead ends and decisions, without
+    /// the transcript or operations.
+    fn read_intent(&self, storage: &GitStorage, id: &str) -> Result<Intent, String> {
+        #[cfg(test)]
+        self.cache
+            .intent_reads
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        storage
+            .read_intent(id)
+            .map_err(|e| format!("Failed to read engram: {e}"))
+    }
+
+    /// The repository's storage, for tools that write to it.
+    fn open_initialized(&self) -> Result<Rc<GitStorage>, String> {
+        let storage = self.open_storage()?;
+        if !storage.is_initialized() {
+            return Err(format!(
+                "Engram is not initialized in {}. Run `{}` there first.",
+                self.repo_path.display(),
+                storage.init_command()
+            ));
+        }
+        Ok(storage)
     }
 }
 
@@ -42,30 +141,146 @@ impl EngramMcpServer {
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct SearchParams {
-    /// Free-text search query across intent, transcript, file paths, and dead ends
+    /// Free-text search query across intent, transcript, file paths, dead ends,
+    /// decisions, tags, and tool names.
+    /// Scope with `agent:`, `model:`, `file:`, `tag:`, `dead_end:` or `has_errors:true` terms.
     pub query: String,
     /// Maximum number of results (default: 10)
     pub limit: Option<usize>,
+    /// Number of results to skip, for fetching later pages (default: 0)
+    pub offset: Option<usize>,
+    /// Only engrams recorded by this agent (exact name, e.g. "claude-code")
+    pub agent: Option<String>,
+    /// Only engrams that changed this file path
+    pub file: Option<String>,
+    /// Only engrams with this tag
+    pub tag: Option<String>,
+    /// Only engrams created on or after this date (YYYY-MM-DD or RFC 3339)
+    pub since: Option<String>,
+    /// Only engrams created on or before this date (YYYY-MM-DD or RFC 3339)
+    pub until: Option<String>,
+    /// Result order: relevance (default), newest, oldest, cost, tokens
+    pub sort: Option<String>,
+    /// Also match words with typos, ranked below exact matches (default: false)
+    pub fuzzy: Option<bool>,
+    /// Append counts of all matches by agent, model and tag (default: false)
+    pub facets: Option<bool>,
+    /// Search these repository paths instead of this one; repositories without a search index are skipped and listed
+    pub repos: Option<Vec<String>>,
+    /// Only engrams that cost at least this much in USD (excludes engrams with no recorded cost)
+    pub min_cost: Option<f64>,
+    /// Only engrams that cost at most this much in USD (excludes engrams with no recorded cost)
+    pub max_cost: Option<f64>,
+    /// Only engrams that used at least this many tokens
+    pub min_tokens: Option<u64>,
+    /// Only engrams that used at most this many tokens
+    pub max_tokens: Option<u64>,
+    /// Result format: "text" (default) or "json", with a `schema_version` field
+    pub format: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ShowParams {
-    /// Engram ID (full or prefix) or "HEAD" for most recent
+    /// Engram ID (full or prefix), "HEAD" for most recent or "HEAD~N" for the
+    /// N-th before it
+    pub id: String,
+    /// Result format: "text" (default) or "json", with a `schema_version` field
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TranscriptParams {
+    /// Engram ID (full or prefix), "HEAD" for most recent or "HEAD~N" for the
+    /// N-th before it
+    pub id: String,
+    /// Number of matching entries to skip, for fetching later pages (default: 0)
+    pub offset: Option<usize>,
+    /// Maximum number of entries (default: 50, at most 200)
+    pub limit: Option<usize>,
+    /// Only entries with these roles: user, assistant, system, tool
+    pub roles: Option<Vec<String>>,
+    /// Include tool results (default: false)
+    pub include_tool_results: Option<bool>,
+    /// Include thinking entries (default: false)
+    pub include_thinking: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RelatedParams {
+    /// Engram ID (full or prefix), "HEAD" for most recent or "HEAD~N" for the
+    /// N-th before it
     pub id: String,
+    /// Maximum number of related engrams besides lineage (default: 10)
+    pub limit: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct LogParams {
     /// Maximum number of entries (default: 10)
     pub limit: Option<usize>,
+    /// Number of entries to skip, for fetching later pages (default: 0)
+    pub offset: Option<usize>,
     /// Filter by agent name
     pub by_agent: Option<String>,
+    /// Only engrams with this tag
+    pub tag: Option<String>,
+    /// Only engrams created on or after this date (YYYY-MM-DD or RFC 3339)
+    pub since: Option<String>,
+    /// Only engrams created on or before this date (YYYY-MM-DD or RFC 3339)
+    pub until: Option<String>,
+    /// Only engrams that cost at least this much in USD (excludes engrams with no recorded cost)
+    pub min_cost: Option<f64>,
+    /// Only engrams that cost at most this much in USD (excludes engrams with no recorded cost)
+    pub max_cost: Option<f64>,
+    /// Only engrams that used at least this many tokens
+    pub min_tokens: Option<u64>,
+    /// Only engrams that used at most this many tokens
+    pub max_tokens: Option<u64>,
+    /// Include trivial sessions: no file changes, few tokens and short (default: false)
+    pub all: Option<bool>,
+    /// Result format: "text" (default) or "json", with a `schema_version` field
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StatsParams {
+    /// Break usage down by agent and model, with files touched and last activity (default: false)
+    pub by_agent: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReviewParams {
+    /// Branch point, such as "main"
+    pub base: String,
+    /// Tip of the branch (default: HEAD)
+    pub head: Option<String>,
+    /// Group changes by file, listing the engrams that changed each (default: false)
+    pub by_file: Option<bool>,
+    /// Also include engrams that ran on the branch and changed its files without recording a commit (default: false)
+    pub inferred: Option<bool>,
+    /// Number of engrams (or files, with by_file) to skip, for fetching the rest of a truncated review (default: 0)
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GraphParams {
+    /// Center node: an engram ID (or prefix), file path or node ID such as "file:src/auth.rs". Without it the whole graph is rendered.
+    pub focus: Option<String>,
+    /// Traversal depth from the focus node (default: 2)
+    pub depth: Option<usize>,
+    /// Output format: "mermaid" (default) or "dot"
+    pub format: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct TraceParams {
-    /// File path to trace reasoning history for
+    /// File path to trace reasoning history for, relative to the repository
+    /// root or absolute
     pub file_path: String,
+    /// Number of engrams to skip, for fetching the rest of a truncated trace (default: 0)
+    pub offset: Option<usize>,
+    /// Result format: "text" (default) or "json", with a `schema_version` field
+    pub format: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -74,6 +289,10 @@ pub struct DiffParams {
     pub id_a: String,
     /// Second engram ID (or prefix)
     pub id_b: String,
+    /// List differing dead ends, decisions, goals and tool call counts instead of summarizing them (default: false)
+    pub full: Option<bool>,
+    /// Result format: "text" (default) or "json", with a `schema_version` field
+    pub format: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -82,6 +301,92 @@ pub struct DeadEndsParams {
     pub id: Option<String>,
     /// Search for dead ends matching this text (optional)
     pub query: Option<String>,
+    /// Number of engrams to skip, for fetching later pages (default: 0)
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RecordParams {
+    /// Name of the agent recording the session (e.g. "claude-code")
+    pub agent: String,
+    /// Model used (optional)
+    pub model: Option<String>,
+    /// What the user asked for
+    pub original_request: String,
+    /// One-line summary of what was done (default: the request)
+    pub summary: Option<String>,
+    /// Conversation messages, in order
+    pub messages: Option<Vec<MessageParam>>,
+    /// Tools called during the session
+    pub tool_calls: Option<Vec<ToolCallParam>>,
+    /// Files created, modified or deleted
+    pub file_changes: Option<Vec<FileChangeParam>>,
+    /// Approaches tried and rejected
+    pub dead_ends: Option<Vec<DeadEndParam>>,
+    /// Decisions made, with their rationale
+    pub decisions: Option<Vec<DecisionParam>>,
+    /// Token usage and cost
+    pub tokens: Option<TokensParam>,
+    /// Tags for later search
+    pub tags: Option<Vec<String>>,
+    /// Engram this session continues (full ID, prefix, "HEAD" or "HEAD~N")
+    pub parent_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MessageParam {
+    /// user, assistant, system or tool
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ToolCallParam {
+    pub tool_name: String,
+    /// The tool's input, as JSON
+    pub input: Option<serde_json::Value>,
+    /// What the tool returned, in brief
+    pub output_summary: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FileChangeParam {
+    pub path: String,
+    /// created, modified (default) or deleted
+    pub change_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DeadEndParam {
+    /// The approach that was tried
+    pub approach: String,
+    /// Why it was abandoned
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnnotateParams {
+    /// Engram ID (full or prefix), 'HEAD' for most recent or 'HEAD~N' for the
+    /// N-th before it
+    pub id: String,
+    /// What was learned about the engram since, e.g. "This approach was reverted in 3f2a91c"
+    pub text: String,
+    /// Who the note is from (default: "agent")
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DecisionParam {
+    pub description: String,
+    pub rationale: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TokensParam {
+    pub input: Option<u64>,
+    pub output: Option<u64>,
+    /// Cost in USD
+    pub cost: Option<f64>,
 }
 
 // -- Tool implementations --
@@ -89,47 +394,110 @@ pub struct DeadEndsParams {
 #[tool_router]
 impl EngramMcpServer {
     #[tool(
-        description = "Search engram reasoning history by free-text query. Searches across intent, transcript, file paths, and dead ends."
+        description = "Search engram reasoning history by free-text query. Searches across intent, transcript, file paths, dead ends, decisions, tags, and tool names. Optional agent, file and tag narrow the matches; since/until restrict by creation date, given as YYYY-MM-DD (a whole UTC day) or RFC 3339 (e.g. 2026-01-15T10:00:00Z). Use offset/limit to page; repos searches other repositories."
     )]
     fn engram_search(
         &self,
         Parameters(params): Parameters<SearchParams>,
     ) -> Result<String, String> {
-        let storage = self.open_storage()?;
-        let engine =
-            SearchEngine::open(&storage).map_err(|e| format!("Failed to open search: {e}"))?;
-        let limit = params.limit.unwrap_or(10);
-        let results = engine
-            .search(&storage, &params.query, limit)
-            .map_err(|e| format!("Search failed: {e}"))?;
+        let filters = [
+            (FilterField::Agent, &params.agent),
+            (FilterField::File, &params.file),
+            (FilterField::Tag, &params.tag),
+        ]
+        .into_iter()
+        .filter_map(|(field, value)| Some((field, value.clone()?)))
+        .collect();
+        let format = Format::parse(params.format.as_deref())?;
+        let sort = match params.sort.as_deref() {
+            Some(sort) => sort.parse::<SearchSort>()?,
+            None => SearchSort::default(),
+        };
+        let options = SearchOptions {
+            limit: params.limit.unwrap_or(10),
+            offset: params.offset.unwrap_or(0),
+            sort,
+            since: date_bound(params.since.as_deref(), false)?,
+            until: date_bound(params.until.as_deref(), true)?,
+            fuzzy: params.fuzzy.unwrap_or(false),
+            facets: params.facets.unwrap_or(false),
+            min_cost: params.min_cost,
+            max_cost: params.max_cost,
+            min_tokens: params.min_tokens,
+            max_tokens: params.max_tokens,
+            filters,
+            ..Default::default()
+        };
+        let (page, skipped) = match params.repos.as_deref() {
+            Some(repos) if !repos.is_empty() => {
+                let multi = MultiRepoSearch::new(repos.iter().map(PathBuf::from));
+                let found = multi.search(&params.query, &options);
+                (found.page, found.skipped)
+            }
+            _ => {
+                let storage = self.open_storage()?;
+                let engine = self.search_engine(&storage)?;
+                let page = engine
+                    .search(&storage, &params.query, &options)
+                    .map_err(|e| format!("Search failed: {e}"))?;
+                (page, Vec::new())
+            }
+        };
+        if format == Format::Json {
+            return json::to_string(&json::SearchJson::new(&params.query, page, &skipped));
+        }
+        let skipped: String = skipped
+            .iter()
+            .map(|s| format!("Skipped {}: {}\n", s.path.display(), s.reason))
+            .collect();
 
-        if results.is_empty() {
-            return Ok(format!("No results found for: {}", params.query));
+        if page.total == 0 {
+            return Ok(format!("{skipped}No results found for: {}", params.query));
         }
 
-        let mut out = format!(
-            "Found {} result(s) for: {}\n\n",
-            results.len(),
-            params.query
+        let head = format!(
+            "{skipped}Found {} result(s) for: {}, {}\n\n",
+            page.total,
+            params.query,
+            page.showing()
         );
-        for r in &results {
+        let mut out = BoundedText::new(self.max_response_chars, head);
+        for r in &page.results {
             let m = &r.manifest;
             let short_id = &m.id.as_str()[..8.min(m.id.as_str().len())];
             let summary = m.summary.as_deref().unwrap_or("(no summary)");
             let agent = &m.agent.name;
             let model = m.agent.model.as_deref().unwrap_or("unknown");
             let date = m.created_at.format("%Y-%m-%d %H:%M");
-            out.push_str(&format!(
-                "- {short_id} [{agent}/{model}] {date}\n  {summary}\n"
-            ));
+            let mut entry = format!("- {short_id} [{agent}/{model}] {date}\n  {summary}\n");
+            if let Some(repo) = &r.repo {
+                entry.push_str(&format!("  Repo: {}\n", repo.display()));
+            }
+            if !r.highlights.is_empty() {
+                if let Some(snippet) = &r.snippet {
+                    let fragment = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+                    entry.push_str(&format!("  Match: {fragment}\n"));
+                    if r.truncated_transcript {
+                        entry.push_str("  (matched in truncated transcript)\n");
+                    }
+                }
+            }
+            out.push(&entry);
         }
-        Ok(out)
+        if let Some(facets) = &page.facets {
+            out.push(&format!("\nFacets:\n{facets}"));
+        }
+        Ok(out.finish(|shown, _| {
+            let next = page.offset + shown;
+            paging_hint(page.total.saturating_sub(next), "results", next)
+        }))
     }
 
     #[tool(
-        description = "Show full details of a specific engram including manifest, intent, file changes, and transcript summary. Supports 'HEAD' for most recent."
+        description = "Show full details of a specific engram including manifest, intent, file changes, transcript summary and notes. Supports 'HEAD' for most recent and 'HEAD~N' for the N-th before it."
     )]
     fn engram_show(&self, Parameters(params): Parameters<ShowParams>) -> Result<String, String> {
+        let format = Format::parse(params.format.as_deref())?;
         let storage = self.open_storage()?;
         let resolved = storage
             .resolve(&params.id)
@@ -137,6 +505,12 @@ impl EngramMcpServer {
         let data = storage
             .read(&resolved)
             .map_err(|e| format!("Failed to read engram: {e}"))?;
+        let notes = storage
+            .read_notes(&resolved)
+            .map_err(|e| format!("Failed to read notes: {e}"))?;
+        if format == Format::Json {
+            return json::to_string(&json::ShowJson::new(data, notes));
+        }
 
         let m = &data.manifest;
         let mut out = String::new();
@@ -154,6 +528,12 @@ impl EngramMcpServer {
             "Date: {}\n",
             m.created_at.format("%Y-%m-%d %H:%M:%S UTC")
         ));
+        if let Some(finished_at) = m.finished_at {
+            out.push_str(&format!(
+                "Duration: {}\n",
+                format_duration(finished_at - m.created_at)
+            ));
+        }
         if let Some(summary) = &m.summary {
             out.push_str(&format!("Summary: {summary}\n"));
         }
@@ -183,6 +563,8 @@ impl EngramMcpServer {
             out.push_str(&format!("Intent Summary: {intent_summary}\n"));
         }
 
+        let mut out = BoundedText::new(self.max_response_chars, out);
+
         // File changes
         if !data.operations.file_changes.is_empty() {
             out.push_str(&format!(
@@ -195,11 +577,11 @@ impl EngramMcpServer {
                     FileChangeType::Modified => "~",
                     FileChangeType::Deleted => "-",
                     FileChangeType::Renamed { from } => {
-                        out.push_str(&format!("  {from} -> {}\n", fc.path));
+                        out.push(&format!("  {from} -> {}\n", fc.path));
                         continue;
                     }
                 };
-                out.push_str(&format!("  {symbol} {}\n", fc.path));
+                out.push(&format!("  {symbol} {}\n", fc.path));
             }
         }
 
@@ -207,7 +589,7 @@ impl EngramMcpServer {
         if !data.intent.dead_ends.is_empty() {
             out.push_str("\nDead Ends:\n");
             for de in &data.intent.dead_ends {
-                out.push_str(&format!("  - {}: {}\n", de.approach, de.reason));
+                out.push(&format!("  - {}: {}\n", de.approach, de.reason));
             }
         }
 
@@ -215,36 +597,177 @@ impl EngramMcpServer {
         if !data.intent.decisions.is_empty() {
             out.push_str("\nDecisions:\n");
             for d in &data.intent.decisions {
-                out.push_str(&format!("  - {}: {}\n", d.description, d.rationale));
+                out.push(&format!("  - {}: {}\n", d.description, d.rationale));
             }
         }
 
         out.push_str(&format!(
-            "\nTranscript: {} entries\n",
+            "\nTranscript: {} entries (read them with engram_transcript)\n",
             data.transcript.entries.len()
         ));
 
+        if !notes.is_empty() {
+            out.push_str("\nNotes:\n");
+            for note in &notes {
+                out.push(&format!(
+                    "  - {} {}: {}\n",
+                    note.created_at.format("%Y-%m-%d %H:%M"),
+                    note.author,
+                    note.text
+                ));
+            }
+        }
+
+        Ok(out.finish(|_, dropped| {
+            format!(
+                "truncated; {dropped} more file changes, dead ends, decisions or notes — \
+                 call again with format=\"json\", or use engram_dead_ends"
+            )
+        }))
+    }
+
+    #[tool(
+        description = "Read a page of an engram's transcript: each entry's number, time, role and content, cut to 1000 characters. Thinking and tool results are left out unless asked for. Trivial sessions (no file changes, few tokens, short) are hidden unless all is true. Use offset/limit to page."
+    )]
+    fn engram_transcript(
+        &self,
+        Parameters(params): Parameters<TranscriptParams>,
+    ) -> Result<String, String> {
+        let roles = params
+            .roles
+            .unwrap_or_default()
+            .iter()
+            .map(|role| {
+                serde_json::from_value::<Role>(serde_json::Value::String(role.to_lowercase()))
+                    .map_err(|_| {
+                        format!("Unknown role '{role}'; use user, assistant, system or tool")
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let include_tool_results = params.include_tool_results.unwrap_or(false);
+        let include_thinking = params.include_thinking.unwrap_or(false);
+        let offset = params.offset.unwrap_or(0);
+        let limit = params.limit.unwrap_or(50).clamp(1, MAX_TRANSCRIPT_PAGE);
+
+        let storage = self.open_storage()?;
+        let resolved = storage
+            .resolve(&params.id)
+            .map_err(|e| format!("Failed to resolve '{}': {e}", params.id))?;
+        let bytes = storage
+            .read_part(&resolved, EngramPart::Transcript)
+            .map_err(|e| format!("Failed to read transcript: {e}"))?;
+        let text =
+            String::from_utf8(bytes).map_err(|e| format!("Failed to read transcript: {e}"))?;
+
+        // Entries past the page are still counted, for the total
+        let mut total = 0;
+        let mut end = offset;
+        let mut full = false;
+        let mut page = String::new();
+        for (index, entry) in Transcript::entries_from_jsonl(&text).enumerate() {
+            let entry = entry.map_err(|e| format!("Failed to read transcript: {e}"))?;
+            let wanted = match entry.content {
+                TranscriptContent::ToolResult { .. } => include_tool_results,
+                TranscriptContent::Thinking { .. } => include_thinking,
+                _ => true,
+            } && (roles.is_empty() || roles.contains(&entry.role));
+            if !wanted {
+                continue;
+            }
+            total += 1;
+            if total <= offset || full {
+                continue;
+            }
+            if end - offset == limit {
+                full = true;
+                continue;
+            }
+            let line = render_entry(index, &entry);
+            if end > offset && page.len() + line.len() > MAX_TRANSCRIPT_PAGE_BYTES {
+                full = true;
+                continue;
+            }
+            page.push_str(&line);
+            end += 1;
+        }
+
+        if total == 0 {
+            return Ok(format!("No transcript entries to show for {}", params.id));
+        }
+        if offset >= total {
+            return Ok(format!(
+                "Offset {offset} is past the end: {total} entries to show for {}",
+                params.id
+            ));
+        }
+        let mut out = format!("Entries {offset}..{end} of {total}\n\n{page}");
+        if end < total {
+            out.push_str(&format!(
+                "\nMore entries follow; continue with offset={end}.\n"
+            ));
+        }
         Ok(out)
     }
 
     #[tool(
-        description = "List recent engrams (most recent first). Shows ID, agent, model, date, and summary."
+        description = "List recent engrams (most recent first). Shows ID, agent, model, date, and summary. Optional by_agent and tag narrow the list; since/until restrict by creation date, given as YYYY-MM-DD (a whole UTC day) or RFC 3339 (e.g. 2026-01-15T10:00:00Z). Trivial sessions (no file changes, few tokens, short) are hidden unless all is true. Use offset/limit to page."
     )]
     fn engram_log(&self, Parameters(params): Parameters<LogParams>) -> Result<String, String> {
+        let format = Format::parse(params.format.as_deref())?;
         let storage = self.open_storage()?;
+        let hide_trivial = if params.all.unwrap_or(false) {
+            None
+        } else {
+            let config = storage
+                .repo()
+                .config()
+                .map_err(|e| format!("Failed to read git config: {e}"))?;
+            Some(TrivialThresholds::load(&config))
+        };
+        let offset = params.offset.unwrap_or(0);
+        let limit = params.limit.unwrap_or(10);
+        // One engram past the page tells whether more follow
         let opts = ListOptions {
-            limit: Some(params.limit.unwrap_or(10)),
+            limit: Some(limit.saturating_add(1)),
+            offset,
             agent_filter: params.by_agent.clone(),
+            min_cost: params.min_cost,
+            max_cost: params.max_cost,
+            min_tokens: params.min_tokens,
+            max_tokens: params.max_tokens,
+            tag: params.tag.clone(),
+            since: date_bound(params.since.as_deref(), false)?,
+            until: date_bound(params.until.as_deref(), true)?,
+            hide_trivial,
         };
-        let manifests = storage
+        let mut manifests = storage
             .list(&opts)
             .map_err(|e| format!("Failed to list engrams: {e}"))?;
+        let more = manifests.len() > limit;
+        manifests.truncate(limit);
 
+        if format == Format::Json {
+            return json::to_string(&json::LogJson {
+                schema_version: json::SCHEMA_VERSION,
+                offset,
+                more,
+                engrams: manifests,
+            });
+        }
         if manifests.is_empty() {
-            return Ok("No engrams found.".to_string());
+            return Ok(if offset == 0 {
+                "No engrams found.".to_string()
+            } else {
+                format!("No engrams past offset {offset}.")
+            });
         }
 
-        let mut out = format!("{} engram(s):\n\n", manifests.len());
+        let head = format!(
+            "Engrams {}–{}, most recent first:\n\n",
+            offset + 1,
+            offset + manifests.len()
+        );
+        let mut out = BoundedText::new(self.max_response_chars, head);
         for m in &manifests {
             let short_id = &m.id.as_str()[..8.min(m.id.as_str().len())];
             let summary = m.summary.as_deref().unwrap_or("(no summary)");
@@ -257,51 +780,282 @@ impl EngramMcpServer {
                 .cost_usd
                 .map(|c| format!(" ${c:.2}"))
                 .unwrap_or_default();
-            out.push_str(&format!(
+            out.push(&format!(
                 "- {short_id} [{agent}/{model}] {date} {tokens}tok{cost}\n  {summary}\n"
             ));
         }
+        if more && !out.truncated() {
+            let next = offset + manifests.len();
+            out.push_str(&format!(
+                "\nMore engrams follow; continue with offset={next}.\n"
+            ));
+        }
+        Ok(out.finish(|shown, dropped| {
+            let next = offset + shown;
+            if more {
+                format!("truncated; more engrams follow — call again with offset={next} or a narrower query")
+            } else {
+                paging_hint(dropped, "engrams", next)
+            }
+        }))
+    }
+
+    #[tool(
+        description = "Aggregate token and cost statistics across all engrams. With by_agent, also per agent and model: engram count, capture modes, tokens, cost, files touched, and last activity."
+    )]
+    fn engram_stats(&self, Parameters(params): Parameters<StatsParams>) -> Result<String, String> {
+        let storage = self.open_storage()?;
+        let manifests = storage
+            .list(&ListOptions::default())
+            .map_err(|e| format!("Failed to list engrams: {e}"))?;
+
+        if manifests.is_empty() {
+            return Ok("No engrams found.".to_string());
+        }
+
+        let total_tokens: u64 = manifests.iter().map(|m| m.token_usage.total_tokens).sum();
+        let total_cost: f64 = manifests
+            .iter()
+            .filter_map(|m| m.token_usage.cost_usd)
+            .sum();
+        let mut out = format!(
+            "{} engram(s), {total_tokens} tokens, ${total_cost:.2}\n",
+            manifests.len()
+        );
+
+        if params.by_agent.unwrap_or(false) {
+            let stats = agent_stats_for(&storage, &manifests)
+                .map_err(|e| format!("Failed to compute agent statistics: {e}"))?;
+            out.push('\n');
+            for s in &stats {
+                let model = s.model.as_deref().unwrap_or("");
+                let modes: Vec<_> = s
+                    .capture_modes
+                    .iter()
+                    .map(|(mode, count)| format!("{count} {mode}"))
+                    .collect();
+                out.push_str(&format!(
+                    "- {}/{model}: {} engram(s) ({}), {} tokens ({:.0}/session), ${:.2}, {} file(s), last active {}\n",
+                    s.agent,
+                    s.engrams,
+                    modes.join(", "),
+                    s.total_tokens,
+                    s.avg_tokens,
+                    s.cost_usd,
+                    s.files_touched,
+                    s.last_active.format("%Y-%m-%d %H:%M")
+                ));
+            }
+        }
+        Ok(out)
+    }
+
+    #[tool(
+        description = "Find engrams related to one: its lineage parent and children, then others ranked by shared files, commits, tags, and similar intent, each with the reason."
+    )]
+    fn engram_related(
+        &self,
+        Parameters(params): Parameters<RelatedParams>,
+    ) -> Result<String, String> {
+        let storage = self.open_storage()?;
+        let related = related_engrams(&storage, &params.id, params.limit.unwrap_or(10))
+            .map_err(|e| format!("Failed to find related engrams: {e}"))?;
+
+        if related.is_empty() {
+            return Ok(format!("No engrams related to {}", params.id));
+        }
+
+        let mut out = format!("{} engram(s) related to {}:\n\n", related.len(), params.id);
+        for r in &related {
+            let m = &r.manifest;
+            let short_id = &m.id.as_str()[..8.min(m.id.as_str().len())];
+            let summary = m.summary.as_deref().unwrap_or("(no summary)");
+            let date = m.created_at.format("%Y-%m-%d %H:%M");
+            let label = match r.relation {
+                Relation::Parent => "parent".to_string(),
+                Relation::Child => "child".to_string(),
+                Relation::Similar => format!("score {:.2}", r.score),
+            };
+            out.push_str(&format!("- {short_id} [{label}] {date}\n  {summary}\n"));
+            for reason in &r.reasons {
+                out.push_str(&format!("  {reason}\n"));
+            }
+        }
         Ok(out)
     }
 
     #[tool(
-        description = "Trace the full reasoning history of a file. Shows every engram that created, modified, or deleted the file."
+        description = "Summarize the engrams behind a branch in Markdown: tokens, cost and commits, then each engram's commit, summary, changed files and dead ends. With by_file, list the engrams that changed each file instead. Long reviews are truncated; use offset to continue."
+    )]
+    fn engram_review(
+        &self,
+        Parameters(params): Parameters<ReviewParams>,
+    ) -> Result<String, String> {
+        let storage = self.open_storage()?;
+        let head = params.head.as_deref().unwrap_or("HEAD");
+        let options = ReviewOptions {
+            inferred: params.inferred.unwrap_or(false),
+            ..Default::default()
+        };
+        let review = review_branch(&storage, &params.base, head, &options)
+            .map_err(|e| format!("Review failed: {e}"))?;
+
+        if review.engrams.is_empty() {
+            return Ok(format!(
+                "No engrams found in range {} ({} commit(s))",
+                review.range, review.total_commits
+            ));
+        }
+
+        let mut head = format!(
+            "## Summary\n\n{} engram(s) in {}\n\n- **Tokens:** {} total\n",
+            review.engrams.len(),
+            review.range,
+            review.total_tokens
+        );
+        if let Some(cost) = review.total_cost {
+            head.push_str(&format!("- **Cost:** ${cost:.2}\n"));
+        }
+        head.push_str(&format!("- **Commits:** {}\n\n", review.total_commits));
+
+        let offset = params.offset.unwrap_or(0);
+        if params.by_file.unwrap_or(false) {
+            head.push_str("## Changes\n\n");
+            let mut out = BoundedText::new(self.max_response_chars, head);
+            for file in review.by_file().iter().skip(offset) {
+                out.push(&files_markdown(std::slice::from_ref(file)));
+            }
+            return Ok(out.finish(|shown, dropped| paging_hint(dropped, "files", offset + shown)));
+        }
+
+        head.push_str("## Reasoning\n\n");
+        let mut out = BoundedText::new(self.max_response_chars, head);
+        for entry in review.engrams.iter().skip(offset) {
+            let m = &entry.manifest;
+            let model = m.agent.model.as_deref().unwrap_or("unknown");
+            let summary = m.summary.as_deref().unwrap_or("(no summary)");
+            let mut text = match &entry.commit_sha {
+                Some(sha) => format!(
+                    "### {} ({}/{model})\n\n",
+                    &sha[..8.min(sha.len())],
+                    m.agent.name
+                ),
+                None => format!(
+                    "### {} ({}/{model}, inferred)\n\n",
+                    &m.id.as_str()[..8.min(m.id.as_str().len())],
+                    m.agent.name
+                ),
+            };
+            text.push_str(&format!("{summary}\n\n"));
+            for fc in &entry.file_changes {
+                text.push_str(&format!(
+                    "- `{}` — {}\n",
+                    fc.path,
+                    change_label(&fc.change_type)
+                ));
+            }
+            for de in &entry.dead_ends {
+                text.push_str(&format!("- Dead end: {} — {}\n", de.approach, de.reason));
+            }
+            text.push('\n');
+            out.push(&text);
+        }
+        Ok(out.finish(|shown, dropped| paging_hint(dropped, "engrams", offset + shown)))
+    }
+
+    #[tool(
+        description = "Render the context graph linking engrams, files, agents and commits as a Mermaid flowchart or Graphviz DOT, optionally around a focus node up to a depth. Large graphs must be narrowed with a focus."
+    )]
+    fn engram_graph(&self, Parameters(params): Parameters<GraphParams>) -> Result<String, String> {
+        let dot = match params.format.as_deref() {
+            None | Some("mermaid") => false,
+            Some("dot") => true,
+            Some(other) => {
+                return Err(format!(
+                    "Unknown format '{other}'; use \"mermaid\" or \"dot\""
+                ))
+            }
+        };
+        let storage = self.open_storage()?;
+        let full_graph =
+            build_graph(&storage).map_err(|e| format!("Failed to build graph: {e}"))?;
+        if full_graph.nodes.is_empty() {
+            return Ok("No engrams found.".to_string());
+        }
+
+        let graph = match &params.focus {
+            Some(reference) => {
+                let focus = full_graph
+                    .resolve_node(reference)
+                    .ok_or_else(|| format!("No graph node matches '{reference}'"))?;
+                full_graph.subgraph(&focus, params.depth.unwrap_or(2))
+            }
+            None => full_graph,
+        };
+        if graph.nodes.len() > MAX_GRAPH_NODES {
+            return Err(format!(
+                "The graph has {} nodes; the limit is {MAX_GRAPH_NODES}. \
+                 Pass a focus node or a smaller depth.",
+                graph.nodes.len()
+            ));
+        }
+        Ok(if dot {
+            graph.to_dot()
+        } else {
+            graph.to_mermaid()
+        })
+    }
+
+    #[tool(
+        description = "Trace the full reasoning history of a file. Shows every engram that created, modified, renamed, or deleted the file, including under its earlier names. Use offset to continue a truncated trace."
     )]
     fn engram_trace(&self, Parameters(params): Parameters<TraceParams>) -> Result<String, String> {
+        let format = Format::parse(params.format.as_deref())?;
         let storage = self.open_storage()?;
-        let engine =
-            SearchEngine::open(&storage).map_err(|e| format!("Failed to open search: {e}"))?;
-        let results = engine
-            .search_by_file(&storage, &params.file_path, 20)
+        let engine = self.search_engine(&storage)?;
+        // Absolute paths, such as an editor's, become repository-relative
+        let file_path = match storage.workdir() {
+            Some(workdir) => engram_core::paths::repo_relative(workdir, workdir, &params.file_path)
+                .map_err(|e| e.to_string())?,
+            None => params.file_path.clone(),
+        };
+        let results = trace_file(&storage, &engine, &file_path, true)
             .map_err(|e| format!("Trace failed: {e}"))?;
+        if format == Format::Json {
+            return json::to_string(&json::TraceJson::new(&file_path, results));
+        }
 
         if results.is_empty() {
-            return Ok(format!(
-                "No engrams found that touched: {}",
-                params.file_path
-            ));
+            return Ok(format!("No engrams found that touched: {}", file_path));
         }
 
-        let mut out = format!(
+        let head = format!(
             "Reasoning trace for {} ({} engram(s)):\n\n",
-            params.file_path,
+            file_path,
             results.len()
         );
-        for r in &results {
+        let offset = params.offset.unwrap_or(0);
+        let mut out = BoundedText::new(self.max_response_chars, head);
+        for r in results.iter().skip(offset) {
             let m = &r.manifest;
             let short_id = &m.id.as_str()[..8.min(m.id.as_str().len())];
             let summary = m.summary.as_deref().unwrap_or("(no summary)");
             let agent = &m.agent.name;
             let date = m.created_at.format("%Y-%m-%d %H:%M");
-            out.push_str(&format!("- {short_id} [{agent}] {date}\n  {summary}\n"));
+            let change = &r.change_type;
+            out.push(&format!(
+                "- {short_id} [{agent}] {date} {change} {}\n  {summary}\n",
+                r.path
+            ));
         }
-        Ok(out)
+        Ok(out.finish(|shown, dropped| paging_hint(dropped, "engrams", offset + shown)))
     }
 
     #[tool(
-        description = "Compare two engrams showing common files, unique files, and token/cost deltas."
+        description = "Compare two engrams showing common files, unique files, token/cost deltas, and how their reasoning differs: dead ends and decisions in only one, a changed interpreted goal, tool call counts, and transcript length. Set full to list the differing items."
     )]
     fn engram_diff(&self, Parameters(params): Parameters<DiffParams>) -> Result<String, String> {
+        let format = Format::parse(params.format.as_deref())?;
         let storage = self.open_storage()?;
         let data_a = storage
             .read(&params.id_a)
@@ -312,16 +1066,20 @@ impl EngramMcpServer {
 
         let diff: EngramDiff = diff_engrams(&storage, &data_a.manifest.id, &data_b.manifest.id)
             .map_err(|e| format!("Diff failed: {e}"))?;
+        if format == Format::Json {
+            return json::to_string(&json::DiffJson::from(diff));
+        }
 
         let short_a = &diff.id_a.as_str()[..8.min(diff.id_a.as_str().len())];
         let short_b = &diff.id_b.as_str()[..8.min(diff.id_b.as_str().len())];
 
-        let mut out = format!("Comparing {short_a} vs {short_b}\n\n");
+        let head = format!("Comparing {short_a} vs {short_b}\n\n");
+        let mut out = BoundedText::new(self.max_response_chars, head);
 
         if !diff.common_files.is_empty() {
             out.push_str(&format!("Common files ({}):\n", diff.common_files.len()));
             for f in &diff.common_files {
-                out.push_str(&format!("  {f}\n"));
+                out.push(&format!("  {f}\n"));
             }
         }
         if !diff.only_a_files.is_empty() {
@@ -330,7 +1088,7 @@ impl EngramMcpServer {
                 diff.only_a_files.len()
             ));
             for f in &diff.only_a_files {
-                out.push_str(&format!("  {f}\n"));
+                out.push(&format!("  {f}\n"));
             }
         }
         if !diff.only_b_files.is_empty() {
@@ -339,7 +1097,7 @@ impl EngramMcpServer {
                 diff.only_b_files.len()
             ));
             for f in &diff.only_b_files {
-                out.push_str(&format!("  {f}\n"));
+                out.push(&format!("  {f}\n"));
             }
         }
 
@@ -347,12 +1105,69 @@ impl EngramMcpServer {
         if let Some(cost) = diff.cost_delta {
             out.push_str(&format!("Cost delta: {:+.4}\n", cost));
         }
+        out.push_str(&format!(
+            "Transcript delta: {:+} entries\n",
+            diff.transcript_entry_delta
+        ));
 
-        Ok(out)
+        let full = params.full.unwrap_or(false);
+        if let Some((a, b)) = &diff.intent_changed {
+            if full {
+                out.push_str(&format!(
+                    "\nInterpreted goal:\n  {short_a}: {a}\n  {short_b}: {b}\n"
+                ));
+            } else {
+                out.push_str("\nInterpreted goal changed\n");
+            }
+        }
+        for (short, dead_ends) in [
+            (short_a, &diff.dead_ends_only_a),
+            (short_b, &diff.dead_ends_only_b),
+        ] {
+            if !dead_ends.is_empty() {
+                out.push_str(&format!(
+                    "Dead ends only in {short} ({}):\n",
+                    dead_ends.len()
+                ));
+                for de in dead_ends.iter().filter(|_| full) {
+                    out.push(&format!("  - {}: {}\n", de.approach, de.reason));
+                }
+            }
+        }
+        for (short, decisions) in [
+            (short_a, &diff.decisions_only_a),
+            (short_b, &diff.decisions_only_b),
+        ] {
+            if !decisions.is_empty() {
+                out.push_str(&format!(
+                    "Decisions only in {short} ({}):\n",
+                    decisions.len()
+                ));
+                for d in decisions.iter().filter(|_| full) {
+                    out.push(&format!("  - {}: {}\n", d.description, d.rationale));
+                }
+            }
+        }
+        let tools: Vec<_> = diff
+            .tool_call_counts
+            .iter()
+            .filter(|(_, (a, b))| full || a != b)
+            .map(|(tool, (a, b))| format!("{tool} {a} -> {b}"))
+            .collect();
+        if !tools.is_empty() {
+            out.push(&format!("Tool calls: {}\n", tools.join(", ")));
+        }
+
+        Ok(out.finish(|_, dropped| {
+            format!(
+                "truncated; {dropped} more files, dead ends or decisions — \
+                 call again with format=\"json\" for the whole diff"
+            )
+        }))
     }
 
     #[tool(
-        description = "Surface rejected approaches (dead ends) and architectural decisions. Search across all engrams or get dead ends from a specific engram."
+        description = "Surface rejected approaches (dead ends) and architectural decisions. Search across all engrams or get dead ends from a specific engram. At most 50 engrams are covered per call; use offset to page."
     )]
     fn engram_dead_ends(
         &self,
@@ -365,127 +1180,1426 @@ impl EngramMcpServer {
             let resolved = storage
                 .resolve(id)
                 .map_err(|e| format!("Failed to resolve '{id}': {e}"))?;
-            let data = storage
-                .read(&resolved)
-                .map_err(|e| format!("Failed to read engram: {e}"))?;
+            let intent = self.read_intent(&storage, &resolved)?;
 
             let mut out = String::new();
-            if data.intent.dead_ends.is_empty() && data.intent.decisions.is_empty() {
+            if intent.dead_ends.is_empty() && intent.decisions.is_empty() {
                 return Ok(format!(
                     "No dead ends or decisions recorded for engram {}",
                     &resolved[..8.min(resolved.len())]
                 ));
             }
 
-            if !data.intent.dead_ends.is_empty() {
+            if !intent.dead_ends.is_empty() {
                 out.push_str("Dead Ends:\n");
-                for de in &data.intent.dead_ends {
+                for de in &intent.dead_ends {
                     out.push_str(&format!("  - {}: {}\n", de.approach, de.reason));
                 }
             }
-            if !data.intent.decisions.is_empty() {
+            if !intent.decisions.is_empty() {
                 out.push_str("Decisions:\n");
-                for d in &data.intent.decisions {
+                for d in &intent.decisions {
                     out.push_str(&format!("  - {}: {}\n", d.description, d.rationale));
                 }
             }
             return Ok(out);
         }
 
-        // Search across all engrams for dead ends
-        let opts = ListOptions {
-            limit: Some(50),
-            agent_filter: None,
+        // With a query, the index picks the engrams worth reading; without
+        // one, walk the most recent engrams
+        let query = params.query.as_deref().unwrap_or("").trim();
+        let offset = params.offset.unwrap_or(0);
+        let (manifests, total) = if query.is_empty() {
+            let all = storage
+                .list(&ListOptions::default())
+                .map_err(|e| format!("Failed to list engrams: {e}"))?;
+            let total = all.len();
+            let page: Vec<_> = all
+                .into_iter()
+                .skip(offset)
+                .take(MAX_DEAD_END_ENGRAMS)
+                .collect();
+            (page, total)
+        } else {
+            let engine = self.search_engine(&storage)?;
+            let page = engine
+                .search_insights(&storage, query, MAX_DEAD_END_ENGRAMS, offset)
+                .map_err(|e| format!("Search failed: {e}"))?;
+            let manifests = page.results.into_iter().map(|r| r.manifest).collect();
+            (manifests, page.total)
         };
-        let manifests = storage
-            .list(&opts)
-            .map_err(|e| format!("Failed to list engrams: {e}"))?;
 
-        let query_lower = params.query.as_deref().unwrap_or("").to_lowercase();
+        let query_lower = query.to_lowercase();
         let mut out = String::new();
         let mut found = 0;
 
         for m in &manifests {
-            if let Ok(data) = storage.read(m.id.as_str()) {
-                let matching_dead_ends: Vec<_> = data
-                    .intent
-                    .dead_ends
-                    .iter()
-                    .filter(|de| {
-                        query_lower.is_empty()
-                            || de.approach.to_lowercase().contains(&query_lower)
-                            || de.reason.to_lowercase().contains(&query_lower)
-                    })
-                    .collect();
+            let Ok(intent) = self.read_intent(&storage, m.id.as_str()) else {
+                continue;
+            };
+            let matching_dead_ends: Vec<_> = intent
+                .dead_ends
+                .iter()
+                .filter(|de| {
+                    query_lower.is_empty()
+                        || de.approach.to_lowercase().contains(&query_lower)
+                        || de.reason.to_lowercase().contains(&query_lower)
+                })
+                .collect();
 
-                let matching_decisions: Vec<_> = data
-                    .intent
-                    .decisions
-                    .iter()
-                    .filter(|d| {
-                        query_lower.is_empty()
-                            || d.description.to_lowercase().contains(&query_lower)
-                            || d.rationale.to_lowercase().contains(&query_lower)
-                    })
-                    .collect();
+            let matching_decisions: Vec<_> = intent
+                .decisions
+                .iter()
+                .filter(|d| {
+                    query_lower.is_empty()
+                        || d.description.to_lowercase().contains(&query_lower)
+                        || d.rationale.to_lowercase().contains(&query_lower)
+                })
+                .collect();
 
-                if !matching_dead_ends.is_empty() || !matching_decisions.is_empty() {
-                    let short_id = &m.id.as_str()[..8.min(m.id.as_str().len())];
-                    let summary = m.summary.as_deref().unwrap_or("(no summary)");
-                    out.push_str(&format!("{short_id} - {summary}:\n"));
+            if !matching_dead_ends.is_empty() || !matching_decisions.is_empty() {
+                let short_id = &m.id.as_str()[..8.min(m.id.as_str().len())];
+                let summary = m.summary.as_deref().unwrap_or("(no summary)");
+                out.push_str(&format!("{short_id} - {summary}:\n"));
 
-                    for de in &matching_dead_ends {
-                        out.push_str(&format!("  Dead end: {} — {}\n", de.approach, de.reason));
-                        found += 1;
-                    }
-                    for d in &matching_decisions {
-                        out.push_str(&format!(
-                            "  Decision: {} — {}\n",
-                            d.description, d.rationale
-                        ));
-                        found += 1;
-                    }
-                    out.push('\n');
+                for de in &matching_dead_ends {
+                    out.push_str(&format!("  Dead end: {} — {}\n", de.approach, de.reason));
+                    found += 1;
                 }
+                for d in &matching_decisions {
+                    out.push_str(&format!(
+                        "  Decision: {} — {}\n",
+                        d.description, d.rationale
+                    ));
+                    found += 1;
+                }
+                out.push('\n');
             }
         }
 
+        let end = offset + manifests.len();
+        let more = if end < total {
+            format!("More engrams follow; continue with offset={end}.\n")
+        } else {
+            String::new()
+        };
         if found == 0 {
-            if query_lower.is_empty() {
-                return Ok("No dead ends or decisions found in any engrams.".to_string());
-            }
-            return Ok(format!(
-                "No dead ends or decisions matching '{}' found.",
-                params.query.as_deref().unwrap_or("")
-            ));
+            let none = if query.is_empty() {
+                "No dead ends or decisions found in any engrams.".to_string()
+            } else {
+                format!("No dead ends or decisions matching '{query}' found.")
+            };
+            return Ok(if more.is_empty() {
+                none
+            } else {
+                format!("{none}\n{more}")
+            });
         }
 
+        out.push_str(&more);
         Ok(out)
     }
+
+    #[tool(
+        description = "Record a finished session as a new engram: the request, messages, tool calls, file changes, dead ends, decisions, tokens and tags. Returns the new engram's ID."
+    )]
+    fn engram_record(
+        &self,
+        Parameters(params): Parameters<RecordParams>,
+    ) -> Result<String, String> {
+        let storage = self.open_initialized()?;
+
+        let messages = params.messages.unwrap_or_default();
+        let transcript_bytes: usize = messages.iter().map(|m| m.content.len()).sum();
+        if transcript_bytes > MAX_TRANSCRIPT_BYTES {
+            return Err(format!(
+                "Transcript is {transcript_bytes} bytes; the limit is {MAX_TRANSCRIPT_BYTES}. \
+                 Record a summary of long messages instead."
+            ));
+        }
+
+        let mut session = EngramSession::begin(&params.agent, params.model.as_deref());
+        session.set_request(&params.original_request);
+        if let Some(parent) = &params.parent_id {
+            let resolved = storage
+                .resolve(parent)
+                .map_err(|e| format!("Failed to resolve parent '{parent}': {e}"))?;
+            session.parent(EngramId(resolved));
+        }
+        for m in &messages {
+            session.log_message(&m.role, &m.content);
+        }
+        for call in params.tool_calls.unwrap_or_default() {
+            let input = call.input.map(|v| v.to_string()).unwrap_or_default();
+            session.log_tool_call(&call.tool_name, &input, call.output_summary.as_deref());
+        }
+        for change in params.file_changes.unwrap_or_default() {
+            let change_type = change.change_type.as_deref().unwrap_or("modified");
+            session.log_file_change(&change.path, change_type);
+        }
+        for de in params.dead_ends.unwrap_or_default() {
+            session.log_rejection(&de.approach, &de.reason);
+        }
+        for d in params.decisions.unwrap_or_default() {
+            session.log_decision(&d.description, &d.rationale);
+        }
+        if let Some(tokens) = params.tokens {
+            session.add_tokens(
+                tokens.input.unwrap_or(0),
+                tokens.output.unwrap_or(0),
+                tokens.cost,
+            );
+        }
+        for tag in params.tags.unwrap_or_default() {
+            session.tag(&tag);
+        }
+
+        let data = session.build(None, params.summary.as_deref());
+        let id = storage
+            .create(&data)
+            .map_err(|e| format!("Failed to store engram: {e}"))?;
+        // Stored either way; an index update that fails is only reported
+        if let Ok(search) = self.search_engine(&storage) {
+            if let Err(e) = search.index_engram(&data) {
+                return Ok(format!(
+                    "Recorded engram {id}, but did not index it: {e}. \
+                     Run `engram reindex --incremental` to catch up."
+                ));
+            }
+        }
+        Ok(format!("Recorded engram {id}"))
+    }
+
+    #[tool(
+        description = "Add a rejected approach (dead end) to the session being recorded by `engram record`, so it is saved with the engram when the session ends."
+    )]
+    fn engram_append_dead_end(
+        &self,
+        Parameters(params): Parameters<DeadEndParam>,
+    ) -> Result<String, String> {
+        let storage = self.open_initialized()?;
+        let git_dir = storage.repo().path();
+        let mut session = ActiveSession::load(git_dir).ok_or(
+            "No session is being recorded here. Use engram_record to record a finished session.",
+        )?;
+        session
+            .add_dead_end(
+                DeadEnd {
+                    approach: params.approach,
+                    reason: params.reason,
+                },
+                git_dir,
+            )
+            .map_err(|e| format!("Failed to update the session: {e}"))?;
+        Ok(format!(
+            "Added dead end {} to the session recording engram {}",
+            session.dead_ends.len(),
+            &session.engram_id.as_str()[..8.min(session.engram_id.as_str().len())]
+        ))
+    }
+
+    #[tool(
+        description = "Add a note to an existing engram, for something learned about it later (e.g. that its approach was reverted). Notes are listed at the bottom of engram_show."
+    )]
+    fn engram_annotate(
+        &self,
+        Parameters(params): Parameters<AnnotateParams>,
+    ) -> Result<String, String> {
+        let text = params.text.trim();
+        if text.is_empty() {
+            return Err("Note text is empty".into());
+        }
+        let storage = self.open_initialized()?;
+        let resolved = storage
+            .resolve(&params.id)
+            .map_err(|e| format!("Failed to resolve '{}': {e}", params.id))?;
+        let author = params.author.as_deref().unwrap_or("agent");
+        storage
+            .add_note(&resolved, author, text)
+            .map_err(|e| format!("Failed to add note: {e}"))?;
+        let count = storage.read_notes(&resolved).map_or(0, |notes| notes.len());
+        Ok(format!(
+            "Added note {count} to engram {}",
+            &resolved[..8.min(resolved.len())]
+        ))
+    }
+}
+
+/// A `since`/`until` parameter as a time; see [`parse_date_bound`].
+fn date_bound(
+    value: Option<&str>,
+    end_of_day: bool,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, String> {
+    value
+        .map(|v| parse_date_bound(v, end_of_day))
+        .transpose()
+        .map_err(|e| e.to_string())
+}
+
+/// One transcript entry as `#<index> <time> <role>: <content>`, with
+/// content over [`MAX_ENTRY_CHARS`] cut short and later lines indented.
+fn render_entry(index: usize, entry: &TranscriptEntry) -> String {
+    let (label, content) = match &entry.content {
+        TranscriptContent::Text { text } => (String::new(), text.clone()),
+        TranscriptContent::Thinking { text } => ("(thinking) ".into(), text.clone()),
+        TranscriptContent::ToolUse {
+            tool_name, input, ..
+        } => (format!("(tool call) {tool_name} "), input.to_string()),
+        TranscriptContent::ToolResult {
+            output, is_error, ..
+        } => {
+            let label = if *is_error {
+                "tool error"
+            } else {
+                "tool result"
+            };
+            (format!("({label}) "), output.clone())
+        }
+    };
+    let content = match content.char_indices().nth(MAX_ENTRY_CHARS) {
+        Some((cut, _)) => format!(
+            "{}… [{} more chars]",
+            &content[..cut],
+            content[cut..].chars().count()
+        ),
+        None => content,
+    };
+    let content = content.trim_end().replace('\n', "\n    ");
+    let role = match entry.role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::System => "system",
+        Role::Tool => "tool",
+    };
+    format!(
+        "#{index} {} {role}: {label}{content}\n",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S")
+    )
 }
 
 #[tool_handler]
+#[prompt_handler]
 impl ServerHandler for EngramMcpServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             instructions: Some(
                 "Engram MCP Server - Query agent reasoning history stored in Git. \
-                 Search reasoning, trace file history, surface dead ends and decisions."
+                 Search reasoning, trace file history, surface dead ends and decisions, \
+                 summarize branches and map the context graph. \
+                 Record your own sessions with engram_record. \
+                 Prompts: explain-file-history, pre-change-check, pr-context."
                     .into(),
             ),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_prompts()
+                .build(),
             ..Default::default()
         }
     }
 }
 
 /// Start the MCP server on stdio transport.
-pub async fn run_stdio(repo_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn run_stdio(
+    repo_path: PathBuf,
+    cache_ttl: Duration,
+    max_response_chars: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
     use rmcp::transport::stdio;
     use rmcp::ServiceExt;
 
-    let server = EngramMcpServer::new(repo_path);
+    let server = EngramMcpServer::new(repo_path, cache_ttl, max_response_chars);
     let service = server.serve(stdio()).await?;
     service.waiting().await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn initialized_repo() -> (TempDir, EngramMcpServer) {
+        let tmp = TempDir::new().unwrap();
+        git2::Repository::init(tmp.path()).unwrap();
+        GitStorage::open(tmp.path()).unwrap().init().unwrap();
+        let server = EngramMcpServer::new(
+            tmp.path().to_path_buf(),
+            DEFAULT_CACHE_TTL,
+            DEFAULT_MAX_RESPONSE_CHARS,
+        );
+        (tmp, server)
+    }
+
+    fn record_params(request: &str) -> RecordParams {
+        RecordParams {
+            agent: "cursor".into(),
+            model: Some("gpt-5".into()),
+            original_request: request.into(),
+            summary: None,
+            messages: None,
+            tool_calls: None,
+            file_changes: None,
+            dead_ends: None,
+            decisions: None,
+            tokens: None,
+            tags: None,
+            parent_id: None,
+        }
+    }
+
+    fn recorded_id(output: &str) -> String {
+        output.strip_prefix("Recorded engram ").unwrap().to_string()
+    }
+
+    #[test]
+    fn test_record_and_read_back() {
+        let (tmp, server) = initialized_repo();
+        let first = server
+            .engram_record(Parameters(record_params("Add caching")))
+            .unwrap();
+        let parent = recorded_id(&first);
+
+        let params = RecordParams {
+            summary: Some("Cache user lookups".into()),
+            messages: Some(vec![
+                MessageParam {
+                    role: "user".into(),
+                    content: "Cache the user lookups".into(),
+                },
+                MessageParam {
+                    role: "assistant".into(),
+                    content: "Added an LRU cache.".into(),
+                },
+            ]),
+            tool_calls: Some(vec![ToolCallParam {
+                tool_name: "edit".into(),
+                input: Some(serde_json::json!({"path": "src/users.rs"})),
+                output_summary: None,
+            }]),
+            file_changes: Some(vec![FileChangeParam {
+                path: "src/users.rs".into(),
+                change_type: None,
+            }]),
+            dead_ends: Some(vec![DeadEndParam {
+                approach: "Redis".into(),
+                reason: "No new services".into(),
+            }]),
+            decisions: Some(vec![DecisionParam {
+                description: "LRU of 1000 entries".into(),
+                rationale: "Fits the working set".into(),
+            }]),
+            tokens: Some(TokensParam {
+                input: Some(1200),
+                output: Some(300),
+                cost: Some(0.01),
+            }),
+            tags: Some(vec!["perf".into()]),
+            parent_id: Some(parent[..8].to_string()),
+            ..record_params("Add caching to user lookups")
+        };
+        let id = recorded_id(&server.engram_record(Parameters(params)).unwrap());
+
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        let data = storage.read(&id).unwrap();
+        assert_eq!(data.manifest.agent.name, "cursor");
+        assert_eq!(data.manifest.summary.as_deref(), Some("Cache user lookups"));
+        assert_eq!(data.manifest.tags, ["perf"]);
+        assert_eq!(data.manifest.token_usage.total_tokens, 1500);
+        assert_eq!(data.intent.original_request, "Add caching to user lookups");
+        assert_eq!(data.intent.dead_ends[0].approach, "Redis");
+        assert_eq!(data.intent.decisions.len(), 1);
+        assert_eq!(data.transcript.entries.len(), 2);
+        assert_eq!(data.operations.tool_calls[0].input["path"], "src/users.rs");
+        assert_eq!(
+            data.operations.file_changes[0].change_type,
+            FileChangeType::Modified
+        );
+        assert_eq!(
+            data.lineage.parent_engram.map(|p| p.0),
+            Some(parent.clone())
+        );
+
+        // <id>~1 is the parent engram
+        let show = |id: String| {
+            server.engram_show(Parameters(ShowParams {
+                id,
+                format: Some("json".into()),
+            }))
+        };
+        let shown: serde_json::Value =
+            serde_json::from_str(&show(format!("{id}~1")).unwrap()).unwrap();
+        assert_eq!(shown["manifest"]["id"], parent);
+        let err = show("HEAD~2".into()).unwrap_err();
+        assert!(err.contains("only 2 engram(s) exist"), "{err}");
+    }
+
+    #[test]
+    fn test_record_rejects_bad_input() {
+        let tmp = TempDir::new().unwrap();
+        git2::Repository::init(tmp.path()).unwrap();
+        let server = EngramMcpServer::new(
+            tmp.path().to_path_buf(),
+            DEFAULT_CACHE_TTL,
+            DEFAULT_MAX_RESPONSE_CHARS,
+        );
+        let err = server
+            .engram_record(Parameters(record_params("Add caching")))
+            .unwrap_err();
+        assert!(err.contains("not initialized"), "{err}");
+
+        let (_tmp, server) = initialized_repo();
+        let params = RecordParams {
+            messages: Some(vec![MessageParam {
+                role: "assistant".into(),
+                content: "x".repeat(MAX_TRANSCRIPT_BYTES + 1),
+            }]),
+            ..record_params("Dump everything")
+        };
+        let err = server.engram_record(Parameters(params)).unwrap_err();
+        assert!(err.contains("limit"), "{err}");
+    }
+
+    #[test]
+    fn test_append_dead_end_to_active_session() {
+        let (tmp, server) = initialized_repo();
+        let dead_end = || DeadEndParam {
+            approach: "Polling".into(),
+            reason: "Too slow".into(),
+        };
+        let err = server
+            .engram_append_dead_end(Parameters(dead_end()))
+            .unwrap_err();
+        assert!(err.contains("No session"), "{err}");
+
+        let git_dir = tmp.path().join(".git");
+        let session = ActiveSession::new(
+            EngramId::new(),
+            engram_core::model::AgentInfo {
+                name: "claude-code".into(),
+                model: None,
+                version: None,
+            },
+        );
+        session.save(&git_dir).unwrap();
+        server
+            .engram_append_dead_end(Parameters(dead_end()))
+            .unwrap();
+        let saved = ActiveSession::load(&git_dir).unwrap();
+        assert_eq!(saved.dead_ends[0].reason, "Too slow");
+    }
+
+    #[test]
+    fn test_annotate() {
+        let (_tmp, server) = initialized_repo();
+        let id = recorded_id(
+            &server
+                .engram_record(Parameters(record_params("Add caching")))
+                .unwrap(),
+        );
+        let annotate = |text: &str, author: Option<&str>| {
+            server.engram_annotate(Parameters(AnnotateParams {
+                id: id[..8].to_string(),
+                text: text.into(),
+                author: author.map(str::to_string),
+            }))
+        };
+
+        let show = |format: Option<&str>| {
+            server
+                .engram_show(Parameters(ShowParams {
+                    id: id.clone(),
+                    format: format.map(str::to_string),
+                }))
+                .unwrap()
+        };
+        assert!(!show(None).contains("Notes:"));
+        // Recorded in one call, so the session took no time
+        assert!(show(None).contains("\nDuration: 0s\n"), "{}", show(None));
+
+        let out = annotate("Reverted in 3f2a91c", None).unwrap();
+        assert_eq!(out, format!("Added note 1 to engram {}", &id[..8]));
+        annotate("Cache came back with a TTL", Some("alice")).unwrap();
+        assert!(annotate("  ", None).unwrap_err().contains("empty"));
+
+        let text = show(None);
+        let notes = text.split_once("\nNotes:\n").unwrap().1;
+        let lines: Vec<_> = notes.lines().collect();
+        assert_eq!(lines.len(), 2, "{text}");
+        assert!(lines[0].ends_with(" agent: Reverted in 3f2a91c"), "{text}");
+        assert!(
+            lines[1].ends_with(" alice: Cache came back with a TTL"),
+            "{text}"
+        );
+
+        let json: json::ShowJson = serde_json::from_str(&show(Some("json"))).unwrap();
+        let texts: Vec<_> = json.notes.iter().map(|n| n.text.as_str()).collect();
+        assert_eq!(texts, ["Reverted in 3f2a91c", "Cache came back with a TTL"]);
+        assert_eq!(json.manifest.id.as_str(), id);
+    }
+
+    /// Record an engram that modified `path`, returning its ID.
+    fn record_change(server: &EngramMcpServer, summary: &str, path: &str) -> String {
+        let params = RecordParams {
+            summary: Some(summary.into()),
+            file_changes: Some(vec![FileChangeParam {
+                path: path.into(),
+                change_type: None,
+            }]),
+            dead_ends: Some(vec![DeadEndParam {
+                approach: "Polling".into(),
+                reason: "Too slow".into(),
+            }]),
+            tokens: Some(TokensParam {
+                input: Some(100),
+                output: Some(20),
+                cost: Some(0.5),
+            }),
+            ..record_params(summary)
+        };
+        recorded_id(&server.engram_record(Parameters(params)).unwrap())
+    }
+
+    /// Commit on HEAD with `message`.
+    fn commit(repo: &git2::Repository, message: &str) -> git2::Oid {
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_stats() {
+        let (_tmp, server) = initialized_repo();
+        let out = server
+            .engram_stats(Parameters(StatsParams { by_agent: None }))
+            .unwrap();
+        assert_eq!(out, "No engrams found.");
+
+        record_change(&server, "Add caching", "src/cache.rs");
+        record_change(&server, "Tune caching", "src/cache.rs");
+        let out = server
+            .engram_stats(Parameters(StatsParams {
+                by_agent: Some(true),
+            }))
+            .unwrap();
+        assert!(out.starts_with("2 engram(s), 240 tokens, $1.00"), "{out}");
+        assert!(out.contains("- cursor/gpt-5: 2 engram(s)"), "{out}");
+    }
+
+    #[test]
+    fn test_review() {
+        let (tmp, server) = initialized_repo();
+        let repo = git2::Repository::open(tmp.path()).unwrap();
+        let base = commit(&repo, "init").to_string();
+        let review = |by_file| ReviewParams {
+            base: base.clone(),
+            head: None,
+            by_file: Some(by_file),
+            inferred: None,
+            offset: None,
+        };
+
+        commit(&repo, "Unrelated");
+        let out = server.engram_review(Parameters(review(false))).unwrap();
+        assert!(out.starts_with("No engrams found in range"), "{out}");
+
+        let id = record_change(&server, "Add caching", "src/cache.rs");
+        commit(&repo, &format!("Add caching\n\nEngram-Id: {id}"));
+        let out = server.engram_review(Parameters(review(false))).unwrap();
+        assert!(out.starts_with("## Summary\n\n1 engram(s) in "), "{out}");
+        assert!(out.contains("- **Commits:** 2"), "{out}");
+        assert!(out.contains("\nAdd caching\n"), "{out}");
+        assert!(out.contains("- `src/cache.rs` — modified"), "{out}");
+        assert!(out.contains("- Dead end: Polling — Too slow"), "{out}");
+
+        let out = server.engram_review(Parameters(review(true))).unwrap();
+        assert!(out.contains("### `src/cache.rs`"), "{out}");
+
+        let err = server
+            .engram_review(Parameters(ReviewParams {
+                base: "no-such-branch".into(),
+                ..review(false)
+            }))
+            .unwrap_err();
+        assert!(err.contains("no-such-branch"), "{err}");
+    }
+
+    #[test]
+    fn test_long_review_is_paged() {
+        let tmp = TempDir::new().unwrap();
+        let repo = git2::Repository::init(tmp.path()).unwrap();
+        GitStorage::open(tmp.path()).unwrap().init().unwrap();
+        let server = EngramMcpServer::new(tmp.path().to_path_buf(), DEFAULT_CACHE_TTL, 800);
+        let base = commit(&repo, "init").to_string();
+        for i in 0..12 {
+            let id = record_change(&server, &format!("Change {i:02}"), "src/cache.rs");
+            commit(&repo, &format!("Change {i:02}\n\nEngram-Id: {id}"));
+        }
+
+        let mut seen = Vec::new();
+        let mut offset = 0;
+        loop {
+            let out = server
+                .engram_review(Parameters(ReviewParams {
+                    base: base.clone(),
+                    head: None,
+                    by_file: None,
+                    inferred: None,
+                    offset: Some(offset),
+                }))
+                .unwrap();
+            assert!(out.chars().count() <= 800, "{out}");
+            assert!(out.contains("12 engram(s)"), "{out}");
+            let shown: Vec<_> = out
+                .lines()
+                .filter(|line| line.starts_with("Change "))
+                .collect();
+            assert!(!shown.is_empty(), "{out}");
+            seen.extend(shown.iter().map(|s| s.to_string()));
+            let Some((_, hint)) = out.split_once("call again with offset=") else {
+                break;
+            };
+            offset = hint.split_once(' ').unwrap().0.parse().unwrap();
+            assert_eq!(offset, seen.len(), "{out}");
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 12);
+    }
+
+    #[test]
+    fn test_graph() {
+        let (_tmp, server) = initialized_repo();
+        let graph = |focus: Option<&str>, depth, format: Option<&str>| GraphParams {
+            focus: focus.map(String::from),
+            depth,
+            format: format.map(String::from),
+        };
+        let out = server
+            .engram_graph(Parameters(graph(None, None, None)))
+            .unwrap();
+        assert_eq!(out, "No engrams found.");
+
+        let id = record_change(&server, "Add caching", "src/cache.rs");
+        record_change(&server, "Write docs", "docs/cache.md");
+
+        let out = server
+            .engram_graph(Parameters(graph(Some(&id[..8]), Some(1), None)))
+            .unwrap();
+        assert!(out.starts_with("flowchart"), "{out}");
+        assert!(out.contains("src/cache.rs"), "{out}");
+        assert!(!out.contains("docs/cache.md"), "{out}");
+
+        let out = server
+            .engram_graph(Parameters(graph(None, None, Some("dot"))))
+            .unwrap();
+        assert!(out.starts_with("digraph engram {"), "{out}");
+        assert!(out.contains("docs/cache.md"), "{out}");
+
+        let err = server
+            .engram_graph(Parameters(graph(Some("nothing-here"), None, None)))
+            .unwrap_err();
+        assert!(err.contains("No graph node matches"), "{err}");
+        let err = server
+            .engram_graph(Parameters(graph(None, None, Some("svg"))))
+            .unwrap_err();
+        assert!(err.contains("Unknown format"), "{err}");
+    }
+
+    /// An engram whose 1000-entry transcript cycles through a user message,
+    /// thinking, a tool call and a tool result.
+    fn long_transcript(tmp: &TempDir) -> String {
+        let mut data = EngramSession::begin("claude-code", None).build(None, None);
+        let at = |i: i64| {
+            "2026-01-15T10:00:00Z"
+                .parse::<chrono::DateTime<chrono::Utc>>()
+                .unwrap()
+                + chrono::Duration::seconds(i)
+        };
+        data.transcript.entries = (0..1000)
+            .map(|i| {
+                let (role, content) = match i % 4 {
+                    0 => (
+                        Role::User,
+                        TranscriptContent::Text {
+                            text: format!("Message {i}"),
+                        },
+                    ),
+                    1 => (
+                        Role::Assistant,
+                        TranscriptContent::Thinking {
+                            text: format!("Thought {i}"),
+                        },
+                    ),
+                    2 => (
+                        Role::Assistant,
+                        TranscriptContent::ToolUse {
+                            tool_name: "Bash".into(),
+                            tool_id: format!("t{i}"),
+                            input: serde_json::json!({"command": "ls"}),
+                        },
+                    ),
+                    _ => (
+                        Role::Tool,
+                        TranscriptContent::ToolResult {
+                            tool_id: format!("t{}", i - 1),
+                            output: "x".repeat(if i == 3 { 5000 } else { 10 }),
+                            is_error: false,
+                        },
+                    ),
+                };
+                TranscriptEntry {
+                    timestamp: at(i),
+                    role,
+                    content,
+                    token_count: None,
+                }
+            })
+            .collect();
+        GitStorage::open(tmp.path())
+            .unwrap()
+            .create(&data)
+            .unwrap()
+            .0
+    }
+
+    fn transcript_params(id: &str) -> TranscriptParams {
+        TranscriptParams {
+            id: id.into(),
+            offset: None,
+            limit: None,
+            roles: None,
+            include_tool_results: None,
+            include_thinking: None,
+        }
+    }
+
+    #[test]
+    fn test_transcript_pages() {
+        let (tmp, server) = initialized_repo();
+        let id = long_transcript(&tmp);
+
+        // Thinking and tool results are left out by default
+        let out = server
+            .engram_transcript(Parameters(transcript_params(&id)))
+            .unwrap();
+        assert!(out.starts_with("Entries 0..50 of 500\n\n"), "{out}");
+        assert!(
+            out.contains("#0 2026-01-15 10:00:00 user: Message 0\n"),
+            "{out}"
+        );
+        assert!(out.contains("#2 2026-01-15 10:00:02 assistant: (tool call) Bash"));
+        assert!(!out.contains("Thought"));
+        assert!(out.ends_with("continue with offset=50.\n"), "{out}");
+
+        let out = server
+            .engram_transcript(Parameters(TranscriptParams {
+                offset: Some(490),
+                ..transcript_params(&id)
+            }))
+            .unwrap();
+        assert!(out.starts_with("Entries 490..500 of 500"), "{out}");
+        assert!(out.contains("#998 "), "{out}");
+        assert!(!out.contains("continue with"), "{out}");
+
+        let out = server
+            .engram_transcript(Parameters(TranscriptParams {
+                roles: Some(vec!["Tool".into()]),
+                include_tool_results: Some(true),
+                limit: Some(2),
+                ..transcript_params(&id)
+            }))
+            .unwrap();
+        assert!(out.starts_with("Entries 0..2 of 250"), "{out}");
+        assert!(out.contains("#3 2026-01-15 10:00:03 tool: (tool result) xxx"));
+        assert!(out.contains("… [4000 more chars]"), "{out}");
+
+        let out = server
+            .engram_transcript(Parameters(TranscriptParams {
+                include_thinking: Some(true),
+                include_tool_results: Some(true),
+                limit: Some(1000),
+                ..transcript_params(&id)
+            }))
+            .unwrap();
+        assert!(out.starts_with("Entries 0..200 of 1000"), "{out}");
+        assert!(out.contains("#1 2026-01-15 10:00:01 assistant: (thinking) Thought 1"));
+    }
+
+    #[test]
+    fn test_transcript_caps_response_size() {
+        let (tmp, server) = initialized_repo();
+        let mut data = EngramSession::begin("claude-code", None).build(None, None);
+        for i in 0..300 {
+            data.transcript.entries.push(TranscriptEntry {
+                timestamp: data.manifest.created_at,
+                role: Role::Assistant,
+                content: TranscriptContent::Text {
+                    text: format!("{i} {}", "y".repeat(2000)),
+                },
+                token_count: None,
+            });
+        }
+        let id = GitStorage::open(tmp.path())
+            .unwrap()
+            .create(&data)
+            .unwrap()
+            .0;
+
+        let out = server
+            .engram_transcript(Parameters(TranscriptParams {
+                limit: Some(200),
+                ..transcript_params(&id)
+            }))
+            .unwrap();
+        assert!(
+            out.len() <= MAX_TRANSCRIPT_PAGE_BYTES + 200,
+            "{}",
+            out.len()
+        );
+        let end: usize = out
+            .strip_prefix("Entries 0..")
+            .and_then(|rest| rest.split(' ').next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(end > 0 && end < 200, "{end}");
+        assert!(out.ends_with(&format!("continue with offset={end}.\n")));
+
+        let out = server
+            .engram_transcript(Parameters(TranscriptParams {
+                offset: Some(300),
+                ..transcript_params(&id)
+            }))
+            .unwrap();
+        assert!(out.starts_with("Offset 300 is past the end"), "{out}");
+        let err = server
+            .engram_transcript(Parameters(TranscriptParams {
+                roles: Some(vec!["robot".into()]),
+                ..transcript_params(&id)
+            }))
+            .unwrap_err();
+        assert!(err.contains("Unknown role 'robot'"), "{err}");
+    }
+
+    #[test]
+    fn test_json_outputs() {
+        let (_tmp, server) = initialized_repo();
+        let a = record_change(&server, "Add caching", "src/cache.rs");
+        let b = record_change(&server, "Tune caching", "src/cache.rs");
+        let json = Some("json".to_string());
+
+        let out = server
+            .engram_search(Parameters(SearchParams {
+                facets: Some(true),
+                format: json.clone(),
+                ..search_params("caching")
+            }))
+            .unwrap();
+        let search: json::SearchJson = serde_json::from_str(&out).unwrap();
+        assert_eq!(search.schema_version, json::SCHEMA_VERSION);
+        assert_eq!(search.total, 2);
+        assert!(search.facets.is_some());
+
+        let out = server
+            .engram_log(Parameters(LogParams {
+                limit: Some(1),
+                format: json.clone(),
+                ..log_params()
+            }))
+            .unwrap();
+        let log: json::LogJson = serde_json::from_str(&out).unwrap();
+        assert_eq!((log.more, log.engrams.len()), (true, 1));
+
+        let out = server
+            .engram_show(Parameters(ShowParams {
+                id: a[..8].to_string(),
+                format: json.clone(),
+            }))
+            .unwrap();
+        let show: json::ShowJson = serde_json::from_str(&out).unwrap();
+        assert_eq!(show.manifest.id.as_str(), a);
+        assert_eq!(show.intent.dead_ends[0].approach, "Polling");
+        assert_eq!(show.file_changes[0].path, "src/cache.rs");
+
+        let out = server
+            .engram_trace(Parameters(TraceParams {
+                file_path: "src/cache.rs".into(),
+                offset: None,
+                format: json.clone(),
+            }))
+            .unwrap();
+        let trace: json::TraceJson = serde_json::from_str(&out).unwrap();
+        assert_eq!(trace.entries.len(), 2);
+        assert_eq!(trace.entries[0].path, "src/cache.rs");
+
+        let out = server
+            .engram_diff(Parameters(DiffParams {
+                id_a: a.clone(),
+                id_b: b.clone(),
+                full: None,
+                format: json.clone(),
+            }))
+            .unwrap();
+        let diff: json::DiffJson = serde_json::from_str(&out).unwrap();
+        assert_eq!((diff.id_a.0, diff.id_b.0), (a.clone(), b));
+        assert_eq!(diff.common_files, ["src/cache.rs"]);
+        assert_eq!(diff.token_delta, 0);
+        assert!(diff.goal_changed.is_some());
+
+        // Prose stays the default; unknown formats are refused
+        let out = server
+            .engram_show(Parameters(ShowParams {
+                id: a.clone(),
+                format: None,
+            }))
+            .unwrap();
+        assert!(out.starts_with("Engram: "), "{out}");
+        let err = server
+            .engram_show(Parameters(ShowParams {
+                id: a,
+                format: Some("yaml".into()),
+            }))
+            .unwrap_err();
+        assert!(err.contains("Unknown format 'yaml'"), "{err}");
+    }
+
+    fn log_params() -> LogParams {
+        LogParams {
+            limit: None,
+            offset: None,
+            by_agent: None,
+            tag: None,
+            since: None,
+            until: None,
+            min_cost: None,
+            max_cost: None,
+            min_tokens: None,
+            max_tokens: None,
+            all: None,
+            format: None,
+        }
+    }
+
+    fn search_params(query: &str) -> SearchParams {
+        SearchParams {
+            query: query.into(),
+            limit: None,
+            offset: None,
+            agent: None,
+            file: None,
+            tag: None,
+            since: None,
+            until: None,
+            sort: None,
+            fuzzy: None,
+            facets: None,
+            repos: None,
+            min_cost: None,
+            max_cost: None,
+            min_tokens: None,
+            max_tokens: None,
+            format: None,
+        }
+    }
+
+    #[test]
+    fn test_search_and_log_filters() {
+        let (tmp, server) = initialized_repo();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        for (summary, agent, file, tag, created_at) in [
+            (
+                "alpha",
+                "claude-code",
+                "src/auth.rs",
+                "auth",
+                "2026-01-15T10:00:00Z",
+            ),
+            (
+                "beta",
+                "cursor",
+                "src/auth.rs",
+                "perf",
+                "2026-02-10T12:00:00Z",
+            ),
+            (
+                "gamma",
+                "cursor",
+                "docs/login.md",
+                "auth",
+                "2026-03-05T09:00:00Z",
+            ),
+        ] {
+            let mut session = EngramSession::begin(agent, None);
+            session
+                .set_request("Rework the login flow")
+                .log_file_change(file, "modified")
+                .tag(tag);
+            let mut data = session.build(None, Some(summary));
+            data.manifest.created_at = created_at.parse().unwrap();
+            storage.create(&data).unwrap();
+        }
+        let json = Some("json".to_string());
+        let search = |params: SearchParams| -> Vec<String> {
+            let out = server
+                .engram_search(Parameters(SearchParams {
+                    format: json.clone(),
+                    sort: Some("oldest".into()),
+                    ..params
+                }))
+                .unwrap();
+            let page: json::SearchJson = serde_json::from_str(&out).unwrap();
+            page.results
+                .into_iter()
+                .map(|r| r.manifest.summary.unwrap())
+                .collect()
+        };
+        let log = |params: LogParams| -> Vec<String> {
+            let out = server
+                .engram_log(Parameters(LogParams {
+                    format: json.clone(),
+                    ..params
+                }))
+                .unwrap();
+            let page: json::LogJson = serde_json::from_str(&out).unwrap();
+            page.engrams
+                .into_iter()
+                .map(|m| m.summary.unwrap())
+                .collect()
+        };
+        let some = |v: &str| Some(v.to_string());
+
+        assert_eq!(search(search_params("login")), ["alpha", "beta", "gamma"]);
+        let by_agent = SearchParams {
+            agent: some("cursor"),
+            ..search_params("login")
+        };
+        assert_eq!(search(by_agent), ["beta", "gamma"]);
+        let by_file = SearchParams {
+            file: some("src/auth.rs"),
+            ..search_params("login")
+        };
+        assert_eq!(search(by_file), ["alpha", "beta"]);
+        let by_tag = SearchParams {
+            tag: some("auth"),
+            ..search_params("login")
+        };
+        assert_eq!(search(by_tag), ["alpha", "gamma"]);
+        let february = SearchParams {
+            since: some("2026-02-01"),
+            until: some("2026-02-28"),
+            ..search_params("login")
+        };
+        assert_eq!(search(february), ["beta"]);
+        // Filters combine with each other and with terms in the query
+        let combined = SearchParams {
+            agent: some("cursor"),
+            tag: some("auth"),
+            ..search_params("login file:docs/login.md")
+        };
+        assert_eq!(search(combined), ["gamma"]);
+
+        let by_tag = LogParams {
+            tag: some("perf"),
+            ..log_params()
+        };
+        assert_eq!(log(by_tag), ["beta"]);
+        let since = LogParams {
+            since: some("2026-02-10T12:00:00Z"),
+            ..log_params()
+        };
+        assert_eq!(log(since), ["gamma", "beta"]);
+        // A bare `until` date includes the whole day
+        let until = LogParams {
+            until: some("2026-01-15"),
+            ..log_params()
+        };
+        assert_eq!(log(until), ["alpha"]);
+
+        let err = server
+            .engram_log(Parameters(LogParams {
+                since: some("last week"),
+                ..log_params()
+            }))
+            .unwrap_err();
+        assert!(err.contains("YYYY-MM-DD"), "{err}");
+    }
+
+    #[test]
+    fn test_dead_ends_read_only_matching_engrams() {
+        use std::sync::atomic::Ordering;
+        let (tmp, server) = initialized_repo();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        for i in 0..200 {
+            let mut session = EngramSession::begin("cursor", None);
+            session.set_request(&format!("Task {i}"));
+            if i % 70 == 0 {
+                session.log_rejection("Redis cache", "too much memory");
+            } else {
+                session.log_decision("Plain structs", "nothing to share");
+            }
+            let mut data = session.build(None, Some(&format!("task {i}")));
+            data.manifest.created_at += chrono::Duration::minutes(i);
+            storage.create(&data).unwrap();
+        }
+        let dead_ends = |query: Option<&str>, offset: Option<usize>| {
+            server
+                .engram_dead_ends(Parameters(DeadEndsParams {
+                    id: None,
+                    query: query.map(str::to_string),
+                    offset,
+                }))
+                .unwrap()
+        };
+        let reads = || server.cache.intent_reads.swap(0, Ordering::Relaxed);
+
+        let out = dead_ends(Some("redis"), None);
+        assert_eq!(
+            out.matches("Dead end: Redis cache — too much memory")
+                .count(),
+            3
+        );
+        assert!(!out.contains("Plain structs"), "{out}");
+        assert!(!out.contains("continue with"), "{out}");
+        assert_eq!(reads(), 3);
+
+        let out = dead_ends(Some("postgres"), None);
+        assert_eq!(out, "No dead ends or decisions matching 'postgres' found.");
+        assert_eq!(reads(), 0);
+
+        // Without a query, each call reads one page of intents
+        let out = dead_ends(None, None);
+        assert_eq!(out.matches("Decision: Plain structs").count(), 50);
+        assert!(out.ends_with("continue with offset=50.\n"), "{out}");
+        assert_eq!(reads(), MAX_DEAD_END_ENGRAMS);
+        let out = dead_ends(None, Some(190));
+        assert!(
+            out.contains("- task 9:") && out.contains("- task 0:"),
+            "{out}"
+        );
+        assert!(!out.contains("continue with"), "{out}");
+        assert_eq!(reads(), 10);
+    }
+
+    fn opens(server: &EngramMcpServer) -> (usize, usize) {
+        use std::sync::atomic::Ordering;
+        (
+            server.cache.storage_opens.load(Ordering::Relaxed),
+            server.cache.engine_opens.load(Ordering::Relaxed),
+        )
+    }
+
+    #[test]
+    fn test_long_results_are_truncated_between_entries() {
+        const MAX: usize = 600;
+        let tmp = TempDir::new().unwrap();
+        git2::Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let server = EngramMcpServer::new(tmp.path().to_path_buf(), DEFAULT_CACHE_TTL, MAX);
+        let mut ids = Vec::new();
+        for i in 0..20 {
+            let mut session = EngramSession::begin("cursor", None);
+            session
+                .set_request("Rework the login flow")
+                .log_file_change("src/login.rs", "modified");
+            if i == 0 {
+                for n in 0..30 {
+                    session.log_rejection(&format!("approach {n:02}"), "too slow for login");
+                }
+            }
+            let mut data = session.build(None, Some(&format!("login change {i:02}")));
+            data.manifest.created_at += chrono::Duration::minutes(i);
+            ids.push(storage.create(&data).unwrap().as_str().to_string());
+        }
+
+        // Split off the hint, checking every entry kept is whole
+        let split = |out: &str| -> (Vec<String>, Option<(usize, usize)>) {
+            assert!(out.chars().count() <= MAX, "{out}");
+            let (body, hint) = match out.split_once("\ntruncated; ") {
+                Some((body, hint)) => (body, Some(hint)),
+                None => (out, None),
+            };
+            let lines: Vec<_> = body.lines().collect();
+            let mut summaries = Vec::new();
+            for (i, line) in lines.iter().enumerate() {
+                if line.starts_with("- ") {
+                    let summary = lines[i + 1].trim();
+                    assert!(summary.starts_with("login change "), "{out}");
+                    summaries.push(summary.to_string());
+                }
+            }
+            let hint = hint.map(|hint| {
+                let (more, rest) = hint.split_once(' ').unwrap();
+                let next = rest.split_once("offset=").unwrap().1;
+                let next = next.split_once(' ').unwrap().0;
+                assert!(rest.ends_with("or a narrower query\n"), "{hint}");
+                (more.parse().unwrap(), next.parse().unwrap())
+            });
+            (summaries, hint)
+        };
+        // Page through a tool's results by following its hints
+        let all = |call: &dyn Fn(usize) -> String| {
+            let mut seen = Vec::new();
+            let mut offset = 0;
+            loop {
+                let (summaries, hint) = split(&call(offset));
+                assert!(!summaries.is_empty());
+                seen.extend(summaries);
+                let Some((more, next)) = hint else { break };
+                assert_eq!(next, seen.len());
+                assert_eq!(more, 20 - next);
+                offset = next;
+            }
+            seen.sort();
+            seen.dedup();
+            assert_eq!(seen.len(), 20);
+        };
+
+        all(&|offset| {
+            server
+                .engram_search(Parameters(SearchParams {
+                    limit: Some(50),
+                    offset: Some(offset),
+                    ..search_params("login")
+                }))
+                .unwrap()
+        });
+        all(&|offset| {
+            server
+                .engram_log(Parameters(LogParams {
+                    limit: Some(50),
+                    offset: Some(offset),
+                    ..log_params()
+                }))
+                .unwrap()
+        });
+        all(&|offset| {
+            server
+                .engram_trace(Parameters(TraceParams {
+                    file_path: "src/login.rs".into(),
+                    offset: Some(offset),
+                    format: None,
+                }))
+                .unwrap()
+        });
+
+        let dead_ends = |out: &str| {
+            assert!(out.chars().count() <= MAX, "{out}");
+            let (body, hint) = out.split_once("\ntruncated; ").expect(out);
+            let shown = body
+                .lines()
+                .filter(|line| line.starts_with("  - approach"))
+                .inspect(|line| assert!(line.ends_with(": too slow for login"), "{out}"))
+                .count();
+            let more: usize = hint.split_once(' ').unwrap().0.parse().unwrap();
+            assert_eq!(shown + more, 30, "{out}");
+            hint.to_string()
+        };
+        let out = server
+            .engram_show(Parameters(ShowParams {
+                id: ids[0].clone(),
+                format: None,
+            }))
+            .unwrap();
+        assert!(dead_ends(&out).contains("engram_dead_ends"), "{out}");
+        let out = server
+            .engram_diff(Parameters(DiffParams {
+                id_a: ids[0].clone(),
+                id_b: ids[1].clone(),
+                full: Some(true),
+                format: None,
+            }))
+            .unwrap();
+        assert!(dead_ends(&out).contains("format=\"json\""), "{out}");
+        // JSON results are left whole
+        let out = server
+            .engram_show(Parameters(ShowParams {
+                id: ids[0].clone(),
+                format: Some("json".into()),
+            }))
+            .unwrap();
+        let show: json::ShowJson = serde_json::from_str(&out).unwrap();
+        assert_eq!(show.intent.dead_ends.len(), 30);
+    }
+
+    #[test]
+    fn test_handles_are_reused_between_calls() {
+        let (tmp, server) = initialized_repo();
+        record_change(&server, "Add caching", "src/cache.rs");
+        server.engram_log(Parameters(log_params())).unwrap();
+        let before = opens(&server);
+
+        for _ in 0..3 {
+            server.engram_log(Parameters(log_params())).unwrap();
+            let out = server
+                .engram_search(Parameters(search_params("caching")))
+                .unwrap();
+            assert!(out.starts_with("Found 1 result(s)"), "{out}");
+        }
+        let after = opens(&server);
+        assert_eq!(after.0, before.0, "storage was reopened");
+        assert_eq!(after.1, 1, "search engine was reopened");
+
+        // An engram written by another process invalidates the repository
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        let mut session = EngramSession::begin("aider", None);
+        session.log_file_change("src/cache.rs", "modified");
+        let data = session.build(None, Some("Tune caching"));
+        storage.create(&data).unwrap();
+        SearchEngine::open(&storage)
+            .unwrap()
+            .index_engram(&data)
+            .unwrap();
+
+        let out = server.engram_log(Parameters(log_params())).unwrap();
+        assert!(out.starts_with("Engrams 1–2,"), "{out}");
+        assert!(out.contains("Tune caching"), "{out}");
+        let out = server
+            .engram_search(Parameters(search_params("caching")))
+            .unwrap();
+        assert!(out.starts_with("Found 2 result(s)"), "{out}");
+        assert_eq!(opens(&server), (after.0 + 1, 1));
+    }
+
+    #[test]
+    fn test_servers_do_not_share_handles() {
+        let (tmp, first) = initialized_repo();
+        first.engram_log(Parameters(log_params())).unwrap();
+        let second = EngramMcpServer::new(
+            tmp.path().to_path_buf(),
+            DEFAULT_CACHE_TTL,
+            DEFAULT_MAX_RESPONSE_CHARS,
+        );
+
+        for _ in 0..2 {
+            second.engram_log(Parameters(log_params())).unwrap();
+            first.engram_log(Parameters(log_params())).unwrap();
+        }
+        assert_eq!(opens(&first).0, 1);
+        assert_eq!(opens(&second).0, 1);
+    }
+
+    #[test]
+    fn test_zero_ttl_reopens_every_call() {
+        let tmp = TempDir::new().unwrap();
+        git2::Repository::init(tmp.path()).unwrap();
+        GitStorage::open(tmp.path()).unwrap().init().unwrap();
+        let server = EngramMcpServer::new(
+            tmp.path().to_path_buf(),
+            Duration::ZERO,
+            DEFAULT_MAX_RESPONSE_CHARS,
+        );
+
+        for _ in 0..2 {
+            server
+                .engram_search(Parameters(search_params("anything")))
+                .unwrap();
+        }
+        assert_eq!(opens(&server), (2, 2));
+    }
+}
@@ -0,0 +1,341 @@
+//! Prompts for common ways of digging through reasoning history. Each one
+//! fills in its instructions with the output of the matching tools.
+
+use std::collections::BTreeSet;
+
+use rmcp::handler::server::wrapper::Parameters;
+use rmcp::model::{GetPromptResult, PromptMessage, PromptMessageRole};
+use rmcp::{prompt, prompt_router, ErrorData};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use engram_query::trace_file;
+
+use crate::{DeadEndsParams, EngramMcpServer, ReviewParams, TraceParams};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FileHistoryArgs {
+    /// File to explain the history of
+    pub file_path: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PreChangeArgs {
+    /// Files about to be changed, separated by commas or spaces
+    pub files: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PrContextArgs {
+    /// Commit range, as "base..head" (e.g. "main..feature")
+    pub range: String,
+}
+
+fn user_prompt(description: String, text: String) -> GetPromptResult {
+    GetPromptResult {
+        description: Some(description),
+        messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+    }
+}
+
+#[prompt_router(vis = "pub(crate)")]
+impl EngramMcpServer {
+    #[prompt(
+        name = "explain-file-history",
+        description = "Explain why a file is the way it is, from the agent sessions that changed it."
+    )]
+    fn explain_file_history(
+        &self,
+        Parameters(args): Parameters<FileHistoryArgs>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let trace = self
+            .engram_trace(Parameters(TraceParams {
+                file_path: args.file_path.clone(),
+                offset: None,
+                format: None,
+            }))
+            .map_err(|e| ErrorData::internal_error(e, None))?;
+        let path = &args.file_path;
+
+        let text = if trace.starts_with("No engrams found") {
+            format!(
+                "Explain how `{path}` came to be as it is. No recorded agent session \
+                 changed it, so work from the code and its Git history, and say that \
+                 no reasoning history was available."
+            )
+        } else {
+            format!(
+                "Explain how `{path}` came to be as it is, using the reasoning history \
+                 below. Walk through the sessions in order: what each set out to do, \
+                 what it changed, and what it ruled out. Cite engram IDs so they can be \
+                 looked up with engram_show.\n\n{trace}"
+            )
+        };
+        Ok(user_prompt(format!("History of {path}"), text))
+    }
+
+    #[prompt(
+        name = "pre-change-check",
+        description = "Before changing files, gather the dead ends and decisions of earlier sessions that touched them."
+    )]
+    fn pre_change_check(
+        &self,
+        Parameters(args): Parameters<PreChangeArgs>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let files: BTreeSet<&str> = args
+            .files
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|f| !f.is_empty())
+            .collect();
+        if files.is_empty() {
+            return Err(ErrorData::invalid_params("No files given", None));
+        }
+        let internal = |e: String| ErrorData::internal_error(e, None);
+        let storage = self.open_storage().map_err(internal)?;
+        let engine = self.search_engine(&storage).map_err(internal)?;
+
+        let mut seen = BTreeSet::new();
+        let mut history = String::new();
+        for file in &files {
+            let entries = trace_file(&storage, &engine, file, true)
+                .map_err(|e| internal(format!("Trace failed: {e}")))?;
+            for entry in entries {
+                let id = entry.manifest.id.as_str().to_string();
+                if !seen.insert(id.clone()) {
+                    continue;
+                }
+                let notes = self
+                    .engram_dead_ends(Parameters(DeadEndsParams {
+                        id: Some(id.clone()),
+                        query: None,
+                        offset: None,
+                    }))
+                    .map_err(internal)?;
+                if notes.starts_with("No dead ends") {
+                    continue;
+                }
+                let summary = entry.manifest.summary.as_deref().unwrap_or("(no summary)");
+                history.push_str(&format!(
+                    "{} ({file}) - {summary}:\n{notes}\n",
+                    &id[..8.min(id.len())]
+                ));
+            }
+        }
+
+        let list: Vec<String> = files.iter().map(|f| format!("`{f}`")).collect();
+        let list = list.join(", ");
+        let text = if history.is_empty() {
+            format!(
+                "I am about to change {list}. No earlier session that touched these \
+                 files recorded a dead end or decision, so there is nothing to avoid \
+                 repeating; go ahead, and record any approaches you rule out."
+            )
+        } else {
+            format!(
+                "I am about to change {list}. Earlier sessions that touched these files \
+                 ruled out the approaches and made the decisions below. Check the plan \
+                 against them: don't retry a dead end unless what made it fail has \
+                 changed, and say so if the plan departs from a decision.\n\n{history}"
+            )
+        };
+        Ok(user_prompt(format!("Before changing {list}"), text))
+    }
+
+    #[prompt(
+        name = "pr-context",
+        description = "Give the reasoning behind a branch's commits as context for reviewing or describing its pull request."
+    )]
+    fn pr_context(
+        &self,
+        Parameters(args): Parameters<PrContextArgs>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let Some((base, head)) = args.range.split_once("..") else {
+            return Err(ErrorData::invalid_params(
+                "Invalid range; use 'base..head' (e.g. 'main..feature')",
+                None,
+            ));
+        };
+        let review = self
+            .engram_review(Parameters(ReviewParams {
+                base: base.to_string(),
+                head: (!head.is_empty()).then(|| head.to_string()),
+                by_file: None,
+                inferred: None,
+                offset: None,
+            }))
+            .map_err(|e| ErrorData::internal_error(e, None))?;
+        let range = &args.range;
+
+        let text = if review.starts_with("No engrams found") {
+            format!(
+                "Review the pull request for {range}. {review}, so no agent reasoning \
+                 is available; work from the diff and commit messages."
+            )
+        } else {
+            format!(
+                "Review the pull request for {range}. Below is the reasoning recorded by \
+                 the agent sessions behind its commits. Use it to judge whether the \
+                 changes do what was intended, and point out anything the recorded \
+                 dead ends or decisions leave unexplained.\n\n{review}"
+            )
+        };
+        Ok(user_prompt(format!("Context for {range}"), text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DeadEndParam, FileChangeParam, RecordParams, DEFAULT_CACHE_TTL, DEFAULT_MAX_RESPONSE_CHARS,
+    };
+    use engram_core::storage::GitStorage;
+    use rmcp::model::PromptMessageContent;
+    use tempfile::TempDir;
+
+    fn initialized_repo() -> (TempDir, EngramMcpServer) {
+        let tmp = TempDir::new().unwrap();
+        git2::Repository::init(tmp.path()).unwrap();
+        GitStorage::open(tmp.path()).unwrap().init().unwrap();
+        let server = EngramMcpServer::new(
+            tmp.path().to_path_buf(),
+            DEFAULT_CACHE_TTL,
+            DEFAULT_MAX_RESPONSE_CHARS,
+        );
+        (tmp, server)
+    }
+
+    /// Record an engram that changed `path` and ruled out polling.
+    fn record(server: &EngramMcpServer, summary: &str, path: &str) -> String {
+        let out = server
+            .engram_record(Parameters(RecordParams {
+                agent: "cursor".into(),
+                model: None,
+                original_request: summary.into(),
+                summary: Some(summary.into()),
+                messages: None,
+                tool_calls: None,
+                file_changes: Some(vec![FileChangeParam {
+                    path: path.into(),
+                    change_type: None,
+                }]),
+                dead_ends: Some(vec![DeadEndParam {
+                    approach: "Polling".into(),
+                    reason: "Too slow".into(),
+                }]),
+                decisions: None,
+                tokens: None,
+                tags: None,
+                parent_id: None,
+            }))
+            .unwrap();
+        out.strip_prefix("Recorded engram ").unwrap().to_string()
+    }
+
+    fn text(result: &GetPromptResult) -> &str {
+        match &result.messages[0].content {
+            PromptMessageContent::Text { text } => text,
+            other => panic!("unexpected content {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_prompts_are_listed_with_arguments() {
+        let (_tmp, server) = initialized_repo();
+        let prompts = server.prompt_router.list_all();
+        let mut names: Vec<_> = prompts.iter().map(|p| p.name.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["explain-file-history", "pr-context", "pre-change-check"]
+        );
+        let pre_change = prompts
+            .iter()
+            .find(|p| p.name == "pre-change-check")
+            .unwrap();
+        let arguments = pre_change.arguments.as_ref().unwrap();
+        assert_eq!(arguments[0].name, "files");
+        assert_eq!(arguments[0].required, Some(true));
+    }
+
+    #[test]
+    fn test_prompts_embed_history() {
+        let (_tmp, server) = initialized_repo();
+        let id = record(&server, "Add caching", "src/cache.rs");
+        record(&server, "Write docs", "docs/cache.md");
+
+        let history = server
+            .explain_file_history(Parameters(FileHistoryArgs {
+                file_path: "src/cache.rs".into(),
+            }))
+            .unwrap();
+        assert_eq!(
+            history.description.as_deref(),
+            Some("History of src/cache.rs")
+        );
+        let body = text(&history);
+        assert!(body.starts_with("Explain how `src/cache.rs`"), "{body}");
+        assert!(body.contains(&id[..8]), "{body}");
+        assert!(!body.contains("Write docs"), "{body}");
+
+        let check = server
+            .pre_change_check(Parameters(PreChangeArgs {
+                files: "src/cache.rs, src/cache.rs src/other.rs".into(),
+            }))
+            .unwrap();
+        let body = text(&check);
+        assert!(
+            body.starts_with("I am about to change `src/cache.rs`, `src/other.rs`."),
+            "{body}"
+        );
+        assert!(body.contains("Polling: Too slow"), "{body}");
+        assert_eq!(body.matches(&id[..8]).count(), 1, "{body}");
+    }
+
+    #[test]
+    fn test_prompts_without_history() {
+        let (tmp, server) = initialized_repo();
+
+        let history = server
+            .explain_file_history(Parameters(FileHistoryArgs {
+                file_path: "src/new.rs".into(),
+            }))
+            .unwrap();
+        assert!(text(&history).contains("No recorded agent session changed it"));
+
+        let check = server
+            .pre_change_check(Parameters(PreChangeArgs {
+                files: "src/new.rs".into(),
+            }))
+            .unwrap();
+        assert!(text(&check).contains("nothing to avoid repeating"));
+
+        let repo = git2::Repository::open(tmp.path()).unwrap();
+        let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+        let context = server
+            .pr_context(Parameters(PrContextArgs {
+                range: "HEAD..HEAD".into(),
+            }))
+            .unwrap();
+        let body = text(&context);
+        assert!(
+            body.starts_with("Review the pull request for HEAD..HEAD."),
+            "{body}"
+        );
+        assert!(body.contains("No engrams found in range"), "{body}");
+
+        let err = server
+            .pr_context(Parameters(PrContextArgs {
+                range: "main".into(),
+            }))
+            .unwrap_err();
+        assert!(err.message.contains("base..head"), "{}", err.message);
+        assert!(server
+            .pre_change_check(Parameters(PreChangeArgs { files: " ,".into() }))
+            .is_err());
+    }
+}
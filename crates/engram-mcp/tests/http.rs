@@ -0,0 +1,197 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use engram_core::storage::GitStorage;
+use engram_sdk::EngramSession;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+struct Reply {
+    status: u16,
+    session: Option<String>,
+    /// The JSON-RPC message in the body, whether sent as JSON or as an SSE
+    /// event.
+    message: Option<Value>,
+}
+
+/// POST one JSON-RPC message to the MCP endpoint over a fresh connection.
+async fn post(addr: SocketAddr, session: Option<&str>, token: Option<&str>, body: Value) -> Reply {
+    let body = body.to_string();
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\n\
+         Accept: application/json, text/event-stream\r\nContent-Length: {}\r\n\
+         Connection: close\r\n",
+        engram_mcp::MCP_PATH,
+        body.len()
+    );
+    if let Some(session) = session {
+        request.push_str(&format!("Mcp-Session-Id: {session}\r\n"));
+    }
+    if let Some(token) = token {
+        request.push_str(&format!("Authorization: Bearer {token}\r\n"));
+    }
+    request.push_str("\r\n");
+    request.push_str(&body);
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    tokio::time::timeout(
+        Duration::from_secs(30),
+        stream.read_to_string(&mut response),
+    )
+    .await
+    .expect("no response within 30s")
+    .unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    let session = head.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("mcp-session-id")
+            .then(|| value.trim().to_string())
+    });
+    let message = body
+        .lines()
+        .map(|line| line.strip_prefix("data:").unwrap_or(line).trim())
+        .find_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(Value::is_object);
+    Reply {
+        status,
+        session,
+        message,
+    }
+}
+
+/// Start a server for `repo` on an ephemeral port.
+async fn start(repo: &TempDir, token: Option<&str>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(engram_mcp::serve_http(
+        listener,
+        repo.path().to_path_buf(),
+        engram_mcp::DEFAULT_CACHE_TTL,
+        engram_mcp::DEFAULT_MAX_RESPONSE_CHARS,
+        token.map(str::to_string),
+        false,
+    ));
+    addr
+}
+
+/// Initialize a session, returning its ID.
+async fn initialize(addr: SocketAddr, token: Option<&str>) -> String {
+    let reply = post(
+        addr,
+        None,
+        token,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": {"name": "test", "version": "0"}
+            }
+        }),
+    )
+    .await;
+    assert_eq!(reply.status, 200);
+    let session = reply.session.expect("no session ID");
+    let reply = post(
+        addr,
+        Some(&session),
+        token,
+        json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+    )
+    .await;
+    assert_eq!(reply.status, 202);
+    session
+}
+
+fn repo_with_engram() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    git2::Repository::init(tmp.path()).unwrap();
+    let storage = GitStorage::open(tmp.path()).unwrap();
+    storage.init().unwrap();
+    let mut session = EngramSession::begin("claude-code", None);
+    session.set_request("Add a websocket transport");
+    session
+        .commit_to(&storage, None, Some("Websocket transport"))
+        .unwrap();
+    tmp
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_search_over_http() {
+    let repo = repo_with_engram();
+    let addr = start(&repo, None).await;
+    let session = initialize(addr, None).await;
+
+    let reply = post(
+        addr,
+        Some(&session),
+        None,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {"name": "engram_search", "arguments": {"query": "websocket"}}
+        }),
+    )
+    .await;
+    assert_eq!(reply.status, 200);
+    let message = reply.message.expect("no JSON-RPC response");
+    assert_eq!(message["id"], 2);
+    let text = message["result"]["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("Websocket transport"), "{text}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_token_is_required_when_set() {
+    let repo = repo_with_engram();
+    let addr = start(&repo, Some("s3cret")).await;
+
+    let ping = json!({"jsonrpc": "2.0", "id": 1, "method": "ping"});
+    let reply = post(addr, None, None, ping.clone()).await;
+    assert_eq!(reply.status, 401);
+    let reply = post(addr, None, Some("wrong"), ping).await;
+    assert_eq!(reply.status, 401);
+
+    initialize(addr, Some("s3cret")).await;
+}
+
+/// Serve `repo` on every interface, which needs a token or `insecure`.
+async fn serve_on_network(
+    repo: &TempDir,
+    token: Option<&str>,
+    insecure: bool,
+) -> (u16, tokio::task::JoinHandle<std::io::Result<()>>) {
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = tokio::spawn(engram_mcp::serve_http(
+        listener,
+        repo.path().to_path_buf(),
+        engram_mcp::DEFAULT_CACHE_TTL,
+        engram_mcp::DEFAULT_MAX_RESPONSE_CHARS,
+        token.map(str::to_string),
+        insecure,
+    ));
+    (port, server)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_network_bind_needs_a_token() {
+    let repo = repo_with_engram();
+    let (_, server) = serve_on_network(&repo, None, false).await;
+    let err = server.await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+
+    for (token, insecure) in [(Some("s3cret"), false), (None, true)] {
+        let (port, server) = serve_on_network(&repo, token, insecure).await;
+        initialize(SocketAddr::from(([127, 0, 0, 1], port)), token).await;
+        server.abort();
+    }
+}
@@ -0,0 +1,245 @@
+//! Offline sync through Git bundle files, for machines that cannot reach a
+//! shared remote. Bundles are written in the v2 format of `git bundle`, so
+//! `git bundle verify` and `git fetch` accept them too.
+
+use std::io::Write;
+use std::path::Path;
+
+use git2::{Buf, Oid, Repository};
+
+use engram_core::model::EngramId;
+use engram_core::storage::refs::engram_ref_name;
+
+use crate::error::ProtocolError;
+use crate::prune::engram_id;
+use crate::sync::{select_engrams, PushFilter};
+
+const SIGNATURE: &str = "# v2 git bundle\n";
+
+/// What [`write`] put in a bundle.
+#[derive(Debug)]
+pub struct BundleSummary {
+    /// The engram refs in the bundle.
+    pub refs: Vec<String>,
+    /// Objects in its pack.
+    pub objects: usize,
+}
+
+/// What [`read`] did with a bundle's engram refs.
+#[derive(Debug, Default)]
+pub struct BundleImport {
+    /// Engrams this repository did not have.
+    pub created: Vec<EngramId>,
+    /// Engrams fast-forwarded to a newer version from the bundle.
+    pub updated: Vec<EngramId>,
+    /// Engrams already at the bundle's version.
+    pub unchanged: usize,
+    /// Engrams kept as they are because they have moved on here.
+    pub kept: Vec<EngramId>,
+}
+
+/// Write the engrams `filter` selects, with their full history, to a
+/// bundle at `path`.
+pub fn write(
+    repo: &Repository,
+    path: &Path,
+    filter: &PushFilter,
+) -> Result<BundleSummary, ProtocolError> {
+    let selected = select_engrams(repo, filter)?;
+    if selected.is_empty() {
+        return Err(ProtocolError::Bundle("no engrams to bundle".into()));
+    }
+
+    let mut walk = repo.revwalk()?;
+    for (_, oid) in &selected {
+        walk.push(*oid)?;
+    }
+    let mut builder = repo.packbuilder()?;
+    builder.insert_walk(&mut walk)?;
+    let mut pack = Buf::new();
+    builder.write_buf(&mut pack)?;
+
+    let refs: Vec<String> = selected.iter().map(|(id, _)| engram_ref_name(id)).collect();
+    let mut header = String::from(SIGNATURE);
+    for ((_, oid), name) in selected.iter().zip(&refs) {
+        header.push_str(&format!("{oid} {name}\n"));
+    }
+    header.push('\n');
+
+    let mut bytes = header.into_bytes();
+    bytes.extend_from_slice(&pack);
+    std::fs::write(path, bytes)
+        .map_err(|e| ProtocolError::Bundle(format!("cannot write {}: {e}", path.display())))?;
+
+    Ok(BundleSummary {
+        refs,
+        objects: builder.object_count(),
+    })
+}
+
+/// Unpack the bundle at `path` and create or fast-forward its engram refs.
+/// Refs outside `refs/engrams/` are ignored.
+pub fn read(repo: &Repository, path: &Path) -> Result<BundleImport, ProtocolError> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| ProtocolError::Bundle(format!("cannot read {}: {e}", path.display())))?;
+    let bundle = parse(&bytes)?;
+
+    let odb = repo.odb()?;
+    if let Some(missing) = bundle.prerequisites.iter().find(|oid| !odb.exists(**oid)) {
+        return Err(ProtocolError::Bundle(format!(
+            "bundle builds on commit {missing}, which this repository lacks"
+        )));
+    }
+    let mut writer = odb.packwriter()?;
+    writer
+        .write_all(bundle.pack)
+        .map_err(|e| ProtocolError::Bundle(format!("cannot unpack: {e}")))?;
+    writer.commit()?;
+
+    let mut import = BundleImport::default();
+    for (name, oid) in bundle.refs {
+        let Some(id) = engram_id(&name) else {
+            continue;
+        };
+        match repo.refname_to_id(&name) {
+            Err(_) => {
+                repo.reference(&name, oid, false, "engram: bundle import")?;
+                import.created.push(id);
+            }
+            Ok(local) if local == oid => import.unchanged += 1,
+            Ok(local) if repo.graph_descendant_of(oid, local)? => {
+                repo.reference(&name, oid, true, "engram: bundle import")?;
+                import.updated.push(id);
+            }
+            Ok(_) => import.kept.push(id),
+        }
+    }
+    Ok(import)
+}
+
+/// A bundle file split into its parts.
+struct Parsed<'a> {
+    refs: Vec<(String, Oid)>,
+    /// Commits the pack builds on without containing.
+    prerequisites: Vec<Oid>,
+    pack: &'a [u8],
+}
+
+fn parse(bytes: &[u8]) -> Result<Parsed<'_>, ProtocolError> {
+    let invalid = |why: &str| ProtocolError::Bundle(format!("not a v2 Git bundle: {why}"));
+    let mut rest = bytes
+        .strip_prefix(SIGNATURE.as_bytes())
+        .ok_or_else(|| invalid("bad signature"))?;
+
+    let mut refs = Vec::new();
+    let mut prerequisites = Vec::new();
+    loop {
+        let end = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| invalid("unterminated header"))?;
+        let line = std::str::from_utf8(&rest[..end]).map_err(|_| invalid("bad header"))?;
+        rest = &rest[end + 1..];
+        if line.is_empty() {
+            break;
+        }
+        if let Some(prerequisite) = line.strip_prefix('-') {
+            let oid = prerequisite.split(' ').next().unwrap_or_default();
+            prerequisites.push(Oid::from_str(oid).map_err(|_| invalid("bad object ID"))?);
+            continue;
+        }
+        let (oid, name) = line
+            .split_once(' ')
+            .ok_or_else(|| invalid("bad ref line"))?;
+        let oid = Oid::from_str(oid).map_err(|_| invalid("bad object ID"))?;
+        refs.push((name.to_string(), oid));
+    }
+    Ok(Parsed {
+        refs,
+        prerequisites,
+        pack: rest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn repo_with_engrams(ids: &[&str]) -> (TempDir, Repository) {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        for id in ids {
+            commit_ref(&repo, id, id);
+        }
+        (tmp, repo)
+    }
+
+    fn commit_ref(repo: &Repository, id: &str, message: &str) {
+        let name = format!("refs/engrams/{}/{id}", &id[..2]);
+        let mut builder = repo.treebuilder(None).unwrap();
+        let blob = repo.blob(message.as_bytes()).unwrap();
+        builder.insert("manifest.json", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let parent = repo
+            .find_reference(&name)
+            .ok()
+            .and_then(|r| r.peel_to_commit().ok());
+        let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let parents: Vec<_> = parent.iter().collect();
+        let oid = repo
+            .commit(None, &sig, &sig, message, &tree, &parents)
+            .unwrap();
+        repo.reference(&name, oid, true, "").unwrap();
+    }
+
+    fn ids(ids: &[EngramId]) -> Vec<&str> {
+        ids.iter().map(EngramId::as_str).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (_a_dir, a) = repo_with_engrams(&["aaaa1111", "bbbb2222"]);
+        let (_b_dir, b) = repo_with_engrams(&["cccc3333"]);
+        let out = TempDir::new().unwrap();
+        let path = out.path().join("engrams.bundle");
+
+        let summary = write(&a, &path, &PushFilter::default()).unwrap();
+        assert_eq!(summary.refs.len(), 2);
+        assert!(summary.objects >= 4);
+
+        let import = read(&b, &path).unwrap();
+        assert_eq!(ids(&import.created), ["aaaa1111", "bbbb2222"]);
+        assert_eq!(read(&b, &path).unwrap().unchanged, 2);
+        let blob = b
+            .find_reference("refs/engrams/aa/aaaa1111")
+            .unwrap()
+            .peel_to_tree()
+            .unwrap()
+            .get_name("manifest.json")
+            .unwrap()
+            .id();
+        assert_eq!(b.find_blob(blob).unwrap().content(), b"aaaa1111");
+
+        // A newer version fast-forwards; one changed here is kept
+        commit_ref(&a, "aaaa1111", "edited");
+        commit_ref(&b, "bbbb2222", "local edit");
+        write(&a, &path, &PushFilter::default()).unwrap();
+        let import = read(&b, &path).unwrap();
+        assert_eq!(ids(&import.updated), ["aaaa1111"]);
+        assert_eq!(ids(&import.kept), ["bbbb2222"]);
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let (_dir, repo) = repo_with_engrams(&[]);
+        let out = TempDir::new().unwrap();
+        let path = out.path().join("notes.txt");
+        std::fs::write(&path, "hello\n").unwrap();
+        assert!(matches!(read(&repo, &path), Err(ProtocolError::Bundle(_))));
+        assert!(matches!(
+            write(&repo, &path, &PushFilter::default()),
+            Err(ProtocolError::Bundle(_))
+        ));
+    }
+}
@@ -0,0 +1,442 @@
+use std::cell::RefCell;
+use std::process::Command;
+
+use git2::{Config, Cred, CredentialType, Error, ErrorClass, ErrorCode};
+
+/// Signature of a custom credential callback, as for
+/// [`git2::RemoteCallbacks::credentials`]: URL, username from the URL, and
+/// the credential types the remote accepts.
+pub type CredentialCallback =
+    dyn Fn(&str, Option<&str>, CredentialType) -> Result<Cred, Error> + Send + Sync;
+
+/// An environment variable holding a token for HTTPS remotes on one host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenVariable {
+    pub name: &'static str,
+    /// The username the token is sent with, unless the URL names one.
+    pub username: &'static str,
+    /// The host the token is for.
+    pub host: Option<&'static str>,
+    /// A variable naming the host instead, when set.
+    pub host_variable: Option<&'static str>,
+}
+
+/// Token variables in the order they are tried. Each is only sent over
+/// HTTPS to its own host, never to whichever server asks.
+pub const TOKEN_VARIABLES: &[TokenVariable] = &[
+    TokenVariable {
+        name: "ENGRAM_GIT_TOKEN",
+        username: "x-access-token",
+        host: None,
+        host_variable: Some("ENGRAM_GIT_TOKEN_HOST"),
+    },
+    TokenVariable {
+        name: "GITHUB_TOKEN",
+        username: "x-access-token",
+        host: Some("github.com"),
+        host_variable: None,
+    },
+    TokenVariable {
+        name: "GITLAB_TOKEN",
+        username: "oauth2",
+        host: Some("gitlab.com"),
+        // Set by GitLab CI to the instance's host
+        host_variable: Some("CI_SERVER_HOST"),
+    },
+];
+
+impl TokenVariable {
+    /// Whether the token may be sent to `url`.
+    fn is_for(&self, url: &str, env: &dyn CredentialEnv) -> bool {
+        let Some(url_host) = https_host(url) else {
+            return false;
+        };
+        let host = match self.host_variable.and_then(|name| env.var(name)) {
+            Some(host) => host,
+            None => match self.host {
+                Some(host) => host.to_string(),
+                None => return false,
+            },
+        };
+        url_host.eq_ignore_ascii_case(host.trim())
+    }
+}
+
+/// The host of an `https://` URL, without userinfo or port.
+fn https_host(url: &str) -> Option<&str> {
+    let scheme_len = "https://".len();
+    if !url.get(..scheme_len)?.eq_ignore_ascii_case("https://") {
+        return None;
+    }
+    let authority = url[scheme_len..].split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    let host = match host_port.find(']') {
+        Some(end) if host_port.starts_with('[') => &host_port[..=end],
+        _ => host_port.split(':').next()?,
+    };
+    Some(host).filter(|h| !h.is_empty())
+}
+
+/// One way of answering a remote's request for credentials.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CredentialMethod {
+    /// Just a username, which SSH asks for before a key.
+    Username(String),
+    /// A key held by a running ssh-agent.
+    SshAgent(String),
+    /// Git's configured `credential.helper`.
+    CredentialHelper,
+    /// A token from an environment variable, sent as a basic-auth password.
+    Token {
+        variable: &'static str,
+        username: String,
+    },
+    /// The `GIT_ASKPASS` program, asked for a username and password.
+    Askpass(String),
+    /// The platform's default credentials (e.g. Negotiate/NTLM).
+    Default,
+}
+
+impl CredentialMethod {
+    fn describe(&self) -> String {
+        match self {
+            Self::Username(name) => format!("username '{name}'"),
+            Self::SshAgent(name) => format!("ssh-agent as '{name}'"),
+            Self::CredentialHelper => "credential.helper".into(),
+            Self::Token { variable, .. } => format!("${variable}"),
+            Self::Askpass(program) => format!("GIT_ASKPASS ({program})"),
+            Self::Default => "default credentials".into(),
+        }
+    }
+}
+
+/// Where credentials are looked up. A trait so that the order they are
+/// tried in can be tested without touching the real environment.
+pub trait CredentialEnv {
+    fn var(&self, name: &str) -> Option<String>;
+}
+
+/// The process environment.
+pub struct ProcessEnv;
+
+impl CredentialEnv for ProcessEnv {
+    fn var(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok().filter(|v| !v.is_empty())
+    }
+}
+
+/// The methods to try, in order, for a request to `url` that accepts
+/// `allowed`: ssh-agent for SSH, then `credential.helper`, token variables
+/// for the URL's host and `GIT_ASKPASS` for HTTPS, then default credentials.
+pub fn candidates(
+    url: &str,
+    username_from_url: Option<&str>,
+    allowed: CredentialType,
+    env: &dyn CredentialEnv,
+) -> Vec<CredentialMethod> {
+    let username = username_from_url.unwrap_or("git").to_string();
+    let mut methods = Vec::new();
+
+    if allowed.contains(CredentialType::USERNAME) {
+        methods.push(CredentialMethod::Username(username.clone()));
+    }
+    if allowed.contains(CredentialType::SSH_KEY) {
+        methods.push(CredentialMethod::SshAgent(username));
+    }
+    if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+        methods.push(CredentialMethod::CredentialHelper);
+        for token in TOKEN_VARIABLES {
+            if env.var(token.name).is_some() && token.is_for(url, env) {
+                methods.push(CredentialMethod::Token {
+                    variable: token.name,
+                    username: username_from_url.unwrap_or(token.username).to_string(),
+                });
+            }
+        }
+        if let Some(program) = env.var("GIT_ASKPASS") {
+            methods.push(CredentialMethod::Askpass(program));
+        }
+    }
+    if allowed.contains(CredentialType::DEFAULT) {
+        methods.push(CredentialMethod::Default);
+    }
+    methods
+}
+
+/// Answers credential requests from [`candidates`], never offering the
+/// same method twice, so that a rejected credential moves on to the next
+/// method instead of being retried forever.
+pub struct CredentialResolver<'a> {
+    config: Option<Config>,
+    env: &'a dyn CredentialEnv,
+    tried: RefCell<Vec<CredentialMethod>>,
+}
+
+impl<'a> CredentialResolver<'a> {
+    pub fn new(config: Option<Config>, env: &'a dyn CredentialEnv) -> Self {
+        Self {
+            config,
+            env,
+            tried: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// A credential for the request, from the first untried method that
+    /// produces one.
+    pub fn resolve(
+        &self,
+        url: &str,
+        username_from_url: Option<&str>,
+        allowed: CredentialType,
+    ) -> Result<Cred, Error> {
+        for method in candidates(url, username_from_url, allowed, self.env) {
+            if self.tried.borrow().contains(&method) {
+                continue;
+            }
+            self.tried.borrow_mut().push(method.clone());
+            match self.credential(&method, url) {
+                Ok(cred) => {
+                    tracing::debug!("Trying {} for {url}", method.describe());
+                    return Ok(cred);
+                }
+                Err(e) => tracing::debug!("{} unavailable: {e}", method.describe()),
+            }
+        }
+        Err(Error::new(
+            ErrorCode::Auth,
+            ErrorClass::Net,
+            format!("no credentials left to try for {url}"),
+        ))
+    }
+
+    /// Methods offered so far, in order.
+    pub fn tried(&self) -> Vec<String> {
+        self.tried
+            .borrow()
+            .iter()
+            .map(CredentialMethod::describe)
+            .collect()
+    }
+
+    fn credential(&self, method: &CredentialMethod, url: &str) -> Result<Cred, Error> {
+        match method {
+            CredentialMethod::Username(name) => Cred::username(name),
+            CredentialMethod::SshAgent(name) => Cred::ssh_key_from_agent(name),
+            CredentialMethod::CredentialHelper => {
+                let config = self
+                    .config
+                    .as_ref()
+                    .ok_or_else(|| Error::from_str("no Git config"))?;
+                Cred::credential_helper(config, url, None)
+            }
+            CredentialMethod::Token { variable, username } => {
+                let token = self
+                    .env
+                    .var(variable)
+                    .ok_or_else(|| Error::from_str("token variable is unset"))?;
+                Cred::userpass_plaintext(username, &token)
+            }
+            CredentialMethod::Askpass(program) => {
+                let username = askpass(program, &format!("Username for '{url}': "))?;
+                let password = askpass(program, &format!("Password for '{url}': "))?;
+                Cred::userpass_plaintext(&username, &password)
+            }
+            CredentialMethod::Default => Cred::default(),
+        }
+    }
+}
+
+/// Ask the `GIT_ASKPASS` program `prompt`, as Git does.
+fn askpass(program: &str, prompt: &str) -> Result<String, Error> {
+    let output = Command::new(program)
+        .arg(prompt)
+        .output()
+        .map_err(|e| Error::from_str(&format!("cannot run {program}: {e}")))?;
+    if !output.status.success() {
+        return Err(Error::from_str(&format!("{program} failed")));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end_matches(['\r', '\n'])
+        .to_string())
+}
+
+/// Whether `error` means the remote refused the credentials offered.
+pub fn is_auth_error(error: &Error) -> bool {
+    error.code() == ErrorCode::Auth
+        || (error.class() == ErrorClass::Http
+            && ["401", "403"].iter().any(|s| error.message().contains(s)))
+        || (error.class() == ErrorClass::Ssh && error.message().contains("authentication"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct FakeEnv(HashMap<&'static str, &'static str>);
+
+    impl CredentialEnv for FakeEnv {
+        fn var(&self, name: &str) -> Option<String> {
+            self.0.get(name).map(|v| v.to_string())
+        }
+    }
+
+    fn env(vars: &[(&'static str, &'static str)]) -> FakeEnv {
+        FakeEnv(vars.iter().copied().collect())
+    }
+
+    #[test]
+    fn test_ssh_uses_agent() {
+        let methods = candidates(
+            "ssh://git@github.com/acme/app.git",
+            None,
+            CredentialType::USERNAME | CredentialType::SSH_KEY,
+            &env(&[("GITHUB_TOKEN", "t")]),
+        );
+        assert_eq!(
+            methods,
+            [
+                CredentialMethod::Username("git".into()),
+                CredentialMethod::SshAgent("git".into()),
+            ]
+        );
+    }
+
+    fn tokens(url: &str, vars: &FakeEnv) -> Vec<&'static str> {
+        candidates(url, None, CredentialType::USER_PASS_PLAINTEXT, vars)
+            .into_iter()
+            .filter_map(|method| match method {
+                CredentialMethod::Token { variable, .. } => Some(variable),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_https_order() {
+        let vars = env(&[
+            ("GITLAB_TOKEN", "gl"),
+            ("GITHUB_TOKEN", "gh"),
+            ("GIT_ASKPASS", "/usr/bin/askpass"),
+        ]);
+        let methods = candidates(
+            "https://github.com/acme/app.git",
+            None,
+            CredentialType::USER_PASS_PLAINTEXT | CredentialType::DEFAULT,
+            &vars,
+        );
+        assert_eq!(
+            methods,
+            [
+                CredentialMethod::CredentialHelper,
+                CredentialMethod::Token {
+                    variable: "GITHUB_TOKEN",
+                    username: "x-access-token".into(),
+                },
+                CredentialMethod::Askpass("/usr/bin/askpass".into()),
+                CredentialMethod::Default,
+            ]
+        );
+
+        // A username in the URL wins over the token's conventional one
+        let methods = candidates(
+            "https://ci-bot@github.com/acme/app.git",
+            Some("ci-bot"),
+            CredentialType::USER_PASS_PLAINTEXT,
+            &vars,
+        );
+        assert!(methods.contains(&CredentialMethod::Token {
+            variable: "GITHUB_TOKEN",
+            username: "ci-bot".into(),
+        }));
+    }
+
+    #[test]
+    fn test_tokens_only_go_to_their_host() {
+        let vars = env(&[
+            ("ENGRAM_GIT_TOKEN", "e"),
+            ("ENGRAM_GIT_TOKEN_HOST", "git.acme.dev"),
+            ("GITLAB_TOKEN", "gl"),
+            ("GITHUB_TOKEN", "gh"),
+        ]);
+        assert_eq!(
+            tokens("https://GitHub.com/acme/app.git", &vars),
+            ["GITHUB_TOKEN"]
+        );
+        assert_eq!(
+            tokens("https://gitlab.com:443/acme/app", &vars),
+            ["GITLAB_TOKEN"]
+        );
+        assert_eq!(
+            tokens("https://git.acme.dev/acme/app.git", &vars),
+            ["ENGRAM_GIT_TOKEN"]
+        );
+
+        // A foreign host gets none of them, even one that looks alike
+        assert!(tokens("https://gitea.example.com/acme/app.git", &vars).is_empty());
+        assert!(tokens("https://github.com.evil.test/acme/app.git", &vars).is_empty());
+        assert!(tokens("https://github.com@evil.test/acme/app.git", &vars).is_empty());
+
+        // Without a configured host, ENGRAM_GIT_TOKEN goes nowhere
+        let vars = env(&[("ENGRAM_GIT_TOKEN", "e")]);
+        assert!(tokens("https://github.com/acme/app.git", &vars).is_empty());
+
+        // GitLab CI names its own instance
+        let vars = env(&[
+            ("GITLAB_TOKEN", "gl"),
+            ("CI_SERVER_HOST", "gitlab.acme.dev"),
+        ]);
+        assert_eq!(
+            tokens("https://gitlab.acme.dev/acme/app.git", &vars),
+            ["GITLAB_TOKEN"]
+        );
+        assert!(tokens("https://gitlab.com/acme/app.git", &vars).is_empty());
+    }
+
+    #[test]
+    fn test_tokens_never_sent_over_plain_http() {
+        let vars = env(&[
+            ("ENGRAM_GIT_TOKEN", "e"),
+            ("ENGRAM_GIT_TOKEN_HOST", "gitea.local"),
+            ("GITHUB_TOKEN", "gh"),
+            ("GIT_ASKPASS", "/usr/bin/askpass"),
+        ]);
+        assert!(tokens("http://gitea.local:3000/acme/app.git", &vars).is_empty());
+        assert!(tokens("http://github.com/acme/app.git", &vars).is_empty());
+
+        // Other methods are still offered
+        let methods = candidates(
+            "http://gitea.local:3000/acme/app.git",
+            None,
+            CredentialType::USER_PASS_PLAINTEXT,
+            &vars,
+        );
+        assert_eq!(
+            methods,
+            [
+                CredentialMethod::CredentialHelper,
+                CredentialMethod::Askpass("/usr/bin/askpass".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolver_moves_on_after_rejection() {
+        let vars = env(&[("GITHUB_TOKEN", "gh")]);
+        let resolver = CredentialResolver::new(None, &vars);
+        let url = "https://github.com/acme/app.git";
+
+        // No config, so the helper is skipped and the token is offered
+        assert!(resolver
+            .resolve(url, None, CredentialType::USER_PASS_PLAINTEXT)
+            .is_ok());
+        assert_eq!(resolver.tried(), ["credential.helper", "$GITHUB_TOKEN"]);
+
+        // Rejected: nothing left to offer
+        let err = resolver
+            .resolve(url, None, CredentialType::USER_PASS_PLAINTEXT)
+            .err()
+            .unwrap();
+        assert!(is_auth_error(&err));
+    }
+}
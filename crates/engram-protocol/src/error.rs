@@ -11,6 +11,19 @@ pub enum ProtocolError {
     #[error("Remote not found: {0}")]
     RemoteNotFound(String),
 
+    #[error("Authentication failed for remote '{remote}': {message} (tried: {tried})")]
+    Auth {
+        remote: String,
+        message: String,
+        tried: String,
+    },
+
+    #[error("Ref not found on remote '{remote}': {message}")]
+    RefNotFound { remote: String, message: String },
+
+    #[error("Bundle error: {0}")]
+    Bundle(String),
+
     #[error("Sync error: {0}")]
     Sync(String),
 }
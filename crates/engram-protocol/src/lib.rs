@@ -1,7 +1,22 @@
+pub mod bundle;
+pub mod credentials;
 pub mod error;
+pub mod prune;
 pub mod refspec;
+pub mod remotes;
+pub mod status;
 pub mod sync;
 
+pub use credentials::{CredentialCallback, CredentialEnv, CredentialMethod};
 pub use error::ProtocolError;
+pub use prune::push_deletions;
 pub use refspec::{ensure_all_refspecs, ensure_refspecs};
-pub use sync::{fetch_engrams, push_engrams, FetchResult, PushResult, SyncOptions};
+pub use remotes::{
+    all_remotes, configured_push_remotes, fetch_from_remotes, push_to_remotes, RemoteOutcome,
+    PUSH_REMOTES_KEY,
+};
+pub use status::{sync_status, SyncStatus};
+pub use sync::{
+    fetch_engrams, push_engrams, push_engrams_filtered, FetchResult, ManifestPredicate,
+    ProgressCallback, PushFilter, PushResult, SyncOptions, SyncProgress,
+};
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use git2::{Oid, Repository};
+
+use engram_core::model::EngramId;
+use engram_core::storage::refs::ENGRAM_REF_PREFIX;
+
+use crate::error::ProtocolError;
+use crate::refspec::ensure_refspecs;
+use crate::sync::{push_refspecs, remote_engram_refs, PushResult, SyncOptions};
+
+/// Directory under `.git` holding, per remote, the engram refs it was last
+/// seen with, as `<oid> <ref>` lines. Pruning only touches refs recorded
+/// here, so engrams that were never synced are left alone.
+const KNOWN_REFS_DIR: &str = "engram-remotes";
+
+/// Engram refs last seen on a remote, by ref name.
+pub(crate) type KnownRefs = BTreeMap<String, Oid>;
+
+fn known_refs_path(repo: &Repository, remote_name: &str) -> PathBuf {
+    repo.path().join(KNOWN_REFS_DIR).join(remote_name)
+}
+
+/// The refs recorded for `remote_name`; empty if it was never synced.
+pub(crate) fn read_known_refs(repo: &Repository, remote_name: &str) -> KnownRefs {
+    let Ok(content) = std::fs::read_to_string(known_refs_path(repo, remote_name)) else {
+        return KnownRefs::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let (oid, name) = line.split_once(' ')?;
+            Some((name.to_string(), Oid::from_str(oid).ok()?))
+        })
+        .collect()
+}
+
+/// Record the refs `remote_name` now has. Best-effort: a failure only
+/// means the next prune finds less to do.
+pub(crate) fn write_known_refs(repo: &Repository, remote_name: &str, refs: &KnownRefs) {
+    let path = known_refs_path(repo, remote_name);
+    let content: String = refs
+        .iter()
+        .map(|(name, oid)| format!("{oid} {name}\n"))
+        .collect();
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, content));
+    if let Err(e) = written {
+        tracing::warn!("Could not record engram refs of {remote_name}: {e}");
+    }
+}
+
+/// Delete local engram refs that the remote had when last synced but no
+/// longer advertises, unless they have moved on locally since.
+pub(crate) fn prune_local(
+    repo: &Repository,
+    remote_name: &str,
+    advertised: &KnownRefs,
+) -> Result<Vec<EngramId>, ProtocolError> {
+    let mut pruned = Vec::new();
+    for (name, oid) in read_known_refs(repo, remote_name) {
+        if advertised.contains_key(&name) {
+            continue;
+        }
+        let Ok(mut reference) = repo.find_reference(&name) else {
+            continue;
+        };
+        if reference.target() != Some(oid) {
+            tracing::info!("Keeping {name}: changed locally since it was synced");
+            continue;
+        }
+        reference.delete()?;
+        if let Some(id) = engram_id(&name) {
+            pruned.push(id);
+        }
+    }
+    Ok(pruned)
+}
+
+/// Delete from the remote the engram refs deleted here since they were
+/// last synced with it. A ref the remote has moved on since is kept. For a
+/// dry run, `refs` lists what would be deleted.
+pub fn push_deletions(
+    repo: &Repository,
+    remote_name: &str,
+    opts: &SyncOptions,
+) -> Result<PushResult, ProtocolError> {
+    ensure_refspecs(repo, remote_name)?;
+
+    let known = read_known_refs(repo, remote_name);
+    let deleted: Vec<_> = known
+        .iter()
+        .filter(|(name, _)| repo.find_reference(name).is_err())
+        .collect();
+    let refspecs = if deleted.is_empty() {
+        Vec::new()
+    } else {
+        let on_remote = remote_engram_refs(repo, remote_name, opts)?;
+        deleted
+            .into_iter()
+            .filter(|(name, oid)| on_remote.get(*name) == Some(oid))
+            .map(|(name, _)| format!(":{name}"))
+            .collect()
+    };
+    push_refspecs(repo, remote_name, refspecs, opts)
+}
+
+/// The ID in an engram ref name, `refs/engrams/<ab>/<id>`.
+pub(crate) fn engram_id(ref_name: &str) -> Option<EngramId> {
+    let (_fanout, id) = ref_name.strip_prefix(ENGRAM_REF_PREFIX)?.split_once('/')?;
+    Some(EngramId(id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::{fetch_engrams, push_engrams};
+    use tempfile::TempDir;
+
+    fn repo_with_remote(bare: &TempDir) -> (TempDir, Repository) {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        repo.remote("origin", &format!("file://{}", bare.path().display()))
+            .unwrap();
+        (tmp, repo)
+    }
+
+    fn commit_ref(repo: &Repository, id: &str, message: &str) {
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let oid = repo.commit(None, &sig, &sig, message, &tree, &[]).unwrap();
+        repo.reference(&ref_name(id), oid, true, "").unwrap();
+    }
+
+    fn ref_name(id: &str) -> String {
+        format!("refs/engrams/{}/{id}", &id[..2])
+    }
+
+    fn has_ref(repo: &Repository, id: &str) -> bool {
+        repo.find_reference(&ref_name(id)).is_ok()
+    }
+
+    #[test]
+    fn test_deletions_propagate_both_ways() {
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        let remote = Repository::open_bare(bare.path()).unwrap();
+        let (_a_dir, alice) = repo_with_remote(&bare);
+        let (_b_dir, bob) = repo_with_remote(&bare);
+
+        for id in ["aaaa1111", "bbbb2222"] {
+            commit_ref(&alice, id, id);
+        }
+        push_engrams(&alice, "origin", &SyncOptions::default()).unwrap();
+        fetch_engrams(&bob, "origin", &SyncOptions::default()).unwrap();
+        // Never synced, so never pruned
+        commit_ref(&bob, "cccc3333", "cccc3333");
+
+        // Alice deletes one and pushes the deletion
+        alice
+            .find_reference(&ref_name("aaaa1111"))
+            .unwrap()
+            .delete()
+            .unwrap();
+        let dry = SyncOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let planned = push_deletions(&alice, "origin", &dry).unwrap();
+        assert_eq!(planned.refs, [ref_name("aaaa1111")]);
+        assert!(has_ref(&remote, "aaaa1111"));
+
+        push_deletions(&alice, "origin", &SyncOptions::default()).unwrap();
+        assert!(!has_ref(&remote, "aaaa1111"));
+        assert!(has_ref(&remote, "bbbb2222"));
+        let again = push_deletions(&alice, "origin", &dry).unwrap();
+        assert!(again.refs.is_empty());
+
+        // Without prune Bob keeps the stale ref; a later prune drops it
+        fetch_engrams(&bob, "origin", &SyncOptions::default()).unwrap();
+        assert!(has_ref(&bob, "aaaa1111"));
+
+        alice
+            .find_reference(&ref_name("bbbb2222"))
+            .unwrap()
+            .delete()
+            .unwrap();
+        push_deletions(&alice, "origin", &SyncOptions::default()).unwrap();
+        let prune = SyncOptions {
+            prune: true,
+            ..Default::default()
+        };
+        let fetched = fetch_engrams(&bob, "origin", &prune).unwrap();
+        let pruned: Vec<_> = fetched.pruned.iter().map(EngramId::as_str).collect();
+        assert_eq!(pruned, ["aaaa1111", "bbbb2222"]);
+        assert!(!has_ref(&bob, "aaaa1111"));
+        assert!(!has_ref(&bob, "bbbb2222"));
+        assert!(has_ref(&bob, "cccc3333"));
+    }
+
+    #[test]
+    fn test_prune_keeps_refs_changed_locally() {
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        let (_dir, repo) = repo_with_remote(&bare);
+        commit_ref(&repo, "aaaa1111", "first");
+        push_engrams(&repo, "origin", &SyncOptions::default()).unwrap();
+
+        Repository::open_bare(bare.path())
+            .unwrap()
+            .find_reference(&ref_name("aaaa1111"))
+            .unwrap()
+            .delete()
+            .unwrap();
+        commit_ref(&repo, "aaaa1111", "second");
+
+        let prune = SyncOptions {
+            prune: true,
+            ..Default::default()
+        };
+        let fetched = fetch_engrams(&repo, "origin", &prune).unwrap();
+        assert!(fetched.pruned.is_empty());
+        assert!(has_ref(&repo, "aaaa1111"));
+    }
+}
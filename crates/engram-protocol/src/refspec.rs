@@ -2,8 +2,15 @@ use git2::Repository;
 
 use crate::error::ProtocolError;
 
-/// Refspec for fetching engram refs from remotes.
-pub const ENGRAM_FETCH_REFSPEC: &str = "+refs/engrams/*:refs/engrams/*";
+/// Refspec for fetching engram refs from remotes. It isn't forced, so a
+/// plain `git fetch` refuses to replace an engram that has unpushed notes
+/// or edits; `engram fetch` merges those instead (see
+/// [`crate::sync::fetch_engrams`]).
+pub const ENGRAM_FETCH_REFSPEC: &str = "refs/engrams/*:refs/engrams/*";
+
+/// The forced fetch refspec earlier versions configured, replaced by
+/// [`ENGRAM_FETCH_REFSPEC`].
+const FORCED_FETCH_REFSPEC: &str = "+refs/engrams/*:refs/engrams/*";
 
 /// Refspec for pushing engram refs to remotes.
 pub const ENGRAM_PUSH_REFSPEC: &str = "refs/engrams/*:refs/engrams/*";
@@ -16,6 +23,7 @@ pub fn ensure_refspecs(repo: &Repository, remote_name: &str) -> Result<bool, Pro
 
     let mut needs_fetch = true;
     let mut needs_push = true;
+    let mut forced_fetch = false;
 
     // Check existing fetch refspecs
     if let Ok(refspecs) = remote.fetch_refspecs() {
@@ -23,6 +31,8 @@ pub fn ensure_refspecs(repo: &Repository, remote_name: &str) -> Result<bool, Pro
             if let Some(spec) = refspecs.get(i) {
                 if spec == ENGRAM_FETCH_REFSPEC {
                     needs_fetch = false;
+                } else if spec == FORCED_FETCH_REFSPEC {
+                    forced_fetch = true;
                 }
             }
         }
@@ -43,6 +53,14 @@ pub fn ensure_refspecs(repo: &Repository, remote_name: &str) -> Result<bool, Pro
 
     let mut changed = false;
 
+    if forced_fetch {
+        repo.config()?.remove_multivar(
+            &format!("remote.{remote_name}.fetch"),
+            r"^\+refs/engrams/\*:refs/engrams/\*$",
+        )?;
+        changed = true;
+    }
+
     if needs_fetch {
         repo.remote_add_fetch(remote_name, ENGRAM_FETCH_REFSPEC)?;
         changed = true;
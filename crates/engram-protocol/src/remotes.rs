@@ -0,0 +1,166 @@
+use git2::Repository;
+
+use crate::error::ProtocolError;
+use crate::refspec::ensure_refspecs;
+use crate::sync::{
+    fetch_engrams, push_engrams_filtered, FetchResult, PushFilter, PushResult, SyncOptions,
+};
+
+/// Git config key listing the remotes `engram push` targets by default,
+/// one value per remote.
+pub const PUSH_REMOTES_KEY: &str = "engram.pushRemotes";
+
+/// The outcome of a push or fetch against one of several remotes.
+#[derive(Debug)]
+pub struct RemoteOutcome<T> {
+    pub remote: String,
+    pub result: Result<T, ProtocolError>,
+}
+
+/// The remotes configured in [`PUSH_REMOTES_KEY`], in order; empty if
+/// none are.
+pub fn configured_push_remotes(repo: &Repository) -> Result<Vec<String>, ProtocolError> {
+    let config = repo.config()?;
+    let mut remotes = Vec::new();
+    if let Ok(entries) = config.multivar(PUSH_REMOTES_KEY, None) {
+        entries.for_each(|entry| {
+            if let Some(name) = entry.value() {
+                if !remotes.iter().any(|r| r == name) {
+                    remotes.push(name.to_string());
+                }
+            }
+        })?;
+    }
+    Ok(remotes)
+}
+
+/// Every remote in the repository.
+pub fn all_remotes(repo: &Repository) -> Result<Vec<String>, ProtocolError> {
+    Ok(repo.remotes()?.iter().flatten().map(String::from).collect())
+}
+
+/// Push the engrams `filter` selects to each of `remotes` in turn. Engram
+/// refspecs are configured on every remote before the first push; a remote
+/// that cannot be configured is reported as failed and skipped.
+pub fn push_to_remotes(
+    repo: &Repository,
+    remotes: &[String],
+    filter: &PushFilter,
+    opts: &SyncOptions,
+) -> Vec<RemoteOutcome<PushResult>> {
+    for_each_remote(repo, remotes, |remote| {
+        push_engrams_filtered(repo, remote, filter, opts)
+    })
+}
+
+/// Fetch engram refs from each of `remotes` in turn, configured as for
+/// [`push_to_remotes`].
+pub fn fetch_from_remotes(
+    repo: &Repository,
+    remotes: &[String],
+    opts: &SyncOptions,
+) -> Vec<RemoteOutcome<FetchResult>> {
+    for_each_remote(repo, remotes, |remote| fetch_engrams(repo, remote, opts))
+}
+
+fn for_each_remote<T>(
+    repo: &Repository,
+    remotes: &[String],
+    sync: impl Fn(&str) -> Result<T, ProtocolError>,
+) -> Vec<RemoteOutcome<T>> {
+    let configured: Vec<_> = remotes
+        .iter()
+        .map(|remote| ensure_refspecs(repo, remote).map(|_| ()))
+        .collect();
+    remotes
+        .iter()
+        .zip(configured)
+        .map(|(remote, configured)| RemoteOutcome {
+            remote: remote.clone(),
+            result: configured.and_then(|()| sync(remote)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn repo_with_engram(id: &str) -> (TempDir, Repository) {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        {
+            let tree = repo.treebuilder(None).unwrap().write().unwrap();
+            let tree = repo.find_tree(tree).unwrap();
+            let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+            let oid = repo.commit(None, &sig, &sig, id, &tree, &[]).unwrap();
+            repo.reference(&format!("refs/engrams/{}/{id}", &id[..2]), oid, false, "")
+                .unwrap();
+        }
+        (tmp, repo)
+    }
+
+    fn bare_remote(repo: &Repository, name: &str) -> TempDir {
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        repo.remote(name, &format!("file://{}", bare.path().display()))
+            .unwrap();
+        bare
+    }
+
+    fn has_engram(bare: &TempDir, id: &str) -> bool {
+        Repository::open_bare(bare.path())
+            .unwrap()
+            .find_reference(&format!("refs/engrams/{}/{id}", &id[..2]))
+            .is_ok()
+    }
+
+    #[test]
+    fn test_push_to_two_remotes() {
+        let (_dir, repo) = repo_with_engram("aaaa1111");
+        let github = bare_remote(&repo, "github");
+        let gitea = bare_remote(&repo, "gitea");
+
+        let mut config = repo.config().unwrap();
+        config
+            .set_multivar(PUSH_REMOTES_KEY, "^$", "github")
+            .unwrap();
+        config
+            .set_multivar(PUSH_REMOTES_KEY, "^$", "gitea")
+            .unwrap();
+        let remotes = configured_push_remotes(&repo).unwrap();
+        assert_eq!(remotes, ["github", "gitea"]);
+
+        let outcomes = push_to_remotes(
+            &repo,
+            &remotes,
+            &PushFilter::default(),
+            &SyncOptions::default(),
+        );
+        assert!(outcomes.iter().all(|o| o.result.is_ok()));
+        assert!(has_engram(&github, "aaaa1111"));
+        assert!(has_engram(&gitea, "aaaa1111"));
+
+        // Fetch from both mirrors; a missing remote fails on its own
+        let other_dir = TempDir::new().unwrap();
+        let other = Repository::init(other_dir.path()).unwrap();
+        for (name, bare) in [("github", &github), ("gitea", &gitea)] {
+            other
+                .remote(name, &format!("file://{}", bare.path().display()))
+                .unwrap();
+        }
+        let remotes = ["github", "nowhere", "gitea"].map(String::from);
+        let outcomes = fetch_from_remotes(&other, &remotes, &SyncOptions::default());
+        let created: Vec<_> = outcomes
+            .iter()
+            .map(|o| o.result.as_ref().ok().map(|r| r.refs_created))
+            .collect();
+        assert_eq!(created, [Some(1), None, Some(0)]);
+        assert!(matches!(
+            outcomes[1].result,
+            Err(ProtocolError::RemoteNotFound(_))
+        ));
+        assert_eq!(outcomes[2].result.as_ref().unwrap().refs_unchanged, 1);
+    }
+}
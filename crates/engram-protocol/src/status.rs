@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use git2::{Oid, Repository};
+
+use engram_core::model::EngramId;
+use engram_core::storage::refs::list_engram_refs;
+
+use crate::error::ProtocolError;
+use crate::prune::engram_id;
+use crate::sync::{remote_engram_refs, SyncOptions};
+
+/// How the engrams here compare with those on a remote.
+#[derive(Debug, Default)]
+pub struct SyncStatus {
+    pub remote: String,
+    /// Engrams only this repository has; a push would send them.
+    pub local_only: Vec<EngramId>,
+    /// Engrams only the remote has; a fetch would bring them.
+    pub remote_only: Vec<EngramId>,
+    /// Engrams both have, at different commits.
+    pub diverged: Vec<EngramId>,
+    /// Engrams both have at the same commit.
+    pub in_sync: usize,
+}
+
+impl SyncStatus {
+    /// Whether both sides hold the same engrams at the same commits.
+    pub fn is_in_sync(&self) -> bool {
+        self.local_only.is_empty() && self.remote_only.is_empty() && self.diverged.is_empty()
+    }
+}
+
+/// Compare local engram refs with those the remote advertises. Only the
+/// remote's ref list is read; no objects are transferred.
+pub fn sync_status(
+    repo: &Repository,
+    remote_name: &str,
+    opts: &SyncOptions,
+) -> Result<SyncStatus, ProtocolError> {
+    let remote_refs = remote_engram_refs(repo, remote_name, opts)?
+        .into_iter()
+        .filter_map(|(name, oid)| Some((engram_id(&name)?.0, oid)))
+        .collect();
+
+    Ok(compare(
+        remote_name,
+        list_engram_refs(repo)?
+            .into_iter()
+            .map(|(id, oid)| (id.0, oid))
+            .collect(),
+        remote_refs,
+    ))
+}
+
+fn compare(
+    remote_name: &str,
+    local: BTreeMap<String, Oid>,
+    mut remote: BTreeMap<String, Oid>,
+) -> SyncStatus {
+    let mut status = SyncStatus {
+        remote: remote_name.into(),
+        ..Default::default()
+    };
+    for (id, oid) in local {
+        match remote.remove(&id) {
+            None => status.local_only.push(EngramId(id)),
+            Some(theirs) if theirs != oid => status.diverged.push(EngramId(id)),
+            Some(_) => status.in_sync += 1,
+        }
+    }
+    status.remote_only = remote.into_keys().map(EngramId).collect();
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::push_engrams;
+    use tempfile::TempDir;
+
+    fn commit_ref(repo: &Repository, id: &str, message: &str) {
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let oid = repo.commit(None, &sig, &sig, message, &tree, &[]).unwrap();
+        repo.reference(&format!("refs/engrams/{}/{id}", &id[..2]), oid, true, "")
+            .unwrap();
+    }
+
+    fn ids(ids: &[EngramId]) -> Vec<&str> {
+        ids.iter().map(EngramId::as_str).collect()
+    }
+
+    #[test]
+    fn test_status_against_bare_remote() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        repo.remote("origin", &format!("file://{}", bare.path().display()))
+            .unwrap();
+
+        for id in ["aaaa1111", "bbbb2222", "cccc3333"] {
+            commit_ref(&repo, id, id);
+        }
+        let status = sync_status(&repo, "origin", &SyncOptions::default()).unwrap();
+        assert_eq!(status.local_only.len(), 3);
+        assert!(status.remote_only.is_empty());
+
+        push_engrams(&repo, "origin", &SyncOptions::default()).unwrap();
+
+        // Drop one locally, move one on, add one the remote lacks
+        repo.find_reference("refs/engrams/cc/cccc3333")
+            .unwrap()
+            .delete()
+            .unwrap();
+        commit_ref(&repo, "bbbb2222", "again");
+        commit_ref(&repo, "dddd4444", "dddd4444");
+
+        let status = sync_status(&repo, "origin", &SyncOptions::default()).unwrap();
+        assert_eq!(ids(&status.local_only), ["dddd4444"]);
+        assert_eq!(ids(&status.remote_only), ["cccc3333"]);
+        assert_eq!(ids(&status.diverged), ["bbbb2222"]);
+        assert_eq!(status.in_sync, 1);
+        assert!(!status.is_in_sync());
+
+        // Nothing was fetched
+        assert!(repo.find_reference("refs/engrams/cc/cccc3333").is_err());
+    }
+
+    #[test]
+    fn test_status_unknown_remote() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let err = sync_status(&repo, "nowhere", &SyncOptions::default()).unwrap_err();
+        assert!(matches!(err, ProtocolError::RemoteNotFound(_)));
+    }
+}
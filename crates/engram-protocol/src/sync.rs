@@ -1,29 +1,114 @@
-use git2::Repository;
+use std::cell::{Cell, RefCell};
+use std::path::Path;
 
+use git2::{
+    ErrorClass, ErrorCode, FetchOptions, Oid, PushOptions, Remote, RemoteCallbacks, Repository,
+};
+
+use engram_core::model::{EngramId, Manifest};
+use engram_core::storage::objects::merge_engram_objects;
+use engram_core::storage::read::read_manifest;
+use engram_core::storage::refs::{
+    engram_ref_name, list_engram_refs, resolve_engram_ref, ENGRAM_REF_PREFIX,
+};
+
+use crate::credentials::{is_auth_error, CredentialCallback, CredentialResolver, ProcessEnv};
 use crate::error::ProtocolError;
-use crate::refspec::{ensure_refspecs, ENGRAM_FETCH_REFSPEC, ENGRAM_PUSH_REFSPEC};
+use crate::prune::{engram_id, prune_local, read_known_refs, write_known_refs, KnownRefs};
+use crate::refspec::{ensure_refspecs, ENGRAM_FETCH_REFSPEC};
+
+/// Receives [`SyncProgress`] updates during a push or fetch.
+pub type ProgressCallback = dyn Fn(SyncProgress);
 
 /// Options for push/fetch operations.
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct SyncOptions {
     /// Only sync these specific engram ref patterns (empty = all).
     pub refspecs: Vec<String>,
     /// Dry run — don't actually transfer data.
     pub dry_run: bool,
+    /// Answers the remote's credential requests instead of the default
+    /// chain: ssh-agent, `credential.helper`, token variables such as
+    /// `GITHUB_TOKEN`, then `GIT_ASKPASS`.
+    pub credentials: Option<Box<CredentialCallback>>,
+    /// Called as objects are transferred and refs updated.
+    pub progress: Option<Box<ProgressCallback>>,
+    /// On fetch, delete local engram refs the remote has dropped since they
+    /// were last synced with it.
+    pub prune: bool,
+}
+
+impl std::fmt::Debug for SyncOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncOptions")
+            .field("refspecs", &self.refspecs)
+            .field("dry_run", &self.dry_run)
+            .field("credentials", &self.credentials.as_ref().map(|_| "custom"))
+            .field("progress", &self.progress.is_some())
+            .field("prune", &self.prune)
+            .finish()
+    }
+}
+
+/// How far a push or fetch has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// Objects sent or received so far, of `total_objects`.
+    pub objects: usize,
+    pub total_objects: usize,
+    /// Bytes sent or received so far.
+    pub bytes: usize,
+    /// Refs updated on the receiving side so far.
+    pub refs_updated: usize,
+}
+
+/// Selects engrams for [`push_engrams_filtered`] and [`crate::bundle::write`].
+#[derive(Default)]
+pub struct PushFilter {
+    /// Engram IDs or unique prefixes; empty selects every engram.
+    pub ids: Vec<String>,
+    /// Keeps only the selected engrams whose manifest it accepts.
+    pub predicate: Option<Box<ManifestPredicate>>,
 }
 
+/// A test over an engram's manifest, for [`PushFilter::predicate`].
+pub type ManifestPredicate = dyn Fn(&Manifest) -> bool;
+
 /// Result of a push operation.
 #[derive(Debug)]
 pub struct PushResult {
     pub remote: String,
+    /// Refs named in the push; for a dry run, the refs that would be.
     pub refs_pushed: usize,
+    /// The local refs behind `refs_pushed`.
+    pub refs: Vec<String>,
+    /// Refs the remote did not have.
+    pub refs_created: usize,
+    /// Refs the remote had at another commit.
+    pub refs_updated: usize,
+    /// Refs the remote already had.
+    pub refs_unchanged: usize,
 }
 
 /// Result of a fetch operation.
 #[derive(Debug)]
 pub struct FetchResult {
     pub remote: String,
+    /// Refs created, updated or merged locally.
     pub refs_fetched: usize,
+    pub refs_created: usize,
+    /// Refs fast-forwarded to the remote's commit.
+    pub refs_updated: usize,
+    /// Refs where both sides had changes, now combined in a merge commit.
+    pub refs_merged: usize,
+    /// Matching refs on the remote that were already up to date here, or
+    /// that only have local changes on top.
+    pub refs_unchanged: usize,
+    /// Engrams changed differently here and on the remote, so kept as they
+    /// are here.
+    pub diverged: Vec<EngramId>,
+    /// Engrams deleted here because the remote no longer has them.
+    pub pruned: Vec<EngramId>,
 }
 
 /// Push engram refs to a remote.
@@ -34,18 +119,103 @@ pub fn push_engrams(
 ) -> Result<PushResult, ProtocolError> {
     ensure_refspecs(repo, remote_name)?;
 
+    // libgit2 cannot push a glob refspec, so each engram ref is named
     let refspecs = if opts.refspecs.is_empty() {
-        vec![ENGRAM_PUSH_REFSPEC.to_string()]
+        list_engram_refs(repo)?
+            .into_iter()
+            .map(|(id, _)| ref_refspec(&id))
+            .collect()
     } else {
         opts.refspecs.clone()
     };
+    push_refspecs(repo, remote_name, refspecs, opts)
+}
 
-    if opts.dry_run {
-        // Count refs that would be pushed
-        let refs = engram_core::storage::refs::list_engram_refs(repo)?;
+/// Push only the engrams `filter` selects, each by its own refspec, so
+/// that nothing else reaches the remote. `opts.refspecs` is ignored.
+pub fn push_engrams_filtered(
+    repo: &Repository,
+    remote_name: &str,
+    filter: &PushFilter,
+    opts: &SyncOptions,
+) -> Result<PushResult, ProtocolError> {
+    ensure_refspecs(repo, remote_name)?;
+
+    let refspecs = select_engrams(repo, filter)?
+        .iter()
+        .map(|(id, _)| ref_refspec(id))
+        .collect();
+    push_refspecs(repo, remote_name, refspecs, opts)
+}
+
+/// The engrams `filter` selects, with the commits their refs point at.
+pub(crate) fn select_engrams(
+    repo: &Repository,
+    filter: &PushFilter,
+) -> Result<Vec<(EngramId, git2::Oid)>, ProtocolError> {
+    let candidates = if filter.ids.is_empty() {
+        list_engram_refs(repo)?
+    } else {
+        let mut resolved = Vec::new();
+        for id in &filter.ids {
+            let found = resolve_engram_ref(repo, id)?;
+            if !resolved.contains(&found) {
+                resolved.push(found);
+            }
+        }
+        resolved
+    };
+
+    let mut selected = Vec::new();
+    for (id, oid) in candidates {
+        if let Some(predicate) = &filter.predicate {
+            match read_manifest(repo, oid) {
+                Ok(manifest) if predicate(&manifest) => {}
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::warn!("Skipping engram {id}: {e}");
+                    continue;
+                }
+            }
+        }
+        selected.push((id, oid));
+    }
+    Ok(selected)
+}
+
+/// `name:name` for an engram's ref.
+fn ref_refspec(id: &EngramId) -> String {
+    let name = engram_ref_name(id);
+    format!("{name}:{name}")
+}
+
+pub(crate) fn push_refspecs(
+    repo: &Repository,
+    remote_name: &str,
+    refspecs: Vec<String>,
+    opts: &SyncOptions,
+) -> Result<PushResult, ProtocolError> {
+    // The ref each refspec pushes, or for `:dst`, deletes
+    let refs: Vec<String> = refspecs
+        .iter()
+        .map(|spec| {
+            let spec = spec.trim_start_matches('+');
+            match spec.split_once(':') {
+                Some(("", dst)) => dst.to_string(),
+                Some((src, _)) => src.to_string(),
+                None => spec.to_string(),
+            }
+        })
+        .collect();
+
+    if opts.dry_run || refspecs.is_empty() {
         return Ok(PushResult {
             remote: remote_name.into(),
-            refs_pushed: refs.len(),
+            refs_pushed: refspecs.len(),
+            refs,
+            refs_created: 0,
+            refs_updated: 0,
+            refs_unchanged: 0,
         });
     }
 
@@ -55,20 +225,50 @@ pub fn push_engrams(
 
     let refspec_strs: Vec<&str> = refspecs.iter().map(|s| s.as_str()).collect();
 
+    let resolver = CredentialResolver::new(repo.config().ok(), &ProcessEnv);
+    let transfer = Transfer::default();
+    let mut push_opts = PushOptions::new();
+    push_opts.remote_callbacks(callbacks(opts, &resolver, &transfer, Direction::Push));
     remote
-        .push(&refspec_strs, None)
-        .map_err(|e| ProtocolError::Sync(format!("Push failed: {e}")))?;
+        .push(&refspec_strs, Some(&mut push_opts))
+        .map_err(|e| sync_error("Push", remote_name, e, opts, &resolver))?;
 
-    // Count refs (approximate)
-    let refs = engram_core::storage::refs::list_engram_refs(repo)?;
+    let rejected = transfer.rejected.take();
+    if !rejected.is_empty() {
+        return Err(ProtocolError::Sync(format!(
+            "Remote rejected {} ref(s): {}",
+            rejected.len(),
+            rejected.join("; ")
+        )));
+    }
+
+    let mut known = read_known_refs(repo, remote_name);
+    for name in &refs {
+        match repo.refname_to_id(name) {
+            Ok(oid) => known.insert(name.clone(), oid),
+            Err(_) => known.remove(name),
+        };
+    }
+    write_known_refs(repo, remote_name, &known);
 
+    let (created, updated) = (transfer.created.get(), transfer.updated.get());
     Ok(PushResult {
         remote: remote_name.into(),
-        refs_pushed: refs.len(),
+        refs_pushed: refspecs.len(),
+        refs,
+        refs_created: created,
+        refs_updated: updated,
+        refs_unchanged: refspecs.len().saturating_sub(created + updated),
     })
 }
 
 /// Fetch engram refs from a remote.
+///
+/// Local changes are never lost: the remote's refs are fetched to one side,
+/// then each local ref is fast-forwarded to the remote's commit, or, when
+/// both sides have changes (such as notes added on two clones), merged
+/// with it. Refs changed differently on both sides are kept as they are
+/// here and listed in [`FetchResult::diverged`].
 pub fn fetch_engrams(
     repo: &Repository,
     remote_name: &str,
@@ -76,36 +276,737 @@ pub fn fetch_engrams(
 ) -> Result<FetchResult, ProtocolError> {
     ensure_refspecs(repo, remote_name)?;
 
-    let refspecs = if opts.refspecs.is_empty() {
+    let staging = format!("refs/engram-fetch/{remote_name}/");
+    let refspecs: Vec<String> = if opts.refspecs.is_empty() {
         vec![ENGRAM_FETCH_REFSPEC.to_string()]
     } else {
         opts.refspecs.clone()
-    };
+    }
+    .iter()
+    .map(|spec| staged_refspec(spec, &staging))
+    .collect();
 
     if opts.dry_run {
         return Ok(FetchResult {
             remote: remote_name.into(),
             refs_fetched: 0,
+            refs_created: 0,
+            refs_updated: 0,
+            refs_merged: 0,
+            refs_unchanged: 0,
+            diverged: Vec::new(),
+            pruned: Vec::new(),
         });
     }
 
-    let refs_before = engram_core::storage::refs::list_engram_refs(repo)?;
-
-    let mut remote = repo
+    let url = repo
         .find_remote(remote_name)
-        .map_err(|_| ProtocolError::RemoteNotFound(remote_name.into()))?;
+        .map_err(|_| ProtocolError::RemoteNotFound(remote_name.into()))?
+        .url()
+        .map(String::from)
+        .ok_or_else(|| ProtocolError::Sync(format!("Remote '{remote_name}' has no URL")))?;
+    // libgit2 also updates the refs a named remote's configured refspecs
+    // match, forcing them, so the fetch goes through an anonymous one
+    let mut remote = repo.remote_anonymous(&url)?;
 
     let refspec_strs: Vec<&str> = refspecs.iter().map(|s| s.as_str()).collect();
 
-    remote
-        .fetch(&refspec_strs, None, None)
-        .map_err(|e| ProtocolError::Sync(format!("Fetch failed: {e}")))?;
+    let resolver = CredentialResolver::new(repo.config().ok(), &ProcessEnv);
+    let transfer = Transfer::default();
+    let mut fetch_opts = FetchOptions::new();
+    fetch_opts.remote_callbacks(callbacks(opts, &resolver, &transfer, Direction::Fetch));
+    let fetched = remote
+        .fetch(&refspec_strs, Some(&mut fetch_opts), None)
+        .map_err(|e| sync_error("Fetch", remote_name, e, opts, &resolver))
+        .and_then(|()| integrate_staged(repo, &staging));
+    for reference in repo.references_glob(&format!("{staging}*"))? {
+        reference?.delete()?;
+    }
+    let integrated = fetched?;
 
-    let refs_after = engram_core::storage::refs::list_engram_refs(repo)?;
-    let new_refs = refs_after.len().saturating_sub(refs_before.len());
+    // The remote's ref list outlives the connection
+    let advertised = advertised_engram_refs(&remote)?;
+    let pruned = if opts.prune {
+        prune_local(repo, remote_name, &advertised)?
+    } else {
+        Vec::new()
+    };
+    // Without pruning, dropped refs stay known so a later prune finds them
+    let mut known = if opts.prune {
+        KnownRefs::new()
+    } else {
+        read_known_refs(repo, remote_name)
+    };
+    known.extend(advertised.iter().map(|(name, oid)| (name.clone(), *oid)));
+    write_known_refs(repo, remote_name, &known);
 
+    let count = |kind| integrated.iter().filter(|(_, i)| *i == kind).count();
+    let (created, updated, merged) = (
+        count(Integration::Created),
+        count(Integration::FastForwarded),
+        count(Integration::Merged),
+    );
     Ok(FetchResult {
         remote: remote_name.into(),
-        refs_fetched: new_refs,
+        refs_fetched: created + updated + merged,
+        refs_created: created,
+        refs_updated: updated,
+        refs_merged: merged,
+        refs_unchanged: count(Integration::Unchanged),
+        diverged: integrated
+            .into_iter()
+            .filter(|(_, i)| *i == Integration::Diverged)
+            .map(|(id, _)| id)
+            .collect(),
+        pruned,
     })
 }
+
+/// `spec` fetching into `staging` instead of `refs/engrams/`, forced, as
+/// the staged refs are only compared with the local ones.
+fn staged_refspec(spec: &str, staging: &str) -> String {
+    let spec = spec.trim_start_matches('+');
+    match spec.split_once(':') {
+        Some((src, dst)) => match dst.strip_prefix(ENGRAM_REF_PREFIX) {
+            Some(rest) => format!("+{src}:{staging}{rest}"),
+            None => spec.to_string(),
+        },
+        None => spec.to_string(),
+    }
+}
+
+/// How a fetched engram ref was brought into the local one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Integration {
+    Created,
+    FastForwarded,
+    Merged,
+    /// Already there, or only behind the local ref.
+    Unchanged,
+    /// Changed differently on both sides; the local ref is kept.
+    Diverged,
+}
+
+/// Bring each ref fetched under `staging` into its engram ref.
+fn integrate_staged(
+    repo: &Repository,
+    staging: &str,
+) -> Result<Vec<(EngramId, Integration)>, ProtocolError> {
+    let mut staged = Vec::new();
+    for reference in repo.references_glob(&format!("{staging}*"))? {
+        let reference = reference?;
+        let (Some(name), Some(oid)) = (reference.name(), reference.target()) else {
+            continue;
+        };
+        let local = format!("{ENGRAM_REF_PREFIX}{}", &name[staging.len()..]);
+        if let Some(id) = engram_id(&local) {
+            staged.push((id, local, oid));
+        }
+    }
+
+    let mut integrated = Vec::with_capacity(staged.len());
+    for (id, name, fetched) in staged {
+        let integration = integrate(repo, &name, fetched)?;
+        if integration == Integration::Diverged {
+            tracing::warn!("Engram {id} changed both here and on the remote; kept the local one");
+        }
+        integrated.push((id, integration));
+    }
+    Ok(integrated)
+}
+
+/// Point the engram ref `name` at `fetched`, or merge the two, without
+/// dropping anything the local ref has that `fetched` lacks.
+fn integrate(repo: &Repository, name: &str, fetched: Oid) -> Result<Integration, ProtocolError> {
+    let message = "engram: fetch";
+    let local = match repo.refname_to_id(name) {
+        Ok(local) => local,
+        Err(_) => {
+            repo.reference(name, fetched, false, message)?;
+            return Ok(Integration::Created);
+        }
+    };
+    if local == fetched || repo.graph_descendant_of(local, fetched)? {
+        return Ok(Integration::Unchanged);
+    }
+    if repo.graph_descendant_of(fetched, local)? {
+        repo.reference_matching(name, fetched, true, local, message)?;
+        return Ok(Integration::FastForwarded);
+    }
+    match merge_engram_objects(repo, local, fetched)? {
+        Some(merged) => {
+            repo.reference_matching(name, merged, true, local, message)?;
+            Ok(Integration::Merged)
+        }
+        None => Ok(Integration::Diverged),
+    }
+}
+
+/// The engram refs on a remote, read from its ref advertisement without
+/// fetching anything.
+pub(crate) fn remote_engram_refs(
+    repo: &Repository,
+    remote_name: &str,
+    opts: &SyncOptions,
+) -> Result<KnownRefs, ProtocolError> {
+    let mut remote = repo
+        .find_remote(remote_name)
+        .map_err(|_| ProtocolError::RemoteNotFound(remote_name.into()))?;
+
+    let resolver = CredentialResolver::new(repo.config().ok(), &ProcessEnv);
+    let transfer = Transfer::default();
+    remote
+        .connect_auth(
+            git2::Direction::Fetch,
+            Some(callbacks(opts, &resolver, &transfer, Direction::Fetch)),
+            None,
+        )
+        .map_err(|e| sync_error("Listing refs", remote_name, e, opts, &resolver))?;
+    // Dropping the connection disconnects, but the ref list is kept
+    Ok(advertised_engram_refs(&remote)?)
+}
+
+/// The engram refs a remote advertised when last connected, with the
+/// commits they point at.
+fn advertised_engram_refs(remote: &Remote) -> Result<KnownRefs, git2::Error> {
+    if is_empty_local(remote) {
+        return Ok(KnownRefs::new());
+    }
+    Ok(remote
+        .list()?
+        .iter()
+        .filter(|head| engram_id(head.name()).is_some())
+        .map(|head| (head.name().to_string(), head.oid()))
+        .collect())
+}
+
+/// Whether `remote` is a repository on this machine without any refs.
+/// libgit2's local transport advertises such a repository as a null list,
+/// which `Remote::list` cannot take, so it is checked directly.
+fn is_empty_local(remote: &Remote) -> bool {
+    let Some(url) = remote.url() else {
+        return false;
+    };
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    Path::new(path).exists()
+        && Repository::open(path)
+            .and_then(|repo| Ok(repo.references()?.next().is_none()))
+            .unwrap_or(false)
+}
+
+/// What a transfer's callbacks have seen.
+#[derive(Default)]
+struct Transfer {
+    progress: Cell<SyncProgress>,
+    created: Cell<usize>,
+    updated: Cell<usize>,
+    /// `ref: reason` for each ref the remote refused.
+    rejected: RefCell<Vec<String>>,
+}
+
+impl Transfer {
+    fn report(&self, opts: &SyncOptions, update: impl FnOnce(&mut SyncProgress)) {
+        let mut progress = self.progress.get();
+        update(&mut progress);
+        self.progress.set(progress);
+        if let Some(callback) = &opts.progress {
+            callback(progress);
+        }
+    }
+
+    fn count(&self, old: git2::Oid, new: git2::Oid) {
+        if new.is_zero() {
+            return; // A deletion
+        }
+        if old.is_zero() {
+            self.created.set(self.created.get() + 1);
+        } else if old != new {
+            self.updated.set(self.updated.get() + 1);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Push,
+    Fetch,
+}
+
+/// Callbacks answering credential requests with `opts.credentials`, or
+/// else `resolver`, and recording progress and ref updates in `transfer`.
+/// Only the callbacks for `direction` are set, as libgit2 also updates
+/// local tips after a push.
+fn callbacks<'a>(
+    opts: &'a SyncOptions,
+    resolver: &'a CredentialResolver<'a>,
+    transfer: &'a Transfer,
+    direction: Direction,
+) -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+    match &opts.credentials {
+        Some(custom) => {
+            callbacks.credentials(move |url, username, allowed| custom(url, username, allowed))
+        }
+        None => callbacks
+            .credentials(move |url, username, allowed| resolver.resolve(url, username, allowed)),
+    };
+
+    match direction {
+        Direction::Fetch => {
+            callbacks.transfer_progress(move |stats| {
+                transfer.report(opts, |p| {
+                    p.objects = stats.received_objects();
+                    p.total_objects = stats.total_objects();
+                    p.bytes = stats.received_bytes();
+                });
+                true
+            });
+            callbacks.update_tips(move |_, old, new| {
+                transfer.count(old, new);
+                transfer.report(opts, |p| p.refs_updated += 1);
+                true
+            });
+        }
+        Direction::Push => {
+            callbacks.push_negotiation(move |updates| {
+                for update in updates {
+                    transfer.count(update.src(), update.dst());
+                }
+                Ok(())
+            });
+            callbacks.push_transfer_progress(move |current, total, bytes| {
+                transfer.report(opts, |p| {
+                    p.objects = current;
+                    p.total_objects = total;
+                    p.bytes = bytes;
+                });
+            });
+            callbacks.push_update_reference(move |refname, status| {
+                match status {
+                    Some(reason) => transfer
+                        .rejected
+                        .borrow_mut()
+                        .push(format!("{refname}: {reason}")),
+                    None => transfer.report(opts, |p| p.refs_updated += 1),
+                }
+                Ok(())
+            });
+        }
+    }
+    callbacks
+}
+
+/// Tell refused credentials and missing refs apart from other failures.
+fn sync_error(
+    action: &str,
+    remote_name: &str,
+    e: git2::Error,
+    opts: &SyncOptions,
+    resolver: &CredentialResolver,
+) -> ProtocolError {
+    if is_auth_error(&e) {
+        let tried = match (&opts.credentials, resolver.tried()) {
+            (Some(_), _) => "custom credentials".to_string(),
+            (None, tried) if tried.is_empty() => "nothing".to_string(),
+            (None, tried) => tried.join(", "),
+        };
+        ProtocolError::Auth {
+            remote: remote_name.into(),
+            message: e.message().to_string(),
+            tried,
+        }
+    } else if e.code() == ErrorCode::NotFound || e.class() == ErrorClass::Reference {
+        ProtocolError::RefNotFound {
+            remote: remote_name.into(),
+            message: e.message().to_string(),
+        }
+    } else {
+        ProtocolError::Sync(format!("{action} failed: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use tempfile::TempDir;
+
+    /// A repository with one engram ref per ID, each on an empty commit.
+    fn repo_with_engrams(ids: &[&str]) -> (TempDir, Repository) {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        {
+            let tree = repo.find_tree(repo.treebuilder(None).unwrap().write().unwrap());
+            let tree = tree.unwrap();
+            let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+            for id in ids {
+                let oid = repo.commit(None, &sig, &sig, id, &tree, &[]).unwrap();
+                repo.reference(&format!("refs/engrams/{}/{id}", &id[..2]), oid, false, "")
+                    .unwrap();
+            }
+        }
+        (tmp, repo)
+    }
+
+    fn add_file_remote(repo: &Repository, remote: &TempDir) {
+        let url = format!("file://{}", remote.path().display());
+        repo.remote("origin", &url).unwrap();
+    }
+
+    /// Point the ref for `id` at a new empty commit.
+    fn advance(repo: &Repository, id: &str) {
+        let name = format!("refs/engrams/{}/{id}", &id[..2]);
+        let parent = repo
+            .find_reference(&name)
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let oid = repo
+            .commit(
+                None,
+                &sig,
+                &sig,
+                "again",
+                &parent.tree().unwrap(),
+                &[&parent],
+            )
+            .unwrap();
+        repo.reference(&name, oid, true, "").unwrap();
+    }
+
+    /// Options whose progress callback records every update.
+    fn recording() -> (SyncOptions, Rc<RefCell<Vec<SyncProgress>>>) {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&seen);
+        let opts = SyncOptions {
+            progress: Some(Box::new(move |p| sink.borrow_mut().push(p))),
+            ..Default::default()
+        };
+        (opts, seen)
+    }
+
+    #[test]
+    fn test_push_and_fetch_over_file_protocol() {
+        let (_local_dir, local) = repo_with_engrams(&["aaaa1111", "bbbb2222"]);
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        add_file_remote(&local, &bare);
+
+        let (opts, seen) = recording();
+        let pushed = push_engrams(&local, "origin", &opts).unwrap();
+        assert_eq!(pushed.refs_pushed, 2);
+        assert_eq!(
+            (
+                pushed.refs_created,
+                pushed.refs_updated,
+                pushed.refs_unchanged
+            ),
+            (2, 0, 0)
+        );
+        let last = *seen.borrow().last().expect("progress reported");
+        assert_eq!(last.refs_updated, 2);
+
+        let (_other_dir, other) = repo_with_engrams(&[]);
+        add_file_remote(&other, &bare);
+        let (opts, seen) = recording();
+        let fetched = fetch_engrams(&other, "origin", &opts).unwrap();
+        assert_eq!(fetched.refs_fetched, 2);
+        assert_eq!(
+            (
+                fetched.refs_created,
+                fetched.refs_updated,
+                fetched.refs_unchanged
+            ),
+            (2, 0, 0)
+        );
+        assert!(other.find_reference("refs/engrams/aa/aaaa1111").is_ok());
+        let last = *seen.borrow().last().expect("progress reported");
+        assert_eq!(last.refs_updated, 2);
+        assert!(last.objects > 0 && last.objects == last.total_objects);
+
+        // One ref moves on, one is new, one is already there
+        advance(&local, "aaaa1111");
+        let tree = local.find_tree(local.treebuilder(None).unwrap().write().unwrap());
+        let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let oid = local
+            .commit(None, &sig, &sig, "cccc", &tree.unwrap(), &[])
+            .unwrap();
+        local
+            .reference("refs/engrams/cc/cccc3333", oid, false, "")
+            .unwrap();
+
+        let pushed = push_engrams(&local, "origin", &SyncOptions::default()).unwrap();
+        assert_eq!(
+            (
+                pushed.refs_created,
+                pushed.refs_updated,
+                pushed.refs_unchanged
+            ),
+            (1, 1, 1)
+        );
+        let fetched = fetch_engrams(&other, "origin", &SyncOptions::default()).unwrap();
+        assert_eq!(
+            (
+                fetched.refs_created,
+                fetched.refs_updated,
+                fetched.refs_unchanged
+            ),
+            (1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_missing_ref_is_not_an_auth_error() {
+        let (_local_dir, local) = repo_with_engrams(&["aaaa1111"]);
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        add_file_remote(&local, &bare);
+
+        let opts = SyncOptions {
+            refspecs: vec!["refs/engrams/zz/missing:refs/engrams/zz/missing".into()],
+            ..Default::default()
+        };
+        let err = push_engrams(&local, "origin", &opts).unwrap_err();
+        assert!(
+            matches!(err, ProtocolError::RefNotFound { .. }),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_fetch_from_empty_remote() {
+        let (_dir, repo) = repo_with_engrams(&[]);
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        add_file_remote(&repo, &bare);
+
+        let fetched = fetch_engrams(&repo, "origin", &SyncOptions::default()).unwrap();
+        assert_eq!((fetched.refs_fetched, fetched.refs_unchanged), (0, 0));
+    }
+
+    #[test]
+    fn test_unknown_remote() {
+        let (_dir, repo) = repo_with_engrams(&[]);
+        let err = fetch_engrams(&repo, "nowhere", &SyncOptions::default()).unwrap_err();
+        assert!(matches!(err, ProtocolError::RemoteNotFound(_)));
+    }
+
+    fn engram(tags: &[&str]) -> engram_core::model::EngramData {
+        let builder = engram_core::model::EngramData::builder()
+            .created_at("2026-01-15T10:00:00Z".parse().unwrap());
+        tags.iter().fold(builder, |b, tag| b.tag(tag)).build()
+    }
+
+    #[test]
+    fn test_notes_travel_with_engrams() {
+        use engram_core::storage::GitStorage;
+
+        let local_dir = TempDir::new().unwrap();
+        Repository::init(local_dir.path()).unwrap();
+        let local = GitStorage::open(local_dir.path()).unwrap();
+        local.init().unwrap();
+        let id = local.create(&engram(&[])).unwrap();
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        add_file_remote(local.repo(), &bare);
+        push_engrams(local.repo(), "origin", &SyncOptions::default()).unwrap();
+
+        let other_dir = TempDir::new().unwrap();
+        Repository::init(other_dir.path()).unwrap();
+        let other = GitStorage::open(other_dir.path()).unwrap();
+        add_file_remote(other.repo(), &bare);
+        fetch_engrams(other.repo(), "origin", &SyncOptions::default()).unwrap();
+        assert!(other.read_notes(id.as_str()).unwrap().is_empty());
+
+        // A note moves the ref forward, so it pushes and fetches as an update
+        local
+            .add_note(id.as_str(), "alice", "Reverted later")
+            .unwrap();
+        let pushed = push_engrams(local.repo(), "origin", &SyncOptions::default()).unwrap();
+        assert_eq!(pushed.refs_updated, 1);
+        let fetched = fetch_engrams(other.repo(), "origin", &SyncOptions::default()).unwrap();
+        assert_eq!(fetched.refs_updated, 1);
+        let notes = other.read_notes(id.as_str()).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].text, "Reverted later");
+    }
+
+    /// A storage in a new repository with `bare` as its `origin`.
+    fn clone_of(bare: &TempDir) -> (TempDir, engram_core::storage::GitStorage) {
+        let dir = TempDir::new().unwrap();
+        Repository::init(dir.path()).unwrap();
+        let storage = engram_core::storage::GitStorage::open(dir.path()).unwrap();
+        add_file_remote(storage.repo(), bare);
+        (dir, storage)
+    }
+
+    #[test]
+    fn test_fetch_keeps_unpushed_changes() {
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        let (_a_dir, a) = clone_of(&bare);
+        let id = a.create(&engram(&[])).unwrap();
+        let id = id.as_str();
+        push_engrams(a.repo(), "origin", &SyncOptions::default()).unwrap();
+        let (_b_dir, b) = clone_of(&bare);
+        fetch_engrams(b.repo(), "origin", &SyncOptions::default()).unwrap();
+
+        // A note and a tag that aren't pushed survive a fetch
+        a.add_note(id, "alice", "local note A").unwrap();
+        a.update_manifest(id, |m| m.tags.push("wip".into()))
+            .unwrap();
+        let fetched = fetch_engrams(a.repo(), "origin", &SyncOptions::default()).unwrap();
+        assert_eq!((fetched.refs_fetched, fetched.refs_unchanged), (0, 1));
+        assert_eq!(a.read_notes(id).unwrap()[0].text, "local note A");
+        assert_eq!(a.read_manifest(id).unwrap().tags, ["wip"]);
+
+        // Both clones add a note: the second push is refused, and fetching
+        // merges the notes instead of dropping either
+        b.add_note(id, "bob", "local note B").unwrap();
+        push_engrams(b.repo(), "origin", &SyncOptions::default()).unwrap();
+        assert!(push_engrams(a.repo(), "origin", &SyncOptions::default()).is_err());
+        let fetched = fetch_engrams(a.repo(), "origin", &SyncOptions::default()).unwrap();
+        assert_eq!((fetched.refs_merged, fetched.refs_fetched), (1, 1));
+        let texts = |s: &engram_core::storage::GitStorage| -> Vec<String> {
+            s.read_notes(id)
+                .unwrap()
+                .into_iter()
+                .map(|n| n.text)
+                .collect()
+        };
+        assert_eq!(texts(&a), ["local note A", "local note B"]);
+        assert_eq!(a.read_manifest(id).unwrap().tags, ["wip"]);
+
+        // The merge pushes as a fast-forward
+        push_engrams(a.repo(), "origin", &SyncOptions::default()).unwrap();
+        let fetched = fetch_engrams(b.repo(), "origin", &SyncOptions::default()).unwrap();
+        assert_eq!(fetched.refs_updated, 1);
+        assert_eq!(texts(&b), texts(&a));
+        assert_eq!(b.read_manifest(id).unwrap().tags, ["wip"]);
+
+        // Conflicting edits are reported and the local one kept
+        a.update_manifest(id, |m| m.summary = Some("From A".into()))
+            .unwrap();
+        b.update_manifest(id, |m| m.summary = Some("From B".into()))
+            .unwrap();
+        push_engrams(b.repo(), "origin", &SyncOptions::default()).unwrap();
+        let fetched = fetch_engrams(a.repo(), "origin", &SyncOptions::default()).unwrap();
+        assert_eq!(fetched.refs_fetched, 0);
+        assert_eq!(
+            fetched
+                .diverged
+                .iter()
+                .map(EngramId::as_str)
+                .collect::<Vec<_>>(),
+            [id]
+        );
+        assert_eq!(
+            a.read_manifest(id).unwrap().summary.as_deref(),
+            Some("From A")
+        );
+        assert!(a
+            .repo()
+            .references_glob("refs/engram-fetch/*")
+            .unwrap()
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_forced_fetch_refspec_is_replaced() {
+        let (_dir, repo) = repo_with_engrams(&[]);
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        add_file_remote(&repo, &bare);
+        repo.remote_add_fetch("origin", "+refs/engrams/*:refs/engrams/*")
+            .unwrap();
+
+        assert!(ensure_refspecs(&repo, "origin").unwrap());
+        let remote = repo.find_remote("origin").unwrap();
+        let fetch: Vec<_> = remote
+            .fetch_refspecs()
+            .unwrap()
+            .iter()
+            .flatten()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            fetch,
+            ["+refs/heads/*:refs/remotes/origin/*", ENGRAM_FETCH_REFSPEC]
+        );
+    }
+
+    fn remote_refs(bare: &TempDir) -> Vec<String> {
+        let repo = Repository::open_bare(bare.path()).unwrap();
+        let mut names: Vec<_> = repo
+            .references()
+            .unwrap()
+            .filter_map(|r| r.ok()?.name().map(String::from))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_filtered_push_sends_only_selected_engrams() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = engram_core::storage::GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let pr_a = engram(&["pr-42"]);
+        let pr_b = engram(&["pr-42", "auth"]);
+        let private = engram(&["spike"]);
+        for data in [&pr_a, &pr_b, &private] {
+            storage.create(data).unwrap();
+        }
+        let repo = storage.repo();
+        let bare = TempDir::new().unwrap();
+        Repository::init_bare(bare.path()).unwrap();
+        add_file_remote(repo, &bare);
+        let ref_of = |data: &engram_core::model::EngramData| engram_ref_name(&data.manifest.id);
+
+        // Dry run names the refs but sends nothing
+        let by_id = PushFilter {
+            ids: vec![pr_a.manifest.id.as_str()[..8].to_string()],
+            ..Default::default()
+        };
+        let dry = SyncOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let result = push_engrams_filtered(repo, "origin", &by_id, &dry).unwrap();
+        assert_eq!(result.refs, [ref_of(&pr_a)]);
+        assert!(remote_refs(&bare).is_empty());
+
+        let result =
+            push_engrams_filtered(repo, "origin", &by_id, &SyncOptions::default()).unwrap();
+        assert_eq!(result.refs_created, 1);
+        assert_eq!(remote_refs(&bare), [ref_of(&pr_a)]);
+
+        let by_tag = PushFilter {
+            predicate: Some(Box::new(|m: &Manifest| m.tags.iter().any(|t| t == "pr-42"))),
+            ..Default::default()
+        };
+        let result =
+            push_engrams_filtered(repo, "origin", &by_tag, &SyncOptions::default()).unwrap();
+        assert_eq!(
+            (
+                result.refs_pushed,
+                result.refs_created,
+                result.refs_unchanged
+            ),
+            (2, 1, 1)
+        );
+        let mut expected = vec![ref_of(&pr_a), ref_of(&pr_b)];
+        expected.sort();
+        assert_eq!(remote_refs(&bare), expected);
+        assert!(!remote_refs(&bare).contains(&ref_of(&private)));
+
+        let unknown = PushFilter {
+            ids: vec!["ffffffffff".into()],
+            ..Default::default()
+        };
+        assert!(push_engrams_filtered(repo, "origin", &unknown, &dry).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+//! The `engram` Python module: [`engram_sdk::EngramSession`] for recording
+//! engrams, and `list`, `show` and `search` for reading them.
+//!
+//! ```python
+//! import engram
+//!
+//! session = engram.EngramSession.begin("my-agent", "gpt-4")
+//! session.log_message("user", "Add authentication to the API")
+//! session.log_file_change("src/auth.py", "created")
+//! engram_id = session.commit(summary="Add JWT auth")
+//!
+//! for manifest in engram.list(limit=5, agent="my-agent"):
+//!     print(manifest["id"], manifest["summary"])
+//! ```
+//!
+//! Engrams come back as plain dicts and lists, shaped like `engram show
+//! --format json`. Storage work runs with the GIL released, and failures
+//! raise `engram.EngramError`.
+
+mod session;
+
+use std::path::Path;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use serde::Serialize;
+
+use engram_core::error::CoreError;
+use engram_core::storage::{GitStorage, ListOptions};
+use engram_query::{SearchEngine, SearchOptions};
+
+create_exception!(
+    engram,
+    EngramError,
+    PyException,
+    "An engram operation failed; the message says why."
+);
+
+/// `e` as an [`EngramError`].
+pub(crate) fn engram_error(e: impl std::fmt::Display) -> PyErr {
+    EngramError::new_err(e.to_string())
+}
+
+/// The repository at `repo`, or the current directory's.
+pub(crate) fn open_storage(repo: Option<&str>) -> Result<GitStorage, CoreError> {
+    match repo {
+        Some(path) => GitStorage::open(Path::new(path)),
+        None => GitStorage::discover(),
+    }
+}
+
+/// `value` as plain Python dicts, lists and scalars, by way of its JSON.
+fn to_python(py: Python<'_>, value: &impl Serialize) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(engram_error)?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// Manifests of the newest engrams, newest first: at most `limit`, and
+/// only those whose agent name contains `agent`.
+#[pyfunction]
+#[pyo3(signature = (limit=None, agent=None, repo=None))]
+fn list(
+    py: Python<'_>,
+    limit: Option<usize>,
+    agent: Option<String>,
+    repo: Option<String>,
+) -> PyResult<PyObject> {
+    let manifests = py
+        .allow_threads(|| {
+            open_storage(repo.as_deref())?.list(&ListOptions {
+                limit,
+                agent_filter: agent,
+                ..Default::default()
+            })
+        })
+        .map_err(engram_error)?;
+    to_python(py, &manifests)
+}
+
+/// The engram with this ID or unique ID prefix: its manifest, intent,
+/// transcript, operations and lineage.
+#[pyfunction]
+#[pyo3(signature = (id, repo=None))]
+fn show(py: Python<'_>, id: String, repo: Option<String>) -> PyResult<PyObject> {
+    let data = py
+        .allow_threads(|| open_storage(repo.as_deref())?.read(&id))
+        .map_err(engram_error)?;
+    to_python(py, &data)
+}
+
+/// Engrams matching a search query (the syntax of `engram search`), best
+/// first, as dicts of `manifest`, `score` and `snippet`.
+#[pyfunction]
+#[pyo3(signature = (query, limit=10, repo=None))]
+fn search(py: Python<'_>, query: String, limit: usize, repo: Option<String>) -> PyResult<PyObject> {
+    let results = py
+        .allow_threads(|| {
+            let storage = open_storage(repo.as_deref()).map_err(|e| e.to_string())?;
+            let options = SearchOptions {
+                limit,
+                ..Default::default()
+            };
+            SearchEngine::open(&storage)
+                .and_then(|engine| engine.search(&storage, &query, &options))
+                .map(|page| {
+                    page.results
+                        .into_iter()
+                        .map(|r| {
+                            serde_json::json!({
+                                "manifest": r.manifest,
+                                "score": r.score,
+                                "snippet": r.snippet,
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .map_err(|e| e.to_string())
+        })
+        .map_err(engram_error)?;
+    to_python(py, &results)
+}
+
+#[pymodule]
+fn engram(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<session::Session>()?;
+    m.add("EngramError", m.py().get_type::<EngramError>())?;
+    m.add_function(wrap_pyfunction!(list, m)?)?;
+    m.add_function(wrap_pyfunction!(show, m)?)?;
+    m.add_function(wrap_pyfunction!(search, m)?)?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
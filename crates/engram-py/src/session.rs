@@ -0,0 +1,117 @@
+use pyo3::prelude::*;
+
+use crate::{engram_error, open_storage};
+
+/// A session being recorded; `engram.EngramSession` in Python. Logging
+/// methods return the session, so calls chain like the Rust SDK's.
+#[pyclass(name = "EngramSession", module = "engram")]
+pub struct Session {
+    /// `None` once committed.
+    inner: Option<engram_sdk::EngramSession>,
+}
+
+impl Session {
+    fn recording(&mut self) -> PyResult<&mut engram_sdk::EngramSession> {
+        self.inner
+            .as_mut()
+            .ok_or_else(|| engram_error("the session was already committed"))
+    }
+}
+
+#[pymethods]
+impl Session {
+    #[new]
+    #[pyo3(signature = (agent_name, model=None))]
+    fn new(agent_name: &str, model: Option<&str>) -> Self {
+        Self {
+            inner: Some(engram_sdk::EngramSession::begin(agent_name, model)),
+        }
+    }
+
+    /// Begin a session for an agent and, if known, its model.
+    #[staticmethod]
+    #[pyo3(signature = (agent_name, model=None))]
+    fn begin(agent_name: &str, model: Option<&str>) -> Self {
+        Self::new(agent_name, model)
+    }
+
+    /// Log a message from `user`, `assistant`, `system` or `tool`. The first
+    /// user message becomes the engram's request.
+    fn log_message<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        role: &str,
+        content: &str,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.recording()?.log_message(role, content);
+        Ok(slf)
+    }
+
+    /// Log a tool call. `input` is kept as JSON when it parses.
+    #[pyo3(signature = (tool_name, input, output_summary=None))]
+    fn log_tool_call<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        tool_name: &str,
+        input: &str,
+        output_summary: Option<&str>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.recording()?
+            .log_tool_call(tool_name, input, output_summary);
+        Ok(slf)
+    }
+
+    /// Log a changed file: `created`, `modified` or `deleted`.
+    fn log_file_change<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        path: &str,
+        change_type: &str,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.recording()?.log_file_change(path, change_type);
+        Ok(slf)
+    }
+
+    /// Log an approach that was tried and abandoned (a dead end).
+    fn log_rejection<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        approach: &str,
+        reason: &str,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.recording()?.log_rejection(approach, reason);
+        Ok(slf)
+    }
+
+    /// Add token usage, accumulating across calls.
+    #[pyo3(signature = (input_tokens, output_tokens, cost_usd=None))]
+    fn add_tokens<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        input_tokens: u64,
+        output_tokens: u64,
+        cost_usd: Option<f64>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.recording()?
+            .add_tokens(input_tokens, output_tokens, cost_usd);
+        Ok(slf)
+    }
+
+    /// Store the session as an engram in the repository at `repo` (default:
+    /// the current directory's) and return its ID. A failed commit can be
+    /// retried; after a successful one the session can't be used again.
+    #[pyo3(signature = (git_sha=None, summary=None, repo=None))]
+    fn commit(
+        &mut self,
+        py: Python<'_>,
+        git_sha: Option<String>,
+        summary: Option<String>,
+        repo: Option<String>,
+    ) -> PyResult<String> {
+        // Committed from a copy, so nothing is lost if storing fails
+        let session = self.recording()?.clone();
+        let id = py
+            .allow_threads(|| {
+                let storage = open_storage(repo.as_deref())?;
+                session.commit_to(&storage, git_sha.as_deref(), summary.as_deref())
+            })
+            .map_err(engram_error)?;
+        self.inner = None;
+        Ok(id.to_string())
+    }
+}
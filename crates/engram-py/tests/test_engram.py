@@ -0,0 +1,109 @@
+"""Tests for the native `engram` module. Build it into the active
+environment first:
+
+    cd crates/engram-py && maturin develop && pytest
+"""
+
+import subprocess
+from pathlib import Path
+
+import pytest
+
+import engram
+
+
+@pytest.fixture
+def repo(tmp_path: Path) -> str:
+    """An empty Git repository engram has not been initialized in."""
+    subprocess.run(["git", "init", "-q", str(tmp_path)], check=True)
+    return str(tmp_path)
+
+
+def record(repo: str, agent: str, request: str) -> str:
+    session = engram.EngramSession.begin(agent, "gpt-4")
+    session.log_message("user", request).log_message("assistant", "On it.")
+    return session.commit(repo=repo)
+
+
+def test_commit_and_show(repo: str) -> None:
+    session = engram.EngramSession.begin("py-agent", "gpt-4")
+    (
+        session.log_message("user", "Add auth to the API")
+        .log_tool_call("write_file", '{"path": "src/auth.py"}', "Created")
+        .log_file_change("src/auth.py", "created")
+        .log_rejection("Session auth", "Too stateful")
+        .add_tokens(1500, 800, 0.02)
+        .add_tokens(100, 50)
+    )
+    engram_id = session.commit(git_sha="abc123", summary="Add JWT auth", repo=repo)
+
+    data = engram.show(engram_id[:8], repo=repo)
+    assert isinstance(data, dict)
+    manifest = data["manifest"]
+    assert manifest["id"] == engram_id
+    assert manifest["agent"] == {"name": "py-agent", "model": "gpt-4"}
+    assert manifest["summary"] == "Add JWT auth"
+    assert manifest["token_usage"]["total_tokens"] == 2450
+    assert manifest["token_usage"]["cost_usd"] == pytest.approx(0.02)
+    assert data["intent"]["original_request"] == "Add auth to the API"
+    assert data["intent"]["dead_ends"] == [
+        {"approach": "Session auth", "reason": "Too stateful"}
+    ]
+    assert data["operations"]["tool_calls"][0]["input"] == {"path": "src/auth.py"}
+    assert data["operations"]["file_changes"][0]["path"] == "src/auth.py"
+    assert isinstance(data["transcript"], list)
+
+
+def test_committed_session_is_spent(repo: str) -> None:
+    session = engram.EngramSession("py-agent")
+    session.log_message("user", "Fix the build")
+    session.commit(repo=repo)
+
+    with pytest.raises(engram.EngramError, match="already committed"):
+        session.log_message("user", "More")
+    with pytest.raises(engram.EngramError, match="already committed"):
+        session.commit(repo=repo)
+
+
+def test_failed_commit_can_be_retried(repo: str, tmp_path: Path) -> None:
+    session = engram.EngramSession("py-agent")
+    session.log_message("user", "Fix the build")
+    with pytest.raises(engram.EngramError):
+        session.commit(repo=str(tmp_path / "missing"))
+
+    engram_id = session.commit(repo=repo)
+    assert engram.show(engram_id, repo=repo)["intent"]["original_request"] == (
+        "Fix the build"
+    )
+
+
+def test_list(repo: str) -> None:
+    assert engram.list(repo=repo) == []
+    record(repo, "claude-code", "Add caching")
+    record(repo, "cursor", "Fix login")
+    record(repo, "cursor", "Add metrics")
+
+    manifests = engram.list(repo=repo)
+    assert [m["agent"]["name"] for m in manifests].count("cursor") == 2
+    assert len(engram.list(limit=2, repo=repo)) == 2
+    cursor = engram.list(agent="cursor", repo=repo)
+    assert {m["summary"] for m in cursor} == {"Fix login", "Add metrics"}
+
+
+def test_search(repo: str) -> None:
+    record(repo, "claude-code", "Add response caching")
+    record(repo, "claude-code", "Fix the login redirect")
+
+    results = engram.search("caching", repo=repo)
+    assert len(results) == 1
+    assert results[0]["manifest"]["summary"] == "Add response caching"
+    assert results[0]["score"] > 0
+    assert engram.search("oauth", repo=repo) == []
+
+
+def test_errors_raise_engram_error(repo: str, tmp_path: Path) -> None:
+    assert issubclass(engram.EngramError, Exception)
+    with pytest.raises(engram.EngramError):
+        engram.show("0000", repo=repo)
+    with pytest.raises(engram.EngramError):
+        engram.list(repo=str(tmp_path / "missing"))
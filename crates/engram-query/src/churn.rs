@@ -0,0 +1,243 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use engram_core::model::{FileChange, FileChangeType, Manifest};
+use engram_core::storage::{GitStorage, ListOptions};
+
+use crate::error::QueryError;
+use crate::renames::Renames;
+
+/// How many recorded changes to a file were of each kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChangeCounts {
+    pub created: usize,
+    pub modified: usize,
+    pub deleted: usize,
+    pub renamed: usize,
+}
+
+/// How often engrams touched one file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChurn {
+    /// The file's latest name.
+    pub path: String,
+    /// Earlier names, from recorded renames, whose changes are counted here.
+    pub previous_paths: Vec<String>,
+    /// Number of engrams that touched the file under any of its names.
+    pub touches: usize,
+    pub changes: ChangeCounts,
+    /// Distinct agents that touched the file, sorted.
+    pub agents: Vec<String>,
+    /// Sum over changes that recorded line counts; `None` if none did.
+    pub lines_added: Option<u64>,
+    pub lines_removed: Option<u64>,
+    pub first_touched: DateTime<Utc>,
+    pub last_touched: DateTime<Utc>,
+}
+
+/// Per-file churn across the engrams `opts` selects (as for
+/// [`GitStorage::list`]), most-touched first. Renames recorded in file
+/// changes are followed, so a file's history under earlier names is merged
+/// into its latest name.
+pub fn file_churn(storage: &GitStorage, opts: &ListOptions) -> Result<Vec<FileChurn>, QueryError> {
+    let mut engrams = Vec::new();
+    for manifest in storage.list(opts)? {
+        match storage.read(manifest.id.as_str()) {
+            Ok(data) => engrams.push((manifest, data.operations.file_changes)),
+            Err(e) => tracing::warn!("Failed to read engram {}: {e}", manifest.id),
+        }
+    }
+    // Oldest first, so later renames of a name win
+    engrams.sort_by_key(|(m, _)| m.created_at);
+
+    let mut renames = Renames::default();
+    for (_, changes) in &engrams {
+        renames.record(changes);
+    }
+
+    let mut files: HashMap<String, Churn> = HashMap::new();
+    for (manifest, changes) in &engrams {
+        let mut touched = HashSet::new();
+        for fc in changes {
+            let path = renames.latest_name(&fc.path);
+            let churn = files
+                .entry(path.clone())
+                .or_insert_with(|| Churn::new(manifest));
+            churn.record_change(fc, &path);
+            if touched.insert(path) {
+                churn.record_touch(manifest);
+            }
+        }
+    }
+
+    let mut churn: Vec<FileChurn> = files
+        .into_iter()
+        .map(|(path, churn)| churn.finish(path))
+        .collect();
+    churn.sort_by(|a, b| {
+        b.touches
+            .cmp(&a.touches)
+            .then_with(|| b.last_touched.cmp(&a.last_touched))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(churn)
+}
+
+/// A [`FileChurn`] being accumulated.
+struct Churn {
+    previous_paths: BTreeSet<String>,
+    touches: usize,
+    changes: ChangeCounts,
+    agents: BTreeSet<String>,
+    lines_added: Option<u64>,
+    lines_removed: Option<u64>,
+    first_touched: DateTime<Utc>,
+    last_touched: DateTime<Utc>,
+}
+
+impl Churn {
+    fn new(manifest: &Manifest) -> Self {
+        Self {
+            previous_paths: BTreeSet::new(),
+            touches: 0,
+            changes: ChangeCounts::default(),
+            agents: BTreeSet::new(),
+            lines_added: None,
+            lines_removed: None,
+            first_touched: manifest.created_at,
+            last_touched: manifest.created_at,
+        }
+    }
+
+    fn record_change(&mut self, fc: &FileChange, latest: &str) {
+        if fc.path != latest {
+            self.previous_paths.insert(fc.path.clone());
+        }
+        match &fc.change_type {
+            FileChangeType::Created => self.changes.created += 1,
+            FileChangeType::Modified => self.changes.modified += 1,
+            FileChangeType::Deleted => self.changes.deleted += 1,
+            FileChangeType::Renamed { .. } => self.changes.renamed += 1,
+        }
+        if let Some(n) = fc.lines_added {
+            *self.lines_added.get_or_insert(0) += u64::from(n);
+        }
+        if let Some(n) = fc.lines_removed {
+            *self.lines_removed.get_or_insert(0) += u64::from(n);
+        }
+    }
+
+    fn record_touch(&mut self, manifest: &Manifest) {
+        self.touches += 1;
+        self.agents.insert(manifest.agent.name.clone());
+        self.first_touched = self.first_touched.min(manifest.created_at);
+        self.last_touched = self.last_touched.max(manifest.created_at);
+    }
+
+    fn finish(self, path: String) -> FileChurn {
+        FileChurn {
+            path,
+            previous_paths: self.previous_paths.into_iter().collect(),
+            touches: self.touches,
+            changes: self.changes,
+            agents: self.agents.into_iter().collect(),
+            lines_added: self.lines_added,
+            lines_removed: self.lines_removed,
+            first_touched: self.first_touched,
+            last_touched: self.last_touched,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(agent: &str, changes: Vec<FileChange>) -> EngramData {
+        let mut data = EngramData::builder().agent(agent).build();
+        data.operations.file_changes = changes;
+        data
+    }
+
+    fn change(path: &str, change_type: FileChangeType, lines: Option<(u32, u32)>) -> FileChange {
+        FileChange {
+            path: path.into(),
+            change_type,
+            lines_added: lines.map(|(added, _)| added),
+            lines_removed: lines.map(|(_, removed)| removed),
+        }
+    }
+
+    #[test]
+    fn test_hot_file_ranks_first_across_renames() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let modified = FileChangeType::Modified;
+        let corpus = [
+            engram(
+                "aider",
+                vec![
+                    change("src/login.rs", FileChangeType::Created, Some((40, 0))),
+                    change("README.md", modified.clone(), None),
+                ],
+            ),
+            engram(
+                "claude-code",
+                vec![change("src/login.rs", modified.clone(), Some((5, 3)))],
+            ),
+            engram(
+                "claude-code",
+                vec![change(
+                    "src/auth.rs",
+                    FileChangeType::Renamed {
+                        from: "src/login.rs".into(),
+                    },
+                    None,
+                )],
+            ),
+            engram(
+                "claude-code",
+                vec![
+                    change("src/auth.rs", modified.clone(), Some((10, 2))),
+                    change("Cargo.toml", modified.clone(), None),
+                ],
+            ),
+        ];
+        let mut times = Vec::new();
+        for (i, mut data) in corpus.into_iter().enumerate() {
+            data.manifest.created_at += chrono::Duration::minutes(i as i64);
+            times.push(data.manifest.created_at);
+            storage.create(&data).unwrap();
+        }
+
+        let churn = file_churn(&storage, &ListOptions::default()).unwrap();
+        let paths: Vec<_> = churn.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["src/auth.rs", "Cargo.toml", "README.md"]);
+
+        let hot = &churn[0];
+        assert_eq!(hot.previous_paths, ["src/login.rs"]);
+        assert_eq!(hot.touches, 4);
+        assert_eq!(
+            hot.changes,
+            ChangeCounts {
+                created: 1,
+                modified: 2,
+                deleted: 0,
+                renamed: 1,
+            }
+        );
+        assert_eq!(hot.agents, ["aider", "claude-code"]);
+        assert_eq!((hot.lines_added, hot.lines_removed), (Some(55), Some(5)));
+        assert_eq!((hot.first_touched, hot.last_touched), (times[0], times[3]));
+
+        assert_eq!(churn[2].lines_added, None);
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use engram_core::model::{EngramData, EngramId};
+use engram_core::storage::GitStorage;
+
+use crate::error::QueryError;
+
+/// Usage of one engram in an [`EngramComparison`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparedEngram {
+    pub id: EngramId,
+    pub agent: String,
+    pub model: Option<String>,
+    pub total_tokens: u64,
+    pub cost_usd: Option<f64>,
+    /// Seconds from start to finish, for sessions with a recorded end.
+    pub duration_secs: Option<i64>,
+    pub files_touched: usize,
+}
+
+/// Which of the compared engrams have an item (a file or a dead end).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Presence {
+    pub name: String,
+    /// One flag per engram, in the order they were compared.
+    pub present: Vec<bool>,
+}
+
+impl Presence {
+    /// Whether every engram has the item.
+    pub fn is_shared(&self) -> bool {
+        self.present.iter().all(|p| *p)
+    }
+
+    /// The index of the only engram with the item, if just one has it.
+    pub fn unique_to(&self) -> Option<usize> {
+        let mut holders = self.present.iter().enumerate().filter(|(_, p)| **p);
+        match (holders.next(), holders.next()) {
+            (Some((i, _)), None) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+/// Side-by-side view of several engrams, e.g. runs of one task by different
+/// models.
+#[derive(Debug, Clone, Serialize)]
+pub struct EngramComparison {
+    pub engrams: Vec<ComparedEngram>,
+    /// Every file any engram changed, most widely shared first.
+    pub files: Vec<Presence>,
+    /// Every dead end approach, matched case-insensitively and named as first
+    /// written, most widely shared first.
+    pub dead_ends: Vec<Presence>,
+    /// Index of the engram that touched the most files; the first on a tie,
+    /// `None` if none touched any.
+    pub most_files: Option<usize>,
+}
+
+/// Compare any number of engrams.
+pub fn compare_engrams(
+    storage: &GitStorage,
+    ids: &[EngramId],
+) -> Result<EngramComparison, QueryError> {
+    let data = ids
+        .iter()
+        .map(|id| storage.read(id.as_str()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(compute_comparison(&data))
+}
+
+fn compute_comparison(data: &[EngramData]) -> EngramComparison {
+    let mut files = PresenceMatrix::new(data.len());
+    let mut dead_ends = PresenceMatrix::new(data.len());
+    let mut engrams = Vec::with_capacity(data.len());
+
+    for (i, d) in data.iter().enumerate() {
+        for fc in &d.operations.file_changes {
+            files.mark(&fc.path, fc.path.clone(), i);
+        }
+        for de in &d.intent.dead_ends {
+            let key = de
+                .approach
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase();
+            dead_ends.mark(&key, de.approach.trim().to_string(), i);
+        }
+
+        let m = &d.manifest;
+        engrams.push(ComparedEngram {
+            id: m.id.clone(),
+            agent: m.agent.name.clone(),
+            model: m.agent.model.clone(),
+            total_tokens: m.token_usage.total_tokens,
+            cost_usd: m.token_usage.cost_usd,
+            duration_secs: m.finished_at.map(|end| (end - m.created_at).num_seconds()),
+            files_touched: 0,
+        });
+    }
+
+    let files = files.finish();
+    for (i, engram) in engrams.iter_mut().enumerate() {
+        engram.files_touched = files.iter().filter(|f| f.present[i]).count();
+    }
+    let most_files = engrams
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.files_touched > 0)
+        .max_by(|(i, a), (j, b)| a.files_touched.cmp(&b.files_touched).then(j.cmp(i)))
+        .map(|(i, _)| i);
+
+    EngramComparison {
+        engrams,
+        files,
+        dead_ends: dead_ends.finish(),
+        most_files,
+    }
+}
+
+/// [`Presence`] rows being collected, keyed by a normalized name.
+struct PresenceMatrix {
+    width: usize,
+    rows: Vec<Presence>,
+    index: HashMap<String, usize>,
+}
+
+impl PresenceMatrix {
+    fn new(width: usize) -> Self {
+        Self {
+            width,
+            rows: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    fn mark(&mut self, key: &str, name: String, engram: usize) {
+        let row = *self.index.entry(key.to_string()).or_insert_with(|| {
+            self.rows.push(Presence {
+                name,
+                present: vec![false; self.width],
+            });
+            self.rows.len() - 1
+        });
+        self.rows[row].present[engram] = true;
+    }
+
+    fn finish(mut self) -> Vec<Presence> {
+        self.rows.sort_by(|a, b| {
+            let count = |p: &Presence| p.present.iter().filter(|p| **p).count();
+            count(b).cmp(&count(a)).then_with(|| a.name.cmp(&b.name))
+        });
+        self.rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engram(model: &str, files: &[&str], dead_ends: &[&str]) -> EngramData {
+        let builder = EngramData::builder().model(model).request("add caching");
+        let builder = files.iter().fold(builder, |b, path| b.file(path));
+        dead_ends
+            .iter()
+            .fold(builder, |b, approach| b.dead_end(approach, "too slow"))
+            .build()
+    }
+
+    fn present(rows: &[Presence]) -> Vec<(&str, Vec<bool>)> {
+        rows.iter()
+            .map(|p| (p.name.as_str(), p.present.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_three_engrams() {
+        let mut a = engram("opus", &["src/cache.rs", "src/lib.rs"], &["LRU crate"]);
+        a.manifest.finished_at = Some(a.manifest.created_at + chrono::Duration::seconds(90));
+        let b = engram(
+            "sonnet",
+            &["src/cache.rs", "src/lib.rs", "tests/cache.rs"],
+            &["lru  crate", "Redis"],
+        );
+        let c = engram("haiku", &["src/cache.rs", "README.md"], &[]);
+
+        let cmp = compute_comparison(&[a, b, c]);
+        assert_eq!(
+            present(&cmp.files),
+            [
+                ("src/cache.rs", vec![true, true, true]),
+                ("src/lib.rs", vec![true, true, false]),
+                ("README.md", vec![false, false, true]),
+                ("tests/cache.rs", vec![false, true, false]),
+            ]
+        );
+        assert!(cmp.files[0].is_shared());
+        assert_eq!(cmp.files[1].unique_to(), None);
+        assert_eq!(cmp.files[2].unique_to(), Some(2));
+
+        assert_eq!(
+            present(&cmp.dead_ends),
+            [
+                ("LRU crate", vec![true, true, false]),
+                ("Redis", vec![false, true, false]),
+            ]
+        );
+
+        let touched: Vec<_> = cmp.engrams.iter().map(|e| e.files_touched).collect();
+        assert_eq!(touched, [2, 3, 2]);
+        assert_eq!(cmp.most_files, Some(1));
+        assert_eq!(cmp.engrams[0].duration_secs, Some(90));
+        assert_eq!(cmp.engrams[1].duration_secs, None);
+    }
+
+    #[test]
+    fn test_compare_two_engrams() {
+        let a = engram("opus", &["src/lib.rs"], &[]);
+        let b = engram("sonnet", &["src/lib.rs"], &[]);
+        let cmp = compute_comparison(&[a, b]);
+        assert_eq!(present(&cmp.files), [("src/lib.rs", vec![true, true])]);
+        // A tie goes to the first engram
+        assert_eq!(cmp.most_files, Some(0));
+        assert!(cmp.dead_ends.is_empty());
+    }
+}
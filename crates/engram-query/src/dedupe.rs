@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use engram_core::model::Manifest;
+use engram_core::storage::{EngramPart, GitStorage, ListOptions};
+
+use crate::error::QueryError;
+
+/// How far apart two engrams with the same agent and request may start and
+/// still count as the same session recorded twice.
+pub const DUPLICATE_TOLERANCE_MINUTES: i64 = 10;
+
+/// Why the engrams in a [`DuplicateGroup`] are taken for copies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    /// Imported from the same session file.
+    SourceHash,
+    /// Same agent and request, started within [`DUPLICATE_TOLERANCE_MINUTES`].
+    SameRequest,
+}
+
+/// One engram in a [`DuplicateGroup`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateEngram {
+    pub manifest: Manifest,
+    pub transcript_entries: usize,
+}
+
+/// Engrams recording the same session.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub reason: DuplicateReason,
+    /// Richest first (most transcript entries, then oldest): the one to keep.
+    pub engrams: Vec<DuplicateEngram>,
+}
+
+impl DuplicateGroup {
+    /// The engram to keep.
+    pub fn keep(&self) -> &DuplicateEngram {
+        &self.engrams[0]
+    }
+
+    /// The engrams the kept one makes redundant.
+    pub fn redundant(&self) -> &[DuplicateEngram] {
+        &self.engrams[1..]
+    }
+}
+
+/// Groups of engrams that record the same session: those sharing a source
+/// hash, and those by the same agent for the same request started within
+/// [`DUPLICATE_TOLERANCE_MINUTES`] of one another. Groups are ordered by
+/// their oldest engram.
+pub fn find_duplicates(storage: &GitStorage) -> Result<Vec<DuplicateGroup>, QueryError> {
+    let mut manifests = storage.list(&ListOptions::default())?;
+    manifests.sort_by_key(|m| m.created_at);
+
+    let mut groups = Groups::new(manifests.len());
+    let mut by_source: HashMap<&str, usize> = HashMap::new();
+    for (i, m) in manifests.iter().enumerate() {
+        if let Some(hash) = m.source_hash.as_deref() {
+            match by_source.get(hash) {
+                Some(&first) => groups.join(first, i),
+                None => {
+                    by_source.insert(hash, i);
+                }
+            }
+        }
+    }
+
+    // Oldest first, so each engram is compared with the one just before it
+    let tolerance = Duration::minutes(DUPLICATE_TOLERANCE_MINUTES);
+    let mut last_by_request: HashMap<(String, String), usize> = HashMap::new();
+    for (i, m) in manifests.iter().enumerate() {
+        let request = match storage.read_intent(m.id.as_str()) {
+            Ok(intent) => intent.original_request,
+            Err(e) => {
+                tracing::warn!("Failed to read engram {}: {e}", m.id);
+                continue;
+            }
+        };
+        // Engrams without a request say nothing about being the same session
+        if request.trim().is_empty() {
+            continue;
+        }
+        let key = (m.agent.name.clone(), request_hash(&request));
+        if let Some(&previous) = last_by_request.get(&key) {
+            if m.created_at - manifests[previous].created_at <= tolerance {
+                groups.join(previous, i);
+            }
+        }
+        last_by_request.insert(key, i);
+    }
+
+    let mut found = Vec::new();
+    for members in groups.into_members() {
+        if members.len() < 2 {
+            continue;
+        }
+        let first_hash = manifests[members[0]].source_hash.as_deref();
+        let reason = if first_hash.is_some()
+            && members
+                .iter()
+                .all(|&i| manifests[i].source_hash.as_deref() == first_hash)
+        {
+            DuplicateReason::SourceHash
+        } else {
+            DuplicateReason::SameRequest
+        };
+        let mut engrams: Vec<_> = members
+            .iter()
+            .map(|&i| DuplicateEngram {
+                transcript_entries: transcript_entries(storage, &manifests[i]),
+                manifest: manifests[i].clone(),
+            })
+            .collect();
+        // Stable, so among equally rich engrams the oldest comes first
+        engrams.sort_by_key(|e| std::cmp::Reverse(e.transcript_entries));
+        found.push((members[0], DuplicateGroup { reason, engrams }));
+    }
+    found.sort_by_key(|(oldest, _)| *oldest);
+    Ok(found.into_iter().map(|(_, group)| group).collect())
+}
+
+/// A request's hash, ignoring surrounding whitespace.
+fn request_hash(request: &str) -> String {
+    format!("{:x}", Sha256::digest(request.trim().as_bytes()))
+}
+
+/// How many entries the engram's transcript has, counted without parsing.
+fn transcript_entries(storage: &GitStorage, manifest: &Manifest) -> usize {
+    match storage.read_part(manifest.id.as_str(), EngramPart::Transcript) {
+        Ok(bytes) => bytes
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+            .count(),
+        Err(e) => {
+            tracing::warn!("Failed to read the transcript of {}: {e}", manifest.id);
+            0
+        }
+    }
+}
+
+/// Disjoint sets of indices, joined pairwise.
+struct Groups {
+    parent: Vec<usize>,
+}
+
+impl Groups {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn root(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn join(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.root(a), self.root(b));
+        // The smaller index roots the set, so members stay in order
+        self.parent[a.max(b)] = a.min(b);
+    }
+
+    /// Every set's members in ascending order, by their smallest member.
+    fn into_members(mut self) -> Vec<Vec<usize>> {
+        let mut sets: Vec<Vec<usize>> = vec![Vec::new(); self.parent.len()];
+        for i in 0..self.parent.len() {
+            let root = self.root(i);
+            sets[root].push(i);
+        }
+        sets.retain(|set| !set.is_empty());
+        sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(agent: &str, request: &str, minutes: i64, entries: usize) -> EngramData {
+        let created_at = chrono::DateTime::parse_from_rfc3339("2026-03-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+            + Duration::minutes(minutes);
+        let builder = EngramData::builder()
+            .agent(agent)
+            .created_at(created_at)
+            .request(request)
+            .summary(&format!("{request} ({entries})"));
+        (0..entries)
+            .fold(builder, |b, _| b.message(Role::User, request))
+            .build()
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let mut imported = engram("claude-code", "Add OAuth", 0, 2);
+        imported.manifest.source_hash = Some("abc".into());
+        let mut reimported = engram("claude-code", "Add OAuth", 300, 5);
+        reimported.manifest.source_hash = Some("abc".into());
+        let engrams = [
+            imported,
+            reimported,
+            // Recorded alongside the import: same request, minutes apart
+            engram("claude-code", "Add OAuth ", 4, 3),
+            // Near misses: another agent, and the same request a day later
+            engram("cursor", "Add OAuth", 1, 9),
+            engram("claude-code", "Add OAuth", 24 * 60, 9),
+            engram("claude-code", "Fix the cache", 30, 1),
+            engram("claude-code", "Fix the cache", 35, 1),
+            engram("claude-code", "", 0, 1),
+            engram("claude-code", "", 1, 1),
+        ];
+        for data in &engrams {
+            storage.create(data).unwrap();
+        }
+
+        let groups = find_duplicates(&storage).unwrap();
+        let found: Vec<_> = groups
+            .iter()
+            .map(|g| {
+                let summaries: Vec<_> = g
+                    .engrams
+                    .iter()
+                    .map(|e| e.manifest.summary.clone().unwrap())
+                    .collect();
+                (g.reason.clone(), summaries)
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    DuplicateReason::SameRequest,
+                    vec![
+                        "Add OAuth (5)".to_string(),
+                        "Add OAuth  (3)".to_string(),
+                        "Add OAuth (2)".to_string(),
+                    ]
+                ),
+                (
+                    DuplicateReason::SameRequest,
+                    vec![
+                        "Fix the cache (1)".to_string(),
+                        "Fix the cache (1)".to_string(),
+                    ]
+                ),
+            ]
+        );
+        assert_eq!(groups[0].keep().manifest.id, engrams[1].manifest.id);
+        assert_eq!(groups[0].redundant().len(), 2);
+        // Equally rich, the oldest is kept
+        assert_eq!(groups[1].keep().manifest.id, engrams[5].manifest.id);
+    }
+
+    #[test]
+    fn test_find_duplicates_by_source_hash() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let mut first = engram("claude-code", "Add OAuth", 0, 2);
+        first.manifest.source_hash = Some("abc".into());
+        let mut second = engram("claude-code", "Add OAuth, again", 600, 2);
+        second.manifest.source_hash = Some("abc".into());
+        let mut other = engram("claude-code", "Add OAuth", 1200, 2);
+        other.manifest.source_hash = Some("def".into());
+        for data in [&first, &second, &other] {
+            storage.create(data).unwrap();
+        }
+
+        let groups = find_duplicates(&storage).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].reason, DuplicateReason::SourceHash);
+        assert_eq!(groups[0].keep().manifest.id, first.manifest.id);
+        assert_eq!(groups[0].redundant()[0].manifest.id, second.manifest.id);
+    }
+}
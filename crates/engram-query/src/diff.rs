@@ -1,6 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
-use engram_core::model::{EngramData, EngramId};
+use engram_core::model::{DeadEnd, Decision, EngramData, EngramId};
 use engram_core::storage::GitStorage;
 
 use crate::error::QueryError;
@@ -15,6 +15,21 @@ pub struct EngramDiff {
     pub only_b_files: Vec<String>,
     pub token_delta: i64,
     pub cost_delta: Option<f64>,
+    /// Dead ends whose approach (compared case-insensitively) appears in only
+    /// one of the engrams.
+    pub dead_ends_only_a: Vec<DeadEnd>,
+    pub dead_ends_only_b: Vec<DeadEnd>,
+    /// Decisions in only one of the engrams; a decision whose rationale
+    /// changed appears in both lists.
+    pub decisions_only_a: Vec<Decision>,
+    pub decisions_only_b: Vec<Decision>,
+    /// The interpreted goals (falling back to the original request) of A and
+    /// B, when they differ.
+    pub intent_changed: Option<(String, String)>,
+    /// Number of calls to each tool in A and in B.
+    pub tool_call_counts: BTreeMap<String, (usize, usize)>,
+    /// B's transcript entries minus A's.
+    pub transcript_entry_delta: i64,
 }
 
 /// Compare two engrams.
@@ -72,6 +87,21 @@ fn compute_diff(
         _ => None,
     };
 
+    let dead_end_key = |d: &DeadEnd| normalize(&d.approach);
+    let decision_key = |d: &Decision| (normalize(&d.description), normalize(&d.rationale));
+
+    let goal_a = goal(data_a);
+    let goal_b = goal(data_b);
+    let intent_changed = (goal_a != goal_b).then(|| (goal_a.to_string(), goal_b.to_string()));
+
+    let mut tool_call_counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for tc in &data_a.operations.tool_calls {
+        tool_call_counts.entry(tc.tool_name.clone()).or_default().0 += 1;
+    }
+    for tc in &data_b.operations.tool_calls {
+        tool_call_counts.entry(tc.tool_name.clone()).or_default().1 += 1;
+    }
+
     Ok(EngramDiff {
         id_a: id_a.clone(),
         id_b: id_b.clone(),
@@ -80,9 +110,63 @@ fn compute_diff(
         only_b_files: only_b,
         token_delta,
         cost_delta,
+        dead_ends_only_a: only_in(
+            &data_a.intent.dead_ends,
+            &data_b.intent.dead_ends,
+            dead_end_key,
+        ),
+        dead_ends_only_b: only_in(
+            &data_b.intent.dead_ends,
+            &data_a.intent.dead_ends,
+            dead_end_key,
+        ),
+        decisions_only_a: only_in(
+            &data_a.intent.decisions,
+            &data_b.intent.decisions,
+            decision_key,
+        ),
+        decisions_only_b: only_in(
+            &data_b.intent.decisions,
+            &data_a.intent.decisions,
+            decision_key,
+        ),
+        intent_changed,
+        tool_call_counts,
+        transcript_entry_delta: data_b.transcript.entries.len() as i64
+            - data_a.transcript.entries.len() as i64,
     })
 }
 
+/// Items of `items` whose key matches no item of `other`, in order.
+fn only_in<T: Clone, K: Eq + std::hash::Hash>(
+    items: &[T],
+    other: &[T],
+    key: impl Fn(&T) -> K,
+) -> Vec<T> {
+    let other: HashSet<K> = other.iter().map(&key).collect();
+    items
+        .iter()
+        .filter(|item| !other.contains(&key(item)))
+        .cloned()
+        .collect()
+}
+
+/// Lowercased with whitespace runs collapsed, for comparing free text.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn goal(data: &EngramData) -> &str {
+    data.intent
+        .interpreted_goal
+        .as_deref()
+        .unwrap_or(&data.intent.original_request)
+        .trim()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,6 +194,7 @@ mod tests {
                 tags: Vec::new(),
                 capture_mode: CaptureMode::Import,
                 source_hash: None,
+                source_path: None,
             },
             intent: Intent {
                 original_request: "test".into(),
@@ -135,6 +220,7 @@ mod tests {
                 shell_commands: Vec::new(),
             },
             lineage: Lineage::default(),
+            attachments: Vec::new(),
         }
     }
 
@@ -153,5 +239,82 @@ mod tests {
         assert_eq!(diff.token_delta, 1000);
         let cost = diff.cost_delta.unwrap();
         assert!((cost - 0.02).abs() < 1e-10, "cost_delta was {cost}");
+        assert!(diff.dead_ends_only_a.is_empty() && diff.dead_ends_only_b.is_empty());
+        assert_eq!(diff.intent_changed, None);
+        assert_eq!(diff.transcript_entry_delta, 0);
+    }
+
+    fn dead_end(approach: &str) -> DeadEnd {
+        DeadEnd {
+            approach: approach.into(),
+            reason: "did not work".into(),
+        }
+    }
+
+    fn tool_call(name: &str) -> ToolCall {
+        ToolCall {
+            timestamp: chrono::Utc::now(),
+            tool_name: name.into(),
+            input: serde_json::Value::Null,
+            output_summary: None,
+            duration_ms: None,
+            is_error: false,
+        }
+    }
+
+    #[test]
+    fn test_diff_overlapping_reasoning() {
+        let mut data_a = make_test_data(&[], 0, None);
+        data_a.intent.dead_ends = vec![dead_end("Use a mutex"), dead_end("Retry forever")];
+        data_a.intent.decisions = vec![Decision {
+            description: "Use channels".into(),
+            rationale: "No shared state".into(),
+        }];
+        data_a.intent.interpreted_goal = Some("Fix the race".into());
+        data_a.operations.tool_calls = vec![tool_call("Bash"), tool_call("Edit")];
+
+        let mut data_b = make_test_data(&[], 0, None);
+        // Same approach, different case and spacing: not a difference
+        data_b.intent.dead_ends = vec![dead_end("use  a MUTEX"), dead_end("Add a sleep")];
+        data_b.intent.decisions = vec![Decision {
+            description: "Use channels".into(),
+            rationale: "Simpler to test".into(),
+        }];
+        data_b.intent.interpreted_goal = Some("Remove the race".into());
+        data_b.operations.tool_calls = vec![tool_call("Bash"), tool_call("Bash")];
+        data_b.transcript.entries = vec![TranscriptEntry {
+            timestamp: chrono::Utc::now(),
+            role: Role::User,
+            content: TranscriptContent::Text {
+                text: "again".into(),
+            },
+            token_count: None,
+        }];
+
+        let diff = compute_diff(&EngramId::new(), &EngramId::new(), &data_a, &data_b).unwrap();
+        assert_eq!(diff.dead_ends_only_a, [dead_end("Retry forever")]);
+        assert_eq!(diff.dead_ends_only_b, [dead_end("Add a sleep")]);
+        // A changed rationale shows up on both sides
+        assert_eq!(diff.decisions_only_a.len(), 1);
+        assert_eq!(diff.decisions_only_b[0].rationale, "Simpler to test");
+        assert_eq!(
+            diff.intent_changed,
+            Some(("Fix the race".into(), "Remove the race".into()))
+        );
+        assert_eq!(diff.tool_call_counts["Bash"], (1, 2));
+        assert_eq!(diff.tool_call_counts["Edit"], (1, 0));
+        assert_eq!(diff.transcript_entry_delta, 1);
+    }
+
+    #[test]
+    fn test_diff_disjoint_dead_ends() {
+        let mut data_a = make_test_data(&[], 0, None);
+        data_a.intent.dead_ends = vec![dead_end("Cache in memory")];
+        let mut data_b = make_test_data(&[], 0, None);
+        data_b.intent.dead_ends = vec![dead_end("Cache in Redis"), dead_end("Skip caching")];
+
+        let diff = compute_diff(&EngramId::new(), &EngramId::new(), &data_a, &data_b).unwrap();
+        assert_eq!(diff.dead_ends_only_a, data_a.intent.dead_ends);
+        assert_eq!(diff.dead_ends_only_b, data_b.intent.dead_ends);
     }
 }
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use engram_core::error::CoreError;
 
 #[derive(Debug, thiserror::Error)]
@@ -8,6 +10,36 @@ pub enum QueryError {
     #[error("Search error: {0}")]
     Search(String),
 
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+
+    #[error(
+        "Search index was built with schema version {found} (current: {current}); \
+         run `engram reindex` to rebuild it"
+    )]
+    OutdatedIndex { found: u32, current: u32 },
+
+    #[error(
+        "Search index at {0} is missing or outdated and its directory is not writable; \
+         point ENGRAM_INDEX_DIR or engram.indexPath at a writable location"
+    )]
+    ReadOnlyIndex(PathBuf),
+
+    #[error(
+        "Search index at {path} can't be opened ({1}); run `engram reindex` to rebuild it",
+        path = .0.display()
+    )]
+    CorruptIndex(PathBuf, String),
+
+    #[error(
+        "Search index at {path} is locked by another engram process",
+        path = .0.display()
+    )]
+    IndexLocked(PathBuf),
+
+    #[error("Config error: {0}")]
+    Config(String),
+
     #[error("Core error: {0}")]
     Core(#[from] CoreError),
 
@@ -0,0 +1,144 @@
+use serde::Serialize;
+
+use engram_core::model::{EngramData, Manifest};
+use engram_core::storage::{GitStorage, ListOptions};
+
+use crate::error::QueryError;
+
+/// A shell command that exited non-zero.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailedCommand {
+    pub command: String,
+    pub exit_code: i32,
+}
+
+/// What went wrong in one engram's session.
+#[derive(Debug, Clone, Serialize)]
+pub struct FailureSummary {
+    pub manifest: Manifest,
+    /// Number of tool calls whose result was an error.
+    pub error_tool_calls: usize,
+    pub failed_commands: Vec<FailedCommand>,
+}
+
+impl FailureSummary {
+    /// The failures in `data`, or `None` if its session had none.
+    pub fn of(data: &EngramData) -> Option<Self> {
+        let ops = &data.operations;
+        let error_tool_calls = ops.tool_calls.iter().filter(|tc| tc.is_error).count();
+        let failed_commands: Vec<_> = ops
+            .shell_commands
+            .iter()
+            .filter_map(|sc| {
+                let exit_code = sc.exit_code.filter(|code| *code != 0)?;
+                Some(FailedCommand {
+                    command: sc.command.trim().to_string(),
+                    exit_code,
+                })
+            })
+            .collect();
+        if error_tool_calls == 0 && failed_commands.is_empty() {
+            return None;
+        }
+        Some(Self {
+            manifest: data.manifest.clone(),
+            error_tool_calls,
+            failed_commands,
+        })
+    }
+}
+
+/// Engrams whose sessions had failed tool calls or non-zero shell exits, most
+/// recent first. `opts` filters as for [`GitStorage::list`], except that its
+/// limit counts failing engrams rather than all engrams.
+pub fn find_failures(
+    storage: &GitStorage,
+    opts: &ListOptions,
+) -> Result<Vec<FailureSummary>, QueryError> {
+    let all = ListOptions {
+        limit: None,
+        ..opts.clone()
+    };
+    let mut failures = Vec::new();
+    for manifest in storage.list(&all)? {
+        if opts.limit.is_some_and(|limit| failures.len() >= limit) {
+            break;
+        }
+        match storage.read(manifest.id.as_str()) {
+            Ok(data) => failures.extend(FailureSummary::of(&data)),
+            Err(e) => tracing::warn!("Failed to read engram {}: {e}", manifest.id),
+        }
+    }
+    Ok(failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(request: &str, tool_errors: &[bool], exit_codes: &[Option<i32>]) -> EngramData {
+        let builder = EngramData::builder().request(request).summary(request);
+        let builder = tool_errors
+            .iter()
+            .fold(builder, |b, &is_error| b.tool_call("Bash", is_error));
+        exit_codes
+            .iter()
+            .fold(builder, |b, &exit_code| {
+                b.shell_command("cargo test ", exit_code)
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_find_failures() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let steps = [
+            engram("clean", &[false, false], &[Some(0), None]),
+            engram("tool errors", &[true, false, true], &[Some(0)]),
+            engram("tests failed", &[], &[Some(0), Some(101)]),
+        ];
+        for (i, mut data) in steps.into_iter().enumerate() {
+            data.manifest.created_at += chrono::Duration::minutes(i as i64);
+            storage.create(&data).unwrap();
+        }
+
+        let failures = find_failures(&storage, &ListOptions::default()).unwrap();
+        let found: Vec<_> = failures
+            .iter()
+            .map(|f| {
+                (
+                    f.manifest.summary.as_deref().unwrap(),
+                    f.error_tool_calls,
+                    f.failed_commands.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "tests failed",
+                    0,
+                    vec![FailedCommand {
+                        command: "cargo test".into(),
+                        exit_code: 101,
+                    }]
+                ),
+                ("tool errors", 2, Vec::new()),
+            ]
+        );
+
+        // The limit counts failing engrams only
+        let opts = ListOptions {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(find_failures(&storage, &opts).unwrap().len(), 1);
+    }
+}
@@ -1,14 +1,32 @@
 use std::collections::HashSet;
 
+use engram_core::model::{EngramId, RelationType};
 use engram_core::storage::GitStorage;
 
 use super::model::*;
 use crate::error::QueryError;
 
+/// Options for [`build_graph_with`].
+#[derive(Debug, Clone, Default)]
+pub struct GraphOptions {
+    /// Add a placeholder node for each engram that lineage refers to but is
+    /// not in local storage, instead of dropping the edge.
+    pub show_missing: bool,
+}
+
 /// Build a context graph from all engrams in storage.
 pub fn build_graph(storage: &GitStorage) -> Result<ContextGraph, QueryError> {
+    build_graph_with(storage, &GraphOptions::default())
+}
+
+/// Build a context graph from all engrams in storage, with `options`.
+pub fn build_graph_with(
+    storage: &GitStorage,
+    options: &GraphOptions,
+) -> Result<ContextGraph, QueryError> {
     let manifests = storage.list(&Default::default())?;
     let mut graph = ContextGraph::default();
+    let mut lineage = Vec::new();
     let mut seen_agents = HashSet::new();
     let mut seen_files = HashSet::new();
     let mut seen_commits = HashSet::new();
@@ -32,6 +50,8 @@ pub fn build_graph(storage: &GitStorage) -> Result<ContextGraph, QueryError> {
                 .summary
                 .clone()
                 .unwrap_or_else(|| manifest.id.as_str()[..8].to_string()),
+            created_at: Some(manifest.created_at),
+            agent: Some(manifest.agent.name.clone()),
         });
 
         // Add agent node + edge
@@ -41,6 +61,8 @@ pub fn build_graph(storage: &GitStorage) -> Result<ContextGraph, QueryError> {
                 id: agent_id.clone(),
                 node_type: NodeType::Agent,
                 label: manifest.agent.name.clone(),
+                created_at: None,
+                agent: None,
             });
         }
         graph.edges.push(GraphEdge {
@@ -57,6 +79,8 @@ pub fn build_graph(storage: &GitStorage) -> Result<ContextGraph, QueryError> {
                     id: file_id.clone(),
                     node_type: NodeType::File,
                     label: fc.path.clone(),
+                    created_at: None,
+                    agent: None,
                 });
             }
             graph.edges.push(GraphEdge {
@@ -79,6 +103,8 @@ pub fn build_graph(storage: &GitStorage) -> Result<ContextGraph, QueryError> {
                     id: commit_id.clone(),
                     node_type: NodeType::Commit,
                     label: sha[..std::cmp::min(8, sha.len())].to_string(),
+                    created_at: None,
+                    agent: None,
                 });
             }
             graph.edges.push(GraphEdge {
@@ -88,16 +114,169 @@ pub fn build_graph(storage: &GitStorage) -> Result<ContextGraph, QueryError> {
             });
         }
 
-        // Add lineage edges
+        // Lineage edges wait until every local engram has a node
         if let Some(parent) = &data.lineage.parent_engram {
-            let parent_node_id = format!("engram:{}", parent.as_str());
-            graph.edges.push(GraphEdge {
-                from: engram_node_id,
-                to: parent_node_id,
-                edge_type: EdgeType::FollowsFrom,
-            });
+            lineage.push((manifest.id.clone(), parent.clone(), EdgeType::FollowsFrom));
+        }
+        for child in &data.lineage.child_engrams {
+            lineage.push((child.clone(), manifest.id.clone(), EdgeType::FollowsFrom));
+        }
+        for rel in &data.lineage.related_engrams {
+            let edge_type = match rel.relation_type {
+                RelationType::FollowsFrom => EdgeType::FollowsFrom,
+                RelationType::Motivates => EdgeType::Motivates,
+                RelationType::DependsOn => EdgeType::DependsOn,
+                RelationType::Supersedes => EdgeType::Supersedes,
+                RelationType::ConflictsWith => EdgeType::ConflictsWith,
+            };
+            lineage.push((manifest.id.clone(), rel.engram_id.clone(), edge_type));
         }
     }
 
+    add_lineage_edges(&mut graph, lineage, options);
     Ok(graph)
 }
+
+/// Add an edge per distinct `(from, to, type)` link between engrams. A link
+/// to an engram without a node is dropped, or with `show_missing` gets a
+/// placeholder node.
+fn add_lineage_edges(
+    graph: &mut ContextGraph,
+    links: Vec<(EngramId, EngramId, EdgeType)>,
+    options: &GraphOptions,
+) {
+    let mut known: HashSet<String> = graph
+        .nodes
+        .iter()
+        .filter(|n| n.node_type == NodeType::Engram)
+        .map(|n| n.id.clone())
+        .collect();
+    let mut seen = HashSet::new();
+
+    for (from, to, edge_type) in links {
+        if from == to || !seen.insert((from.clone(), to.clone(), edge_type.clone())) {
+            continue;
+        }
+        let ends = [from, to].map(|id| (format!("engram:{}", id.as_str()), id));
+        let missing: Vec<_> = ends
+            .iter()
+            .filter(|(node, _)| !known.contains(node))
+            .collect();
+        if !missing.is_empty() && !options.show_missing {
+            continue;
+        }
+        for (node, id) in missing {
+            known.insert(node.clone());
+            graph.nodes.push(GraphNode {
+                id: node.clone(),
+                node_type: NodeType::Engram,
+                label: format!("{} (missing)", &id.as_str()[..id.as_str().len().min(8)]),
+                created_at: None,
+                agent: None,
+            });
+        }
+        let [(from, _), (to, _)] = ends;
+        graph.edges.push(GraphEdge {
+            from,
+            to,
+            edge_type,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphFilter;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(lineage: Lineage) -> EngramData {
+        EngramData::builder().lineage(lineage).build()
+    }
+
+    fn lineage_edges(graph: &ContextGraph) -> Vec<(String, String, EdgeType)> {
+        let mut edges: Vec<_> = graph
+            .edges
+            .iter()
+            .filter(|e| e.edge_type != EdgeType::UsedAgent)
+            .map(|e| (e.from.clone(), e.to.clone(), e.edge_type.clone()))
+            .collect();
+        edges.sort_by(|a, b| (&a.0, &a.1, a.2.as_str()).cmp(&(&b.0, &b.1, b.2.as_str())));
+        edges
+    }
+
+    /// `c` follows from `b`, which follows from `a`; `a` follows from an
+    /// engram that was never fetched, and `c` supersedes `a`.
+    #[test]
+    fn test_three_engram_parent_chain() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let missing = EngramId::new();
+        let a = engram(Lineage {
+            parent_engram: Some(missing.clone()),
+            ..Default::default()
+        });
+        let b = engram(Lineage {
+            parent_engram: Some(a.manifest.id.clone()),
+            ..Default::default()
+        });
+        let c = engram(Lineage {
+            parent_engram: Some(b.manifest.id.clone()),
+            related_engrams: vec![Relationship {
+                engram_id: a.manifest.id.clone(),
+                relation_type: RelationType::Supersedes,
+                description: None,
+            }],
+            ..Default::default()
+        });
+        // `b` also lists `c` as its child; that is the same link as c's parent
+        let mut b = b;
+        b.lineage.child_engrams.push(c.manifest.id.clone());
+        for data in [&a, &b, &c] {
+            storage.create(data).unwrap();
+        }
+        let node = |data: &EngramData| format!("engram:{}", data.manifest.id.as_str());
+        let (a, b, c) = (node(&a), node(&b), node(&c));
+
+        let graph = build_graph(&storage).unwrap();
+        let mut expected = vec![
+            (b.clone(), a.clone(), EdgeType::FollowsFrom),
+            (c.clone(), b.clone(), EdgeType::FollowsFrom),
+            (c.clone(), a.clone(), EdgeType::Supersedes),
+        ];
+        expected.sort_by(|x, y| (&x.0, &x.1, x.2.as_str()).cmp(&(&y.0, &y.1, y.2.as_str())));
+        assert_eq!(lineage_edges(&graph), expected);
+        assert_eq!(graph.nodes.len(), 4); // three engrams and their agent
+
+        // Focusing on the newest engram reaches the whole chain
+        let focused = graph.filter(&GraphFilter {
+            focus: Some(c.clone()),
+            depth: 2,
+            node_types: Some(vec![NodeType::Engram]),
+            ..Default::default()
+        });
+        let mut ids: Vec<_> = focused.nodes.iter().map(|n| n.id.clone()).collect();
+        ids.sort();
+        let mut chain = vec![a.clone(), b, c];
+        chain.sort();
+        assert_eq!(ids, chain);
+
+        // The dangling parent appears only when asked for
+        let graph = build_graph_with(&storage, &GraphOptions { show_missing: true }).unwrap();
+        let missing_node = format!("engram:{}", missing.as_str());
+        let placeholder = graph.nodes.iter().find(|n| n.id == missing_node).unwrap();
+        assert_eq!(
+            placeholder.label,
+            format!("{} (missing)", &missing.as_str()[..8])
+        );
+        assert!(graph
+            .edges
+            .iter()
+            .any(|e| e.from == a && e.to == missing_node && e.edge_type == EdgeType::FollowsFrom));
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
+use super::model::{ContextGraph, NodeType};
+
+/// Narrows a [`ContextGraph`] to a readable part of it.
+#[derive(Debug, Clone, Default)]
+pub struct GraphFilter {
+    /// Keep only these node types; `None` keeps all.
+    pub node_types: Option<Vec<NodeType>>,
+    /// Drop these node types.
+    pub exclude_node_types: Vec<NodeType>,
+    /// Only engrams created on or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only engrams created on or before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Only engrams by these agents; empty keeps all.
+    pub agents: Vec<String>,
+    /// Keep only nodes within `depth` edges of this node ID, as for
+    /// [`ContextGraph::subgraph`].
+    pub focus: Option<String>,
+    pub depth: usize,
+}
+
+impl GraphFilter {
+    fn keeps_type(&self, node_type: &NodeType) -> bool {
+        self.node_types
+            .as_ref()
+            .map_or(true, |types| types.contains(node_type))
+            && !self.exclude_node_types.contains(node_type)
+    }
+}
+
+impl ContextGraph {
+    /// Apply `filter`. Engrams outside its time window or agents are dropped
+    /// along with the files, agents and commits only they reached; then nodes
+    /// of excluded types; then, with a focus, everything beyond its depth.
+    /// Edges are kept only between kept nodes.
+    pub fn filter(&self, filter: &GraphFilter) -> ContextGraph {
+        let engram_kept = |created_at: Option<DateTime<Utc>>, agent: Option<&str>| {
+            let in_window = created_at.map_or(true, |t| {
+                filter.since.map_or(true, |since| t >= since)
+                    && filter.until.map_or(true, |until| t <= until)
+            });
+            let by_agent = filter.agents.is_empty()
+                || agent.is_some_and(|a| filter.agents.iter().any(|f| f == a));
+            in_window && by_agent
+        };
+        let engrams: HashSet<&str> = self
+            .nodes
+            .iter()
+            .filter(|n| n.node_type == NodeType::Engram)
+            .filter(|n| engram_kept(n.created_at, n.agent.as_deref()))
+            .map(|n| n.id.as_str())
+            .collect();
+
+        // Other nodes survive if a kept engram still reaches them
+        let mut reached: HashSet<&str> = HashSet::new();
+        for edge in &self.edges {
+            if engrams.contains(edge.from.as_str()) {
+                reached.insert(&edge.to);
+            }
+            if engrams.contains(edge.to.as_str()) {
+                reached.insert(&edge.from);
+            }
+        }
+
+        let mut graph = ContextGraph {
+            nodes: self
+                .nodes
+                .iter()
+                .filter(|n| match n.node_type {
+                    NodeType::Engram => engrams.contains(n.id.as_str()),
+                    _ => reached.contains(n.id.as_str()),
+                })
+                .filter(|n| filter.keeps_type(&n.node_type))
+                .cloned()
+                .collect(),
+            edges: Vec::new(),
+        };
+        let kept: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        graph.edges = self
+            .edges
+            .iter()
+            .filter(|e| kept.contains(e.from.as_str()) && kept.contains(e.to.as_str()))
+            .cloned()
+            .collect();
+
+        match &filter.focus {
+            Some(focus) => graph.subgraph(focus, filter.depth),
+            None => graph,
+        }
+    }
+
+    /// The ID of the node `reference` names: a node ID (`file:src/auth.rs`),
+    /// an engram ID or unique prefix, or a file path.
+    pub fn resolve_node(&self, reference: &str) -> Option<String> {
+        if self.nodes.iter().any(|n| n.id == reference) {
+            return Some(reference.to_string());
+        }
+        let prefix = format!("engram:{reference}");
+        let mut engrams = self.nodes.iter().filter(|n| n.id.starts_with(&prefix));
+        if let (Some(node), None) = (engrams.next(), engrams.next()) {
+            return Some(node.id.clone());
+        }
+        let file = format!("file:{reference}");
+        self.nodes
+            .iter()
+            .find(|n| n.id == file)
+            .map(|n| n.id.clone())
+    }
+}
+
+impl std::str::FromStr for NodeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "engram" => Ok(Self::Engram),
+            "file" => Ok(Self::File),
+            "agent" => Ok(Self::Agent),
+            "commit" => Ok(Self::Commit),
+            _ => Err(format!(
+                "Unknown node type '{s}'. Use one of: engram, file, agent, commit"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::model::{EdgeType, GraphEdge, GraphNode};
+
+    fn engram(id: &str, agent: &str, created_at: &str) -> GraphNode {
+        GraphNode {
+            id: format!("engram:{id}"),
+            node_type: NodeType::Engram,
+            label: id.into(),
+            created_at: Some(created_at.parse().unwrap()),
+            agent: Some(agent.into()),
+        }
+    }
+
+    fn node(id: &str, node_type: NodeType) -> GraphNode {
+        GraphNode {
+            id: id.into(),
+            node_type,
+            label: id.into(),
+            created_at: None,
+            agent: None,
+        }
+    }
+
+    fn edge(from: &str, to: &str, edge_type: EdgeType) -> GraphEdge {
+        GraphEdge {
+            from: from.into(),
+            to: to.into(),
+            edge_type,
+        }
+    }
+
+    /// aaaa (claude, January) and bbbb (aider, March) both touch auth.rs;
+    /// bbbb follows from aaaa and produced a commit.
+    fn graph() -> ContextGraph {
+        let mut edges = Vec::new();
+        for (id, agent) in [("aaaa", "claude-code"), ("bbbb", "aider")] {
+            let engram = format!("engram:{id}");
+            let agent = format!("agent:{agent}");
+            edges.push(edge(&engram, &agent, EdgeType::UsedAgent));
+            edges.push(edge(&engram, "file:src/auth.rs", EdgeType::TouchedFile));
+            edges.push(edge("file:src/auth.rs", &engram, EdgeType::ModifiedBy));
+        }
+        edges.push(edge("engram:bbbb", "commit:1234567", EdgeType::ProducedBy));
+        edges.push(edge("engram:bbbb", "engram:aaaa", EdgeType::FollowsFrom));
+        ContextGraph {
+            nodes: vec![
+                engram("aaaa", "claude-code", "2026-01-10T12:00:00Z"),
+                engram("bbbb", "aider", "2026-03-10T12:00:00Z"),
+                node("agent:claude-code", NodeType::Agent),
+                node("agent:aider", NodeType::Agent),
+                node("file:src/auth.rs", NodeType::File),
+                node("commit:1234567", NodeType::Commit),
+            ],
+            edges,
+        }
+    }
+
+    fn ids(graph: &ContextGraph) -> Vec<&str> {
+        let mut ids: Vec<_> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn test_excluding_files_drops_their_edges() {
+        let filter = GraphFilter {
+            exclude_node_types: vec![NodeType::File],
+            ..Default::default()
+        };
+        let filtered = graph().filter(&filter);
+        assert!(!ids(&filtered).contains(&"file:src/auth.rs"));
+        assert!(filtered
+            .edges
+            .iter()
+            .all(|e| !matches!(e.edge_type, EdgeType::TouchedFile | EdgeType::ModifiedBy)));
+        assert_eq!(filtered.edges.len(), 4);
+
+        let only = GraphFilter {
+            node_types: Some(vec![NodeType::Engram, NodeType::Agent]),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&graph().filter(&only)),
+            [
+                "agent:aider",
+                "agent:claude-code",
+                "engram:aaaa",
+                "engram:bbbb"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_time_window_and_agents_drop_what_only_they_reach() {
+        let since = GraphFilter {
+            since: Some("2026-02-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let filtered = graph().filter(&since);
+        assert_eq!(
+            ids(&filtered),
+            [
+                "agent:aider",
+                "commit:1234567",
+                "engram:bbbb",
+                "file:src/auth.rs"
+            ]
+        );
+        // The lineage edge to the dropped engram goes with it
+        assert!(filtered
+            .edges
+            .iter()
+            .all(|e| e.edge_type != EdgeType::FollowsFrom));
+
+        let agents = GraphFilter {
+            agents: vec!["claude-code".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&graph().filter(&agents)),
+            ["agent:claude-code", "engram:aaaa", "file:src/auth.rs"]
+        );
+    }
+
+    #[test]
+    fn test_focus_matches_subgraph() {
+        let full = graph();
+        for depth in 0..3 {
+            let filter = GraphFilter {
+                focus: Some("commit:1234567".into()),
+                depth,
+                ..Default::default()
+            };
+            let focused = full.filter(&filter);
+            let expected = full.subgraph("commit:1234567", depth);
+            assert_eq!(ids(&focused), ids(&expected));
+            assert_eq!(focused.edges.len(), expected.edges.len());
+        }
+        let one_hop = full.filter(&GraphFilter {
+            focus: Some("commit:1234567".into()),
+            depth: 1,
+            ..Default::default()
+        });
+        assert_eq!(ids(&one_hop), ["commit:1234567", "engram:bbbb"]);
+    }
+
+    #[test]
+    fn test_resolve_node() {
+        let g = graph();
+        assert_eq!(g.resolve_node("bb").as_deref(), Some("engram:bbbb"));
+        assert_eq!(
+            g.resolve_node("src/auth.rs").as_deref(),
+            Some("file:src/auth.rs")
+        );
+        assert_eq!(
+            g.resolve_node("agent:aider").as_deref(),
+            Some("agent:aider")
+        );
+        assert_eq!(g.resolve_node("nope"), None);
+    }
+}
@@ -1,5 +1,9 @@
 pub mod builder;
+pub mod filter;
 pub mod model;
 
-pub use builder::build_graph;
-pub use model::{ContextGraph, EdgeType, GraphEdge, GraphNode, NodeType};
+pub use builder::{build_graph, build_graph_with, GraphOptions};
+pub use filter::GraphFilter;
+pub use model::{
+    ContextGraph, EdgeType, GraphEdge, GraphNode, NodeType, GRAPH_JSON_VERSION, MERMAID_LABEL_MAX,
+};
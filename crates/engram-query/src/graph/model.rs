@@ -1,5 +1,15 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Version of the [`ContextGraph::to_json`] document, bumped when its shape
+/// changes.
+pub const GRAPH_JSON_VERSION: u32 = 1;
+
+/// Longest node label, in characters, in [`ContextGraph::to_mermaid`] output.
+pub const MERMAID_LABEL_MAX: usize = 40;
+
 /// Type of node in the context graph.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NodeType {
@@ -9,22 +19,61 @@ pub enum NodeType {
     Commit,
 }
 
+impl NodeType {
+    /// Lowercase name, used as the Mermaid class.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Engram => "engram",
+            Self::File => "file",
+            Self::Agent => "agent",
+            Self::Commit => "commit",
+        }
+    }
+}
+
 /// A node in the context graph.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphNode {
     pub id: String,
     pub node_type: NodeType,
     pub label: String,
+    /// When the engram was created; engram nodes only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Name of the agent that produced the engram; engram nodes only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
 }
 
 /// Type of edge in the context graph.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EdgeType {
     ModifiedBy,
     ProducedBy,
     UsedAgent,
     FollowsFrom,
     TouchedFile,
+    Motivates,
+    DependsOn,
+    Supersedes,
+    ConflictsWith,
+}
+
+impl EdgeType {
+    /// Snake-case name, used as the edge label.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ModifiedBy => "modified_by",
+            Self::ProducedBy => "produced_by",
+            Self::UsedAgent => "used_agent",
+            Self::FollowsFrom => "follows_from",
+            Self::TouchedFile => "touched_file",
+            Self::Motivates => "motivates",
+            Self::DependsOn => "depends_on",
+            Self::Supersedes => "supersedes",
+            Self::ConflictsWith => "conflicts_with",
+        }
+    }
 }
 
 /// An edge in the context graph.
@@ -104,20 +153,158 @@ impl ContextGraph {
         }
 
         for edge in &self.edges {
-            let label = match edge.edge_type {
-                EdgeType::ModifiedBy => "modified_by",
-                EdgeType::ProducedBy => "produced_by",
-                EdgeType::UsedAgent => "used_agent",
-                EdgeType::FollowsFrom => "follows_from",
-                EdgeType::TouchedFile => "touched_file",
-            };
             dot.push_str(&format!(
                 "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
-                edge.from, edge.to, label
+                edge.from,
+                edge.to,
+                edge.edge_type.as_str()
             ));
         }
 
         dot.push_str("}\n");
         dot
     }
+
+    /// Render as a Mermaid flowchart. Nodes get short IDs (`n0`, `n1`, ...)
+    /// and a class per [`NodeType`]; labels are escaped and capped at
+    /// [`MERMAID_LABEL_MAX`] characters, and repeated edges drawn once.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        let mut ids: HashMap<&str, String> = HashMap::new();
+
+        for node in &self.nodes {
+            if ids.contains_key(node.id.as_str()) {
+                continue;
+            }
+            let id = format!("n{}", ids.len());
+            let label = mermaid_label(&node.label);
+            let shape = match node.node_type {
+                NodeType::Engram => format!("{id}[\"{label}\"]"),
+                NodeType::File => format!("{id}([\"{label}\"])"),
+                NodeType::Agent => format!("{id}{{{{\"{label}\"}}}}"),
+                NodeType::Commit => format!("{id}((\"{label}\"))"),
+            };
+            out.push_str(&format!("  {shape}:::{}\n", node.node_type.as_str()));
+            ids.insert(&node.id, id);
+        }
+
+        let mut seen = HashSet::new();
+        for edge in &self.edges {
+            if !seen.insert((&edge.from, &edge.to, &edge.edge_type)) {
+                continue;
+            }
+            // Edges to nodes outside the graph get a plain node of their own
+            for node_id in [edge.from.as_str(), edge.to.as_str()] {
+                if !ids.contains_key(node_id) {
+                    let id = format!("n{}", ids.len());
+                    out.push_str(&format!("  {id}[\"{}\"]\n", mermaid_label(node_id)));
+                    ids.insert(node_id, id);
+                }
+            }
+            let from = &ids[edge.from.as_str()];
+            let to = &ids[edge.to.as_str()];
+            out.push_str(&format!("  {from} -->|{}| {to}\n", edge.edge_type.as_str()));
+        }
+
+        for (class, style) in [
+            ("engram", "fill:#dbeafe,stroke:#1d4ed8"),
+            ("file", "fill:#f3f4f6,stroke:#4b5563"),
+            ("agent", "fill:#fef3c7,stroke:#b45309"),
+            ("commit", "fill:#dcfce7,stroke:#15803d"),
+        ] {
+            out.push_str(&format!("  classDef {class} {style}\n"));
+        }
+        out
+    }
+
+    /// The graph as JSON, with a `version` ([`GRAPH_JSON_VERSION`]) beside
+    /// its nodes and edges.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "version": GRAPH_JSON_VERSION,
+            "nodes": self.nodes,
+            "edges": self.edges,
+        })
+    }
+}
+
+/// A node label made safe for a quoted Mermaid label: capped at
+/// [`MERMAID_LABEL_MAX`] characters, with characters Mermaid would parse
+/// written as entity codes.
+fn mermaid_label(label: &str) -> String {
+    let label = label.split_whitespace().collect::<Vec<_>>().join(" ");
+    let label: String = if label.chars().count() > MERMAID_LABEL_MAX {
+        let cut: String = label.chars().take(MERMAID_LABEL_MAX - 1).collect();
+        format!("{cut}…")
+    } else {
+        label
+    };
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => out.push_str("#quot;"),
+            '#' | '[' | ']' | '(' | ')' | '{' | '}' | '<' | '>' | '|' => {
+                out.push_str(&format!("#{};", c as u32))
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two engrams by one agent touching one file, with a duplicated edge and
+    /// labels Mermaid would otherwise misparse.
+    fn small_graph() -> ContextGraph {
+        let node = |id: &str, node_type, label: &str| GraphNode {
+            id: id.into(),
+            node_type,
+            label: label.into(),
+            created_at: None,
+            agent: None,
+        };
+        let edge = |from: &str, to: &str, edge_type| GraphEdge {
+            from: from.into(),
+            to: to.into(),
+            edge_type,
+        };
+        ContextGraph {
+            nodes: vec![
+                node(
+                    "engram:aaaa",
+                    NodeType::Engram,
+                    "Fix \"login\" [retry] loop (again) in the session middleware",
+                ),
+                node("engram:bbbb", NodeType::Engram, "Add <rate> limit | #2"),
+                node("agent:claude-code", NodeType::Agent, "claude-code"),
+                node("file:src/auth.rs", NodeType::File, "src/auth.rs"),
+                node("commit:1234567", NodeType::Commit, "1234567"),
+            ],
+            edges: vec![
+                edge("engram:aaaa", "agent:claude-code", EdgeType::UsedAgent),
+                edge("engram:aaaa", "file:src/auth.rs", EdgeType::TouchedFile),
+                edge("engram:aaaa", "file:src/auth.rs", EdgeType::TouchedFile),
+                edge("engram:bbbb", "agent:claude-code", EdgeType::UsedAgent),
+                edge("engram:bbbb", "engram:aaaa", EdgeType::FollowsFrom),
+                edge("engram:bbbb", "commit:1234567", EdgeType::ProducedBy),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_to_mermaid_golden() {
+        assert_eq!(
+            small_graph().to_mermaid(),
+            include_str!("testdata/small.mmd")
+        );
+    }
+
+    #[test]
+    fn test_to_json_golden() {
+        let json = serde_json::to_string_pretty(&small_graph().to_json()).unwrap();
+        assert_eq!(format!("{json}\n"), include_str!("testdata/small.json"));
+    }
 }
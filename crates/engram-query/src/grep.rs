@@ -0,0 +1,526 @@
+//! Regex scan over stored engrams, for text the tokenized index can't find
+//! (punctuation such as `--dry-run` or `refs/engrams/*` is stripped there).
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use std::collections::HashSet;
+
+use engram_core::model::{EngramId, Intent, Operations, Transcript, TranscriptContent};
+use engram_core::storage::{EngramPart, GitStorage, ListOptions};
+
+use crate::error::QueryError;
+use crate::index::{SearchOptions, SearchSort};
+use crate::search::SearchEngine;
+
+/// Compiled patterns may use at most this much memory.
+const REGEX_SIZE_LIMIT: usize = 1024 * 1024;
+
+/// Part of an engram that [`grep_engrams`] matches against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrepField {
+    /// intent.md, apart from its dead ends.
+    Intent,
+    /// Transcript text, including thinking and tool output, which the
+    /// search index leaves out.
+    Transcript,
+    /// One "approach: reason" line per dead end.
+    DeadEnd,
+    /// Paths of changed files.
+    File,
+}
+
+impl GrepField {
+    pub const ALL: [GrepField; 4] = [Self::Intent, Self::Transcript, Self::DeadEnd, Self::File];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Intent => "intent",
+            Self::Transcript => "transcript",
+            Self::DeadEnd => "dead_end",
+            Self::File => "file",
+        }
+    }
+}
+
+impl fmt::Display for GrepField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GrepField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.as_str() == s)
+            .ok_or_else(|| {
+                format!("Unknown field '{s}'. Use one of: intent, transcript, dead_end, file")
+            })
+    }
+}
+
+/// What to scan and when to stop.
+#[derive(Debug, Clone)]
+pub struct GrepOptions {
+    pub fields: Vec<GrepField>,
+    /// Match the pattern as plain text instead of a regular expression.
+    pub literal: bool,
+    /// Only scan engrams matching this search query, to narrow candidates.
+    pub query: Option<String>,
+    /// Only scan engrams these list filters (agent, tag, dates, ...) select.
+    pub filter: ListOptions,
+    /// Lines of context to keep before and after each match, from the same
+    /// transcript entry or intent.
+    pub context: usize,
+    /// Stop after this many matching lines.
+    pub limit: usize,
+    /// Stop scanning after this long.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for GrepOptions {
+    fn default() -> Self {
+        Self {
+            fields: GrepField::ALL.to_vec(),
+            literal: false,
+            query: None,
+            filter: ListOptions::default(),
+            context: 0,
+            limit: 100,
+            timeout: Some(Duration::from_secs(10)),
+        }
+    }
+}
+
+/// A line that matched the pattern.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GrepMatch {
+    pub engram_id: EngramId,
+    pub field: GrepField,
+    /// Index of the transcript entry the line is in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<usize>,
+    pub line: String,
+    /// Up to [`GrepOptions::context`] lines before and after the match.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+}
+
+/// Matches from [`grep_engrams`], and whether a limit cut the scan short.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GrepResults {
+    pub matches: Vec<GrepMatch>,
+    /// Engrams scanned, in full or until a limit was hit.
+    pub scanned: usize,
+    /// Stopped at [`GrepOptions::limit`]; there may be more matches.
+    pub truncated: bool,
+    /// Stopped at [`GrepOptions::timeout`].
+    pub timed_out: bool,
+}
+
+/// Compile a grep pattern with a bounded size. The regex engine runs in
+/// linear time, so with the size bound no pattern can stall a scan.
+pub fn compile_pattern(pattern: &str) -> Result<Regex, QueryError> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| QueryError::InvalidPattern(e.to_string()))
+}
+
+/// Scan engrams, newest first, for lines matching `pattern`.
+///
+/// Only the parts of each engram needed for `options.fields` are read, and
+/// the transcript is scanned one entry at a time.
+pub fn grep_engrams(
+    storage: &GitStorage,
+    pattern: &str,
+    options: &GrepOptions,
+) -> Result<GrepResults, QueryError> {
+    let regex = if options.literal {
+        compile_pattern(&regex::escape(pattern))?
+    } else {
+        compile_pattern(pattern)?
+    };
+    let listed: Vec<EngramId> = storage
+        .list(&options.filter)?
+        .into_iter()
+        .map(|m| m.id)
+        .collect();
+    let candidates = match options.query.as_deref().filter(|q| !q.trim().is_empty()) {
+        Some(query) => {
+            let listed: HashSet<_> = listed.into_iter().collect();
+            search_candidates(storage, query)?
+                .into_iter()
+                .filter(|id| listed.contains(id))
+                .collect()
+        }
+        None => listed,
+    };
+
+    let mut scan = Scan {
+        regex,
+        context: options.context,
+        limit: options.limit,
+        deadline: options.timeout.map(|t| Instant::now() + t),
+        results: GrepResults::default(),
+    };
+    for id in candidates {
+        if scan.stopped() {
+            break;
+        }
+        scan.results.scanned += 1;
+        scan_engram(storage, &id, &options.fields, &mut scan)?;
+    }
+    Ok(scan.results)
+}
+
+/// IDs of every engram matching `query`, newest first.
+fn search_candidates(storage: &GitStorage, query: &str) -> Result<Vec<EngramId>, QueryError> {
+    let engine = SearchEngine::open(storage)?;
+    let count = engine
+        .search(storage, query, &SearchOptions::with_limit(1))?
+        .total;
+    let options = SearchOptions {
+        limit: count,
+        sort: SearchSort::CreatedDesc,
+        ..Default::default()
+    };
+    let page = engine.search(storage, query, &options)?;
+    Ok(page.results.into_iter().map(|r| r.manifest.id).collect())
+}
+
+struct Scan {
+    regex: Regex,
+    context: usize,
+    limit: usize,
+    deadline: Option<Instant>,
+    results: GrepResults,
+}
+
+impl Scan {
+    fn stopped(&mut self) -> bool {
+        if self.results.truncated || self.results.timed_out {
+            return true;
+        }
+        if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            self.results.timed_out = true;
+            return true;
+        }
+        false
+    }
+
+    /// Check one line; returns false once the scan should stop.
+    fn line(&mut self, id: &EngramId, field: GrepField, line: &str) -> bool {
+        self.lines(id, field, None, &[line])
+    }
+
+    /// Check each of a block of lines, taking context from the block.
+    fn lines(
+        &mut self,
+        id: &EngramId,
+        field: GrepField,
+        entry: Option<usize>,
+        lines: &[&str],
+    ) -> bool {
+        for (i, line) in lines.iter().enumerate() {
+            if self.stopped() {
+                return false;
+            }
+            if !self.regex.is_match(line) {
+                continue;
+            }
+            if self.results.matches.len() >= self.limit {
+                self.results.truncated = true;
+                return false;
+            }
+            let owned = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect();
+            let after_end = (i + 1 + self.context).min(lines.len());
+            self.results.matches.push(GrepMatch {
+                engram_id: id.clone(),
+                field,
+                entry,
+                line: line.to_string(),
+                before: owned(&lines[i.saturating_sub(self.context)..i]),
+                after: owned(&lines[i + 1..after_end]),
+            });
+        }
+        true
+    }
+}
+
+fn scan_engram(
+    storage: &GitStorage,
+    id: &EngramId,
+    fields: &[GrepField],
+    scan: &mut Scan,
+) -> Result<(), QueryError> {
+    let mut intent_md = None;
+    for &field in fields {
+        let more = match field {
+            GrepField::Intent | GrepField::DeadEnd => {
+                if intent_md.is_none() {
+                    let bytes = storage.read_part(id.as_str(), EngramPart::Intent)?;
+                    intent_md = Some(String::from_utf8_lossy(&bytes).into_owned());
+                }
+                let md = intent_md.as_deref().unwrap_or_default();
+                if field == GrepField::Intent {
+                    scan_intent(md, id, scan)
+                } else {
+                    let intent = Intent::from_markdown(md)?;
+                    intent
+                        .dead_ends
+                        .iter()
+                        .all(|de| scan.line(id, field, &format!("{}: {}", de.approach, de.reason)))
+                }
+            }
+            GrepField::Transcript => {
+                let jsonl = storage.read_part(id.as_str(), EngramPart::Transcript)?;
+                scan_transcript(&jsonl, id, scan)
+            }
+            GrepField::File => {
+                let json = storage.read_part(id.as_str(), EngramPart::Operations)?;
+                let operations: Operations = serde_json::from_slice(&json)?;
+                operations
+                    .file_changes
+                    .iter()
+                    .all(|fc| scan.line(id, field, &fc.path))
+            }
+        };
+        if !more {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Scan intent.md, skipping the dead-ends section ([`GrepField::DeadEnd`]).
+fn scan_intent(md: &str, id: &EngramId, scan: &mut Scan) -> bool {
+    let mut in_dead_ends = false;
+    let lines: Vec<&str> = md
+        .lines()
+        .filter(|line| {
+            if let Some(heading) = line.strip_prefix("## ") {
+                in_dead_ends = heading.trim() == "Dead Ends";
+            }
+            !in_dead_ends
+        })
+        .collect();
+    scan.lines(id, GrepField::Intent, None, &lines)
+}
+
+/// Scan transcript text one JSONL entry at a time, without building the
+/// whole transcript. Tool calls' inputs are left out; their results are not.
+fn scan_transcript(jsonl: &[u8], id: &EngramId, scan: &mut Scan) -> bool {
+    let jsonl = String::from_utf8_lossy(jsonl);
+    for (index, entry) in Transcript::entries_from_jsonl(&jsonl).enumerate() {
+        let Ok(entry) = entry else {
+            tracing::warn!("Skipping unreadable transcript entry {index} in {id}");
+            continue;
+        };
+        let text = match &entry.content {
+            TranscriptContent::Text { text } | TranscriptContent::Thinking { text } => text,
+            TranscriptContent::ToolResult { output, .. } => output,
+            TranscriptContent::ToolUse { .. } => continue,
+        };
+        let lines: Vec<&str> = text.lines().collect();
+        if !scan.lines(id, GrepField::Transcript, Some(index), &lines) {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(request: &str, said: &str, dead_end: &str, file: &str) -> EngramData {
+        EngramData::builder()
+            .request(request)
+            .dead_end(dead_end, "did not work")
+            .message(Role::Assistant, said)
+            .file(file)
+            .build()
+    }
+
+    fn storage() -> (TempDir, GitStorage, Vec<EngramId>) {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let ids = [
+            engram(
+                "add a --dry-run flag to gc",
+                "Ran engram gc --dry-run\nNothing deleted",
+                "--force by default",
+                "crates/engram-cli/src/commands/gc.rs",
+            ),
+            engram(
+                "list refs/engrams/* faster",
+                "Globbing refs/engrams/*/* instead",
+                "walk every ref",
+                "crates/engram-core/src/storage/refs.rs",
+            ),
+        ]
+        .iter()
+        .map(|data| storage.create(data).unwrap())
+        .collect();
+        (tmp, storage, ids)
+    }
+
+    #[test]
+    fn test_anchored_regex_matches_lines() {
+        let (_tmp, storage, ids) = storage();
+
+        // Anchored to the start of a line: the request, not the transcript
+        let results = grep_engrams(&storage, r"^add a --dry-run", &GrepOptions::default()).unwrap();
+        assert_eq!(
+            results.matches,
+            vec![GrepMatch {
+                engram_id: ids[0].clone(),
+                field: GrepField::Intent,
+                entry: None,
+                line: "add a --dry-run flag to gc".into(),
+                before: Vec::new(),
+                after: Vec::new(),
+            }]
+        );
+        assert_eq!(results.scanned, 2);
+
+        let results = grep_engrams(&storage, r"refs/engrams/\*", &GrepOptions::default()).unwrap();
+        let fields: Vec<_> = results.matches.iter().map(|m| m.field).collect();
+        assert_eq!(fields, vec![GrepField::Intent, GrepField::Transcript]);
+
+        // Dead ends are their own field, not part of the intent
+        let results = grep_engrams(&storage, r"^--force", &GrepOptions::default()).unwrap();
+        assert_eq!(results.matches.len(), 1);
+        assert_eq!(results.matches[0].field, GrepField::DeadEnd);
+        assert_eq!(results.matches[0].line, "--force by default: did not work");
+
+        let files_only = GrepOptions {
+            fields: vec![GrepField::File],
+            ..Default::default()
+        };
+        let results = grep_engrams(&storage, r"\.rs$", &files_only).unwrap();
+        assert_eq!(results.matches.len(), 2);
+        assert!(results.matches.iter().all(|m| m.field == GrepField::File));
+    }
+
+    #[test]
+    fn test_tool_output_thinking_and_context() {
+        let (_tmp, storage, ids) = storage();
+        let mut data = engram("start the api", "Starting it", "none", "src/main.rs");
+        data.manifest.agent.name = "aider".into();
+        let entry = |content| TranscriptEntry {
+            timestamp: chrono::Utc::now(),
+            role: Role::Tool,
+            content,
+            token_count: None,
+        };
+        data.transcript.entries.extend([
+            entry(TranscriptContent::ToolUse {
+                tool_name: "Bash".into(),
+                tool_id: "t1".into(),
+                input: serde_json::json!({"command": "psql -h 127.0.0.1"}),
+            }),
+            entry(TranscriptContent::ToolResult {
+                tool_id: "t1".into(),
+                output: "psql: error\nECONNREFUSED 127.0.0.1:5432\nIs the server running?".into(),
+                is_error: true,
+            }),
+            entry(TranscriptContent::Thinking {
+                text: "Postgres is probably down".into(),
+            }),
+        ]);
+        let id = storage.create(&data).unwrap();
+
+        let options = GrepOptions {
+            context: 1,
+            ..Default::default()
+        };
+        let results = grep_engrams(&storage, r"ECONNREFUSED 127\.0\.0\.1:5432", &options).unwrap();
+        assert_eq!(results.matches.len(), 1);
+        let m = &results.matches[0];
+        assert_eq!(
+            (&m.engram_id, m.field, m.entry),
+            (&id, GrepField::Transcript, Some(2))
+        );
+        assert_eq!(m.before, ["psql: error"]);
+        assert_eq!(m.after, ["Is the server running?"]);
+
+        let literal = GrepOptions {
+            literal: true,
+            ..Default::default()
+        };
+        let results = grep_engrams(&storage, "127.0.0.1:5432", &literal).unwrap();
+        assert_eq!(results.matches.len(), 1);
+        assert!(grep_engrams(&storage, "127.0.0.1:54.2", &literal)
+            .unwrap()
+            .matches
+            .is_empty());
+
+        let results = grep_engrams(&storage, "probably down", &GrepOptions::default()).unwrap();
+        assert_eq!(results.matches[0].entry, Some(3));
+        // Tool inputs are not scanned
+        let results = grep_engrams(&storage, "psql -h", &GrepOptions::default()).unwrap();
+        assert!(results.matches.is_empty());
+
+        let claude_only = GrepOptions {
+            filter: ListOptions {
+                agent_filter: Some("claude-code".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let results = grep_engrams(&storage, "ECONNREFUSED", &claude_only).unwrap();
+        assert!(results.matches.is_empty());
+        assert_eq!(results.scanned, ids.len());
+    }
+
+    #[test]
+    fn test_limits_and_narrowing() {
+        let (_tmp, storage, ids) = storage();
+
+        let one = GrepOptions {
+            limit: 1,
+            ..Default::default()
+        };
+        let results = grep_engrams(&storage, "engram", &one).unwrap();
+        assert_eq!(results.matches.len(), 1);
+        assert!(results.truncated);
+
+        let expired = GrepOptions {
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let results = grep_engrams(&storage, "engram", &expired).unwrap();
+        assert!(results.timed_out);
+        assert!(results.matches.is_empty());
+
+        let narrowed = GrepOptions {
+            query: Some("globbing".into()),
+            ..Default::default()
+        };
+        let results = grep_engrams(&storage, "refs", &narrowed).unwrap();
+        assert_eq!(results.scanned, 1);
+        assert!(results.matches.iter().all(|m| m.engram_id == ids[1]));
+
+        assert!(matches!(
+            grep_engrams(&storage, "(unclosed", &GrepOptions::default()),
+            Err(QueryError::InvalidPattern(_))
+        ));
+    }
+}
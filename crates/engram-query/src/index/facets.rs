@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::StrColumn;
+use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+use engram_core::model::Manifest;
+
+/// How many matching engrams have each agent, model and tag, most common
+/// first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SearchFacets {
+    pub agents: Vec<(String, usize)>,
+    pub models: Vec<(String, usize)>,
+    pub tags: Vec<(String, usize)>,
+}
+
+impl SearchFacets {
+    /// Add another set of counts to these, e.g. from another repository.
+    pub(crate) fn merge(&mut self, other: SearchFacets) {
+        for (ours, theirs) in [
+            (&mut self.agents, other.agents),
+            (&mut self.models, other.models),
+            (&mut self.tags, other.tags),
+        ] {
+            let mut counts: HashMap<String, usize> = ours.drain(..).collect();
+            for (value, n) in theirs {
+                *counts.entry(value).or_default() += n;
+            }
+            *ours = sorted(counts);
+        }
+    }
+}
+
+impl SearchFacets {
+    /// Counts over `manifests`, for searches that don't go through the index.
+    pub(crate) fn of<'a>(manifests: impl IntoIterator<Item = &'a Manifest>) -> Self {
+        let mut counts: Counts = Default::default();
+        for m in manifests {
+            *counts[0].entry(m.agent.name.clone()).or_default() += 1;
+            if let Some(model) = &m.agent.model {
+                *counts[1].entry(model.clone()).or_default() += 1;
+            }
+            for tag in &m.tags {
+                *counts[2].entry(tag.clone()).or_default() += 1;
+            }
+        }
+        let [agents, models, tags] = counts;
+        Self {
+            agents: sorted(agents),
+            models: sorted(models),
+            tags: sorted(tags),
+        }
+    }
+}
+
+impl fmt::Display for SearchFacets {
+    /// One line per non-empty group, e.g. `agent: claude-code 62, aider 18`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (label, counts) in [
+            ("agent", &self.agents),
+            ("model", &self.models),
+            ("tag", &self.tags),
+        ] {
+            if counts.is_empty() {
+                continue;
+            }
+            let counts: Vec<String> = counts.iter().map(|(v, n)| format!("{v} {n}")).collect();
+            writeln!(f, "{label}: {}", counts.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts values of the agent, model and tag fast fields over matching docs.
+pub(crate) struct FacetCollector {
+    /// Fast-field names: agent, model, tags.
+    pub(crate) fields: [String; 3],
+}
+
+type Counts = [HashMap<String, usize>; 3];
+
+impl Collector for FacetCollector {
+    type Fruit = SearchFacets;
+    type Child = FacetSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let mut columns = Vec::with_capacity(self.fields.len());
+        for field in &self.fields {
+            let column = segment.fast_fields().str(field)?;
+            let counts = vec![0; column.as_ref().map_or(0, |c| c.num_terms())];
+            columns.push((column, counts));
+        }
+        Ok(FacetSegmentCollector { columns })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segments: Vec<Counts>) -> tantivy::Result<SearchFacets> {
+        let mut total: Counts = Default::default();
+        for counts in segments {
+            for (total, counts) in total.iter_mut().zip(counts) {
+                for (value, n) in counts {
+                    *total.entry(value).or_default() += n;
+                }
+            }
+        }
+        let [agents, models, tags] = total.map(sorted);
+        Ok(SearchFacets {
+            agents,
+            models,
+            tags,
+        })
+    }
+}
+
+pub(crate) struct FacetSegmentCollector {
+    /// Per field: the column (absent if no doc in the segment has a value)
+    /// and a count per term ordinal.
+    columns: Vec<(Option<StrColumn>, Vec<usize>)>,
+}
+
+impl SegmentCollector for FacetSegmentCollector {
+    type Fruit = Counts;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        for (column, counts) in &mut self.columns {
+            if let Some(column) = column {
+                for ord in column.term_ords(doc) {
+                    counts[ord as usize] += 1;
+                }
+            }
+        }
+    }
+
+    fn harvest(self) -> Counts {
+        let mut out: Counts = Default::default();
+        for ((column, counts), out) in self.columns.into_iter().zip(out.iter_mut()) {
+            let Some(column) = column else {
+                continue;
+            };
+            for (ord, n) in counts.into_iter().enumerate().filter(|(_, n)| *n > 0) {
+                let mut value = String::new();
+                // Engrams without a model index an empty one
+                if column.ord_to_str(ord as u64, &mut value).unwrap_or(false) && !value.is_empty() {
+                    out.insert(value, n);
+                }
+            }
+        }
+        out
+    }
+}
+
+fn sorted(counts: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
@@ -1,8 +1,15 @@
+pub mod facets;
+pub mod query;
 pub mod reader;
 pub mod rebuild;
 pub mod schema;
+pub mod transcript;
 pub mod writer;
 
-pub use reader::{EngramSearcher, SearchResult};
+pub use facets::SearchFacets;
+pub use query::{parse_date_bound, parse_search_query, FilterField, ParsedQuery};
+pub use reader::{
+    EngramSearcher, SearchOptions, SearchPage, SearchResult, SearchSort, DEFAULT_SNIPPET_MAX_CHARS,
+};
 pub use rebuild::rebuild_index;
-pub use writer::EngramIndexWriter;
+pub use writer::{EngramIndexWriter, WRITER_LOCK_TIMEOUT};
@@ -0,0 +1,347 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use tantivy::query::{
+    AllQuery, BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, Query, QueryParser, TermQuery,
+};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{Index, Score, Term};
+
+use super::schema::EngramSchema;
+use crate::error::QueryError;
+
+/// A structured field a search can be scoped to with `field:value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    Agent,
+    Model,
+    File,
+    Tag,
+    DeadEnd,
+    /// `has_errors:true` or `has_errors:false`.
+    HasErrors,
+}
+
+impl FilterField {
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "agent" => Some(Self::Agent),
+            "model" => Some(Self::Model),
+            "file" => Some(Self::File),
+            "tag" => Some(Self::Tag),
+            "dead_end" => Some(Self::DeadEnd),
+            "has_errors" => Some(Self::HasErrors),
+            _ => None,
+        }
+    }
+}
+
+/// A search string split into field constraints and free text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    pub filters: Vec<(FilterField, String)>,
+    /// Remaining text for the full-text query parser.
+    pub text: String,
+}
+
+/// Split `agent:claude-code file:"src/a b.rs" jwt` into filters and text.
+///
+/// Values may be double-quoted to include spaces or colons; only the first
+/// colon separates field and value, so `file:src/a:b.rs` works unquoted too.
+/// Tokens with an unknown prefix (`http://...`) are kept as literal text.
+pub fn parse_search_query(input: &str) -> ParsedQuery {
+    let mut parsed = ParsedQuery::default();
+    let mut text = Vec::new();
+
+    for token in tokenize(input) {
+        let scoped = token
+            .split_once(':')
+            .and_then(|(prefix, value)| Some((FilterField::from_prefix(prefix)?, value)));
+        match scoped {
+            Some((field, value)) if !value.is_empty() => {
+                parsed.filters.push((field, unquote(value).to_string()));
+            }
+            _ if token.contains(':') && !token.starts_with('"') => {
+                // Quote so the parser does not read it as `field:term`
+                text.push(format!("\"{}\"", token.replace('"', "")));
+            }
+            _ => text.push(token),
+        }
+    }
+
+    parsed.text = text.join(" ");
+    parsed
+}
+
+/// Split on whitespace outside double quotes, keeping the quotes.
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .map(|v| v.strip_suffix('"').unwrap_or(v))
+        .unwrap_or(value)
+}
+
+/// Weight of fuzzy and prefix matches. Such clauses also match the exact
+/// word, so a document with the exact word always outranks one that only
+/// matches approximately.
+const APPROXIMATE_BOOST: Score = 0.2;
+
+/// Build the tantivy query: every filter, from the input or from `filters`,
+/// is a must-clause, and the free text (if any) goes through the query
+/// parser over `text_fields`.
+///
+/// When the text is plain words, the last one also matches as a prefix
+/// unless the input ends in whitespace (so `EngramSess` finds
+/// `EngramSession`), and with `fuzzy` every word also matches within one or
+/// two edits. Approximate matches rank below exact ones.
+pub(crate) fn build_query(
+    index: &Index,
+    schema: &EngramSchema,
+    text_fields: Vec<Field>,
+    input: &str,
+    filters: &[(FilterField, String)],
+    fuzzy: bool,
+) -> Result<Box<dyn Query>, QueryError> {
+    let mut parsed = parse_search_query(input);
+    let text = text_query(index, &text_fields, &parsed.text, input, fuzzy)?;
+    parsed.filters.extend_from_slice(filters);
+
+    if parsed.filters.is_empty() {
+        // Nothing to match on: everything, so date ranges alone can filter
+        return Ok(text.unwrap_or_else(|| Box::new(AllQuery)));
+    }
+
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for (field, value) in &parsed.filters {
+        let clause: Box<dyn Query> = match field {
+            // Exact, untokenized fields
+            FilterField::Agent => exact(schema.agent_name, value),
+            FilterField::Model => exact(schema.agent_model, value),
+            FilterField::Tag => exact(schema.tags, value),
+            // Tokenized fields: match the value as a phrase
+            FilterField::File => phrase(index, schema.file_paths, value)?,
+            FilterField::DeadEnd => phrase(index, schema.dead_ends, value)?,
+            FilterField::HasErrors => {
+                let value = value.parse::<bool>().map_err(|_| {
+                    QueryError::Search(format!("has_errors: expects true or false, got '{value}'"))
+                })?;
+                Box::new(TermQuery::new(
+                    Term::from_field_bool(schema.has_errors, value),
+                    IndexRecordOption::Basic,
+                ))
+            }
+        };
+        clauses.push((Occur::Must, clause));
+    }
+    if let Some(text) = text {
+        clauses.push((Occur::Must, text));
+    }
+    Ok(Box::new(BooleanQuery::new(clauses)))
+}
+
+/// The free-text part of the query, with approximate matching added for
+/// plain-word input.
+fn text_query(
+    index: &Index,
+    fields: &[Field],
+    text: &str,
+    input: &str,
+    fuzzy: bool,
+) -> Result<Option<Box<dyn Query>>, QueryError> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let exact = QueryParser::for_index(index, fields.to_vec())
+        .parse_query(text)
+        .map_err(|e| QueryError::Search(e.to_string()))?;
+
+    // Leave quoted phrases and query syntax to the parser alone
+    let Some(words) = plain_words(text) else {
+        return Ok(Some(exact));
+    };
+    let mut approximate: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    let mut add = |word: &str, prefix: bool, distance: u8| {
+        for &field in fields {
+            let term = Term::from_field_text(field, &word.to_lowercase());
+            let query = if prefix {
+                FuzzyTermQuery::new_prefix(term, distance, true)
+            } else {
+                FuzzyTermQuery::new(term, distance, true)
+            };
+            approximate.push((Occur::Should, Box::new(query)));
+        }
+    };
+    if fuzzy {
+        for word in &words {
+            if let Some(distance) = fuzzy_distance(word) {
+                add(word, false, distance);
+            }
+        }
+    }
+    let last = words.last().filter(|w| w.chars().count() >= 3);
+    if let Some(last) = last.filter(|_| !input.ends_with(char::is_whitespace)) {
+        add(last, true, 0);
+    }
+
+    if approximate.is_empty() {
+        return Ok(Some(exact));
+    }
+    let approximate = BoostQuery::new(Box::new(BooleanQuery::new(approximate)), APPROXIMATE_BOOST);
+    Ok(Some(Box::new(BooleanQuery::new(vec![
+        (Occur::Should, exact),
+        (Occur::Should, Box::new(approximate)),
+    ]))))
+}
+
+/// The words of `text`, or `None` if it uses phrases, operators or fields.
+fn plain_words(text: &str) -> Option<Vec<&str>> {
+    text.split_whitespace()
+        .map(|word| {
+            let plain = word.chars().all(|c| c.is_alphanumeric() || c == '_')
+                && !matches!(word, "AND" | "OR" | "NOT");
+            plain.then_some(word)
+        })
+        .collect()
+}
+
+/// Edits allowed for a fuzzy match: none for short words, which would
+/// match too much, then one, then two from eight characters.
+fn fuzzy_distance(word: &str) -> Option<u8> {
+    match word.chars().count() {
+        0..=3 => None,
+        4..=7 => Some(1),
+        _ => Some(2),
+    }
+}
+
+/// Parse a `--since`/`--until` value: RFC 3339 or `YYYY-MM-DD`. A bare date
+/// means the start of that day (UTC), or its last second when `end_of_day`
+/// is set, so `until` includes the whole day.
+pub fn parse_date_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, QueryError> {
+    let value = value.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(value) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        QueryError::Search(format!(
+            "Invalid date '{value}': expected YYYY-MM-DD or RFC 3339"
+        ))
+    })?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    Ok(time.expect("valid time of day").and_utc())
+}
+
+fn exact(field: Field, value: &str) -> Box<dyn Query> {
+    Box::new(TermQuery::new(
+        Term::from_field_text(field, value),
+        IndexRecordOption::Basic,
+    ))
+}
+
+fn phrase(index: &Index, field: Field, value: &str) -> Result<Box<dyn Query>, QueryError> {
+    QueryParser::for_index(index, vec![field])
+        .parse_query(&format!("\"{}\"", value.replace('"', "")))
+        .map_err(|e| QueryError::Search(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filters_and_text() {
+        let parsed = parse_search_query("agent:claude-code file:src/auth.rs jwt refresh");
+        assert_eq!(
+            parsed.filters,
+            vec![
+                (FilterField::Agent, "claude-code".to_string()),
+                (FilterField::File, "src/auth.rs".to_string()),
+            ]
+        );
+        assert_eq!(parsed.text, "jwt refresh");
+    }
+
+    #[test]
+    fn test_parse_quoted_values_with_colons() {
+        let parsed = parse_search_query(
+            r#"dead_end:"redis: cluster unreachable" file:C:\src\a.rs "exact phrase""#,
+        );
+        assert_eq!(
+            parsed.filters,
+            vec![
+                (
+                    FilterField::DeadEnd,
+                    "redis: cluster unreachable".to_string()
+                ),
+                (FilterField::File, r"C:\src\a.rs".to_string()),
+            ]
+        );
+        assert_eq!(parsed.text, "\"exact phrase\"");
+    }
+
+    #[test]
+    fn test_unknown_prefix_is_literal_text() {
+        let parsed = parse_search_query("see http://example.com author:alice");
+        assert!(parsed.filters.is_empty());
+        assert_eq!(parsed.text, r#"see "http://example.com" "author:alice""#);
+    }
+
+    #[test]
+    fn test_parse_date_bound() {
+        assert_eq!(
+            parse_date_bound("2026-02-01", false).unwrap().to_rfc3339(),
+            "2026-02-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_date_bound("2026-02-01", true).unwrap().to_rfc3339(),
+            "2026-02-01T23:59:59+00:00"
+        );
+        assert_eq!(
+            parse_date_bound("2026-02-01T10:00:00+02:00", true)
+                .unwrap()
+                .to_rfc3339(),
+            "2026-02-01T08:00:00+00:00"
+        );
+        assert!(parse_date_bound("last tuesday", false).is_err());
+    }
+
+    #[test]
+    fn test_plain_words() {
+        assert_eq!(
+            plain_words("jwt refresh_token"),
+            Some(vec!["jwt", "refresh_token"])
+        );
+        assert_eq!(plain_words("\"exact phrase\""), None);
+        assert_eq!(plain_words("jwt -cookie"), None);
+        assert_eq!(plain_words("jwt OR oauth"), None);
+        assert_eq!(fuzzy_distance("jwt"), None);
+        assert_eq!(fuzzy_distance("oauth"), Some(1));
+        assert_eq!(fuzzy_distance("authetication"), Some(2));
+    }
+}
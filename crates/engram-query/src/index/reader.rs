@@ -1,21 +1,188 @@
-use std::path::Path;
+use std::collections::HashSet;
+use std::ops::{Bound, Range};
+use std::path::{Path, PathBuf};
 
-use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
-use tantivy::schema::Value;
-use tantivy::{Index, ReloadPolicy};
+use chrono::{DateTime, Utc};
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::{
+    BooleanQuery, MoreLikeThisQuery, Occur, Query, QueryParser, RangeQuery, TermQuery,
+};
+use tantivy::schema::{Field, IndexRecordOption, OwnedValue, Value};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{DocAddress, DocSet, Index, Order, ReloadPolicy, Searcher, Term, TERMINATED};
 
 use engram_core::model::Manifest;
 
-use super::schema::EngramSchema;
+use super::facets::{FacetCollector, SearchFacets};
+use super::query::{build_query, FilterField};
+use super::schema::{stored_version, EngramSchema, SCHEMA_VERSION};
 use crate::error::QueryError;
 
 /// Result of a search query.
 #[derive(Debug, Clone)]
 pub struct SearchResult {
     pub manifest: Manifest,
+    /// Relevance score; 0.0 when results are ordered by another [`SearchSort`].
     pub score: f32,
+    /// Fragment of the request or transcript around the matched terms, or
+    /// the intent summary when the match has no text positions (e.g. a
+    /// file-path-only match).
     pub snippet: Option<String>,
+    /// Byte ranges of matched terms within `snippet`; empty for a summary.
+    pub highlights: Vec<Range<usize>>,
+    /// Whether `snippet` comes from a transcript that was too long to index
+    /// in full, so passages from its middle were not searched.
+    pub truncated_transcript: bool,
+    /// Repository the engram came from, set by [`crate::MultiRepoSearch`].
+    pub repo: Option<PathBuf>,
+}
+
+impl SearchResult {
+    /// The snippet with each highlighted term wrapped in `open`/`close`.
+    pub fn marked_snippet(&self, open: &str, close: &str) -> Option<String> {
+        let snippet = self.snippet.as_deref()?;
+        let mut out = String::with_capacity(snippet.len());
+        let mut pos = 0;
+        for range in &self.highlights {
+            out.push_str(&snippet[pos..range.start]);
+            out.push_str(open);
+            out.push_str(&snippet[range.clone()]);
+            out.push_str(close);
+            pos = range.end;
+        }
+        out.push_str(&snippet[pos..]);
+        Some(out)
+    }
+}
+
+/// Default [`SearchOptions::snippet_max_chars`].
+pub const DEFAULT_SNIPPET_MAX_CHARS: usize = 150;
+
+/// Result order for [`EngramSearcher::search`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchSort {
+    #[default]
+    Relevance,
+    /// Newest first.
+    CreatedDesc,
+    /// Oldest first.
+    CreatedAsc,
+    /// Most expensive first.
+    CostDesc,
+    /// Most tokens first.
+    TokensDesc,
+}
+
+impl SearchSort {
+    /// Names accepted by [`FromStr`](std::str::FromStr), for help text.
+    pub const NAMES: &'static [&'static str] = &["relevance", "newest", "oldest", "cost", "tokens"];
+}
+
+impl std::str::FromStr for SearchSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "relevance" => Ok(Self::Relevance),
+            "newest" | "created-desc" => Ok(Self::CreatedDesc),
+            "oldest" | "created-asc" => Ok(Self::CreatedAsc),
+            "cost" | "cost-desc" => Ok(Self::CostDesc),
+            "tokens" | "tokens-desc" => Ok(Self::TokensDesc),
+            _ => Err(format!(
+                "Unknown sort '{s}'. Use one of: {}",
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Constraints and limits for [`EngramSearcher::search`].
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub limit: usize,
+    /// Number of matches to skip, for paging through results.
+    pub offset: usize,
+    pub sort: SearchSort,
+    /// Only engrams created at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only engrams created at or before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Maximum length of a highlighted snippet.
+    pub snippet_max_chars: usize,
+    /// Also match words within one or two edits (typos), ranked below
+    /// exact matches.
+    pub fuzzy: bool,
+    /// Count all matches by agent, model and tag ([`SearchPage::facets`]).
+    pub facets: bool,
+    /// Only engrams that cost at least this much (USD). Engrams with no
+    /// recorded cost are excluded whenever either cost bound is set.
+    pub min_cost: Option<f64>,
+    pub max_cost: Option<f64>,
+    /// Only engrams with at least this many total tokens.
+    pub min_tokens: Option<u64>,
+    pub max_tokens: Option<u64>,
+    /// Field constraints applied like `field:value` terms in the query.
+    pub filters: Vec<(FilterField, String)>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            limit: 10,
+            offset: 0,
+            sort: SearchSort::Relevance,
+            since: None,
+            until: None,
+            snippet_max_chars: DEFAULT_SNIPPET_MAX_CHARS,
+            fuzzy: false,
+            facets: false,
+            min_cost: None,
+            max_cost: None,
+            min_tokens: None,
+            max_tokens: None,
+            filters: Vec::new(),
+        }
+    }
+}
+
+impl SearchOptions {
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            limit,
+            ..Default::default()
+        }
+    }
+}
+
+/// One page of [`EngramSearcher::search`] results.
+#[derive(Debug, Clone, Default)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// Number of engrams matching the query across all pages.
+    pub total: usize,
+    /// Position of the first result among all matches.
+    pub offset: usize,
+    /// Breakdown of all matches, when [`SearchOptions::facets`] is set.
+    pub facets: Option<SearchFacets>,
+}
+
+impl SearchPage {
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// "showing 11–20 of 25", or "showing 0 of 25" for a page past the end.
+    pub fn showing(&self) -> String {
+        if self.results.is_empty() {
+            return format!("showing 0 of {}", self.total);
+        }
+        format!(
+            "showing {}–{} of {}",
+            self.offset + 1,
+            self.offset + self.results.len(),
+            self.total
+        )
+    }
 }
 
 /// Searches the engram index.
@@ -27,13 +194,25 @@ pub struct EngramSearcher {
 impl EngramSearcher {
     /// Open an existing index for reading.
     pub fn open(path: &Path) -> Result<Self, QueryError> {
+        if let Some(found) = stored_version(path).filter(|v| *v != SCHEMA_VERSION) {
+            return Err(QueryError::OutdatedIndex {
+                found,
+                current: SCHEMA_VERSION,
+            });
+        }
         let schema = EngramSchema::new();
         let index = Index::open_in_dir(path)?;
         Ok(Self { schema, index })
     }
 
-    /// Search engrams with a free-text query.
-    pub fn search(&self, query_str: &str, limit: usize) -> Result<Vec<SearchResult>, QueryError> {
+    /// Search engrams with a free-text query, optionally scoped with
+    /// `agent:`, `model:`, `file:`, `tag:`, `dead_end:` and `has_errors:` terms
+    /// (see [`parse_search_query`](super::query::parse_search_query)).
+    pub fn search(
+        &self,
+        query_str: &str,
+        options: &SearchOptions,
+    ) -> Result<SearchPage, QueryError> {
         let reader = self
             .index
             .reader_builder()
@@ -41,25 +220,111 @@ impl EngramSearcher {
             .try_into()?;
         let searcher = reader.searcher();
 
-        let query_parser = QueryParser::for_index(
+        let query = build_query(
             &self.index,
+            &self.schema,
             vec![
                 self.schema.intent_request,
                 self.schema.intent_summary,
                 self.schema.transcript_text,
                 self.schema.dead_ends,
+                self.schema.decisions,
                 self.schema.file_paths,
+                self.schema.tags,
+                self.schema.tool_names,
             ],
-        );
+            query_str,
+            &options.filters,
+            options.fuzzy,
+        )?;
+        let query = self.restrict(query, options);
 
-        let query = query_parser
-            .parse_query(query_str)
-            .map_err(|e| QueryError::Search(e.to_string()))?;
+        // TopDocs panics on a zero limit; fetch one and drop it below
+        let top = TopDocs::with_limit(options.limit.max(1)).and_offset(options.offset);
+        let created_at = self.field_name(self.schema.created_at);
+        let (total, mut hits) = match options.sort {
+            SearchSort::Relevance => searcher.search(&query, &(Count, top))?,
+            SearchSort::CreatedDesc => unscored(searcher.search(
+                &query,
+                &(
+                    Count,
+                    top.order_by_fast_field::<tantivy::DateTime>(created_at, Order::Desc),
+                ),
+            )?),
+            SearchSort::CreatedAsc => unscored(searcher.search(
+                &query,
+                &(
+                    Count,
+                    top.order_by_fast_field::<tantivy::DateTime>(created_at, Order::Asc),
+                ),
+            )?),
+            SearchSort::CostDesc => unscored(searcher.search(
+                &query,
+                &(
+                    Count,
+                    top.order_by_fast_field::<f64>(
+                        self.field_name(self.schema.cost_usd),
+                        Order::Desc,
+                    ),
+                ),
+            )?),
+            SearchSort::TokensDesc => unscored(searcher.search(
+                &query,
+                &(
+                    Count,
+                    top.order_by_fast_field::<u64>(
+                        self.field_name(self.schema.total_tokens),
+                        Order::Desc,
+                    ),
+                ),
+            )?),
+        };
+        hits.truncate(options.limit);
+
+        // Prefer a fragment of the request, then of the transcript
+        let mut snippets = Vec::new();
+        for field in [self.schema.intent_request, self.schema.transcript_text] {
+            let mut generator = SnippetGenerator::create(&searcher, &*query, field)?;
+            generator.set_max_num_chars(options.snippet_max_chars);
+            snippets.push((field, generator));
+        }
+
+        let facets = if options.facets {
+            let collector = FacetCollector {
+                fields: [
+                    self.schema.agent_name,
+                    self.schema.agent_model,
+                    self.schema.tags,
+                ]
+                .map(|field| self.field_name(field).to_string()),
+            };
+            Some(searcher.search(&query, &collector)?)
+        } else {
+            None
+        };
 
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+        Ok(SearchPage {
+            results: self.load_results(&searcher, hits, &snippets)?,
+            total,
+            offset: options.offset,
+            facets,
+        })
+    }
+
+    fn field_name(&self, field: Field) -> &str {
+        self.schema.schema.get_field_name(field)
+    }
 
-        let mut results = Vec::with_capacity(top_docs.len());
-        for (score, doc_address) in top_docs {
+    /// Read the stored manifest for each hit, with a snippet from the first
+    /// of `snippets` that highlights anything.
+    fn load_results(
+        &self,
+        searcher: &Searcher,
+        hits: Vec<(f32, DocAddress)>,
+        snippets: &[(Field, SnippetGenerator)],
+    ) -> Result<Vec<SearchResult>, QueryError> {
+        let mut results = Vec::with_capacity(hits.len());
+        for (score, doc_address) in hits {
             let doc: tantivy::TantivyDocument = searcher.doc(doc_address)?;
 
             // Extract manifest JSON
@@ -70,28 +335,149 @@ impl EngramSearcher {
 
             let manifest: Manifest = serde_json::from_str(manifest_json)?;
 
-            // Extract snippet from intent summary
-            let snippet = doc
-                .get_first(self.schema.intent_summary)
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
+            let highlighted = snippets
+                .iter()
+                .map(|(field, generator)| (*field, generator.snippet_from_doc(&doc)))
+                .find(|(_, snippet)| !snippet.highlighted().is_empty());
+            let mut truncated_transcript = false;
+            let (snippet, highlights) = match highlighted {
+                Some((field, snippet)) => {
+                    truncated_transcript = field == self.schema.transcript_text
+                        && doc
+                            .get_first(self.schema.transcript_truncated)
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false);
+                    (
+                        Some(snippet.fragment().to_string()),
+                        snippet.highlighted().to_vec(),
+                    )
+                }
+                // No text positions to show; fall back to the intent summary
+                None => (
+                    doc.get_first(self.schema.intent_summary)
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string()),
+                    Vec::new(),
+                ),
+            };
 
             results.push(SearchResult {
                 manifest,
                 score,
                 snippet,
+                highlights,
+                truncated_transcript,
+                repo: None,
             });
         }
 
         Ok(results)
     }
 
+    /// Intersect `query` with the date, cost and token bounds, if any.
+    fn restrict(&self, query: Box<dyn Query>, options: &SearchOptions) -> Box<dyn Query> {
+        fn bound<T>(value: Option<T>) -> Bound<T> {
+            value.map_or(Bound::Unbounded, Bound::Included)
+        }
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+
+        if options.since.is_some() || options.until.is_some() {
+            let to_tantivy =
+                |t: DateTime<Utc>| tantivy::DateTime::from_timestamp_secs(t.timestamp());
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_date_bounds(
+                    self.field_name(self.schema.created_at).to_string(),
+                    bound(options.since.map(to_tantivy)),
+                    bound(options.until.map(to_tantivy)),
+                )),
+            ));
+        }
+        if options.min_cost.is_some() || options.max_cost.is_some() {
+            clauses.push((
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_bool(self.schema.has_cost, true),
+                    IndexRecordOption::Basic,
+                )),
+            ));
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_f64_bounds(
+                    self.field_name(self.schema.cost_usd).to_string(),
+                    bound(options.min_cost),
+                    bound(options.max_cost),
+                )),
+            ));
+        }
+        if options.min_tokens.is_some() || options.max_tokens.is_some() {
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_u64_bounds(
+                    self.field_name(self.schema.total_tokens).to_string(),
+                    bound(options.min_tokens),
+                    bound(options.max_tokens),
+                )),
+            ));
+        }
+
+        if clauses.is_empty() {
+            return query;
+        }
+        clauses.insert(0, (Occur::Must, query));
+        Box::new(BooleanQuery::new(clauses))
+    }
+
+    /// IDs of every engram in the index, read from the `id` term dictionary
+    /// rather than the stored documents.
+    pub fn indexed_ids(&self) -> Result<HashSet<String>, QueryError> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+
+        let mut ids = HashSet::new();
+        for segment in searcher.segment_readers() {
+            let inverted = segment.inverted_index(self.schema.id)?;
+            let mut terms = inverted.terms().stream()?;
+            while terms.advance() {
+                let Ok(id) = std::str::from_utf8(terms.key()) else {
+                    continue;
+                };
+                let term = Term::from_field_text(self.schema.id, id);
+                let Some(mut postings) = inverted.read_postings(&term, IndexRecordOption::Basic)?
+                else {
+                    continue;
+                };
+                // Deleted documents keep their terms until segments merge
+                let mut doc = postings.doc();
+                while doc != TERMINATED {
+                    if segment
+                        .alive_bitset()
+                        .map_or(true, |alive| alive.is_alive(doc))
+                    {
+                        ids.insert(id.to_string());
+                        break;
+                    }
+                    doc = postings.advance();
+                }
+            }
+        }
+        Ok(ids)
+    }
+
     /// Search for engrams that modified a specific file path.
     pub fn search_by_file(
         &self,
         file_path: &str,
         limit: usize,
+        offset: usize,
     ) -> Result<Vec<SearchResult>, QueryError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
         let reader = self
             .index
             .reader_builder()
@@ -105,29 +491,676 @@ impl EngramSearcher {
             .parse_query(file_path)
             .map_err(|e| QueryError::Search(e.to_string()))?;
 
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit).and_offset(offset))?;
+        self.load_results(&searcher, top_docs, &[])
+    }
 
-        let mut results = Vec::with_capacity(top_docs.len());
-        for (score, doc_address) in top_docs {
-            let doc: tantivy::TantivyDocument = searcher.doc(doc_address)?;
-            let manifest_json = doc
-                .get_first(self.schema.manifest_json)
-                .and_then(|v| v.as_str())
-                .unwrap_or("{}");
-            let manifest: Manifest = serde_json::from_str(manifest_json)?;
+    /// Engrams whose dead ends or decisions match `query_str`, newest first.
+    pub fn search_insights(
+        &self,
+        query_str: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<SearchPage, QueryError> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        let searcher = reader.searcher();
 
-            let snippet = doc
-                .get_first(self.schema.intent_summary)
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
+        let query = build_query(
+            &self.index,
+            &self.schema,
+            vec![self.schema.dead_ends, self.schema.decisions],
+            query_str,
+            &[],
+            false,
+        )?;
+        // TopDocs panics on a zero limit; fetch one and drop it below
+        let top = TopDocs::with_limit(limit.max(1))
+            .and_offset(offset)
+            .order_by_fast_field::<tantivy::DateTime>(
+                self.field_name(self.schema.created_at),
+                Order::Desc,
+            );
+        let (total, mut hits) = unscored(searcher.search(&query, &(Count, top))?);
+        hits.truncate(limit);
+        Ok(SearchPage {
+            results: self.load_results(&searcher, hits, &[])?,
+            total,
+            offset,
+            facets: None,
+        })
+    }
 
-            results.push(SearchResult {
-                manifest,
-                score,
-                snippet,
-            });
+    /// Engrams whose request or summary shares distinctive terms with
+    /// `text`, best first, as `(engram id, score)`.
+    pub fn more_like_this(
+        &self,
+        text: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, f32)>, QueryError> {
+        if limit == 0 || text.trim().is_empty() {
+            return Ok(Vec::new());
         }
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        let searcher = reader.searcher();
 
+        let fields = [self.schema.intent_request, self.schema.intent_summary];
+        let query = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1)
+            .with_min_word_length(3)
+            .with_document_fields(
+                fields
+                    .iter()
+                    .map(|field| (*field, vec![OwnedValue::from(text)]))
+                    .collect(),
+            );
+
+        let mut results = Vec::new();
+        for (score, address) in searcher.search(&query, &TopDocs::with_limit(limit))? {
+            let doc: tantivy::TantivyDocument = searcher.doc(address)?;
+            if let Some(id) = doc.get_first(self.schema.id).and_then(|v| v.as_str()) {
+                results.push((id.to_string(), score));
+            }
+        }
         Ok(results)
     }
 }
+
+/// Results ordered by a field carry no relevance score.
+fn unscored<T>((total, hits): (usize, Vec<(T, DocAddress)>)) -> (usize, Vec<(f32, DocAddress)>) {
+    (total, hits.into_iter().map(|(_, doc)| (0.0, doc)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{parse_date_bound, EngramIndexWriter};
+    use engram_core::model::*;
+    use tempfile::TempDir;
+
+    fn engram(agent: &str, model: &str, file: &str, tag: &str, dead_end: &str) -> EngramData {
+        EngramData::builder()
+            .agent(agent)
+            .model(model)
+            .tag(tag)
+            .request("add jwt auth")
+            .intent_summary(&format!("{agent} session"))
+            .dead_end(dead_end, "too slow")
+            .file(file)
+            .build()
+    }
+
+    /// Index two engrams that share the free text "jwt" but differ in every field.
+    fn searcher() -> (TempDir, EngramSearcher, Vec<EngramId>) {
+        let tmp = TempDir::new().unwrap();
+        let engrams = [
+            engram(
+                "claude-code",
+                "claude-sonnet-4-5",
+                "src/auth.rs",
+                "security",
+                "session cookies",
+            ),
+            engram(
+                "aider",
+                "gpt-4o",
+                "src/db:legacy.rs",
+                "perf",
+                "redis: cluster cache",
+            ),
+        ];
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        for data in &engrams {
+            writer.index_engram(data).unwrap();
+        }
+        writer.commit().unwrap();
+        let ids = engrams.iter().map(|e| e.manifest.id.clone()).collect();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+        (tmp, searcher, ids)
+    }
+
+    fn ids(searcher: &EngramSearcher, query: &str) -> Vec<EngramId> {
+        searcher
+            .search(query, &SearchOptions::default())
+            .unwrap()
+            .results
+            .into_iter()
+            .map(|r| r.manifest.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_field_scoped_search() {
+        let (_tmp, searcher, all) = searcher();
+        assert_eq!(ids(&searcher, "jwt").len(), 2);
+
+        for (query, expected) in [
+            ("agent:claude-code jwt", &all[0]),
+            ("model:gpt-4o", &all[1]),
+            ("file:src/auth.rs jwt", &all[0]),
+            ("tag:perf", &all[1]),
+            ("dead_end:\"session cookies\"", &all[0]),
+        ] {
+            assert_eq!(ids(&searcher, query), vec![expected.clone()], "{query}");
+        }
+
+        // Filters are combined with AND
+        assert!(ids(&searcher, "agent:aider tag:security").is_empty());
+        assert!(ids(&searcher, "agent:aider nonexistentword").is_empty());
+    }
+
+    #[test]
+    fn test_has_errors_filter() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        let mut failed = engram("aider", "m", "src/a.rs", "t", "x");
+        failed.operations.shell_commands.push(ShellCommand {
+            timestamp: chrono::Utc::now(),
+            command: "cargo test".into(),
+            exit_code: Some(101),
+            duration_ms: None,
+        });
+        let clean = engram("claude-code", "m", "src/a.rs", "t", "x");
+        writer.index_engram(&failed).unwrap();
+        writer.index_engram(&clean).unwrap();
+        writer.commit().unwrap();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+
+        assert_eq!(ids(&searcher, "has_errors:true"), [failed.manifest.id]);
+        assert_eq!(ids(&searcher, "has_errors:false jwt"), [clean.manifest.id]);
+        assert!(matches!(
+            searcher.search("has_errors:maybe", &SearchOptions::default()),
+            Err(QueryError::Search(_))
+        ));
+    }
+
+    #[test]
+    fn test_quoted_values_with_colons() {
+        let (_tmp, searcher, all) = searcher();
+        assert_eq!(
+            ids(&searcher, "file:\"src/db:legacy.rs\""),
+            vec![all[1].clone()]
+        );
+        assert_eq!(
+            ids(&searcher, "file:src/db:legacy.rs"),
+            vec![all[1].clone()]
+        );
+        assert_eq!(
+            ids(&searcher, "dead_end:\"redis: cluster\""),
+            vec![all[1].clone()]
+        );
+    }
+
+    #[test]
+    fn test_unknown_prefix_is_not_an_error() {
+        let (_tmp, searcher, _) = searcher();
+        assert!(searcher
+            .search("owner:alice", &SearchOptions::default())
+            .unwrap()
+            .is_empty());
+        assert_eq!(ids(&searcher, "legacy:rs").len(), 1);
+    }
+
+    #[test]
+    fn test_date_range_excludes_better_matches() {
+        let tmp = TempDir::new().unwrap();
+        let month = |m: u32| {
+            chrono::NaiveDate::from_ymd_opt(2026, m, 15)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        let mut ids = Vec::new();
+        for (m, request) in [
+            (1, "jwt jwt jwt jwt rotation"),
+            (2, "jwt rotation"),
+            (3, "jwt jwt jwt jwt jwt rotation"),
+        ] {
+            let mut data = engram("claude-code", "m", "src/a.rs", "t", "x");
+            data.manifest.created_at = month(m);
+            data.intent.original_request = request.into();
+            writer.index_engram(&data).unwrap();
+            ids.push(data.manifest.id.clone());
+        }
+        writer.commit().unwrap();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+
+        let window = SearchOptions {
+            since: Some(month(2) - chrono::Duration::days(1)),
+            until: Some(month(2) + chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        let results = searcher.search("jwt", &window).unwrap().results;
+        let found: Vec<_> = results.into_iter().map(|r| r.manifest.id).collect();
+        assert_eq!(found, vec![ids[1].clone()]);
+
+        // Open-ended bounds; an empty query lists everything in range
+        let since_feb = SearchOptions {
+            since: Some(month(2)),
+            ..Default::default()
+        };
+        assert_eq!(searcher.search("jwt", &since_feb).unwrap().total, 2);
+        let until_feb = SearchOptions {
+            until: Some(month(2)),
+            ..Default::default()
+        };
+        assert_eq!(searcher.search("", &until_feb).unwrap().total, 2);
+    }
+
+    #[test]
+    fn test_sort_orders_equal_relevance_results() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        let mut ids = Vec::new();
+        // Same text everywhere; only date, cost and tokens differ
+        for (day, cost, tokens) in [(1, 0.50, 100), (3, 0.10, 900), (2, 2.00, 500)] {
+            let mut data = engram("claude-code", "m", "src/pay.rs", "t", "x");
+            data.manifest.created_at = chrono::NaiveDate::from_ymd_opt(2026, 1, day)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc();
+            data.manifest.token_usage.cost_usd = Some(cost);
+            data.manifest.token_usage.total_tokens = tokens;
+            writer.index_engram(&data).unwrap();
+            ids.push(data.manifest.id.clone());
+        }
+        writer.commit().unwrap();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+
+        let order = |sort: SearchSort| -> Vec<EngramId> {
+            let options = SearchOptions {
+                sort,
+                ..Default::default()
+            };
+            searcher
+                .search("file:src/pay.rs", &options)
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| r.manifest.id)
+                .collect()
+        };
+        let by = |idx: [usize; 3]| idx.map(|i| ids[i].clone()).to_vec();
+
+        assert_eq!(order(SearchSort::CreatedDesc), by([1, 2, 0]));
+        assert_eq!(order(SearchSort::CreatedAsc), by([0, 2, 1]));
+        assert_eq!(order(SearchSort::CostDesc), by([2, 0, 1]));
+        assert_eq!(order(SearchSort::TokensDesc), by([1, 2, 0]));
+        assert_eq!(order(SearchSort::Relevance).len(), 3);
+    }
+
+    #[test]
+    fn test_sort_names() {
+        assert_eq!("newest".parse(), Ok(SearchSort::CreatedDesc));
+        assert_eq!("COST".parse(), Ok(SearchSort::CostDesc));
+        assert!("size".parse::<SearchSort>().is_err());
+        for name in SearchSort::NAMES {
+            assert!(name.parse::<SearchSort>().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_pagination() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        for day in 1..=25 {
+            let mut data = engram("claude-code", "m", "src/page.rs", "t", "x");
+            data.manifest.created_at = chrono::NaiveDate::from_ymd_opt(2026, 1, day)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc();
+            writer.index_engram(&data).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+
+        let page = |offset: usize| {
+            let options = SearchOptions {
+                limit: 10,
+                offset,
+                sort: SearchSort::CreatedDesc,
+                ..Default::default()
+            };
+            searcher.search("file:src/page.rs", &options).unwrap()
+        };
+
+        let mut seen = Vec::new();
+        for (offset, len, showing) in [
+            (0, 10, "showing 1–10 of 25"),
+            (10, 10, "showing 11–20 of 25"),
+            (20, 5, "showing 21–25 of 25"),
+        ] {
+            let page = page(offset);
+            assert_eq!(page.results.len(), len);
+            assert_eq!(page.total, 25);
+            assert_eq!(page.showing(), showing);
+            seen.extend(page.results.into_iter().map(|r| r.manifest.created_at));
+        }
+        // Every engram exactly once, newest first
+        assert_eq!(seen.len(), 25);
+        assert!(seen.windows(2).all(|w| w[0] > w[1]));
+
+        // Past the end is an empty page, not an error
+        let past = page(30);
+        assert!(past.is_empty());
+        assert_eq!(past.total, 25);
+        assert_eq!(past.showing(), "showing 0 of 25");
+
+        let by_file = |offset| searcher.search_by_file("src/page.rs", 10, offset).unwrap();
+        assert_eq!(by_file(20).len(), 5);
+        assert!(by_file(25).is_empty());
+    }
+
+    #[test]
+    fn test_snippet_highlights_transcript_match() {
+        let tmp = TempDir::new().unwrap();
+        let mut data = engram("claude-code", "m", "src/cache.rs", "t", "x");
+        data.transcript.entries.push(TranscriptEntry {
+            timestamp: Utc::now(),
+            role: Role::Assistant,
+            content: TranscriptContent::Text {
+                text: "The flaky test was caused by a stale memoization table, \
+                       so I cleared it between runs."
+                    .into(),
+            },
+            token_count: None,
+        });
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        writer.index_engram(&data).unwrap();
+        writer.commit().unwrap();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+
+        // A transcript-only term is highlighted inside a transcript fragment
+        let page = searcher
+            .search("memoization", &SearchOptions::default())
+            .unwrap();
+        let result = &page.results[0];
+        let snippet = result.snippet.as_deref().unwrap();
+        assert_eq!(result.highlights.len(), 1);
+        assert_eq!(&snippet[result.highlights[0].clone()], "memoization");
+        assert!(result
+            .marked_snippet("**", "**")
+            .unwrap()
+            .contains("**memoization**"));
+
+        let short = SearchOptions {
+            snippet_max_chars: 30,
+            ..Default::default()
+        };
+        let page = searcher.search("memoization", &short).unwrap();
+        let snippet = page.results[0].snippet.as_deref().unwrap();
+        assert!(snippet.len() <= 30 && snippet.contains("memoization"));
+
+        // A match with no text positions falls back to the summary
+        let page = searcher
+            .search("file:src/cache.rs", &SearchOptions::default())
+            .unwrap();
+        assert_eq!(
+            page.results[0].snippet.as_deref(),
+            Some("claude-code session")
+        );
+        assert!(page.results[0].highlights.is_empty());
+    }
+
+    #[test]
+    fn test_decisions_tags_and_tools_are_searchable() {
+        let tmp = TempDir::new().unwrap();
+        let mut data = engram("claude-code", "m", "src/db.rs", "postgres-migration", "x");
+        data.intent.decisions.push(Decision {
+            description: "use sqlx".into(),
+            rationale: "compile-time checked queries".into(),
+        });
+        for name in ["Bash", "WebFetch", "Bash"] {
+            data.operations.tool_calls.push(ToolCall {
+                timestamp: Utc::now(),
+                tool_name: name.into(),
+                input: serde_json::json!({}),
+                output_summary: None,
+                duration_ms: None,
+                is_error: false,
+            });
+        }
+        let other = engram("aider", "m", "src/ui.rs", "frontend", "y");
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        writer.index_engram(&data).unwrap();
+        writer.index_engram(&other).unwrap();
+        writer.commit().unwrap();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+
+        let expected = vec![data.manifest.id.clone()];
+        // Only the decision mentions sqlx or compile-time checks
+        assert_eq!(ids(&searcher, "sqlx"), expected);
+        assert_eq!(ids(&searcher, "\"compile-time checked\""), expected);
+        assert_eq!(ids(&searcher, "webfetch"), expected);
+        assert_eq!(ids(&searcher, "postgres-migration"), expected);
+    }
+
+    #[test]
+    fn test_search_insights_only_matches_dead_ends_and_decisions() {
+        let (_tmp, searcher, all) = searcher();
+        let insights = |query: &str| {
+            let page = searcher.search_insights(query, 10, 0).unwrap();
+            let ids: Vec<_> = page.results.into_iter().map(|r| r.manifest.id).collect();
+            (page.total, ids)
+        };
+
+        assert_eq!(insights("redis"), (1, vec![all[1].clone()]));
+        assert_eq!(insights("slow").0, 2);
+        // The request mentions jwt, but no dead end or decision does
+        assert_eq!(insights("jwt"), (0, Vec::new()));
+
+        let page = searcher.search_insights("slow", 1, 1).unwrap();
+        assert_eq!((page.total, page.offset, page.results.len()), (2, 1, 1));
+    }
+
+    #[test]
+    fn test_outdated_index_is_reported() {
+        let tmp = TempDir::new().unwrap();
+        EngramIndexWriter::open(tmp.path()).unwrap();
+        std::fs::write(
+            tmp.path().join(super::super::schema::SCHEMA_VERSION_FILE),
+            "2",
+        )
+        .unwrap();
+
+        let err = EngramSearcher::open(tmp.path()).err().unwrap();
+        assert!(matches!(err, QueryError::OutdatedIndex { found: 2, .. }));
+        assert!(err.to_string().contains("engram reindex"));
+        assert!(matches!(
+            EngramIndexWriter::open(tmp.path()).err().unwrap(),
+            QueryError::OutdatedIndex { found: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_fuzzy_and_prefix_matching() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        let mut ids = Vec::new();
+        for request in [
+            "fix authentication for EngramSession",
+            "fix authetication",
+            "update docs",
+        ] {
+            let mut data = engram("claude-code", "m", "src/a.rs", "t", "x");
+            data.intent.original_request = request.into();
+            writer.index_engram(&data).unwrap();
+            ids.push(data.manifest.id.clone());
+        }
+        writer.commit().unwrap();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+        let fuzzy = SearchOptions {
+            fuzzy: true,
+            ..Default::default()
+        };
+        let found = |query: &str, options: &SearchOptions| -> Vec<EngramId> {
+            searcher
+                .search(query, options)
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| r.manifest.id)
+                .collect()
+        };
+
+        // One-edit typo: only found in fuzzy mode; a trailing space turns
+        // off prefix matching so only the exact typo matches
+        assert_eq!(
+            found("authenticatio ", &Default::default()),
+            Vec::<EngramId>::new()
+        );
+        assert_eq!(found("authenticatio ", &fuzzy).len(), 2);
+
+        // The exact match outranks the fuzzy one, in either direction
+        assert_eq!(
+            found("authentication", &fuzzy),
+            vec![ids[0].clone(), ids[1].clone()]
+        );
+        assert_eq!(
+            found("authetication", &fuzzy),
+            vec![ids[1].clone(), ids[0].clone()]
+        );
+
+        // A partial identifier as the last word matches as a prefix
+        assert_eq!(
+            found("EngramSess", &Default::default()),
+            vec![ids[0].clone()]
+        );
+        assert!(found("EngramSess ", &Default::default()).is_empty());
+    }
+
+    #[test]
+    fn test_facets_count_all_matches_within_filters() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        for (month, agent, model, file, tag) in [
+            (1, "claude-code", "sonnet", "src/a.rs", "auth"),
+            (1, "claude-code", "opus", "src/b.rs", "auth"),
+            (2, "claude-code", "sonnet", "src/a.rs", "perf"),
+            (2, "aider", "gpt-4o", "src/a.rs", "auth"),
+            (3, "aider", "gpt-4o", "src/c.rs", "perf"),
+        ] {
+            let mut data = engram(agent, model, file, tag, "x");
+            data.manifest.created_at = chrono::NaiveDate::from_ymd_opt(2026, month, 10)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc();
+            writer.index_engram(&data).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+        let facets = |query: &str, options: SearchOptions| {
+            let options = SearchOptions {
+                facets: true,
+                // Facets cover every match, not just the page
+                limit: 1,
+                ..options
+            };
+            searcher.search(query, &options).unwrap().facets.unwrap()
+        };
+        let counts = |pairs: &[(&str, usize)]| -> Vec<(String, usize)> {
+            pairs.iter().map(|(v, n)| (v.to_string(), *n)).collect()
+        };
+
+        let all = facets("jwt", SearchOptions::default());
+        assert_eq!(all.agents, counts(&[("claude-code", 3), ("aider", 2)]));
+        assert_eq!(
+            all.models,
+            counts(&[("gpt-4o", 2), ("sonnet", 2), ("opus", 1)])
+        );
+        assert_eq!(all.tags, counts(&[("auth", 3), ("perf", 2)]));
+        assert_eq!(
+            all.to_string(),
+            "agent: claude-code 3, aider 2\nmodel: gpt-4o 2, sonnet 2, opus 1\ntag: auth 3, perf 2\n"
+        );
+
+        let scoped = facets("file:src/a.rs", SearchOptions::default());
+        assert_eq!(scoped.agents, counts(&[("claude-code", 2), ("aider", 1)]));
+        assert_eq!(scoped.tags, counts(&[("auth", 2), ("perf", 1)]));
+
+        let february = SearchOptions {
+            since: parse_date_bound("2026-02-01", false).ok(),
+            until: parse_date_bound("2026-02-28", true).ok(),
+            ..Default::default()
+        };
+        let dated = facets("", february);
+        assert_eq!(dated.agents, counts(&[("aider", 1), ("claude-code", 1)]));
+        assert_eq!(dated.models, counts(&[("gpt-4o", 1), ("sonnet", 1)]));
+
+        // Off unless asked for
+        assert!(searcher
+            .search("jwt", &SearchOptions::default())
+            .unwrap()
+            .facets
+            .is_none());
+    }
+
+    #[test]
+    fn test_cost_and_token_bounds() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = EngramIndexWriter::open(tmp.path()).unwrap();
+        for (agent, cost, tokens) in [
+            ("cheap", Some(4.99), 99_999),
+            ("exact", Some(5.0), 100_000),
+            ("pricey", Some(12.0), 250_000),
+            ("unpriced", None, 500_000),
+        ] {
+            let mut data = engram(agent, "m", "src/a.rs", "t", "x");
+            data.manifest.token_usage.cost_usd = cost;
+            data.manifest.token_usage.total_tokens = tokens;
+            writer.index_engram(&data).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = EngramSearcher::open(tmp.path()).unwrap();
+        let agents = |options: SearchOptions| -> Vec<String> {
+            let mut agents: Vec<_> = searcher
+                .search("jwt", &options)
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| r.manifest.agent.name)
+                .collect();
+            agents.sort();
+            agents
+        };
+
+        // Bounds are inclusive; no recorded cost never satisfies a cost bound,
+        // even though it is indexed as 0.0
+        let min_cost = SearchOptions {
+            min_cost: Some(5.0),
+            ..Default::default()
+        };
+        assert_eq!(agents(min_cost), ["exact", "pricey"]);
+        let max_cost = SearchOptions {
+            max_cost: Some(5.0),
+            ..Default::default()
+        };
+        assert_eq!(agents(max_cost), ["cheap", "exact"]);
+
+        // Token bounds apply regardless of cost
+        let min_tokens = SearchOptions {
+            min_tokens: Some(100_000),
+            ..Default::default()
+        };
+        assert_eq!(agents(min_tokens), ["exact", "pricey", "unpriced"]);
+        let window = SearchOptions {
+            min_tokens: Some(100_000),
+            max_cost: Some(12.0),
+            ..Default::default()
+        };
+        assert_eq!(agents(window), ["exact", "pricey"]);
+    }
+}
@@ -1,18 +1,24 @@
 use std::path::Path;
 
+use engram_core::config::IndexPolicy;
 use engram_core::storage::GitStorage;
 
 use super::writer::EngramIndexWriter;
 use crate::error::QueryError;
 
-/// Rebuild the index from scratch by reading all engrams from Git.
-pub fn rebuild_index(storage: &GitStorage, index_path: &Path) -> Result<usize, QueryError> {
+/// Rebuild the index from scratch by reading all engrams from Git,
+/// indexing transcripts as `policy` allows.
+pub fn rebuild_index(
+    storage: &GitStorage,
+    index_path: &Path,
+    policy: IndexPolicy,
+) -> Result<usize, QueryError> {
     // Remove existing index
     if index_path.exists() {
         std::fs::remove_dir_all(index_path).map_err(QueryError::Io)?;
     }
 
-    let mut writer = EngramIndexWriter::open(index_path)?;
+    let mut writer = EngramIndexWriter::open(index_path)?.with_policy(policy);
     let manifests = storage.list(&Default::default())?;
 
     let mut count = 0;
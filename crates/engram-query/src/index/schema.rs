@@ -1,5 +1,30 @@
 use tantivy::schema::*;
 
+/// Bumped whenever fields change; an index built with another version is
+/// rebuilt before use.
+pub const SCHEMA_VERSION: u32 = 9;
+
+/// File in the index directory recording its [`SCHEMA_VERSION`].
+pub const SCHEMA_VERSION_FILE: &str = "engram-schema-version";
+
+/// Schema version of the index at `path`, if one exists. Indexes from
+/// before versioning report version 1.
+pub fn stored_version(path: &std::path::Path) -> Option<u32> {
+    if !path.join("meta.json").exists() {
+        return None;
+    }
+    let version = std::fs::read_to_string(path.join(SCHEMA_VERSION_FILE))
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(1);
+    Some(version)
+}
+
+/// Whether the index at `path` exists and was built with the current schema.
+pub fn is_current(path: &std::path::Path) -> bool {
+    stored_version(path) == Some(SCHEMA_VERSION)
+}
+
 /// Holds field handles for the engram Tantivy schema.
 pub struct EngramSchema {
     pub schema: Schema,
@@ -7,13 +32,26 @@ pub struct EngramSchema {
     pub intent_request: Field,
     pub intent_summary: Field,
     pub transcript_text: Field,
+    /// Whether `transcript_text` was cut to the index's byte budget.
+    pub transcript_truncated: Field,
     pub agent_name: Field,
     pub agent_model: Field,
     pub created_at: Field,
     pub file_paths: Field,
     pub dead_ends: Field,
+    /// "description: rationale" per decision.
+    pub decisions: Field,
+    /// Distinct tool names from the session's tool calls.
+    pub tool_names: Field,
+    /// 0.0 when the cost is unknown, so sorting works; see `has_cost`.
     pub cost_usd: Field,
+    /// Whether the engram has a recorded cost, for cost-bounded searches.
+    pub has_cost: Field,
+    /// Whether a tool call failed or a shell command exited non-zero.
+    pub has_errors: Field,
     pub total_tokens: Field,
+    /// Untokenized, so `tag:` filters and free text both match whole tags.
+    pub tags: Field,
     pub manifest_json: Field,
 }
 
@@ -24,14 +62,20 @@ impl EngramSchema {
         let id = builder.add_text_field("id", STRING | STORED);
         let intent_request = builder.add_text_field("intent_request", TEXT | STORED);
         let intent_summary = builder.add_text_field("intent_summary", TEXT | STORED);
-        let transcript_text = builder.add_text_field("transcript_text", TEXT);
-        let agent_name = builder.add_text_field("agent_name", STRING | STORED);
-        let agent_model = builder.add_text_field("agent_model", STRING | STORED);
-        let created_at = builder.add_date_field("created_at", INDEXED | STORED);
+        let transcript_text = builder.add_text_field("transcript_text", TEXT | STORED);
+        let transcript_truncated = builder.add_bool_field("transcript_truncated", STORED);
+        let agent_name = builder.add_text_field("agent_name", STRING | STORED | FAST);
+        let agent_model = builder.add_text_field("agent_model", STRING | STORED | FAST);
+        let created_at = builder.add_date_field("created_at", INDEXED | STORED | FAST);
         let file_paths = builder.add_text_field("file_paths", TEXT | STORED);
         let dead_ends = builder.add_text_field("dead_ends", TEXT | STORED);
-        let cost_usd = builder.add_f64_field("cost_usd", INDEXED | STORED);
-        let total_tokens = builder.add_u64_field("total_tokens", INDEXED | STORED);
+        let decisions = builder.add_text_field("decisions", TEXT | STORED);
+        let tool_names = builder.add_text_field("tool_names", TEXT | STORED);
+        let cost_usd = builder.add_f64_field("cost_usd", INDEXED | STORED | FAST);
+        let has_cost = builder.add_bool_field("has_cost", INDEXED);
+        let has_errors = builder.add_bool_field("has_errors", INDEXED);
+        let total_tokens = builder.add_u64_field("total_tokens", INDEXED | STORED | FAST);
+        let tags = builder.add_text_field("tags", STRING | FAST);
         let manifest_json = builder.add_text_field("manifest_json", STORED);
 
         let schema = builder.build();
@@ -42,13 +86,19 @@ impl EngramSchema {
             intent_request,
             intent_summary,
             transcript_text,
+            transcript_truncated,
             agent_name,
             agent_model,
             created_at,
             file_paths,
             dead_ends,
+            decisions,
+            tool_names,
             cost_usd,
+            has_cost,
+            has_errors,
             total_tokens,
+            tags,
             manifest_json,
         }
     }
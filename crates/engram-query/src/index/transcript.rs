@@ -0,0 +1,181 @@
+use engram_core::config::IndexPolicy;
+use engram_core::model::{Transcript, TranscriptContent};
+
+/// Put where the middle of an over-budget transcript was cut out.
+const ELISION: &str = "\n…\n";
+
+/// Runs of base64 or hex characters at least this long are taken for
+/// encoded data, not words.
+const MIN_ENCODED_RUN: usize = 80;
+
+/// A transcript's text as indexed under `policy`, and whether it was cut to
+/// fit the byte budget. Text entries are kept, and tool outputs within the
+/// per-entry cap; encoded and binary runs are left out of both.
+pub fn indexed_text(transcript: &Transcript, policy: &IndexPolicy) -> (String, bool) {
+    let tool_output_max = usize::try_from(policy.tool_output_max_bytes).unwrap_or(usize::MAX);
+    let text = transcript
+        .entries
+        .iter()
+        .filter_map(|e| match &e.content {
+            TranscriptContent::Text { text } => Some(text.as_str()),
+            TranscriptContent::ToolResult { output, .. } if output.len() <= tool_output_max => {
+                Some(output.as_str())
+            }
+            _ => None,
+        })
+        .map(without_encoded_runs)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let max = usize::try_from(policy.transcript_max_bytes).unwrap_or(usize::MAX);
+    if max == 0 || text.len() <= max {
+        return (text, false);
+    }
+    (start_and_end(&text, max), true)
+}
+
+/// `text` with its whitespace-separated runs of encoded or binary data
+/// dropped, keeping the whitespace around them.
+fn without_encoded_runs(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end_matches(char::is_whitespace);
+        if is_encoded(word) {
+            out.push_str(&piece[word.len()..]);
+        } else {
+            out.push_str(piece);
+        }
+    }
+    out
+}
+
+/// Whether `word` looks like base64, hex or binary rather than text.
+fn is_encoded(word: &str) -> bool {
+    if word.chars().any(|c| c == '\u{FFFD}' || c.is_control()) {
+        return true;
+    }
+    word.len() >= MIN_ENCODED_RUN
+        && word
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=' | b'-' | b'_'))
+}
+
+/// The start and end of `text`, in equal parts, joined by [`ELISION`] and
+/// at most `max` bytes long in all.
+fn start_and_end(text: &str, max: usize) -> String {
+    if max <= ELISION.len() {
+        let mut end = max;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        return text[..end].to_string();
+    }
+    let budget = max - ELISION.len();
+    let mut head = budget / 2;
+    while !text.is_char_boundary(head) {
+        head -= 1;
+    }
+    let mut tail = text.len() - (budget - budget / 2);
+    while !text.is_char_boundary(tail) {
+        tail += 1;
+    }
+    format!("{}{ELISION}{}", &text[..head], &text[tail..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram_core::model::{Role, TranscriptEntry};
+
+    fn entry(content: TranscriptContent) -> TranscriptEntry {
+        TranscriptEntry {
+            timestamp: chrono::Utc::now(),
+            role: Role::Assistant,
+            content,
+            token_count: None,
+        }
+    }
+
+    fn text(text: &str) -> TranscriptEntry {
+        entry(TranscriptContent::Text { text: text.into() })
+    }
+
+    fn tool_result(output: &str) -> TranscriptEntry {
+        entry(TranscriptContent::ToolResult {
+            tool_id: "t1".into(),
+            output: output.into(),
+            is_error: false,
+        })
+    }
+
+    fn policy(transcript_max_bytes: u64, tool_output_max_bytes: u64) -> IndexPolicy {
+        IndexPolicy {
+            transcript_max_bytes,
+            tool_output_max_bytes,
+        }
+    }
+
+    #[test]
+    fn test_budget_keeps_start_and_end() {
+        let transcript = Transcript {
+            entries: (0..2000)
+                .map(|i| text(&format!("step {i}: résumé of the migration")))
+                .collect(),
+        };
+        let full = transcript
+            .entries
+            .iter()
+            .map(|e| match &e.content {
+                TranscriptContent::Text { text } => text.as_str(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let (indexed, truncated) = indexed_text(&transcript, &policy(1000, 0));
+        assert!(truncated);
+        assert!(indexed.len() <= 1000, "{} bytes", indexed.len());
+        assert!(indexed.len() >= 990, "{} bytes", indexed.len());
+        assert!(indexed.starts_with("step 0: "));
+        assert!(indexed.ends_with("step 1999: résumé of the migration"));
+        assert!(!indexed.contains("step 1000:"));
+
+        let (indexed, truncated) = indexed_text(&transcript, &policy(0, 0));
+        assert!(!truncated);
+        assert_eq!(indexed, full);
+
+        let (indexed, truncated) = indexed_text(&transcript, &policy(full.len() as u64, 0));
+        assert!(!truncated);
+        assert_eq!(indexed.len(), full.len());
+    }
+
+    #[test]
+    fn test_tool_outputs_within_the_cap() {
+        let transcript = Transcript {
+            entries: vec![
+                text("Run the tests"),
+                tool_result("2 failed: test_login"),
+                tool_result(&"warning: unused import\n".repeat(100)),
+            ],
+        };
+        let (indexed, _) = indexed_text(&transcript, &policy(0, 100));
+        assert_eq!(indexed, "Run the tests\n2 failed: test_login");
+
+        let (indexed, _) = indexed_text(&transcript, &policy(0, 0));
+        assert_eq!(indexed, "Run the tests");
+    }
+
+    #[test]
+    fn test_encoded_runs_are_left_out() {
+        let base64 = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+        let transcript = Transcript {
+            entries: vec![
+                text(&format!("The logo is\n{base64}\nin the header")),
+                tool_result("PNG\u{FFFD}\u{FFFD}\0 decoded"),
+            ],
+        };
+        let (indexed, truncated) = indexed_text(&transcript, &policy(0, 1000));
+        assert!(!truncated);
+        assert_eq!(indexed, "The logo is\n\nin the header\n decoded");
+    }
+}
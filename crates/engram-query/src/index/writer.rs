@@ -1,56 +1,80 @@
 use std::path::Path;
+use std::time::{Duration, Instant};
 
 use tantivy::doc;
-use tantivy::{Index, IndexWriter};
+use tantivy::{Index, IndexWriter, TantivyError};
 
-use engram_core::model::{EngramData, TranscriptContent};
+use engram_core::config::IndexPolicy;
+use engram_core::model::EngramData;
 
-use super::schema::EngramSchema;
+use super::schema::{stored_version, EngramSchema, SCHEMA_VERSION, SCHEMA_VERSION_FILE};
+use super::transcript::indexed_text;
 use crate::error::QueryError;
 
+/// How long [`EngramIndexWriter::open`] waits for another process (an
+/// import running alongside a recording, say) to release the index.
+pub const WRITER_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest pause between attempts to take the index lock.
+const MAX_LOCK_RETRY_DELAY: Duration = Duration::from_millis(500);
+
 /// Writes engrams to the Tantivy index.
 pub struct EngramIndexWriter {
     schema: EngramSchema,
     index: Index,
     writer: IndexWriter,
+    policy: IndexPolicy,
 }
 
 impl EngramIndexWriter {
-    /// Open or create an index at the given path.
+    /// Open or create an index at the given path, waiting up to
+    /// [`WRITER_LOCK_TIMEOUT`] for another writer to release it.
     pub fn open(path: &Path) -> Result<Self, QueryError> {
+        Self::open_waiting(path, WRITER_LOCK_TIMEOUT)
+    }
+
+    /// Like [`Self::open`], but waiting up to `timeout` for the lock before
+    /// failing with [`QueryError::IndexLocked`].
+    pub fn open_waiting(path: &Path, timeout: Duration) -> Result<Self, QueryError> {
         let schema = EngramSchema::new();
-        let index = if path.exists() && path.join("meta.json").exists() {
+        let index = if let Some(found) = stored_version(path) {
+            if found != SCHEMA_VERSION {
+                return Err(QueryError::OutdatedIndex {
+                    found,
+                    current: SCHEMA_VERSION,
+                });
+            }
             Index::open_in_dir(path)?
         } else {
             std::fs::create_dir_all(path).map_err(QueryError::Io)?;
-            Index::create_in_dir(path, schema.schema.clone())?
+            let index = Index::create_in_dir(path, schema.schema.clone())?;
+            std::fs::write(path.join(SCHEMA_VERSION_FILE), SCHEMA_VERSION.to_string())
+                .map_err(QueryError::Io)?;
+            index
         };
 
-        // 50MB heap for indexing
-        let writer = index.writer(50_000_000)?;
+        let writer = lock_writer(&index, path, timeout)?;
 
         Ok(Self {
             schema,
             index,
             writer,
+            policy: IndexPolicy::default(),
         })
     }
 
+    /// Index transcripts as `policy` allows (default:
+    /// [`IndexPolicy::default`]).
+    pub fn with_policy(mut self, policy: IndexPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
     /// Index a single engram.
     pub fn index_engram(&mut self, data: &EngramData) -> Result<(), QueryError> {
         let s = &self.schema;
 
-        // Concatenate transcript text entries
-        let transcript_text: String = data
-            .transcript
-            .entries
-            .iter()
-            .filter_map(|e| match &e.content {
-                TranscriptContent::Text { text } => Some(text.as_str()),
-                _ => None,
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+        let (transcript_text, transcript_truncated) = indexed_text(&data.transcript, &self.policy);
 
         // Concatenate file paths
         let file_paths: String = data
@@ -70,26 +94,50 @@ impl EngramIndexWriter {
             .collect::<Vec<_>>()
             .join("\n");
 
+        let decisions: String = data
+            .intent
+            .decisions
+            .iter()
+            .map(|d| format!("{}: {}", d.description, d.rationale))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut tool_names: Vec<&str> = Vec::new();
+        for call in &data.operations.tool_calls {
+            if !tool_names.contains(&call.tool_name.as_str()) {
+                tool_names.push(&call.tool_name);
+            }
+        }
+
         // Convert chrono to tantivy datetime
         let created_at =
             tantivy::DateTime::from_timestamp_secs(data.manifest.created_at.timestamp());
 
         let manifest_json = serde_json::to_string(&data.manifest)?;
 
-        self.writer.add_document(doc!(
+        let mut document = doc!(
             s.id => data.manifest.id.as_str(),
             s.intent_request => data.intent.original_request.as_str(),
             s.intent_summary => data.intent.summary.as_deref().unwrap_or(""),
             s.transcript_text => transcript_text,
+            s.transcript_truncated => transcript_truncated,
             s.agent_name => data.manifest.agent.name.as_str(),
             s.agent_model => data.manifest.agent.model.as_deref().unwrap_or(""),
             s.created_at => created_at,
             s.file_paths => file_paths,
             s.dead_ends => dead_ends,
+            s.decisions => decisions,
+            s.tool_names => tool_names.join("\n"),
             s.cost_usd => data.manifest.token_usage.cost_usd.unwrap_or(0.0),
+            s.has_cost => data.manifest.token_usage.cost_usd.is_some(),
+            s.has_errors => data.operations.has_errors(),
             s.total_tokens => data.manifest.token_usage.total_tokens,
             s.manifest_json => manifest_json,
-        ))?;
+        );
+        for tag in &data.manifest.tags {
+            document.add_text(s.tags, tag);
+        }
+        self.writer.add_document(document)?;
 
         Ok(())
     }
@@ -112,3 +160,25 @@ impl EngramIndexWriter {
         &self.index
     }
 }
+
+/// A writer for `index`, retrying with backoff while another process holds
+/// its lock.
+fn lock_writer(index: &Index, path: &Path, timeout: Duration) -> Result<IndexWriter, QueryError> {
+    let started = Instant::now();
+    let mut delay = Duration::from_millis(20);
+    loop {
+        // 50MB heap for indexing
+        match index.writer(50_000_000) {
+            Ok(writer) => return Ok(writer),
+            Err(TantivyError::LockFailure(..)) => {
+                let left = timeout.saturating_sub(started.elapsed());
+                if left.is_zero() {
+                    return Err(QueryError::IndexLocked(path.to_path_buf()));
+                }
+                std::thread::sleep(delay.min(left));
+                delay = (delay * 2).min(MAX_LOCK_RETRY_DELAY);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
@@ -1,15 +1,40 @@
+pub mod churn;
+pub mod compare;
+pub mod dedupe;
 pub mod diff;
 pub mod error;
+pub mod failures;
 pub mod graph;
+pub mod grep;
 pub mod index;
+pub mod multi;
+pub mod related;
+pub mod renames;
 pub mod review;
+pub mod scan;
 pub mod search;
+pub mod stats;
 pub mod trace;
+pub mod usage;
 
+pub use churn::{file_churn, ChangeCounts, FileChurn};
+pub use compare::{compare_engrams, ComparedEngram, EngramComparison, Presence};
+pub use dedupe::{find_duplicates, DuplicateEngram, DuplicateGroup, DuplicateReason};
 pub use diff::{diff_engrams, EngramDiff};
 pub use error::QueryError;
-pub use graph::{build_graph, ContextGraph};
-pub use index::{EngramSearcher, SearchResult};
-pub use review::{review_branch, BranchReview};
+pub use failures::{find_failures, FailedCommand, FailureSummary};
+pub use graph::{build_graph, build_graph_with, ContextGraph, GraphFilter, GraphOptions};
+pub use grep::{grep_engrams, GrepField, GrepMatch, GrepOptions, GrepResults};
+pub use index::{
+    EngramSearcher, SearchFacets, SearchOptions, SearchPage, SearchResult, SearchSort,
+};
+pub use multi::{MultiRepoPage, MultiRepoSearch, SkippedRepo};
+pub use related::{related_engrams, RelatedEngram, Relation};
+pub use renames::Renames;
+pub use review::{
+    review_branch, BranchReview, FileContribution, FileReview, Linkage, ReviewOptions,
+};
 pub use search::SearchEngine;
+pub use stats::{agent_stats, agent_stats_for, usage_by, AgentStats, GroupUsage, StatsDimension};
 pub use trace::{trace_file, TraceEntry};
+pub use usage::{usage_buckets, usage_over_time, AgentUsage, Bucket, UsageBucket, UsageTotals};
@@ -0,0 +1,274 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use engram_core::storage::GitStorage;
+use serde::Deserialize;
+
+use crate::error::QueryError;
+use crate::index::schema::{is_current, stored_version};
+use crate::index::{
+    EngramSearcher, SearchFacets, SearchOptions, SearchPage, SearchResult, SearchSort,
+};
+use crate::search::SearchEngine;
+
+/// Searches several repositories' indexes at once.
+///
+/// Each repository is searched with its own index, so relevance scores are
+/// only roughly comparable across repositories. A repository whose index is
+/// missing or outdated is skipped rather than built, since building it would
+/// write into that repository; [`MultiRepoPage::skipped`] says which and why.
+pub struct MultiRepoSearch {
+    repos: Vec<PathBuf>,
+}
+
+/// A repository left out of a [`MultiRepoSearch`].
+#[derive(Debug, Clone)]
+pub struct SkippedRepo {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// Merged results of a [`MultiRepoSearch`]; each result's `repo` is set.
+#[derive(Debug, Clone)]
+pub struct MultiRepoPage {
+    pub page: SearchPage,
+    pub skipped: Vec<SkippedRepo>,
+}
+
+#[derive(Deserialize)]
+struct ReposConfig {
+    #[serde(default)]
+    repos: Vec<String>,
+}
+
+impl MultiRepoSearch {
+    /// Search the given repositories; duplicates are dropped.
+    pub fn new(repos: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut seen = HashSet::new();
+        let repos = repos
+            .into_iter()
+            .filter(|p| seen.insert(p.canonicalize().unwrap_or_else(|_| p.clone())))
+            .collect();
+        Self { repos }
+    }
+
+    /// `~/.config/engram/repos.toml`, or `None` if `HOME` is unset.
+    pub fn default_config_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME")?;
+        Some(PathBuf::from(home).join(".config/engram/repos.toml"))
+    }
+
+    /// Read repository paths from a config file such as
+    ///
+    /// ```toml
+    /// repos = ["~/src/api", "/work/web"]
+    /// ```
+    ///
+    /// A leading `~/` is expanded to `HOME`.
+    pub fn load_config(path: &Path) -> Result<Vec<PathBuf>, QueryError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| QueryError::Config(format!("{}: {e}", path.display())))?;
+        let config: ReposConfig = toml::from_str(&text)
+            .map_err(|e| QueryError::Config(format!("{}: {e}", path.display())))?;
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        Ok(config
+            .repos
+            .iter()
+            .map(|repo| match (repo.strip_prefix("~/"), &home) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(repo),
+            })
+            .collect())
+    }
+
+    pub fn repos(&self) -> &[PathBuf] {
+        &self.repos
+    }
+
+    /// Run `query` against every repository and merge the results in
+    /// `options.sort` order, then apply `options.offset` and `options.limit`
+    /// to the merged list.
+    pub fn search(&self, query: &str, options: &SearchOptions) -> MultiRepoPage {
+        // Each repository must supply enough results to fill the merged page
+        let per_repo = SearchOptions {
+            limit: options.offset + options.limit,
+            offset: 0,
+            ..options.clone()
+        };
+
+        let mut results = Vec::new();
+        let mut total = 0;
+        let mut facets: Option<SearchFacets> = None;
+        let mut skipped = Vec::new();
+        for repo in &self.repos {
+            match search_repo(repo, query, &per_repo) {
+                Ok(page) => {
+                    total += page.total;
+                    results.extend(page.results.into_iter().map(|mut r| {
+                        r.repo = Some(repo.clone());
+                        r
+                    }));
+                    if let Some(page_facets) = page.facets {
+                        facets
+                            .get_or_insert_with(Default::default)
+                            .merge(page_facets);
+                    }
+                }
+                Err(reason) => skipped.push(SkippedRepo {
+                    path: repo.clone(),
+                    reason,
+                }),
+            }
+        }
+
+        results.sort_by(|a, b| compare(options.sort, a, b));
+        let results = results
+            .into_iter()
+            .skip(options.offset)
+            .take(options.limit)
+            .collect();
+        MultiRepoPage {
+            page: SearchPage {
+                results,
+                total,
+                offset: options.offset,
+                facets: facets.filter(|_| options.facets),
+            },
+            skipped,
+        }
+    }
+}
+
+/// Search one repository's existing index, or say why it can't be searched.
+fn search_repo(repo: &Path, query: &str, options: &SearchOptions) -> Result<SearchPage, String> {
+    let storage = GitStorage::open(repo).map_err(|e| format!("not a Git repository ({e})"))?;
+    if !storage.is_initialized() {
+        return Err("engram is not initialized".into());
+    }
+    let engine = SearchEngine::open(&storage).map_err(|e| e.to_string())?;
+    let index_path = engine.index_path();
+    if !is_current(index_path) {
+        return Err(match stored_version(index_path) {
+            Some(_) => "search index is outdated; run `engram reindex` there".into(),
+            None => "no search index; run `engram reindex` there".into(),
+        });
+    }
+    EngramSearcher::open(index_path)
+        .and_then(|searcher| searcher.search(query, options))
+        .map_err(|e| e.to_string())
+}
+
+fn compare(sort: SearchSort, a: &SearchResult, b: &SearchResult) -> Ordering {
+    let (a, b, score) = (&a.manifest, &b.manifest, (a.score, b.score));
+    match sort {
+        SearchSort::Relevance => score.1.total_cmp(&score.0),
+        SearchSort::CreatedDesc => b.created_at.cmp(&a.created_at),
+        SearchSort::CreatedAsc => a.created_at.cmp(&b.created_at),
+        SearchSort::CostDesc => {
+            let cost = |m: &engram_core::model::Manifest| m.token_usage.cost_usd.unwrap_or(0.0);
+            cost(b).total_cmp(&cost(a))
+        }
+        SearchSort::TokensDesc => b.token_usage.total_tokens.cmp(&a.token_usage.total_tokens),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(request: &str, tokens: u64) -> EngramData {
+        EngramData::builder()
+            .request(request)
+            .total_tokens(tokens)
+            .build()
+    }
+
+    /// A repository holding engrams with the given requests and token counts.
+    fn repo(engrams: &[(&str, u64)], indexed: bool) -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        for (request, tokens) in engrams {
+            storage.create(&engram(request, *tokens)).unwrap();
+        }
+        if indexed {
+            SearchEngine::open(&storage)
+                .unwrap()
+                .rebuild(&storage)
+                .unwrap();
+        }
+        tmp
+    }
+
+    #[test]
+    fn test_merges_results_across_repos() {
+        let api = repo(&[("add a rate limiter", 300), ("rotate keys", 10)], true);
+        let web = repo(&[("rate limiter for uploads", 200)], true);
+        let multi = MultiRepoSearch::new([api.path().into(), web.path().into()]);
+
+        let options = SearchOptions {
+            sort: SearchSort::TokensDesc,
+            ..Default::default()
+        };
+        let found = multi.search("limiter", &options);
+        assert!(found.skipped.is_empty());
+        assert_eq!(found.page.total, 2);
+        let repos: Vec<_> = found.page.results.iter().map(|r| r.repo.clone()).collect();
+        assert_eq!(repos, [Some(api.path().into()), Some(web.path().into())]);
+
+        // Paging applies to the merged order
+        let second = SearchOptions {
+            offset: 1,
+            limit: 1,
+            ..options
+        };
+        let found = multi.search("limiter", &second);
+        assert_eq!(found.page.results.len(), 1);
+        assert_eq!(found.page.results[0].repo.as_deref(), Some(web.path()));
+    }
+
+    #[test]
+    fn test_skips_repos_without_index() {
+        let indexed = repo(&[("add a rate limiter", 1)], true);
+        let unindexed = repo(&[("rate limiter again", 1)], false);
+        let missing = indexed.path().join("no-such-repo");
+        let multi = MultiRepoSearch::new([
+            indexed.path().into(),
+            unindexed.path().into(),
+            missing.clone(),
+        ]);
+
+        let found = multi.search("limiter", &SearchOptions::default());
+        assert_eq!(found.page.total, 1);
+        let skipped: Vec<_> = found.skipped.iter().map(|s| s.path.clone()).collect();
+        assert_eq!(skipped, [unindexed.path().to_path_buf(), missing]);
+        assert!(found.skipped[0].reason.contains("engram reindex"));
+        // Skipping must not build the index behind the user's back
+        let storage = GitStorage::open(unindexed.path()).unwrap();
+        let engine = SearchEngine::open(&storage).unwrap();
+        assert_eq!(engine.index_version(), None);
+    }
+
+    #[test]
+    fn test_load_config() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("repos.toml");
+        std::fs::write(&path, "repos = [\"/work/api\", \"/work/web\"]\n").unwrap();
+        let repos = MultiRepoSearch::load_config(&path).unwrap();
+        assert_eq!(
+            repos,
+            [PathBuf::from("/work/api"), PathBuf::from("/work/web")]
+        );
+
+        std::fs::write(&path, "repos = \"/work/api\"\n").unwrap();
+        assert!(matches!(
+            MultiRepoSearch::load_config(&path),
+            Err(QueryError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use engram_core::model::{EngramData, Manifest};
+use engram_core::storage::GitStorage;
+
+use crate::error::QueryError;
+use crate::search::SearchEngine;
+
+/// Weight of each kind of overlap in a [`RelatedEngram::score`]; file and
+/// commit overlap say more about shared work than a shared tag or wording.
+const FILE_WEIGHT: f64 = 1.0;
+const COMMIT_WEIGHT: f64 = 1.0;
+const TAG_WEIGHT: f64 = 0.25;
+const TEXT_WEIGHT: f64 = 0.5;
+
+/// Candidates fetched from the more-like-this query.
+const TEXT_CANDIDATES: usize = 50;
+
+/// How a related engram is connected to the one asked about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    /// Its lineage parent.
+    Parent,
+    /// One of its lineage children.
+    Child,
+    /// Overlapping files, tags, commits or intent.
+    Similar,
+}
+
+/// An engram related to another, with why.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedEngram {
+    pub manifest: Manifest,
+    pub relation: Relation,
+    /// Weighted sum of Jaccard overlaps and text similarity; 0.0 for lineage,
+    /// which is listed regardless of overlap.
+    pub score: f64,
+    /// One line per kind of overlap, e.g. `shared files: src/auth.rs`.
+    pub reasons: Vec<String>,
+}
+
+/// Engrams related to `id` (an ID, prefix or `HEAD`): its lineage parent and
+/// children first, then up to `limit` others ranked by overlap of files, tags
+/// and git commits, plus intent similarity when the search index has been
+/// built.
+pub fn related_engrams(
+    storage: &GitStorage,
+    id: &str,
+    limit: usize,
+) -> Result<Vec<RelatedEngram>, QueryError> {
+    let target = storage.read(&storage.resolve(id)?)?;
+    let target_id = target.manifest.id.as_str();
+
+    let text = match &target.intent.summary {
+        Some(summary) => format!("{}\n{summary}", target.intent.original_request),
+        None => target.intent.original_request.clone(),
+    };
+    let similar_text = SearchEngine::open(storage)?.more_like_this(&text, TEXT_CANDIDATES)?;
+    // Relative to the best match, since raw scores depend on the index
+    let best = similar_text.iter().map(|(_, s)| *s).fold(0.0, f32::max);
+    let text_scores: HashMap<String, f64> = similar_text
+        .into_iter()
+        .filter(|_| best > 0.0)
+        .map(|(id, score)| (id, f64::from(score / best)))
+        .collect();
+
+    let mut lineage = Vec::new();
+    let mut similar = Vec::new();
+    for manifest in storage.list(&Default::default())? {
+        let other_id = manifest.id.as_str();
+        if other_id == target_id {
+            continue;
+        }
+        let data = match storage.read(other_id) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("Failed to read engram {other_id}: {e}");
+                continue;
+            }
+        };
+
+        let relation = if target.lineage.parent_engram.as_ref() == Some(&manifest.id)
+            || data.lineage.child_engrams.contains(&target.manifest.id)
+        {
+            Some(Relation::Parent)
+        } else if target.lineage.child_engrams.contains(&manifest.id)
+            || data.lineage.parent_engram.as_ref() == Some(&target.manifest.id)
+        {
+            Some(Relation::Child)
+        } else {
+            None
+        };
+        if let Some(relation) = relation {
+            lineage.push(RelatedEngram {
+                manifest,
+                relation,
+                score: 0.0,
+                reasons: Vec::new(),
+            });
+            continue;
+        }
+
+        let (score, reasons) = overlap(&target, &data, text_scores.get(other_id).copied());
+        if score > 0.0 {
+            similar.push(RelatedEngram {
+                manifest,
+                relation: Relation::Similar,
+                score,
+                reasons,
+            });
+        }
+    }
+
+    lineage.sort_by_key(|r| (r.relation != Relation::Parent, r.manifest.created_at));
+    similar.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.manifest.created_at.cmp(&a.manifest.created_at))
+    });
+    similar.truncate(limit);
+    lineage.extend(similar);
+    Ok(lineage)
+}
+
+/// Score and reasons for how much `other` overlaps `target`.
+fn overlap(target: &EngramData, other: &EngramData, text: Option<f64>) -> (f64, Vec<String>) {
+    let mut score = 0.0;
+    let mut reasons = Vec::new();
+    let mut add = |label: &str, weight: f64, a: HashSet<&str>, b: HashSet<&str>| {
+        let mut shared: Vec<&str> = a.intersection(&b).copied().collect();
+        if shared.is_empty() {
+            return;
+        }
+        shared.sort_unstable();
+        score += weight * shared.len() as f64 / a.union(&b).count() as f64;
+        reasons.push(format!("shared {label}: {}", shared.join(", ")));
+    };
+
+    add("files", FILE_WEIGHT, files(target), files(other));
+    add("commits", COMMIT_WEIGHT, commits(target), commits(other));
+    add("tags", TAG_WEIGHT, tags(target), tags(other));
+    if let Some(text) = text.filter(|t| *t > 0.0) {
+        score += TEXT_WEIGHT * text;
+        reasons.push("similar intent".to_string());
+    }
+    (score, reasons)
+}
+
+fn files(data: &EngramData) -> HashSet<&str> {
+    data.operations
+        .file_changes
+        .iter()
+        .map(|fc| fc.path.as_str())
+        .collect()
+}
+
+fn commits(data: &EngramData) -> HashSet<&str> {
+    data.manifest
+        .git_commits
+        .iter()
+        .chain(&data.lineage.git_commits)
+        .map(String::as_str)
+        .collect()
+}
+
+fn tags(data: &EngramData) -> HashSet<&str> {
+    data.manifest.tags.iter().map(String::as_str).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(request: &str, files: &[&str], tags: &[&str]) -> EngramData {
+        let builder = EngramData::builder().request(request).summary(request);
+        let builder = files.iter().fold(builder, |b, path| b.file(path));
+        tags.iter().fold(builder, |b, tag| b.tag(tag)).build()
+    }
+
+    #[test]
+    fn test_file_overlap_outranks_tag_overlap() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let target = engram("add login", &["src/auth.rs", "src/session.rs"], &["auth"]);
+        let target_id = storage.create(&target).unwrap();
+        storage
+            .create(&engram("fix tokens", &["src/auth.rs", "src/token.rs"], &[]))
+            .unwrap();
+        storage
+            .create(&engram("update docs", &["README.md"], &["auth"]))
+            .unwrap();
+        storage
+            .create(&engram("bump deps", &["Cargo.toml"], &[]))
+            .unwrap();
+        let mut child = engram("follow up", &[], &[]);
+        child.lineage.parent_engram = Some(target_id.clone());
+        storage.create(&child).unwrap();
+
+        let related = related_engrams(&storage, target_id.as_str(), 10).unwrap();
+        let summaries: Vec<_> = related
+            .iter()
+            .map(|r| (r.manifest.summary.as_deref().unwrap(), r.relation))
+            .collect();
+        // Lineage first; the unrelated engram is left out
+        assert_eq!(
+            summaries,
+            [
+                ("follow up", Relation::Child),
+                ("fix tokens", Relation::Similar),
+                ("update docs", Relation::Similar),
+            ]
+        );
+        assert_eq!(related[0].score, 0.0);
+        assert!(related[1].score > related[2].score);
+        assert_eq!(related[1].reasons, ["shared files: src/auth.rs"]);
+        assert_eq!(related[2].reasons, ["shared tags: auth"]);
+
+        let limited = related_engrams(&storage, target_id.as_str(), 1).unwrap();
+        assert_eq!(limited.len(), 2);
+    }
+
+    #[test]
+    fn test_similar_intent_when_indexed() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let target = storage
+            .create(&engram("throttle webhook retries", &[], &[]))
+            .unwrap();
+        storage
+            .create(&engram("webhook retries storm the queue", &[], &[]))
+            .unwrap();
+        storage
+            .create(&engram("rename config keys", &[], &[]))
+            .unwrap();
+
+        // No index: no text similarity, and nothing else overlaps
+        assert!(related_engrams(&storage, target.as_str(), 10)
+            .unwrap()
+            .is_empty());
+
+        let engine = SearchEngine::open(&storage).unwrap();
+        engine.rebuild(&storage).unwrap();
+        let related = related_engrams(&storage, target.as_str(), 10).unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(
+            related[0].manifest.summary.as_deref(),
+            Some("webhook retries storm the queue")
+        );
+        assert_eq!(related[0].reasons, ["similar intent"]);
+    }
+}
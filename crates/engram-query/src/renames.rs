@@ -0,0 +1,101 @@
+use std::collections::{HashMap, HashSet};
+
+use engram_core::model::{FileChange, FileChangeType};
+
+/// Renames recorded by `Renamed` file changes, for following a file across
+/// its names. Record engrams oldest first, so that when a name is renamed
+/// more than once the later rename wins.
+#[derive(Debug, Clone, Default)]
+pub struct Renames {
+    renamed_to: HashMap<String, String>,
+    renamed_from: HashMap<String, Vec<String>>,
+}
+
+impl Renames {
+    /// Record the renames among `changes`.
+    pub fn record<'a>(&mut self, changes: impl IntoIterator<Item = &'a FileChange>) {
+        for fc in changes {
+            if let FileChangeType::Renamed { from } = &fc.change_type {
+                if *from == fc.path {
+                    continue;
+                }
+                self.renamed_to.insert(from.clone(), fc.path.clone());
+                let earlier = self.renamed_from.entry(fc.path.clone()).or_default();
+                if !earlier.contains(from) {
+                    earlier.push(from.clone());
+                }
+            }
+        }
+    }
+
+    /// The name `path` was last renamed to, following renames of renames.
+    /// A rename cycle ends at the last name before it repeats.
+    pub fn latest_name(&self, path: &str) -> String {
+        let mut name = path;
+        let mut seen = HashSet::from([path]);
+        while let Some(next) = self
+            .renamed_to
+            .get(name)
+            .filter(|next| seen.insert(next.as_str()))
+        {
+            name = next;
+        }
+        name.to_string()
+    }
+
+    /// `path` followed by every name it was renamed from, most recent name
+    /// first.
+    pub fn chain(&self, path: &str) -> Vec<String> {
+        let mut chain = vec![path.to_string()];
+        let mut next = 0;
+        // Breadth-first; `chain` doubles as the visited set, so rename
+        // cycles end
+        while let Some(name) = chain.get(next).cloned() {
+            next += 1;
+            for from in self.renamed_from.get(&name).into_iter().flatten() {
+                if !chain.contains(from) {
+                    chain.push(from.clone());
+                }
+            }
+        }
+        chain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renamed(from: &str, to: &str) -> FileChange {
+        FileChange {
+            path: to.into(),
+            change_type: FileChangeType::Renamed { from: from.into() },
+            lines_added: None,
+            lines_removed: None,
+        }
+    }
+
+    #[test]
+    fn test_renames_are_followed_both_ways() {
+        let mut renames = Renames::default();
+        renames.record(&[renamed("a.rs", "b.rs"), renamed("b.rs", "c.rs")]);
+        renames.record(&[renamed("old.rs", "c.rs"), renamed("same.rs", "same.rs")]);
+
+        assert_eq!(renames.latest_name("a.rs"), "c.rs");
+        assert_eq!(renames.latest_name("old.rs"), "c.rs");
+        assert_eq!(renames.latest_name("other.rs"), "other.rs");
+        assert_eq!(renames.chain("c.rs"), ["c.rs", "b.rs", "old.rs", "a.rs"]);
+        assert_eq!(renames.chain("same.rs"), ["same.rs"]);
+    }
+
+    #[test]
+    fn test_rename_cycles_end() {
+        let mut renames = Renames::default();
+        renames.record(&[renamed("a.rs", "b.rs")]);
+        renames.record(&[renamed("b.rs", "a.rs")]);
+
+        assert_eq!(renames.latest_name("a.rs"), "b.rs");
+        assert_eq!(renames.latest_name("b.rs"), "a.rs");
+        assert_eq!(renames.chain("a.rs"), ["a.rs", "b.rs"]);
+    }
+}
@@ -1,13 +1,62 @@
-use engram_core::model::Manifest;
-use engram_core::storage::GitStorage;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use engram_core::model::{
+    DeadEnd, Decision, EngramData, EngramId, FileChange, FileChangeType, Manifest, Operations,
+};
+use engram_core::storage::{EngramPart, GitStorage, ListOptions};
 
 use crate::error::QueryError;
+use crate::renames::Renames;
+
+/// How a [`ReviewEntry`] was tied to the branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Linkage {
+    /// A commit in the range has an `Engram-Id` trailer naming the engram.
+    Trailer,
+    /// The engram's manifest records a commit in the range.
+    Manifest,
+    /// The engram has no recorded link, but ran while the branch was being
+    /// worked on and changed files the branch changes.
+    Inferred,
+}
+
+impl Linkage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Trailer => "trailer",
+            Self::Manifest => "manifest",
+            Self::Inferred => "inferred",
+        }
+    }
+}
 
 /// A single engram found during a branch review.
 #[derive(Debug, Clone)]
 pub struct ReviewEntry {
     pub manifest: Manifest,
-    pub commit_sha: String,
+    /// The commit that links the engram; `None` for inferred engrams.
+    pub commit_sha: Option<String>,
+    pub linkage: Linkage,
+    pub dead_ends: Vec<DeadEnd>,
+    pub decisions: Vec<Decision>,
+    pub file_changes: Vec<FileChange>,
+}
+
+impl ReviewEntry {
+    fn new(data: EngramData, commit_sha: Option<String>, linkage: Linkage) -> Self {
+        Self {
+            manifest: data.manifest,
+            commit_sha,
+            linkage,
+            dead_ends: data.intent.dead_ends,
+            decisions: data.intent.decisions,
+            file_changes: data.operations.file_changes,
+        }
+    }
 }
 
 /// Result of reviewing a branch range.
@@ -21,12 +70,230 @@ pub struct BranchReview {
     pub files_changed: Vec<String>,
 }
 
+/// One engram's change to a file, in [`BranchReview::by_file`].
+#[derive(Debug, Clone, Serialize)]
+pub struct FileContribution {
+    pub engram_id: EngramId,
+    /// The name the engram changed, which is an earlier name if the file
+    /// was renamed later.
+    pub path: String,
+    pub change_type: FileChangeType,
+    pub summary: Option<String>,
+    pub agent: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The engrams that changed one file on a branch.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReview {
+    /// The file's latest name.
+    pub path: String,
+    /// Earlier names, from renames recorded on the branch.
+    pub previous_paths: Vec<String>,
+    /// Every change, oldest engram first.
+    pub contributions: Vec<FileContribution>,
+}
+
+impl BranchReview {
+    /// The review grouped by file, sorted by path. Renames recorded by the
+    /// branch's engrams are followed, so a file's changes under earlier
+    /// names are listed under its latest name.
+    pub fn by_file(&self) -> Vec<FileReview> {
+        let mut entries: Vec<&ReviewEntry> = self.engrams.iter().collect();
+        // Oldest first, so later renames of a name win
+        entries.sort_by_key(|e| e.manifest.created_at);
+
+        let mut renames = Renames::default();
+        for entry in &entries {
+            renames.record(&entry.file_changes);
+        }
+
+        let mut files: BTreeMap<String, (BTreeSet<String>, Vec<FileContribution>)> =
+            BTreeMap::new();
+        for entry in entries {
+            let m = &entry.manifest;
+            for fc in &entry.file_changes {
+                let path = renames.latest_name(&fc.path);
+                let (previous, contributions) = files.entry(path.clone()).or_default();
+                if fc.path != path {
+                    previous.insert(fc.path.clone());
+                }
+                if let FileChangeType::Renamed { from } = &fc.change_type {
+                    if *from != path {
+                        previous.insert(from.clone());
+                    }
+                }
+                contributions.push(FileContribution {
+                    engram_id: m.id.clone(),
+                    path: fc.path.clone(),
+                    change_type: fc.change_type.clone(),
+                    summary: m.summary.clone(),
+                    agent: m.agent.name.clone(),
+                    created_at: m.created_at,
+                });
+            }
+        }
+
+        files
+            .into_iter()
+            .map(|(path, (previous, contributions))| FileReview {
+                path,
+                previous_paths: previous.into_iter().collect(),
+                contributions,
+            })
+            .collect()
+    }
+}
+
+impl BranchReview {
+    /// The review as a Markdown pull request description: summary, changes,
+    /// reasoning, dead ends and economics. With `by_file`, changes are
+    /// grouped by file as in [`files_markdown`].
+    pub fn to_markdown(&self, by_file: bool) -> String {
+        let mut out = String::from("## Summary\n\n");
+        for entry in &self.engrams {
+            if let Some(summary) = &entry.manifest.summary {
+                out.push_str(&format!("- {summary}\n"));
+            }
+        }
+        out.push('\n');
+
+        if by_file {
+            out.push_str("## Changes\n\n");
+            out.push_str(&files_markdown(&self.by_file()));
+        } else if !self.files_changed.is_empty() {
+            out.push_str("## Changes\n\n");
+            let mut file_types: Vec<(&str, String)> = Vec::new();
+            let mut seen: BTreeSet<&str> = BTreeSet::new();
+            for fc in self.engrams.iter().flat_map(|e| &e.file_changes) {
+                if seen.insert(&fc.path) {
+                    let change = match &fc.change_type {
+                        FileChangeType::Created => "Created".to_string(),
+                        FileChangeType::Modified => "Modified".to_string(),
+                        FileChangeType::Deleted => "Deleted".to_string(),
+                        FileChangeType::Renamed { from } => format!("Renamed from `{from}`"),
+                    };
+                    file_types.push((&fc.path, change));
+                }
+            }
+            file_types.sort_by(|a, b| a.0.cmp(b.0));
+            for (path, change) in &file_types {
+                out.push_str(&format!("- `{path}` — {change}\n"));
+            }
+            out.push('\n');
+        }
+
+        out.push_str("## Reasoning\n\n");
+        for entry in &self.engrams {
+            let m = &entry.manifest;
+            let agent = &m.agent.name;
+            let model = m.agent.model.as_deref().unwrap_or("unknown");
+            let summary = m.summary.as_deref().unwrap_or("(no summary)");
+            match &entry.commit_sha {
+                Some(sha) => {
+                    let short_sha = &sha[..8.min(sha.len())];
+                    out.push_str(&format!("- **{short_sha}** ({agent}/{model}): {summary}\n"));
+                }
+                None => {
+                    let short_id = &m.id.as_str()[..8.min(m.id.as_str().len())];
+                    out.push_str(&format!(
+                        "- **{short_id}** ({agent}/{model}, inferred): {summary}\n"
+                    ));
+                }
+            }
+        }
+        out.push('\n');
+
+        let dead_ends: Vec<_> = self.engrams.iter().flat_map(|e| &e.dead_ends).collect();
+        if !dead_ends.is_empty() {
+            out.push_str("## Dead Ends\n\n");
+            for de in dead_ends {
+                out.push_str(&format!("- {} — {}\n", de.approach, de.reason));
+            }
+            out.push('\n');
+        }
+
+        out.push_str("## Economics\n\n");
+        out.push_str(&format!("- **Tokens:** {} total\n", self.total_tokens));
+        if let Some(cost) = self.total_cost {
+            out.push_str(&format!("- **Cost:** ${cost:.2}\n"));
+        }
+        out.push_str(&format!("- **Commits:** {}\n\n", self.total_commits));
+
+        out.push_str(
+            "\u{1f916} Generated with [Engram](https://github.com/AtticAIInc/Engram-SDK)\n",
+        );
+        out
+    }
+}
+
+/// Each file as a Markdown heading, so that hosts which fold sections can
+/// collapse them, over a list of the engrams that changed it.
+pub fn files_markdown(files: &[FileReview]) -> String {
+    let mut out = String::new();
+    for file in files {
+        out.push_str(&format!("### `{}`\n\n", file.path));
+        if !file.previous_paths.is_empty() {
+            let previous: Vec<_> = file
+                .previous_paths
+                .iter()
+                .map(|p| format!("`{p}`"))
+                .collect();
+            out.push_str(&format!("_Previously {}_\n\n", previous.join(", ")));
+        }
+        for c in &file.contributions {
+            let short_id = &c.engram_id.as_str()[..8.min(c.engram_id.as_str().len())];
+            let summary = c.summary.as_deref().unwrap_or("(no summary)");
+            out.push_str(&format!(
+                "- **{short_id}** ({}, {}): {summary}\n",
+                c.agent,
+                change_label(&c.change_type)
+            ));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A file change as a lowercase phrase, such as "renamed from old.rs".
+pub fn change_label(change_type: &FileChangeType) -> String {
+    match change_type {
+        FileChangeType::Created => "created".into(),
+        FileChangeType::Modified => "modified".into(),
+        FileChangeType::Deleted => "deleted".into(),
+        FileChangeType::Renamed { from } => format!("renamed from {from}"),
+    }
+}
+
+/// Which engrams [`review_branch`] collects besides those named by
+/// `Engram-Id` trailers.
+#[derive(Debug, Clone)]
+pub struct ReviewOptions {
+    /// Engrams whose manifest lists a commit in the range. On by default.
+    pub manifest_commits: bool,
+    /// Engrams created between the branch point and the last commit that
+    /// changed a file the branch changes. Off by default, as such a match
+    /// may be a coincidence.
+    pub inferred: bool,
+}
+
+impl Default for ReviewOptions {
+    fn default() -> Self {
+        Self {
+            manifest_commits: true,
+            inferred: false,
+        }
+    }
+}
+
 /// Review a branch by walking git log for `base..head`, finding commits
-/// with `Engram-Id` trailers, and collecting referenced engrams.
+/// with `Engram-Id` trailers, and collecting referenced engrams, plus those
+/// `options` asks for.
 pub fn review_branch(
     storage: &GitStorage,
     base: &str,
     head: &str,
+    options: &ReviewOptions,
 ) -> Result<BranchReview, QueryError> {
     let repo = storage.repo();
     let range = format!("{base}..{head}");
@@ -52,8 +319,9 @@ pub fn review_branch(
 
     let mut engrams = Vec::new();
     let mut total_commits = 0;
-    let mut seen_engram_ids = std::collections::HashSet::new();
-    let mut all_files = std::collections::HashSet::new();
+    let mut seen_engram_ids = HashSet::new();
+    let mut range_shas = Vec::new();
+    let mut last_commit_time = None;
 
     for oid_result in revwalk {
         let oid = oid_result.map_err(|e| QueryError::Search(format!("Revwalk error: {e}")))?;
@@ -63,6 +331,9 @@ pub fn review_branch(
 
         total_commits += 1;
         let sha = oid.to_string();
+        range_shas.push(sha.clone());
+        let time = commit_time(commit.time().seconds());
+        last_commit_time = last_commit_time.max(time);
 
         // Check commit message for Engram-Id trailer
         if let Some(message) = commit.message() {
@@ -72,14 +343,13 @@ pub fn review_branch(
                     if seen_engram_ids.insert(engram_id.to_string()) {
                         // Try to read the engram
                         if let Ok(data) = storage.read(engram_id) {
-                            // Collect files
-                            for fc in &data.operations.file_changes {
-                                all_files.insert(fc.path.clone());
-                            }
-                            engrams.push(ReviewEntry {
-                                manifest: data.manifest,
-                                commit_sha: sha.clone(),
-                            });
+                            // A trailer may abbreviate the ID
+                            seen_engram_ids.insert(data.manifest.id.as_str().to_string());
+                            engrams.push(ReviewEntry::new(
+                                data,
+                                Some(sha.clone()),
+                                Linkage::Trailer,
+                            ));
                         }
                     }
                 }
@@ -87,6 +357,106 @@ pub fn review_branch(
         }
     }
 
+    let mut manifests = None;
+    if options.manifest_commits {
+        let all = manifests.get_or_insert(storage.list(&ListOptions::default())?);
+        for m in all.iter() {
+            if seen_engram_ids.contains(m.id.as_str()) {
+                continue;
+            }
+            // Manifests may record abbreviated SHAs
+            let linked = range_shas.iter().find(|sha| {
+                m.git_commits
+                    .iter()
+                    .any(|recorded| !recorded.is_empty() && sha.starts_with(recorded.as_str()))
+            });
+            if let Some(sha) = linked {
+                seen_engram_ids.insert(m.id.as_str().to_string());
+                match storage.read(m.id.as_str()) {
+                    Ok(data) => {
+                        engrams.push(ReviewEntry::new(data, Some(sha.clone()), Linkage::Manifest))
+                    }
+                    Err(e) => tracing::warn!("Failed to read engram {}: {e}", m.id),
+                }
+            }
+        }
+    }
+
+    if options.inferred {
+        if let Some(last) = last_commit_time {
+            let fork = repo
+                .merge_base(base_obj.id(), head_obj.id())
+                .unwrap_or(base_obj.id());
+            let fork_commit = repo
+                .find_commit(fork)
+                .map_err(|e| QueryError::Search(format!("Cannot find commit {fork}: {e}")))?;
+            let fork_tree = fork_commit
+                .tree()
+                .map_err(|e| QueryError::Search(format!("Cannot read tree of {fork}: {e}")))?;
+            let head_tree = head_obj
+                .peel_to_tree()
+                .map_err(|e| QueryError::Search(format!("Cannot read tree of '{head}': {e}")))?;
+            let diff = repo
+                .diff_tree_to_tree(Some(&fork_tree), Some(&head_tree), None)
+                .map_err(|e| QueryError::Search(format!("Cannot diff {range}: {e}")))?;
+            // Both names of a renamed file count
+            let branch_files: HashSet<String> = diff
+                .deltas()
+                .flat_map(|d| [d.old_file().path(), d.new_file().path()])
+                .flatten()
+                .map(|p| p.to_string_lossy().into_owned())
+                .collect();
+            let start = commit_time(fork_commit.time().seconds());
+
+            let all = match manifests {
+                Some(all) => all,
+                None => storage.list(&ListOptions::default())?,
+            };
+            for m in all {
+                let end = m.finished_at.unwrap_or(m.created_at);
+                if seen_engram_ids.contains(m.id.as_str())
+                    || start.is_some_and(|start| end < start)
+                    || m.created_at > last
+                {
+                    continue;
+                }
+                // Only the operations are needed to tell, so the rest of
+                // the engram is read just for those that match
+                let operations = storage
+                    .read_part(m.id.as_str(), EngramPart::Operations)
+                    .map_err(QueryError::from)
+                    .and_then(|json| Ok(serde_json::from_slice::<Operations>(&json)?));
+                let touches_branch = match operations {
+                    Ok(ops) => ops
+                        .file_changes
+                        .iter()
+                        .any(|fc| branch_files.contains(&fc.path)),
+                    Err(e) => {
+                        tracing::warn!("Failed to read operations of engram {}: {e}", m.id);
+                        continue;
+                    }
+                };
+                if !touches_branch {
+                    continue;
+                }
+                match storage.read(m.id.as_str()) {
+                    Ok(data) => {
+                        seen_engram_ids.insert(m.id.as_str().to_string());
+                        engrams.push(ReviewEntry::new(data, None, Linkage::Inferred));
+                    }
+                    Err(e) => tracing::warn!("Failed to read engram {}: {e}", m.id),
+                }
+            }
+        }
+    }
+
+    let all_files: HashSet<&str> = engrams
+        .iter()
+        .flat_map(|e| &e.file_changes)
+        .map(|fc| fc.path.as_str())
+        .collect();
+    let files_changed = all_files.into_iter().map(String::from).collect();
+
     // Compute totals
     let total_tokens: u64 = engrams
         .iter()
@@ -111,6 +481,231 @@ pub fn review_branch(
         total_commits,
         total_tokens,
         total_cost,
-        files_changed: all_files.into_iter().collect(),
+        files_changed,
     })
 }
+
+fn commit_time(seconds: i64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(seconds, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Oid, Repository, Signature, Time};
+    use tempfile::TempDir;
+
+    fn engram(created_at: &str, files: &[&str], commits: &[String]) -> EngramData {
+        let builder = EngramData::builder().created_at(created_at.parse().unwrap());
+        let builder = files.iter().fold(builder, |b, path| b.file(path));
+        commits.iter().fold(builder, |b, sha| b.commit(sha)).build()
+    }
+
+    /// Commit `path` with `message` on HEAD at `when` (RFC 3339).
+    fn commit(repo: &Repository, path: &str, message: &str, when: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        std::fs::write(workdir.join(path), message).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new(path)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let when: DateTime<Utc> = when.parse().unwrap();
+        let sig =
+            Signature::new("dev", "dev@example.com", &Time::new(when.timestamp(), 0)).unwrap();
+        let parents: Vec<_> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    fn linkages(review: &BranchReview) -> Vec<(String, Linkage, bool)> {
+        review
+            .engrams
+            .iter()
+            .map(|e| {
+                (
+                    e.manifest.id.as_str().to_string(),
+                    e.linkage,
+                    e.commit_sha.is_some(),
+                )
+            })
+            .collect()
+    }
+
+    /// The branch forks on 2026-01-10 and commits on the 12th and 14th,
+    /// changing `src/auth.rs` and `src/session.rs`.
+    #[test]
+    fn test_review_linkage() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        std::fs::create_dir(tmp.path().join("src")).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let base = commit(&repo, "README.md", "init", "2026-01-10T09:00:00Z");
+        let mut trailer = engram("2026-01-11T09:00:00Z", &["src/auth.rs"], &[]);
+        trailer.intent.dead_ends.push(DeadEnd {
+            approach: "JWT".into(),
+            reason: "no revocation".into(),
+        });
+        trailer.intent.decisions.push(Decision {
+            description: "Server-side sessions".into(),
+            rationale: "easy to revoke".into(),
+        });
+        storage.create(&trailer).unwrap();
+        let first = commit(
+            &repo,
+            "src/auth.rs",
+            &format!("Add auth\n\nEngram-Id: {}", trailer.manifest.id.as_str()),
+            "2026-01-12T09:00:00Z",
+        );
+        let second = commit(
+            &repo,
+            "src/session.rs",
+            "Add sessions",
+            "2026-01-14T09:00:00Z",
+        );
+
+        // Records an abbreviated SHA of the second commit, with no trailer
+        let manifest = engram(
+            "2026-01-13T09:00:00Z",
+            &["src/session.rs"],
+            &[second.to_string()[..8].to_string()],
+        );
+        // Ran on the branch and touched a branch file, but recorded nothing
+        let inferred = engram("2026-01-13T12:00:00Z", &["src/session.rs"], &[]);
+        // Ran on the branch but only touched files the branch didn't change
+        let unrelated = engram("2026-01-13T15:00:00Z", &["docs/notes.md"], &[]);
+        // Touched a branch file, but before the branch existed
+        let earlier = engram("2026-01-01T09:00:00Z", &["src/auth.rs"], &[]);
+        // Records a commit outside the range
+        let outside = engram("2026-01-09T09:00:00Z", &[], &[base.to_string()]);
+        for data in [&manifest, &inferred, &unrelated, &earlier, &outside] {
+            storage.create(data).unwrap();
+        }
+
+        let id = |data: &EngramData| data.manifest.id.as_str().to_string();
+        let head = second.to_string();
+        let base = base.to_string();
+
+        let trailers_only = ReviewOptions {
+            manifest_commits: false,
+            inferred: false,
+        };
+        let review = review_branch(&storage, &base, &head, &trailers_only).unwrap();
+        assert_eq!(linkages(&review), [(id(&trailer), Linkage::Trailer, true)]);
+        assert_eq!(
+            review.engrams[0].commit_sha.as_deref(),
+            Some(first.to_string().as_str())
+        );
+
+        let review = review_branch(&storage, &base, &head, &ReviewOptions::default()).unwrap();
+        assert_eq!(
+            linkages(&review),
+            [
+                (id(&trailer), Linkage::Trailer, true),
+                (id(&manifest), Linkage::Manifest, true),
+            ]
+        );
+        assert_eq!(review.engrams[1].commit_sha.as_deref(), Some(head.as_str()));
+
+        let all = ReviewOptions {
+            manifest_commits: true,
+            inferred: true,
+        };
+        let review = review_branch(&storage, &base, &head, &all).unwrap();
+        assert_eq!(
+            linkages(&review),
+            [
+                (id(&trailer), Linkage::Trailer, true),
+                (id(&manifest), Linkage::Manifest, true),
+                (id(&inferred), Linkage::Inferred, false),
+            ]
+        );
+        assert_eq!(review.total_commits, 2);
+
+        // Each entry carries what renderers need, so they don't read again
+        let entry = &review.engrams[0];
+        assert_eq!(entry.dead_ends, trailer.intent.dead_ends);
+        assert_eq!(entry.decisions, trailer.intent.decisions);
+        assert_eq!(entry.file_changes, trailer.operations.file_changes);
+        let inferred_files: Vec<_> = review.engrams[2]
+            .file_changes
+            .iter()
+            .map(|fc| fc.path.as_str())
+            .collect();
+        assert_eq!(inferred_files, ["src/session.rs"]);
+        let mut files = review.files_changed.clone();
+        files.sort();
+        assert_eq!(files, ["src/auth.rs", "src/session.rs"]);
+    }
+
+    #[test]
+    fn test_by_file_groups_engrams_sharing_a_file() {
+        let mut first = engram(
+            "2026-01-12T09:00:00Z",
+            &["src/charge.rs", "src/lib.rs"],
+            &[],
+        );
+        first.manifest.summary = Some("Add charging".into());
+        let mut second = engram("2026-01-13T09:00:00Z", &["src/charge.rs"], &[]);
+        second.manifest.summary = Some("Retry failed charges".into());
+        second.manifest.agent.name = "aider".into();
+        // Renames lib.rs, so its history joins the new name
+        second.operations.file_changes.push(FileChange {
+            path: "src/payments.rs".into(),
+            change_type: FileChangeType::Renamed {
+                from: "src/lib.rs".into(),
+            },
+            lines_added: None,
+            lines_removed: None,
+        });
+        let ids = [first.manifest.id.clone(), second.manifest.id.clone()];
+
+        // Newest first, as a revwalk lists them
+        let review = BranchReview {
+            range: "main..feature".into(),
+            engrams: vec![
+                ReviewEntry::new(second, None, Linkage::Inferred),
+                ReviewEntry::new(first, None, Linkage::Inferred),
+            ],
+            total_commits: 2,
+            total_tokens: 0,
+            total_cost: None,
+            files_changed: Vec::new(),
+        };
+
+        let files = review.by_file();
+        let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["src/charge.rs", "src/payments.rs"]);
+
+        let charge: Vec<_> = files[0]
+            .contributions
+            .iter()
+            .map(|c| (&c.engram_id, c.agent.as_str(), c.summary.as_deref()))
+            .collect();
+        assert_eq!(
+            charge,
+            [
+                (&ids[0], "claude-code", Some("Add charging")),
+                (&ids[1], "aider", Some("Retry failed charges")),
+            ]
+        );
+
+        assert_eq!(files[1].previous_paths, ["src/lib.rs"]);
+        let payments: Vec<_> = files[1]
+            .contributions
+            .iter()
+            .map(|c| (c.path.as_str(), &c.engram_id))
+            .collect();
+        assert_eq!(
+            payments,
+            [("src/lib.rs", &ids[0]), ("src/payments.rs", &ids[1])]
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Searches that read engram refs directly instead of the search index, for
+//! when the index can't be opened. Much slower on large repositories: every
+//! engram's manifest and intent is read, and its operations when a filter
+//! needs them. Text matches are case-insensitive substrings, so word forms
+//! and typos the index would match are missed.
+
+use engram_core::model::{Intent, Manifest, Operations};
+use engram_core::storage::{EngramPart, GitStorage, ListOptions};
+
+use crate::error::QueryError;
+use crate::index::facets::SearchFacets;
+use crate::index::{
+    parse_search_query, FilterField, SearchOptions, SearchPage, SearchResult, SearchSort,
+};
+
+/// Like [`crate::EngramSearcher::search`]: engrams matching any word of the
+/// free text (ranked by how many) and every filter, in `options.sort` order.
+pub fn scan_search(
+    storage: &GitStorage,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<SearchPage, QueryError> {
+    let mut parsed = parse_search_query(query);
+    parsed.filters.extend_from_slice(&options.filters);
+    let words = words(&parsed.text);
+
+    let list = ListOptions {
+        since: options.since,
+        until: options.until,
+        min_cost: options.min_cost,
+        max_cost: options.max_cost,
+        min_tokens: options.min_tokens,
+        max_tokens: options.max_tokens,
+        ..Default::default()
+    };
+    let mut matches = Vec::new();
+    for manifest in storage.list(&list)? {
+        let intent = match storage.read_intent(manifest.id.as_str()) {
+            Ok(intent) => intent,
+            Err(e) => {
+                tracing::warn!("Failed to read engram {}: {e}", manifest.id);
+                continue;
+            }
+        };
+        let engram = Scanned { manifest, intent };
+        if !engram.passes(storage, &parsed.filters)? {
+            continue;
+        }
+        let text = engram.text().to_lowercase();
+        let score = words.iter().filter(|w| text.contains(w.as_str())).count();
+        if words.is_empty() || score > 0 {
+            matches.push((score as f32, engram));
+        }
+    }
+
+    sort(&mut matches, options.sort);
+    let facets = options
+        .facets
+        .then(|| SearchFacets::of(matches.iter().map(|(_, e)| &e.manifest)));
+    let total = matches.len();
+    let results = matches
+        .into_iter()
+        .skip(options.offset)
+        .take(options.limit)
+        .map(|(score, engram)| {
+            let score = match options.sort {
+                SearchSort::Relevance => score,
+                _ => 0.0,
+            };
+            engram.into_result(score, options.snippet_max_chars)
+        })
+        .collect();
+    Ok(SearchPage {
+        results,
+        total,
+        offset: options.offset,
+        facets,
+    })
+}
+
+/// Like [`crate::EngramSearcher::search_by_file`]: engrams that changed
+/// `file_path`, newest first.
+pub fn scan_by_file(
+    storage: &GitStorage,
+    file_path: &str,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<SearchResult>, QueryError> {
+    let mut results = Vec::new();
+    for manifest in storage.list(&ListOptions::default())? {
+        if operations(storage, &manifest)?
+            .file_changes
+            .iter()
+            .any(|fc| fc.path == file_path)
+        {
+            results.push(SearchResult {
+                manifest,
+                score: 0.0,
+                snippet: None,
+                highlights: Vec::new(),
+                truncated_transcript: false,
+                repo: None,
+            });
+        }
+    }
+    Ok(results.into_iter().skip(offset).take(limit).collect())
+}
+
+/// Like [`crate::EngramSearcher::search_insights`]: engrams whose dead ends
+/// or decisions contain a word of `query`, newest first.
+pub fn scan_insights(
+    storage: &GitStorage,
+    query: &str,
+    limit: usize,
+    offset: usize,
+) -> Result<SearchPage, QueryError> {
+    let words = words(query);
+    let mut matches = Vec::new();
+    for manifest in storage.list(&ListOptions::default())? {
+        let intent = storage.read_intent(manifest.id.as_str())?;
+        let insights = intent
+            .dead_ends
+            .iter()
+            .map(|de| format!("{}: {}", de.approach, de.reason))
+            .chain(
+                intent
+                    .decisions
+                    .iter()
+                    .map(|d| format!("{}: {}", d.description, d.rationale)),
+            )
+            .collect::<Vec<_>>()
+            .join("\n")
+            .to_lowercase();
+        if words.iter().any(|w| insights.contains(w.as_str())) {
+            matches.push(Scanned { manifest, intent }.into_result(0.0, 0));
+        }
+    }
+    let total = matches.len();
+    Ok(SearchPage {
+        results: matches.into_iter().skip(offset).take(limit).collect(),
+        total,
+        offset,
+        facets: None,
+    })
+}
+
+/// The lowercase words of free text, without quotes or query operators.
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|w| w.trim_matches(|c: char| c == '"' || c == '+' || c == '-'))
+        .filter(|w| !w.is_empty() && !matches!(*w, "AND" | "OR" | "NOT"))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn operations(storage: &GitStorage, manifest: &Manifest) -> Result<Operations, QueryError> {
+    let json = storage.read_part(manifest.id.as_str(), EngramPart::Operations)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Order matches as the index would; ties and relevance-less sorts fall
+/// back to newest first.
+fn sort(matches: &mut [(f32, Scanned)], sort: SearchSort) {
+    matches.sort_by(|(sa, a), (sb, b)| {
+        let (a, b) = (&a.manifest, &b.manifest);
+        let newest = b.created_at.cmp(&a.created_at);
+        match sort {
+            SearchSort::Relevance => sb.total_cmp(sa).then(newest),
+            SearchSort::CreatedDesc => newest,
+            SearchSort::CreatedAsc => a.created_at.cmp(&b.created_at),
+            SearchSort::CostDesc => {
+                let cost = |m: &Manifest| m.token_usage.cost_usd.unwrap_or(0.0);
+                cost(b).total_cmp(&cost(a)).then(newest)
+            }
+            SearchSort::TokensDesc => b
+                .token_usage
+                .total_tokens
+                .cmp(&a.token_usage.total_tokens)
+                .then(newest),
+        }
+    });
+}
+
+/// An engram's manifest and intent.
+struct Scanned {
+    manifest: Manifest,
+    intent: Intent,
+}
+
+impl Scanned {
+    /// Whether the engram meets every filter, reading its operations only
+    /// for filters on them.
+    fn passes(
+        &self,
+        storage: &GitStorage,
+        filters: &[(FilterField, String)],
+    ) -> Result<bool, QueryError> {
+        let m = &self.manifest;
+        let mut ops = None;
+        for (field, value) in filters {
+            let passes = match field {
+                FilterField::Agent => m.agent.name == *value,
+                FilterField::Model => m.agent.model.as_ref() == Some(value),
+                FilterField::Tag => m.tags.contains(value),
+                FilterField::DeadEnd => {
+                    let value = value.to_lowercase();
+                    self.intent.dead_ends.iter().any(|de| {
+                        format!("{} {}", de.approach, de.reason)
+                            .to_lowercase()
+                            .contains(&value)
+                    })
+                }
+                FilterField::File => {
+                    let ops = match &ops {
+                        Some(ops) => ops,
+                        None => ops.insert(operations(storage, m)?),
+                    };
+                    ops.file_changes
+                        .iter()
+                        .any(|fc| fc.path.contains(value.as_str()))
+                }
+                FilterField::HasErrors => {
+                    let wanted = value.parse::<bool>().map_err(|_| {
+                        QueryError::Search(format!(
+                            "has_errors: expects true or false, got '{value}'"
+                        ))
+                    })?;
+                    let ops = match &ops {
+                        Some(ops) => ops,
+                        None => ops.insert(operations(storage, m)?),
+                    };
+                    ops.has_errors() == wanted
+                }
+            };
+            if !passes {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// The text free-text words are matched against.
+    fn text(&self) -> String {
+        let intent = &self.intent;
+        let mut parts = vec![intent.original_request.as_str()];
+        parts.extend(intent.interpreted_goal.as_deref());
+        parts.extend(intent.summary.as_deref());
+        parts.extend(self.manifest.summary.as_deref());
+        parts.extend(self.manifest.tags.iter().map(String::as_str));
+        for de in &intent.dead_ends {
+            parts.extend([de.approach.as_str(), de.reason.as_str()]);
+        }
+        for d in &intent.decisions {
+            parts.extend([d.description.as_str(), d.rationale.as_str()]);
+        }
+        parts.join("\n")
+    }
+
+    /// A result whose snippet is the intent summary, else the request, cut
+    /// to `max_chars` (or left out when 0).
+    fn into_result(self, score: f32, max_chars: usize) -> SearchResult {
+        let snippet = (max_chars > 0).then(|| {
+            let text = self
+                .intent
+                .summary
+                .as_deref()
+                .unwrap_or(&self.intent.original_request);
+            text.chars().take(max_chars).collect()
+        });
+        SearchResult {
+            manifest: self.manifest,
+            score,
+            snippet,
+            highlights: Vec::new(),
+            truncated_transcript: false,
+            repo: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(agent: &str, request: &str, path: &str, minutes: i64) -> EngramData {
+        EngramData::builder()
+            .agent(agent)
+            .created_at(chrono::Utc::now() + chrono::Duration::minutes(minutes))
+            .request(request)
+            .summary(request)
+            .dead_end("Session cookies", "Stateful")
+            .file(path)
+            .build()
+    }
+
+    fn summaries(results: &[SearchResult]) -> Vec<&str> {
+        results
+            .iter()
+            .map(|r| r.manifest.summary.as_deref().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_scan() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        for data in [
+            engram("claude-code", "Add JWT authentication", "src/auth.rs", 0),
+            engram("cursor", "Refresh JWT tokens", "src/auth.rs", 1),
+            engram("cursor", "Speed up the cache", "src/cache.rs", 2),
+        ] {
+            storage.create(&data).unwrap();
+        }
+
+        let search = |query: &str| scan_search(&storage, query, &SearchOptions::default()).unwrap();
+        let page = search("jwt authentication");
+        assert_eq!(
+            summaries(&page.results),
+            ["Add JWT authentication", "Refresh JWT tokens"]
+        );
+        assert_eq!(page.total, 2);
+        assert_eq!(
+            summaries(&search("agent:cursor jwt").results),
+            ["Refresh JWT tokens"]
+        );
+        assert_eq!(
+            summaries(&search("file:src/cache.rs").results),
+            ["Speed up the cache"]
+        );
+        assert!(search("oauth").is_empty());
+
+        let files = scan_by_file(&storage, "src/auth.rs", 10, 0).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(
+            scan_by_file(&storage, "src/auth.rs", 10, 1).unwrap().len(),
+            1
+        );
+
+        let insights = scan_insights(&storage, "cookies", 10, 0).unwrap();
+        assert_eq!(insights.total, 3);
+    }
+}
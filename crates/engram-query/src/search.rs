@@ -1,42 +1,199 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use engram_core::config::{EngramConfig, IndexPolicy};
+use engram_core::error::CoreError;
 use engram_core::model::EngramData;
+use engram_core::storage::refs::list_engram_refs;
 use engram_core::storage::GitStorage;
+use sha2::{Digest, Sha256};
 
 use crate::error::QueryError;
-use crate::index::{rebuild_index, EngramIndexWriter, EngramSearcher, SearchResult};
+use crate::index::schema::{is_current, stored_version};
+use crate::index::{
+    rebuild_index, EngramIndexWriter, EngramSearcher, SearchOptions, SearchPage, SearchResult,
+    WRITER_LOCK_TIMEOUT,
+};
+use crate::scan;
+
+/// Directory to keep search indexes in, one subdirectory per repository.
+/// Takes precedence over `engram.indexPath`.
+pub const INDEX_DIR_ENV: &str = "ENGRAM_INDEX_DIR";
 
 /// High-level search engine that manages index lifecycle.
 pub struct SearchEngine {
     index_path: PathBuf,
+    /// The index directory can't be written to, so an existing index is
+    /// searched but never built or updated.
+    read_only: bool,
+    /// The index as last opened, kept for later searches and dropped when
+    /// this engine rebuilds it.
+    searcher: Mutex<Option<Arc<EngramSearcher>>>,
+    /// Whether a search has already warned that it scanned engram refs
+    /// because the index can't be used.
+    warned: AtomicBool,
+    /// How long incremental updates wait for another process's writer.
+    lock_timeout: Duration,
+    /// How much of each transcript is indexed (`engram.index.*`).
+    policy: IndexPolicy,
 }
 
 impl SearchEngine {
-    /// Open a search engine for a repository. Index is stored at `.git/engram-index/`.
+    /// Open a search engine for a repository. The index is stored under
+    /// [`INDEX_DIR_ENV`] if set, else at `engram.indexPath`, else at
+    /// `.git/engram-index/`.
     pub fn open(storage: &GitStorage) -> Result<Self, QueryError> {
-        let git_dir = storage.repo().path();
-        let index_path = git_dir.join("engram-index");
-        Ok(Self { index_path })
+        let env_dir = std::env::var_os(INDEX_DIR_ENV).filter(|dir| !dir.is_empty());
+        let index_path = resolve_index_path(storage, env_dir.as_deref().map(Path::new))?;
+        let read_only = !is_writable(&index_path);
+        if read_only {
+            tracing::info!(
+                "Search index directory {} is not writable; searching read-only",
+                index_path.display()
+            );
+        }
+        let config = storage.repo().config().map_err(CoreError::Git)?;
+        Ok(Self {
+            index_path,
+            read_only,
+            searcher: Mutex::new(None),
+            warned: AtomicBool::new(false),
+            lock_timeout: WRITER_LOCK_TIMEOUT,
+            policy: IndexPolicy::load(&config),
+        })
     }
 
-    /// Ensure the index exists, creating it if needed.
+    /// Wait at most `timeout` for another process to release the index
+    /// before an update fails with [`QueryError::IndexLocked`] (default:
+    /// [`WRITER_LOCK_TIMEOUT`]).
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    /// Treat the index as read-only, as if its directory couldn't be
+    /// written to. File permissions don't bind root, so tests use this.
+    #[cfg(test)]
+    fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// A writer on the index, once no other process holds it.
+    fn writer(&self) -> Result<EngramIndexWriter, QueryError> {
+        Ok(
+            EngramIndexWriter::open_waiting(&self.index_path, self.lock_timeout)?
+                .with_policy(self.policy),
+        )
+    }
+
+    /// Whether the index can only be searched, not built or updated.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Ensure the index exists with the current schema, (re)building it if needed.
     pub fn ensure_index(&self, storage: &GitStorage) -> Result<(), QueryError> {
-        if !self.index_path.exists() || !self.index_path.join("meta.json").exists() {
-            rebuild_index(storage, &self.index_path)?;
+        if !is_current(&self.index_path) {
+            // Files but no meta.json: a build that was cut short, or damage.
+            // Left for `engram reindex`, as the files may not be ours
+            if !self.index_path.join("meta.json").exists() && !is_empty_dir(&self.index_path) {
+                return Err(QueryError::CorruptIndex(
+                    self.index_path.clone(),
+                    "meta.json is missing".into(),
+                ));
+            }
+            if self.read_only {
+                return Err(QueryError::ReadOnlyIndex(self.index_path.clone()));
+            }
+            if self.index_path.join("meta.json").exists() {
+                tracing::info!("Search index was built with an older schema; rebuilding");
+            }
+            self.rebuild(storage)?;
         }
         Ok(())
     }
 
-    /// Search engrams by free-text query.
+    /// The opened index, reused across calls. Searches still see commits
+    /// made since it was opened, as each takes a fresh reader.
+    fn searcher(&self) -> Result<Arc<EngramSearcher>, QueryError> {
+        let mut cached = self.searcher.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(searcher) = cached.as_ref() {
+            return Ok(Arc::clone(searcher));
+        }
+        let searcher = EngramSearcher::open(&self.index_path).map_err(|e| match e {
+            QueryError::Index(e) => QueryError::CorruptIndex(self.index_path.clone(), e),
+            e => e,
+        })?;
+        let searcher = Arc::new(searcher);
+        *cached = Some(Arc::clone(&searcher));
+        Ok(searcher)
+    }
+
+    /// Schema version of the on-disk index, or `None` if it has not been built.
+    pub fn index_version(&self) -> Option<u32> {
+        stored_version(&self.index_path)
+    }
+
+    /// The index, built if missing, or why it can't be used.
+    fn indexed(&self, storage: &GitStorage) -> Result<Arc<EngramSearcher>, QueryError> {
+        self.ensure_index(storage)?;
+        self.searcher()
+    }
+
+    /// Rebuild the index if it can't be opened. Returns whether it was
+    /// rebuilt.
+    pub fn repair(&self, storage: &GitStorage) -> Result<bool, QueryError> {
+        match self.indexed(storage) {
+            Ok(_) => Ok(false),
+            Err(e) if is_unusable(&e) => {
+                tracing::info!("{e}; rebuilding it");
+                self.rebuild(storage)?;
+                Ok(true)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Warn, once per engine, that `e` makes searches scan engram refs.
+    fn fall_back(&self, e: &QueryError) {
+        if !self.warned.swap(true, Ordering::Relaxed) {
+            tracing::warn!("{e}. Scanning engrams instead, which is slower");
+        }
+    }
+
+    /// Search engrams by free-text query. When the index can't be opened,
+    /// engram refs are scanned instead (see [`scan::scan_search`]).
     pub fn search(
         &self,
         storage: &GitStorage,
         query: &str,
-        limit: usize,
-    ) -> Result<Vec<SearchResult>, QueryError> {
-        self.ensure_index(storage)?;
-        let searcher = EngramSearcher::open(&self.index_path)?;
-        searcher.search(query, limit)
+        options: &SearchOptions,
+    ) -> Result<SearchPage, QueryError> {
+        match self.indexed(storage) {
+            Ok(searcher) => searcher.search(query, options),
+            Err(e) if is_unusable(&e) => {
+                self.fall_back(&e);
+                scan::scan_search(storage, query, options)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Like [`Self::search`], but `None` instead of building the index when
+    /// it is missing or outdated, for callers that must not write.
+    pub fn search_if_indexed(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Option<SearchPage>, QueryError> {
+        if !is_current(&self.index_path) {
+            return Ok(None);
+        }
+        self.searcher()?.search(query, options).map(Some)
     }
 
     /// Search for engrams that touched a file.
@@ -45,26 +202,132 @@ impl SearchEngine {
         storage: &GitStorage,
         file_path: &str,
         limit: usize,
+        offset: usize,
     ) -> Result<Vec<SearchResult>, QueryError> {
-        self.ensure_index(storage)?;
-        let searcher = EngramSearcher::open(&self.index_path)?;
-        searcher.search_by_file(file_path, limit)
+        match self.indexed(storage) {
+            Ok(searcher) => searcher.search_by_file(file_path, limit, offset),
+            Err(e) if is_unusable(&e) => {
+                self.fall_back(&e);
+                scan::scan_by_file(storage, file_path, limit, offset)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Engrams whose dead ends or decisions match `query`, newest first.
+    pub fn search_insights(
+        &self,
+        storage: &GitStorage,
+        query: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<SearchPage, QueryError> {
+        match self.indexed(storage) {
+            Ok(searcher) => searcher.search_insights(query, limit, offset),
+            Err(e) if is_unusable(&e) => {
+                self.fall_back(&e);
+                scan::scan_insights(storage, query, limit, offset)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Engrams with intent text similar to `text`, as `(engram id, score)`,
+    /// best first. Empty if the index hasn't been built; this never builds it.
+    pub fn more_like_this(
+        &self,
+        text: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, f32)>, QueryError> {
+        if !is_current(&self.index_path) {
+            return Ok(Vec::new());
+        }
+        self.searcher()?.more_like_this(text, limit)
     }
 
     /// Index a single new engram (incremental update).
     pub fn index_engram(&self, data: &EngramData) -> Result<(), QueryError> {
-        if !self.index_path.exists() {
-            return Ok(()); // Index doesn't exist yet, skip
+        if self.read_only || !is_current(&self.index_path) {
+            return Ok(()); // Missing or outdated; the next search rebuilds it
+        }
+        let mut writer = self.writer()?;
+        writer.index_engram(data)?;
+        writer.commit()?;
+        Ok(())
+    }
+
+    /// Replace the document of an engram that was changed in place, such as
+    /// by `engram tag` (incremental update).
+    pub fn reindex_engram(&self, data: &EngramData) -> Result<(), QueryError> {
+        if self.read_only || !is_current(&self.index_path) {
+            return Ok(()); // Missing or outdated; the next search rebuilds it
         }
-        let mut writer = EngramIndexWriter::open(&self.index_path)?;
+        let mut writer = self.writer()?;
+        writer.delete_engram(data.manifest.id.as_str())?;
         writer.index_engram(data)?;
         writer.commit()?;
         Ok(())
     }
 
+    /// Remove a single engram from the index (incremental update).
+    pub fn remove_engram(&self, id: &str) -> Result<(), QueryError> {
+        if self.read_only || !is_current(&self.index_path) {
+            return Ok(()); // Missing or outdated; the next search rebuilds it
+        }
+        let mut writer = self.writer()?;
+        writer.delete_engram(id)?;
+        writer.commit()?;
+        Ok(())
+    }
+
+    /// Catch the index up with the engram refs: index engrams it is missing
+    /// and drop those whose refs are gone, in one commit. Falls back to a full
+    /// rebuild if the index is missing or outdated.
+    ///
+    /// Returns the number of engrams newly indexed.
+    pub fn index_missing(&self, storage: &GitStorage) -> Result<usize, QueryError> {
+        if self.read_only {
+            return Err(QueryError::ReadOnlyIndex(self.index_path.clone()));
+        }
+        if !is_current(&self.index_path) {
+            return self.rebuild(storage);
+        }
+
+        let indexed = self.searcher()?.indexed_ids()?;
+        let refs = list_engram_refs(storage.repo())?;
+        let present: HashSet<&str> = refs.iter().map(|(id, _)| id.as_str()).collect();
+
+        let mut writer = self.writer()?;
+        let mut removed = 0;
+        for id in indexed.iter().filter(|id| !present.contains(id.as_str())) {
+            writer.delete_engram(id)?;
+            removed += 1;
+        }
+        let mut added = 0;
+        for (id, _) in refs.iter().filter(|(id, _)| !indexed.contains(id.as_str())) {
+            match storage.read(id.as_str()) {
+                Ok(data) => {
+                    writer.index_engram(&data)?;
+                    added += 1;
+                }
+                Err(e) => tracing::warn!("Failed to read engram {id}: {e}"),
+            }
+        }
+        if added > 0 || removed > 0 {
+            writer.commit()?;
+        }
+
+        tracing::info!("Indexed {added} missing engram(s), removed {removed} stale");
+        Ok(added)
+    }
+
     /// Rebuild the index from scratch.
     pub fn rebuild(&self, storage: &GitStorage) -> Result<usize, QueryError> {
-        rebuild_index(storage, &self.index_path)
+        if self.read_only {
+            return Err(QueryError::ReadOnlyIndex(self.index_path.clone()));
+        }
+        *self.searcher.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        rebuild_index(storage, &self.index_path, self.policy)
     }
 
     /// Return the index path.
@@ -72,3 +335,344 @@ impl SearchEngine {
         &self.index_path
     }
 }
+
+/// Where a repository's index lives: a per-repository subdirectory of
+/// `env_dir`, else `engram.indexPath`, else `.git/engram-index`.
+fn resolve_index_path(storage: &GitStorage, env_dir: Option<&Path>) -> Result<PathBuf, QueryError> {
+    let repo = storage.repo();
+    if let Some(dir) = env_dir {
+        return Ok(dir.join(repo_key(repo.path())));
+    }
+    let config = repo.config().map_err(CoreError::Git)?;
+    if let Some(path) = EngramConfig::load(&config)?.index_path {
+        // Joining an absolute path replaces the base
+        return Ok(repo.workdir().unwrap_or(repo.path()).join(path));
+    }
+    Ok(repo.path().join("engram-index"))
+}
+
+/// `<repo name>-<hash of its path>`, so repositories sharing an index
+/// directory don't collide.
+fn repo_key(git_dir: &Path) -> String {
+    let git_dir = git_dir
+        .canonicalize()
+        .unwrap_or_else(|_| git_dir.to_path_buf());
+    // `.git` directories are named after their work tree
+    let named = match git_dir.file_name() {
+        Some(name) if name == ".git" => git_dir.parent().unwrap_or(&git_dir),
+        _ => &git_dir,
+    };
+    let name = named
+        .file_name()
+        .map_or("repo".into(), |n| n.to_string_lossy());
+    let hash = Sha256::digest(git_dir.to_string_lossy().as_bytes());
+    let hash: String = hash[..6].iter().map(|b| format!("{b:02x}")).collect();
+    format!("{name}-{hash}")
+}
+
+/// Whether `e` means the index can't be searched at all, rather than that
+/// a search failed.
+fn is_unusable(e: &QueryError) -> bool {
+    matches!(
+        e,
+        QueryError::CorruptIndex(..)
+            | QueryError::ReadOnlyIndex(_)
+            | QueryError::OutdatedIndex { .. }
+    )
+}
+
+/// Whether `path` is missing or an empty directory.
+fn is_empty_dir(path: &Path) -> bool {
+    std::fs::read_dir(path).map_or(true, |mut entries| entries.next().is_none())
+}
+
+/// Whether the index at `path` can be created or updated, probed with a
+/// scratch file in it or its nearest existing ancestor.
+fn is_writable(path: &Path) -> bool {
+    let Some(dir) = path.ancestors().find(|p| p.is_dir()) else {
+        return false;
+    };
+    let probe = dir.join(format!(".engram-write-probe-{}", std::process::id()));
+    match std::fs::File::create(&probe) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::schema::EngramSchema;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(request: &str) -> EngramData {
+        EngramData::builder().request(request).build()
+    }
+
+    fn hits(engine: &SearchEngine, storage: &GitStorage, query: &str) -> usize {
+        engine
+            .search(storage, query, &SearchOptions::default())
+            .unwrap()
+            .total
+    }
+
+    #[test]
+    fn test_index_missing_adds_and_removes_without_rebuild() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let stale = storage.create(&engram("retire the cron worker")).unwrap();
+
+        let engine = SearchEngine::open(&storage).unwrap();
+        assert_eq!(engine.rebuild(&storage).unwrap(), 1);
+        // A rebuild deletes the index directory, and this marker with it
+        let marker = engine.index_path().join("not-rebuilt");
+        std::fs::write(&marker, "").unwrap();
+
+        // Written straight to storage, as a fetch would
+        storage.create(&engram("add rate limiting")).unwrap();
+        storage.create(&engram("rotate signing keys")).unwrap();
+        storage.delete(stale.as_str()).unwrap();
+
+        assert_eq!(engine.index_missing(&storage).unwrap(), 2);
+        assert!(marker.exists());
+        assert_eq!(hits(&engine, &storage, "rate"), 1);
+        assert_eq!(hits(&engine, &storage, "signing"), 1);
+        assert_eq!(hits(&engine, &storage, "cron"), 0);
+
+        // Already up to date
+        assert_eq!(engine.index_missing(&storage).unwrap(), 0);
+        assert_eq!(hits(&engine, &storage, ""), 2);
+    }
+
+    #[test]
+    fn test_kept_engine_sees_later_changes() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        storage.create(&engram("add rate limiting")).unwrap();
+
+        let engine = SearchEngine::open(&storage).unwrap();
+        assert_eq!(hits(&engine, &storage, "rate"), 1);
+
+        // Indexed through another engine, as a second process would
+        let other = SearchEngine::open(&storage).unwrap();
+        let data = engram("rotate signing keys");
+        storage.create(&data).unwrap();
+        other.index_engram(&data).unwrap();
+        assert_eq!(hits(&engine, &storage, "signing"), 1);
+
+        // Even a rebuild that replaces the index directory
+        storage.create(&engram("retire the cron worker")).unwrap();
+        other.rebuild(&storage).unwrap();
+        assert_eq!(hits(&engine, &storage, "cron"), 1);
+        assert_eq!(hits(&engine, &storage, ""), 3);
+    }
+
+    #[test]
+    fn test_transcript_indexed_within_budget() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config
+            .set_i64("engram.index.transcriptMaxBytes", 4096)
+            .unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let mut data = engram("migrate the billing tables");
+        let filler = (0..1000).map(|i| format!("line {i}")).collect::<Vec<_>>();
+        for text in ["opening kickoff"]
+            .into_iter()
+            .chain(filler.iter().map(String::as_str))
+            .chain(["midway checkpoint"])
+            .chain(filler.iter().map(String::as_str))
+            .chain(["closing wrapup"])
+        {
+            data.transcript.entries.push(TranscriptEntry {
+                timestamp: chrono::Utc::now(),
+                role: Role::Assistant,
+                content: TranscriptContent::Text { text: text.into() },
+                token_count: None,
+            });
+        }
+        storage.create(&data).unwrap();
+
+        let engine = SearchEngine::open(&storage).unwrap();
+        engine.rebuild(&storage).unwrap();
+        let index = tantivy::Index::open_in_dir(engine.index_path()).unwrap();
+        let reader = index.reader().unwrap();
+        let doc: tantivy::TantivyDocument = reader
+            .searcher()
+            .doc(tantivy::DocAddress::new(0, 0))
+            .unwrap();
+        let indexed = doc
+            .get_first(EngramSchema::new().transcript_text)
+            .and_then(|v| tantivy::schema::Value::as_str(&v))
+            .unwrap();
+        assert!(indexed.len() <= 4096, "{} bytes", indexed.len());
+
+        let search = |query: &str| {
+            engine
+                .search(&storage, query, &SearchOptions::default())
+                .unwrap()
+                .results
+        };
+        assert!(search("midway").is_empty());
+        for query in ["kickoff", "wrapup"] {
+            let results = search(query);
+            assert_eq!(results.len(), 1, "{query}");
+            assert!(results[0].truncated_transcript, "{query}");
+        }
+        // Matched in the request, not the transcript
+        assert!(!search("billing")[0].truncated_transcript);
+    }
+
+    #[test]
+    fn test_reindex_replaces_document() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let mut data = engram("add rate limiting");
+        storage.create(&data).unwrap();
+
+        let engine = SearchEngine::open(&storage).unwrap();
+        assert_eq!(hits(&engine, &storage, "tag:api"), 0);
+
+        data.manifest.tags.push("api".into());
+        engine.reindex_engram(&data).unwrap();
+        assert_eq!(hits(&engine, &storage, "tag:api"), 1);
+        assert_eq!(hits(&engine, &storage, "rate"), 1);
+    }
+
+    #[test]
+    fn test_search_if_indexed_never_builds() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        storage.create(&engram("add rate limiting")).unwrap();
+
+        let engine = SearchEngine::open(&storage).unwrap();
+        let options = SearchOptions::default();
+        assert!(engine
+            .search_if_indexed("rate", &options)
+            .unwrap()
+            .is_none());
+        assert!(engine.index_version().is_none());
+
+        engine.rebuild(&storage).unwrap();
+        let page = engine.search_if_indexed("rate", &options).unwrap().unwrap();
+        assert_eq!(page.total, 1);
+    }
+
+    #[test]
+    fn test_index_path_from_config_and_env() {
+        let tmp = TempDir::new().unwrap();
+        let repo = Repository::init(tmp.path().join("api")).unwrap();
+        let storage = GitStorage::open(repo.workdir().unwrap()).unwrap();
+        let default = resolve_index_path(&storage, None).unwrap();
+        assert_eq!(default, repo.path().join("engram-index"));
+
+        repo.config()
+            .unwrap()
+            .set_str("engram.indexPath", "../cache/api-index")
+            .unwrap();
+        let configured = resolve_index_path(&storage, None).unwrap();
+        assert_eq!(
+            configured,
+            repo.workdir().unwrap().join("../cache/api-index")
+        );
+
+        // The env dir wins, keyed by repository
+        let env_dir = tmp.path().join("indexes");
+        let keyed = resolve_index_path(&storage, Some(&env_dir)).unwrap();
+        assert_eq!(keyed.parent(), Some(env_dir.as_path()));
+        let key = keyed.file_name().unwrap().to_str().unwrap();
+        assert!(key.starts_with("api-"), "{key}");
+    }
+
+    #[test]
+    fn test_concurrent_writers_wait_for_the_lock() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let engine = SearchEngine::open(&storage).unwrap();
+        engine.rebuild(&storage).unwrap();
+
+        // One process holds the writer while another stores and indexes
+        let mut holder = EngramIndexWriter::open(engine.index_path()).unwrap();
+        let path = tmp.path().to_path_buf();
+        let contender = std::thread::spawn(move || {
+            let storage = GitStorage::open(&path).unwrap();
+            let data = engram("add rate limiting");
+            storage.create(&data).unwrap();
+            SearchEngine::open(&storage).unwrap().index_engram(&data)
+        });
+        std::thread::sleep(Duration::from_millis(200));
+        let data = engram("rate limit the uploads");
+        storage.create(&data).unwrap();
+        holder.index_engram(&data).unwrap();
+        holder.commit().unwrap();
+        drop(holder);
+
+        contender.join().unwrap().unwrap();
+        assert_eq!(storage.list(&Default::default()).unwrap().len(), 2);
+        assert_eq!(hits(&engine, &storage, "rate"), 2);
+    }
+
+    #[test]
+    fn test_locked_index_is_caught_up_later() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        let engine = SearchEngine::open(&storage)
+            .unwrap()
+            .with_lock_timeout(Duration::from_millis(50));
+        engine.rebuild(&storage).unwrap();
+
+        let holder = EngramIndexWriter::open(engine.index_path()).unwrap();
+        let data = engram("add rate limiting");
+        storage.create(&data).unwrap();
+        let err = engine.index_engram(&data).unwrap_err();
+        assert!(matches!(err, QueryError::IndexLocked(_)), "{err}");
+        drop(holder);
+
+        // Stored but not indexed, until an incremental reindex
+        assert_eq!(hits(&engine, &storage, "rate"), 0);
+        assert_eq!(engine.index_missing(&storage).unwrap(), 1);
+        assert_eq!(hits(&engine, &storage, "rate"), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_only_index_is_searched_not_rebuilt() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+        storage.create(&engram("add rate limiting")).unwrap();
+        let engine = SearchEngine::open(&storage).unwrap();
+        engine.rebuild(&storage).unwrap();
+
+        let engine = SearchEngine::open(&storage).unwrap().read_only();
+        assert!(engine.is_read_only());
+        assert_eq!(hits(&engine, &storage, "rate"), 1);
+        assert!(matches!(
+            engine.rebuild(&storage),
+            Err(QueryError::ReadOnlyIndex(_))
+        ));
+        assert!(engine.index_engram(&engram("more")).is_ok());
+        assert_eq!(hits(&engine, &storage, "more"), 0);
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use engram_core::model::{Manifest, Operations};
+use engram_core::storage::{EngramPart, GitStorage, ListOptions};
+
+use crate::error::QueryError;
+use crate::usage::UsageTotals;
+
+/// Usage by one agent and model.
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentStats {
+    pub agent: String,
+    pub model: Option<String>,
+    pub engrams: usize,
+    /// Engram count per capture mode (e.g. `Import`, `Wrapper`).
+    pub capture_modes: BTreeMap<String, usize>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    /// Sum over engrams with a recorded cost.
+    pub cost_usd: f64,
+    /// Mean `total_tokens` per engram.
+    pub avg_tokens: f64,
+    /// Distinct file paths changed across these engrams.
+    pub files_touched: usize,
+    pub last_active: DateTime<Utc>,
+}
+
+/// Per-(agent, model) statistics over all engrams, most engrams first.
+pub fn agent_stats(storage: &GitStorage) -> Result<Vec<AgentStats>, QueryError> {
+    agent_stats_for(storage, &storage.list(&ListOptions::default())?)
+}
+
+/// Like [`agent_stats`], over `manifests` already listed by the caller, so
+/// that a report that also totals them lists engrams only once. Only each
+/// engram's operations are read, to count the files it touched.
+pub fn agent_stats_for(
+    storage: &GitStorage,
+    manifests: &[Manifest],
+) -> Result<Vec<AgentStats>, QueryError> {
+    let mut groups = HashMap::new();
+    for m in manifests {
+        let key = (m.agent.name.as_str(), m.agent.model.as_deref());
+        let (stats, files) = groups.entry(key).or_insert_with(|| {
+            (
+                AgentStats {
+                    agent: m.agent.name.clone(),
+                    model: m.agent.model.clone(),
+                    engrams: 0,
+                    capture_modes: BTreeMap::new(),
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    total_tokens: 0,
+                    cost_usd: 0.0,
+                    avg_tokens: 0.0,
+                    files_touched: 0,
+                    last_active: m.created_at,
+                },
+                HashSet::new(),
+            )
+        });
+        stats.engrams += 1;
+        *stats
+            .capture_modes
+            .entry(format!("{:?}", m.capture_mode))
+            .or_default() += 1;
+        stats.input_tokens += m.token_usage.input_tokens;
+        stats.output_tokens += m.token_usage.output_tokens;
+        stats.total_tokens += m.token_usage.total_tokens;
+        stats.cost_usd += m.token_usage.cost_usd.unwrap_or(0.0);
+        stats.last_active = stats.last_active.max(m.created_at);
+
+        let operations = storage
+            .read_part(m.id.as_str(), EngramPart::Operations)
+            .map_err(QueryError::from)
+            .and_then(|json| Ok(serde_json::from_slice::<Operations>(&json)?));
+        match operations {
+            Ok(ops) => files.extend(ops.file_changes.into_iter().map(|fc| fc.path)),
+            Err(e) => tracing::warn!("Failed to read operations of engram {}: {e}", m.id),
+        }
+    }
+
+    let mut stats: Vec<AgentStats> = groups
+        .into_values()
+        .map(|(mut stats, files)| {
+            stats.avg_tokens = stats.total_tokens as f64 / stats.engrams as f64;
+            stats.files_touched = files.len();
+            stats
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        b.engrams
+            .cmp(&a.engrams)
+            .then_with(|| a.agent.cmp(&b.agent))
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    Ok(stats)
+}
+
+/// What [`usage_by`] groups engrams by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatsDimension {
+    Agent,
+    Model,
+    CaptureMode,
+    /// An engram counts once for each of its tags.
+    Tag,
+}
+
+impl std::str::FromStr for StatsDimension {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "agent" => Ok(Self::Agent),
+            "model" => Ok(Self::Model),
+            "capture-mode" | "capture_mode" | "mode" => Ok(Self::CaptureMode),
+            "tag" => Ok(Self::Tag),
+            _ => Err(format!(
+                "Unknown dimension '{s}'. Use one of: agent, model, capture-mode, tag"
+            )),
+        }
+    }
+}
+
+/// Usage of the engrams sharing one agent, model, capture mode or tag.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupUsage {
+    /// The agent, model, capture mode or tag; `unknown` for engrams without
+    /// a model and `untagged` for engrams without tags.
+    pub key: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Token and cost totals of `manifests` grouped by `by`, most engrams first.
+pub fn usage_by(manifests: &[Manifest], by: StatsDimension) -> Vec<GroupUsage> {
+    let mut groups: BTreeMap<String, UsageTotals> = BTreeMap::new();
+    for m in manifests {
+        let keys = match by {
+            StatsDimension::Agent => vec![m.agent.name.clone()],
+            StatsDimension::Model => {
+                vec![m.agent.model.clone().unwrap_or_else(|| "unknown".into())]
+            }
+            StatsDimension::CaptureMode => vec![format!("{:?}", m.capture_mode)],
+            StatsDimension::Tag if m.tags.is_empty() => vec!["untagged".into()],
+            StatsDimension::Tag => m.tags.clone(),
+        };
+        for key in keys {
+            groups.entry(key).or_default().add(m);
+        }
+    }
+    let mut groups: Vec<_> = groups
+        .into_iter()
+        .map(|(key, totals)| GroupUsage { key, totals })
+        .collect();
+    // Stable, so equal counts stay in key order
+    groups.sort_by_key(|g| std::cmp::Reverse(g.totals.engrams));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(agent: &str, model: &str, mode: CaptureMode, files: &[&str]) -> EngramData {
+        let usage = TokenUsage {
+            input_tokens: 100,
+            output_tokens: 50,
+            total_tokens: 150,
+            cost_usd: Some(0.5),
+            ..Default::default()
+        };
+        let builder = EngramData::builder()
+            .agent(agent)
+            .model(model)
+            .capture_mode(mode)
+            .token_usage(usage);
+        files.iter().fold(builder, |b, path| b.file(path)).build()
+    }
+
+    #[test]
+    fn test_agent_stats_per_agent_and_model() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        let corpus = [
+            engram(
+                "claude-code",
+                "sonnet",
+                CaptureMode::Import,
+                &["a.rs", "b.rs"],
+            ),
+            engram("claude-code", "sonnet", CaptureMode::Wrapper, &["a.rs"]),
+            engram("claude-code", "sonnet", CaptureMode::Import, &[]),
+            engram("aider", "gpt-4o", CaptureMode::Import, &["c.rs"]),
+            engram("codex", "o3", CaptureMode::Wrapper, &[]),
+        ];
+        let mut last = None;
+        for (i, mut data) in corpus.into_iter().enumerate() {
+            data.manifest.created_at += chrono::Duration::minutes(i as i64);
+            if i == 2 {
+                last = Some(data.manifest.created_at);
+            }
+            storage.create(&data).unwrap();
+        }
+
+        let stats = agent_stats(&storage).unwrap();
+        let keys: Vec<_> = stats
+            .iter()
+            .map(|s| (s.agent.as_str(), s.model.as_deref().unwrap(), s.engrams))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                ("claude-code", "sonnet", 3),
+                ("aider", "gpt-4o", 1),
+                ("codex", "o3", 1),
+            ]
+        );
+
+        let claude = &stats[0];
+        assert_eq!(claude.capture_modes["Import"], 2);
+        assert_eq!(claude.capture_modes["Wrapper"], 1);
+        assert_eq!((claude.total_tokens, claude.cost_usd), (450, 1.5));
+        assert_eq!(claude.avg_tokens, 150.0);
+        assert_eq!(claude.files_touched, 2);
+        assert_eq!(Some(claude.last_active), last);
+        assert_eq!(stats[2].files_touched, 0);
+    }
+
+    #[test]
+    fn test_usage_by_dimension() {
+        let mut corpus: Vec<Manifest> = [
+            engram("claude-code", "sonnet", CaptureMode::Import, &[]),
+            engram("claude-code", "opus", CaptureMode::Wrapper, &[]),
+            engram("aider", "sonnet", CaptureMode::Import, &[]),
+        ]
+        .into_iter()
+        .map(|data| data.manifest)
+        .collect();
+        corpus[0].tags = vec!["auth".into(), "perf".into()];
+        corpus[1].tags = vec!["auth".into()];
+        corpus[2].agent.model = None;
+        corpus[2].token_usage.cost_usd = None;
+
+        let keys = |by| {
+            usage_by(&corpus, by)
+                .into_iter()
+                .map(|g| (g.key, g.totals.engrams))
+                .collect::<Vec<_>>()
+        };
+        let owned = |pairs: &[(&str, usize)]| {
+            pairs
+                .iter()
+                .map(|(k, n)| (k.to_string(), *n))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            keys(StatsDimension::Agent),
+            owned(&[("claude-code", 2), ("aider", 1)])
+        );
+        assert_eq!(
+            keys(StatsDimension::Model),
+            owned(&[("opus", 1), ("sonnet", 1), ("unknown", 1)])
+        );
+        assert_eq!(
+            keys(StatsDimension::CaptureMode),
+            owned(&[("Import", 2), ("Wrapper", 1)])
+        );
+        assert_eq!(
+            keys(StatsDimension::Tag),
+            owned(&[("auth", 2), ("perf", 1), ("untagged", 1)])
+        );
+
+        let agents = usage_by(&corpus, StatsDimension::Agent);
+        assert_eq!(agents[0].totals.total_tokens, 300);
+        assert_eq!(agents[0].totals.cost_usd, 1.0);
+        assert_eq!(agents[1].totals.cost_unknown, 1);
+
+        assert_eq!("capture-mode".parse(), Ok(StatsDimension::CaptureMode));
+        assert!("branch".parse::<StatsDimension>().is_err());
+    }
+}
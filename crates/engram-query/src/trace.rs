@@ -1,27 +1,49 @@
-use engram_core::model::Manifest;
+use std::collections::HashSet;
+
+use engram_core::model::{FileChangeType, Manifest};
 use engram_core::storage::GitStorage;
 
 use crate::error::QueryError;
+use crate::renames::Renames;
 use crate::search::SearchEngine;
 
+/// Most engrams fetched per historical file name.
+const MAX_PER_NAME: usize = 100;
+
 /// An entry in a file's reasoning trace.
 #[derive(Debug, Clone)]
 pub struct TraceEntry {
     pub manifest: Manifest,
     pub change_type: String,
+    /// Name the file had in this engram; differs from the traced path when
+    /// the engram predates a rename.
+    pub path: String,
 }
 
-/// Trace all engrams that touched a file, ordered by time.
+/// Trace all engrams that touched a file, ordered by time. With
+/// `follow_renames`, engrams that touched the file under an earlier name are
+/// included too (see [`rename_chain`]).
 pub fn trace_file(
     storage: &GitStorage,
     search: &SearchEngine,
     file_path: &str,
+    follow_renames: bool,
 ) -> Result<Vec<TraceEntry>, QueryError> {
-    let results = search.search_by_file(storage, file_path, 100)?;
+    let names = if follow_renames {
+        rename_chain(storage, search, file_path)?
+    } else {
+        vec![file_path.to_string()]
+    };
 
-    let mut entries: Vec<TraceEntry> = results
-        .into_iter()
-        .map(|r| {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    // Newest name first, so an engram that did the rename is labeled with
+    // the name it renamed the file to
+    for name in &names {
+        for r in search.search_by_file(storage, name, MAX_PER_NAME, 0)? {
+            if !seen.insert(r.manifest.id.clone()) {
+                continue;
+            }
             // Fetch the full engram to get actual change type for this file
             let change_type = storage
                 .read(r.manifest.id.as_str())
@@ -30,26 +52,166 @@ pub fn trace_file(
                     data.operations
                         .file_changes
                         .iter()
-                        .find(|fc| fc.path == file_path)
+                        .find(|fc| fc.path == *name)
                         .map(|fc| match &fc.change_type {
-                            engram_core::model::FileChangeType::Created => "created".to_string(),
-                            engram_core::model::FileChangeType::Modified => "modified".to_string(),
-                            engram_core::model::FileChangeType::Deleted => "deleted".to_string(),
-                            engram_core::model::FileChangeType::Renamed { from } => {
-                                format!("renamed from {from}")
-                            }
+                            FileChangeType::Created => "created".to_string(),
+                            FileChangeType::Modified => "modified".to_string(),
+                            FileChangeType::Deleted => "deleted".to_string(),
+                            FileChangeType::Renamed { from } => format!("renamed from {from}"),
                         })
                 })
                 .unwrap_or_else(|| "modified".to_string());
-            TraceEntry {
+            entries.push(TraceEntry {
                 manifest: r.manifest,
                 change_type,
-            }
-        })
-        .collect();
+                path: name.clone(),
+            });
+        }
+    }
 
     // Sort by creation time (oldest first for a trace)
     entries.sort_by_key(|e| e.manifest.created_at);
 
     Ok(entries)
 }
+
+/// `file_path` followed by every name it was renamed from, as recorded by
+/// `Renamed` file changes in engrams, most recent name first.
+pub fn rename_chain(
+    storage: &GitStorage,
+    search: &SearchEngine,
+    file_path: &str,
+) -> Result<Vec<String>, QueryError> {
+    let mut renames = Renames::default();
+    let mut searched = HashSet::new();
+    let mut chain = vec![file_path.to_string()];
+    // Each name found leads to the engrams that may have renamed it
+    while let Some(name) = chain.iter().find(|name| !searched.contains(*name)).cloned() {
+        for r in search.search_by_file(storage, &name, MAX_PER_NAME, 0)? {
+            let Ok(data) = storage.read(r.manifest.id.as_str()) else {
+                continue;
+            };
+            // Only renames to this name, so unrelated ones don't leak in
+            renames.record(
+                data.operations
+                    .file_changes
+                    .iter()
+                    .filter(|fc| fc.path == name),
+            );
+        }
+        searched.insert(name);
+        chain = renames.chain(file_path);
+    }
+    Ok(chain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(request: &str, changes: Vec<FileChange>) -> EngramData {
+        let mut data = EngramData::builder()
+            .request(request)
+            .summary(request)
+            .build();
+        data.operations.file_changes = changes;
+        data
+    }
+
+    fn change(path: &str, change_type: FileChangeType) -> FileChange {
+        FileChange {
+            path: path.into(),
+            change_type,
+            lines_added: None,
+            lines_removed: None,
+        }
+    }
+
+    #[test]
+    fn test_trace_follows_renames() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        // Created as auth.rs, renamed to session.rs, then to login.rs
+        let steps = [
+            (
+                "create auth",
+                change("src/auth.rs", FileChangeType::Created),
+            ),
+            (
+                "tweak auth",
+                change("src/auth.rs", FileChangeType::Modified),
+            ),
+            (
+                "rename to session",
+                change(
+                    "src/session.rs",
+                    FileChangeType::Renamed {
+                        from: "src/auth.rs".into(),
+                    },
+                ),
+            ),
+            (
+                "rename to login",
+                change(
+                    "src/login.rs",
+                    FileChangeType::Renamed {
+                        from: "src/session.rs".into(),
+                    },
+                ),
+            ),
+            (
+                "fix login",
+                change("src/login.rs", FileChangeType::Modified),
+            ),
+        ];
+        for (i, (request, change)) in steps.into_iter().enumerate() {
+            let mut data = engram(request, vec![change]);
+            data.manifest.created_at += chrono::Duration::minutes(i as i64);
+            storage.create(&data).unwrap();
+        }
+        let engine = SearchEngine::open(&storage).unwrap();
+
+        let chain = rename_chain(&storage, &engine, "src/login.rs").unwrap();
+        assert_eq!(chain, ["src/login.rs", "src/session.rs", "src/auth.rs"]);
+
+        let trace = trace_file(&storage, &engine, "src/login.rs", true).unwrap();
+        let trace: Vec<_> = trace
+            .iter()
+            .map(|e| {
+                (
+                    e.manifest.summary.as_deref().unwrap(),
+                    e.path.as_str(),
+                    e.change_type.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            trace,
+            [
+                ("create auth", "src/auth.rs", "created"),
+                ("tweak auth", "src/auth.rs", "modified"),
+                (
+                    "rename to session",
+                    "src/session.rs",
+                    "renamed from src/auth.rs"
+                ),
+                (
+                    "rename to login",
+                    "src/login.rs",
+                    "renamed from src/session.rs"
+                ),
+                ("fix login", "src/login.rs", "modified"),
+            ]
+        );
+
+        // Without following, only engrams under the current name
+        let unfollowed = trace_file(&storage, &engine, "src/login.rs", false).unwrap();
+        assert_eq!(unfollowed.len(), 2);
+    }
+}
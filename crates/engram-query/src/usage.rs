@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveTime, TimeZone, Utc};
+use serde::Serialize;
+
+use engram_core::model::Manifest;
+use engram_core::storage::{GitStorage, ListOptions};
+
+use crate::error::QueryError;
+
+/// Width of a [`UsageBucket`]. All buckets are in UTC; weeks start on Monday
+/// (ISO 8601) and months on the 1st.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bucket {
+    Day,
+    Week,
+    Month,
+}
+
+impl Bucket {
+    /// Start of the bucket containing `t`.
+    pub fn start_of(self, t: DateTime<Utc>) -> DateTime<Utc> {
+        let date = t.date_naive();
+        let date = match self {
+            Self::Day => date,
+            Self::Week => date - Days::new(u64::from(date.weekday().num_days_from_monday())),
+            Self::Month => date.with_day(1).expect("day 1 exists"),
+        };
+        Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN))
+    }
+
+    /// Start of the bucket after the one starting at `start`.
+    fn next(self, start: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::Day => start + Days::new(1),
+            Self::Week => start + Days::new(7),
+            Self::Month => start + Months::new(1),
+        }
+    }
+}
+
+impl std::str::FromStr for Bucket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            _ => Err(format!(
+                "Unknown bucket '{s}'. Use one of: day, week, month"
+            )),
+        }
+    }
+}
+
+/// Token and cost totals over a set of engrams.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageTotals {
+    pub engrams: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub total_tokens: u64,
+    /// Sum over engrams with a recorded cost.
+    pub cost_usd: f64,
+    /// Engrams with no recorded cost; their tokens are still counted.
+    pub cost_unknown: usize,
+}
+
+impl UsageTotals {
+    pub(crate) fn add(&mut self, manifest: &Manifest) {
+        let usage = &manifest.token_usage;
+        self.engrams += 1;
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+        self.cache_read_tokens += usage.cache_read_tokens;
+        self.cache_write_tokens += usage.cache_write_tokens;
+        self.total_tokens += usage.total_tokens;
+        match usage.cost_usd {
+            Some(cost) => self.cost_usd += cost,
+            None => self.cost_unknown += 1,
+        }
+    }
+}
+
+/// Usage by one agent within a [`UsageBucket`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentUsage {
+    pub agent: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Usage for the engrams created in one day, week or month.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageBucket {
+    /// Start of the bucket (UTC midnight).
+    pub start: DateTime<Utc>,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+    /// Per-agent breakdown, sorted by agent name.
+    pub agents: Vec<AgentUsage>,
+}
+
+/// Token and cost totals per `bucket` for engrams created between `since`
+/// and `until` (inclusive), oldest bucket first. Buckets with no engrams are
+/// included between the oldest and newest engram's, and there are none at
+/// all when no engram matches.
+pub fn usage_over_time(
+    storage: &GitStorage,
+    bucket: Bucket,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<UsageBucket>, QueryError> {
+    let manifests: Vec<Manifest> = storage
+        .list(&ListOptions::default())?
+        .into_iter()
+        .filter(|m| since.map_or(true, |since| m.created_at >= since))
+        .filter(|m| until.map_or(true, |until| m.created_at <= until))
+        .collect();
+    Ok(usage_buckets(&manifests, bucket, since, until))
+}
+
+/// Like [`usage_over_time`], over `manifests` already listed and filtered
+/// by the caller; the range of buckets is clamped to `since` and `until`.
+pub fn usage_buckets(
+    manifests: &[Manifest],
+    bucket: Bucket,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Vec<UsageBucket> {
+    let first = manifests.iter().map(|m| m.created_at).min();
+    let last = manifests.iter().map(|m| m.created_at).max();
+    let (Some(first), Some(last)) = (first, last) else {
+        return Vec::new();
+    };
+    let first = since.map_or(first, |since| first.max(since));
+    let last = until.map_or(last, |until| last.min(until));
+
+    let mut totals: BTreeMap<DateTime<Utc>, (UsageTotals, BTreeMap<String, UsageTotals>)> =
+        BTreeMap::new();
+    let mut start = bucket.start_of(first);
+    while start <= last {
+        totals.insert(start, Default::default());
+        start = bucket.next(start);
+    }
+    for m in manifests {
+        let (all, agents) = totals.entry(bucket.start_of(m.created_at)).or_default();
+        all.add(m);
+        agents.entry(m.agent.name.clone()).or_default().add(m);
+    }
+
+    totals
+        .into_iter()
+        .map(|(start, (totals, agents))| UsageBucket {
+            start,
+            totals,
+            agents: agents
+                .into_iter()
+                .map(|(agent, totals)| AgentUsage { agent, totals })
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram_core::model::*;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn engram(agent: &str, created_at: &str, tokens: u64, cost: Option<f64>) -> EngramData {
+        EngramData::builder()
+            .agent(agent)
+            .created_at(created_at.parse().unwrap())
+            .token_usage(TokenUsage {
+                input_tokens: tokens,
+                output_tokens: tokens / 2,
+                total_tokens: tokens + tokens / 2,
+                cost_usd: cost,
+                ..Default::default()
+            })
+            .build()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_bucket_starts() {
+        // 2026-01-15 is a Thursday
+        let t = utc("2026-01-15T18:30:00Z");
+        assert_eq!(Bucket::Day.start_of(t), utc("2026-01-15T00:00:00Z"));
+        assert_eq!(Bucket::Week.start_of(t), utc("2026-01-12T00:00:00Z"));
+        assert_eq!(Bucket::Month.start_of(t), utc("2026-01-01T00:00:00Z"));
+        // Sunday belongs to the week that started the Monday before
+        let sunday = utc("2026-01-18T23:59:59Z");
+        assert_eq!(Bucket::Week.start_of(sunday), utc("2026-01-12T00:00:00Z"));
+        assert_eq!("WEEK".parse::<Bucket>(), Ok(Bucket::Week));
+        assert!("year".parse::<Bucket>().is_err());
+    }
+
+    #[test]
+    fn test_weekly_usage_across_three_weeks() {
+        let tmp = TempDir::new().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        let storage = GitStorage::open(tmp.path()).unwrap();
+        storage.init().unwrap();
+
+        for data in [
+            // Week of 2026-01-05: Monday midnight and the last second of Sunday
+            engram("claude-code", "2026-01-05T00:00:00Z", 100, Some(1.0)),
+            engram("aider", "2026-01-11T23:59:59Z", 200, None),
+            // Week of 2026-01-12: nothing
+            // Week of 2026-01-19
+            engram("claude-code", "2026-01-19T09:00:00Z", 400, Some(2.5)),
+            engram("claude-code", "2026-01-21T09:00:00Z", 400, Some(0.5)),
+        ] {
+            storage.create(&data).unwrap();
+        }
+
+        let weeks = usage_over_time(&storage, Bucket::Week, None, None).unwrap();
+        let starts: Vec<_> = weeks.iter().map(|w| w.start).collect();
+        assert_eq!(
+            starts,
+            [
+                utc("2026-01-05T00:00:00Z"),
+                utc("2026-01-12T00:00:00Z"),
+                utc("2026-01-19T00:00:00Z"),
+            ]
+        );
+
+        let first = &weeks[0].totals;
+        assert_eq!((first.engrams, first.input_tokens), (2, 300));
+        assert_eq!((first.cost_usd, first.cost_unknown), (1.0, 1));
+        let agents: Vec<_> = weeks[0].agents.iter().map(|a| a.agent.as_str()).collect();
+        assert_eq!(agents, ["aider", "claude-code"]);
+        assert_eq!(weeks[0].agents[0].totals.cost_unknown, 1);
+
+        assert_eq!(weeks[1].totals, UsageTotals::default());
+        assert_eq!(weeks[2].totals.total_tokens, 1200);
+        assert_eq!(weeks[2].totals.cost_usd, 3.0);
+
+        // Bounds drop engrams outside them, and empty buckets are only
+        // padded between the engrams left
+        let since = utc("2026-01-06T00:00:00Z");
+        let until = utc("2026-01-20T00:00:00Z");
+        let weeks = usage_over_time(&storage, Bucket::Week, Some(since), Some(until)).unwrap();
+        let engrams: Vec<_> = weeks.iter().map(|w| w.totals.engrams).collect();
+        assert_eq!(engrams, [1, 0, 1]);
+
+        let since = utc("1900-01-01T00:00:00Z");
+        let until = utc("2100-01-01T00:00:00Z");
+        let days = usage_over_time(&storage, Bucket::Day, Some(since), Some(until)).unwrap();
+        assert_eq!(days.len(), 17);
+        assert_eq!(days[0].start, utc("2026-01-05T00:00:00Z"));
+
+        let until = utc("2025-12-31T00:00:00Z");
+        let none = usage_over_time(&storage, Bucket::Day, Some(since), Some(until)).unwrap();
+        assert!(none.is_empty());
+    }
+}
@@ -14,7 +14,12 @@
 //! let id = session.commit(Some("abc123"), Some("Add JWT auth")).unwrap();
 //! println!("Engram stored: {id}");
 //! ```
+//!
+//! With the `otel` feature, [`EngramSession::export_otel`] and
+//! [`otel::export_engram`] emit sessions as OpenTelemetry spans.
 
+#[cfg(feature = "otel")]
+pub mod otel;
 mod session;
 
 pub use session::EngramSession;
@@ -23,4 +28,4 @@ pub use session::EngramSession;
 pub use engram_core::model::{
     AgentInfo, CaptureMode, EngramData, EngramId, FileChange, FileChangeType, Manifest, TokenUsage,
 };
-pub use engram_core::storage::GitStorage;
+pub use engram_core::storage::{GitStorage, InitPolicy};
@@ -0,0 +1,297 @@
+//! Export engrams as OpenTelemetry traces (the `otel` feature).
+//!
+//! An engram becomes a root `engram.session` span, one child span per tool
+//! call, and an event on the root for each dead end and decision. Spans go
+//! to whatever tracer the caller passes, so the exporter and its endpoint
+//! are the caller's choice. The root span is started in the current
+//! [`Context`], so a session exported while a request is being handled
+//! shows up in that request's trace.
+
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use opentelemetry::trace::{Span, SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+
+use engram_core::model::{EngramData, ToolCall};
+
+/// Name of the span covering a whole session.
+pub const SESSION_SPAN: &str = "engram.session";
+/// Name of the event recording a dead end.
+pub const DEAD_END_EVENT: &str = "engram.dead_end";
+/// Name of the event recording a decision.
+pub const DECISION_EVENT: &str = "engram.decision";
+
+/// Emit `data` as spans through `tracer`, ending them with the times the
+/// engram recorded rather than now.
+pub fn export_engram<T>(data: &EngramData, tracer: &T)
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    let manifest = &data.manifest;
+    let end = session_end(data);
+
+    let mut attributes = vec![
+        KeyValue::new("engram.id", manifest.id.to_string()),
+        KeyValue::new("engram.agent.name", manifest.agent.name.clone()),
+        KeyValue::new(
+            "engram.tokens.input",
+            manifest.token_usage.input_tokens as i64,
+        ),
+        KeyValue::new(
+            "engram.tokens.output",
+            manifest.token_usage.output_tokens as i64,
+        ),
+        KeyValue::new(
+            "engram.tokens.total",
+            manifest.token_usage.total_tokens as i64,
+        ),
+    ];
+    if let Some(model) = &manifest.agent.model {
+        attributes.push(KeyValue::new("engram.agent.model", model.clone()));
+    }
+    if let Some(version) = &manifest.agent.version {
+        attributes.push(KeyValue::new("engram.agent.version", version.clone()));
+    }
+    if let Some(cost) = manifest.token_usage.cost_usd {
+        attributes.push(KeyValue::new("engram.cost_usd", cost));
+    }
+    if let Some(summary) = &manifest.summary {
+        attributes.push(KeyValue::new("engram.summary", summary.clone()));
+    }
+
+    let root = tracer
+        .span_builder(SESSION_SPAN)
+        .with_kind(SpanKind::Internal)
+        .with_start_time(SystemTime::from(manifest.created_at))
+        .with_attributes(attributes)
+        .start(tracer);
+    let cx = Context::current_with_span(root);
+
+    for call in &data.operations.tool_calls {
+        export_tool_call(call, tracer, &cx);
+    }
+
+    let session = cx.span();
+    for dead_end in &data.intent.dead_ends {
+        session.add_event_with_timestamp(
+            DEAD_END_EVENT,
+            end,
+            vec![
+                KeyValue::new("engram.approach", dead_end.approach.clone()),
+                KeyValue::new("engram.reason", dead_end.reason.clone()),
+            ],
+        );
+    }
+    for decision in &data.intent.decisions {
+        session.add_event_with_timestamp(
+            DECISION_EVENT,
+            end,
+            vec![
+                KeyValue::new("engram.description", decision.description.clone()),
+                KeyValue::new("engram.rationale", decision.rationale.clone()),
+            ],
+        );
+    }
+    session.end_with_timestamp(end);
+}
+
+/// A child span of `cx`'s for one tool call. Without a recorded duration
+/// the span is zero-length.
+fn export_tool_call<T: Tracer>(call: &ToolCall, tracer: &T, cx: &Context) {
+    let start = SystemTime::from(call.timestamp);
+    let mut attributes = vec![
+        KeyValue::new("engram.tool.name", call.tool_name.clone()),
+        KeyValue::new("engram.tool.error", call.is_error),
+    ];
+    if let Some(ms) = call.duration_ms {
+        attributes.push(KeyValue::new("engram.tool.duration_ms", ms as i64));
+    }
+
+    let mut span = tracer
+        .span_builder(call.tool_name.clone())
+        .with_kind(SpanKind::Internal)
+        .with_start_time(start)
+        .with_attributes(attributes)
+        .start_with_context(tracer, cx);
+    if call.is_error {
+        span.set_status(Status::error(
+            call.output_summary.clone().unwrap_or_default(),
+        ));
+    }
+    span.end_with_timestamp(start + Duration::from_millis(call.duration_ms.unwrap_or(0)));
+}
+
+/// When the session finished, or else the last time it recorded anything.
+fn session_end(data: &EngramData) -> SystemTime {
+    let last_activity = data
+        .transcript
+        .entries
+        .iter()
+        .map(|entry| entry.timestamp)
+        .chain(data.operations.tool_calls.iter().map(|call| {
+            call.timestamp + chrono::Duration::milliseconds(call.duration_ms.unwrap_or(0) as i64)
+        }))
+        .max();
+    let end: DateTime<Utc> = data
+        .manifest
+        .finished_at
+        .or(last_activity)
+        .unwrap_or(data.manifest.created_at);
+    SystemTime::from(end.max(data.manifest.created_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::{SpanId, Status, TracerProvider as _};
+    use opentelemetry::Value;
+    use opentelemetry_sdk::export::trace::SpanData;
+    use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+    use opentelemetry_sdk::trace::TracerProvider;
+
+    use super::*;
+    use crate::EngramSession;
+
+    fn export(data: &EngramData) -> Vec<SpanData> {
+        let exporter = InMemorySpanExporter::default();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        export_engram(data, &provider.tracer("engram-test"));
+        exporter.get_finished_spans().unwrap()
+    }
+
+    fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| &kv.value)
+    }
+
+    fn session() -> EngramSession {
+        let mut session = EngramSession::begin("test-agent", Some("gpt-4"));
+        session
+            .log_message("user", "Add auth to the API")
+            .log_tool_call("read_file", r#"{"path":"src/api.rs"}"#, None)
+            .log_tool_call("write_file", r#"{"path":"src/auth.rs"}"#, Some("Created"))
+            .log_rejection("Session auth", "Too stateful")
+            .log_decision("Use JWT", "Stateless")
+            .add_tokens(1500, 800, Some(0.02));
+        session
+    }
+
+    #[test]
+    fn test_session_span_hierarchy() {
+        let data = session().build(None, Some("Add JWT auth"));
+        let spans = export(&data);
+        assert_eq!(spans.len(), 3);
+
+        let root = spans.iter().find(|s| s.name == SESSION_SPAN).unwrap();
+        assert_eq!(root.parent_span_id, SpanId::INVALID);
+        let tools: Vec<_> = spans.iter().filter(|s| s.name != SESSION_SPAN).collect();
+        assert_eq!(
+            tools.iter().map(|s| s.name.as_ref()).collect::<Vec<_>>(),
+            ["read_file", "write_file"]
+        );
+        for tool in &tools {
+            assert_eq!(tool.parent_span_id, root.span_context.span_id());
+            assert_eq!(tool.span_context.trace_id(), root.span_context.trace_id());
+            assert_eq!(tool.status, Status::Unset);
+        }
+
+        assert_eq!(
+            attribute(root, "engram.id"),
+            Some(&Value::from(data.manifest.id.to_string()))
+        );
+        assert_eq!(
+            attribute(root, "engram.agent.name"),
+            Some(&Value::from("test-agent"))
+        );
+        assert_eq!(
+            attribute(root, "engram.agent.model"),
+            Some(&Value::from("gpt-4"))
+        );
+        assert_eq!(
+            attribute(root, "engram.tokens.input"),
+            Some(&Value::I64(1500))
+        );
+        assert_eq!(
+            attribute(root, "engram.tokens.total"),
+            Some(&Value::I64(2300))
+        );
+        assert_eq!(attribute(root, "engram.cost_usd"), Some(&Value::F64(0.02)));
+        assert_eq!(
+            attribute(root, "engram.summary"),
+            Some(&Value::from("Add JWT auth"))
+        );
+        assert_eq!(attribute(root, "engram.agent.version"), None);
+        assert_eq!(root.start_time, SystemTime::from(data.manifest.created_at));
+        assert_eq!(
+            root.end_time,
+            SystemTime::from(data.manifest.finished_at.unwrap())
+        );
+
+        let events: Vec<_> = root.events.iter().map(|e| e.name.as_ref()).collect();
+        assert_eq!(events, [DEAD_END_EVENT, DECISION_EVENT]);
+        let dead_end = &root.events.iter().next().unwrap().attributes;
+        assert!(dead_end.contains(&KeyValue::new("engram.approach", "Session auth")));
+        assert!(dead_end.contains(&KeyValue::new("engram.reason", "Too stateful")));
+    }
+
+    #[test]
+    fn test_tool_call_durations_and_errors() {
+        let mut data = session().build(None, None);
+        let started = data.operations.tool_calls[0].timestamp;
+        data.operations.tool_calls[0].duration_ms = Some(1500);
+        data.operations.tool_calls[1].is_error = true;
+        data.operations.tool_calls[1].output_summary = Some("Permission denied".into());
+
+        let spans = export(&data);
+        let read = spans.iter().find(|s| s.name == "read_file").unwrap();
+        assert_eq!(read.start_time, SystemTime::from(started));
+        assert_eq!(
+            read.end_time,
+            SystemTime::from(started) + Duration::from_millis(1500)
+        );
+        assert_eq!(
+            attribute(read, "engram.tool.duration_ms"),
+            Some(&Value::I64(1500))
+        );
+        assert_eq!(
+            attribute(read, "engram.tool.error"),
+            Some(&Value::Bool(false))
+        );
+
+        let write = spans.iter().find(|s| s.name == "write_file").unwrap();
+        assert_eq!(write.start_time, write.end_time);
+        assert_eq!(attribute(write, "engram.tool.duration_ms"), None);
+        assert_eq!(
+            attribute(write, "engram.tool.error"),
+            Some(&Value::Bool(true))
+        );
+        assert_eq!(write.status, Status::error("Permission denied"));
+    }
+
+    #[test]
+    fn test_export_otel_uses_the_committed_id() {
+        let dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(dir.path()).unwrap();
+        let storage = crate::GitStorage::open(dir.path()).unwrap();
+
+        let session = session();
+        let exporter = InMemorySpanExporter::default();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        session.export_otel(&provider.tracer("engram-test"));
+        let id = session.commit_to(&storage, None, None).unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let root = spans.iter().find(|s| s.name == SESSION_SPAN).unwrap();
+        assert_eq!(
+            attribute(root, "engram.id"),
+            Some(&Value::from(id.to_string()))
+        );
+    }
+}
@@ -1,13 +1,17 @@
 use chrono::Utc;
 
 use engram_core::model::*;
-use engram_core::storage::GitStorage;
+use engram_core::storage::{GitStorage, InitPolicy};
 
 /// A fluent session builder for creating engrams programmatically.
 ///
 /// Use this from agent code or wrappers to capture reasoning, tool calls,
 /// file changes, dead ends, and token economics, then store as a Git engram.
+///
+/// A session's engram ID is chosen when it begins. Clones get IDs of their
+/// own, so committing several copies of a template session stores them all.
 pub struct EngramSession {
+    id: EngramId,
     agent: AgentInfo,
     transcript: Vec<TranscriptEntry>,
     tool_calls: Vec<ToolCall>,
@@ -21,12 +25,36 @@ pub struct EngramSession {
     tags: Vec<String>,
     parent: Option<EngramId>,
     started_at: chrono::DateTime<Utc>,
+    init_policy: InitPolicy,
+}
+
+impl Clone for EngramSession {
+    fn clone(&self) -> Self {
+        Self {
+            id: EngramId::new(),
+            agent: self.agent.clone(),
+            transcript: self.transcript.clone(),
+            tool_calls: self.tool_calls.clone(),
+            file_changes: self.file_changes.clone(),
+            shell_commands: self.shell_commands.clone(),
+            dead_ends: self.dead_ends.clone(),
+            decisions: self.decisions.clone(),
+            token_usage: self.token_usage.clone(),
+            original_request: self.original_request.clone(),
+            summary: self.summary.clone(),
+            tags: self.tags.clone(),
+            parent: self.parent.clone(),
+            started_at: self.started_at,
+            init_policy: self.init_policy,
+        }
+    }
 }
 
 impl EngramSession {
     /// Begin a new session for a given agent and optional model name.
     pub fn begin(agent_name: &str, model: Option<&str>) -> Self {
         Self {
+            id: EngramId::new(),
             agent: AgentInfo {
                 name: agent_name.to_string(),
                 model: model.map(String::from),
@@ -44,9 +72,15 @@ impl EngramSession {
             tags: Vec::new(),
             parent: None,
             started_at: Utc::now(),
+            init_policy: InitPolicy::AutoInit,
         }
     }
 
+    /// The ID the engram will be stored under.
+    pub fn id(&self) -> &EngramId {
+        &self.id
+    }
+
     /// Set the agent version.
     pub fn agent_version(&mut self, version: &str) -> &mut Self {
         self.agent.version = Some(version.to_string());
@@ -59,12 +93,25 @@ impl EngramSession {
         self
     }
 
+    /// Set what committing does in a repository `engram init` never ran in
+    /// (default: [`InitPolicy::AutoInit`], so the engram syncs with remotes).
+    pub fn init_policy(&mut self, policy: InitPolicy) -> &mut Self {
+        self.init_policy = policy;
+        self
+    }
+
     /// Set a summary for this session.
     pub fn set_summary(&mut self, summary: &str) -> &mut Self {
         self.summary = Some(summary.to_string());
         self
     }
 
+    /// Set the original request, instead of taking the first user message.
+    pub fn set_request(&mut self, request: &str) -> &mut Self {
+        self.original_request = Some(request.to_string());
+        self
+    }
+
     /// Add a tag.
     pub fn tag(&mut self, tag: &str) -> &mut Self {
         self.tags.push(tag.to_string());
@@ -199,20 +246,35 @@ impl EngramSession {
         self.commit_to(&storage, git_sha, summary)
     }
 
-    /// Finalize and store in a specific GitStorage instance.
+    /// Finalize and store in a specific GitStorage instance, first checking
+    /// it is initialized as the session's [`InitPolicy`] says.
     pub fn commit_to(
         self,
         storage: &GitStorage,
         git_sha: Option<&str>,
         summary: Option<&str>,
     ) -> Result<EngramId, engram_core::error::CoreError> {
+        storage.ensure_initialized(self.init_policy)?;
         let data = self.build(git_sha, summary);
         storage.create(&data)
     }
 
+    /// Emit the session so far as OpenTelemetry spans through `tracer`: a
+    /// root span carrying the agent, tokens, cost and engram ID, a child span
+    /// per tool call, and events for dead ends and decisions. See [`crate::otel`].
+    #[cfg(feature = "otel")]
+    pub fn export_otel<T>(&self, tracer: &T)
+    where
+        T: opentelemetry::trace::Tracer,
+        T::Span: Send + Sync + 'static,
+    {
+        let mut snapshot = self.clone();
+        snapshot.id = self.id.clone();
+        crate::otel::export_engram(&snapshot.build(None, None), tracer);
+    }
+
     /// Build the EngramData without storing it.
     pub fn build(self, git_sha: Option<&str>, summary: Option<&str>) -> EngramData {
-        let id = EngramId::new();
         let finished_at = Utc::now();
 
         let final_summary = summary
@@ -223,7 +285,7 @@ impl EngramSession {
         let git_commits = git_sha.map(|s| vec![s.to_string()]).unwrap_or_default();
 
         let manifest = Manifest {
-            id,
+            id: self.id,
             version: 1,
             created_at: self.started_at,
             finished_at: Some(finished_at),
@@ -234,6 +296,7 @@ impl EngramSession {
             tags: self.tags,
             capture_mode: CaptureMode::Sdk,
             source_hash: None,
+            source_path: None,
         };
 
         let intent = Intent {
@@ -268,6 +331,7 @@ impl EngramSession {
             transcript,
             operations,
             lineage,
+            attachments: Vec::new(),
         }
     }
 }
@@ -361,6 +425,57 @@ mod tests {
         assert_eq!(data.transcript.entries.len(), 2);
     }
 
+    #[test]
+    fn test_commit_init_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(dir.path()).unwrap();
+        let storage = GitStorage::open(dir.path()).unwrap();
+        let session = |policy| {
+            let mut session = EngramSession::begin("test-agent", None);
+            session.log_message("user", "Fix the login bug");
+            session.init_policy(policy);
+            session
+        };
+
+        let err = session(InitPolicy::Error)
+            .commit_to(&storage, None, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("engram init"), "{err}");
+        assert!(storage.list(&Default::default()).unwrap().is_empty());
+
+        // Stored, but the repository is left as it was
+        session(InitPolicy::WarnAndContinue)
+            .commit_to(&storage, None, None)
+            .unwrap();
+        assert!(!storage.is_initialized());
+
+        // The default initializes the repository first
+        EngramSession::begin("test-agent", None)
+            .commit_to(&storage, None, None)
+            .unwrap();
+        assert!(storage.is_initialized());
+        assert_eq!(storage.list(&Default::default()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_clones_store_separate_engrams() {
+        let dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(dir.path()).unwrap();
+        let storage = GitStorage::open(dir.path()).unwrap();
+
+        let mut template = EngramSession::begin("test-agent", None);
+        template.log_message("user", "Triage the failing tests");
+        let first = template.clone();
+        assert_ne!(first.id(), template.id());
+
+        let first_id = first.commit_to(&storage, None, None).unwrap();
+        let second_id = template.clone().commit_to(&storage, None, None).unwrap();
+        let third_id = template.commit_to(&storage, None, None).unwrap();
+        assert_ne!(first_id, second_id);
+        assert_ne!(second_id, third_id);
+        assert_eq!(storage.list(&Default::default()).unwrap().len(), 3);
+    }
+
     #[test]
     fn test_accumulate_tokens() {
         let mut session = EngramSession::begin("test", None);